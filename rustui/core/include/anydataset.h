@@ -0,0 +1,54 @@
+/*
+ * C ABI of anydataset-core: the record pipeline of the AnyDataNext client.
+ *
+ * Arguments and results are NUL-terminated UTF-8 JSON. A function returns a string
+ * to be released with anydataset_string_free(), or NULL on failure; the reason is then
+ * returned by anydataset_last_error() on the same thread.
+ *
+ * Build: cargo build --release -p anydataset-core
+ * Links: target/release/libanydataset_core.{so,dylib,a} or anydataset_core.dll
+ */
+#ifndef ANYDATASET_H
+#define ANYDATASET_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Processes one file with a local processor.
+ * processing_type: "standard", "article", "translate" or "batch".
+ * config_json: processor config, e.g.
+ *   {"model": "gpt-4o", "provider": "openai", "language": "en", "keywords": [],
+ *    "add_reasoning": false, "output_format": "json"}
+ * Returns the processing result: {"records": [...], "source_file": ..., "stats": {...}}.
+ */
+char *anydataset_process_file(const char *path, const char *processing_type, const char *config_json);
+
+/*
+ * Converts a dataset (an array of processing results) to "json", "alpaca" or "sharegpt".
+ * system_prompt may be NULL.
+ */
+char *anydataset_convert_format(const char *results_json, const char *format, const char *system_prompt);
+
+/*
+ * Runs quality checks over a dataset. checks_json is the [checks] section as JSON, e.g.
+ *   {"numbers": true, "terminology": "/path/to/terms.txt"}
+ * Returns the dataset with the issues in the "check_issues" metadata of flagged records.
+ */
+char *anydataset_validate_dataset(const char *results_json, const char *checks_json);
+
+/*
+ * Why the last call on this thread returned NULL. Valid until the next call on the
+ * thread; do not free.
+ */
+const char *anydataset_last_error(void);
+
+/* Releases a string returned by this library; NULL is ignored. */
+void anydataset_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ANYDATASET_H */
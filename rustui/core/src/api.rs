@@ -0,0 +1,609 @@
+use anyhow::{Result, Context};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+use crate::credentials;
+use crate::network::{HttpClient, NetworkMonitor, Transfer};
+use crate::quota::QuotaGuard;
+use crate::ranged::{self, DownloadSettings};
+use crate::resume::PartFile;
+
+/// Bytes of an uploaded file read from disk at a time
+pub const UPLOAD_CHUNK_SIZE: usize = 256 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessingConfig {
+    pub provider: String,
+    pub model: String,
+    pub system_prompt: Option<String>,
+    pub keywords: Option<Vec<String>>,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    /// Nucleus sampling cutoff; left out of the request when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    pub language: Option<String>,
+    pub processing_type: String,
+    pub add_reasoning: Option<bool>,
+    pub output_format: Option<String>,
+    /// Where the provider's API listens, for self-hosted providers such as Ollama
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_base_url: Option<String>,
+    /// API the provider speaks when the backend doesn't know it by name, e.g. "openai-compatible"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_api: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub job_id: String,
+    pub status: String,
+    pub current: Option<u64>,
+    pub total: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Job owner, reported only by multi-user backends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl JobStatus {
+    /// Returns true if the job belongs to the given user
+    pub fn is_owned_by(&self, user: &str) -> bool {
+        self.owner.as_deref() == Some(user)
+    }
+}
+
+/// Backend build information from `/api/version`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendVersion {
+    pub version: String,
+    #[serde(default)]
+    pub build: Option<String>,
+}
+
+/// Jobs requested per page of `/api/jobs`
+pub const JOBS_PER_PAGE: u32 = 50;
+
+/// Page of the backend's job list
+#[derive(Debug, Clone)]
+pub struct JobPage {
+    pub jobs: Vec<JobStatus>,
+    /// Starts at 1
+    pub page: u32,
+    pub total_pages: u32,
+    /// Jobs matching the filter on all pages, when the backend reports it
+    pub total: Option<u64>,
+}
+
+/// Dataset stored on the backend, as listed by `/api/datasets`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetInfo {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Client or user that produced the dataset
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub processing_type: Option<String>,
+    #[serde(default)]
+    pub records: Option<u64>,
+    /// Size in bytes
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl DatasetInfo {
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+/// Dataset details from `/api/datasets/{id}` with its first records
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dataset {
+    #[serde(flatten)]
+    pub info: DatasetInfo,
+    #[serde(default)]
+    pub preview: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiResult<T> {
+    pub success: bool,
+    pub data: Option<T>,
+    pub error: Option<String>,
+}
+
+pub struct ApiClient {
+    client: HttpClient,
+    base_url: String,
+    quota_guard: Option<QuotaGuard>,
+    download: DownloadSettings,
+}
+
+impl ApiClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: HttpClient::default(),
+            base_url: base_url.to_string(),
+            quota_guard: None,
+            download: DownloadSettings::default(),
+        }
+    }
+
+    /// Reports requests to `monitor`, which also limits how many run at once
+    pub fn with_monitor(mut self, monitor: NetworkMonitor) -> Self {
+        self.client = HttpClient::new(monitor).with_headers(self.client.headers().to_vec());
+        self
+    }
+
+    /// Adds `headers` (`Config::request_headers`) to every request to the backend
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.client = self.client.with_headers(headers);
+        self
+    }
+
+    /// Refuses processing requests for providers whose quota is used up.
+    /// The error is a [`crate::quota::QuotaExceeded`].
+    pub fn with_quota_guard(mut self, guard: QuotaGuard) -> Self {
+        self.quota_guard = Some(guard);
+        self
+    }
+
+    /// Splits downloads into parallel ranged requests as configured
+    pub fn with_download_settings(mut self, settings: DownloadSettings) -> Self {
+        self.download = settings;
+        self
+    }
+
+    pub async fn get_version(&self) -> Result<BackendVersion> {
+        let url = format!("{}/api/version", self.base_url);
+        
+        let response = self.client.get(&url)
+            .send()
+            .await
+            .context("Failed to send request")?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("API error ({}): {}", status, error_text);
+        }
+        
+        let version: BackendVersion = response.json().await
+            .context("Failed to parse backend version")?;
+            
+        Ok(version)
+    }
+
+    pub async fn get_job_status(&self, job_id: &str) -> Result<JobStatus> {
+        let url = format!("{}/api/jobs/{}", self.base_url, job_id);
+        
+        let response = self.client.get(&url)
+            .send()
+            .await
+            .context("Failed to send request")?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("API error ({}): {}", status, error_text);
+        }
+        
+        let job_status: JobStatus = response.json().await
+            .context("Failed to parse job status")?;
+            
+        Ok(job_status)
+    }
+
+    /// Uploads the file as it's read from disk, so its size isn't limited by memory
+    pub async fn upload_file(&self, file_path: &Path) -> Result<String> {
+        self.upload_file_with_progress(file_path, |_, _| {}).await
+    }
+
+    /// Like `upload_file`, calling `progress(sent, total)` in bytes as the body goes out
+    pub async fn upload_file_with_progress<F>(&self, file_path: &Path, progress: F) -> Result<String>
+    where
+        F: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        let url = format!("{}/api/upload", self.base_url);
+        
+        let file_name = file_path.file_name()
+            .and_then(|n| n.to_str())
+            .context("Invalid file name")?;
+            
+        let file = tokio::fs::File::open(file_path).await
+            .context("Failed to read file")?;
+        let size = file.metadata().await
+            .context("Failed to read file")?
+            .len();
+            
+        let form = reqwest::multipart::Form::new()
+            .part("file", reqwest::multipart::Part::stream_with_length(file_body(file, size, progress), size)
+                .file_name(file_name.to_string()));
+                
+        let response = self.client.post(&url)
+            .multipart(form)
+            .transfer()
+            .send()
+            .await
+            .context("Failed to upload file")?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("Upload error ({}): {}", status, error_text);
+        }
+        
+        #[derive(Deserialize)]
+        struct UploadResponse {
+            file_id: String,
+        }
+        
+        let upload_result: UploadResponse = response.json().await
+            .context("Failed to parse upload response")?;
+            
+        Ok(upload_result.file_id)
+    }
+
+    /// Submits a job; the provider's stored key goes along in the header that provider expects
+    pub async fn process_file(&self, file_id: &str, config: ProcessingConfig) -> Result<String> {
+        self.process_file_with_key(file_id, config, None).await
+    }
+
+    /// Submits a job with `api_key` for the provider instead of the stored one, e.g. the key
+    /// of a custom provider from the config
+    pub async fn process_file_with_key(&self, file_id: &str, config: ProcessingConfig, api_key: Option<String>) -> Result<String> {
+        if let Some(guard) = &self.quota_guard {
+            guard.check(&config.provider)?;
+        }
+        let url = format!("{}/api/process", self.base_url);
+        
+        #[derive(Serialize)]
+        struct ProcessRequest {
+            file_id: String,
+            #[serde(flatten)]
+            config: ProcessingConfig,
+        }
+        
+        let key = api_key.or_else(|| credentials::get(&config.provider).map(|(key, _)| key));
+        let auth = key.map(|key| credentials::auth_header(&config.provider, &key));
+        let request = ProcessRequest {
+            file_id: file_id.to_string(),
+            config,
+        };
+        
+        let mut builder = self.client.post(&url).json(&request);
+        if let Some((name, value)) = &auth {
+            builder = builder.header(name, value);
+        }
+        let response = builder
+            .send()
+            .await
+            .context("Failed to send process request")?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("Process error ({}): {}", status, error_text);
+        }
+        
+        #[derive(Deserialize)]
+        struct ProcessResponse {
+            job_id: String,
+        }
+        
+        let process_result: ProcessResponse = response.json().await
+            .context("Failed to parse process response")?;
+            
+        Ok(process_result.job_id)
+    }
+
+    pub async fn get_available_models(&self) -> Result<serde_json::Value> {
+        let url = format!("{}/api/models", self.base_url);
+        
+        let response = self.client.get(&url)
+            .send()
+            .await
+            .context("Failed to get available models")?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("API error ({}): {}", status, error_text);
+        }
+        
+        let models = response.json().await
+            .context("Failed to parse models response")?;
+            
+        Ok(models)
+    }
+
+    pub async fn download_results(&self, job_id: &str, output_path: &Path) -> Result<()> {
+        self.download_results_checked(job_id, output_path, |_| Ok(())).await
+    }
+    
+    /// Like `download_results`, but `check` gets the announced size (if any)
+    /// before anything is written and can abort the download
+    pub async fn download_results_checked<F>(&self, job_id: &str, output_path: &Path, check: F) -> Result<()>
+    where
+        F: FnOnce(Option<u64>) -> Result<()>,
+    {
+        let url = format!("{}/api/results/{}", self.base_url, job_id);
+        self.download(&url, output_path, check).await
+    }
+
+    /// Asks the backend to stop a queued or running job
+    pub async fn cancel_job(&self, job_id: &str) -> Result<()> {
+        let url = format!("{}/api/jobs/{}/cancel", self.base_url, job_id);
+        
+        let response = self.client.post(&url)
+            .send()
+            .await
+            .context("Failed to send cancel request")?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("Cancel error ({}): {}", status, error_text);
+        }
+        
+        Ok(())
+    }
+
+    /// One page of the jobs the backend knows about, including ones submitted by other
+    /// clients, newest first. `page` starts at 1; `status` keeps only jobs in that state.
+    pub async fn list_jobs(&self, page: u32, status: Option<&str>) -> Result<JobPage> {
+        let url = format!("{}/api/jobs", self.base_url);
+        let mut query = vec![
+            ("page", page.to_string()),
+            ("per_page", JOBS_PER_PAGE.to_string()),
+            ("sort", "created_at".to_string()),
+            ("order", "desc".to_string()),
+        ];
+        if let Some(status) = status {
+            query.push(("status", status.to_string()));
+        }
+        
+        let response = self.client.get(&url)
+            .query(&query)
+            .send()
+            .await
+            .context("Failed to list jobs")?;
+            
+        let status_code = response.status();
+        if !status_code.is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("API error ({}): {}", status_code, error_text);
+        }
+        
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum JobList {
+            Page {
+                jobs: Vec<JobStatus>,
+                #[serde(default)]
+                page: Option<u32>,
+                #[serde(default)]
+                per_page: Option<u32>,
+                #[serde(default)]
+                total: Option<u64>,
+            },
+            /// Backends without pagination send every job at once
+            All(Vec<JobStatus>),
+        }
+        
+        let list: JobList = response.json().await
+            .context("Failed to parse job list")?;
+            
+        Ok(match list {
+            JobList::Page { jobs, page: returned, per_page, total } => {
+                let per_page = u64::from(per_page.unwrap_or(JOBS_PER_PAGE).max(1));
+                JobPage {
+                    jobs,
+                    page: returned.unwrap_or(page),
+                    total_pages: total.map(|t| t.div_ceil(per_page).max(1) as u32).unwrap_or(1),
+                    total,
+                }
+            },
+            JobList::All(mut jobs) => {
+                if let Some(status) = status {
+                    jobs.retain(|job| job.status == status);
+                }
+                JobPage { total: Some(jobs.len() as u64), jobs, page: 1, total_pages: 1 }
+            },
+        })
+    }
+
+    /// Datasets stored on the backend, including ones produced by other clients
+    pub async fn list_datasets(&self) -> Result<Vec<DatasetInfo>> {
+        let url = format!("{}/api/datasets", self.base_url);
+        
+        let response = self.client.get(&url)
+            .send()
+            .await
+            .context("Failed to list datasets")?;
+            
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            anyhow::bail!("This backend doesn't expose stored datasets");
+        }
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("API error ({}): {}", status, error_text);
+        }
+        
+        let datasets = response.json().await
+            .context("Failed to parse dataset list")?;
+            
+        Ok(datasets)
+    }
+
+    pub async fn get_dataset(&self, dataset_id: &str) -> Result<Dataset> {
+        let url = format!("{}/api/datasets/{}", self.base_url, dataset_id);
+        
+        let response = self.client.get(&url)
+            .send()
+            .await
+            .context("Failed to send request")?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            anyhow::bail!("API error ({}): {}", status, error_text);
+        }
+        
+        let dataset = response.json().await
+            .context("Failed to parse dataset")?;
+            
+        Ok(dataset)
+    }
+
+    /// Downloads a stored dataset; `check` works as in `download_results_checked`
+    pub async fn download_dataset_checked<F>(&self, dataset_id: &str, output_path: &Path, check: F) -> Result<()>
+    where
+        F: FnOnce(Option<u64>) -> Result<()>,
+    {
+        let url = format!("{}/api/datasets/{}/download", self.base_url, dataset_id);
+        self.download(&url, output_path, check).await
+    }
+
+    /// Downloads into a part file next to `output_path` that replaces it once complete. A part
+    /// left by an interrupted download of the same file is resumed.
+    async fn download<F>(&self, url: &str, output_path: &Path, check: F) -> Result<()>
+    where
+        F: FnOnce(Option<u64>) -> Result<()>,
+    {
+        let transfer = self.client.monitor().start_transfer();
+        let mut part = PartFile::open(output_path, url)?;
+        if let Err(err) = self.download_part(url, &mut part, check, &transfer).await {
+            part.suspend();
+            return Err(err);
+        }
+        let output_path = output_path.to_path_buf();
+        tokio::task::spawn_blocking(move || part.finish(&output_path))
+            .await
+            .context("Write task failed")?
+            .context("Failed to write file")
+    }
+
+    /// Asks for the first part of the file, or for the rest of a part being resumed. Backends
+    /// that support ranges answer with `206 Partial Content` and the rest comes in parallel
+    /// parts; others send it whole. `If-Range` makes a backend whose file changed send it whole.
+    async fn download_part<F>(&self, url: &str, part: &mut PartFile, check: F, transfer: &Transfer) -> Result<()>
+    where
+        F: FnOnce(Option<u64>) -> Result<()>,
+    {
+        let mut check = Some(check);
+        loop {
+            let resume_from = part.state.prefix();
+            let mut request = self.client.get(url).transfer();
+            if self.download.ranged() {
+                request = request.header("Range", &ranged::first_range(&self.download));
+            } else if resume_from > 0 {
+                request = request.header("Range", &format!("bytes={}-", resume_from));
+            }
+            if let Some(validator) = part.if_range() {
+                request = request.header("If-Range", validator);
+            }
+            let response = request
+                .send()
+                .await
+                .context("Failed to download")?;
+
+            let status = response.status();
+            if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && part.if_range().is_some() {
+                // The saved part doesn't fit the file on the backend any more
+                part.restart(None, None)?;
+                continue;
+            }
+            if !status.is_success() {
+                let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                anyhow::bail!("Download error ({}): {}", status, error_text);
+            }
+
+            let validator = ranged::validator(&response);
+            if status == reqwest::StatusCode::PARTIAL_CONTENT {
+                let (first, _, total) = ranged::content_range(&response)?;
+                if part.start(Some(total), validator)? && first != 0 {
+                    // The file changed since the part was saved and the backend ignored If-Range
+                    continue;
+                }
+                check.take().map_or(Ok(()), |check| check(Some(total)))?;
+                transfer.set_total(Some(total));
+                if first == 0 && self.download.ranged() {
+                    return ranged::download_rest(&self.client, url, response, &self.download, part, transfer).await;
+                }
+                transfer.add(first);
+                return ranged::download_stream(response, part, first, transfer).await;
+            }
+
+            check.take().map_or(Ok(()), |check| check(response.content_length()))?;
+            transfer.set_total(response.content_length());
+            part.restart(response.content_length(), validator)?;
+            return ranged::download_stream(response, part, 0, transfer).await;
+        }
+    }
+}
+
+/// Body reading the file `UPLOAD_CHUNK_SIZE` bytes at a time, as the request is sent
+fn file_body<F>(file: tokio::fs::File, size: u64, progress: F) -> reqwest::Body
+where
+    F: Fn(u64, u64) + Send + Sync + 'static,
+{
+    let chunks = futures::stream::try_unfold((file, 0u64, progress), move |(mut file, sent, progress)| async move {
+        let mut chunk = vec![0; UPLOAD_CHUNK_SIZE];
+        let read = file.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        chunk.truncate(read);
+        let sent = sent + read as u64;
+        progress(sent, size);
+        Ok::<_, std::io::Error>(Some((chunk, (file, sent, progress))))
+    });
+    reqwest::Body::wrap_stream(chunks)
+}
+
+/// Bytes sent of an upload in progress, for its gauge on the Upload screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UploadProgress {
+    pub sent: u64,
+    pub total: u64,
+    pub started: std::time::Instant,
+}
+
+impl UploadProgress {
+    pub fn new(total: u64) -> Self {
+        Self { sent: 0, total, started: std::time::Instant::now() }
+    }
+
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.sent as f64 / self.total as f64).min(1.0)
+    }
+
+    /// Average bytes per second since the upload started
+    pub fn rate(&self, now: std::time::Instant) -> f64 {
+        let secs = now.saturating_duration_since(self.started).as_secs_f64();
+        if secs <= 0.0 {
+            return 0.0;
+        }
+        self.sent as f64 / secs
+    }
+
+    /// Time left at the average rate; `None` until something was sent
+    pub fn eta(&self, now: std::time::Instant) -> Option<std::time::Duration> {
+        let rate = self.rate(now);
+        (rate > 0.0).then(|| std::time::Duration::from_secs_f64(self.total.saturating_sub(self.sent) as f64 / rate))
+    }
+}
@@ -0,0 +1,370 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use crate::history::HistoryEntry;
+use crate::manifest::DatasetManifest;
+
+/// Extensions of files treated as datasets among the extracted ones
+const DATASET_EXTENSIONS: [&str; 4] = ["json", "jsonl", "csv", "parquet"];
+
+/// `[extract_limits]` in config.toml: how much unpacking one archive may write, so a
+/// small download can't expand until the disk is full
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExtractLimits {
+    pub max_total_mb: u64,
+    pub max_files: usize,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        Self { max_total_mb: 10 * 1024, max_files: 10_000 }
+    }
+}
+
+/// What an extraction has written so far, checked against the limits before every file
+/// and while it's being written
+struct Budget<'a> {
+    limits: &'a ExtractLimits,
+    bytes: u64,
+    files: usize,
+}
+
+impl Budget<'_> {
+    fn max_bytes(&self) -> u64 {
+        self.limits.max_total_mb.saturating_mul(1024 * 1024)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    TarGz,
+    /// A single gzip-compressed file, e.g. `results.json.gz`
+    Gzip,
+}
+
+/// Archive format of a downloaded file, by its first bytes; results are often saved
+/// under a `.json` name whatever the backend sent. Gzip data is a tar.gz only when it
+/// starts with a valid tar header.
+pub fn detect(path: &Path) -> Result<Option<ArchiveKind>> {
+    let mut magic = [0u8; 4];
+    let read = File::open(path)
+        .and_then(|mut file| file.read(&mut magic))
+        .with_context(|| format!("Cannot read {}", path.display()))?;
+    Ok(match &magic[..read] {
+        [b'P', b'K', 3, 4] => Some(ArchiveKind::Zip),
+        [0x1f, 0x8b, ..] if starts_with_tar_header(path) => Some(ArchiveKind::TarGz),
+        [0x1f, 0x8b, ..] => Some(ArchiveKind::Gzip),
+        _ => None,
+    })
+}
+
+/// Whether the decompressed data starts with a 512-byte tar header whose checksum matches
+fn starts_with_tar_header(path: &Path) -> bool {
+    let mut block = [0u8; 512];
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    if flate2::read::GzDecoder::new(file).read_exact(&mut block).is_err() {
+        return false;
+    }
+    let Ok(stored) = tar::Header::from_byte_slice(&block).cksum() else {
+        return false;
+    };
+    // The checksum field itself counts as spaces
+    let computed: u32 = block.iter().enumerate()
+        .map(|(i, byte)| if (148..156).contains(&i) { u32::from(b' ') } else { u32::from(*byte) })
+        .sum();
+    stored == computed
+}
+
+/// Name of the file inside a plain gzip download: the archive's name without `.gz`
+fn gunzipped_name(archive: &Path) -> PathBuf {
+    let name = archive.file_name().map(PathBuf::from).unwrap_or_else(|| PathBuf::from("results"));
+    match name.extension() {
+        Some(ext) if ext.eq_ignore_ascii_case("gz") => name.with_extension(""),
+        _ => name,
+    }
+}
+
+/// Entry name as a path inside the target directory; `None` for absolute paths and
+/// names that climb out with `..`
+pub fn safe_path(name: &Path) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in name.components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {},
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+/// Directory a job's archive is extracted into: `<job_id>/` next to the archive
+pub fn extract_dir(archive: &Path, job_id: &str) -> PathBuf {
+    let dir_name = safe_path(Path::new(job_id)).unwrap_or_else(|| PathBuf::from("extracted"));
+    let dir = archive.with_file_name(&dir_name);
+    if dir == archive {
+        return archive.with_file_name(format!("{}.extracted", dir_name.display()));
+    }
+    dir
+}
+
+/// Unpacks regular files and directories into `dir` and returns the files written; a plain
+/// gzip download becomes one file. Entries with unsafe names are skipped, as are links
+/// and devices in tar archives. Fails as soon as the archive goes over `limits`.
+pub fn extract(archive: &Path, kind: ArchiveKind, dir: &Path, limits: &ExtractLimits) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let file = File::open(archive).with_context(|| format!("Cannot read {}", archive.display()))?;
+    let mut files = Vec::new();
+    let mut budget = Budget { limits, bytes: 0, files: 0 };
+    match kind {
+        ArchiveKind::Zip => {
+            let mut zip = zip::ZipArchive::new(file).context("Not a valid zip archive")?;
+            for i in 0..zip.len() {
+                let mut entry = zip.by_index(i)?;
+                let Some(relative) = entry.enclosed_name().and_then(safe_path) else {
+                    continue;
+                };
+                if entry.is_dir() {
+                    std::fs::create_dir_all(dir.join(&relative))?;
+                } else {
+                    files.push(write_entry(&mut entry, dir, &relative, &mut budget)?);
+                }
+            }
+        },
+        ArchiveKind::TarGz => {
+            let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(file));
+            for entry in tar.entries().context("Not a valid tar.gz archive")? {
+                let mut entry = entry?;
+                let Some(relative) = safe_path(&entry.path()?) else {
+                    continue;
+                };
+                let entry_type = entry.header().entry_type();
+                if entry_type.is_dir() {
+                    std::fs::create_dir_all(dir.join(&relative))?;
+                } else if entry_type.is_file() {
+                    files.push(write_entry(&mut entry, dir, &relative, &mut budget)?);
+                }
+            }
+        },
+        ArchiveKind::Gzip => {
+            let mut decoder = flate2::read::GzDecoder::new(file);
+            files.push(write_entry(&mut decoder, dir, &gunzipped_name(archive), &mut budget)?);
+        },
+    }
+    Ok(files)
+}
+
+fn write_entry(entry: &mut impl Read, dir: &Path, relative: &Path, budget: &mut Budget) -> Result<PathBuf> {
+    budget.files += 1;
+    if budget.files > budget.limits.max_files {
+        anyhow::bail!("Archive holds more than {} files ([extract_limits] max_files)", budget.limits.max_files);
+    }
+    let target = dir.join(relative);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut out = File::create(&target).with_context(|| format!("Failed to create {}", target.display()))?;
+    // Decompressed sizes in headers can lie, so the limit is enforced on what is actually read
+    let remaining = budget.max_bytes().saturating_sub(budget.bytes);
+    let written = std::io::copy(&mut entry.take(remaining.saturating_add(1)), &mut out)
+        .with_context(|| format!("Failed to extract {}", relative.display()))?;
+    if written > remaining {
+        drop(out);
+        let _ = std::fs::remove_file(&target);
+        anyhow::bail!("Archive unpacks to more than {} MB ([extract_limits] max_total_mb)", budget.limits.max_total_mb);
+    }
+    budget.bytes += written;
+    Ok(target)
+}
+
+pub fn is_dataset(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| DATASET_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// What `unpack_results` put on disk
+#[derive(Debug, Clone)]
+pub struct Unpacked {
+    pub dir: PathBuf,
+    /// Every file extracted, datasets included
+    pub files: Vec<PathBuf>,
+    pub datasets: Vec<PathBuf>,
+}
+
+/// Unpacks downloaded results into `<job_id>/` next to them and writes a manifest for
+/// every dataset file, as for a plain download. `None` when the download isn't an archive.
+/// An archive over the limits leaves nothing behind.
+pub fn unpack_results(archive: &Path, entry: &HistoryEntry, limits: &ExtractLimits) -> Result<Option<Unpacked>> {
+    let Some(kind) = detect(archive)? else {
+        return Ok(None);
+    };
+    let dir = extract_dir(archive, &entry.job_id);
+    let existed = dir.exists();
+    let files = match extract(archive, kind, &dir, limits) {
+        Ok(files) => files,
+        Err(err) => {
+            if !existed {
+                let _ = std::fs::remove_dir_all(&dir);
+            }
+            return Err(err);
+        },
+    };
+    let mut datasets: Vec<PathBuf> = files.iter().filter(|f| is_dataset(f)).cloned().collect();
+    datasets.sort();
+    for dataset in &datasets {
+        DatasetManifest::for_dataset(dataset, entry)?.write_for(dataset)?;
+    }
+    Ok(Some(Unpacked { dir, files, datasets }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::archive::{detect, extract, extract_dir, is_dataset, safe_path, unpack_results, ArchiveKind, ExtractLimits};
+    use crate::history::HistoryEntry;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_safe_path() {
+        assert_eq!(safe_path(Path::new("./data/out.jsonl")), Some(PathBuf::from("data/out.jsonl")));
+        assert_eq!(safe_path(Path::new("../x")), None);
+        assert_eq!(safe_path(Path::new("/abs")), None);
+        assert_eq!(safe_path(Path::new(".")), None);
+    }
+
+    #[test]
+    fn test_extract_dir_and_datasets() {
+        let archive = Path::new("/data/results.json");
+        assert_eq!(extract_dir(archive, "job-1"), PathBuf::from("/data/job-1"));
+        // Katalog o nazwie archiwum nie może go zastąpić
+        assert_eq!(extract_dir(archive, "results.json"), PathBuf::from("/data/results.json.extracted"));
+        assert_eq!(extract_dir(archive, "../job"), PathBuf::from("/data/extracted"));
+
+        assert!(is_dataset(Path::new("a/out.JSONL")));
+        assert!(is_dataset(Path::new("out.parquet")));
+        assert!(!is_dataset(Path::new("README.md")));
+    }
+
+    #[test]
+    fn test_extract_zip() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.json");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path)?);
+        let options = zip::write::FileOptions::default();
+        zip.start_file("data/out.jsonl", options)?;
+        zip.write_all(b"{}\n")?;
+        zip.start_file("../escape.txt", options)?;
+        zip.write_all(b"x")?;
+        zip.finish()?;
+
+        assert_eq!(detect(&path)?, Some(ArchiveKind::Zip));
+        let target = extract_dir(&path, "job-1");
+        let files = extract(&path, ArchiveKind::Zip, &target, &ExtractLimits::default())?;
+        assert_eq!(files, vec![target.join("data/out.jsonl")]);
+        assert_eq!(std::fs::read_to_string(&files[0])?, "{}\n");
+        assert!(!dir.path().join("escape.txt").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_tar_gz() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.json");
+        let encoder = flate2::write::GzEncoder::new(std::fs::File::create(&path)?, flate2::Compression::default());
+        let mut tar = tar::Builder::new(encoder);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, "out.csv", &b"a,b\n1"[..])?;
+        let mut link = tar::Header::new_gnu();
+        link.set_entry_type(tar::EntryType::Symlink);
+        link.set_size(0);
+        tar.append_link(&mut link, "passwd", "/etc/passwd")?;
+        tar.into_inner()?.finish()?;
+
+        assert_eq!(detect(&path)?, Some(ArchiveKind::TarGz));
+        let target = dir.path().join("job-2");
+        let files = extract(&path, ArchiveKind::TarGz, &target, &ExtractLimits::default())?;
+        assert_eq!(files, vec![target.join("out.csv")]);
+        // Dowiązania są pomijane
+        assert!(!target.join("passwd").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_plain_gzip_is_not_tar() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.json.gz");
+        let mut encoder = flate2::write::GzEncoder::new(std::fs::File::create(&path)?, flate2::Compression::default());
+        encoder.write_all(&b"[{\"prompt\": \"A\"}]".repeat(40))?;
+        encoder.finish()?;
+
+        // Skompresowany JSON nie ma nagłówka tar, więc jest rozpakowywany jako jeden plik
+        assert_eq!(detect(&path)?, Some(ArchiveKind::Gzip));
+        let target = extract_dir(&path, "job-3");
+        let files = extract(&path, ArchiveKind::Gzip, &target, &ExtractLimits::default())?;
+        assert_eq!(files, vec![target.join("results.json")]);
+        assert!(std::fs::read_to_string(&files[0])?.starts_with("[{\"prompt\""));
+        assert!(is_dataset(&files[0]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_plain_file() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.json");
+        std::fs::write(&path, "[]")?;
+        assert_eq!(detect(&path)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_stops_at_limits() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.json");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path)?);
+        let options = zip::write::FileOptions::default();
+        for i in 0..3 {
+            zip.start_file(format!("part-{}.jsonl", i), options)?;
+            zip.write_all(&vec![b' '; 600 * 1024])?;
+        }
+        zip.finish()?;
+
+        // Trzy pliki po 600 KB nie mieszczą się w 1 MB
+        let target = dir.path().join("too-big");
+        let err = extract(&path, ArchiveKind::Zip, &target, &ExtractLimits { max_total_mb: 1, max_files: 10 }).unwrap_err();
+        assert!(err.to_string().contains("more than 1 MB"));
+        let target = dir.path().join("too-many");
+        let err = extract(&path, ArchiveKind::Zip, &target, &ExtractLimits { max_total_mb: 10, max_files: 2 }).unwrap_err();
+        assert!(err.to_string().contains("more than 2 files"));
+
+        // Przerwane rozpakowanie nie zostawia katalogu zadania
+        let entry = HistoryEntry {
+            job_id: "job-4".to_string(),
+            file: "a.txt".to_string(),
+            processing_type: "standard".to_string(),
+            provider: String::new(),
+            model: String::new(),
+            status: "completed".to_string(),
+            submitted_at: chrono::Utc::now(),
+            finished_at: None,
+            records: None,
+            tokens: None,
+            cost_usd: None,
+            variables: Default::default(),
+        };
+        assert!(unpack_results(&path, &entry, &ExtractLimits { max_total_mb: 1, max_files: 10 }).is_err());
+        assert!(!extract_dir(&path, "job-4").exists());
+        assert_eq!(unpack_results(&path, &entry, &ExtractLimits::default())?.map(|u| u.files.len()), Some(3));
+        Ok(())
+    }
+}
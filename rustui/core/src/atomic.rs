@@ -0,0 +1,140 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
+
+/// Writes `contents` to a temporary file next to `path`, fsyncs it and renames it
+/// over the target, so readers see either the old file or the complete new one.
+/// Permissions of an existing target are kept.
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let mut tmp = temp_file(path)?;
+    tmp.write_all(contents.as_ref()).context("Failed to write temporary file")?;
+    persist(tmp, path)
+}
+
+/// Like `write`, but the file is readable only by the owner from the moment it appears,
+/// whatever the permissions of an existing target. For keys, tokens and other secrets.
+pub fn write_private(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let mut tmp = temp_file(path)?;
+    tmp.write_all(contents.as_ref()).context("Failed to write temporary file")?;
+    replace(tmp, path, private_permissions())
+}
+
+/// Temporary file next to `path` for callers that fill it themselves; `persist` puts it in place
+pub fn temp_file(path: &Path) -> Result<tempfile::NamedTempFile> {
+    let dir = parent_dir(path);
+    let name = path.file_name().context("Target path has no file name")?;
+    tempfile::Builder::new()
+        .prefix(&format!(".{}.", name.to_string_lossy()))
+        .suffix(".tmp")
+        .tempfile_in(dir)
+        .with_context(|| format!("Failed to create temporary file in {}", dir.display()))
+}
+
+/// Fsyncs the temporary file and renames it over `path`, as `write` does
+pub fn persist(tmp: tempfile::NamedTempFile, path: &Path) -> Result<()> {
+    let permissions = match std::fs::metadata(path) {
+        Ok(meta) => Some(meta.permissions()),
+        Err(_) => default_permissions(),
+    };
+    replace(tmp, path, permissions)
+}
+
+fn replace(tmp: tempfile::NamedTempFile, path: &Path, permissions: Option<std::fs::Permissions>) -> Result<()> {
+    if let Some(permissions) = permissions {
+        std::fs::set_permissions(tmp.path(), permissions).context("Failed to set file permissions")?;
+    }
+
+    tmp.as_file().sync_all().context("Failed to flush file to disk")?;
+    tmp.persist(path)
+        .map_err(|e| e.error)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+    sync_dir(parent_dir(path));
+    Ok(())
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+// Temp files are created 0600; new files get the usual 0644 instead
+#[cfg(unix)]
+fn default_permissions() -> Option<std::fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(std::fs::Permissions::from_mode(0o644))
+}
+
+#[cfg(not(unix))]
+fn default_permissions() -> Option<std::fs::Permissions> {
+    None
+}
+
+#[cfg(unix)]
+fn private_permissions() -> Option<std::fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(std::fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(not(unix))]
+fn private_permissions() -> Option<std::fs::Permissions> {
+    None
+}
+
+/// Makes the rename itself durable. Best effort: not every filesystem supports it.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) {
+    if let Ok(dir) = std::fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) {}
+
+#[cfg(test)]
+mod tests {
+    use crate::atomic;
+
+    #[test]
+    fn test_atomic_write_replaces_file() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("history.jsonl");
+
+        atomic::write(&path, "first\n")?;
+        atomic::write(&path, "second\n")?;
+        assert_eq!(std::fs::read_to_string(&path)?, "second\n");
+
+        // Po zapisie nie zostają pliki tymczasowe
+        let files: Vec<_> = std::fs::read_dir(dir.path())?.collect();
+        assert_eq!(files.len(), 1);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+            atomic::write(&path, "third\n")?;
+            assert_eq!(std::fs::metadata(&path)?.permissions().mode() & 0o777, 0o600);
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_write_private_is_owner_only() -> anyhow::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.key");
+
+        atomic::write_private(&path, "secret")?;
+        assert_eq!(std::fs::metadata(&path)?.permissions().mode() & 0o777, 0o600);
+
+        // Nadpisanie pliku 0644 też daje 0600
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644))?;
+        atomic::write_private(&path, "other")?;
+        assert_eq!(std::fs::metadata(&path)?.permissions().mode() & 0o777, 0o600);
+        assert_eq!(std::fs::read_to_string(&path)?, "other");
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::atomic;
+use crate::config::get_config_dir;
+use crate::processors::{ProcessorConfig, Record};
+
+/// Record metadata key holding the id of the exchange that produced the record
+pub const RECORD_KEY: &str = "audit_id";
+
+/// Archive header; bump the digit when the layout changes
+const MAGIC: &[u8; 8] = b"ADAUDIT1";
+const NONCE_LEN: usize = 12;
+
+/// Prompt/response capture from the `[audit]` config section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Encryption key; `audit.key` in the config directory when unset, created on first use
+    #[serde(default)]
+    pub key_file: Option<PathBuf>,
+}
+
+impl AuditSettings {
+    pub fn key_path(&self) -> Result<PathBuf> {
+        match &self.key_file {
+            Some(path) => Ok(path.clone()),
+            None => Ok(get_config_dir()?.join("audit.key")),
+        }
+    }
+}
+
+/// One model call exactly as sent and received, before any cleaning
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelExchange {
+    pub id: String,
+    pub source_file: String,
+    pub provider: String,
+    pub model: String,
+    pub system_prompt: Option<String>,
+    pub prompt: String,
+    pub raw_response: String,
+    pub at: DateTime<Utc>,
+}
+
+impl ModelExchange {
+    /// Captures the call behind `record` and tags the record with its id
+    pub fn for_record(record: &mut Record, source_file: &str, config: &ProcessorConfig) -> Self {
+        let id = uuid::Uuid::new_v4().to_string();
+        record.metadata.insert(RECORD_KEY.to_string(), serde_json::Value::String(id.clone()));
+        Self {
+            id,
+            source_file: source_file.to_string(),
+            provider: config.provider.clone(),
+            model: config.model.clone(),
+            system_prompt: config.system_prompt.clone(),
+            prompt: format!("{}\n\n{}", record.instruction, record.prompt),
+            raw_response: record.completion.clone(),
+            at: Utc::now(),
+        }
+    }
+}
+
+/// Location of a job's archive inside a workspace data directory
+pub fn archive_path(data_dir: &Path, job_id: &str) -> PathBuf {
+    data_dir.join("audit").join(format!("{}.audit", job_id))
+}
+
+pub fn load_key(path: &Path) -> Result<Key<Aes256Gcm>> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read encryption key {}", path.display()))?;
+    if bytes.len() != 32 {
+        anyhow::bail!("Encryption key {} must be 32 bytes, found {}", path.display(), bytes.len());
+    }
+    Ok(*Key::<Aes256Gcm>::from_slice(&bytes))
+}
+
+/// Reads the key, generating a new one (readable only by the owner) if the file doesn't exist
+pub fn load_or_create_key(path: &Path) -> Result<Key<Aes256Gcm>> {
+    if path.exists() {
+        return load_key(path);
+    }
+
+    let key = Aes256Gcm::generate_key(OsRng);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    atomic::write_private(path, key.as_slice()).context("Failed to write encryption key")?;
+    Ok(key)
+}
+
+/// Adds exchanges to a job's archive, re-encrypting it as a whole
+pub fn append(path: &Path, key: &Key<Aes256Gcm>, exchanges: &[ModelExchange]) -> Result<()> {
+    let mut all = if path.exists() { read(path, key)? } else { Vec::new() };
+    all.extend_from_slice(exchanges);
+
+    let plaintext = serde_json::to_vec(&all)?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = Aes256Gcm::new(key)
+        .encrypt(&nonce, plaintext.as_slice())
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt audit archive"))?;
+
+    let mut content = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    content.extend_from_slice(MAGIC);
+    content.extend_from_slice(&nonce);
+    content.extend_from_slice(&ciphertext);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).context("Failed to create audit directory")?;
+    }
+    atomic::write(path, content)
+}
+
+pub fn read(path: &Path, key: &Key<Aes256Gcm>) -> Result<Vec<ModelExchange>> {
+    let content = std::fs::read(path)
+        .with_context(|| format!("Failed to read audit archive {}", path.display()))?;
+    let body = content.strip_prefix(MAGIC.as_slice())
+        .filter(|body| body.len() > NONCE_LEN)
+        .with_context(|| format!("{} is not an audit archive", path.display()))?;
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    let plaintext = Aes256Gcm::new(key)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Cannot decrypt {}: wrong key or corrupted archive", path.display()))?;
+    serde_json::from_slice(&plaintext).context("Invalid audit archive contents")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audit::{append, archive_path, load_key, load_or_create_key, read, RECORD_KEY};
+    use crate::processors::{test_config, Processor, ProcessorConfig, StandardProcessor};
+
+    #[test]
+    fn test_audit_archive_traces_records() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = ProcessorConfig {
+            system_prompt: Some("Be precise".to_string()),
+            ..test_config()
+        };
+        let doc = dir.path().join("doc.txt");
+        std::fs::write(&doc, "Quarterly revenue grew by four percent.")?;
+        let result = StandardProcessor.process_file(&doc.to_string_lossy(), &config)?;
+
+        // Zbiór danych nie zawiera promptów, tylko identyfikator wymiany
+        let dataset = serde_json::to_string(&result)?;
+        assert!(!dataset.contains("Be precise"));
+        let audit_id = result.records[0].metadata[RECORD_KEY][0].as_str().unwrap().to_string();
+
+        let key_file = dir.path().join("audit.key");
+        let key = load_or_create_key(&key_file)?;
+        let archive = archive_path(dir.path(), "job-1");
+        append(&archive, &key, &result.exchanges)?;
+        append(&archive, &key, &result.exchanges)?;
+
+        let raw = std::fs::read(&archive)?;
+        assert!(!String::from_utf8_lossy(&raw).contains("Be precise"));
+
+        let exchanges = read(&archive, &load_key(&key_file)?)?;
+        assert_eq!(exchanges.len(), 2);
+        let exchange = exchanges.iter().find(|e| e.id == audit_id).unwrap();
+        assert_eq!(exchange.system_prompt.as_deref(), Some("Be precise"));
+        assert_eq!(exchange.raw_response, result.records[0].completion);
+
+        // Inny klucz nie odszyfruje archiwum
+        let other = load_or_create_key(&dir.path().join("other.key"))?;
+        assert!(read(&archive, &other).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+use crate::log_debug;
+
+/// Runs `task` on its own thread with a single-threaded tokio runtime.
+/// Only DEBUG-level logging is used here so console output doesn't garble a terminal UI.
+pub fn spawn<F>(name: &'static str, task: F)
+where
+    F: FnOnce(&tokio::runtime::Runtime) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(runtime) => task(&runtime),
+            Err(err) => log_debug!("{} skipped: {}", name, err),
+        }
+    });
+}
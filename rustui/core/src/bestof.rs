@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::processors::ProcessingResult;
+use crate::preference::PreferencePair;
+
+/// Record metadata: candidates that lost the selection, as
+/// `{"chunk": i, "prompt": ..., "chosen": ..., "rejected": ..., "score": ...}` objects
+pub const REJECTED: &str = "rejected_candidates";
+
+/// Words shorter than this don't count as content when scoring coverage
+const MIN_CONTENT_WORD: usize = 4;
+
+/// Best-of-N generation from the `[best_of]` config section or a preset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BestOfSettings {
+    /// Candidates generated per chunk
+    #[serde(default = "default_n")]
+    pub n: usize,
+    #[serde(default)]
+    pub selection: Selection,
+    /// Keep the losing candidates in the record metadata, e.g. for preference training
+    #[serde(default = "default_true")]
+    pub keep_rejected: bool,
+}
+
+fn default_n() -> usize {
+    3
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for BestOfSettings {
+    fn default() -> Self {
+        Self { n: default_n(), selection: Selection::default(), keep_rejected: true }
+    }
+}
+
+impl fmt::Display for BestOfSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "best of {}, {}", self.n, self.selection)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Selection {
+    /// Coverage of the chunk's content words, penalizing candidates much longer than the chunk
+    #[default]
+    Heuristic,
+    /// Scores from a judge model call comparing all candidates
+    Judge,
+}
+
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Selection::Heuristic => write!(f, "heuristic"),
+            Selection::Judge => write!(f, "judge"),
+        }
+    }
+}
+
+/// Heuristic score in 0–1 of a candidate for `source`
+pub fn heuristic_score(candidate: &str, source: &str) -> f64 {
+    let content = |text: &str| -> HashSet<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| w.chars().count() >= MIN_CONTENT_WORD)
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let wanted = content(source);
+    if wanted.is_empty() {
+        return 0.0;
+    }
+    let coverage = wanted.intersection(&content(candidate)).count() as f64 / wanted.len() as f64;
+    let source_len = source.split_whitespace().count().max(1) as f64;
+    let candidate_len = candidate.split_whitespace().count() as f64;
+    // Up to twice the source length is fine; beyond that the candidate is padding
+    let brevity = (2.0 * source_len / candidate_len.max(1.0)).min(1.0);
+    coverage * brevity
+}
+
+/// Asks a judge model to score every candidate from 0 to 10
+pub fn judge_prompt(prompt: &str, candidates: &[String]) -> String {
+    let mut text = format!(
+        "Score each candidate response to the task below from 0 (useless) to 10 (complete and faithful). \
+         Answer with one line per candidate: Candidate <number>: <score>\n\nTask:\n{}\n",
+        prompt
+    );
+    for (i, candidate) in candidates.iter().enumerate() {
+        text.push_str(&format!("\nCandidate {}:\n{}\n", i + 1, candidate));
+    }
+    text
+}
+
+/// Scores from `Candidate N: score` lines; candidates the judge skipped score 0
+pub fn parse_scores(output: &str, count: usize) -> Vec<f64> {
+    let mut scores = vec![0.0; count];
+    for line in output.lines() {
+        let Some(rest) = line.trim().strip_prefix("Candidate ") else {
+            continue;
+        };
+        let Some((number, score)) = rest.split_once(':') else {
+            continue;
+        };
+        let score = score.trim().split('/').next().unwrap_or_default().trim();
+        if let (Ok(number), Ok(score)) = (number.trim().parse::<usize>(), score.parse::<f64>()) {
+            if (1..=count).contains(&number) {
+                scores[number - 1] = score;
+            }
+        }
+    }
+    scores
+}
+
+/// Index of the best candidate and the score of each; ties go to the earlier candidate
+pub fn select(candidates: &[String], prompt: &str, source: &str, selection: Selection) -> (usize, Vec<f64>) {
+    let scores = match selection {
+        Selection::Heuristic => candidates.iter().map(|c| heuristic_score(c, source)).collect(),
+        Selection::Judge => parse_scores(&mock_judge(&judge_prompt(prompt, candidates), source), candidates.len()),
+    };
+    let best = scores.iter().enumerate()
+        .fold(0, |best, (i, score)| if *score > scores[best] { i } else { best });
+    (best, scores)
+}
+
+/// Chosen/rejected pairs from best-of-N selections, for preference training
+pub fn pairs(results: &[ProcessingResult]) -> Vec<PreferencePair> {
+    results.iter()
+        .flat_map(|result| &result.records)
+        .filter_map(|record| record.metadata.get(REJECTED)?.as_array())
+        .flatten()
+        .filter_map(|entry| Some(PreferencePair {
+            prompt: entry["prompt"].as_str()?.to_string(),
+            chosen: entry["chosen"].as_str()?.to_string(),
+            rejected: entry["rejected"].as_str()?.to_string(),
+        }))
+        .collect()
+}
+
+/// Mock judge model call: the heuristic score on a 0–10 scale
+fn mock_judge(prompt: &str, source: &str) -> String {
+    prompt.split("\nCandidate ")
+        .skip(1)
+        .filter_map(|part| {
+            let (number, candidate) = part.split_once(":\n")?;
+            Some(format!("Candidate {}: {:.1}\n", number, heuristic_score(candidate, source) * 10.0))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bestof::{self, BestOfSettings, Selection, REJECTED};
+    use crate::chunking::ChunkSettings;
+    use crate::processors::{test_config, Processor, ProcessorConfig, StandardProcessor};
+
+    #[test]
+    fn test_selection_strategies_agree_on_full_candidate() {
+        let source = "The kidney filters blood and regulates electrolyte balance";
+        let candidates = vec![
+            "The kidney filters".to_string(),
+            "The kidney filters blood and regulates electrolyte balance".to_string(),
+            // Kandydat wielokrotnie dłuższy od źródła jest karany
+            format!("{} {}", source, "padding ".repeat(40)),
+        ];
+        let (best, scores) = bestof::select(&candidates, "Summarize", source, Selection::Heuristic);
+        assert_eq!(best, 1);
+        assert!(scores[2] < scores[1]);
+        assert_eq!(bestof::select(&candidates, "Summarize", source, Selection::Judge).0, 1);
+    }
+
+    #[test]
+    fn test_parse_judge_scores() {
+        let output = "Candidate 2: 7/10\nCandidate 1: 9\nCandidate 5: 10\nnoise";
+        assert_eq!(bestof::parse_scores(output, 3), vec![9.0, 7.0, 0.0]);
+    }
+
+    #[test]
+    fn test_chunked_run_keeps_rejected_candidates() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("doc.txt");
+        let text: Vec<String> = (1..=24).map(|i| format!("word{}", i)).collect();
+        std::fs::write(&path, text.join(" "))?;
+
+        let config = ProcessorConfig {
+            chunking: Some(ChunkSettings { size_words: 12, overlap_words: 0, ..Default::default() }),
+            best_of: Some(BestOfSettings { n: 3, selection: Selection::Heuristic, keep_rejected: true }),
+            ..test_config()
+        };
+        let result = StandardProcessor.process_file(path.to_str().unwrap(), &config)?;
+
+        // Trzy wywołania na każdy z dwóch fragmentów
+        assert_eq!(result.exchanges.len(), 6);
+        assert_eq!(result.records[0].completion, text[..12].join(" "));
+        assert_eq!(result.records[1].completion, text[12..].join(" "));
+        // Odrzuceni kandydaci trafiają do rekordu swojego fragmentu
+        let rejected = result.records[1].metadata[REJECTED].as_array().unwrap();
+        assert_eq!(rejected.len(), 2);
+        assert_eq!(rejected[0]["chunk"], 1);
+
+        let pairs = bestof::pairs(&[result]);
+        assert_eq!(pairs.len(), 4);
+        assert_eq!(pairs[0].chosen, text[..12].join(" "));
+        assert_eq!(pairs[0].rejected, text[..6].join(" "));
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use toml::{Table, Value};
+
+use crate::config::Config;
+
+/// Bumped when the bundle layout changes incompatibly
+pub const BUNDLE_VERSION: i64 = 1;
+
+/// Settings that only make sense on the machine they were made on
+const MACHINE_LOCAL_KEYS: &[&str] = &["username", "tutorial_completed", "workspace", "downloads_directory"];
+
+/// Keys dropped anywhere in the tree; the importing side keeps its own values
+const SECRET_KEYS: &[&str] = &["secret", "password", "token", "api_key"];
+
+/// Setting that differs between the local config and an imported bundle
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// Dotted path, e.g. `output.name_template`
+    pub key: String,
+    pub local: Value,
+    pub bundled: Value,
+}
+
+/// Serializes a shareable copy of the config: no secrets, no machine-local settings
+pub fn export(config: &Config) -> Result<String> {
+    let mut table = Table::try_from(config).context("Failed to serialize config")?;
+    for key in MACHINE_LOCAL_KEYS {
+        table.remove(*key);
+    }
+    strip_secrets(&mut table);
+
+    let mut meta = Table::new();
+    meta.insert("version".to_string(), Value::Integer(BUNDLE_VERSION));
+    meta.insert("client_version".to_string(), Value::String(env!("CARGO_PKG_VERSION").to_string()));
+    meta.insert("exported_at".to_string(), Value::String(chrono::Utc::now().to_rfc3339()));
+
+    let mut bundle = Table::new();
+    bundle.insert("bundle".to_string(), Value::Table(meta));
+    bundle.insert("config".to_string(), Value::Table(table));
+    toml::to_string_pretty(&bundle).context("Failed to serialize bundle")
+}
+
+/// Parses a bundle and returns its config table
+pub fn read(content: &str) -> Result<Table> {
+    let mut bundle: Table = toml::from_str(content).context("Invalid bundle file")?;
+    let version = bundle.get("bundle")
+        .and_then(|meta| meta.get("version"))
+        .and_then(|v| v.as_integer())
+        .context("Not a configuration bundle (missing [bundle] version)")?;
+    if version > BUNDLE_VERSION {
+        anyhow::bail!("Bundle version {} is newer than supported ({}); update the client first", version, BUNDLE_VERSION);
+    }
+    match bundle.remove("config") {
+        Some(Value::Table(config)) => Ok(config),
+        _ => anyhow::bail!("Bundle has no [config] section"),
+    }
+}
+
+/// Settings present on both sides with different values. Keys only in the
+/// bundle are added and keys only in the local config are kept, without asking.
+pub fn conflicts(local: &Table, bundled: &Table) -> Vec<Conflict> {
+    let local_leaves = leaves(local);
+    leaves(bundled).into_iter()
+        .filter_map(|(key, bundled)| {
+            let local = local_leaves.iter().find(|(k, _)| *k == key)?.1.clone();
+            (local != bundled).then_some(Conflict { key, local, bundled })
+        })
+        .collect()
+}
+
+/// Applies the bundle over the local config, except for conflicting keys listed in `keep_local`
+pub fn merge(local: &Table, bundled: &Table, keep_local: &HashSet<String>) -> Result<Config> {
+    let mut merged = local.clone();
+    for (key, value) in leaves(bundled) {
+        if !keep_local.contains(&key) {
+            set_path(&mut merged, &key, value);
+        }
+    }
+    Value::Table(merged).try_into().context("Merged configuration is invalid")
+}
+
+fn strip_secrets(table: &mut Table) {
+    table.retain(|key, _| !SECRET_KEYS.contains(&key));
+    for (_, value) in table.iter_mut() {
+        strip_value(value);
+    }
+}
+
+fn strip_value(value: &mut Value) {
+    match value {
+        Value::Table(table) => strip_secrets(table),
+        Value::Array(items) => items.iter_mut().for_each(strip_value),
+        Value::String(s) => {
+            // Credentials embedded in URLs, e.g. redis://:password@host
+            if let Ok(mut url) = reqwest::Url::parse(s) {
+                if url.password().is_some() && url.set_password(None).is_ok() {
+                    *s = url.to_string();
+                }
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Flattens nested tables into dotted keys; arrays are compared as a whole
+fn leaves(table: &Table) -> Vec<(String, Value)> {
+    let mut out = Vec::new();
+    collect_leaves("", table, &mut out);
+    out
+}
+
+fn collect_leaves(prefix: &str, table: &Table, out: &mut Vec<(String, Value)>) {
+    for (key, value) in table {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            Value::Table(inner) if !inner.is_empty() => collect_leaves(&path, inner, out),
+            _ => out.push((path, value.clone())),
+        }
+    }
+}
+
+fn set_path(table: &mut Table, path: &str, value: Value) {
+    match path.split_once('.') {
+        Some((head, rest)) => {
+            let entry = table.entry(head.to_string()).or_insert_with(|| Value::Table(Table::new()));
+            if !entry.is_table() {
+                *entry = Value::Table(Table::new());
+            }
+            if let Value::Table(inner) = entry {
+                set_path(inner, rest, value);
+            }
+        },
+        None => {
+            table.insert(path.to_string(), value);
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bundle::{conflicts, export, merge, read};
+    use crate::config::Config;
+    use crate::webhooks::WebhookSettings;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_bundle_roundtrip_without_secrets() -> anyhow::Result<()> {
+        let mut source = Config { username: Some("alice".to_string()), ..Default::default() };
+        source.output.name_template = "{job_id}.{ext}".to_string();
+        source.webhooks.push(WebhookSettings {
+            url: "https://hooks.example.com/x".to_string(),
+            secret: Some("s3cret".to_string()),
+            events: Vec::new(),
+        });
+
+        let content = export(&source)?;
+        assert!(!content.contains("s3cret"));
+        assert!(!content.contains("alice"));
+
+        let bundled = read(&content)?;
+        let target = Config {
+            username: Some("bob".to_string()),
+            backend_url: "http://backend.local".to_string(),
+            ..Default::default()
+        };
+        let local = toml::Table::try_from(&target)?;
+
+        let found: Vec<String> = conflicts(&local, &bundled).into_iter().map(|c| c.key).collect();
+        assert!(found.contains(&"output.name_template".to_string()));
+        assert!(found.contains(&"backend_url".to_string()));
+
+        // Lokalny backend zostaje, reszta przychodzi z paczki
+        let keep: HashSet<String> = ["backend_url".to_string()].into();
+        let merged = merge(&local, &bundled, &keep)?;
+        assert_eq!(merged.backend_url, "http://backend.local");
+        assert_eq!(merged.output.name_template, "{job_id}.{ext}");
+        assert_eq!(merged.username.as_deref(), Some("bob"));
+        assert_eq!(merged.webhooks.len(), 1);
+        assert_eq!(merged.webhooks[0].secret, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Renames and sunsets the client knows about; `[model_catalog.replacements]` entries win
+const KNOWN_REPLACEMENTS: [(&str, &str); 4] = [
+    ("gpt-3.5-turbo", "gpt-4o-mini"),
+    ("gpt-4-turbo", "gpt-4o"),
+    ("claude-3-sonnet", "claude-3-5-sonnet"),
+    ("claude-3-opus", "claude-3-5-sonnet"),
+];
+
+/// `[model_catalog]` in config.toml: refreshing the provider model lists from the backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelCatalogSettings {
+    /// How often to refresh while the TUI is running; 0 only refreshes at startup
+    #[serde(default = "default_refresh_hours")]
+    pub refresh_hours: u64,
+    /// How long fetched lists are reused, across restarts too, before opening Settings
+    /// fetches them again
+    #[serde(default = "default_cache_minutes")]
+    pub cache_minutes: u64,
+    /// Model to suggest in place of a deprecated or withdrawn one, by model id
+    #[serde(default)]
+    pub replacements: BTreeMap<String, String>,
+}
+
+fn default_refresh_hours() -> u64 {
+    12
+}
+
+fn default_cache_minutes() -> u64 {
+    60
+}
+
+impl Default for ModelCatalogSettings {
+    fn default() -> Self {
+        Self { refresh_hours: default_refresh_hours(), cache_minutes: default_cache_minutes(), replacements: BTreeMap::new() }
+    }
+}
+
+impl ModelCatalogSettings {
+    pub fn refresh_interval(&self) -> Option<Duration> {
+        (self.refresh_hours > 0).then(|| Duration::from_secs(self.refresh_hours * 3600))
+    }
+
+    pub fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.cache_minutes * 60)
+    }
+
+    fn mapped_replacement(&self, model: &str) -> Option<&str> {
+        self.replacements.get(model).map(String::as_str).or_else(|| {
+            KNOWN_REPLACEMENTS.iter().find(|(old, _)| *old == model).map(|(_, new)| *new)
+        })
+    }
+}
+
+/// One model as the backend lists it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+    #[serde(default)]
+    pub deprecated: bool,
+    /// Date after which the provider stops serving the model
+    #[serde(default)]
+    pub sunset: Option<String>,
+    /// Successor named by the provider
+    #[serde(default, alias = "replaced_by")]
+    pub replacement: Option<String>,
+}
+
+/// Where the last fetched catalog is kept between runs
+pub fn cache_path() -> Result<PathBuf> {
+    Ok(crate::config::get_config_dir()?.join("model_catalog.json"))
+}
+
+/// Models offered per provider, as of the last refresh
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelCatalog {
+    pub providers: BTreeMap<String, Vec<ModelInfo>>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl ModelCatalog {
+    /// Reads the `/api/models` response: provider names mapped to lists of model ids or
+    /// model objects, optionally wrapped in `providers` or `models`
+    pub fn parse(response: &Value) -> Result<Self> {
+        let providers = response.get("providers")
+            .or_else(|| response.get("models"))
+            .unwrap_or(response)
+            .as_object()
+            .ok_or_else(|| anyhow::anyhow!("Model list is not an object of providers"))?;
+        let mut catalog = BTreeMap::new();
+        for (provider, models) in providers {
+            let Some(models) = models.as_array() else {
+                continue;
+            };
+            let models: Vec<ModelInfo> = models.iter().filter_map(|model| match model {
+                Value::String(id) => Some(ModelInfo { id: id.clone(), deprecated: false, sunset: None, replacement: None }),
+                other => serde_json::from_value(other.clone()).ok(),
+            }).collect();
+            catalog.insert(provider.clone(), models);
+        }
+        if catalog.is_empty() {
+            anyhow::bail!("Model list names no providers");
+        }
+        Ok(Self { providers: catalog, fetched_at: Utc::now() })
+    }
+
+    /// The catalog cached at `path`, unless there is none or it is older than `ttl`
+    pub fn load_cached(path: &Path, ttl: Duration) -> Result<Option<Self>> {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let catalog: Self = serde_json::from_slice(&data)?;
+        Ok(catalog.is_fresh(ttl).then_some(catalog))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        crate::atomic::write(path, serde_json::to_vec_pretty(self)?)
+    }
+
+    /// Whether the catalog was fetched less than `ttl` ago
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        chrono::Duration::from_std(ttl).is_ok_and(|ttl| Utc::now() - self.fetched_at < ttl)
+    }
+
+    pub fn model(&self, provider: &str, model: &str) -> Option<&ModelInfo> {
+        self.providers.get(provider)?.iter().find(|info| info.id == model)
+    }
+
+    /// Model ids of a provider, for the model picker
+    pub fn ids(&self, provider: &str) -> Vec<String> {
+        self.providers.get(provider).map(|models| models.iter().map(|m| m.id.clone()).collect()).unwrap_or_default()
+    }
+
+    /// Why `model` of `provider` is about to fail, if it is. Providers missing from the
+    /// catalog aren't judged: the backend may just not list them.
+    pub fn check(&self, provider: &str, model: &str, settings: &ModelCatalogSettings) -> Option<ModelWarning> {
+        let offered = self.providers.get(provider)?;
+        let (status, named) = match offered.iter().find(|info| info.id == model) {
+            Some(info) if info.deprecated => (ModelStatus::Deprecated { sunset: info.sunset.clone() }, info.replacement.as_deref()),
+            Some(_) => return None,
+            None => (ModelStatus::Withdrawn, None),
+        };
+        // A suggestion is only useful when the provider still offers it
+        let usable = |candidate: &&str| self.model(provider, candidate).is_some_and(|info| !info.deprecated);
+        let replacement = settings.mapped_replacement(model).filter(usable)
+            .or(named.filter(usable))
+            .map(str::to_string);
+        Some(ModelWarning { provider: provider.to_string(), model: model.to_string(), status, replacement })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelStatus {
+    /// Still served, but scheduled to go
+    Deprecated { sunset: Option<String> },
+    /// No longer listed by the provider; jobs using it fail
+    Withdrawn,
+}
+
+/// A configured or selected model that is deprecated or gone, with what to use instead
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelWarning {
+    pub provider: String,
+    pub model: String,
+    pub status: ModelStatus,
+    pub replacement: Option<String>,
+}
+
+impl ModelWarning {
+    pub fn is_withdrawn(&self) -> bool {
+        self.status == ModelStatus::Withdrawn
+    }
+}
+
+impl fmt::Display for ModelWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.status {
+            ModelStatus::Deprecated { sunset: Some(date) } => write!(f, "{} is deprecated (sunset {})", self.model, date)?,
+            ModelStatus::Deprecated { sunset: None } => write!(f, "{} is deprecated", self.model)?,
+            ModelStatus::Withdrawn => write!(f, "{} is no longer offered by {}", self.model, self.provider)?,
+        }
+        match &self.replacement {
+            Some(replacement) => write!(f, "; use {}", replacement),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::catalog::{ModelCatalog, ModelCatalogSettings, ModelStatus};
+    use serde_json::json;
+
+    fn catalog() -> ModelCatalog {
+        ModelCatalog::parse(&json!({
+            "providers": {
+                "openai": [
+                    "gpt-4o",
+                    "gpt-4o-mini",
+                    { "id": "gpt-4-turbo", "deprecated": true, "sunset": "2026-12-01", "replaced_by": "gpt-4o" }
+                ],
+                "anthropic": ["claude-3-5-sonnet"],
+                "broken": "not a list"
+            }
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let catalog = catalog();
+        assert_eq!(catalog.ids("openai"), vec!["gpt-4o", "gpt-4o-mini", "gpt-4-turbo"]);
+        assert!(catalog.model("openai", "gpt-4-turbo").unwrap().deprecated);
+        // Dostawca bez listy modeli jest pomijany
+        assert!(!catalog.providers.contains_key("broken"));
+
+        // Odpowiedź bez opakowania też jest akceptowana
+        let bare = ModelCatalog::parse(&json!({ "openai": ["gpt-4o"] })).unwrap();
+        assert_eq!(bare.ids("openai"), vec!["gpt-4o"]);
+        assert!(ModelCatalog::parse(&json!(["gpt-4o"])).is_err());
+        assert!(ModelCatalog::parse(&json!({})).is_err());
+    }
+
+    #[test]
+    fn test_check_suggests_offered_replacement() {
+        let catalog = catalog();
+        let mut settings = ModelCatalogSettings::default();
+
+        assert_eq!(catalog.check("openai", "gpt-4o", &settings), None);
+        // Nieznany dostawca nie jest oceniany
+        assert_eq!(catalog.check("mistral", "mistral-large", &settings), None);
+
+        let deprecated = catalog.check("openai", "gpt-4-turbo", &settings).unwrap();
+        assert_eq!(deprecated.status, ModelStatus::Deprecated { sunset: Some("2026-12-01".to_string()) });
+        assert_eq!(deprecated.to_string(), "gpt-4-turbo is deprecated (sunset 2026-12-01); use gpt-4o");
+
+        let withdrawn = catalog.check("anthropic", "claude-3-opus", &settings).unwrap();
+        assert!(withdrawn.is_withdrawn());
+        assert_eq!(withdrawn.to_string(), "claude-3-opus is no longer offered by anthropic; use claude-3-5-sonnet");
+
+        // Zamiennik z konfiguracji wygrywa, ale tylko jeśli dostawca go oferuje
+        settings.replacements.insert("gpt-4-turbo".to_string(), "gpt-4o-mini".to_string());
+        settings.replacements.insert("claude-3-opus".to_string(), "claude-4-opus".to_string());
+        assert_eq!(catalog.check("openai", "gpt-4-turbo", &settings).unwrap().replacement.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(catalog.check("anthropic", "claude-3-opus", &settings).unwrap().replacement, None);
+        assert_eq!(catalog.check("anthropic", "claude-2", &settings).unwrap().to_string(), "claude-2 is no longer offered by anthropic");
+    }
+
+    #[test]
+    fn test_cache_expires() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("model_catalog.json");
+        let ttl = std::time::Duration::from_secs(3600);
+        assert_eq!(ModelCatalog::load_cached(&path, ttl)?, None);
+
+        let mut cached = catalog();
+        cached.save(&path)?;
+        assert_eq!(ModelCatalog::load_cached(&path, ttl)?, Some(cached.clone()));
+
+        // Lista starsza niż TTL nie jest używana
+        cached.fetched_at -= chrono::Duration::hours(2);
+        cached.save(&path)?;
+        assert_eq!(ModelCatalog::load_cached(&path, ttl)?, None);
+
+        std::fs::write(&path, "not json")?;
+        assert!(ModelCatalog::load_cached(&path, ttl).is_err());
+        Ok(())
+    }
+}
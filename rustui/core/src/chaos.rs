@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Body of injected error responses, shaped like the backend's own errors
+const INJECTED_DETAIL: &str = r#"{"detail":"Injected by chaos mode"}"#;
+
+/// `[chaos]` in config.toml: faults injected into backend requests to try out retries and
+/// resuming without breaking a real backend. Also enabled with `--chaos`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChaosSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Each request is held back by up to this many milliseconds
+    #[serde(default = "default_max_latency_ms")]
+    pub max_latency_ms: u64,
+    /// Share of requests answered with 429 or 500 instead of being sent
+    #[serde(default = "default_error_rate")]
+    pub error_rate: f64,
+    /// Share of requests failing as if the connection dropped
+    #[serde(default = "default_drop_rate")]
+    pub drop_rate: f64,
+    /// Repeats the same faults on every run; random when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+fn default_max_latency_ms() -> u64 {
+    1500
+}
+
+fn default_error_rate() -> f64 {
+    0.1
+}
+
+fn default_drop_rate() -> f64 {
+    0.05
+}
+
+impl Default for ChaosSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_latency_ms: default_max_latency_ms(),
+            error_rate: default_error_rate(),
+            drop_rate: default_drop_rate(),
+            seed: None,
+        }
+    }
+}
+
+/// What happens to one request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Sent to the backend as usual
+    Pass,
+    /// Answered locally with this status
+    Status(reqwest::StatusCode),
+    /// Fails like a connection reset; GETs are retried as after a real one
+    Drop,
+}
+
+/// Error of a request dropped by chaos mode
+#[derive(Debug)]
+pub struct Dropped;
+
+impl std::fmt::Display for Dropped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Connection dropped (chaos mode)")
+    }
+}
+
+impl std::error::Error for Dropped {}
+
+/// Faults injected so far, for the Network screen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChaosStats {
+    pub delayed: u64,
+    pub errors: u64,
+    pub dropped: u64,
+}
+
+/// Decides the fault of each request from a seeded generator
+#[derive(Debug)]
+pub struct Chaos {
+    settings: ChaosSettings,
+    state: Mutex<u64>,
+    delayed: AtomicU64,
+    errors: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl Chaos {
+    pub fn new(settings: &ChaosSettings) -> Self {
+        let seed = settings.seed.unwrap_or_else(|| {
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or_default()
+        });
+        Self {
+            settings: settings.clone(),
+            // xorshift gets stuck at zero
+            state: Mutex::new(seed.max(1)),
+            delayed: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// `None` when chaos mode is off
+    pub fn from_settings(settings: &ChaosSettings) -> Option<Self> {
+        settings.enabled.then(|| Self::new(settings))
+    }
+
+    /// xorshift64*; good enough to spread faults, and repeatable with a seed
+    fn next(&self) -> u64 {
+        let Ok(mut state) = self.state.lock() else {
+            return 0;
+        };
+        let mut x = *state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        *state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in [0, 1)
+    fn chance(&self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// How long to hold back the next request
+    pub fn latency(&self) -> Duration {
+        if self.settings.max_latency_ms == 0 {
+            return Duration::ZERO;
+        }
+        let ms = self.next() % (self.settings.max_latency_ms + 1);
+        if ms > 0 {
+            self.delayed.fetch_add(1, Ordering::Relaxed);
+        }
+        Duration::from_millis(ms)
+    }
+
+    pub fn fault(&self) -> Fault {
+        let roll = self.chance();
+        let error_rate = self.settings.error_rate.clamp(0.0, 1.0);
+        if roll < error_rate {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+            // Rate limits are the more common failure of a busy backend
+            return Fault::Status(if self.next().is_multiple_of(3) {
+                reqwest::StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                reqwest::StatusCode::TOO_MANY_REQUESTS
+            });
+        }
+        if roll < error_rate + self.settings.drop_rate.clamp(0.0, 1.0) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return Fault::Drop;
+        }
+        Fault::Pass
+    }
+
+    pub fn stats(&self) -> ChaosStats {
+        ChaosStats {
+            delayed: self.delayed.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The answer of an injected error, as if the backend had sent it
+pub fn response(status: reqwest::StatusCode) -> reqwest::Response {
+    let mut response = http::Response::new(INJECTED_DETAIL);
+    *response.status_mut() = status;
+    response.headers_mut().insert(reqwest::header::CONTENT_TYPE, reqwest::header::HeaderValue::from_static("application/json"));
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        response.headers_mut().insert(reqwest::header::RETRY_AFTER, reqwest::header::HeaderValue::from_static("1"));
+    }
+    reqwest::Response::from(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::ApiClient;
+    use crate::chaos::{self, Chaos, ChaosSettings, Dropped, Fault};
+    use crate::network::{NetworkMonitor, MAX_RETRIES};
+
+    fn settings(error_rate: f64, drop_rate: f64) -> ChaosSettings {
+        ChaosSettings { enabled: true, max_latency_ms: 0, error_rate, drop_rate, seed: Some(7) }
+    }
+
+    #[test]
+    fn test_faults_follow_rates_and_seed() {
+        assert!(Chaos::from_settings(&ChaosSettings::default()).is_none());
+
+        let chaos = Chaos::new(&settings(0.3, 0.2));
+        let faults: Vec<Fault> = (0..1000).map(|_| chaos.fault()).collect();
+        let stats = chaos.stats();
+        assert_eq!(faults.iter().filter(|f| matches!(f, Fault::Status(_))).count() as u64, stats.errors);
+        assert_eq!(faults.iter().filter(|f| **f == Fault::Drop).count() as u64, stats.dropped);
+        assert!((200..400).contains(&stats.errors), "{:?}", stats);
+        assert!((100..300).contains(&stats.dropped), "{:?}", stats);
+
+        // To samo ziarno daje te same usterki
+        let again = Chaos::new(&settings(0.3, 0.2));
+        assert_eq!((0..1000).map(|_| again.fault()).collect::<Vec<_>>(), faults);
+
+        let calm = Chaos::new(&settings(0.0, 0.0));
+        assert!((0..100).all(|_| calm.fault() == Fault::Pass));
+        assert!(calm.latency().is_zero());
+        let slow = Chaos::new(&ChaosSettings { max_latency_ms: 50, ..settings(0.0, 0.0) });
+        assert!((0..100).all(|_| slow.latency().as_millis() <= 50));
+    }
+
+    #[tokio::test]
+    async fn test_injected_response() -> anyhow::Result<()> {
+        let response = chaos::response(reqwest::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.status(), 429);
+        assert_eq!(response.headers()["retry-after"], "1");
+        assert!(response.text().await?.contains("chaos mode"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_requests_fail_without_reaching_backend() -> anyhow::Result<()> {
+        // Nikt nie odpowiada na tym porcie, więc każda odpowiedź pochodzi z trybu chaosu
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+
+        let monitor = NetworkMonitor::default().with_chaos(Chaos::from_settings(&settings(1.0, 0.0)));
+        let err = ApiClient::new(&url).with_monitor(monitor.clone()).get_job_status("job-1").await.unwrap_err();
+        assert!(err.to_string().contains("API error (429") || err.to_string().contains("API error (500"), "{}", err);
+        assert_eq!(monitor.chaos_stats().unwrap().errors, 1);
+
+        // Zerwane połączenie jest ponawiane jak prawdziwe
+        let monitor = NetworkMonitor::default().with_chaos(Chaos::from_settings(&settings(0.0, 1.0)));
+        let err = ApiClient::new(&url).with_monitor(monitor.clone()).get_job_status("job-1").await.unwrap_err();
+        assert!(err.chain().any(|cause| cause.is::<Dropped>()));
+        assert_eq!(monitor.chaos_stats().unwrap().dropped, 1 + MAX_RETRIES as u64);
+        assert!(NetworkMonitor::default().chaos_stats().is_none());
+        Ok(())
+    }
+}
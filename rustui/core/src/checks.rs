@@ -0,0 +1,114 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::processors::{ProcessingResult, Record};
+use crate::numeric;
+use crate::terminology::Terminology;
+
+/// Record metadata: problems found by the checks, as `{"check": ..., "detail": ...}` objects
+pub const ISSUES: &str = "check_issues";
+
+/// Verification passes over generated records from the `[checks]` config section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CheckSettings {
+    /// Domain terminology list (one term per line) completions are checked against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminology: Option<PathBuf>,
+    /// Flag completions with numbers, amounts or dates that don't appear in the source
+    #[serde(default)]
+    pub numbers: bool,
+}
+
+impl CheckSettings {
+    pub fn is_empty(&self) -> bool {
+        self.terminology.is_none() && !self.numbers
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub check: &'static str,
+    pub detail: String,
+}
+
+/// Checks with their reference data loaded
+#[derive(Debug, Clone, Default)]
+pub struct Checks {
+    terminology: Option<Terminology>,
+    numbers: bool,
+}
+
+impl Checks {
+    pub fn load(settings: &CheckSettings) -> Result<Self> {
+        let terminology = settings.terminology.as_deref().map(Terminology::load).transpose()?;
+        Ok(Self { terminology, numbers: settings.numbers })
+    }
+
+    /// Issues of one record; `source` is the text the completion must be faithful to
+    pub fn record_issues(&self, record: &Record, source: &str) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        if let Some(terminology) = &self.terminology {
+            issues.extend(terminology.check(&record.completion, source).into_iter()
+                .map(|issue| Issue { check: "terminology", detail: issue.to_string() }));
+        }
+        if self.numbers {
+            issues.extend(numeric::unsupported(&record.completion, source).into_iter()
+                .map(|figure| Issue { check: "numbers", detail: format!("'{}' does not appear in the source", figure) }));
+        }
+        issues
+    }
+
+    /// Records the issues in each record's metadata, replacing those of an earlier pass.
+    /// A record's source is its prompt plus the source document, when it can still be read.
+    /// Returns the number of flagged records.
+    pub fn apply(&self, results: &mut [ProcessingResult]) -> usize {
+        let mut flagged = 0;
+        for result in results.iter_mut() {
+            let document = std::fs::read_to_string(&result.source_file).unwrap_or_default();
+            flagged += self.apply_to(&mut result.records, &document);
+        }
+        flagged
+    }
+
+    fn apply_to(&self, records: &mut [Record], document: &str) -> usize {
+        let mut flagged = 0;
+        for record in records.iter_mut() {
+            let source = format!("{}\n{}", record.prompt, document);
+            let issues = self.record_issues(record, &source);
+            if issues.is_empty() {
+                record.metadata.remove(ISSUES);
+                continue;
+            }
+            flagged += 1;
+            let issues: Vec<serde_json::Value> = issues.into_iter()
+                .map(|issue| serde_json::json!({ "check": issue.check, "detail": issue.detail }))
+                .collect();
+            record.metadata.insert(ISSUES.to_string(), issues.into());
+        }
+        flagged
+    }
+}
+
+/// Flagged records with their issues, for listing on the command line
+pub fn summary(results: &[ProcessingResult]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (i, record) in results.iter().flat_map(|result| &result.records).enumerate() {
+        let Some(issues) = record.metadata.get(ISSUES).and_then(|v| v.as_array()) else {
+            continue;
+        };
+        let id = record.metadata.get(crate::lineage::RECORD_ID)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("#{}", i + 1));
+        lines.push(format!("{}:", id));
+        for issue in issues {
+            lines.push(format!(
+                "  [{}] {}",
+                issue["check"].as_str().unwrap_or_default(),
+                issue["detail"].as_str().unwrap_or_default()
+            ));
+        }
+    }
+    lines
+}
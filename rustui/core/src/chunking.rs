@@ -0,0 +1,442 @@
+use serde::{Deserialize, Serialize};
+
+/// Average characters per token of the tokenizers in use, for estimates without one
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Where chunk boundaries go
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkStrategy {
+    /// Fixed number of words, `size_words` per chunk
+    #[default]
+    Words,
+    /// Fixed token budget, `size_tokens` per chunk, cut anywhere between words
+    Tokens,
+    /// Whole sentences up to the token budget
+    Sentence,
+    /// Whole paragraphs up to the token budget
+    Paragraph,
+    /// Paragraphs, then lines, then sentences, then words, whichever first fits the budget
+    Recursive,
+}
+
+impl ChunkStrategy {
+    pub const ALL: [ChunkStrategy; 5] = [
+        ChunkStrategy::Words,
+        ChunkStrategy::Tokens,
+        ChunkStrategy::Sentence,
+        ChunkStrategy::Paragraph,
+        ChunkStrategy::Recursive,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChunkStrategy::Words => "words",
+            ChunkStrategy::Tokens => "tokens",
+            ChunkStrategy::Sentence => "sentence",
+            ChunkStrategy::Paragraph => "paragraph",
+            ChunkStrategy::Recursive => "recursive",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let i = Self::ALL.iter().position(|s| s == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+/// Overlapping chunks from the `[chunking]` config section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkSettings {
+    #[serde(default)]
+    pub strategy: ChunkStrategy,
+    /// Words per chunk, excluding context
+    #[serde(default = "default_size_words")]
+    pub size_words: usize,
+    /// Words of neighboring text passed as context on each side
+    #[serde(default = "default_overlap_words")]
+    pub overlap_words: usize,
+    /// Estimated tokens per chunk for all strategies but `words`
+    #[serde(default = "default_size_tokens")]
+    pub size_tokens: usize,
+    /// Estimated tokens of context on each side for all strategies but `words`
+    #[serde(default = "default_overlap_tokens")]
+    pub overlap_tokens: usize,
+}
+
+fn default_size_words() -> usize {
+    800
+}
+
+fn default_overlap_words() -> usize {
+    100
+}
+
+fn default_size_tokens() -> usize {
+    1000
+}
+
+fn default_overlap_tokens() -> usize {
+    120
+}
+
+impl Default for ChunkSettings {
+    fn default() -> Self {
+        Self {
+            strategy: ChunkStrategy::default(),
+            size_words: default_size_words(),
+            overlap_words: default_overlap_words(),
+            size_tokens: default_size_tokens(),
+            overlap_tokens: default_overlap_tokens(),
+        }
+    }
+}
+
+/// Part of a document together with the neighboring text the model sees for context
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub index: usize,
+    /// Position of the chunk's first word among the words of the document
+    pub start: usize,
+    pub before: String,
+    pub text: String,
+    pub after: String,
+}
+
+impl Chunk {
+    /// Prompt with the context windows marked so the model doesn't treat them as content
+    pub fn prompt(&self, instruction: &str) -> String {
+        let mut prompt = format!("{}\n\n", instruction);
+        if !self.before.is_empty() {
+            prompt.push_str(&format!("Preceding context (for continuity only):\n{}\n\n", self.before));
+        }
+        prompt.push_str(&format!("Text:\n{}", self.text));
+        if !self.after.is_empty() {
+            prompt.push_str(&format!("\n\nFollowing context (for continuity only):\n{}", self.after));
+        }
+        prompt
+    }
+}
+
+/// Estimated token count of `text`, see `word_tokens`
+pub fn estimate_tokens(text: &str) -> usize {
+    text.split_whitespace().map(word_tokens).sum()
+}
+
+/// Estimated tokens of a word: one per `CHARS_PER_TOKEN` characters, at least one
+fn word_tokens(word: &str) -> usize {
+    word.chars().count().div_ceil(CHARS_PER_TOKEN).max(1)
+}
+
+/// Splits text into chunks along the boundaries of `settings.strategy`, each with the
+/// neighboring text of up to the overlap as context. Whitespace is normalized.
+pub fn split(text: &str, settings: &ChunkSettings) -> Vec<Chunk> {
+    let levels: &[Boundary] = match settings.strategy {
+        ChunkStrategy::Words => return split_words(text, settings),
+        ChunkStrategy::Tokens => &[],
+        ChunkStrategy::Sentence => &[Boundary::Sentence],
+        ChunkStrategy::Paragraph => &[Boundary::Paragraph],
+        ChunkStrategy::Recursive => &[Boundary::Paragraph, Boundary::Line, Boundary::Sentence],
+    };
+    let document = Document::parse(text);
+    let budget = settings.size_tokens.max(1);
+    let mut pieces = Vec::new();
+    document.pieces(0..document.words.len(), levels, budget, &mut pieces);
+
+    // Neighboring pieces are packed together while they fit the budget
+    let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+    let mut tokens = 0;
+    for piece in pieces {
+        let piece_tokens = document.tokens(piece.clone());
+        match ranges.last_mut() {
+            Some(last) if tokens + piece_tokens <= budget => {
+                last.end = piece.end;
+                tokens += piece_tokens;
+            },
+            _ => {
+                ranges.push(piece);
+                tokens = piece_tokens;
+            },
+        }
+    }
+    ranges.into_iter().enumerate()
+        .map(|(index, range)| {
+            let before_start = document.context_start(range.start, settings.overlap_tokens);
+            let after_end = document.context_end(range.end, settings.overlap_tokens);
+            Chunk {
+                index,
+                start: range.start,
+                before: document.words[before_start..range.start].join(" "),
+                text: document.words[range.clone()].join(" "),
+                after: document.words[range.end..after_end].join(" "),
+            }
+        })
+        .collect()
+}
+
+/// Texts of the chunks of `text`, or the whole text when chunking is off
+pub fn split_text(text: &str, settings: Option<&ChunkSettings>) -> Vec<String> {
+    match settings {
+        Some(settings) => split(text, settings).into_iter().map(|chunk| chunk.text).collect(),
+        None => vec![text.to_string()],
+    }
+}
+
+/// Chunks of `size_words` words, each with up to `overlap_words` words of the previous
+/// and next chunk as context
+fn split_words(text: &str, settings: &ChunkSettings) -> Vec<Chunk> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let size = settings.size_words.max(1);
+    (0..words.len()).step_by(size)
+        .enumerate()
+        .map(|(index, start)| {
+            let end = (start + size).min(words.len());
+            let before_start = start.saturating_sub(settings.overlap_words);
+            let after_end = (end + settings.overlap_words).min(words.len());
+            Chunk {
+                index,
+                start,
+                before: words[before_start..start].join(" "),
+                text: words[start..end].join(" "),
+                after: words[end..after_end].join(" "),
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Boundary {
+    Paragraph,
+    Line,
+    Sentence,
+}
+
+/// Words of a text with the positions where its paragraphs, lines and sentences end
+struct Document<'a> {
+    words: Vec<&'a str>,
+    paragraph_ends: Vec<usize>,
+    line_ends: Vec<usize>,
+    sentence_ends: Vec<usize>,
+}
+
+impl<'a> Document<'a> {
+    fn parse(text: &'a str) -> Self {
+        let mut document = Self { words: Vec::new(), paragraph_ends: Vec::new(), line_ends: Vec::new(), sentence_ends: Vec::new() };
+        for line in text.lines() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.is_empty() {
+                // A blank line closes the paragraph
+                if document.line_ends.last() == Some(&document.words.len()) && document.paragraph_ends.last() != Some(&document.words.len()) {
+                    document.paragraph_ends.push(document.words.len());
+                }
+                continue;
+            }
+            document.words.extend(words);
+            document.line_ends.push(document.words.len());
+        }
+        let len = document.words.len();
+        if document.paragraph_ends.last() != Some(&len) {
+            document.paragraph_ends.push(len);
+        }
+        document.sentence_ends = (1..=len)
+            .filter(|&end| end == len || (ends_sentence(document.words[end - 1]) && starts_sentence(document.words[end])))
+            .collect();
+        document
+    }
+
+    fn ends(&self, boundary: Boundary) -> &[usize] {
+        match boundary {
+            Boundary::Paragraph => &self.paragraph_ends,
+            Boundary::Line => &self.line_ends,
+            Boundary::Sentence => &self.sentence_ends,
+        }
+    }
+
+    fn tokens(&self, range: std::ops::Range<usize>) -> usize {
+        self.words[range].iter().map(|word| word_tokens(word)).sum()
+    }
+
+    /// Cuts `range` into pieces within `budget`: at the first boundary of `levels` that is
+    /// fine enough, or between words when none is
+    fn pieces(&self, range: std::ops::Range<usize>, levels: &[Boundary], budget: usize, out: &mut Vec<std::ops::Range<usize>>) {
+        if range.is_empty() {
+            return;
+        }
+        if self.tokens(range.clone()) <= budget {
+            out.push(range);
+            return;
+        }
+        let Some((&level, finer)) = levels.split_first() else {
+            let mut start = range.start;
+            let mut tokens = 0;
+            for i in range.clone() {
+                let word = word_tokens(self.words[i]);
+                if i > start && tokens + word > budget {
+                    out.push(start..i);
+                    start = i;
+                    tokens = 0;
+                }
+                tokens += word;
+            }
+            out.push(start..range.end);
+            return;
+        };
+        let mut start = range.start;
+        for &end in self.ends(level).iter().filter(|&&end| end > range.start && end < range.end) {
+            self.pieces(start..end, finer, budget, out);
+            start = end;
+        }
+        self.pieces(start..range.end, finer, budget, out);
+    }
+
+    /// First word of the context before `start` that fits `budget` tokens
+    fn context_start(&self, start: usize, budget: usize) -> usize {
+        let mut tokens = 0;
+        let mut first = start;
+        while first > 0 && tokens + word_tokens(self.words[first - 1]) <= budget {
+            first -= 1;
+            tokens += word_tokens(self.words[first]);
+        }
+        first
+    }
+
+    /// End of the context after `end` that fits `budget` tokens
+    fn context_end(&self, end: usize, budget: usize) -> usize {
+        let mut tokens = 0;
+        let mut last = end;
+        while last < self.words.len() && tokens + word_tokens(self.words[last]) <= budget {
+            tokens += word_tokens(self.words[last]);
+            last += 1;
+        }
+        last
+    }
+}
+
+fn ends_sentence(word: &str) -> bool {
+    word.trim_end_matches(['"', '\'', ')', ']', '»', '”', '’']).ends_with(['.', '!', '?', '…'])
+}
+
+/// Abbreviations like "e.g." are followed by lowercase words, sentences aren't
+fn starts_sentence(word: &str) -> bool {
+    word.trim_start_matches(['"', '\'', '(', '[', '«', '„', '“', '‘', '-', '—'])
+        .chars().next()
+        .is_some_and(|c| c.is_uppercase() || c.is_numeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunking::{split, split_text, ChunkSettings, ChunkStrategy};
+    use crate::processors::{test_config, BatchProcessor, Processor, ProcessorConfig, StandardProcessor};
+
+    #[test]
+    fn test_split_with_context_windows() {
+        let text = "one two three four five six seven eight nine ten";
+        let chunks = split(text, &ChunkSettings { size_words: 4, overlap_words: 2, ..Default::default() });
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].before, "");
+        assert_eq!(chunks[1].before, "three four");
+        assert_eq!(chunks[1].text, "five six seven eight");
+        assert_eq!(chunks[1].after, "nine ten");
+        assert_eq!(chunks[2].after, "");
+
+        let prompt = chunks[1].prompt("Summarize");
+        assert!(prompt.contains("Preceding context (for continuity only):\nthree four"));
+        assert!(prompt.contains("Text:\nfive six seven eight"));
+    }
+
+    fn settings(strategy: ChunkStrategy, size_tokens: usize, overlap_tokens: usize) -> ChunkSettings {
+        ChunkSettings { strategy, size_tokens, overlap_tokens, ..Default::default() }
+    }
+
+    fn texts(text: &str, settings: &ChunkSettings) -> Vec<String> {
+        split(text, settings).into_iter().map(|chunk| chunk.text).collect()
+    }
+
+    #[test]
+    fn test_token_windows() {
+        // Słowa do 4 znaków to jeden token, dłuższe – jeden na każde 4 znaki
+        let chunks = split("a b c d e f g h i j", &settings(ChunkStrategy::Tokens, 4, 2));
+        assert_eq!(chunks.iter().map(|c| c.text.as_str()).collect::<Vec<_>>(), vec!["a b c d", "e f g h", "i j"]);
+        assert_eq!(chunks[1].start, 4);
+        assert_eq!(chunks[1].before, "c d");
+        assert_eq!(chunks[1].after, "i j");
+        assert_eq!(texts("abcdefghi jk lm", &settings(ChunkStrategy::Tokens, 3, 0)), vec!["abcdefghi", "jk lm"]);
+        // Słowo dłuższe niż cały fragment i tak trafia do jednego fragmentu
+        assert_eq!(texts("abcdefghijklmnopqrst x", &settings(ChunkStrategy::Tokens, 2, 0)), vec!["abcdefghijklmnopqrst", "x"]);
+        assert!(split("", &settings(ChunkStrategy::Tokens, 4, 2)).is_empty());
+    }
+
+    #[test]
+    fn test_sentence_and_paragraph_strategies() {
+        let text = "A b c. D e f g. H i.";
+        assert_eq!(texts(text, &settings(ChunkStrategy::Sentence, 5, 0)), vec!["A b c.", "D e f g.", "H i."]);
+        assert_eq!(texts(text, &settings(ChunkStrategy::Sentence, 7, 0)), vec!["A b c. D e f g.", "H i."]);
+        // Skrót przed małą literą nie kończy zdania
+        assert_eq!(texts("See e.g. the end. Then go.", &settings(ChunkStrategy::Sentence, 5, 0)), vec!["See e.g. the end.", "Then go."]);
+
+        let text = "a b c\nd e\n\n\nf g h\n\ni j";
+        assert_eq!(texts(text, &settings(ChunkStrategy::Paragraph, 6, 0)), vec!["a b c d e", "f g h i j"]);
+        assert_eq!(texts(text, &settings(ChunkStrategy::Paragraph, 4, 0)), vec!["a b c d", "e f g h", "i j"]);
+        // Kontekst nie przekracza budżetu tokenów
+        let chunks = split(text, &settings(ChunkStrategy::Paragraph, 6, 3));
+        assert_eq!(chunks[1].before, "c d e");
+        assert_eq!(chunks[0].after, "f g h");
+    }
+
+    #[test]
+    fn test_recursive_strategy() -> anyhow::Result<()> {
+        let text = "Tytuł\n\nPierwsze zdanie. Drugie zdanie tutaj.\nNowa linia\n\nKoniec";
+        // Akapit mieszczący się w budżecie zostaje w całości
+        assert_eq!(texts(text, &settings(ChunkStrategy::Recursive, 100, 0)).len(), 1);
+        // Za duży akapit dzielony jest na linie, a za długa linia na zdania
+        assert_eq!(texts(text, &settings(ChunkStrategy::Recursive, 8, 0)), vec![
+            "Tytuł Pierwsze zdanie.",
+            "Drugie zdanie tutaj.",
+            "Nowa linia Koniec",
+        ]);
+
+        let parsed: ChunkSettings = toml::from_str("strategy = \"recursive\"\nsize_tokens = 500")?;
+        assert_eq!(parsed.strategy, ChunkStrategy::Recursive);
+        assert_eq!(parsed.overlap_tokens, 120);
+        assert_eq!(parsed.size_words, 800);
+        assert_eq!(toml::from_str::<ChunkSettings>("size_words = 5").unwrap().strategy, ChunkStrategy::Words);
+
+        assert_eq!(split_text("a  b\nc", None), vec!["a  b\nc"]);
+        assert_eq!(split_text("a  b\nc", Some(&settings(ChunkStrategy::Tokens, 2, 0))), vec!["a b", "c"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_chunked_processing_makes_a_record_per_chunk() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("doc.txt");
+        let text: Vec<String> = (1..=50).map(|i| format!("word{}", i)).collect();
+        std::fs::write(&path, text.join("\n"))?;
+
+        let config = ProcessorConfig {
+            chunking: Some(ChunkSettings { size_words: 12, overlap_words: 4, ..Default::default() }),
+            ..test_config()
+        };
+        let file = path.to_str().unwrap();
+        let result = StandardProcessor.process_file(file, &config)?;
+
+        assert_eq!(result.exchanges.len(), 5);
+        assert!(result.exchanges[1].prompt.contains("Following context"));
+        // Każdy fragment to osobny rekord wskazujący plik źródłowy
+        assert_eq!(result.records.len(), 5);
+        assert_eq!(result.stats.total_records, 5);
+        for (i, record) in result.records.iter().enumerate() {
+            assert_eq!(record.prompt, text[i * 12..(i * 12 + 12).min(50)].join(" "));
+            assert!(record.completion.contains(&record.prompt));
+            assert_eq!(record.metadata["chunk"], i);
+            assert_eq!(record.metadata["chunk_count"], 5);
+            assert_eq!(record.metadata["source_file"], file);
+        }
+
+        // Procesor wsadowy dzieli pliki tak samo
+        let batch = BatchProcessor.process_file(file, &config)?;
+        assert_eq!(batch.records.len(), 5);
+        assert_eq!(batch.records[4].prompt, text[48..].join(" "));
+        Ok(())
+    }
+}
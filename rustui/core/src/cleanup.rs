@@ -0,0 +1,341 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Noise filters from the `[cleanup]` config section, applied before chunking
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CleanupSettings {
+    /// Page headers/footers: lines repeated at the top or bottom of pages (split on form feeds)
+    #[serde(default = "default_true")]
+    pub repeated_lines: bool,
+    /// Joins words hyphenated across line breaks
+    #[serde(default = "default_true")]
+    pub hyphenation: bool,
+    /// Collapses runs of spaces and blank lines
+    #[serde(default = "default_true")]
+    pub whitespace: bool,
+    /// Drops `<nav>`, `<header>`, `<footer>`, `<aside>`, `<script>` and `<style>` elements in HTML
+    #[serde(default = "default_true")]
+    pub html_boilerplate: bool,
+    /// Share of pages a line must appear on to count as a header or footer
+    #[serde(default = "default_min_page_share")]
+    pub min_page_share: f64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_min_page_share() -> f64 {
+    0.5
+}
+
+impl Default for CleanupSettings {
+    fn default() -> Self {
+        Self {
+            repeated_lines: true,
+            hyphenation: true,
+            whitespace: true,
+            html_boilerplate: true,
+            min_page_share: default_min_page_share(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Filter {
+    HtmlBoilerplate,
+    RepeatedLines,
+    Hyphenation,
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::HtmlBoilerplate => write!(f, "HTML navigation/footer"),
+            Filter::RepeatedLines => write!(f, "Page headers/footers"),
+            Filter::Hyphenation => write!(f, "Hyphenation repair"),
+        }
+    }
+}
+
+/// Text a filter took out
+#[derive(Debug, Clone, PartialEq)]
+pub struct Removal {
+    pub filter: Filter,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cleaned {
+    pub text: String,
+    pub removed: Vec<Removal>,
+    /// Whitespace characters dropped by collapsing, not listed in `removed`
+    pub whitespace_chars: usize,
+}
+
+/// Applies the enabled filters in order: HTML boilerplate, page headers/footers,
+/// hyphenation, whitespace
+pub fn clean(text: &str, settings: &CleanupSettings) -> Cleaned {
+    let mut removed = Vec::new();
+    let mut text = text.to_string();
+    if settings.html_boilerplate {
+        text = strip_html_boilerplate(&text, &mut removed);
+    }
+    if settings.repeated_lines {
+        text = strip_repeated_lines(&text, settings.min_page_share, &mut removed);
+    }
+    if settings.hyphenation {
+        text = repair_hyphenation(&text, &mut removed);
+    }
+    let mut whitespace_chars = 0;
+    if settings.whitespace {
+        let collapsed = collapse_whitespace(&text);
+        whitespace_chars = text.chars().count() - collapsed.chars().count();
+        text = collapsed;
+    }
+    Cleaned { text, removed, whitespace_chars }
+}
+
+const BOILERPLATE_ELEMENTS: [&str; 6] = ["nav", "header", "footer", "aside", "script", "style"];
+
+fn strip_html_boilerplate(text: &str, removed: &mut Vec<Removal>) -> String {
+    let mut text = text.to_string();
+    for element in BOILERPLATE_ELEMENTS {
+        while let Some((start, end)) = find_element(&text, element) {
+            removed.push(Removal { filter: Filter::HtmlBoilerplate, text: text[start..end].to_string() });
+            text.replace_range(start..end, "");
+        }
+    }
+    text
+}
+
+/// Byte range of the first `<element ...>...</element>`, including nested elements of the same name
+fn find_element(text: &str, element: &str) -> Option<(usize, usize)> {
+    let lower = text.to_ascii_lowercase();
+    let open = format!("<{}", element);
+    let close = format!("</{}>", element);
+    let is_open_at = |i: usize| {
+        lower[i..].starts_with(&open)
+            && lower[i + open.len()..].starts_with(|c: char| c == '>' || c.is_whitespace())
+    };
+    let start = lower.match_indices(&open).map(|(i, _)| i).find(|&i| is_open_at(i))?;
+
+    let mut depth = 0;
+    let mut pos = start;
+    while pos < lower.len() {
+        let next_open = lower[pos..].match_indices(&open).map(|(i, _)| pos + i).find(|&i| is_open_at(i));
+        let next_close = lower[pos..].find(&close).map(|i| pos + i);
+        match (next_open, next_close) {
+            (Some(o), Some(c)) if o < c => {
+                depth += 1;
+                pos = o + open.len();
+            },
+            (_, Some(c)) => {
+                depth -= 1;
+                pos = c + close.len();
+                if depth == 0 {
+                    return Some((start, pos));
+                }
+            },
+            // Unclosed element: leave it alone
+            (_, None) => return None,
+        }
+    }
+    None
+}
+
+/// Lines at page edges checked for repetition
+const EDGE_LINES: usize = 3;
+
+fn strip_repeated_lines(text: &str, min_page_share: f64, removed: &mut Vec<Removal>) -> String {
+    let pages: Vec<&str> = text.split('\u{c}').collect();
+    if pages.len() < 3 {
+        return text.to_string();
+    }
+
+    // Page numbers differ from page to page, so digits are ignored when comparing
+    let normalize = |line: &str| -> String {
+        line.trim().chars().map(|c| if c.is_ascii_digit() { '#' } else { c }).collect()
+    };
+    let edges = |page: &str| -> Vec<(usize, String)> {
+        let lines: Vec<(usize, &str)> = page.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()).collect();
+        let mut edge: Vec<usize> = lines.iter().take(EDGE_LINES).map(|(i, _)| *i).collect();
+        edge.extend(lines.iter().rev().take(EDGE_LINES).map(|(i, _)| *i));
+        edge.sort_unstable();
+        edge.dedup();
+        let all: Vec<&str> = page.lines().collect();
+        edge.into_iter().map(|i| (i, normalize(all[i]))).collect()
+    };
+
+    let mut pages_with_line: HashMap<String, usize> = HashMap::new();
+    for page in &pages {
+        let mut seen: Vec<String> = edges(page).into_iter().map(|(_, l)| l).collect();
+        seen.sort();
+        seen.dedup();
+        for line in seen {
+            *pages_with_line.entry(line).or_default() += 1;
+        }
+    }
+    let threshold = ((pages.len() as f64 * min_page_share).ceil() as usize).max(2);
+
+    let cleaned: Vec<String> = pages.iter()
+        .map(|page| {
+            let drop: Vec<usize> = edges(page).into_iter()
+                .filter(|(_, line)| pages_with_line[line] >= threshold)
+                .map(|(i, _)| i)
+                .collect();
+            page.lines().enumerate()
+                .filter(|(i, line)| {
+                    let keep = !drop.contains(i);
+                    if !keep {
+                        removed.push(Removal { filter: Filter::RepeatedLines, text: line.trim().to_string() });
+                    }
+                    keep
+                })
+                .map(|(_, line)| line)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect();
+    cleaned.join("\n\n")
+}
+
+/// `exam-\nple` becomes `example` when the word continues in lowercase on the next line.
+/// The joined word stays on the first line.
+fn repair_hyphenation(text: &str, removed: &mut Vec<Removal>) -> String {
+    let mut lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+    let mut i = 0;
+    while i + 1 < lines.len() {
+        let trimmed = lines[i].trim_end();
+        let word_start = trimmed.rfind(char::is_whitespace).map(|p| p + 1).unwrap_or(0);
+        let head = trimmed[word_start..].strip_suffix('-')
+            .filter(|w| w.chars().count() > 1 && w.chars().all(char::is_alphabetic));
+        let next = lines[i + 1].trim_start();
+        let tail = &next[..next.find(char::is_whitespace).unwrap_or(next.len())];
+        let Some(head) = head.filter(|_| tail.starts_with(char::is_lowercase)) else {
+            i += 1;
+            continue;
+        };
+
+        removed.push(Removal { filter: Filter::Hyphenation, text: format!("{}-⏎{}", head, tail) });
+        let joined = format!("{}{}{}", &trimmed[..word_start], head, tail);
+        let rest = next[tail.len()..].trim_start().to_string();
+        lines[i] = joined;
+        if rest.is_empty() {
+            lines.remove(i + 1);
+        } else {
+            lines[i + 1] = rest;
+        }
+    }
+    lines.join("\n")
+}
+
+/// Single spaces within lines, no trailing spaces and at most one blank line in a row
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = Vec::new();
+    let mut blank = false;
+    for line in text.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() {
+            if !blank && !out.is_empty() {
+                out.push(line);
+            }
+            blank = true;
+        } else {
+            out.push(line);
+            blank = false;
+        }
+    }
+    while out.last().is_some_and(|l: &String| l.is_empty()) {
+        out.pop();
+    }
+    out.join("\n")
+}
+
+/// Removed content grouped by filter, most frequent first, for previewing the filters
+pub fn preview(cleaned: &Cleaned) -> Vec<String> {
+    let mut groups: Vec<(Filter, Vec<(String, usize)>)> = Vec::new();
+    for removal in &cleaned.removed {
+        let index = match groups.iter().position(|(f, _)| *f == removal.filter) {
+            Some(index) => index,
+            None => {
+                groups.push((removal.filter, Vec::new()));
+                groups.len() - 1
+            },
+        };
+        let entries = &mut groups[index].1;
+        match entries.iter_mut().find(|(text, _)| *text == removal.text) {
+            Some((_, count)) => *count += 1,
+            None => entries.push((removal.text.clone(), 1)),
+        }
+    }
+
+    let mut lines = Vec::new();
+    groups.sort_by_key(|(filter, _)| *filter);
+    for (filter, mut entries) in groups {
+        let total: usize = entries.iter().map(|(_, n)| n).sum();
+        lines.push(format!("{} ({} removed):", filter, total));
+        entries.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        for (text, count) in entries {
+            let text: String = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            let short: String = text.chars().take(80).collect();
+            let ellipsis = if text.chars().count() > 80 { "…" } else { "" };
+            let times = if count > 1 { format!(" ×{}", count) } else { String::new() };
+            lines.push(format!("  - {}{}{}", short, ellipsis, times));
+        }
+    }
+    if cleaned.whitespace_chars > 0 {
+        lines.push(format!("Whitespace: {} characters collapsed", cleaned.whitespace_chars));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cleanup::{clean, preview, CleanupSettings, Filter};
+
+    fn only(filter: Filter) -> CleanupSettings {
+        CleanupSettings {
+            repeated_lines: filter == Filter::RepeatedLines,
+            hyphenation: filter == Filter::Hyphenation,
+            whitespace: false,
+            html_boilerplate: filter == Filter::HtmlBoilerplate,
+            ..CleanupSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_strip_page_headers_and_footers() {
+        let body = ["Revenue grew.", "Costs fell.", "Hiring paused.", "Outlook is stable."];
+        let pages: Vec<String> = body.iter().enumerate()
+            .map(|(i, text)| format!("ACME Corp - Annual Report\n{}\nPage {} of 4", text, i + 1))
+            .collect();
+        let cleaned = clean(&pages.join("\u{c}"), &only(Filter::RepeatedLines));
+
+        assert!(!cleaned.text.contains("ACME Corp"));
+        assert!(!cleaned.text.contains("of 4"));
+        assert!(cleaned.text.contains("Hiring paused."));
+        assert_eq!(cleaned.removed.len(), 8);
+    }
+
+    #[test]
+    fn test_repair_hyphenation() {
+        let cleaned = clean("The exam-\nple shows a well-\nknown case.\nKeep Anglo-\nSaxon as is.", &only(Filter::Hyphenation));
+        assert_eq!(cleaned.text, "The example\nshows a wellknown\ncase.\nKeep Anglo-\nSaxon as is.");
+        assert_eq!(cleaned.removed[0].text, "exam-⏎ple");
+    }
+
+    #[test]
+    fn test_html_boilerplate_and_whitespace() {
+        let html = "<body><NAV class=\"top\"><ul><li>Home</li></ul></nav>\n<p>Body   text</p>\n\n\n\n<footer>© 2024 <footer>inner</footer> ACME</footer></body>";
+        let cleaned = clean(html, &CleanupSettings::default());
+        assert_eq!(cleaned.text, "<body>\n<p>Body text</p>\n\n</body>");
+        assert_eq!(cleaned.removed.len(), 2);
+        assert!(cleaned.whitespace_chars > 0);
+
+        let preview = preview(&cleaned);
+        assert_eq!(preview[0], "HTML navigation/footer (2 removed):");
+        assert!(preview.last().unwrap().starts_with("Whitespace:"));
+    }
+}
@@ -0,0 +1,230 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Routing of low-confidence OCR/transcription chunks from the `[confidence]` config section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceSettings {
+    /// Chunks with a mean confidence below this (0–1) are routed
+    #[serde(default = "default_threshold")]
+    pub threshold: f64,
+    #[serde(default)]
+    pub low_confidence: LowConfidence,
+}
+
+fn default_threshold() -> f64 {
+    0.6
+}
+
+impl Default for ConfidenceSettings {
+    fn default() -> Self {
+        Self { threshold: default_threshold(), low_confidence: LowConfidence::default() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LowConfidence {
+    /// Let the model fix recognition errors before the chunk is used
+    #[default]
+    Cleanup,
+    /// Leave the chunk out of generation and list it for review
+    Flag,
+}
+
+/// Recognized text with a confidence per segment (OCR line or transcription segment)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub text: String,
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    pub segments: Vec<Segment>,
+}
+
+impl Transcript {
+    pub fn text(&self) -> String {
+        self.segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join("\n")
+    }
+
+    /// Confidence of every word of `text()`, in order
+    pub fn word_confidences(&self) -> Vec<f64> {
+        self.segments.iter()
+            .flat_map(|s| std::iter::repeat_n(s.confidence, s.text.split_whitespace().count()))
+            .collect()
+    }
+}
+
+/// Mean confidence of `len` words starting at word `start`
+pub fn mean(word_confidences: &[f64], start: usize, len: usize) -> Option<f64> {
+    let words = word_confidences.get(start..(start + len).min(word_confidences.len()))?;
+    if words.is_empty() {
+        return None;
+    }
+    Some(words.iter().sum::<f64>() / words.len() as f64)
+}
+
+/// Prompt for the cleanup pass over a low-confidence chunk
+pub fn cleanup_prompt(text: &str) -> String {
+    format!(
+        "The following text was produced by OCR or speech recognition and contains recognition errors. \
+         Correct misrecognized words, spacing and punctuation without adding or removing content. \
+         Reply with the corrected text only.\n\nText:\n{}",
+        text
+    )
+}
+
+/// Reads Tesseract TSV output or Whisper-style JSON (`segments` with `text` and either
+/// `confidence` or `avg_logprob`). Other files aren't transcripts.
+pub fn load(path: &Path) -> Result<Option<Transcript>> {
+    let extension = path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "tsv" => {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            Ok(parse_tesseract_tsv(&content))
+        },
+        "json" => {
+            let content = std::fs::read(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            // Any other JSON (e.g. a dataset from an earlier stage) is not a transcript
+            Ok(serde_json::from_slice(&content).ok().and_then(|value| parse_segments_json(&value)))
+        },
+        _ => Ok(None),
+    }
+}
+
+/// Words grouped into lines; confidence is the mean word confidence of the line
+fn parse_tesseract_tsv(content: &str) -> Option<Transcript> {
+    let mut lines = content.lines();
+    let header: Vec<&str> = lines.next()?.split('\t').collect();
+    let column = |name: &str| header.iter().position(|h| *h == name);
+    let (conf, text) = (column("conf")?, column("text")?);
+    let keys: Vec<usize> = ["page_num", "block_num", "par_num", "line_num"].iter()
+        .map(|name| column(name))
+        .collect::<Option<_>>()?;
+
+    let mut grouped: BTreeMap<Vec<u32>, (Vec<String>, Vec<f64>)> = BTreeMap::new();
+    for row in lines {
+        let fields: Vec<&str> = row.split('\t').collect();
+        let (Some(word), Some(word_conf)) = (fields.get(text), fields.get(conf).and_then(|c| c.parse::<f64>().ok())) else {
+            continue;
+        };
+        // Non-word rows (blocks, lines) have a confidence of -1
+        if word.trim().is_empty() || word_conf < 0.0 {
+            continue;
+        }
+        let key: Vec<u32> = keys.iter()
+            .map(|&k| fields.get(k).and_then(|v| v.parse().ok()).unwrap_or(0))
+            .collect();
+        let (words, confs) = grouped.entry(key).or_default();
+        words.push(word.trim().to_string());
+        confs.push((word_conf / 100.0).clamp(0.0, 1.0));
+    }
+
+    let segments: Vec<Segment> = grouped.into_values()
+        .map(|(words, confs)| Segment {
+            text: words.join(" "),
+            confidence: confs.iter().sum::<f64>() / confs.len() as f64,
+        })
+        .collect();
+    (!segments.is_empty()).then_some(Transcript { segments })
+}
+
+fn parse_segments_json(value: &serde_json::Value) -> Option<Transcript> {
+    let segments: Vec<Segment> = value.get("segments")?.as_array()?.iter()
+        .map(|segment| {
+            let text = segment.get("text")?.as_str()?.trim().to_string();
+            let confidence = match (segment.get("confidence"), segment.get("avg_logprob")) {
+                (Some(c), _) => c.as_f64()?,
+                (None, Some(logprob)) => logprob.as_f64()?.exp(),
+                // Without a score the segment is trusted
+                (None, None) => 1.0,
+            };
+            Some(Segment { text, confidence: confidence.clamp(0.0, 1.0) })
+        })
+        .collect::<Option<_>>()?;
+    (!segments.is_empty()).then_some(Transcript { segments })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunking::ChunkSettings;
+    use crate::confidence::{load, mean, ConfidenceSettings, LowConfidence};
+    use crate::processors::{test_config, Processor, ProcessorConfig, StandardProcessor};
+
+    const TSV: &str = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+            4\t1\t1\t1\t1\t0\t0\t0\t0\t0\t-1\t\n\
+            5\t1\t1\t1\t1\t1\t0\t0\t0\t0\t96\tClear\n\
+            5\t1\t1\t1\t1\t2\t0\t0\t0\t0\t94\tline\n\
+            5\t1\t1\t1\t2\t1\t0\t0\t0\t0\t20\tSm@dged\n\
+            5\t1\t1\t1\t2\t2\t0\t0\t0\t0\t30\tw0rds\n";
+
+    fn config(low_confidence: LowConfidence) -> ProcessorConfig {
+        ProcessorConfig {
+            chunking: Some(ChunkSettings { size_words: 2, overlap_words: 0, ..Default::default() }),
+            confidence: Some(ConfidenceSettings { threshold: 0.6, low_confidence }),
+            ..test_config()
+        }
+    }
+
+    #[test]
+    fn test_load_tesseract_tsv() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("scan.tsv");
+        std::fs::write(&path, TSV)?;
+
+        let transcript = load(&path)?.unwrap();
+        assert_eq!(transcript.text(), "Clear line\nSm@dged w0rds");
+        assert_eq!(transcript.segments[0].confidence, 0.95);
+        assert_eq!(mean(&transcript.word_confidences(), 2, 2), Some(0.25));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_transcription_json() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("talk.json");
+        std::fs::write(&path, r#"{"segments":[{"text":" Hello there","avg_logprob":0.0},{"text":"mumble","confidence":0.3}]}"#)?;
+        let transcript = load(&path)?.unwrap();
+        assert_eq!(transcript.segments[0].confidence, 1.0);
+        assert_eq!(transcript.segments[1].confidence, 0.3);
+
+        // Zbiór danych z poprzedniego etapu to nie transkrypcja
+        std::fs::write(&path, "[]")?;
+        assert!(load(&path)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_low_confidence_chunks_are_routed() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("scan.tsv");
+        std::fs::write(&path, TSV)?;
+        let file = path.to_string_lossy();
+
+        let result = StandardProcessor.process_file(&file, &config(LowConfidence::Flag))?;
+        assert_eq!(result.records[0].completion, "Clear line");
+        assert!(!result.records[0].metadata.contains_key("needs_review"));
+        // Słaby fragment nie trafia do modelu, czeka na przegląd
+        let flagged = &result.records[1];
+        assert_eq!(result.exchanges.len(), 1);
+        assert_eq!(flagged.prompt, "Sm@dged w0rds");
+        assert!(flagged.completion.is_empty());
+        assert_eq!(flagged.metadata["needs_review"], true);
+        assert_eq!(flagged.metadata["confidence"], 0.25);
+
+        // Oczyszczanie: dodatkowe wywołanie modelu dla słabego fragmentu
+        let result = StandardProcessor.process_file(&file, &config(LowConfidence::Cleanup))?;
+        assert_eq!(result.exchanges.len(), 3);
+        assert!(!result.records[0].metadata.contains_key("cleaned"));
+        assert_eq!(result.records[1].metadata["cleaned"], true);
+        assert!(result.records[1].completion.contains("Sm@dged"));
+        Ok(())
+    }
+}
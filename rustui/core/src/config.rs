@@ -0,0 +1,601 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+
+use crate::archive::ExtractLimits;
+use crate::atomic;
+use crate::audit::AuditSettings;
+use crate::checks::CheckSettings;
+use crate::chunking::ChunkSettings;
+use crate::cleanup::CleanupSettings;
+use crate::confidence::ConfidenceSettings;
+use crate::docmeta::MetadataSettings;
+use crate::figures::FigureSettings;
+use crate::paper::PaperSettings;
+use crate::qa::QaSettings;
+use crate::bestof::BestOfSettings;
+use crate::catalog::ModelCatalogSettings;
+use crate::chaos::ChaosSettings;
+use crate::voting::VoteSettings;
+use crate::extract::{ExtractSettings, RecordSchema};
+use crate::git::GitSettings;
+use crate::hooks::PostDownloadAction;
+use crate::network::{self, HttpSettings};
+use crate::notifications::NotificationSettings;
+use crate::ollama::OllamaSettings;
+use crate::ranged::DownloadSettings;
+use crate::theme::ThemeSettings;
+use crate::transcript::TranscriptSettings;
+use crate::vault::EncryptionSettings;
+use crate::signing::SigningSettings;
+use crate::synthetic::SyntheticSettings;
+use crate::lock::FileLock;
+use crate::log_warn;
+use crate::output::OutputSettings;
+use crate::preset::Preset;
+use crate::providers::{self, CustomProvider};
+use crate::preset_sync::PresetSyncSettings;
+use crate::preflight::PreflightSettings;
+use crate::preview::PreviewSettings;
+use crate::quota::ProviderQuota;
+use crate::retry::RetryPolicy;
+use crate::storage::RetentionSettings;
+use crate::summarize::MapReduceSettings;
+use crate::pubsub::BrokerSettings;
+use crate::webhooks::WebhookSettings;
+use crate::workspace::{WorkspaceSettings, DEFAULT_WORKSPACE};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    pub backend_url: String,
+    /// Backends equivalent to `backend_url`, e.g. replicas of the same deployment
+    #[serde(default)]
+    pub backend_mirrors: Vec<String>,
+    /// Ping `backend_url` and its mirrors at startup and use the fastest healthy one for the session
+    #[serde(default)]
+    pub pick_fastest_backend: bool,
+    /// Connection pool and timeouts of the HTTP client shared by the backend, model and webhook requests
+    #[serde(default)]
+    pub http: HttpSettings,
+    /// User-Agent sent to the backend; some gateways only let known clients through
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Extra headers of every backend request, e.g. `X-Org-Id`; `env:NAME` values are read
+    /// from the environment
+    #[serde(default)]
+    pub request_headers: BTreeMap<String, String>,
+    /// Parallel ranged downloads of result files
+    #[serde(default)]
+    pub download: DownloadSettings,
+    /// Artificial latency, errors and dropped connections for trying out retries
+    #[serde(default)]
+    pub chaos: ChaosSettings,
+    /// Resubmitting backend jobs that fail on transient errors
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Color palette of the UI
+    #[serde(default)]
+    pub theme: ThemeSettings,
+    /// Start in the remote-shell rendering mode: fewer redraws and a slower tick while idle,
+    /// for SSH/mosh links with high latency
+    #[serde(default)]
+    pub remote_shell: bool,
+    /// Desktop notifications and the terminal bell when a job finishes
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    pub default_provider: String,
+    pub default_model: String,
+    /// Refresh of the provider model lists and replacements for deprecated models
+    #[serde(default)]
+    pub model_catalog: ModelCatalogSettings,
+    /// Local models served by Ollama, listed under the "ollama" provider
+    #[serde(default)]
+    pub ollama: OllamaSettings,
+    /// OpenAI-compatible servers (LM Studio, vLLM, …) by provider name
+    #[serde(default)]
+    pub providers: BTreeMap<String, CustomProvider>,
+    pub default_language: String,
+    pub default_processing_type: String,
+    pub downloads_directory: Option<PathBuf>,
+    pub max_upload_size_mb: u64,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default = "default_true")]
+    pub check_for_updates: bool,
+    #[serde(default)]
+    pub tutorial_completed: bool,
+    #[serde(default)]
+    pub output: OutputSettings,
+    /// Post-download actions per preset; the "default" entry applies to presets without their own list
+    #[serde(default)]
+    pub post_download: HashMap<String, Vec<PostDownloadAction>>,
+    /// Unpack zip and tar.gz results into a directory named after the job
+    #[serde(default)]
+    pub extract_archives: bool,
+    /// Caps on what unpacking one archive may write
+    #[serde(default)]
+    pub extract_limits: ExtractLimits,
+    /// How long downloaded results and caches are kept
+    #[serde(default)]
+    pub retention: RetentionSettings,
+    #[serde(default)]
+    pub git: Option<GitSettings>,
+    #[serde(default)]
+    pub webhooks: Vec<WebhookSettings>,
+    #[serde(default)]
+    pub event_broker: Option<BrokerSettings>,
+    /// Warn before writing when free space would drop below this
+    #[serde(default = "default_min_free_space_mb")]
+    pub min_free_space_mb: u64,
+    /// Active workspace; `None` is the default workspace
+    #[serde(default)]
+    pub workspace: Option<String>,
+    #[serde(default)]
+    pub workspaces: BTreeMap<String, WorkspaceSettings>,
+    /// Local presets of the default workspace; they override shared presets with the same name.
+    /// Other workspaces keep theirs in `[workspaces.<name>.presets]`, see `active_presets`.
+    #[serde(default)]
+    pub presets: BTreeMap<String, Preset>,
+    /// Local prompt templates; they override shared templates with the same name
+    #[serde(default)]
+    pub templates: BTreeMap<String, String>,
+    /// Values of `{{variables}}` in prompt templates, e.g. `clinic_name`; a workspace's own
+    /// `variables` override them
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    #[serde(default)]
+    pub preset_sync: Option<PresetSyncSettings>,
+    /// Hard usage limits per provider; new jobs are refused once one is reached
+    #[serde(default)]
+    pub quotas: BTreeMap<String, ProviderQuota>,
+    /// Spend cap preselected for new jobs
+    #[serde(default)]
+    pub job_spend_cap_usd: Option<f64>,
+    /// USD per 1000 tokens by provider, used to track the cost of local runs
+    #[serde(default)]
+    pub token_prices: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub audit: AuditSettings,
+    /// Redacted provider requests and responses kept in memory for the Debug screen
+    #[serde(default)]
+    pub transcript: TranscriptSettings,
+    /// Encryption of downloaded results and local run outputs at rest
+    #[serde(default)]
+    pub encryption: EncryptionSettings,
+    /// ed25519 signatures of downloaded and exported datasets
+    #[serde(default)]
+    pub signing: SigningSettings,
+    /// Corpus check before jobs are submitted
+    #[serde(default)]
+    pub preflight: PreflightSettings,
+    /// Dry run of the pipeline on a few sampled chunks
+    #[serde(default)]
+    pub preview: PreviewSettings,
+    /// Overlapping chunks for local runs of summarization/QA-style processing
+    #[serde(default)]
+    pub chunking: Option<ChunkSettings>,
+    /// Boilerplate and noise filters for local runs
+    #[serde(default)]
+    pub cleanup: Option<CleanupSettings>,
+    /// What to do with OCR/transcription chunks the recognizer wasn't sure about
+    #[serde(default)]
+    pub confidence: Option<ConfidenceSettings>,
+    /// Vision-model captions for images in documents processed locally
+    #[serde(default)]
+    pub figures: Option<FigureSettings>,
+    /// Front-matter and document properties merged into record metadata
+    #[serde(default)]
+    pub metadata: MetadataSettings,
+    #[serde(default)]
+    pub summarize: MapReduceSettings,
+    #[serde(default)]
+    pub paper: PaperSettings,
+    /// Question-answer generation with quoted support
+    #[serde(default)]
+    pub qa: QaSettings,
+    /// Records generated from a list of topics, without input files
+    #[serde(default)]
+    pub synthetic: SyntheticSettings,
+    /// Candidates per chunk and how the best one is picked, for chunked local runs
+    #[serde(default)]
+    pub best_of: Option<BestOfSettings>,
+    /// Self-consistency voting for chunked local runs; takes precedence over `best_of`
+    #[serde(default)]
+    pub voting: Option<VoteSettings>,
+    /// User-defined record schemas for structured extraction
+    #[serde(default)]
+    pub schemas: BTreeMap<String, RecordSchema>,
+    #[serde(default)]
+    pub extract: ExtractSettings,
+    /// Verification passes over records generated locally
+    #[serde(default)]
+    pub checks: CheckSettings,
+    /// Command-line settings of this run; they never reach config.toml
+    #[serde(skip)]
+    pub overrides: SessionOverrides,
+    /// config.toml this was loaded from; `None` is the one in `get_config_dir`
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    /// config.toml as last read or written by this copy; `save` keeps only what changed since
+    #[serde(skip)]
+    pub saved: Option<toml::Table>,
+}
+
+/// Settings given on the command line for one run, on top of config.toml
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionOverrides {
+    /// `--chaos`: chaos mode on, whatever `[chaos]` says
+    pub chaos: bool,
+    /// `--remote-shell`: start in the remote-shell rendering mode
+    pub remote_shell: bool,
+    /// `--workspace`: workspace of this run; `workspace` stays the remembered one
+    pub workspace: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_min_free_space_mb() -> u64 {
+    500
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            backend_url: "http://localhost:8000".to_string(),
+            backend_mirrors: Vec::new(),
+            pick_fastest_backend: false,
+            http: HttpSettings::default(),
+            user_agent: None,
+            request_headers: BTreeMap::new(),
+            download: DownloadSettings::default(),
+            chaos: ChaosSettings::default(),
+            retry: RetryPolicy::default(),
+            theme: ThemeSettings::default(),
+            remote_shell: false,
+            notifications: NotificationSettings::default(),
+            default_provider: "openai".to_string(),
+            default_model: "gpt-4-turbo".to_string(),
+            model_catalog: ModelCatalogSettings::default(),
+            ollama: OllamaSettings::default(),
+            providers: BTreeMap::new(),
+            default_language: "en".to_string(),
+            default_processing_type: "standard".to_string(),
+            downloads_directory: dirs::download_dir(),
+            max_upload_size_mb: 100,
+            username: None,
+            check_for_updates: true,
+            tutorial_completed: false,
+            output: OutputSettings::default(),
+            post_download: HashMap::new(),
+            extract_archives: false,
+            extract_limits: ExtractLimits::default(),
+            retention: RetentionSettings::default(),
+            git: None,
+            webhooks: Vec::new(),
+            event_broker: None,
+            min_free_space_mb: default_min_free_space_mb(),
+            workspace: None,
+            workspaces: BTreeMap::new(),
+            presets: BTreeMap::new(),
+            templates: BTreeMap::new(),
+            variables: BTreeMap::new(),
+            preset_sync: None,
+            quotas: BTreeMap::new(),
+            job_spend_cap_usd: None,
+            token_prices: BTreeMap::new(),
+            audit: AuditSettings::default(),
+            transcript: TranscriptSettings::default(),
+            encryption: EncryptionSettings::default(),
+            signing: SigningSettings::default(),
+            preflight: PreflightSettings::default(),
+            preview: PreviewSettings::default(),
+            chunking: None,
+            cleanup: None,
+            confidence: None,
+            figures: None,
+            metadata: MetadataSettings::default(),
+            summarize: MapReduceSettings::default(),
+            paper: PaperSettings::default(),
+            qa: QaSettings::default(),
+            synthetic: SyntheticSettings::default(),
+            best_of: None,
+            voting: None,
+            schemas: BTreeMap::new(),
+            extract: ExtractSettings::default(),
+            checks: CheckSettings::default(),
+            overrides: SessionOverrides::default(),
+            path: None,
+            saved: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        Self::load_from(&get_config_dir()?.join("config.toml"))
+    }
+
+    /// Reads the config at `config_path`, writing the defaults there when it's missing.
+    /// `save` writes back to the same file.
+    pub fn load_from(config_path: &Path) -> Result<Self> {
+        if !config_path.exists() {
+            let mut default_config = Config {
+                path: Some(config_path.to_path_buf()),
+                ..Config::default()
+            };
+            default_config.save()?;
+            return Ok(default_config);
+        }
+
+        let mut config = read_config(config_path)?;
+        config.path = Some(config_path.to_path_buf());
+        config.saved = Some(toml::Table::try_from(&config).context("Failed to serialize config")?);
+        Ok(config)
+    }
+
+    /// Whether to start in the remote-shell rendering mode, from config.toml or `--remote-shell`
+    pub fn starts_remote_shell(&self) -> bool {
+        self.remote_shell || self.overrides.remote_shell
+    }
+
+    /// `[chaos]` with `--chaos` applied
+    pub fn chaos_settings(&self) -> ChaosSettings {
+        ChaosSettings {
+            enabled: self.chaos.enabled || self.overrides.chaos,
+            ..self.chaos.clone()
+        }
+    }
+
+    /// User name used to match job ownership on shared backends.
+    /// Falls back to the OS user when not set explicitly.
+    pub fn effective_username(&self) -> Option<String> {
+        self.username.clone()
+            .or_else(|| std::env::var("USER").ok())
+            .or_else(|| std::env::var("USERNAME").ok())
+    }
+
+    pub fn active_workspace(&self) -> &str {
+        self.overrides.workspace.as_deref()
+            .or(self.workspace.as_deref())
+            .unwrap_or(DEFAULT_WORKSPACE)
+    }
+
+    /// Makes `name` the active workspace and the one remembered for the next runs
+    pub fn select_workspace(&mut self, name: &str) {
+        self.overrides.workspace = None;
+        self.workspace = (name != DEFAULT_WORKSPACE).then(|| name.to_string());
+    }
+
+    /// Local presets of `workspace`; a workspace without any has none of the default one's
+    pub fn workspace_presets(&self, workspace: &str) -> &BTreeMap<String, Preset> {
+        static NONE: BTreeMap<String, Preset> = BTreeMap::new();
+        if workspace == DEFAULT_WORKSPACE {
+            return &self.presets;
+        }
+        self.workspaces.get(workspace).map_or(&NONE, |ws| &ws.presets)
+    }
+
+    /// Local presets of the active workspace
+    pub fn active_presets(&self) -> &BTreeMap<String, Preset> {
+        self.workspace_presets(self.active_workspace())
+    }
+
+    /// Local presets of the active workspace, for saving or deleting one
+    pub fn active_presets_mut(&mut self) -> &mut BTreeMap<String, Preset> {
+        let name = self.active_workspace().to_string();
+        if name == DEFAULT_WORKSPACE {
+            return &mut self.presets;
+        }
+        &mut self.workspaces.entry(name).or_default().presets
+    }
+
+    /// Tags of the active workspace
+    pub fn workspace_tags(&self) -> &[String] {
+        self.workspaces.get(self.active_workspace()).map_or(&[], |ws| &ws.tags)
+    }
+
+    /// Variables of the active workspace over the global ones. `env:NAME` values are read from
+    /// the environment; unset ones are left out so their placeholders stay visible.
+    pub fn profile_variables(&self) -> BTreeMap<String, String> {
+        let mut variables = self.variables.clone();
+        if let Some(workspace) = self.workspaces.get(self.active_workspace()) {
+            variables.extend(workspace.variables.clone());
+        }
+        variables.into_iter()
+            .filter_map(|(name, value)| match value.strip_prefix("env:") {
+                Some(var) => std::env::var(var.trim()).ok().map(|value| (name, value)),
+                None => Some((name, value)),
+            })
+            .collect()
+    }
+
+    /// Custom providers, without any that would shadow a built-in one
+    pub fn custom_providers(&self) -> impl Iterator<Item = (&String, &CustomProvider)> {
+        self.providers.iter().filter(|(name, _)| !providers::BUILT_IN.contains(&name.as_str()))
+    }
+
+    pub fn custom_provider(&self, name: &str) -> Option<&CustomProvider> {
+        self.custom_providers().find(|(n, _)| *n == name).map(|(_, provider)| provider)
+    }
+
+    /// Where a self-hosted provider's API listens; `None` for hosted providers
+    pub fn provider_base_url(&self, provider: &str) -> Option<String> {
+        if provider == crate::ollama::PROVIDER {
+            return Some(self.ollama.base_url.clone());
+        }
+        self.custom_provider(provider).map(|custom| custom.base_url.clone())
+    }
+
+    /// Headers of backend requests in the active workspace: the global ones overridden by the
+    /// workspace's, with `User-Agent` when set. Headers that can't be sent are skipped.
+    pub fn request_headers(&self) -> Vec<(String, String)> {
+        let workspace = self.workspaces.get(self.active_workspace());
+        let mut headers = self.request_headers.clone();
+        if let Some(workspace) = workspace {
+            headers.extend(workspace.request_headers.clone());
+        }
+        let user_agent = workspace.and_then(|ws| ws.user_agent.clone()).or_else(|| self.user_agent.clone());
+        if let Some(user_agent) = user_agent.filter(|ua| !ua.trim().is_empty()) {
+            headers.insert("User-Agent".to_string(), user_agent);
+        }
+        headers.into_iter()
+            .filter_map(|(name, value)| match value.strip_prefix("env:") {
+                Some(var) => std::env::var(var.trim()).ok().map(|value| (name, value)),
+                None => Some((name, value)),
+            })
+            .filter(|(name, value)| match network::check_header(name, value) {
+                Ok(()) => true,
+                Err(err) => {
+                    log_warn!("Skipping request header: {:#}", err);
+                    false
+                },
+            })
+            .collect()
+    }
+
+    /// Download directory of the active workspace, falling back to the global one
+    pub fn downloads_dir(&self) -> PathBuf {
+        self.workspaces.get(self.active_workspace())
+            .and_then(|ws| ws.downloads_directory.clone())
+            .or_else(|| self.downloads_directory.clone())
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    pub fn post_download_actions(&self, preset: &str) -> Vec<PostDownloadAction> {
+        self.post_download.get(preset)
+            .or_else(|| self.post_download.get("default"))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Writes the settings changed since this copy was loaded or last saved. config.toml is
+    /// re-read under its lock first, so changes made meanwhile by another instance or a script
+    /// (`config import`, preset sync) are kept unless this copy changed the same setting.
+    /// Afterwards this copy holds the merged settings.
+    pub fn save(&mut self) -> Result<()> {
+        let config_path = match &self.path {
+            Some(path) => path.clone(),
+            None => get_config_dir()?.join("config.toml"),
+        };
+        if let Some(config_dir) = config_path.parent() {
+            fs::create_dir_all(config_dir)
+                .context("Failed to create config directory")?;
+        }
+
+        let edited = toml::Table::try_from(&*self).context("Failed to serialize config")?;
+        let _lock = FileLock::acquire(&config_path)?;
+        // A missing or unreadable file has nothing worth keeping
+        let current = config_path.exists().then(|| read_config(&config_path).ok()).flatten()
+            .and_then(|config| toml::Table::try_from(&config).ok());
+        let merged = match (&self.saved, current) {
+            (Some(saved), Some(current)) => merge_edits(saved, &edited, current),
+            _ => edited,
+        };
+        let mut config: Config = merged.try_into().context("Failed to merge config changes")?;
+        let config_content = toml::to_string_pretty(&config)
+            .context("Failed to serialize config")?;
+        atomic::write(&config_path, config_content)
+            .context("Failed to write config file")?;
+
+        config.overrides = std::mem::take(&mut self.overrides);
+        config.path = self.path.take();
+        config.saved = Some(toml::Table::try_from(&config).context("Failed to serialize config")?);
+        *self = config;
+        Ok(())
+    }
+}
+
+fn read_config(config_path: &Path) -> Result<Config> {
+    let config_content = fs::read_to_string(config_path)
+        .context("Failed to read config file")?;
+    toml::from_str(&config_content)
+        .context("Failed to parse config file")
+}
+
+/// Three-way merge of config tables: settings that `edited` changed since `saved` go over
+/// `current`, the rest of `current` stays. Tables are merged key by key, so two presets
+/// saved from different places both survive.
+fn merge_edits(saved: &toml::Table, edited: &toml::Table, mut current: toml::Table) -> toml::Table {
+    let keys: BTreeSet<&String> = saved.keys().chain(edited.keys()).collect();
+    for key in keys {
+        match (saved.get(key), edited.get(key)) {
+            (before, after) if before == after => {},
+            (Some(toml::Value::Table(before)), Some(toml::Value::Table(after))) => {
+                let merged = match current.remove(key) {
+                    Some(toml::Value::Table(now)) => merge_edits(before, after, now),
+                    _ => after.clone(),
+                };
+                current.insert(key.clone(), toml::Value::Table(merged));
+            },
+            (_, Some(after)) => {
+                current.insert(key.clone(), after.clone());
+            },
+            (_, None) => {
+                current.remove(key);
+            },
+        }
+    }
+    current
+}
+
+pub fn get_config_dir() -> Result<PathBuf> {
+    let mut config_dir = dirs::config_dir()
+        .context("Could not determine config directory")?;
+        
+    config_dir.push("anydataset-tui");
+    Ok(config_dir)
+}
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::preset::Preset;
+
+    #[test]
+    fn test_save_keeps_changes_made_meanwhile() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("config.toml");
+        let mut tui = Config::load_from(&path)?;
+        tui.presets.insert("stary".to_string(), Preset::default());
+        tui.save()?;
+
+        // Skrypt (np. `config import`) zmienia plik, gdy TUI działa na swojej kopii
+        let mut script = Config::load_from(&path)?;
+        script.backend_url = "http://lab:8000".to_string();
+        script.presets.insert("ze-skryptu".to_string(), Preset::default());
+        script.save()?;
+
+        tui.theme.palette = "dark".to_string();
+        tui.presets.remove("stary");
+        tui.presets.insert("faq".to_string(), Preset::default());
+        tui.save()?;
+
+        let saved = Config::load_from(&path)?;
+        assert_eq!(saved.backend_url, "http://lab:8000");
+        assert_eq!(saved.theme.palette, "dark");
+        assert_eq!(saved.presets.keys().collect::<Vec<_>>(), ["faq", "ze-skryptu"]);
+        // Kopia w pamięci po zapisie widzi też zmiany skryptu
+        assert_eq!(tui.backend_url, "http://lab:8000");
+        assert!(tui.presets.contains_key("ze-skryptu"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_own_change_wins_over_file() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("config.toml");
+        let mut tui = Config::load_from(&path)?;
+
+        let mut script = Config::load_from(&path)?;
+        script.backend_url = "http://lab:8000".to_string();
+        script.save()?;
+
+        tui.backend_url = "http://prod:8000".to_string();
+        tui.save()?;
+        assert_eq!(Config::load_from(&path)?.backend_url, "http://prod:8000");
+        Ok(())
+    }
+}
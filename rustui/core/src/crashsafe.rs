@@ -0,0 +1,255 @@
+use anyhow::Result;
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::error::ProcessingErrorKind;
+use crate::processing_error;
+
+thread_local! {
+    static GUARDED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether the current thread is parsing a user file under `parse`. The panic hook leaves
+/// the terminal alone then, as the TUI keeps running.
+pub fn in_guard() -> bool {
+    GUARDED.with(Cell::get)
+}
+
+/// Runs a parser over `file`, turning a panic inside it into an `UnsupportedFormat` error:
+/// a malformed file fails its own job instead of taking down the TUI
+pub fn parse<T>(file: &str, parser: impl FnOnce() -> Result<T>) -> Result<T> {
+    let outer = GUARDED.with(|guarded| guarded.replace(true));
+    let outcome = panic::catch_unwind(AssertUnwindSafe(parser));
+    GUARDED.with(|guarded| guarded.set(outer));
+    outcome.unwrap_or_else(|payload| {
+        let reason = payload.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown error".to_string());
+        Err(processing_error!(ProcessingErrorKind::UnsupportedFormat, file, "the file could not be parsed ({})", reason).into())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crashsafe;
+    use crate::docmeta;
+    use crate::error::{AppError, ProcessingErrorKind};
+    use crate::formats::{self, DatasetFormat};
+    use crate::office::{self, OfficeFormat};
+    use crate::paper;
+    #[cfg(feature = "pdf")]
+    use crate::pdf;
+    use crate::processors::{ProcessingResult, ProcessingStats, Record};
+    use crate::tabular;
+    use serde_json::Value;
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    const CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../fuzz/corpus");
+
+    /// Mutacje na ziarno; deterministyczne, więc błąd da się powtórzyć
+    const ROUNDS: usize = 200;
+
+    /// Fragmenty, które najczęściej psują parsery: liczby spoza zakresu, znaczniki formatów
+    const TOKENS: [&[u8]; 12] = [
+        b"\xff\xfe", b"0", b"-1", b"99999999999999999999", b"(", b"\\", b"---\n",
+        b"\\section{", b"<w:p>", b" 0 obj", b"\"", b"\n",
+    ];
+
+    /// Prosty mutator bajtów (xorshift64*), bez libFuzzera
+    pub struct Mutator(u64);
+
+    impl Mutator {
+        pub fn new(seed: u64) -> Self {
+            Self(seed.max(1))
+        }
+
+        fn next(&mut self, below: usize) -> usize {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            (self.0.wrapping_mul(0x2545_F491_4F6C_DD1D) % below.max(1) as u64) as usize
+        }
+
+        /// Od jednej do czterech zmian: podmiana, wstawienie lub usunięcie bajtów,
+        /// ucięcie, powielenie fragmentu albo wstawienie jednego z `TOKENS`
+        pub fn mutate(&mut self, seed: &[u8]) -> Vec<u8> {
+            let mut data = seed.to_vec();
+            for _ in 0..=self.next(4) {
+                let at = self.next(data.len() + 1);
+                match self.next(6) {
+                    0 if at < data.len() => data[at] = self.next(256) as u8,
+                    1 => data.insert(at, self.next(256) as u8),
+                    2 if at < data.len() => {
+                        let end = (at + 1 + self.next(16)).min(data.len());
+                        data.drain(at..end);
+                    },
+                    3 => data.truncate(at),
+                    4 if at < data.len() => {
+                        let end = (at + 1 + self.next(32)).min(data.len());
+                        let chunk = data[at..end].to_vec();
+                        let to = self.next(data.len() + 1);
+                        data.splice(to..to, chunk);
+                    },
+                    _ => {
+                        let token = TOKENS[self.next(TOKENS.len())];
+                        data.splice(at..at, token.iter().copied());
+                    },
+                }
+            }
+            data
+        }
+    }
+
+    fn seeds(kind: &str) -> Vec<(PathBuf, Vec<u8>)> {
+        let mut seeds: Vec<(PathBuf, Vec<u8>)> = std::fs::read_dir(Path::new(CORPUS).join(kind))
+            .expect("corpus directory")
+            .map(|entry| {
+                let path = entry.expect("corpus entry").path();
+                let bytes = std::fs::read(&path).expect("corpus seed");
+                (path, bytes)
+            })
+            .collect();
+        seeds.sort();
+        assert!(!seeds.is_empty(), "no seeds in {}", kind);
+        seeds
+    }
+
+    /// Błąd parsera musi być błędem formatu, a nie paniką ani błędem innego rodzaju
+    fn assert_unsupported(err: &anyhow::Error, case: &str) {
+        assert!(
+            matches!(err.downcast_ref::<AppError>(), Some(AppError::Processing { kind: ProcessingErrorKind::UnsupportedFormat, .. })),
+            "{}: {:#}", case, err
+        );
+    }
+
+    /// Parser wywołany bez osłony `crashsafe::parse`: jego błąd jest w porządku, panika oblewa test
+    fn assert_no_panic<T>(case: &str, parser: impl FnOnce() -> anyhow::Result<T>) {
+        if let Err(payload) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(parser)) {
+            let reason = payload.downcast_ref::<&str>().map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            panic!("{}: parser panicked: {}", case, reason);
+        }
+    }
+
+    /// Rekordy o dowolnej treści: pola i metadane wzięte z tego, co dało się odczytać
+    fn to_results(values: &[Value], source: &str) -> Vec<ProcessingResult> {
+        let records: Vec<Record> = values.iter().map(|value| Record {
+            instruction: value.get("instruction").map(|v| v.to_string()).unwrap_or_default(),
+            prompt: value.to_string(),
+            completion: value.get("completion").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+            metadata: value.as_object()
+                .map(|object| object.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+                .unwrap_or_else(HashMap::new),
+        }).collect();
+        let count = records.len();
+        vec![ProcessingResult {
+            records,
+            source_file: source.to_string(),
+            processing_type: "standard".to_string(),
+            stats: ProcessingStats { total_records: count, total_tokens: 0, processing_time_ms: 0 },
+            exchanges: Vec::new(),
+            error: None,
+        }]
+    }
+
+    #[test]
+    fn test_seeds_parse() -> anyhow::Result<()> {
+        #[cfg(feature = "pdf")]
+        for (path, _) in seeds("pdf") {
+            assert!(!pdf::extract(&path)?.pages.is_empty());
+        }
+        for (path, _) in seeds("office") {
+            let format = OfficeFormat::of(&path).expect("office seed");
+            assert!(!office::read_blocks(&path, format)?.is_empty());
+        }
+        for (path, bytes) in seeds("records") {
+            let records = formats::parse_records(&String::from_utf8(bytes)?)?;
+            assert!(!records.is_empty(), "{}", path.display());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_guard_turns_panic_into_error() {
+        let err = crashsafe::parse("broken.pdf", || -> anyhow::Result<()> { panic!("index out of bounds") })
+            .unwrap_err();
+        assert_unsupported(&err, "panic");
+        assert!(err.to_string().contains("broken.pdf"));
+        assert!(err.to_string().contains("index out of bounds"));
+        assert!(!crashsafe::in_guard());
+
+        assert_eq!(crashsafe::parse("ok.txt", || Ok(7)).unwrap(), 7);
+    }
+
+    #[test]
+    #[cfg(feature = "pdf")]
+    fn test_fuzz_pdf() {
+        let dir = tempfile::tempdir().unwrap();
+        for (seed_path, seed) in seeds("pdf") {
+            let mut mutator = Mutator::new(0x5eed);
+            for round in 0..ROUNDS {
+                let path = dir.path().join("case.pdf");
+                std::fs::write(&path, mutator.mutate(&seed)).unwrap();
+                let case = format!("{} round {}", seed_path.display(), round);
+                assert_no_panic(&case, || pdf::extract(&path));
+            }
+        }
+    }
+
+    #[test]
+    fn test_fuzz_office() {
+        let dir = tempfile::tempdir().unwrap();
+        for (seed_path, seed) in seeds("office") {
+            let format = OfficeFormat::of(&seed_path).unwrap();
+            let mut mutator = Mutator::new(0x0ff1ce);
+            for round in 0..ROUNDS {
+                let path = dir.path().join(seed_path.file_name().unwrap());
+                std::fs::write(&path, mutator.mutate(&seed)).unwrap();
+                let case = format!("{} round {}", seed_path.display(), round);
+                assert_no_panic(&case, || office::read_blocks(&path, format));
+            }
+        }
+    }
+
+    #[test]
+    fn test_fuzz_text_parsers() {
+        for (seed_path, seed) in seeds("text") {
+            let mut mutator = Mutator::new(0x7e47);
+            for round in 0..ROUNDS {
+                let text = String::from_utf8_lossy(&mutator.mutate(&seed)).into_owned();
+                let case = format!("{} round {}", seed_path.display(), round);
+                assert_no_panic(&case, || {
+                    paper::split_latex(&text);
+                    paper::split_sections(&text);
+                    // Zły YAML to zwykły błąd, byle nie panika
+                    docmeta::split_front_matter(&text).map(|_| ())
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn test_fuzz_records_and_converters() {
+        for (seed_path, seed) in seeds("records") {
+            let mut mutator = Mutator::new(0x1_5071);
+            for round in 0..ROUNDS {
+                let text = String::from_utf8_lossy(&mutator.mutate(&seed)).into_owned();
+                let case = format!("{} round {}", seed_path.display(), round);
+                assert_no_panic(&case, || {
+                    // Uszkodzony plik wyników to zwykły błąd; konwertery dostają to, co się wczytało
+                    let Ok(values) = formats::parse_records(&text) else {
+                        return Ok(());
+                    };
+                    let results = to_results(&values, &case);
+                    for format in DatasetFormat::ALL {
+                        formats::convert(&results, format, Some("system"))?;
+                    }
+                    assert_eq!(tabular::rows(&results, &tabular::default_columns()).len(), values.len());
+                    Ok(())
+                });
+            }
+        }
+    }
+}
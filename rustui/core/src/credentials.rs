@@ -0,0 +1,257 @@
+use anyhow::{Context, Result};
+use std::fmt;
+
+/// Service name the keys are stored under in the OS keyring
+pub const KEYRING_SERVICE: &str = "anydataset-tui";
+
+/// Where a provider's API key was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySource {
+    Keyring,
+    /// Environment variable with this name
+    Env(String),
+}
+
+impl fmt::Display for KeySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeySource::Keyring => write!(f, "keyring"),
+            KeySource::Env(var) => write!(f, "${}", var),
+        }
+    }
+}
+
+/// Environment variable checked when the keyring has no key, e.g. `OPENAI_API_KEY`
+pub fn env_var(provider: &str) -> String {
+    let name: String = provider.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("{}_API_KEY", name)
+}
+
+#[cfg(feature = "keyring")]
+fn entry(provider: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, provider).context("OS keyring unavailable")
+}
+
+/// The provider's key from the keyring, or from its environment variable
+pub fn get(provider: &str) -> Option<(String, KeySource)> {
+    #[cfg(feature = "keyring")]
+    if let Some(key) = entry(provider).ok().and_then(|e| e.get_password().ok()) {
+        return Some((key, KeySource::Keyring));
+    }
+    let var = env_var(provider);
+    std::env::var(&var).ok()
+        .filter(|key| !key.trim().is_empty())
+        .map(|key| (key, KeySource::Env(var)))
+}
+
+#[cfg(feature = "keyring")]
+pub fn set(provider: &str, key: &str) -> Result<()> {
+    entry(provider)?.set_password(key.trim()).context("Failed to save the key in the OS keyring")
+}
+
+/// Removes the stored key; the environment variable, if set, applies again
+#[cfg(feature = "keyring")]
+pub fn delete(provider: &str) -> Result<()> {
+    match entry(provider)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(err).context("Failed to remove the key from the OS keyring"),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn set(_provider: &str, _key: &str) -> Result<()> {
+    anyhow::bail!("The OS keyring is not available in this build; rebuild with `--features keyring` or set {}", env_var(_provider))
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn delete(_provider: &str) -> Result<()> {
+    anyhow::bail!("The OS keyring is not available in this build; rebuild with `--features keyring`")
+}
+
+/// Key with all but its ends hidden, for display
+pub fn mask(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 8 {
+        return "•".repeat(chars.len());
+    }
+    let start: String = chars[..3].iter().collect();
+    let end: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", start, end)
+}
+
+/// Header the provider expects its key in
+pub fn auth_header(provider: &str, key: &str) -> (&'static str, String) {
+    match provider {
+        "anthropic" => ("x-api-key", key.to_string()),
+        _ => ("Authorization", format!("Bearer {}", key)),
+    }
+}
+
+/// Endpoint that lists models and so tells whether a key is accepted
+fn validation_url(provider: &str) -> Option<&'static str> {
+    match provider {
+        "openai" => Some("https://api.openai.com/v1/models"),
+        "anthropic" => Some("https://api.anthropic.com/v1/models"),
+        "mistral" => Some("https://api.mistral.ai/v1/models"),
+        "groq" => Some("https://api.groq.com/openai/v1/models"),
+        _ => None,
+    }
+}
+
+/// Asks the provider whether it accepts the key
+pub async fn validate(provider: &str, key: &str) -> Result<()> {
+    let url = validation_url(provider)
+        .with_context(|| format!("Don't know how to check {} keys", provider))?;
+    let (name, value) = auth_header(provider, key);
+    let mut request = crate::network::client().get(url).header(name, value);
+    if provider == "anthropic" {
+        request = request.header("anthropic-version", "2023-06-01");
+    }
+    let response = request.send().await.context("Provider unreachable")?;
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        anyhow::bail!("{} rejected the key ({})", provider, status);
+    }
+    if !status.is_success() {
+        anyhow::bail!("{} answered {}", provider, status);
+    }
+    Ok(())
+}
+
+/// Provider row of the key popup
+#[derive(Debug, Clone)]
+pub struct KeyEntry {
+    pub provider: String,
+    /// Masked key and where it came from
+    pub key: Option<(String, KeySource)>,
+    /// Result of the last check: `Ok` or why the key was rejected
+    pub check: Option<std::result::Result<(), String>>,
+    pub checking: bool,
+}
+
+/// Popup in Settings listing the providers' keys. Keys are looked up when it opens and
+/// after changes, not on every frame.
+#[derive(Debug, Clone, Default)]
+pub struct KeyManager {
+    pub entries: Vec<KeyEntry>,
+    pub selected: usize,
+    /// Key being typed for the selected provider
+    pub editing: Option<String>,
+}
+
+impl KeyManager {
+    pub fn load(providers: &[String]) -> Self {
+        let mut manager = Self {
+            entries: providers.iter()
+                .map(|provider| KeyEntry { provider: provider.clone(), key: None, check: None, checking: false })
+                .collect(),
+            ..Self::default()
+        };
+        for i in 0..manager.entries.len() {
+            manager.refresh(i);
+        }
+        manager
+    }
+
+    pub fn refresh(&mut self, index: usize) {
+        if let Some(entry) = self.entries.get_mut(index) {
+            entry.key = get(&entry.provider).map(|(key, source)| (mask(&key), source));
+        }
+    }
+
+    pub fn current(&self) -> Option<&KeyEntry> {
+        self.entries.get(self.selected)
+    }
+
+    pub fn next(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected = (self.selected + 1) % self.entries.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected = (self.selected + self.entries.len() - 1) % self.entries.len();
+        }
+    }
+
+    pub fn checked(&mut self, provider: &str, result: std::result::Result<(), String>) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.provider == provider) {
+            entry.checking = false;
+            entry.check = Some(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{ApiClient, ProcessingConfig};
+    use crate::credentials::{auth_header, env_var, get, mask, KeyManager, KeySource};
+
+    #[test]
+    fn test_env_var_and_mask() {
+        assert_eq!(env_var("openai"), "OPENAI_API_KEY");
+        assert_eq!(env_var("together-ai"), "TOGETHER_AI_API_KEY");
+        assert_eq!(mask("sk-1234567890abcd"), "sk-…abcd");
+        // Krótkie klucze są ukrywane w całości
+        assert_eq!(mask("short"), "•••••");
+    }
+
+    #[test]
+    fn test_auth_header() {
+        assert_eq!(auth_header("anthropic", "key"), ("x-api-key", "key".to_string()));
+        assert_eq!(auth_header("openai", "key"), ("Authorization", "Bearer key".to_string()));
+    }
+
+    #[test]
+    fn test_env_fallback() {
+        // Unikalny dostawca, żeby nie trafić na klucz z keyringa ani z innego testu
+        std::env::set_var("TEST_KEYS_ENV_API_KEY", "sk-from-environment");
+        assert_eq!(
+            get("test-keys-env"),
+            Some(("sk-from-environment".to_string(), KeySource::Env("TEST_KEYS_ENV_API_KEY".to_string())))
+        );
+        assert_eq!(get("test-keys-missing"), None);
+
+        let mut manager = KeyManager::load(&["test-keys-env".to_string(), "test-keys-missing".to_string()]);
+        assert_eq!(manager.entries[0].key.as_ref().map(|(masked, _)| masked.as_str()), Some("sk-…ment"));
+        assert!(manager.entries[1].key.is_none());
+        manager.previous();
+        assert_eq!(manager.current().map(|e| e.provider.as_str()), Some("test-keys-missing"));
+        manager.checked("test-keys-missing", Err("rejected".to_string()));
+        assert_eq!(manager.entries[1].check, Some(Err("rejected".to_string())));
+    }
+
+    #[test]
+    fn test_process_sends_provider_key() -> anyhow::Result<()> {
+        std::env::set_var("TEST_KEYS_PROCESS_API_KEY", "sk-process");
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/api/process")
+            .match_header("authorization", "Bearer sk-process")
+            .with_body(r#"{"job_id": "job-1"}"#)
+            .create();
+        let config = ProcessingConfig {
+            provider: "test-keys-process".to_string(),
+            model: "model".to_string(),
+            system_prompt: None,
+            keywords: None,
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            language: None,
+            processing_type: "standard".to_string(),
+            add_reasoning: None,
+            output_format: None,
+            provider_base_url: None,
+            provider_api: None,
+        };
+
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        let job_id = runtime.block_on(ApiClient::new(&server.url()).process_file("file-1", config))?;
+        assert_eq!(job_id, "job-1");
+        mock.assert();
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// mDNS service type AnyDataset backends announce themselves with
+pub const SERVICE_TYPE: &str = "_anydataset._tcp.local.";
+/// How long a scan listens for announcements
+pub const SCAN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Backend found on the local network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredBackend {
+    /// Instance name from the announcement, e.g. "gpu-box"
+    pub name: String,
+    pub url: String,
+    /// Backend version from the `version` TXT record, when announced
+    pub version: Option<String>,
+}
+
+/// Backend URL for an announced service. The address is preferred over the host name, which
+/// often doesn't resolve outside the mDNS world; TXT records may set `scheme` and `path`.
+pub fn backend_url(host: &str, addresses: &[IpAddr], port: u16, scheme: Option<&str>, path: Option<&str>) -> String {
+    let host = match addresses.iter().find(|a| a.is_ipv4()).or_else(|| addresses.first()) {
+        Some(IpAddr::V6(address)) => format!("[{}]", address),
+        Some(address) => address.to_string(),
+        None => host.trim_end_matches('.').to_string(),
+    };
+    let path = path.map(|p| p.trim_matches('/')).filter(|p| !p.is_empty());
+    match path {
+        Some(path) => format!("{}://{}:{}/{}", scheme.unwrap_or("http"), host, port, path),
+        None => format!("{}://{}:{}", scheme.unwrap_or("http"), host, port),
+    }
+}
+
+/// Listens for backend announcements for `timeout`; instances are listed once, by name
+#[cfg(feature = "discovery")]
+pub fn scan(timeout: Duration) -> Result<Vec<DiscoveredBackend>> {
+    use mdns_sd::{ServiceDaemon, ServiceEvent};
+
+    let daemon = ServiceDaemon::new()?;
+    let events = daemon.browse(SERVICE_TYPE)?;
+    let deadline = std::time::Instant::now() + timeout;
+    let mut found: Vec<DiscoveredBackend> = Vec::new();
+    while let Some(remaining) = deadline.checked_duration_since(std::time::Instant::now()) {
+        let Ok(event) = events.recv_timeout(remaining) else {
+            break;
+        };
+        let ServiceEvent::ServiceResolved(info) = event else {
+            continue;
+        };
+        let mut addresses: Vec<IpAddr> = info.get_addresses().iter().copied().collect();
+        addresses.sort();
+        let backend = DiscoveredBackend {
+            name: info.get_fullname().trim_end_matches(SERVICE_TYPE).trim_end_matches('.').to_string(),
+            url: backend_url(
+                info.get_hostname(),
+                &addresses,
+                info.get_port(),
+                info.get_property_val_str("scheme"),
+                info.get_property_val_str("path"),
+            ),
+            version: info.get_property_val_str("version").map(str::to_string),
+        };
+        match found.iter_mut().find(|b| b.name == backend.name) {
+            Some(known) => *known = backend,
+            None => found.push(backend),
+        }
+    }
+    let _ = daemon.shutdown();
+    found.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(found)
+}
+
+#[cfg(not(feature = "discovery"))]
+pub fn scan(_timeout: Duration) -> Result<Vec<DiscoveredBackend>> {
+    anyhow::bail!("LAN discovery is not available in this build; rebuild with `--features discovery`")
+}
+
+/// Popup listing the backends found on the network
+#[derive(Debug, Clone, Default)]
+pub struct BackendPicker {
+    pub backends: Vec<DiscoveredBackend>,
+    pub selected: usize,
+    pub scanning: bool,
+    /// Why the last scan failed
+    pub error: Option<String>,
+}
+
+impl BackendPicker {
+    pub fn current(&self) -> Option<&DiscoveredBackend> {
+        self.backends.get(self.selected)
+    }
+
+    pub fn next(&mut self) {
+        if !self.backends.is_empty() {
+            self.selected = (self.selected + 1) % self.backends.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.backends.is_empty() {
+            self.selected = (self.selected + self.backends.len() - 1) % self.backends.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::backend_url;
+    use std::net::IpAddr;
+
+    #[test]
+    fn test_backend_url_prefers_ipv4_address() {
+        let v4: IpAddr = "192.168.1.20".parse().unwrap();
+        let v6: IpAddr = "fe80::1".parse().unwrap();
+        assert_eq!(backend_url("gpu-box.local.", &[v6, v4], 8000, None, None), "http://192.168.1.20:8000");
+        assert_eq!(backend_url("gpu-box.local.", &[v6], 8000, None, None), "http://[fe80::1]:8000");
+        assert_eq!(backend_url("gpu-box.local.", &[], 443, Some("https"), Some("/anydata/")), "https://gpu-box.local:443/anydata");
+    }
+}
@@ -0,0 +1,112 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Outcome of checking free space before writing a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpaceCheck {
+    Ok,
+    /// Output fits, but free space would drop below the configured threshold
+    Low { available: u64, threshold: u64 },
+    /// Projected output is larger than the free space
+    Insufficient { available: u64, required: u64 },
+}
+
+impl SpaceCheck {
+    /// Evaluates a known amount of free space; `required` is `None` when the output size is unknown
+    pub fn evaluate(available: u64, required: Option<u64>, threshold: u64) -> Self {
+        let required_bytes = required.unwrap_or(0);
+        if required_bytes > available {
+            SpaceCheck::Insufficient { available, required: required_bytes }
+        } else if available - required_bytes < threshold {
+            SpaceCheck::Low { available, threshold }
+        } else {
+            SpaceCheck::Ok
+        }
+    }
+
+    pub fn warning(&self) -> Option<String> {
+        match self {
+            SpaceCheck::Ok => None,
+            SpaceCheck::Low { available, threshold } => Some(format!(
+                "Low disk space: {} free (warning threshold {})",
+                format_bytes(*available), format_bytes(*threshold)
+            )),
+            SpaceCheck::Insufficient { available, required } => Some(format!(
+                "Not enough disk space: {} needed, {} free",
+                format_bytes(*required), format_bytes(*available)
+            )),
+        }
+    }
+}
+
+/// Free space on the filesystem that will hold `path`. The path itself doesn't have
+/// to exist yet; the nearest existing ancestor is queried.
+pub fn available_space(path: &Path) -> Result<u64> {
+    let existing = path.ancestors()
+        .find(|p| !p.as_os_str().is_empty() && p.exists())
+        .unwrap_or_else(|| Path::new("."));
+    fs2::available_space(existing)
+        .with_context(|| format!("Cannot read free space of {}", existing.display()))
+}
+
+pub fn check(path: &Path, required: Option<u64>, threshold_mb: u64) -> Result<SpaceCheck> {
+    Ok(SpaceCheck::evaluate(available_space(path)?, required, threshold_mb * 1024 * 1024))
+}
+
+/// Fails when the output won't fit unless `allow_overflow` is set; returns a warning
+/// when free space is merely low. An unreadable free-space figure never blocks writing.
+pub fn ensure_fits(path: &Path, required: Option<u64>, threshold_mb: u64, allow_overflow: bool) -> Result<Option<String>> {
+    let check = match check(path, required, threshold_mb) {
+        Ok(check) => check,
+        Err(_) => return Ok(None),
+    };
+    match check {
+        SpaceCheck::Insufficient { .. } if !allow_overflow => {
+            anyhow::bail!("{}", check.warning().unwrap_or_default())
+        },
+        _ => Ok(check.warning()),
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diskspace::{available_space, ensure_fits, format_bytes, SpaceCheck};
+
+    #[test]
+    fn test_space_check() -> anyhow::Result<()> {
+        const MB: u64 = 1024 * 1024;
+        assert_eq!(SpaceCheck::evaluate(1000 * MB, Some(100 * MB), 500 * MB), SpaceCheck::Ok);
+        assert_eq!(
+            SpaceCheck::evaluate(550 * MB, Some(100 * MB), 500 * MB),
+            SpaceCheck::Low { available: 550 * MB, threshold: 500 * MB }
+        );
+        assert_eq!(
+            SpaceCheck::evaluate(50 * MB, Some(100 * MB), 500 * MB),
+            SpaceCheck::Insufficient { available: 50 * MB, required: 100 * MB }
+        );
+        assert_eq!(format_bytes(1536 * 1024), "1.5 MB");
+
+        // Katalog docelowy nie musi jeszcze istnieć
+        let dir = tempfile::tempdir()?;
+        let target = dir.path().join("not/yet/created.json");
+        assert!(available_space(&target)? > 0);
+        assert!(ensure_fits(&target, Some(u64::MAX), 0, false).is_err());
+        assert!(ensure_fits(&target, Some(u64::MAX), 0, true).is_ok());
+        Ok(())
+    }
+}
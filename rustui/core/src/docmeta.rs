@@ -0,0 +1,552 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+use crate::processors::Record;
+
+/// Properties of a source document, keyed as in the `rename` mapping
+pub type DocumentMetadata = BTreeMap<String, serde_json::Value>;
+
+/// Record metadata key holding the reason harvesting failed
+pub const ERROR_KEY: &str = "metadata_error";
+
+/// Document metadata harvesting from the `[metadata]` config section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetadataSettings {
+    #[serde(default = "default_true")]
+    pub harvest: bool,
+    /// Harvested key to record metadata key; an empty name drops the key
+    #[serde(default)]
+    pub rename: BTreeMap<String, String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for MetadataSettings {
+    fn default() -> Self {
+        Self { harvest: true, rename: BTreeMap::new() }
+    }
+}
+
+impl MetadataSettings {
+    /// Applies the `rename` mapping
+    pub fn rename(&self, metadata: DocumentMetadata) -> DocumentMetadata {
+        metadata.into_iter()
+            .filter_map(|(key, value)| match self.rename.get(&key) {
+                Some(name) if name.is_empty() => None,
+                Some(name) => Some((name.clone(), value)),
+                None => Some((key, value)),
+            })
+            .collect()
+    }
+}
+
+/// Adds the document's metadata to its records. Keys a processor set itself win.
+pub fn merge(records: &mut [Record], metadata: &DocumentMetadata) {
+    for record in records {
+        for (key, value) in metadata {
+            record.metadata.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+}
+
+/// Harvests a file's metadata according to `settings`; failures are reported under
+/// `metadata_error` rather than failing the run
+pub fn for_file(path: &Path, settings: &MetadataSettings) -> DocumentMetadata {
+    if !settings.harvest {
+        return DocumentMetadata::new();
+    }
+    match harvest(path) {
+        Ok(metadata) => settings.rename(metadata),
+        Err(err) => DocumentMetadata::from([(ERROR_KEY.to_string(), format!("{:#}", err).into())]),
+    }
+}
+
+/// Reads YAML front-matter of text files, DOCX, ODT and PDF document properties
+/// and EXIF tags of JPEG/TIFF images. Other files have no metadata.
+pub fn harvest(path: &Path) -> Result<DocumentMetadata> {
+    let extension = path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "md" | "markdown" | "txt" => {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            Ok(split_front_matter(&text)?.map(|(metadata, _)| metadata).unwrap_or_default())
+        },
+        "docx" => docx_properties(path),
+        "odt" => odt_properties(path),
+        "pdf" => Ok(pdf_properties(&read(path)?)),
+        "jpg" | "jpeg" | "tif" | "tiff" => Ok(exif_properties(&read(path)?)),
+        _ => Ok(DocumentMetadata::new()),
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Front-matter between `---` lines at the very start of the text, and the text after it
+pub fn split_front_matter(text: &str) -> Result<Option<(DocumentMetadata, &str)>> {
+    let Some(rest) = text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) else {
+        return Ok(None);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            let yaml = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            let metadata: Option<DocumentMetadata> = serde_yaml::from_str(yaml)
+                .context("Invalid YAML front-matter")?;
+            return Ok(Some((metadata.unwrap_or_default(), body)));
+        }
+        offset += line.len();
+    }
+    // No closing line: a horizontal rule, not front-matter
+    Ok(None)
+}
+
+/// Core properties from `docProps/core.xml`
+fn docx_properties(path: &Path) -> Result<DocumentMetadata> {
+    let fields = [
+        ("dc:title", "title"),
+        ("dc:creator", "author"),
+        ("dc:subject", "subject"),
+        ("cp:keywords", "keywords"),
+        ("dcterms:created", "created"),
+        ("dcterms:modified", "modified"),
+    ];
+    zip_properties(path, "DOCX", "docProps/core.xml", &fields)
+}
+
+/// Document properties from `meta.xml`
+fn odt_properties(path: &Path) -> Result<DocumentMetadata> {
+    let fields = [
+        ("dc:title", "title"),
+        ("meta:initial-creator", "author"),
+        ("dc:subject", "subject"),
+        ("meta:keyword", "keywords"),
+        ("meta:creation-date", "created"),
+        ("dc:date", "modified"),
+    ];
+    zip_properties(path, "ODT", "meta.xml", &fields)
+}
+
+/// Elements of an XML entry of a zipped document, mapped to metadata keys
+fn zip_properties(path: &Path, format: &str, entry_name: &str, fields: &[(&str, &str)]) -> Result<DocumentMetadata> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("{} is not a {} file", path.display(), format))?;
+    let mut xml = String::new();
+    match archive.by_name(entry_name) {
+        Ok(mut entry) => {
+            entry.read_to_string(&mut xml).context("Failed to read document properties")?;
+        },
+        Err(zip::result::ZipError::FileNotFound) => return Ok(DocumentMetadata::new()),
+        Err(err) => return Err(err).context("Failed to read document properties"),
+    }
+    Ok(fields.iter()
+        .filter_map(|(element, key)| Some((key.to_string(), xml_text(&xml, element)?.into())))
+        .collect())
+}
+
+/// Text content of the first `<element ...>...</element>`, unescaped
+fn xml_text(xml: &str, element: &str) -> Option<String> {
+    let open = format!("<{}", element);
+    let start = xml.match_indices(&open)
+        .map(|(i, _)| i + open.len())
+        .find(|&i| xml[i..].starts_with(['>', ' ']))?;
+    let content_start = start + xml[start..].find('>')? + 1;
+    let content_end = content_start + xml[content_start..].find(&format!("</{}>", element))?;
+    let text = xml[content_start..content_end]
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+    Some(text.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// Entries of the document information dictionary. Only uncompressed dictionaries are
+/// found; the last occurrence wins since incremental updates are appended.
+fn pdf_properties(bytes: &[u8]) -> DocumentMetadata {
+    let fields = [
+        ("/Title", "title"),
+        ("/Author", "author"),
+        ("/Subject", "subject"),
+        ("/Keywords", "keywords"),
+        ("/CreationDate", "created"),
+        ("/ModDate", "modified"),
+    ];
+    let mut metadata = DocumentMetadata::new();
+    for (name, key) in fields {
+        let value = find_all(bytes, name.as_bytes())
+            .filter_map(|i| pdf_string(&bytes[i + name.len()..]))
+            .last();
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            let value = if key == "created" || key == "modified" { pdf_date(&value) } else { value };
+            metadata.insert(key.to_string(), value.into());
+        }
+    }
+    metadata
+}
+
+fn find_all<'a>(haystack: &'a [u8], needle: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    haystack.windows(needle.len())
+        .enumerate()
+        .filter(move |(_, window)| *window == needle)
+        .map(|(i, _)| i)
+}
+
+/// Literal `(...)` or hex `<...>` string at the start of `bytes`
+fn pdf_string(bytes: &[u8]) -> Option<String> {
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace())?;
+    let raw = match bytes[start] {
+        b'(' => {
+            let mut raw = Vec::new();
+            let mut depth = 0;
+            let mut iter = bytes[start + 1..].iter();
+            while let Some(&b) = iter.next() {
+                match b {
+                    b'\\' => match iter.next()? {
+                        b'n' => raw.push(b'\n'),
+                        b'r' => raw.push(b'\r'),
+                        b't' => raw.push(b'\t'),
+                        &other => raw.push(other),
+                    },
+                    b'(' => {
+                        depth += 1;
+                        raw.push(b);
+                    },
+                    b')' if depth == 0 => break,
+                    b')' => {
+                        depth -= 1;
+                        raw.push(b);
+                    },
+                    _ => raw.push(b),
+                }
+            }
+            raw
+        },
+        b'<' => {
+            let end = bytes[start..].iter().position(|&b| b == b'>')?;
+            let hex: Vec<u8> = bytes[start + 1..start + end].iter()
+                .copied()
+                .filter(|b| b.is_ascii_hexdigit())
+                .collect();
+            hex.chunks(2)
+                .map(|pair| {
+                    let digits = std::str::from_utf8(pair).ok()?;
+                    // An odd final digit is padded with 0
+                    u8::from_str_radix(&format!("{:0<2}", digits), 16).ok()
+                })
+                .collect::<Option<Vec<u8>>>()?
+        },
+        _ => return None,
+    };
+
+    // UTF-16BE with a byte order mark, otherwise treated as Latin-1
+    let text = match raw.strip_prefix(&[0xFE, 0xFF]) {
+        Some(utf16) => {
+            let units: Vec<u16> = utf16.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+            String::from_utf16_lossy(&units)
+        },
+        None => raw.iter().map(|&b| b as char).collect(),
+    };
+    Some(text.trim().to_string())
+}
+
+/// `D:YYYYMMDDHHmmSS+HH'mm'` as ISO 8601; left as is if it doesn't match
+fn pdf_date(value: &str) -> String {
+    let digits = value.strip_prefix("D:").unwrap_or(value);
+    let part = |range: std::ops::Range<usize>| digits.get(range).filter(|p| p.bytes().all(|b| b.is_ascii_digit()));
+    let (Some(year), Some(month), Some(day)) = (part(0..4), part(4..6), part(6..8)) else {
+        return value.to_string();
+    };
+    let (Some(hour), Some(minute), Some(second)) = (part(8..10), part(10..12), part(12..14)) else {
+        return format!("{}-{}-{}", year, month, day);
+    };
+    let zone = match digits.get(14..).unwrap_or_default() {
+        "" => String::new(),
+        z if z.starts_with('Z') => "Z".to_string(),
+        z => {
+            let z: String = z.chars().filter(|c| *c != '\'').collect();
+            match (z.get(..3), z.get(3..5)) {
+                (Some(hours), Some(minutes)) => format!("{}:{}", hours, minutes),
+                (Some(hours), None) => format!("{}:00", hours),
+                _ => String::new(),
+            }
+        },
+    };
+    format!("{}-{}-{}T{}:{}:{}{}", year, month, day, hour, minute, second, zone)
+}
+
+/// Descriptive EXIF tags of a JPEG (APP1 segment) or TIFF file
+fn exif_properties(bytes: &[u8]) -> DocumentMetadata {
+    let tiff = if bytes.starts_with(&[0xFF, 0xD8]) {
+        match jpeg_exif(bytes) {
+            Some(tiff) => tiff,
+            None => return DocumentMetadata::new(),
+        }
+    } else {
+        bytes
+    };
+    let Some(tags) = Tiff::new(tiff).and_then(|t| t.ascii_tags()) else {
+        return DocumentMetadata::new();
+    };
+
+    let mut metadata = DocumentMetadata::new();
+    let fields = [
+        (0x010E, "description"),
+        (0x010F, "camera_make"),
+        (0x0110, "camera_model"),
+        (0x013B, "author"),
+        (0x0132, "modified"),
+        (0x9003, "created"),
+    ];
+    for (tag, key) in fields {
+        if let Some(value) = tags.get(&tag).filter(|v| !v.is_empty()) {
+            let value = if key == "created" || key == "modified" { exif_date(value) } else { value.clone() };
+            metadata.insert(key.to_string(), value.into());
+        }
+    }
+    metadata
+}
+
+/// TIFF data of the Exif APP1 segment
+fn jpeg_exif(bytes: &[u8]) -> Option<&[u8]> {
+    let mut pos = 2;
+    while pos + 4 <= bytes.len() && bytes[pos] == 0xFF {
+        let marker = bytes[pos + 1];
+        // Start of scan: no metadata segments follow
+        if marker == 0xDA {
+            break;
+        }
+        let len = u16::from_be_bytes([bytes[pos + 2], bytes[pos + 3]]) as usize;
+        let data = bytes.get(pos + 4..pos + 2 + len)?;
+        if marker == 0xE1 {
+            if let Some(tiff) = data.strip_prefix(b"Exif\0\0") {
+                return Some(tiff);
+            }
+        }
+        pos += 2 + len;
+    }
+    None
+}
+
+/// `YYYY:MM:DD HH:MM:SS` as ISO 8601
+fn exif_date(value: &str) -> String {
+    match value.split_once(' ') {
+        Some((date, time)) if date.len() == 10 => format!("{}T{}", date.replace(':', "-"), time),
+        _ => value.to_string(),
+    }
+}
+
+struct Tiff<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        let little_endian = match data.get(..4)? {
+            b"II*\0" => true,
+            b"MM\0*" => false,
+            _ => return None,
+        };
+        Some(Self { data, little_endian })
+    }
+
+    fn u16(&self, at: usize) -> Option<u16> {
+        let b: [u8; 2] = self.data.get(at..at + 2)?.try_into().ok()?;
+        Some(if self.little_endian { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) })
+    }
+
+    fn u32(&self, at: usize) -> Option<u32> {
+        let b: [u8; 4] = self.data.get(at..at + 4)?.try_into().ok()?;
+        Some(if self.little_endian { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+    }
+
+    /// ASCII tags of the first image directory and its Exif sub-directory
+    fn ascii_tags(&self) -> Option<BTreeMap<u16, String>> {
+        let mut tags = BTreeMap::new();
+        let first = self.u32(4)? as usize;
+        if let Some(exif) = self.read_directory(first, &mut tags) {
+            self.read_directory(exif, &mut tags);
+        }
+        Some(tags)
+    }
+
+    /// Collects ASCII entries; returns the Exif sub-directory offset if there is one
+    fn read_directory(&self, offset: usize, tags: &mut BTreeMap<u16, String>) -> Option<usize> {
+        const ASCII: u16 = 2;
+        const EXIF_IFD: u16 = 0x8769;
+        let count = self.u16(offset)? as usize;
+        let mut exif = None;
+        for i in 0..count {
+            let entry = offset + 2 + i * 12;
+            let (Some(tag), Some(kind), Some(len)) = (self.u16(entry), self.u16(entry + 2), self.u32(entry + 4)) else {
+                break;
+            };
+            let len = len as usize;
+            if tag == EXIF_IFD {
+                exif = self.u32(entry + 8).map(|o| o as usize);
+            } else if kind == ASCII {
+                // Values up to 4 bytes are stored in the entry itself
+                let start = if len <= 4 { entry + 8 } else { self.u32(entry + 8)? as usize };
+                if let Some(raw) = self.data.get(start..start + len) {
+                    let text = String::from_utf8_lossy(raw);
+                    tags.insert(tag, text.trim_end_matches('\0').trim().to_string());
+                }
+            }
+        }
+        exif
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::docmeta::{for_file, harvest, merge, split_front_matter, MetadataSettings};
+    use crate::processors::{run_with_cap, test_config, ProcessorConfig, SpendCap, StandardProcessor};
+    use std::io::Write;
+
+    #[test]
+    fn test_front_matter_split() -> anyhow::Result<()> {
+        let text = "---\ntitle: Raport roczny\ntags: [finanse, 2024]\n---\nTreść dokumentu";
+        let (metadata, body) = split_front_matter(text)?.unwrap();
+        assert_eq!(metadata["title"], "Raport roczny");
+        assert_eq!(metadata["tags"], serde_json::json!(["finanse", 2024]));
+        assert_eq!(body, "Treść dokumentu");
+
+        // Pozioma linia bez zamknięcia to nie front-matter
+        assert!(split_front_matter("---\nzwykły tekst")?.is_none());
+        assert!(split_front_matter("---\ntitle: [\n---\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_and_merge() {
+        let settings = MetadataSettings {
+            harvest: true,
+            rename: [("author", "doc_author"), ("tags", "")].into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        };
+        let metadata = settings.rename([
+            ("author".to_string(), serde_json::json!("Anna")),
+            ("tags".to_string(), serde_json::json!(["a"])),
+            ("stage".to_string(), serde_json::json!("draft")),
+        ].into());
+        assert_eq!(metadata.keys().collect::<Vec<_>>(), vec!["doc_author", "stage"]);
+
+        let mut records = vec![crate::processors::Record {
+            instruction: String::new(),
+            prompt: String::new(),
+            completion: String::new(),
+            metadata: [("stage".to_string(), serde_json::json!("standard"))].into(),
+        }];
+        merge(&mut records, &metadata);
+        // Klucze ustawione przez procesor mają pierwszeństwo
+        assert_eq!(records[0].metadata["stage"], "standard");
+        assert_eq!(records[0].metadata["doc_author"], "Anna");
+    }
+
+    #[test]
+    fn test_docx_properties() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("doc.docx");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path)?);
+        zip.start_file("docProps/core.xml", zip::write::FileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored))?;
+        zip.write_all(br#"<cp:coreProperties><dc:title>Q&amp;A guide</dc:title><dc:creator>Jan Nowak</dc:creator><dcterms:created xsi:type="dcterms:W3CDTF">2024-03-01T10:00:00Z</dcterms:created></cp:coreProperties>"#)?;
+        zip.finish()?;
+
+        let metadata = harvest(&path)?;
+        assert_eq!(metadata["title"], "Q&A guide");
+        assert_eq!(metadata["author"], "Jan Nowak");
+        assert_eq!(metadata["created"], "2024-03-01T10:00:00Z");
+        assert_eq!(metadata.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pdf_properties() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("doc.pdf");
+        let mut pdf = b"%PDF-1.4\n1 0 obj << /Title (Old) >> endobj\n".to_vec();
+        // Aktualizacja przyrostowa: obowiązuje ostatni słownik
+        pdf.extend_from_slice(b"9 0 obj << /Title (Annual \\(draft\\) report) /Author <FEFF017B0061006B> /CreationDate (D:20240131120000+01'00') >> endobj\n");
+        std::fs::write(&path, pdf)?;
+
+        let metadata = harvest(&path)?;
+        assert_eq!(metadata["title"], "Annual (draft) report");
+        assert_eq!(metadata["author"], "Żak");
+        assert_eq!(metadata["created"], "2024-01-31T12:00:00+01:00");
+        Ok(())
+    }
+
+    #[test]
+    fn test_jpeg_exif() -> anyhow::Result<()> {
+        // TIFF (little endian): IFD0 z Make i wskaźnikiem Exif, podkatalog Exif z DateTimeOriginal
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend_from_slice(&8u32.to_le_bytes());
+        tiff.extend_from_slice(&2u16.to_le_bytes());
+        tiff.extend_from_slice(&[0x0F, 0x01, 2, 0]);
+        tiff.extend_from_slice(&4u32.to_le_bytes());
+        tiff.extend_from_slice(b"Sny\0");
+        tiff.extend_from_slice(&[0x69, 0x87, 4, 0]);
+        tiff.extend_from_slice(&1u32.to_le_bytes());
+        tiff.extend_from_slice(&38u32.to_le_bytes());
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        tiff.extend_from_slice(&1u16.to_le_bytes());
+        tiff.extend_from_slice(&[0x03, 0x90, 2, 0]);
+        tiff.extend_from_slice(&20u32.to_le_bytes());
+        tiff.extend_from_slice(&56u32.to_le_bytes());
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        tiff.extend_from_slice(b"2023:07:14 09:30:00\0");
+
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        jpeg.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+        jpeg.extend_from_slice(b"Exif\0\0");
+        jpeg.extend_from_slice(&tiff);
+        jpeg.extend_from_slice(&[0xFF, 0xDA]);
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("photo.jpg");
+        std::fs::write(&path, jpeg)?;
+        let metadata = harvest(&path)?;
+        assert_eq!(metadata["camera_make"], "Sny");
+        assert_eq!(metadata["created"], "2023-07-14T09:30:00");
+        Ok(())
+    }
+
+    #[test]
+    fn test_local_run_merges_document_metadata() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("note.md");
+        std::fs::write(&path, "---\ntitle: Notatka\nauthor: Ola\n---\nTreść")?;
+        let config = ProcessorConfig {
+            metadata: MetadataSettings {
+                harvest: true,
+                rename: [("title".to_string(), "source_title".to_string())].into(),
+            },
+            ..test_config()
+        };
+
+        let files = vec![path.to_string_lossy().to_string()];
+        let run = run_with_cap(&StandardProcessor, &files, &config, 0.0, &mut SpendCap::new(1.0));
+        let record = &run.results[0].records[0];
+        assert_eq!(record.metadata["source_title"], "Notatka");
+        assert_eq!(record.metadata["author"], "Ola");
+
+        let disabled = MetadataSettings { harvest: false, ..MetadataSettings::default() };
+        assert!(for_file(&path, &disabled).is_empty());
+        assert!(for_file(&dir.path().join("missing.md"), &MetadataSettings::default()).contains_key("metadata_error"));
+        Ok(())
+    }
+}
@@ -113,7 +113,7 @@ impl StdError for AppError {
 #[macro_export]
 macro_rules! api_error {
     ($kind:expr, $($arg:tt)*) => {
-        crate::error::AppError::Api {
+        $crate::error::AppError::Api {
             kind: $kind,
             message: format!($($arg)*),
         }
@@ -124,21 +124,21 @@ macro_rules! api_error {
 #[macro_export]
 macro_rules! processing_error {
     ($kind:expr, $message:expr) => {
-        crate::error::AppError::Processing {
+        $crate::error::AppError::Processing {
             file_id: None,
             kind: $kind,
             message: $message.to_string(),
         }
     };
     ($kind:expr, $file_id:expr, $message:expr) => {
-        crate::error::AppError::Processing {
+        $crate::error::AppError::Processing {
             file_id: Some($file_id.to_string()),
             kind: $kind,
             message: $message.to_string(),
         }
     };
     ($kind:expr, $file_id:expr, $($arg:tt)*) => {
-        crate::error::AppError::Processing {
+        $crate::error::AppError::Processing {
             file_id: Some($file_id.to_string()),
             kind: $kind,
             message: format!($($arg)*),
@@ -0,0 +1,511 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::path::Path;
+
+use crate::atomic;
+use crate::audit::ModelExchange;
+use crate::chunking;
+use crate::lineage::{self, RECORD_ID};
+use crate::processors::{self, ProcessingResult, ProcessingStats, Processor, ProcessorConfig, Record};
+
+/// Record metadata: name of the schema the record was extracted to
+pub const SCHEMA: &str = "schema";
+/// Record metadata: why a flagged record doesn't match its schema
+pub const SCHEMA_ERRORS: &str = "schema_errors";
+/// Record metadata: index of the chunk the record was extracted from
+pub const CHUNK: &str = "chunk";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+}
+
+impl FieldType {
+    pub fn next(self) -> Self {
+        match self {
+            FieldType::String => FieldType::Integer,
+            FieldType::Integer => FieldType::Number,
+            FieldType::Number => FieldType::Boolean,
+            FieldType::Boolean => FieldType::String,
+        }
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FieldType::String => "string",
+            FieldType::Integer => "integer",
+            FieldType::Number => "number",
+            FieldType::Boolean => "boolean",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    /// Tells the model what belongs in the field
+    #[serde(default)]
+    pub description: String,
+    /// Records missing a required field are invalid; missing optional fields become null
+    #[serde(default = "default_true")]
+    pub required: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// User-defined record layout from a `[schemas.<name>]` config section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordSchema {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub fields: Vec<SchemaField>,
+}
+
+/// Structured extraction from the `[extract]` config section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExtractSettings {
+    /// Schema used when neither the command line nor the preset names one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// What happens to extracted objects that don't match the schema
+    #[serde(default)]
+    pub invalid: Invalid,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Invalid {
+    /// Leave the object out of the dataset
+    #[default]
+    Drop,
+    /// Keep it with the reasons in `schema_errors`; typed exports still leave it out
+    Flag,
+}
+
+/// Typed export of the valid records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    Jsonl,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+/// Asks for every `name` record in the text as a JSON array of objects with the schema's fields
+pub fn instruction(name: &str, schema: &RecordSchema) -> String {
+    let mut text = format!(
+        "Extract every {} record from the text below. Answer with a JSON array of objects with exactly \
+         these fields; use null for values the text doesn't give.\n",
+        name
+    );
+    if let Some(description) = &schema.description {
+        text.push_str(&format!("Records are: {}\n", description));
+    }
+    for field in &schema.fields {
+        let required = if field.required { "required" } else { "optional" };
+        text.push_str(&format!("- {} ({}, {})", field.name, field.field_type, required));
+        if !field.description.is_empty() {
+            text.push_str(&format!(": {}", field.description));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Objects in the model output: a JSON array of objects or a single object,
+/// possibly wrapped in a code fence or surrounded by prose
+pub fn parse_objects(output: &str) -> std::result::Result<Vec<Map<String, Value>>, String> {
+    let start = output.find(['[', '{']).ok_or("no JSON in the response")?;
+    let end = output.rfind([']', '}']).filter(|end| *end >= start).ok_or("unterminated JSON in the response")?;
+    match serde_json::from_str(&output[start..=end]) {
+        Ok(Value::Array(items)) => items.into_iter()
+            .map(|item| match item {
+                Value::Object(object) => Ok(object),
+                other => Err(format!("expected an object, got {}", other)),
+            })
+            .collect(),
+        Ok(Value::Object(object)) => Ok(vec![object]),
+        Ok(other) => Err(format!("expected an array of objects, got {}", other)),
+        Err(err) => Err(format!("invalid JSON: {}", err)),
+    }
+}
+
+/// The object with every schema field converted to its type, and what didn't fit.
+/// Numbers and booleans given as strings are converted; fields outside the schema are left out.
+pub fn validate(object: &Map<String, Value>, schema: &RecordSchema) -> (Map<String, Value>, Vec<String>) {
+    let mut typed = Map::new();
+    let mut errors = Vec::new();
+    for field in &schema.fields {
+        let value = object.get(&field.name).unwrap_or(&Value::Null);
+        let converted = match value {
+            Value::Null if field.required => Err("missing required field".to_string()),
+            Value::Null => Ok(Value::Null),
+            value => convert(value, field.field_type).ok_or_else(|| format!("expected {}, got {}", field.field_type, value)),
+        };
+        match converted {
+            Ok(value) => {
+                typed.insert(field.name.clone(), value);
+            },
+            Err(err) => {
+                errors.push(format!("{}: {}", field.name, err));
+                typed.insert(field.name.clone(), Value::Null);
+            },
+        }
+    }
+    (typed, errors)
+}
+
+/// `value` as `field_type`; None when it can't be converted
+pub fn convert(value: &Value, field_type: FieldType) -> Option<Value> {
+    match (field_type, value) {
+        (FieldType::String, Value::String(_)) => Some(value.clone()),
+        (FieldType::String, Value::Number(n)) => Some(Value::from(n.to_string())),
+        (FieldType::String, Value::Bool(b)) => Some(Value::from(b.to_string())),
+        (FieldType::Integer, Value::Number(n)) => n.as_i64()
+            .or_else(|| n.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i64))
+            .map(Value::from),
+        (FieldType::Integer, Value::String(s)) => s.trim().parse::<i64>().ok().map(Value::from),
+        (FieldType::Number, Value::Number(n)) => n.as_f64().map(Value::from),
+        (FieldType::Number, Value::String(s)) => s.trim().parse::<f64>().ok().map(Value::from),
+        (FieldType::Boolean, Value::Bool(_)) => Some(value.clone()),
+        (FieldType::Boolean, Value::String(s)) => match s.trim().to_lowercase().as_str() {
+            "true" | "yes" => Some(Value::Bool(true)),
+            "false" | "no" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Records of a user-defined schema, extracted per chunk and validated against it
+pub struct ExtractProcessor {
+    pub name: String,
+    pub schema: RecordSchema,
+    pub invalid: Invalid,
+}
+
+impl ExtractProcessor {
+    /// Like `process_file`, also returning the number of dropped objects
+    pub fn extract(&self, file_path: &str, config: &ProcessorConfig) -> Result<(ProcessingResult, usize)> {
+        let sources = processors::read_sources(file_path, config)?;
+        let chunk_settings = config.chunking.clone().unwrap_or_default();
+        let instruction = instruction(&self.name, &self.schema);
+
+        let mut records = Vec::new();
+        let mut exchanges = Vec::new();
+        let mut total_tokens = 0;
+        let mut dropped = 0;
+        for source in sources {
+            let first = records.len();
+            for chunk in chunking::split(&source.text, &chunk_settings) {
+                let prompt = chunk.prompt(&instruction);
+                let output = mock_output(&chunk.text, &self.schema);
+                total_tokens += prompt.split_whitespace().count() + output.split_whitespace().count();
+                let mut call = Record {
+                    instruction: instruction.clone(),
+                    prompt,
+                    completion: output.clone(),
+                    metadata: Default::default(),
+                };
+                let exchange = ModelExchange::for_record(&mut call, file_path, config);
+
+                let objects = match parse_objects(&output) {
+                    Ok(objects) => objects.iter().map(|object| validate(object, &self.schema)).collect(),
+                    // An unreadable response is one invalid object
+                    Err(err) => vec![(Map::new(), vec![err])],
+                };
+                for (typed, errors) in objects {
+                    if !errors.is_empty() && self.invalid == Invalid::Drop {
+                        dropped += 1;
+                        continue;
+                    }
+                    let mut metadata = source.metadata.clone();
+                    metadata.extend(call.metadata.clone());
+                    metadata.insert(SCHEMA.to_string(), self.name.clone().into());
+                    metadata.insert(CHUNK.to_string(), chunk.index.into());
+                    if !errors.is_empty() {
+                        metadata.insert(SCHEMA_ERRORS.to_string(), errors.into());
+                    }
+                    records.push(Record {
+                        instruction: format!("Extract {} records", self.name),
+                        prompt: chunk.text.clone(),
+                        completion: Value::Object(typed).to_string(),
+                        metadata,
+                    });
+                }
+                exchanges.push(exchange);
+            }
+            lineage::link(&mut records[first..], &source.parents);
+        }
+
+        let result = ProcessingResult {
+            stats: ProcessingStats {
+                total_records: records.len(),
+                total_tokens,
+                processing_time_ms: 0,
+            },
+            records,
+            exchanges,
+            source_file: file_path.to_string(),
+            processing_type: "extract".to_string(),
+            error: None,
+        };
+        Ok((result, dropped))
+    }
+}
+
+impl Processor for ExtractProcessor {
+    fn process_file(&self, file_path: &str, config: &ProcessorConfig) -> Result<ProcessingResult> {
+        self.extract(file_path, config).map(|(result, _)| result)
+    }
+
+    fn name(&self) -> &'static str {
+        "extract"
+    }
+
+    fn description(&self) -> &'static str {
+        "Structured extraction to a user-defined record schema"
+    }
+}
+
+/// Typed objects of the records that match their schema, each with its record's id under
+/// `record_id` (unless the schema has a field of that name); flagged records are left out
+pub fn rows(results: &[ProcessingResult]) -> Vec<Map<String, Value>> {
+    results.iter()
+        .flat_map(|result| &result.records)
+        .filter(|record| !record.metadata.contains_key(SCHEMA_ERRORS))
+        .filter_map(|record| match serde_json::from_str(&record.completion) {
+            Ok(Value::Object(mut object)) => {
+                object.entry(RECORD_ID).or_insert_with(|| lineage::id_of(record).into());
+                Some(object)
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Writes `rows` as JSONL or as a Parquet file with the record id and one typed column
+/// per schema field
+pub fn export(path: &Path, schema: &RecordSchema, rows: &[Map<String, Value>], format: ExportFormat) -> Result<()> {
+    let content = match format {
+        ExportFormat::Jsonl => rows.iter().map(|row| format!("{}\n", Value::Object(row.clone()))).collect::<String>().into_bytes(),
+        ExportFormat::Parquet => {
+            let mut schema = schema.clone();
+            if !schema.fields.iter().any(|field| field.name == RECORD_ID) {
+                schema.fields.insert(0, SchemaField {
+                    name: RECORD_ID.to_string(),
+                    field_type: FieldType::String,
+                    description: String::new(),
+                    required: false,
+                });
+            }
+            to_parquet(&schema, rows)?
+        },
+    };
+    atomic::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Parquet file with one typed column per schema field; row values must already have the field's type
+#[cfg(feature = "parquet")]
+pub fn to_parquet(schema: &RecordSchema, rows: &[Map<String, Value>]) -> Result<Vec<u8>> {
+    use parquet::basic::{LogicalType, Repetition, Type as PhysicalType};
+    use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::types::Type;
+    use std::sync::Arc;
+
+    let columns = schema.fields.iter()
+        .map(|field| {
+            let physical = match field.field_type {
+                FieldType::String => PhysicalType::BYTE_ARRAY,
+                FieldType::Integer => PhysicalType::INT64,
+                FieldType::Number => PhysicalType::DOUBLE,
+                FieldType::Boolean => PhysicalType::BOOLEAN,
+            };
+            let repetition = if field.required { Repetition::REQUIRED } else { Repetition::OPTIONAL };
+            let logical = (field.field_type == FieldType::String).then_some(LogicalType::String);
+            Type::primitive_type_builder(&field.name, physical)
+                .with_repetition(repetition)
+                .with_logical_type(logical)
+                .build()
+                .map(Arc::new)
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .context("Invalid Parquet schema")?;
+    let message = Type::group_type_builder("record").with_fields(columns).build()?;
+
+    let mut buffer = Vec::new();
+    let mut writer = SerializedFileWriter::new(&mut buffer, Arc::new(message), Arc::new(WriterProperties::builder().build()))?;
+    let mut row_group = writer.next_row_group()?;
+    for field in &schema.fields {
+        let Some(mut column) = row_group.next_column()? else {
+            break;
+        };
+        let values: Vec<&Value> = rows.iter().map(|row| row.get(&field.name).unwrap_or(&Value::Null)).collect();
+        // Definition level 0 marks a null in an optional column
+        let levels: Vec<i16> = values.iter().map(|value| i16::from(!value.is_null())).collect();
+        let levels = (!field.required).then_some(levels.as_slice());
+        match field.field_type {
+            FieldType::String => {
+                let data: Vec<ByteArray> = values.iter().filter_map(|v| v.as_str()).map(ByteArray::from).collect();
+                column.typed::<ByteArrayType>().write_batch(&data, levels, None)?;
+            },
+            FieldType::Integer => {
+                let data: Vec<i64> = values.iter().filter_map(|v| v.as_i64()).collect();
+                column.typed::<Int64Type>().write_batch(&data, levels, None)?;
+            },
+            FieldType::Number => {
+                let data: Vec<f64> = values.iter().filter_map(|v| v.as_f64()).collect();
+                column.typed::<DoubleType>().write_batch(&data, levels, None)?;
+            },
+            FieldType::Boolean => {
+                let data: Vec<bool> = values.iter().filter_map(|v| v.as_bool()).collect();
+                column.typed::<BoolType>().write_batch(&data, levels, None)?;
+            },
+        }
+        column.close()?;
+    }
+    row_group.close()?;
+    writer.close()?;
+    Ok(buffer)
+}
+
+#[cfg(not(feature = "parquet"))]
+pub fn to_parquet(_schema: &RecordSchema, _rows: &[Map<String, Value>]) -> Result<Vec<u8>> {
+    anyhow::bail!("Parquet export is not available in this build; rebuild with `--features parquet`")
+}
+
+/// Mock model call: one object per chunk. Strings get the chunk's first sentence, numbers the
+/// first number in it, booleans whether the field name occurs in it.
+fn mock_output(text: &str, schema: &RecordSchema) -> String {
+    // A sentence ends at a terminator followed by whitespace, so "31.5" stays whole
+    let end = text.char_indices()
+        .find(|&(i, c)| matches!(c, '.' | '?' | '!') && text[i + 1..].chars().next().is_none_or(char::is_whitespace))
+        .map_or(text.len(), |(i, _)| i + 1);
+    let sentence = text[..end].trim();
+    let numbers: Vec<&str> = text.split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_ascii_digit()))
+        .filter(|w| !w.is_empty() && w.parse::<f64>().is_ok())
+        .collect();
+    let lower = text.to_lowercase();
+    let object: Map<String, Value> = schema.fields.iter()
+        .map(|field| {
+            let value = match field.field_type {
+                FieldType::String if !sentence.is_empty() => Value::from(sentence),
+                FieldType::Integer => numbers.iter().find(|n| n.parse::<i64>().is_ok()).map_or(Value::Null, |n| Value::from(*n)),
+                FieldType::Number => numbers.first().map_or(Value::Null, |n| Value::from(*n)),
+                FieldType::Boolean => Value::from(lower.contains(&field.name.replace('_', " ").to_lowercase())),
+                _ => Value::Null,
+            };
+            (field.name.clone(), value)
+        })
+        .collect();
+    format!("```json\n{}\n```", Value::Array(vec![Value::Object(object)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::extract::{self, ExportFormat, ExtractProcessor, FieldType, Invalid, RecordSchema, SchemaField, SCHEMA_ERRORS};
+    use crate::processors::test_config;
+    #[cfg(feature = "parquet")]
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    fn schema() -> RecordSchema {
+        let field = |name: &str, field_type, required| SchemaField {
+            name: name.to_string(),
+            field_type,
+            description: String::new(),
+            required,
+        };
+        RecordSchema {
+            description: Some("Veterinary visits".to_string()),
+            fields: vec![
+                field("summary", FieldType::String, true),
+                field("weight_kg", FieldType::Number, true),
+                field("visits", FieldType::Integer, false),
+                field("vaccinated", FieldType::Boolean, false),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_parse_and_validate_converts_types() {
+        let output = "Here you go:\n```json\n[{\"summary\": \"Checkup\", \"weight_kg\": \"12.5\", \"visits\": 3.0, \"vaccinated\": \"yes\", \"extra\": 1}]\n```";
+        let objects = extract::parse_objects(output).unwrap();
+        let (typed, errors) = extract::validate(&objects[0], &schema());
+        assert!(errors.is_empty());
+        assert_eq!(typed["weight_kg"], 12.5);
+        assert_eq!(typed["visits"], 3);
+        assert_eq!(typed["vaccinated"], true);
+        // Pola spoza schematu są pomijane
+        assert!(!typed.contains_key("extra"));
+
+        let object = serde_json::json!({ "summary": "Checkup", "visits": "many" });
+        let (typed, errors) = extract::validate(object.as_object().unwrap(), &schema());
+        assert_eq!(errors, vec![
+            "weight_kg: missing required field".to_string(),
+            "visits: expected integer, got \"many\"".to_string(),
+        ]);
+        assert!(typed["vaccinated"].is_null());
+        assert!(extract::parse_objects("no json here").is_err());
+    }
+
+    #[test]
+    fn test_extract_and_export_typed_rows() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("visit.txt");
+        std::fs::write(&path, "Rex weighed 31.5 kg at the checkup. Vaccinated against rabies.")?;
+
+        let config = test_config();
+        let processor = ExtractProcessor { name: "visit".to_string(), schema: schema(), invalid: Invalid::Flag };
+        let (result, dropped) = processor.extract(path.to_str().unwrap(), &config)?;
+        assert_eq!(dropped, 0);
+        assert_eq!(result.records.len(), 1);
+        assert!(!result.records[0].metadata.contains_key(SCHEMA_ERRORS));
+
+        let rows = extract::rows(&[result]);
+        assert_eq!(rows[0]["weight_kg"], 31.5);
+        assert_eq!(rows[0]["summary"], "Rex weighed 31.5 kg at the checkup.");
+
+        let jsonl = dir.path().join("visits.jsonl");
+        extract::export(&jsonl, &processor.schema, &rows, ExportFormat::Jsonl)?;
+        assert_eq!(std::fs::read_to_string(&jsonl)?.lines().count(), 1);
+
+        let parquet = dir.path().join("visits.parquet");
+        #[cfg(feature = "parquet")]
+        {
+            extract::export(&parquet, &processor.schema, &rows, ExportFormat::Parquet)?;
+            let reader = SerializedFileReader::new(std::fs::File::open(&parquet)?)?;
+            assert_eq!(reader.metadata().file_metadata().num_rows(), 1);
+            let columns: Vec<String> = reader.metadata().file_metadata().schema_descr().columns().iter()
+                .map(|c| c.name().to_string())
+                .collect();
+            assert_eq!(columns, vec!["record_id", "summary", "weight_kg", "visits", "vaccinated"]);
+        }
+        // Bez feature `parquet` eksport kończy się błędem z podpowiedzią
+        #[cfg(not(feature = "parquet"))]
+        assert!(extract::export(&parquet, &processor.schema, &rows, ExportFormat::Parquet).unwrap_err().to_string().contains("--features parquet"));
+        Ok(())
+    }
+}
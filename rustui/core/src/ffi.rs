@@ -0,0 +1,208 @@
+//! C ABI over the record pipeline, declared in `include/anydataset.h`.
+//!
+//! Values cross the boundary as NUL-terminated UTF-8 JSON. A function returns a string the
+//! caller releases with `anydataset_string_free`, or NULL on failure; the reason is then
+//! available from `anydataset_last_error` on the same thread. Panics are caught and reported
+//! as errors, never unwound into the caller.
+
+use anyhow::{anyhow, Context, Result};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::checks::{CheckSettings, Checks};
+use crate::formats::{self, DatasetFormat};
+use crate::processors::{self, ProcessingResult, ProcessorConfig};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Processes one file with a local processor, as a local run does.
+///
+/// `processing_type` is "standard", "article", "translate" or "batch"; `config_json` is a
+/// `ProcessorConfig` object. Returns the `ProcessingResult` as JSON.
+///
+/// # Safety
+///
+/// Every argument must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn anydataset_process_file(
+    path: *const c_char,
+    processing_type: *const c_char,
+    config_json: *const c_char,
+) -> *mut c_char {
+    call(|| {
+        let path = required(path, "path")?;
+        let processor = processors::get_processor(required(processing_type, "processing_type")?)?;
+        let config: ProcessorConfig = serde_json::from_str(required(config_json, "config_json")?)
+            .context("config_json is not a processor config")?;
+        let result = processor.process_file(path, &config)?;
+        Ok(serde_json::to_string(&result)?)
+    })
+}
+
+/// Converts a dataset (a JSON array of processing results) to "json", "alpaca" or
+/// "sharegpt". `system_prompt` may be NULL.
+///
+/// # Safety
+///
+/// Every argument must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn anydataset_convert_format(
+    results_json: *const c_char,
+    format: *const c_char,
+    system_prompt: *const c_char,
+) -> *mut c_char {
+    call(|| {
+        let results = dataset(required(results_json, "results_json")?)?;
+        let name = required(format, "format")?;
+        let format = DatasetFormat::ALL.into_iter()
+            .find(|f| f.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| anyhow!("Unknown dataset format '{}'", name))?;
+        let system_prompt = optional(system_prompt, "system_prompt")?;
+        Ok(serde_json::to_string(&formats::convert(&results, format, system_prompt)?)?)
+    })
+}
+
+/// Runs the quality checks of `checks_json` (a `[checks]` section as JSON) over a dataset,
+/// as `anydataset-tui check` does. Returns the dataset with the issues in each flagged
+/// record's `check_issues` metadata.
+///
+/// # Safety
+///
+/// Every argument must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn anydataset_validate_dataset(
+    results_json: *const c_char,
+    checks_json: *const c_char,
+) -> *mut c_char {
+    call(|| {
+        let mut results = dataset(required(results_json, "results_json")?)?;
+        let settings: CheckSettings = serde_json::from_str(required(checks_json, "checks_json")?)
+            .context("checks_json is not a checks section")?;
+        if settings.is_empty() {
+            anyhow::bail!("No checks configured");
+        }
+        Checks::load(&settings)?.apply(&mut results);
+        Ok(serde_json::to_string(&results)?)
+    })
+}
+
+/// Why the last call on this thread returned NULL, or NULL after a successful one. The
+/// string stays valid until the next call on the thread and must not be freed.
+#[no_mangle]
+pub extern "C" fn anydataset_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Releases a string returned by this library; NULL is ignored.
+///
+/// # Safety
+///
+/// `s` must be NULL or a string returned by this library that wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn anydataset_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+fn call(f: impl FnOnce() -> Result<String>) -> *mut c_char {
+    let outcome = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let reason = payload.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown error".to_string());
+        Err(anyhow!("panicked: {}", reason))
+    });
+    let (value, error) = match outcome.and_then(|json| CString::new(json).context("Output contains a NUL byte")) {
+        Ok(json) => (json.into_raw(), None),
+        Err(err) => (ptr::null_mut(), Some(format!("{:#}", err))),
+    };
+    let error = error.map(|message| CString::new(message.replace('\0', " ")).unwrap_or_default());
+    LAST_ERROR.with(|last| *last.borrow_mut() = error);
+    value
+}
+
+unsafe fn optional<'a>(s: *const c_char, name: &str) -> Result<Option<&'a str>> {
+    if s.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(s).to_str().map(Some).with_context(|| format!("{} is not UTF-8", name))
+}
+
+unsafe fn required<'a>(s: *const c_char, name: &str) -> Result<&'a str> {
+    optional(s, name)?.ok_or_else(|| anyhow!("{} is NULL", name))
+}
+
+fn dataset(json: &str) -> Result<Vec<ProcessingResult>> {
+    serde_json::from_str(json).context("results_json is not an array of processing results")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ffi::*;
+    use serde_json::{json, Value};
+    use std::ffi::{c_char, CStr, CString};
+
+    /// Zwraca wynik wywołania jako JSON albo komunikat błędu z `anydataset_last_error`
+    fn take(result: *mut c_char) -> Result<Value, String> {
+        if result.is_null() {
+            let error = unsafe { CStr::from_ptr(anydataset_last_error()) };
+            return Err(error.to_string_lossy().into_owned());
+        }
+        assert!(anydataset_last_error().is_null());
+        let json = unsafe { CStr::from_ptr(result) }.to_str().unwrap().to_string();
+        unsafe { anydataset_string_free(result) };
+        Ok(serde_json::from_str(&json).unwrap())
+    }
+
+    fn c(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    #[test]
+    fn test_process_convert_and_validate() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("note.txt");
+        std::fs::write(&file, "The clinic opened in 1998 and treats 40 patients a day.")?;
+        let config = json!({
+            "model": "test-model", "provider": "test-provider", "language": "en",
+            "keywords": [], "add_reasoning": false, "output_format": "json"
+        });
+
+        let result = take(unsafe { anydataset_process_file(
+            c(file.to_str().unwrap()).as_ptr(), c("standard").as_ptr(), c(&config.to_string()).as_ptr(),
+        ) }).unwrap();
+        assert_eq!(result["processing_type"], "standard");
+        assert!(!result["records"].as_array().unwrap().is_empty());
+
+        let dataset = c(&json!([result]).to_string());
+        let alpaca = take(unsafe { anydataset_convert_format(dataset.as_ptr(), c("Alpaca").as_ptr(), std::ptr::null()) }).unwrap();
+        assert!(alpaca[0].get("instruction").is_some());
+
+        let checked = take(unsafe { anydataset_validate_dataset(dataset.as_ptr(), c(r#"{"numbers": true}"#).as_ptr()) }).unwrap();
+        assert_eq!(checked[0]["records"].as_array().unwrap().len(), result["records"].as_array().unwrap().len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        let error = take(unsafe { anydataset_process_file(c("x.txt").as_ptr(), std::ptr::null(), c("{}").as_ptr()) }).unwrap_err();
+        assert_eq!(error, "processing_type is NULL");
+
+        let error = take(unsafe { anydataset_convert_format(c("[]").as_ptr(), c("csv").as_ptr(), std::ptr::null()) }).unwrap_err();
+        assert_eq!(error, "Unknown dataset format 'csv'");
+
+        let error = take(unsafe { anydataset_validate_dataset(c("{}").as_ptr(), c("{}").as_ptr()) }).unwrap_err();
+        assert!(error.starts_with("results_json is not an array of processing results"));
+
+        let error = take(unsafe { anydataset_validate_dataset(c("[]").as_ptr(), c("{}").as_ptr()) }).unwrap_err();
+        assert_eq!(error, "No checks configured");
+
+        // Udane wywołanie czyści błąd
+        assert!(take(unsafe { anydataset_convert_format(c("[]").as_ptr(), c("json").as_ptr(), std::ptr::null()) }).is_ok());
+        unsafe { anydataset_string_free(std::ptr::null_mut()) };
+    }
+}
@@ -0,0 +1,350 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::network;
+
+/// Vision-model captions from the `[figures]` config section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FigureSettings {
+    /// Base URL of an OpenAI-compatible API with image input
+    #[serde(default = "default_endpoint")]
+    pub endpoint: String,
+    #[serde(default = "default_model")]
+    pub model: String,
+    /// Environment variable holding the API key
+    #[serde(default = "default_api_key_env")]
+    pub api_key_env: String,
+    #[serde(default = "default_prompt")]
+    pub prompt: String,
+}
+
+fn default_endpoint() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+fn default_model() -> String {
+    "gpt-4o-mini".to_string()
+}
+
+fn default_api_key_env() -> String {
+    "OPENAI_API_KEY".to_string()
+}
+
+fn default_prompt() -> String {
+    "Describe this figure in one or two sentences for a reader who cannot see it. \
+     Include any numbers, labels or trends it shows.".to_string()
+}
+
+impl Default for FigureSettings {
+    fn default() -> Self {
+        Self {
+            endpoint: default_endpoint(),
+            model: default_model(),
+            api_key_env: default_api_key_env(),
+            prompt: default_prompt(),
+        }
+    }
+}
+
+/// Image reference found in a document: `![alt](target)` or `<img src=... alt=...>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FigureRef {
+    /// Byte range of the whole reference in the text
+    pub start: usize,
+    pub end: usize,
+    pub target: String,
+    pub alt: String,
+}
+
+/// Document text with its figures replaced by `[Figure N: caption]` placeholders
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptionedText {
+    pub text: String,
+    pub figures: usize,
+    /// Figures that fell back to their alt text, with the reason
+    pub errors: Vec<String>,
+}
+
+/// Image references in order of appearance
+pub fn find(text: &str) -> Vec<FigureRef> {
+    let mut figures = find_markdown(text);
+    figures.extend(find_html(text));
+    figures.sort_by_key(|f| f.start);
+    figures
+}
+
+fn find_markdown(text: &str) -> Vec<FigureRef> {
+    let mut figures = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = text[pos..].find("![") {
+        let start = pos + offset;
+        let parsed = text[start + 2..].find("](").and_then(|alt_len| {
+            let alt = &text[start + 2..start + 2 + alt_len];
+            let target_start = start + 2 + alt_len + 2;
+            let target_len = text[target_start..].find(')')?;
+            // Alt text and targets don't span lines; anything else isn't an image
+            let target = &text[target_start..target_start + target_len];
+            if alt.contains('\n') || target.contains('\n') {
+                return None;
+            }
+            // Drop an optional title: ![alt](path "title")
+            let target = target.split_whitespace().next().unwrap_or_default();
+            Some(FigureRef {
+                start,
+                end: target_start + target_len + 1,
+                target: target.trim_matches(|c| c == '<' || c == '>').to_string(),
+                alt: alt.trim().to_string(),
+            })
+        });
+        match parsed {
+            Some(figure) => {
+                pos = figure.end;
+                figures.push(figure);
+            },
+            None => pos = start + 2,
+        }
+    }
+    figures
+}
+
+fn find_html(text: &str) -> Vec<FigureRef> {
+    let lower = text.to_ascii_lowercase();
+    let mut figures = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = lower[pos..].find("<img") {
+        let start = pos + offset;
+        let Some(len) = text[start..].find('>') else {
+            break;
+        };
+        let end = start + len + 1;
+        let tag = &text[start..end];
+        if let Some(target) = attribute(tag, "src") {
+            figures.push(FigureRef {
+                start,
+                end,
+                target,
+                alt: attribute(tag, "alt").unwrap_or_default(),
+            });
+        }
+        pos = end;
+    }
+    figures
+}
+
+/// Quoted value of an HTML attribute
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut pos = 0;
+    while let Some(offset) = lower[pos..].find(name) {
+        let start = pos + offset;
+        pos = start + name.len();
+        // Skip matches inside other attribute names, e.g. `data-src`
+        if !lower[..start].ends_with(char::is_whitespace) {
+            continue;
+        }
+        let rest = tag[pos..].trim_start().strip_prefix('=')?.trim_start();
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &rest[1..];
+        return value.find(quote).map(|len| value[..len].to_string());
+    }
+    None
+}
+
+/// Replaces each figure with `[Figure N: caption]`, using the alt text where there is no caption
+pub fn replace(text: &str, figures: &[FigureRef], captions: &[Option<String>]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    for (i, figure) in figures.iter().enumerate() {
+        out.push_str(&text[pos..figure.start]);
+        let caption = captions.get(i).cloned().flatten()
+            .or_else(|| Some(figure.alt.clone()).filter(|alt| !alt.is_empty()));
+        match caption {
+            Some(caption) => out.push_str(&format!("[Figure {}: {}]", i + 1, caption.trim())),
+            None => out.push_str(&format!("[Figure {}]", i + 1)),
+        }
+        pos = figure.end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+/// Captions every figure in `text` with the vision model. Relative image paths are
+/// resolved against `base_dir`. Figures that can't be captioned keep their alt text.
+pub fn caption_text(text: &str, base_dir: &Path, settings: &FigureSettings) -> CaptionedText {
+    let figures = find(text);
+    if figures.is_empty() {
+        return CaptionedText { text: text.to_string(), figures: 0, errors: Vec::new() };
+    }
+
+    let results: Vec<Result<String>> = match std::env::var(&settings.api_key_env) {
+        Ok(api_key) => {
+            // Processors are called both from plain threads and from async code,
+            // so the requests run on a thread with a runtime of their own
+            std::thread::scope(|scope| {
+                scope.spawn(|| caption_all(&figures, base_dir, settings, &api_key))
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("Captioning thread panicked")))
+            })
+            .unwrap_or_else(|err| figures.iter().map(|_| Err(anyhow::anyhow!("{}", err))).collect())
+        },
+        Err(_) => figures.iter()
+            .map(|_| Err(anyhow::anyhow!("{} is not set", settings.api_key_env)))
+            .collect(),
+    };
+
+    let mut errors = Vec::new();
+    let captions: Vec<Option<String>> = results.into_iter().zip(&figures)
+        .map(|(result, figure)| result
+            .map_err(|err| errors.push(format!("{}: {:#}", figure.target, err)))
+            .ok())
+        .collect();
+    CaptionedText {
+        text: replace(text, &figures, &captions),
+        figures: figures.len(),
+        errors,
+    }
+}
+
+fn caption_all(figures: &[FigureRef], base_dir: &Path, settings: &FigureSettings, api_key: &str) -> Result<Vec<Result<String>>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start captioning runtime")?;
+    let client = network::client();
+    Ok(runtime.block_on(async {
+        let mut captions = Vec::with_capacity(figures.len());
+        for figure in figures {
+            let caption = match image_url(&figure.target, base_dir) {
+                Ok(url) => caption(&client, settings, api_key, &url, &figure.alt).await,
+                Err(err) => Err(err),
+            };
+            captions.push(caption);
+        }
+        captions
+    }))
+}
+
+/// URL the model can fetch the image from; local files are inlined as data URLs
+fn image_url(target: &str, base_dir: &Path) -> Result<String> {
+    if ["http://", "https://", "data:"].iter().any(|scheme| target.starts_with(scheme)) {
+        return Ok(target.to_string());
+    }
+    let path = base_dir.join(target);
+    let extension = path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mime = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        _ => anyhow::bail!("Unsupported image format"),
+    };
+    let bytes = std::fs::read(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(format!("data:{};base64,{}", mime, base64::engine::general_purpose::STANDARD.encode(bytes)))
+}
+
+async fn caption(client: &reqwest::Client, settings: &FigureSettings, api_key: &str, url: &str, alt: &str) -> Result<String> {
+    let mut prompt = settings.prompt.clone();
+    if !alt.is_empty() {
+        prompt.push_str(&format!("\nThe document's alt text for it is: {}", alt));
+    }
+    let body = serde_json::json!({
+        "model": settings.model,
+        "max_tokens": 150,
+        "messages": [{
+            "role": "user",
+            "content": [
+                { "type": "text", "text": prompt },
+                { "type": "image_url", "image_url": { "url": url } },
+            ],
+        }],
+    });
+    let response = client
+        .post(format!("{}/chat/completions", settings.endpoint.trim_end_matches('/')))
+        .bearer_auth(api_key)
+        .json(&body)
+        .timeout(Duration::from_secs(60))
+        .send()
+        .await
+        .context("Failed to reach the vision model")?;
+    if !response.status().is_success() {
+        anyhow::bail!("Vision model returned {}", response.status());
+    }
+    let reply: serde_json::Value = response.json().await.context("Invalid vision model response")?;
+    reply["choices"][0]["message"]["content"].as_str()
+        .map(|caption| caption.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|caption| !caption.is_empty())
+        .context("Vision model returned no caption")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::figures::{caption_text, find, replace, FigureSettings};
+
+    #[test]
+    fn test_find_markdown_and_html_images() {
+        let text = "Intro ![Sales chart](img/sales.png \"Q3\") and <IMG alt='Logo' data-src=\"x\" src=\"logo.jpg\"> end. Not an image: ![broken\n](x)";
+        let figures = find(text);
+        assert_eq!(figures.len(), 2);
+        assert_eq!((figures[0].target.as_str(), figures[0].alt.as_str()), ("img/sales.png", "Sales chart"));
+        assert_eq!((figures[1].target.as_str(), figures[1].alt.as_str()), ("logo.jpg", "Logo"));
+        assert_eq!(&text[figures[0].start..figures[0].end], "![Sales chart](img/sales.png \"Q3\")");
+    }
+
+    #[test]
+    fn test_replace_with_captions_and_alt_fallback() {
+        let text = "A ![chart](a.png) B ![](b.png) C ![logo](c.png)";
+        let figures = find(text);
+        let captions = vec![Some("Bar chart of sales by month".to_string()), None, None];
+        assert_eq!(
+            replace(text, &figures, &captions),
+            "A [Figure 1: Bar chart of sales by month] B [Figure 2] C [Figure 3: logo]"
+        );
+    }
+
+    #[test]
+    fn test_caption_text_with_vision_model() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("chart.png"), b"\x89PNG")?;
+
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/v1/chat/completions")
+            .match_header("authorization", "Bearer secret")
+            .match_body(mockito::Matcher::Regex("data:image/png;base64,".to_string()))
+            .with_status(200)
+            .with_body(r#"{"choices":[{"message":{"content":"Revenue doubled\nin 2024."}}]}"#)
+            .create();
+
+        std::env::set_var("ANYDATASET_TEST_VISION_KEY", "secret");
+        let settings = FigureSettings {
+            endpoint: format!("{}/v1", server.url()),
+            api_key_env: "ANYDATASET_TEST_VISION_KEY".to_string(),
+            ..FigureSettings::default()
+        };
+        // Drugi obrazek nie istnieje - zostaje tekst alternatywny
+        let captioned = caption_text("See ![chart](chart.png) and ![map](missing.png).", dir.path(), &settings);
+
+        mock.assert();
+        assert_eq!(captioned.text, "See [Figure 1: Revenue doubled in 2024.] and [Figure 2: map].");
+        assert_eq!(captioned.figures, 2);
+        assert_eq!(captioned.errors.len(), 1);
+        assert!(captioned.errors[0].starts_with("missing.png: Failed to read"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_caption_text_without_api_key() {
+        let settings = FigureSettings {
+            api_key_env: "ANYDATASET_TEST_UNSET_KEY".to_string(),
+            ..FigureSettings::default()
+        };
+        let captioned = caption_text("![Diagram of the pipeline](p.png)", std::path::Path::new("."), &settings);
+        assert_eq!(captioned.text, "[Figure 1: Diagram of the pipeline]");
+        assert_eq!(captioned.errors, vec!["p.png: ANYDATASET_TEST_UNSET_KEY is not set".to_string()]);
+    }
+}
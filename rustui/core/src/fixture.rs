@@ -0,0 +1,344 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::atomic;
+use crate::audit::ModelExchange;
+use crate::config::Config;
+use crate::credentials;
+use crate::history::HistoryEntry;
+use crate::processors::ProcessingResult;
+use crate::transcript::{self, TranscriptEntry, REDACTED};
+
+/// Where fixtures go unless `--dir` says otherwise, relative to the repository root
+pub const DEFAULT_DIR: &str = "tests/fixtures";
+
+/// Phone numbers need at least this many digits; shorter runs are counts, prices or dates
+const MIN_PHONE_DIGITS: usize = 9;
+
+/// Replaces personal data in text taken from a real run: given terms, e-mail addresses,
+/// international phone numbers and API keys. The same address always gets the same
+/// placeholder, so records that matched before still match.
+#[derive(Debug, Clone, Default)]
+pub struct Anonymizer {
+    /// Literal replacements, longest first so that a path goes before its directory
+    replacements: Vec<(String, String)>,
+    secrets: Vec<String>,
+    emails: BTreeMap<String, String>,
+}
+
+impl Anonymizer {
+    pub fn new(secrets: Vec<String>) -> Self {
+        Self { secrets, ..Default::default() }
+    }
+
+    /// For a run of `entry`: the home directory, the user name, the profile variables the
+    /// prompts were rendered with and the provider's key are hidden, as are `terms`
+    pub fn for_run(config: &Config, entry: &HistoryEntry, terms: &[String]) -> Self {
+        let secrets = match config.custom_provider(&entry.provider) {
+            Some(custom) => custom.api_key(&entry.provider),
+            None => credentials::get(&entry.provider).map(|(key, _)| key),
+        };
+        let mut anonymizer = Self::new(secrets.into_iter().collect());
+        if let Some(home) = dirs::home_dir() {
+            anonymizer.replace(&home.to_string_lossy(), "~");
+        }
+        if let Some(user) = config.effective_username() {
+            anonymizer.replace(&user, "user");
+        }
+        for (name, value) in &entry.variables {
+            anonymizer.replace(value, &format!("{{{{{}}}}}", name));
+        }
+        for term in terms {
+            anonymizer.replace(term, REDACTED);
+        }
+        anonymizer
+    }
+
+    pub fn replace(&mut self, from: &str, to: &str) {
+        // One- and two-letter terms would hit inside ordinary words
+        if from.trim().chars().count() < 3 {
+            return;
+        }
+        self.replacements.push((from.to_string(), to.to_string()));
+        self.replacements.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+    }
+
+    pub fn text(&mut self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (from, to) in &self.replacements {
+            text = text.replace(from.as_str(), to);
+        }
+        let text = self.hide_emails(&text);
+        transcript::redact(&hide_phones(&text), &self.secrets)
+    }
+
+    pub fn value(&mut self, value: Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.text(&s)),
+            Value::Array(items) => Value::Array(items.into_iter().map(|v| self.value(v)).collect()),
+            Value::Object(map) => Value::Object(map.into_iter().map(|(k, v)| (k, self.value(v))).collect()),
+            other => other,
+        }
+    }
+
+    fn hide_emails(&mut self, text: &str) -> String {
+        let is_local = |c: char| c.is_alphanumeric() || "._%+-".contains(c);
+        let is_domain = |c: char| c.is_alphanumeric() || c == '.' || c == '-';
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(at) = rest.find('@') {
+            let start = rest[..at].char_indices().rev().find(|(_, c)| !is_local(*c)).map_or(0, |(i, c)| i + c.len_utf8());
+            let end = rest[at + 1..].find(|c: char| !is_domain(c)).map_or(rest.len(), |i| at + 1 + i);
+            let domain = rest[at + 1..end].trim_end_matches('.');
+            let end = at + 1 + domain.len();
+            if start == at || !domain.contains('.') {
+                out.push_str(&rest[..=at]);
+                rest = &rest[at + 1..];
+                continue;
+            }
+            let next = self.emails.len() + 1;
+            let placeholder = self.emails.entry(rest[start..end].to_lowercase())
+                .or_insert_with(|| format!("person{}@example.com", next));
+            out.push_str(&rest[..start]);
+            out.push_str(placeholder);
+            rest = &rest[end..];
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+/// `+48 601 234 567`-style numbers with their digits zeroed, so the layout stays
+fn hide_phones(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(plus) = rest.find('+') {
+        let tail = &rest[plus + 1..];
+        let len = tail.find(|c: char| !(c.is_ascii_digit() || " -().".contains(c))).unwrap_or(tail.len());
+        let number = tail[..len].trim_end_matches(|c: char| !c.is_ascii_digit());
+        out.push_str(&rest[..=plus]);
+        if tail.starts_with(|c: char| c.is_ascii_digit()) && number.chars().filter(char::is_ascii_digit).count() >= MIN_PHONE_DIGITS {
+            out.extend(number.chars().map(|c| if c.is_ascii_digit() { '0' } else { c }));
+            rest = &tail[number.len()..];
+        } else {
+            rest = tail;
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// What the fixture says about the run it came from
+#[derive(Debug, Clone, Serialize)]
+pub struct FixtureJob {
+    pub job_id: String,
+    pub processing_type: String,
+    pub provider: String,
+    pub model: String,
+    pub status: String,
+    /// Input files, as named under `inputs/`
+    pub inputs: Vec<String>,
+}
+
+/// Files written for one fixture and what was left out
+#[derive(Debug, Clone, Default)]
+pub struct FixtureReport {
+    pub dir: PathBuf,
+    pub files: Vec<PathBuf>,
+    pub notes: Vec<String>,
+}
+
+/// A recorded run: the job, its model calls, the files it read and the dataset it wrote
+pub struct Recording<'a> {
+    pub entry: &'a HistoryEntry,
+    pub exchanges: &'a [ModelExchange],
+    /// Content of the dataset the run produced, if it's still around
+    pub dataset: Option<Vec<u8>>,
+}
+
+/// Writes `<dir>/<name>/` with `fixture.json` (the job and its model calls as sent on the
+/// wire), `inputs/` and `expected.json`, all passed through `anonymizer`
+pub fn export(recording: &Recording, config: &Config, dir: &Path, name: &str, anonymizer: &mut Anonymizer) -> Result<FixtureReport> {
+    let dir = dir.join(name);
+    if dir.exists() {
+        anyhow::bail!("{} already exists; pick another --name or remove it", dir.display());
+    }
+    let mut report = FixtureReport { dir: dir.clone(), ..Default::default() };
+    let dataset: Option<Value> = recording.dataset.as_deref().map(serde_json::from_slice).transpose()
+        .context("The dataset of the run is not JSON")?;
+
+    // Source files of the model calls and of the results, else the submitted file
+    let mut sources = BTreeSet::new();
+    sources.extend(recording.exchanges.iter().map(|exchange| exchange.source_file.clone()));
+    if let Some(results) = dataset.clone().and_then(|d| serde_json::from_value::<Vec<ProcessingResult>>(d).ok()) {
+        sources.extend(results.into_iter().map(|result| result.source_file));
+    }
+    if sources.is_empty() && Path::new(&recording.entry.file).is_file() {
+        sources.insert(recording.entry.file.clone());
+    }
+
+    let mut inputs = Vec::new();
+    let mut names = BTreeSet::new();
+    for source in sources.iter().filter(|source| !source.is_empty()) {
+        let file_name = Path::new(source).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| source.clone());
+        let mut fixture_name = file_name.clone();
+        let mut n = 1;
+        while !names.insert(fixture_name.clone()) {
+            n += 1;
+            fixture_name = format!("{}-{}", n, file_name);
+        }
+        let relative = format!("inputs/{}", fixture_name);
+        anonymizer.replace(source, &relative);
+        inputs.push((source.clone(), fixture_name));
+    }
+
+    let mut copied = Vec::new();
+    for (source, fixture_name) in &inputs {
+        match std::fs::read(source).map(String::from_utf8) {
+            Ok(Ok(text)) => {
+                let path = dir.join("inputs").join(fixture_name);
+                write(&path, anonymizer.text(&text))?;
+                report.files.push(path);
+                copied.push(format!("inputs/{}", fixture_name));
+            },
+            Ok(Err(_)) => report.notes.push(format!(
+                "{}: binary file not copied; add it by hand if it holds no personal data", source
+            )),
+            Err(err) => report.notes.push(format!("{}: not copied ({})", source, err)),
+        }
+    }
+
+    let exchanges: Vec<TranscriptEntry> = recording.exchanges.iter()
+        .map(|exchange| TranscriptEntry::from_exchange(&recording.entry.job_id, exchange, config, &anonymizer.secrets))
+        .collect();
+    let job = FixtureJob {
+        job_id: recording.entry.job_id.clone(),
+        processing_type: recording.entry.processing_type.clone(),
+        provider: recording.entry.provider.clone(),
+        model: recording.entry.model.clone(),
+        status: recording.entry.status.clone(),
+        inputs: copied,
+    };
+    let fixture = serde_json::json!({ "job": job, "exchanges": exchanges });
+    let path = dir.join("fixture.json");
+    write(&path, serde_json::to_string_pretty(&anonymizer.value(fixture))?)?;
+    report.files.push(path);
+    if recording.exchanges.is_empty() {
+        report.notes.push("No model calls recorded; turn on [audit] before the run to capture them".to_string());
+    }
+
+    match dataset {
+        Some(dataset) => {
+            let path = dir.join("expected.json");
+            write(&path, serde_json::to_string_pretty(&anonymizer.value(dataset))?)?;
+            report.files.push(path);
+        },
+        None => report.notes.push("No dataset found; pass --dataset to add expected.json".to_string()),
+    }
+    Ok(report)
+}
+
+fn write(path: &Path, content: String) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    atomic::write(path, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audit::ModelExchange;
+    use crate::config::Config;
+    use crate::fixture::{self, Anonymizer, Recording};
+    use crate::history::HistoryEntry;
+
+    #[test]
+    fn test_anonymizer() {
+        let mut anonymizer = Anonymizer::new(vec!["local-secret-token".to_string()]);
+        anonymizer.replace("Jan Kowalski", "[redacted]");
+        anonymizer.replace("JK", "[redacted]");
+
+        let text = anonymizer.text(
+            "Jan Kowalski (JK), jan.kowalski@klinika.pl, tel. +48 601 234 567, token local-secret-token, \
+                 key sk-abcdefghijklmnopqrstuvwxyz, 3 @ 12 zł, 2024-05-01"
+        );
+        assert_eq!(
+            text,
+            "[redacted] (JK), person1@example.com, tel. +00 000 000 000, token [redacted], \
+                 key [redacted], 3 @ 12 zł, 2024-05-01"
+        );
+        // Ten sam adres dostaje ten sam zamiennik, także w JSON-ie
+        let value = anonymizer.value(serde_json::json!({ "to": ["Jan.Kowalski@klinika.pl", "biuro@firma.com"], "n": 5 }));
+        assert_eq!(value, serde_json::json!({ "to": ["person1@example.com", "person2@example.com"], "n": 5 }));
+    }
+
+    #[test]
+    fn test_export_fixture() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let source = dir.path().join("ulotka.txt");
+        std::fs::write(&source, "Kontakt: rejestracja@klinika.pl, Klinika Główna")?;
+        let source = source.to_string_lossy().into_owned();
+        let mut entry = HistoryEntry {
+            job_id: "job-1".to_string(),
+            file: source.clone(),
+            processing_type: "standard".to_string(),
+            provider: "openai".to_string(),
+            model: "gpt-4o".to_string(),
+            status: "completed".to_string(),
+            submitted_at: chrono::Utc::now(),
+            finished_at: None,
+            records: None,
+            tokens: None,
+            cost_usd: None,
+            variables: Default::default(),
+        };
+        entry.variables.insert("clinic_name".to_string(), "Klinika Główna".to_string());
+        let exchanges = vec![ModelExchange {
+            id: "ex-1".to_string(),
+            source_file: source.clone(),
+            provider: "openai".to_string(),
+            model: "gpt-4o".to_string(),
+            system_prompt: Some("Odpowiadasz w imieniu: Klinika Główna".to_string()),
+            prompt: "Kontakt: rejestracja@klinika.pl".to_string(),
+            raw_response: "Napisz na rejestracja@klinika.pl".to_string(),
+            at: chrono::Utc::now(),
+        }];
+        let dataset = serde_json::json!([{
+            "records": [{ "instruction": "i", "prompt": "rejestracja@klinika.pl", "completion": "c", "metadata": {} }],
+            "exchanges": [],
+            "source_file": source,
+            "processing_type": "standard",
+            "stats": { "total_records": 1, "total_tokens": 10, "processing_time_ms": 5 }
+        }]);
+        let recording = Recording { entry: &entry, exchanges: &exchanges, dataset: Some(serde_json::to_vec(&dataset)?) };
+        let config = Config::default();
+        let fixtures = dir.path().join("fixtures");
+        let mut anonymizer = Anonymizer::for_run(&config, &entry, &[]);
+        let report = fixture::export(&recording, &config, &fixtures, "ulotka", &mut anonymizer)?;
+        assert!(report.notes.is_empty(), "{:?}", report.notes);
+        assert_eq!(report.files.len(), 3);
+
+        let root = fixtures.join("ulotka");
+        assert_eq!(
+            std::fs::read_to_string(root.join("inputs/ulotka.txt"))?,
+            "Kontakt: person1@example.com, {{clinic_name}}"
+        );
+        let fixture: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(root.join("fixture.json"))?)?;
+        assert_eq!(fixture["job"]["inputs"], serde_json::json!(["inputs/ulotka.txt"]));
+        let exchange = &fixture["exchanges"][0];
+        assert_eq!(exchange["source_file"], "inputs/ulotka.txt");
+        assert_eq!(exchange["url"], "https://api.openai.com/v1/chat/completions");
+        assert_eq!(exchange["response"], "Napisz na person1@example.com");
+        assert_eq!(exchange["request"]["messages"][0]["content"], "Odpowiadasz w imieniu: {{clinic_name}}");
+        let expected: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(root.join("expected.json"))?)?;
+        assert_eq!(expected[0]["source_file"], "inputs/ulotka.txt");
+        assert_eq!(expected[0]["records"][0]["prompt"], "person1@example.com");
+
+        // Istniejący fixture nie jest nadpisywany
+        assert!(fixture::export(&recording, &config, &fixtures, "ulotka", &mut anonymizer).is_err());
+        Ok(())
+    }
+}
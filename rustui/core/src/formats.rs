@@ -0,0 +1,204 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::lineage;
+use crate::processors::{ProcessingResult, Record};
+
+/// Layout of the records in a dataset file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetFormat {
+    /// Processing results as produced, with metadata
+    #[default]
+    Json,
+    /// `instruction`/`input`/`output` objects
+    Alpaca,
+    /// `conversations` of `system`/`human`/`gpt` turns
+    Sharegpt,
+}
+
+impl DatasetFormat {
+    pub const ALL: [DatasetFormat; 3] = [DatasetFormat::Json, DatasetFormat::Alpaca, DatasetFormat::Sharegpt];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DatasetFormat::Json => "json",
+            DatasetFormat::Alpaca => "alpaca",
+            DatasetFormat::Sharegpt => "sharegpt",
+        }
+    }
+
+    /// Unknown names read as the default, as older configs only knew "json"
+    pub fn from_name(name: &str) -> Self {
+        Self::ALL.into_iter().find(|f| f.name().eq_ignore_ascii_case(name)).unwrap_or_default()
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|f| *f == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    /// Where the converted copy of `dataset` goes: `report.json` -> `report.alpaca.json`
+    pub fn path_for(&self, dataset: &Path) -> PathBuf {
+        dataset.with_extension(format!("{}.json", self.name()))
+    }
+}
+
+impl fmt::Display for DatasetFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Alpaca entry: the instruction, the prompt as input and the completion as output
+pub fn alpaca(record: &Record, system_prompt: Option<&str>) -> Value {
+    let mut entry = json!({
+        "instruction": record.instruction,
+        "input": record.prompt,
+        "output": record.completion,
+    });
+    if let Some(system) = system_prompt {
+        entry["system"] = Value::from(system);
+    }
+    entry
+}
+
+/// ShareGPT conversation: the instruction and prompt as the human turn, the completion as
+/// the model's; the record id is kept so entries can be traced back
+pub fn sharegpt(record: &Record, system_prompt: Option<&str>) -> Value {
+    let mut conversations = Vec::new();
+    if let Some(system) = system_prompt {
+        conversations.push(json!({ "from": "system", "value": system }));
+    }
+    let human = match (record.instruction.trim(), record.prompt.trim()) {
+        (instruction, "") => instruction.to_string(),
+        ("", prompt) => prompt.to_string(),
+        (instruction, prompt) => format!("{}\n\n{}", instruction, prompt),
+    };
+    conversations.push(json!({ "from": "human", "value": human }));
+    conversations.push(json!({ "from": "gpt", "value": record.completion }));
+    json!({ "id": lineage::id_of(record), "conversations": conversations })
+}
+
+/// All records of the results in `format`; `Json` keeps the results as they are
+pub fn convert(results: &[ProcessingResult], format: DatasetFormat, system_prompt: Option<&str>) -> Result<Value> {
+    let system_prompt = system_prompt.filter(|s| !s.trim().is_empty());
+    let records = results.iter().flat_map(|result| &result.records);
+    Ok(match format {
+        DatasetFormat::Json => serde_json::to_value(results)?,
+        DatasetFormat::Alpaca => records.map(|record| alpaca(record, system_prompt)).collect(),
+        DatasetFormat::Sharegpt => records.map(|record| sharegpt(record, system_prompt)).collect(),
+    })
+}
+
+/// Records of a results file: a JSON array (results of a local run are flattened to their
+/// records), an object with `records`, a single record or JSON Lines
+pub fn parse_records(content: &str) -> Result<Vec<Value>> {
+    let Ok(value) = serde_json::from_str::<Value>(content) else {
+        return content.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| serde_json::from_str(line).with_context(|| format!("Line {} is not valid JSON", i + 1)))
+            .collect();
+    };
+    let items = match value {
+        Value::Array(items) => items,
+        other => vec![other],
+    };
+    Ok(items.into_iter()
+        .flat_map(|item| match item {
+            Value::Object(mut object) if object.get("records").is_some_and(Value::is_array) => {
+                match object.remove("records") {
+                    Some(Value::Array(records)) => records,
+                    _ => Vec::new(),
+                }
+            },
+            other => vec![other],
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formats::{convert, parse_records, DatasetFormat};
+    use crate::processors::{ProcessingResult, ProcessingStats, Record};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    fn results() -> Vec<ProcessingResult> {
+        let record = |instruction: &str, prompt: &str, completion: &str| Record {
+            instruction: instruction.to_string(),
+            prompt: prompt.to_string(),
+            completion: completion.to_string(),
+            metadata: HashMap::from([("page".to_string(), serde_json::json!(1))]),
+        };
+        vec![ProcessingResult {
+            records: vec![
+                record("Streść tekst", "Długi tekst.", "Krótko."),
+                record("Co to jest RODO?", "", "Rozporządzenie o ochronie danych."),
+            ],
+            source_file: "a.txt".to_string(),
+            processing_type: "standard".to_string(),
+            stats: ProcessingStats { total_records: 2, total_tokens: 10, processing_time_ms: 0 },
+            exchanges: vec![],
+            error: None,
+        }]
+    }
+
+    #[test]
+    fn test_alpaca() -> anyhow::Result<()> {
+        let alpaca = convert(&results(), DatasetFormat::Alpaca, None)?;
+        assert_eq!(alpaca, serde_json::json!([
+            { "instruction": "Streść tekst", "input": "Długi tekst.", "output": "Krótko." },
+            { "instruction": "Co to jest RODO?", "input": "", "output": "Rozporządzenie o ochronie danych." },
+        ]));
+        let with_system = convert(&results(), DatasetFormat::Alpaca, Some("Jesteś asystentem"))?;
+        assert_eq!(with_system[0]["system"], "Jesteś asystentem");
+        // Pusty prompt systemowy jest pomijany
+        assert!(convert(&results(), DatasetFormat::Alpaca, Some("  "))?[0].get("system").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sharegpt() -> anyhow::Result<()> {
+        let sharegpt = convert(&results(), DatasetFormat::Sharegpt, Some("Jesteś asystentem"))?;
+        assert_eq!(sharegpt[0]["conversations"], serde_json::json!([
+            { "from": "system", "value": "Jesteś asystentem" },
+            { "from": "human", "value": "Streść tekst\n\nDługi tekst." },
+            { "from": "gpt", "value": "Krótko." },
+        ]));
+        assert!(sharegpt[0]["id"].as_str().unwrap().starts_with("rec-"));
+        let without_prompt = convert(&results(), DatasetFormat::Sharegpt, None)?;
+        assert_eq!(without_prompt[1]["conversations"][0], serde_json::json!({ "from": "human", "value": "Co to jest RODO?" }));
+
+        let native = convert(&results(), DatasetFormat::Json, None)?;
+        assert_eq!(native[0]["records"][0]["metadata"]["page"], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_names_and_paths() {
+        assert_eq!(DatasetFormat::from_name("ShareGPT"), DatasetFormat::Sharegpt);
+        assert_eq!(DatasetFormat::from_name("jsonl"), DatasetFormat::Json);
+        assert_eq!(DatasetFormat::Sharegpt.next(), DatasetFormat::Json);
+        assert_eq!(DatasetFormat::Alpaca.path_for(Path::new("out/raport.json")), PathBuf::from("out/raport.alpaca.json"));
+    }
+
+    #[test]
+    fn test_parse_records_formats() -> anyhow::Result<()> {
+        // Wyniki lokalnego przebiegu są spłaszczane do rekordów
+        let results = r#"[{"records": [{"prompt": "A"}, {"prompt": "B"}], "source_file": "a.txt"}, {"records": [{"prompt": "C"}]}]"#;
+        assert_eq!(parse_records(results)?.len(), 3);
+        assert_eq!(parse_records(r#"{"prompt": "A"}"#)?, vec![json!({"prompt": "A"})]);
+
+        let jsonl = "{\"prompt\": \"A\"}\n\n{\"prompt\": \"B\"}\n";
+        assert_eq!(parse_records(jsonl)?.len(), 2);
+        let err = parse_records("{\"prompt\": \"A\"}\nnot json\n").unwrap_err();
+        assert!(err.to_string().contains("Line 2"));
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::templating::{render_path, TemplateContext};
+
+/// Automatic versioning of exported datasets in a git repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    pub repository: PathBuf,
+    /// Subdirectory of the repository the datasets are copied into
+    #[serde(default)]
+    pub directory: Option<PathBuf>,
+    #[serde(default = "default_message_template")]
+    pub message_template: String,
+    /// Files larger than this are tracked with git LFS
+    #[serde(default)]
+    pub lfs_threshold_mb: Option<u64>,
+    #[serde(default)]
+    pub push: bool,
+}
+
+fn default_message_template() -> String {
+    "Add {file_name} from job {job_id}".to_string()
+}
+
+pub fn run(repository: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Copies `files` into the repository (unless already inside it) and returns their repo-relative paths
+fn stage_files(repository: &Path, directory: Option<&Path>, files: &[&Path]) -> Result<Vec<PathBuf>> {
+    let target_dir = match directory {
+        Some(dir) => repository.join(dir),
+        None => repository.to_path_buf(),
+    };
+    std::fs::create_dir_all(&target_dir).context("Failed to create dataset directory in repository")?;
+
+    files.iter().map(|file| {
+        let target = if file.starts_with(repository) {
+            file.to_path_buf()
+        } else {
+            let name = file.file_name().context("File has no name")?;
+            let target = target_dir.join(name);
+            std::fs::copy(file, &target)
+                .with_context(|| format!("Failed to copy {} into repository", file.display()))?;
+            target
+        };
+        Ok(target.strip_prefix(repository).unwrap_or(&target).to_path_buf())
+    }).collect()
+}
+
+/// Commits the given files with a plain message, optionally pushing afterwards
+pub fn commit_files(repository: &Path, files: &[&Path], message: &str, push: bool) -> Result<()> {
+    let staged = stage_files(repository, None, files)?;
+    commit_staged(repository, &staged, message, push)
+}
+
+fn commit_staged(repository: &Path, staged: &[PathBuf], message: &str, push: bool) -> Result<()> {
+    let paths: Vec<String> = staged.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+    let mut add = vec!["add", "--"];
+    add.extend(paths.iter().map(|p| p.as_str()));
+    run(repository, &add)?;
+
+    let mut commit = vec!["commit", "-m", message, "--"];
+    commit.extend(paths.iter().map(|p| p.as_str()));
+    run(repository, &commit)?;
+
+    if push {
+        run(repository, &["push"])?;
+    }
+    Ok(())
+}
+
+/// Commits a downloaded dataset with the files written along with it (its manifest, the
+/// rest of an unpacked archive) in one commit, according to the git settings
+pub fn commit_dataset(settings: &GitSettings, files: &[&Path], ctx: &TemplateContext) -> Result<()> {
+    let repository = settings.repository.as_path();
+    let mut staged = stage_files(repository, settings.directory.as_deref(), files)?;
+
+    if let Some(threshold) = settings.lfs_threshold_mb {
+        let large: Vec<String> = files.iter().zip(&staged)
+            .filter(|(file, _)| std::fs::metadata(file).map(|m| m.len()).unwrap_or(0) > threshold * 1024 * 1024)
+            .map(|(_, staged)| staged.to_string_lossy().into_owned())
+            .collect();
+        if !large.is_empty() {
+            let mut track = vec!["lfs", "track", "--"];
+            track.extend(large.iter().map(|p| p.as_str()));
+            run(repository, &track).context("git lfs is required for large datasets")?;
+            staged.push(PathBuf::from(".gitattributes"));
+        }
+    }
+
+    let message = render_path(&settings.message_template, ctx);
+    commit_staged(repository, &staged, &message, settings.push)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::git::{commit_dataset, run, GitSettings};
+    use crate::manifest::DatasetManifest;
+    use crate::templating::TemplateContext;
+    use std::path::Path;
+
+    #[test]
+    fn test_manifest_path_for() {
+        assert_eq!(
+            DatasetManifest::path_for(Path::new("/out/data.jsonl")),
+            Path::new("/out/data.jsonl.manifest.json")
+        );
+    }
+
+    #[test]
+    fn test_commit_dataset_with_templated_message() -> anyhow::Result<()> {
+        let repo = tempfile::tempdir()?;
+        let out = tempfile::tempdir()?;
+        if run(repo.path(), &["init", "-q"]).is_err() {
+            // Brak gita w środowisku testowym
+            return Ok(());
+        }
+        run(repo.path(), &["config", "user.email", "test@example.com"])?;
+        run(repo.path(), &["config", "user.name", "Test"])?;
+
+        let dataset = out.path().join("data.jsonl");
+        let manifest = out.path().join("data.jsonl.manifest.json");
+        std::fs::write(&dataset, "{}\n")?;
+        std::fs::write(&manifest, "{}")?;
+
+        let settings = GitSettings {
+            enabled: true,
+            repository: repo.path().to_path_buf(),
+            directory: Some("datasets".into()),
+            message_template: "Add {file_name} from job {job_id}".to_string(),
+            lfs_threshold_mb: None,
+            push: false,
+        };
+        let mut ctx = TemplateContext::default();
+        ctx.set("file_name", "data.jsonl");
+        ctx.set("job_id", "job-1");
+        commit_dataset(&settings, &[&dataset, &manifest], &ctx)?;
+
+        assert!(repo.path().join("datasets/data.jsonl").exists());
+        let log = std::process::Command::new("git")
+            .arg("-C").arg(repo.path())
+            .args(["log", "--format=%s"])
+            .output()?;
+        assert_eq!(String::from_utf8_lossy(&log.stdout).trim(), "Add data.jsonl from job job-1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_dataset_takes_unpacked_files_in_one_commit() -> anyhow::Result<()> {
+        let repo = tempfile::tempdir()?;
+        let out = tempfile::tempdir()?;
+        if run(repo.path(), &["init", "-q"]).is_err() {
+            return Ok(());
+        }
+        run(repo.path(), &["config", "user.email", "test@example.com"])?;
+        run(repo.path(), &["config", "user.name", "Test"])?;
+
+        // Rozpakowane archiwum: zbiór z manifestem i plik, który nie jest zbiorem
+        let files: Vec<_> = ["data.jsonl", "data.jsonl.manifest.json", "README.md"].iter()
+            .map(|name| out.path().join(name))
+            .collect();
+        for file in &files {
+            std::fs::write(file, "x")?;
+        }
+        let settings = GitSettings {
+            enabled: true,
+            repository: repo.path().to_path_buf(),
+            directory: None,
+            message_template: "Add job {job_id}".to_string(),
+            lfs_threshold_mb: None,
+            push: false,
+        };
+        let mut ctx = TemplateContext::default();
+        ctx.set("job_id", "job-1");
+        commit_dataset(&settings, &files.iter().map(|f| f.as_path()).collect::<Vec<_>>(), &ctx)?;
+
+        assert!(repo.path().join("README.md").exists());
+        let log = std::process::Command::new("git")
+            .arg("-C").arg(repo.path())
+            .args(["log", "--name-only", "--format=%s"])
+            .output()?;
+        let log = String::from_utf8_lossy(&log.stdout);
+        assert_eq!(log.lines().filter(|line| !line.is_empty()).count(), 4);
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::fs;
+
+use crate::atomic;
+use crate::lock::FileLock;
+use crate::workspace;
+
+/// Single job submitted from this client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub job_id: String,
+    pub file: String,
+    pub processing_type: String,
+    pub provider: String,
+    pub model: String,
+    pub status: String,
+    pub submitted_at: DateTime<Utc>,
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub records: Option<u64>,
+    #[serde(default)]
+    pub tokens: Option<u64>,
+    #[serde(default)]
+    pub cost_usd: Option<f64>,
+    /// Profile variables the job's prompts were rendered with
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+}
+
+/// Local job history stored as JSONL next to the config file
+#[derive(Debug, Clone, Default)]
+pub struct JobHistory {
+    path: Option<PathBuf>,
+    entries: Vec<HistoryEntry>,
+}
+
+impl JobHistory {
+    pub fn load_for(workspace: &str) -> Result<Self> {
+        let path = workspace::data_dir(workspace)?.join("history.jsonl");
+        Self::load_from(path)
+    }
+
+    pub fn load_from(path: PathBuf) -> Result<Self> {
+        let entries = read_entries(&path)?;
+        Ok(Self { path: Some(path), entries })
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub fn last(&self) -> Option<&HistoryEntry> {
+        self.entries.last()
+    }
+
+    /// Most recent entries, newest first
+    pub fn recent(&self, count: usize) -> Vec<&HistoryEntry> {
+        self.entries.iter().rev().take(count).collect()
+    }
+
+    pub fn record(&mut self, entry: HistoryEntry) -> Result<()> {
+        self.modify(|entries| {
+            entries.push(entry);
+            true
+        })
+    }
+
+    pub fn update_status(&mut self, job_id: &str, status: &str) -> Result<()> {
+        self.modify(|entries| {
+            let Some(entry) = entries.iter_mut().rev().find(|e| e.job_id == job_id) else {
+                return false;
+            };
+            if entry.status == status {
+                return false;
+            }
+            entry.status = status.to_string();
+            if matches!(status, "completed" | "failed" | "cancelled") {
+                entry.finished_at = Some(Utc::now());
+            }
+            true
+        })
+    }
+
+    /// Stores the usage of a job processed so far
+    pub fn update_usage(&mut self, job_id: &str, records: u64, tokens: u64, cost_usd: f64) -> Result<()> {
+        self.modify(|entries| {
+            let Some(entry) = entries.iter_mut().rev().find(|e| e.job_id == job_id) else {
+                return false;
+            };
+            entry.records = Some(records);
+            entry.tokens = Some(tokens);
+            entry.cost_usd = Some(cost_usd);
+            true
+        })
+    }
+
+    /// Read-modify-write under the history lock. The file is re-read first so
+    /// jobs recorded meanwhile by other instances aren't overwritten.
+    /// `change` returns whether anything needs saving.
+    fn modify(&mut self, change: impl FnOnce(&mut Vec<HistoryEntry>) -> bool) -> Result<()> {
+        let Some(path) = self.path.clone() else {
+            change(&mut self.entries);
+            return Ok(());
+        };
+        let _lock = FileLock::acquire(&path)?;
+        self.entries = read_entries(&path)?;
+        if change(&mut self.entries) {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Sum of known costs for jobs submitted in the current calendar month
+    pub fn spend_this_month(&self) -> f64 {
+        let now = Utc::now();
+        self.entries.iter()
+            .filter(|e| e.submitted_at.year() == now.year() && e.submitted_at.month() == now.month())
+            .filter_map(|e| e.cost_usd)
+            .sum()
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("Failed to create history directory")?;
+        }
+
+        let mut content = String::new();
+        for entry in &self.entries {
+            content.push_str(&serde_json::to_string(entry).context("Failed to serialize history entry")?);
+            content.push('\n');
+        }
+        atomic::write(path, content).context("Failed to write job history")?;
+        Ok(())
+    }
+}
+
+fn read_entries(path: &Path) -> Result<Vec<HistoryEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)
+        .context("Failed to read job history")?;
+    // Skip corrupted lines so a single bad entry doesn't hide the whole history
+    Ok(content.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
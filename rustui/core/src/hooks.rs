@@ -0,0 +1,238 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::git;
+use crate::manifest::DatasetManifest;
+
+/// Action run after a job's results have been downloaded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PostDownloadAction {
+    /// Open the file in `$EDITOR` (runs in the foreground terminal)
+    OpenEditor,
+    /// Open the file with the system default viewer
+    OpenViewer,
+    /// Run a shell command; `{path}` is replaced with the quoted file path
+    Command { command: String },
+    /// Move the file into another directory
+    MoveTo { directory: PathBuf },
+    /// Copy the file into a git repository, commit and push it
+    GitPush {
+        repository: PathBuf,
+        #[serde(default)]
+        message: Option<String>,
+    },
+}
+
+impl PostDownloadAction {
+    pub fn label(&self) -> String {
+        match self {
+            PostDownloadAction::OpenEditor => "Open in $EDITOR".to_string(),
+            PostDownloadAction::OpenViewer => "Open in system viewer".to_string(),
+            PostDownloadAction::Command { command } => format!("Run: {}", command),
+            PostDownloadAction::MoveTo { directory } => format!("Move to {}", directory.display()),
+            PostDownloadAction::GitPush { repository, .. } => format!("Push to git repo {}", repository.display()),
+        }
+    }
+}
+
+/// Result of running a chain of actions on a downloaded file
+#[derive(Debug, Clone, Default)]
+pub struct HookOutcome {
+    /// Final location of the file (changes after `MoveTo`)
+    pub path: PathBuf,
+    /// Human-readable log of what happened, one line per action
+    pub notes: Vec<String>,
+    /// Set when the caller should open `path` in the editor
+    pub open_in_editor: bool,
+}
+
+/// Runs actions in order. A failing action is reported in the notes and
+/// does not stop the remaining ones.
+pub fn run_actions(actions: &[PostDownloadAction], path: &Path) -> HookOutcome {
+    let mut outcome = HookOutcome {
+        path: path.to_path_buf(),
+        ..HookOutcome::default()
+    };
+
+    for action in actions {
+        let result = match action {
+            PostDownloadAction::OpenEditor => {
+                outcome.open_in_editor = true;
+                Ok(())
+            },
+            PostDownloadAction::OpenViewer => open_viewer(&outcome.path),
+            PostDownloadAction::Command { command } => run_command(command, &outcome.path),
+            PostDownloadAction::MoveTo { directory } => move_to(&outcome.path, directory).map(|new_path| {
+                outcome.path = new_path;
+            }),
+            PostDownloadAction::GitPush { repository, message } => {
+                git_push(&outcome.path, repository, message.as_deref())
+            },
+        };
+
+        match result {
+            Ok(()) => outcome.notes.push(format!("{}: ok", action.label())),
+            Err(err) => outcome.notes.push(format!("Failed {}: {:#}", action.label(), err)),
+        }
+    }
+
+    outcome
+}
+
+/// Editor command from `$VISUAL`/`$EDITOR` with a platform default
+pub fn editor_command() -> String {
+    std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() })
+}
+
+fn open_viewer(path: &Path) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.args(["/C", "start", ""]);
+        c
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(path)
+        .spawn()
+        .context("Failed to launch system viewer")?;
+    Ok(())
+}
+
+fn shell_quote(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    if cfg!(windows) {
+        format!("\"{}\"", path)
+    } else {
+        format!("'{}'", path.replace('\'', r"'\''"))
+    }
+}
+
+fn run_command(template: &str, path: &Path) -> Result<()> {
+    let command_line = template.replace("{path}", &shell_quote(path));
+    // Output is captured so the command can't draw over the TUI
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", &command_line]).output()
+    } else {
+        Command::new("sh").args(["-c", &command_line]).output()
+    }
+    .context("Failed to run command")?;
+
+    if !output.status.success() {
+        anyhow::bail!("Command exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Moves the file and its `.manifest.json`, if there is one, so the pair stays together
+fn move_to(path: &Path, directory: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(directory)
+        .with_context(|| format!("Failed to create {}", directory.display()))?;
+    let file_name = path.file_name().context("Downloaded file has no name")?;
+    let target = directory.join(file_name);
+    move_file(path, &target)?;
+
+    let manifest = DatasetManifest::path_for(path);
+    if manifest.exists() {
+        move_file(&manifest, &DatasetManifest::path_for(&target))?;
+    }
+    Ok(target)
+}
+
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    // rename doesn't work across filesystems, fall back to copy + delete
+    if std::fs::rename(from, to).is_err() {
+        std::fs::copy(from, to).with_context(|| format!("Failed to copy {}", from.display()))?;
+        std::fs::remove_file(from).with_context(|| format!("Failed to remove {}", from.display()))?;
+    }
+    Ok(())
+}
+
+fn git_push(path: &Path, repository: &Path, message: Option<&str>) -> Result<()> {
+    let file_name = path.file_name().context("Downloaded file has no name")?;
+    let default_message = format!("Add dataset {}", file_name.to_string_lossy());
+    let manifest = DatasetManifest::path_for(path);
+    let mut files = vec![path];
+    if manifest.exists() {
+        files.push(&manifest);
+    }
+    git::commit_files(repository, &files, message.unwrap_or(&default_message), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hooks::{run_actions, PostDownloadAction};
+    use crate::manifest::DatasetManifest;
+
+    #[test]
+    fn test_post_download_actions_from_toml() -> anyhow::Result<()> {
+        let config: crate::config::Config = toml::from_str(r#"
+                backend_url = "http://test:8000"
+                default_provider = "openai"
+                default_model = "gpt-4-turbo"
+                default_language = "en"
+                default_processing_type = "standard"
+                max_upload_size_mb = 10
+                
+                [[post_download.default]]
+                action = "open_viewer"
+                
+                [[post_download.article]]
+                action = "move_to"
+                directory = "/data/articles"
+            "#)?;
+
+        assert_eq!(config.post_download_actions("article"), vec![PostDownloadAction::MoveTo { directory: "/data/articles".into() }]);
+        // Preset bez własnej listy dostaje akcje domyślne
+        assert_eq!(config.post_download_actions("standard"), vec![PostDownloadAction::OpenViewer]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_actions_moves_file_and_continues_after_failure() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("results.json");
+        std::fs::write(&file, "[]")?;
+        let target = dir.path().join("archive");
+
+        let actions = vec![
+            PostDownloadAction::Command { command: "exit 3".to_string() },
+            PostDownloadAction::MoveTo { directory: target.clone() },
+            PostDownloadAction::OpenEditor,
+        ];
+        let outcome = run_actions(&actions, &file);
+
+        assert_eq!(outcome.path, target.join("results.json"));
+        assert!(outcome.path.exists());
+        assert!(outcome.open_in_editor);
+        assert!(outcome.notes[0].starts_with("Failed"));
+        assert_eq!(outcome.notes.len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_to_takes_manifest_along() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("results.json");
+        std::fs::write(&file, "[]")?;
+        std::fs::write(DatasetManifest::path_for(&file), "{}")?;
+        let target = dir.path().join("archive");
+
+        let outcome = run_actions(&[PostDownloadAction::MoveTo { directory: target.clone() }], &file);
+
+        // Manifest ląduje obok pliku, w starym miejscu nic nie zostaje
+        assert_eq!(outcome.path, target.join("results.json"));
+        assert!(DatasetManifest::path_for(&outcome.path).exists());
+        assert!(!DatasetManifest::path_for(&file).exists());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,403 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::checks::Checks;
+use crate::config::Config;
+use crate::formats::DatasetFormat;
+use crate::preset::{Origin, Preset};
+use crate::processors::{self, LocalRun, ProcessorConfig, SpendCap};
+use crate::vault::Vault;
+
+/// Job file (`jobs.yaml`): inputs, presets and outputs of many jobs, run together by
+/// `anydataset-tui run` or queued in the TUI with `--jobs`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobFile {
+    /// Jobs `run` processes at once; 1 runs them one after another
+    #[serde(default = "default_parallel")]
+    pub parallel: usize,
+    /// Settings every job starts from
+    #[serde(default)]
+    pub defaults: JobSettings,
+    pub jobs: Vec<JobSpec>,
+}
+
+fn default_parallel() -> usize {
+    1
+}
+
+/// Named preset plus preset fields given directly; the fields win
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    #[serde(flatten)]
+    pub fields: Preset,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobSpec {
+    /// Shown in the progress table (default: job-<number>)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Files or glob patterns (`docs/*.pdf`), relative to the job file
+    pub inputs: Vec<String>,
+    /// Dataset of all inputs, relative to the job file (default: <name>.json)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+    #[serde(flatten)]
+    pub settings: JobSettings,
+}
+
+/// Job with its files found, presets applied and paths made absolute
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedJob {
+    pub name: String,
+    pub files: Vec<String>,
+    pub processing_type: String,
+    pub settings: Preset,
+    /// System prompt of the settings, with a template put in
+    pub system_prompt: Option<String>,
+    pub output: PathBuf,
+}
+
+impl JobFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid job file {}", path.display()))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let file: JobFile = serde_yaml::from_str(content)?;
+        if file.jobs.is_empty() {
+            anyhow::bail!("No jobs listed under `jobs`");
+        }
+        if file.parallel == 0 {
+            anyhow::bail!("`parallel` must be at least 1");
+        }
+        Ok(file)
+    }
+
+    /// Jobs ready to run: inputs found relative to `base` (the job file's directory), named
+    /// presets (`presets`) and prompt templates (`templates`) applied
+    pub fn resolve(
+        &self,
+        base: &Path,
+        presets: &BTreeMap<String, (Preset, Origin)>,
+        templates: &BTreeMap<String, (String, Origin)>,
+    ) -> Result<Vec<ResolvedJob>> {
+        let mut outputs = BTreeSet::new();
+        let mut jobs = Vec::with_capacity(self.jobs.len());
+        for (i, spec) in self.jobs.iter().enumerate() {
+            let name = spec.name.clone().unwrap_or_else(|| format!("job-{}", i + 1));
+            let job = self.resolve_job(spec, &name, base, presets, templates)
+                .with_context(|| format!("Job {}", name))?;
+            if !outputs.insert(job.output.clone()) {
+                anyhow::bail!("Jobs {} and another one both write {}", name, job.output.display());
+            }
+            jobs.push(job);
+        }
+        Ok(jobs)
+    }
+
+    fn resolve_job(
+        &self,
+        spec: &JobSpec,
+        name: &str,
+        base: &Path,
+        presets: &BTreeMap<String, (Preset, Origin)>,
+        templates: &BTreeMap<String, (String, Origin)>,
+    ) -> Result<ResolvedJob> {
+        let mut settings = Preset::default();
+        for layer in [&self.defaults, &spec.settings] {
+            if let Some(preset) = &layer.preset {
+                let (preset, _) = presets.get(preset).with_context(|| format!("Unknown preset {}", preset))?;
+                settings.overlay(preset);
+            }
+            settings.overlay(&layer.fields);
+        }
+        let processing_type = settings.processing_type.clone().unwrap_or_else(|| "standard".to_string());
+        processors::get_processor(&processing_type)?;
+        let system_prompt = match &settings.template {
+            Some(template) => {
+                let (prompt, _) = templates.get(template).with_context(|| format!("Unknown template {}", template))?;
+                Some(prompt.clone())
+            },
+            None => settings.system_prompt.clone(),
+        };
+
+        let mut files = Vec::new();
+        for input in &spec.inputs {
+            files.extend(expand(base, input)?);
+        }
+        if files.is_empty() {
+            anyhow::bail!("No inputs");
+        }
+        let output = base.join(spec.output.clone().unwrap_or_else(|| PathBuf::from(format!("{}.json", name))));
+        Ok(ResolvedJob { name: name.to_string(), files, processing_type, settings, system_prompt, output })
+    }
+}
+
+/// Files of one input: the file itself or the sorted matches of a glob pattern
+fn expand(base: &Path, input: &str) -> Result<Vec<String>> {
+    let path = base.join(input);
+    let pattern = path.to_string_lossy();
+    if !input.contains(['*', '?', '[']) {
+        if !path.is_file() {
+            anyhow::bail!("Input {} not found", path.display());
+        }
+        return Ok(vec![pattern.into_owned()]);
+    }
+    let mut files = Vec::new();
+    for entry in glob::glob(&pattern).with_context(|| format!("Invalid pattern {}", input))? {
+        let file = entry?;
+        if file.is_file() {
+            files.push(file.to_string_lossy().into_owned());
+        }
+    }
+    if files.is_empty() {
+        anyhow::bail!("Pattern {} matches no files", input);
+    }
+    files.sort();
+    Ok(files)
+}
+
+impl ResolvedJob {
+    /// Configuration the local processors run this job's files with
+    pub fn processor_config(&self, config: &Config) -> ProcessorConfig {
+        ProcessorConfig {
+            model: self.settings.model.clone().unwrap_or_default(),
+            provider: self.settings.provider.clone().unwrap_or_default(),
+            language: self.settings.language.clone().unwrap_or_else(|| config.default_language.clone()),
+            system_prompt: self.system_prompt.clone(),
+            keywords: self.settings.keywords.clone(),
+            output_format: self.settings.output_format.clone().unwrap_or_else(|| "json".to_string()),
+            chunking: config.chunking.clone(),
+            cleanup: config.cleanup.clone(),
+            confidence: config.confidence.clone(),
+            figures: config.figures.clone(),
+            metadata: config.metadata.clone(),
+            best_of: self.settings.best_of.clone().or_else(|| config.best_of.clone()),
+            voting: self.settings.voting.clone().or_else(|| config.voting.clone()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Where a job of a job file is
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobState {
+    Queued,
+    Running { done: usize, total: usize },
+    Finished { records: usize, failed_files: usize },
+    Failed(String),
+}
+
+impl JobState {
+    pub fn is_failed(&self) -> bool {
+        matches!(self, JobState::Failed(_))
+    }
+}
+
+/// Processes the files of `job` one by one, reporting each step to `update`, and writes the
+/// dataset as the TUI's local jobs do: `[checks]` applied, encrypted when `vault` is on and
+/// converted to the preset's output format next to it
+pub fn run_job(job: &ResolvedJob, config: &Config, vault: &Vault, update: impl Fn(JobState)) -> Result<LocalRun> {
+    let processor = processors::get_processor(&job.processing_type)?;
+    let processor_config = job.processor_config(config);
+    let mut run = LocalRun::default();
+    for (done, file) in job.files.iter().enumerate() {
+        update(JobState::Running { done, total: job.files.len() });
+        let mut cap = SpendCap::new(f64::INFINITY);
+        let step = processors::run_with_cap(processor.as_ref(), std::slice::from_ref(file), &processor_config, 0.0, &mut cap);
+        run.results.extend(step.results);
+    }
+    if !config.checks.is_empty() {
+        Checks::load(&config.checks)?.apply(&mut run.results);
+    }
+    if let Some(dir) = job.output.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    vault.write(&job.output, serde_json::to_vec_pretty(&run.results)?)?;
+    let format = DatasetFormat::from_name(&processor_config.output_format);
+    if format != DatasetFormat::Json {
+        let converted = crate::formats::convert(&run.results, format, processor_config.system_prompt.as_deref())?;
+        vault.write(&format.path_for(&job.output), serde_json::to_vec_pretty(&converted)?)?;
+    }
+    Ok(run)
+}
+
+/// Runs `jobs` on up to `parallel` threads, in order of the file. `update` hears about every
+/// change of a job's state by its index; the final states are returned.
+pub fn run_all(
+    jobs: &[ResolvedJob],
+    config: &Config,
+    vault: &Vault,
+    parallel: usize,
+    update: &(dyn Fn(usize, &JobState) + Sync),
+) -> Vec<JobState> {
+    let next = AtomicUsize::new(0);
+    let finished = std::sync::Mutex::new(vec![JobState::Queued; jobs.len()]);
+    std::thread::scope(|scope| {
+        for _ in 0..parallel.clamp(1, jobs.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(job) = jobs.get(index) else {
+                    break;
+                };
+                let state = match run_job(job, config, vault, |state| update(index, &state)) {
+                    Ok(run) => JobState::Finished { records: run.records(), failed_files: run.failed().len() },
+                    Err(err) => JobState::Failed(format!("{:#}", err)),
+                };
+                update(index, &state);
+                finished.lock().unwrap_or_else(std::sync::PoisonError::into_inner)[index] = state;
+            });
+        }
+    });
+    finished.into_inner().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::jobfile::{run_all, JobFile, JobState};
+    use crate::preset::{Origin, Preset};
+    use crate::vault::Vault;
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    const JOBS: &str = r#"
+parallel: 2
+defaults:
+  preset: umowy
+  language: en
+jobs:
+  - name: notatki
+    inputs: ["notes/*.txt", "extra.md"]
+    output: out/notes.json
+    keywords: [prawo]
+  - inputs: [extra.md]
+    processing_type: article
+    template: krotki
+"#;
+
+    fn presets() -> BTreeMap<String, (Preset, Origin)> {
+        let preset = Preset {
+            processing_type: Some("legal".to_string()),
+            model: Some("gpt-4o".to_string()),
+            system_prompt: Some("Wyodrębnij klauzule".to_string()),
+            keywords: vec!["umowa".to_string()],
+            ..Default::default()
+        };
+        BTreeMap::from([("umowy".to_string(), (preset, Origin::Local))])
+    }
+
+    fn templates() -> BTreeMap<String, (String, Origin)> {
+        BTreeMap::from([("krotki".to_string(), ("Streść krótko".to_string(), Origin::Shared))])
+    }
+
+    fn write_inputs(dir: &Path) -> anyhow::Result<()> {
+        std::fs::create_dir_all(dir.join("notes"))?;
+        std::fs::write(dir.join("notes/b.txt"), "Druga notatka.")?;
+        std::fs::write(dir.join("notes/a.txt"), "Pierwsza notatka.")?;
+        std::fs::write(dir.join("notes/c.md"), "Nie pasuje do wzorca.")?;
+        std::fs::write(dir.join("extra.md"), "# Dodatek")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_jobs() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        write_inputs(dir.path())?;
+        let file = JobFile::parse(JOBS)?;
+        assert_eq!(file.parallel, 2);
+        let jobs = file.resolve(dir.path(), &presets(), &templates())?;
+
+        let notes = &jobs[0];
+        assert_eq!(notes.name, "notatki");
+        let files: Vec<String> = ["notes/a.txt", "notes/b.txt", "extra.md"].iter()
+            .map(|f| dir.path().join(f).to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(notes.files, files);
+        assert_eq!(notes.output, dir.path().join("out/notes.json"));
+        // Pola zadania wygrywają z presetem, a preset z braku ustawień
+        assert_eq!(notes.processing_type, "legal");
+        assert_eq!(notes.settings.language.as_deref(), Some("en"));
+        assert_eq!(notes.settings.keywords, vec!["prawo".to_string()]);
+        assert_eq!(notes.system_prompt.as_deref(), Some("Wyodrębnij klauzule"));
+        let config = notes.processor_config(&Config::default());
+        assert_eq!(config.model, "gpt-4o");
+        assert_eq!(config.language, "en");
+
+        let second = &jobs[1];
+        assert_eq!(second.name, "job-2");
+        assert_eq!(second.output, dir.path().join("job-2.json"));
+        assert_eq!(second.processing_type, "article");
+        // Szablon zastępuje prompt presetu
+        assert_eq!(second.system_prompt.as_deref(), Some("Streść krótko"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_job_files() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        write_inputs(dir.path())?;
+        let resolve = |yaml: &str| JobFile::parse(yaml).and_then(|file| file.resolve(dir.path(), &presets(), &templates()));
+
+        assert!(resolve("jobs: []").is_err());
+        assert!(resolve("parallel: 0\njobs: [{inputs: [extra.md]}]").is_err());
+        let err = resolve("jobs: [{inputs: [extra.md], preset: brak}]").unwrap_err();
+        assert!(format!("{:#}", err).contains("Unknown preset brak"));
+        let err = resolve("jobs: [{inputs: ['*.pdf']}]").unwrap_err();
+        assert!(format!("{:#}", err).contains("matches no files"));
+        assert!(resolve("jobs: [{inputs: [missing.txt]}]").is_err());
+        assert!(resolve("jobs: [{inputs: [extra.md], processing_type: unknown}]").is_err());
+        // Dwa zadania nie mogą nadpisywać tego samego wyniku
+        let err = resolve("jobs: [{inputs: [extra.md], output: a.json}, {inputs: [extra.md], output: a.json}]").unwrap_err();
+        assert!(format!("{:#}", err).contains("both write"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_all() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        write_inputs(dir.path())?;
+        // Wynik trzeciego zadania nie ma gdzie powstać: jego katalog jest plikiem
+        let yaml = "parallel: 2\njobs:\n  - inputs: [notes/a.txt, notes/b.txt]\n  - inputs: [extra.md]\n    output: out/extra.json\n  - inputs: [extra.md]\n    output: extra.md/x.json\n";
+        let jobs = JobFile::parse(yaml)?.resolve(dir.path(), &BTreeMap::new(), &BTreeMap::new())?;
+        let updates = std::sync::Mutex::new(Vec::new());
+        let states = run_all(&jobs, &Config::default(), &Vault::default(), 2, &|index, state| {
+            updates.lock().unwrap().push((index, state.clone()));
+        });
+
+        assert_eq!(states[0], JobState::Finished { records: 2, failed_files: 0 });
+        assert_eq!(states[1], JobState::Finished { records: 1, failed_files: 0 });
+        assert!(states[2].is_failed());
+        let dataset: Vec<serde_json::Value> = serde_json::from_slice(&std::fs::read(dir.path().join("job-1.json"))?)?;
+        assert_eq!(dataset.len(), 2);
+        assert!(dir.path().join("out/extra.json").exists());
+        let updates = updates.into_inner().unwrap();
+        assert!(updates.contains(&(0, JobState::Running { done: 1, total: 2 })));
+        Ok(())
+    }
+
+    #[test]
+    fn test_preset_overlay() {
+        let mut preset = Preset {
+            model: Some("a".to_string()),
+            template: Some("szablon".to_string()),
+            keywords: vec!["x".to_string()],
+            ..Default::default()
+        };
+        preset.overlay(&Preset { system_prompt: Some("prompt".to_string()), temperature: Some(0.2), ..Default::default() });
+        assert_eq!(preset.model.as_deref(), Some("a"));
+        assert_eq!(preset.keywords, vec!["x".to_string()]);
+        assert_eq!(preset.temperature, Some(0.2));
+        // Prompt podany wyżej zastępuje szablon
+        assert_eq!(preset.template, None);
+        assert_eq!(preset.system_prompt.as_deref(), Some("prompt"));
+    }
+}
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::processors::Record;
+
+/// Record metadata: `easy`, `medium` or `hard`
+pub const DIFFICULTY: &str = "difficulty";
+/// Record metadata: topic category of the question
+pub const CATEGORY: &str = "category";
+
+/// Category used when the model's answer isn't one of the configured categories
+const OTHER: &str = "other";
+
+/// Difficulty and category labeling of QA records, from the `[qa.labels]` config section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LabelSettings {
+    /// Allowed categories; the model names its own when empty
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Labels {
+    pub difficulty: Difficulty,
+    pub category: String,
+}
+
+/// Asks for the difficulty and category of one question-answer pair
+pub fn prompt(record: &Record, settings: &LabelSettings) -> String {
+    let categories = if settings.categories.is_empty() {
+        "a short topic category of your choice".to_string()
+    } else {
+        format!("one of: {}", settings.categories.join(", "))
+    };
+    format!(
+        "Label this question-answer pair. Difficulty is easy (answer stated directly), medium (needs \
+         combining facts) or hard (needs reasoning or expertise). Category is {}. Use this format:\n\
+         Difficulty: ...\nCategory: ...\n\nQuestion: {}\nAnswer: {}",
+        categories, record.prompt, record.completion
+    )
+}
+
+/// Labels in the `Difficulty:`/`Category:` format. Categories outside the configured list become `other`.
+pub fn parse_labels(output: &str, settings: &LabelSettings) -> Option<Labels> {
+    let field = |label: &str| {
+        output.lines()
+            .find_map(|line| line.trim().strip_prefix(label))
+            .map(|value| value.trim().trim_end_matches('.').to_string())
+    };
+    let difficulty = match field("Difficulty:")?.to_lowercase().as_str() {
+        "easy" => Difficulty::Easy,
+        "medium" => Difficulty::Medium,
+        "hard" => Difficulty::Hard,
+        _ => return None,
+    };
+    let category = field("Category:").filter(|c| !c.is_empty()).unwrap_or_else(|| OTHER.to_string());
+    let category = if settings.categories.is_empty() {
+        category.to_lowercase()
+    } else {
+        settings.categories.iter()
+            .find(|allowed| allowed.eq_ignore_ascii_case(&category))
+            .cloned()
+            .unwrap_or_else(|| OTHER.to_string())
+    };
+    Some(Labels { difficulty, category })
+}
+
+/// Adds difficulty and category to every record, replacing earlier labels.
+/// Returns the number of records labeled; the rest keep no labels.
+pub fn label(records: &mut [Record], settings: &LabelSettings) -> usize {
+    let mut labeled = 0;
+    for record in records.iter_mut() {
+        let output = mock_output(&prompt(record, settings), settings);
+        record.metadata.remove(DIFFICULTY);
+        record.metadata.remove(CATEGORY);
+        let Some(labels) = parse_labels(&output, settings) else {
+            continue;
+        };
+        record.metadata.insert(DIFFICULTY.to_string(), labels.difficulty.to_string().into());
+        record.metadata.insert(CATEGORY.to_string(), labels.category.into());
+        labeled += 1;
+    }
+    labeled
+}
+
+/// Number of records per difficulty, for reporting
+pub fn counts(records: &[Record]) -> [(Difficulty, usize); 3] {
+    [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard].map(|difficulty| {
+        let name = difficulty.to_string();
+        let count = records.iter()
+            .filter(|r| r.metadata.get(DIFFICULTY).and_then(|v| v.as_str()) == Some(name.as_str()))
+            .count();
+        (difficulty, count)
+    })
+}
+
+/// Mock model call: difficulty from the answer length, the first configured category the pair mentions
+fn mock_output(prompt: &str, settings: &LabelSettings) -> String {
+    let pair = prompt.split("\n\nQuestion: ").nth(1).unwrap_or_default().to_lowercase();
+    let answer_words = pair.split("\nanswer: ").nth(1).unwrap_or_default().split_whitespace().count();
+    let difficulty = match answer_words {
+        0..=8 => "easy",
+        9..=25 => "medium",
+        _ => "hard",
+    };
+    let category = settings.categories.iter()
+        .find(|category| pair.contains(&category.to_lowercase()))
+        .map(String::as_str)
+        .unwrap_or("general");
+    format!("Difficulty: {}\nCategory: {}\n", difficulty, category)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::labeling::{counts, label, parse_labels, Difficulty, LabelSettings, CATEGORY, DIFFICULTY};
+    use crate::processors::Record;
+
+    fn settings() -> LabelSettings {
+        LabelSettings { categories: vec!["Anatomy".to_string(), "Pharmacology".to_string()] }
+    }
+
+    #[test]
+    fn test_parse_labels() {
+        let labels = parse_labels("Difficulty: Hard\nCategory: pharmacology.\n", &settings());
+        assert_eq!(labels.map(|l| (l.difficulty, l.category)), Some((Difficulty::Hard, "Pharmacology".to_string())));
+
+        // Kategoria spoza listy to "other", bez listy model wybiera sam
+        let labels = parse_labels("Difficulty: easy\nCategory: Surgery", &settings());
+        assert_eq!(labels.map(|l| l.category), Some("other".to_string()));
+        let labels = parse_labels("Difficulty: easy\nCategory: Surgery", &LabelSettings::default());
+        assert_eq!(labels.map(|l| l.category), Some("surgery".to_string()));
+
+        assert_eq!(parse_labels("Difficulty: trivial\nCategory: Anatomy", &settings()), None);
+    }
+
+    #[test]
+    fn test_label_records() {
+        let record = |prompt: &str, completion: &str| Record {
+            instruction: String::new(),
+            prompt: prompt.to_string(),
+            completion: completion.to_string(),
+            metadata: Default::default(),
+        };
+        let mut records = vec![
+            record("Which bone forms the hock?", "The talus, in anatomy of the tarsus."),
+            record("Why is enrofloxacin avoided in young dogs?", &"It damages growing cartilage in joints ".repeat(5)),
+        ];
+        assert_eq!(label(&mut records, &settings()), 2);
+        assert_eq!(records[0].metadata[DIFFICULTY], "easy");
+        assert_eq!(records[0].metadata[CATEGORY], "Anatomy");
+        assert_eq!(records[1].metadata[DIFFICULTY], "hard");
+        assert_eq!(records[1].metadata[CATEGORY], "other");
+        assert_eq!(counts(&records), [(Difficulty::Easy, 1), (Difficulty::Medium, 0), (Difficulty::Hard, 1)]);
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::{Duration, Instant};
+
+use crate::api::ApiClient;
+
+/// How long a backend may take to answer before it counts as unhealthy
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of pinging one backend
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendProbe {
+    pub url: String,
+    /// Round trip of the version request; `None` when the backend didn't answer
+    pub latency: Option<Duration>,
+    pub error: Option<String>,
+}
+
+impl BackendProbe {
+    pub fn healthy(&self) -> bool {
+        self.latency.is_some()
+    }
+
+    /// "42 ms" or why the backend was skipped
+    pub fn describe(&self) -> String {
+        match (self.latency, &self.error) {
+            (Some(latency), _) => format!("{} ms", latency.as_millis()),
+            (None, Some(error)) => format!("unreachable ({})", error),
+            (None, None) => "unreachable".to_string(),
+        }
+    }
+}
+
+/// The configured backend followed by its mirrors, each URL once
+pub fn candidates(backend_url: &str, mirrors: &[String]) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for url in std::iter::once(backend_url).chain(mirrors.iter().map(String::as_str)) {
+        let url = url.trim_end_matches('/');
+        if !url.is_empty() && !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+    }
+    urls
+}
+
+/// Times a version request to the backend
+pub async fn probe(url: &str, headers: &[(String, String)]) -> BackendProbe {
+    let client = ApiClient::new(url).with_headers(headers.to_vec());
+    let started = Instant::now();
+    let error = match tokio::time::timeout(PROBE_TIMEOUT, client.get_version()).await {
+        Ok(Ok(_)) => None,
+        Ok(Err(err)) => Some(format!("{:#}", err)),
+        Err(_) => Some(format!("no answer within {}s", PROBE_TIMEOUT.as_secs())),
+    };
+    BackendProbe {
+        url: url.to_string(),
+        latency: error.is_none().then(|| started.elapsed()),
+        error,
+    }
+}
+
+/// Pings all backends at once; results keep the order of `urls`
+pub async fn probe_all(urls: &[String], headers: &[(String, String)]) -> Vec<BackendProbe> {
+    futures::future::join_all(urls.iter().map(|url| probe(url, headers))).await
+}
+
+/// Lowest-latency healthy backend; ties go to the one listed first
+pub fn fastest(probes: &[BackendProbe]) -> Option<&BackendProbe> {
+    probes.iter()
+        .filter(|p| p.healthy())
+        .min_by_key(|p| p.latency)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::latency::{candidates, fastest, probe_all, BackendProbe};
+    use std::time::Duration;
+
+    fn probe(url: &str, millis: Option<u64>) -> BackendProbe {
+        BackendProbe {
+            url: url.to_string(),
+            latency: millis.map(Duration::from_millis),
+            error: millis.is_none().then(|| "connection refused".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_candidates_skip_duplicates() {
+        let mirrors = vec!["http://a:8000/".to_string(), "http://b:8000".to_string(), String::new()];
+        assert_eq!(candidates("http://a:8000", &mirrors), vec!["http://a:8000", "http://b:8000"]);
+    }
+
+    #[test]
+    fn test_fastest_skips_unhealthy() {
+        let probes = vec![probe("http://a", Some(80)), probe("http://b", None), probe("http://c", Some(12))];
+        assert_eq!(fastest(&probes).map(|p| p.url.as_str()), Some("http://c"));
+        assert_eq!(probes[1].describe(), "unreachable (connection refused)");
+        assert!(fastest(&probes[1..2]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_probe_all_marks_failing_backend() {
+        let mut server = mockito::Server::new_async().await;
+        let _ok = server.mock("GET", "/api/version")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"version": "1.4.0"}"#)
+            .create_async().await;
+
+        let probes = probe_all(&[server.url(), "http://127.0.0.1:9".to_string()], &[]).await;
+        assert!(probes[0].healthy());
+        assert!(!probes[1].healthy());
+    }
+}
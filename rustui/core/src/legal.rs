@@ -0,0 +1,218 @@
+use crate::audit::ModelExchange;
+use crate::lineage;
+use crate::processors::{self, ProcessingResult, ProcessingStats, Processor, ProcessorConfig, Record};
+
+/// Record metadata: identifier as written in the document, e.g. `Art. 5`, `§ 3`, `2.1`
+pub const CLAUSE_ID: &str = "clause_id";
+/// Record metadata: identifier of the enclosing clause, if any
+pub const CLAUSE_PARENT: &str = "clause_parent";
+/// Record metadata: identifiers from the outermost clause down, e.g. `Art. 5 / 2 / 2.1`;
+/// unique even where numbering restarts in every article
+pub const CLAUSE_PATH: &str = "clause_path";
+
+/// Numbered part of a contract or regulation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clause {
+    pub id: String,
+    /// Identifiers of the enclosing clauses, outermost first
+    pub ancestors: Vec<String>,
+    /// Heading line followed by the clause body, up to the next clause
+    pub text: String,
+}
+
+impl Clause {
+    pub fn parent(&self) -> Option<&str> {
+        self.ancestors.last().map(String::as_str)
+    }
+
+    pub fn path(&self) -> String {
+        let mut parts = self.ancestors.clone();
+        parts.push(self.id.clone());
+        parts.join(" / ")
+    }
+}
+
+/// Splits on lines starting with a clause marker: `§ 12`, `Art. 5` (also `Article`/`Artykuł`),
+/// `Section 3` or a decimal number like `1.`, `1.1`, `2.3.1`. Text before the first marker
+/// becomes a `preamble` clause.
+pub fn split_clauses(text: &str) -> Vec<Clause> {
+    let mut clauses = Vec::new();
+    // Open clauses with their nesting depth, innermost last
+    let mut open: Vec<(usize, String)> = Vec::new();
+    let mut current = Clause { id: "preamble".to_string(), ancestors: Vec::new(), text: String::new() };
+    for line in text.lines() {
+        if let Some((depth, id)) = clause_marker(line) {
+            while open.last().is_some_and(|(d, _)| *d >= depth) {
+                open.pop();
+            }
+            let ancestors = open.iter().map(|(_, id)| id.clone()).collect();
+            open.push((depth, id.clone()));
+            let next = Clause { id, ancestors, text: String::new() };
+            push_clause(&mut clauses, std::mem::replace(&mut current, next));
+        }
+        current.text.push_str(line);
+        current.text.push('\n');
+    }
+    push_clause(&mut clauses, current);
+    clauses
+}
+
+fn push_clause(clauses: &mut Vec<Clause>, mut clause: Clause) {
+    clause.text = clause.text.trim().to_string();
+    if !clause.text.is_empty() {
+        clauses.push(clause);
+    }
+}
+
+/// Nesting depth and identifier of a clause starting on this line.
+/// Articles and sections are outermost; numbered clauses nest by their number of parts.
+fn clause_marker(line: &str) -> Option<(usize, String)> {
+    let line = line.trim_start();
+    if let Some(rest) = line.strip_prefix('§') {
+        let number = leading_number(rest.trim_start())?;
+        return Some((0, format!("§ {}", number)));
+    }
+    for (prefix, label) in [("Art.", "Art."), ("Article", "Art."), ("Artykuł", "Art."), ("Section", "Section")] {
+        if let Some(rest) = line.strip_prefix(prefix) {
+            if rest.starts_with(char::is_whitespace) || prefix.ends_with('.') {
+                let number = leading_number(rest.trim_start())?;
+                return Some((0, format!("{} {}", label, number)));
+            }
+        }
+    }
+
+    // `1.`, `1.1`, `1.1.` followed by the clause text (or nothing)
+    let token = line.split_whitespace().next()?;
+    let number = token.strip_suffix('.').unwrap_or(token);
+    let parts: Vec<&str> = number.split('.').collect();
+    let numeric = parts.iter().all(|p| !p.is_empty() && p.len() <= 3 && p.bytes().all(|b| b.is_ascii_digit()));
+    // A bare `1` is a number in the text, `1.` or `1.1` a clause
+    if !numeric || (parts.len() == 1 && !token.ends_with('.')) {
+        return None;
+    }
+    Some((parts.len(), number.to_string()))
+}
+
+/// Number (optionally with a letter, e.g. `12a`) at the start of `text`
+fn leading_number(text: &str) -> Option<&str> {
+    let end = text.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(text.len());
+    let number = &text[..end];
+    number.starts_with(|c: char| c.is_ascii_digit()).then_some(number)
+}
+
+/// One record per clause, for legal QA datasets
+pub struct ClauseProcessor;
+
+impl Processor for ClauseProcessor {
+    fn process_file(&self, file_path: &str, config: &ProcessorConfig) -> anyhow::Result<ProcessingResult> {
+        let mut records = Vec::new();
+        for source in processors::read_sources(file_path, config)? {
+            let first = records.len();
+            records.extend(split_clauses(&source.text).into_iter().map(|clause| {
+                let mut metadata = source.metadata.clone();
+                metadata.insert(CLAUSE_ID.to_string(), clause.id.clone().into());
+                metadata.insert(CLAUSE_PATH.to_string(), clause.path().into());
+                if let Some(parent) = clause.parent() {
+                    metadata.insert(CLAUSE_PARENT.to_string(), parent.into());
+                }
+                Record {
+                    instruction: format!("Answer questions about clause {} of the document", clause.id),
+                    // Mock model call; the clause itself is the reference answer
+                    completion: clause.text.clone(),
+                    prompt: clause.text,
+                    metadata,
+                }
+            }));
+            lineage::link(&mut records[first..], &source.parents);
+        }
+
+        let total_tokens = records.iter()
+            .map(|r| r.prompt.split_whitespace().count() + r.completion.split_whitespace().count())
+            .sum();
+        let exchanges = records.iter_mut()
+            .map(|record| ModelExchange::for_record(record, file_path, config))
+            .collect();
+        Ok(ProcessingResult {
+            stats: ProcessingStats {
+                total_records: records.len(),
+                total_tokens,
+                processing_time_ms: 0,
+            },
+            records,
+            exchanges,
+            source_file: file_path.to_string(),
+            processing_type: "legal".to_string(),
+            error: None,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "legal"
+    }
+
+    fn description(&self) -> &'static str {
+        "Clause-level segmentation of contracts and regulations"
+    }
+}
+
+/// Clause tree of segmented records, one line per clause indented by depth
+pub fn outline(records: &[Record]) -> Vec<String> {
+    records.iter()
+        .filter_map(|record| {
+            let path = record.metadata.get(CLAUSE_PATH)?.as_str()?;
+            let depth = path.matches(" / ").count();
+            let id = path.rsplit(" / ").next().unwrap_or(path);
+            let heading: String = record.prompt.lines().next().unwrap_or_default().chars().take(60).collect();
+            Some(format!("{}{}: {}", "  ".repeat(depth), id, heading.trim()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::legal::{outline, split_clauses, CLAUSE_ID, CLAUSE_PARENT, CLAUSE_PATH};
+    use crate::processors::{get_processor, test_config};
+
+    const CONTRACT: &str = "UMOWA NAJMU\nzawarta w dniu 1 marca 2024 r.\n\n\
+            § 1. Przedmiot umowy\n1. Wynajmujący oddaje lokal w najem.\n2. Lokal ma 50 m2.\n2.1 Z piwnicą.\n\n\
+            § 2. Czynsz\n1. Czynsz wynosi 2000 zł.\nW 2024 roku nie podlega waloryzacji.\n\n\
+            Art. 3a Postanowienia końcowe\nZmiany wymagają formy pisemnej.";
+
+    #[test]
+    fn test_split_clauses() {
+        let clauses = split_clauses(CONTRACT);
+        let ids: Vec<&str> = clauses.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["preamble", "§ 1", "1", "2", "2.1", "§ 2", "1", "Art. 3a"]);
+
+        assert_eq!(clauses[4].path(), "§ 1 / 2 / 2.1");
+        assert_eq!(clauses[6].parent(), Some("§ 2"));
+        // Liczba w treści (bez kropki) nie otwiera nowego punktu
+        assert_eq!(clauses[6].text, "1. Czynsz wynosi 2000 zł.\nW 2024 roku nie podlega waloryzacji.");
+        assert_eq!(clauses[7].parent(), None);
+    }
+
+    #[test]
+    fn test_clause_processor_records() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("umowa.txt");
+        std::fs::write(&path, CONTRACT)?;
+
+        let config = crate::processors::ProcessorConfig {
+            language: "pl".to_string(),
+            ..test_config()
+        };
+        let result = get_processor("legal")?.process_file(&path.to_string_lossy(), &config)?;
+        assert_eq!(result.records.len(), 8);
+        assert_eq!(result.exchanges.len(), 8);
+
+        let record = &result.records[6];
+        assert_eq!(record.metadata[CLAUSE_ID], "1");
+        assert_eq!(record.metadata[CLAUSE_PARENT], "§ 2");
+        assert_eq!(record.metadata[CLAUSE_PATH], "§ 2 / 1");
+
+        let lines = outline(&result.records);
+        assert_eq!(lines[1], "§ 1: § 1. Przedmiot umowy");
+        assert_eq!(lines[4], "    2.1: 2.1 Z piwnicą.");
+        Ok(())
+    }
+}
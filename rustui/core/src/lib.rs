@@ -0,0 +1,185 @@
+//! Core of the AnyDataNext client without the terminal UI: the backend API client, the local
+//! processors, dataset formats, configuration and the tools around datasets.
+//!
+//! `anydataset-tui` is a thin binary on top of this crate; other Rust tools can embed the same
+//! pipeline. The configuration is read from the same `config.toml` the TUI uses, so a tool
+//! shares keys, presets and workspaces with it.
+//!
+//! Processing a document locally and writing the records as Alpaca:
+//!
+//! ```no_run
+//! use anydataset_core::config::Config;
+//! use anydataset_core::formats::{self, DatasetFormat};
+//! use anydataset_core::processors::{self, ProcessorConfig};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let config = Config::load()?;
+//! let processor = processors::get_processor("standard")?;
+//! let settings = ProcessorConfig {
+//!     model: config.default_model.clone(),
+//!     provider: config.default_provider.clone(),
+//!     language: config.default_language.clone(),
+//!     chunking: config.chunking.clone(),
+//!     cleanup: config.cleanup.clone(),
+//!     confidence: config.confidence.clone(),
+//!     figures: config.figures.clone(),
+//!     metadata: config.metadata.clone(),
+//!     best_of: config.best_of.clone(),
+//!     voting: config.voting.clone(),
+//!     ..Default::default()
+//! };
+//! let result = processor.process_file("report.txt", &settings)?;
+//! let alpaca = formats::convert(&[result], DatasetFormat::Alpaca, None)?;
+//! std::fs::write("report.alpaca.json", serde_json::to_vec_pretty(&alpaca)?)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Jobs on a backend go through [`api::ApiClient`]; HTTP settings of `config.toml` apply
+//! once [`network::configure`] has been called with them. Non-Rust programs reach the same
+//! processing, conversion and checks through the C ABI in [`ffi`].
+
+/// Client of the AnyDataNext backend: uploads, jobs, results and datasets
+pub mod api;
+/// Unpacking zip/tar.gz result archives
+pub mod archive;
+/// Atomic file writes (temporary file + rename)
+pub mod atomic;
+/// Encrypted archive of prompts and raw model responses
+pub mod audit;
+/// Work on a thread of its own with a tokio runtime
+pub mod background;
+/// Picking the best of N answers per chunk
+pub mod bestof;
+/// Export and import of portable configuration bundles
+pub mod bundle;
+/// Model lists of the providers, withdrawn models and their replacements
+pub mod catalog;
+/// Chaos mode: delays, errors and dropped connections injected into backend requests
+pub mod chaos;
+/// Quality checks of generated records
+pub mod checks;
+/// Chunking strategies and merging of chunk results
+pub mod chunking;
+/// Filters removing headers, footers and other noise from documents
+pub mod cleanup;
+/// OCR/transcription confidence and routing of weak chunks
+pub mod confidence;
+/// `config.toml`: loading, saving and every settings section
+pub mod config;
+/// Parsing of user files that turns panics into errors
+pub mod crashsafe;
+/// Provider API keys in the system keyring
+pub mod credentials;
+/// Backends on the local network (mDNS, with the `discovery` feature)
+pub mod discovery;
+/// Free space checks before writing
+pub mod diskspace;
+/// Document metadata (front matter, DOCX, PDF, EXIF)
+pub mod docmeta;
+/// Error types and the `processing_error!` and `api_error!` macros
+pub mod error;
+/// Extraction into user-defined schemas and JSONL/Parquet export
+pub mod extract;
+/// C ABI over the record pipeline for non-Rust frontends
+pub mod ffi;
+/// Figure captions from a vision model
+pub mod figures;
+/// Anonymized test fixtures from recorded runs
+pub mod fixture;
+/// Conversion of datasets to the Alpaca and ShareGPT layouts
+pub mod formats;
+/// Committing datasets to a git repository
+pub mod git;
+/// Local job history
+pub mod history;
+/// Actions run after results are downloaded
+pub mod hooks;
+/// Job files: many inputs, presets and outputs run in one go
+pub mod jobfile;
+/// Difficulty and category labels of QA records
+pub mod labeling;
+/// Latency of the backend and its mirrors
+pub mod latency;
+/// Splitting legal documents into their units
+pub mod legal;
+/// Provenance of records across pipeline stages
+pub mod lineage;
+/// Advisory locks on files shared between instances
+pub mod lock;
+/// Logging to the console and a log file, and the `log_*!` macros
+pub mod logger;
+/// Manifests of downloaded and produced datasets
+pub mod manifest;
+/// Shared HTTP client, request queue and its monitor
+pub mod network;
+/// Desktop notifications about finished jobs
+pub mod notifications;
+/// Numbers, units and dates for consistency checks
+pub mod numeric;
+/// Sections and tables of DOCX/ODT documents
+pub mod office;
+/// Local models of an Ollama server
+pub mod ollama;
+/// Names of output files
+pub mod output;
+/// Sections of scientific papers (text, Markdown, LaTeX)
+pub mod paper;
+/// Partial results of failed jobs and resuming from them
+pub mod partial;
+/// Text of PDF files page by page
+pub mod pdf;
+/// Judging answer pairs and exporting preference datasets
+pub mod preference;
+/// Corpus report before processing
+pub mod preflight;
+/// Processing presets and prompt templates
+pub mod preset;
+/// Team-shared presets (git/HTTP)
+pub mod preset_sync;
+/// The pipeline on a sample of chunks
+pub mod preview;
+/// Local processors of every processing type
+pub mod processors;
+/// Custom OpenAI-compatible providers
+pub mod providers;
+/// Job events published to MQTT/Redis
+pub mod pubsub;
+/// Question-answer pairs backed by a quote from the source
+pub mod qa;
+/// Usage quotas of the providers
+pub mod quota;
+/// Downloads in parallel ranged parts
+pub mod ranged;
+/// Usage reports as CSV/JSON
+pub mod report;
+/// Part files that let an interrupted download continue where it stopped
+pub mod resume;
+/// Resubmitting jobs after transient errors
+pub mod retry;
+/// ed25519 signatures of exported datasets
+pub mod signing;
+/// Disk space taken by results and the retention policy
+pub mod storage;
+/// Hierarchical map-reduce summaries
+pub mod summarize;
+/// Records generated from topics and a prompt template, with no input files
+pub mod synthetic;
+/// CSV/Parquet export with column mappings
+pub mod tabular;
+/// `{{variables}}` in prompts and paths
+pub mod templating;
+/// Glossary of terms and detection of mangled names
+pub mod terminology;
+/// `[theme]` settings; the palettes themselves belong to the UI
+pub mod theme;
+/// Provider requests and responses kept for debugging, with keys masked
+pub mod transcript;
+/// Encryption of results with the workspace key
+pub mod vault;
+/// Majority voting over sampled answers
+pub mod voting;
+/// Signed events for webhooks
+pub mod webhooks;
+/// Workspaces with their own history and directories
+pub mod workspace;
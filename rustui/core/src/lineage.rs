@@ -0,0 +1,359 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::processors::{ProcessingResult, Record};
+
+/// Record metadata keys used to link records across pipeline stages
+pub const RECORD_ID: &str = "record_id";
+pub const PARENT_IDS: &str = "parent_ids";
+pub const STAGE: &str = "stage";
+
+/// Text as hashed: line endings unified and surrounding whitespace dropped
+fn canonical_text(text: &str) -> String {
+    text.replace("\r\n", "\n").trim().to_string()
+}
+
+/// JSON with object keys sorted at every level, whatever order it was parsed in
+fn canonical_json(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut entries: Vec<(String, Value)> = object.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(entries.into_iter().map(|(key, value)| (key, canonical_json(value))).collect())
+        },
+        Value::Array(items) => Value::Array(items.into_iter().map(canonical_json).collect()),
+        other => other,
+    }
+}
+
+/// JSON completions (structured extraction) are compared as data, so re-serializing
+/// them with other spacing or key order keeps the id
+fn canonical_completion(completion: &str) -> String {
+    match serde_json::from_str::<Value>(completion) {
+        Ok(value @ (Value::Object(_) | Value::Array(_))) => canonical_json(value).to_string(),
+        _ => canonical_text(completion),
+    }
+}
+
+/// Id derived from a record's instruction, prompt and completion. The same content gets
+/// the same id in every run, file and format it passes through; metadata doesn't count.
+pub fn content_id(record: &Record) -> String {
+    let canonical = Value::from(vec![
+        canonical_text(&record.instruction),
+        canonical_text(&record.prompt),
+        canonical_completion(&record.completion),
+    ]);
+    let digest = Sha256::digest(canonical.to_string().as_bytes());
+    let hex: String = digest[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("rec-{}", hex)
+}
+
+/// The id a record carries, or its content id when it has none yet
+pub fn id_of(record: &Record) -> String {
+    record.metadata.get(RECORD_ID)
+        .and_then(|id| id.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| content_id(record))
+}
+
+/// Gives records of datasets written before they had ids their content id.
+/// Ids already assigned are never changed. Returns how many records got one.
+pub fn ensure_ids(results: &mut [ProcessingResult]) -> usize {
+    let mut assigned = 0;
+    for record in results.iter_mut().flat_map(|result| &mut result.records) {
+        if !record.metadata.contains_key(RECORD_ID) {
+            let id = content_id(record);
+            record.metadata.insert(RECORD_ID.to_string(), id.into());
+            assigned += 1;
+        }
+    }
+    assigned
+}
+
+/// Gives each record its content id and stage. Ids and links a processor set itself
+/// (from the source record, or between summary levels) are kept.
+pub fn tag(records: &mut [Record], stage: &str) {
+    for record in records {
+        if !record.metadata.contains_key(RECORD_ID) {
+            let id = content_id(record);
+            record.metadata.insert(RECORD_ID.to_string(), id.into());
+        }
+        record.metadata.entry(STAGE.to_string()).or_insert_with(|| stage.into());
+    }
+}
+
+/// Links records to the records they were derived from; links already set are kept.
+/// Records of a raw document have no parents and stay unlinked.
+pub fn link(records: &mut [Record], parents: &[String]) {
+    if parents.is_empty() {
+        return;
+    }
+    for record in records {
+        record.metadata.entry(PARENT_IDS.to_string()).or_insert_with(|| parents.to_vec().into());
+    }
+}
+
+/// Record of an earlier stage's output, as the input of the next stage
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceRecord {
+    pub id: String,
+    pub text: String,
+}
+
+/// Records of an input that is the output of an earlier stage, with their ids (content ids
+/// for records written before they had one). `None` for raw source documents.
+pub fn source_records(path: &Path) -> Option<Vec<SourceRecord>> {
+    let content = std::fs::read(path).ok()?;
+    let results = serde_json::from_slice::<Vec<ProcessingResult>>(&content).ok()?;
+    Some(results.iter()
+        .flat_map(|result| &result.records)
+        .map(|record| SourceRecord { id: id_of(record), text: record.completion.clone() })
+        .collect())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineageNode {
+    pub id: String,
+    pub stage: String,
+    /// Dataset file the record was found in
+    pub dataset: PathBuf,
+    pub completion: String,
+    pub parents: Vec<String>,
+}
+
+/// Records of several datasets, linked by their parent ids
+#[derive(Debug, Clone, Default)]
+pub struct LineageGraph {
+    nodes: BTreeMap<String, LineageNode>,
+}
+
+impl LineageGraph {
+    pub fn load(datasets: &[PathBuf]) -> Result<Self> {
+        let mut graph = Self::default();
+        for path in datasets {
+            let content = std::fs::read(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let results: Vec<ProcessingResult> = serde_json::from_slice(&content)
+                .with_context(|| format!("{} is not a dataset produced by a local run", path.display()))?;
+            graph.add_dataset(path, &results);
+        }
+        Ok(graph)
+    }
+
+    /// Datasets of a directory; files that aren't datasets of a local run are skipped
+    pub fn load_dir(dir: &Path) -> Self {
+        let mut graph = Self::default();
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return graph;
+        };
+        let mut paths: Vec<PathBuf> = entries.flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let Ok(content) = std::fs::read(&path) else {
+                continue;
+            };
+            if let Ok(results) = serde_json::from_slice::<Vec<ProcessingResult>>(&content) {
+                graph.add_dataset(&path, &results);
+            }
+        }
+        graph
+    }
+
+    pub fn add_dataset(&mut self, path: &Path, results: &[ProcessingResult]) {
+        for record in results.iter().flat_map(|result| &result.records) {
+            let Some(id) = record.metadata.get(RECORD_ID).and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let parents = record.metadata.get(PARENT_IDS)
+                .and_then(|v| v.as_array())
+                .map(|ids| ids.iter().filter_map(|id| id.as_str().map(str::to_string)).collect())
+                .unwrap_or_default();
+            let stage = record.metadata.get(STAGE).and_then(|v| v.as_str()).unwrap_or("unknown");
+            self.nodes.insert(id.to_string(), LineageNode {
+                id: id.to_string(),
+                stage: stage.to_string(),
+                dataset: path.to_path_buf(),
+                completion: record.completion.clone(),
+                parents,
+            });
+        }
+    }
+
+    /// How a record was derived: the record first, then its ancestors as an indented tree.
+    /// Parents missing from the loaded datasets are listed as such.
+    pub fn render(&self, id: &str) -> Option<Vec<String>> {
+        let node = self.nodes.get(id)?;
+        let mut lines = vec![describe(node)];
+        let mut visited = HashSet::from([id.to_string()]);
+        self.render_parents(node, "", &mut visited, &mut lines);
+        Some(lines)
+    }
+
+    fn render_parents(&self, node: &LineageNode, prefix: &str, visited: &mut HashSet<String>, lines: &mut Vec<String>) {
+        for (i, parent) in node.parents.iter().enumerate() {
+            let last = i + 1 == node.parents.len();
+            let (branch, indent) = if last { ("└─ ", "   ") } else { ("├─ ", "│  ") };
+            match self.nodes.get(parent) {
+                Some(parent_node) if visited.insert(parent.clone()) => {
+                    lines.push(format!("{}{}{}", prefix, branch, describe(parent_node)));
+                    self.render_parents(parent_node, &format!("{}{}", prefix, indent), visited, lines);
+                },
+                Some(_) => lines.push(format!("{}{}{} (shown above)", prefix, branch, parent)),
+                None => lines.push(format!("{}{}{} (not in the loaded datasets)", prefix, branch, parent)),
+            }
+        }
+    }
+}
+
+fn describe(node: &LineageNode) -> String {
+    let preview: String = node.completion.chars().take(60).collect();
+    let ellipsis = if node.completion.chars().count() > 60 { "…" } else { "" };
+    format!("[{}] {} ({}): {}{}", node.stage, node.id, node.dataset.display(), preview, ellipsis)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunking::ChunkSettings;
+    use crate::legal::ClauseProcessor;
+    use crate::lineage::{content_id, ensure_ids, id_of, tag, LineageGraph, PARENT_IDS, RECORD_ID};
+    use crate::processors::{run_with_cap, test_config, ProcessingResult, ProcessingStats, ProcessorConfig, Record, SpendCap, StandardProcessor, TranslateProcessor};
+    use crate::qa::{QaProcessor, QaSettings};
+    use crate::tabular;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_lineage_across_stages() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = ProcessorConfig {
+            language: "pl".to_string(),
+            ..test_config()
+        };
+        let mut cap = SpendCap::new(f64::MAX);
+        let doc = dir.path().join("doc.txt");
+        std::fs::write(&doc, "Rex przyszedł na coroczne badanie.")?;
+
+        // Etap 1 na surowym dokumencie, etap 2 na wynikach etapu 1
+        let stage1 = run_with_cap(&StandardProcessor, &[doc.display().to_string()], &config, 0.0, &mut cap);
+        let summary_path = dir.path().join("summary.json");
+        std::fs::write(&summary_path, serde_json::to_vec(&stage1.results)?)?;
+        let stage2 = run_with_cap(&TranslateProcessor, &[summary_path.display().to_string()], &config, 0.0, &mut cap);
+        let translated_path = dir.path().join("translated.json");
+        std::fs::write(&translated_path, serde_json::to_vec(&stage2.results)?)?;
+
+        let parent_id = stage1.results[0].records[0].metadata[RECORD_ID].as_str().unwrap().to_string();
+        let record = &stage2.results[0].records[0];
+        assert_eq!(record.metadata[PARENT_IDS], serde_json::json!([parent_id]));
+
+        let graph = LineageGraph::load(&[summary_path, translated_path])?;
+        let lines = graph.render(record.metadata[RECORD_ID].as_str().unwrap()).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("[translate]"));
+        assert!(lines[1].starts_with("└─ [standard]"));
+        assert!(lines[1].contains(&parent_id));
+        Ok(())
+    }
+
+    #[test]
+    fn test_outputs_link_to_their_own_source_record() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let contract = dir.path().join("umowa.txt");
+        std::fs::write(&contract, "§ 1. Najem
+Wynajmujący oddaje lokal w najem.
+
+§ 2. Czynsz
+Czynsz wynosi 2000 zł.")?;
+        let mut config = ProcessorConfig {
+            language: "pl".to_string(),
+            ..test_config()
+        };
+        let mut cap = SpendCap::new(f64::MAX);
+
+        // Surowy dokument nie ma rodziców
+        let clauses = run_with_cap(&ClauseProcessor, &[contract.display().to_string()], &config, 0.0, &mut cap);
+        assert_eq!(clauses.results[0].records.len(), 2);
+        assert!(clauses.results[0].records.iter().all(|r| !r.metadata.contains_key(PARENT_IDS)));
+        let clauses_path = dir.path().join("clauses.json");
+        std::fs::write(&clauses_path, serde_json::to_vec(&clauses.results)?)?;
+        let clause_ids: Vec<String> = clauses.results[0].records.iter().map(id_of).collect();
+
+        // Każdy wynik następnego etapu wskazuje tylko rekord, z którego powstał
+        config.chunking = Some(ChunkSettings::default());
+        for processor in [&TranslateProcessor as &dyn crate::processors::Processor, &StandardProcessor, &QaProcessor { settings: QaSettings::default() }] {
+            let run = run_with_cap(processor, &[clauses_path.display().to_string()], &config, 0.0, &mut cap);
+            let records = &run.results[0].records;
+            assert!(!records.is_empty(), "{}", processor.name());
+            for record in records {
+                let parents = record.metadata[PARENT_IDS].as_array().unwrap();
+                assert_eq!(parents.len(), 1, "{}", processor.name());
+                let parent = parents[0].as_str().unwrap();
+                let source = &clauses.results[0].records[clause_ids.iter().position(|id| id == parent).unwrap()];
+                // Wynik powstał z treści swojego rodzica
+                let first_words: String = source.completion.split_whitespace().take(2).collect::<Vec<_>>().join(" ");
+                assert!(record.prompt.contains(&first_words) || record.completion.contains(&first_words), "{}: {}", processor.name(), record.prompt);
+            }
+            let linked: std::collections::BTreeSet<&str> = records.iter().map(|r| r.metadata[PARENT_IDS][0].as_str().unwrap()).collect();
+            assert_eq!(linked.len(), 2, "{}", processor.name());
+        }
+        Ok(())
+    }
+
+    fn record(prompt: &str, completion: &str) -> Record {
+        Record {
+            instruction: "Podsumuj".to_string(),
+            prompt: prompt.to_string(),
+            completion: completion.to_string(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_content_id_is_canonical() {
+        let id = content_id(&record("Ala ma kota.\nI psa.", r#"{"a": 1, "b": [2, 3]}"#));
+        assert!(id.starts_with("rec-") && id.len() == 36);
+        // Końce linii, spacje wokół tekstu i kolejność kluczy JSON nie zmieniają id
+        assert_eq!(content_id(&record("  Ala ma kota.\r\nI psa.\n", r#"{"b":[2,3],"a":1}"#)), id);
+        // Metadane też nie
+        let mut tagged = record("Ala ma kota.\nI psa.", r#"{"a": 1, "b": [2, 3]}"#);
+        tagged.metadata.insert("stage".to_string(), "qa".into());
+        assert_eq!(content_id(&tagged), id);
+
+        assert_ne!(content_id(&record("Ala ma kota.\nI psa.", r#"{"a": 2, "b": [2, 3]}"#)), id);
+        assert_ne!(content_id(&record("Ala ma kota.", "I psa.")), content_id(&record("Ala ma kota. I psa.", "")));
+    }
+
+    #[test]
+    fn test_ids_are_kept() {
+        let mut records = vec![record("a", "b"), record("c", "d")];
+        records[1].metadata.insert(RECORD_ID.to_string(), "stary-uuid".into());
+        tag(&mut records, "standard");
+        assert_eq!(records[0].metadata[RECORD_ID], content_id(&records[0]));
+        assert_eq!(records[1].metadata[RECORD_ID], "stary-uuid");
+        // Ponowne przetworzenie tej samej treści daje to samo id
+        let mut again = vec![record("a", "b")];
+        tag(&mut again, "standard");
+        assert_eq!(again[0].metadata[RECORD_ID], records[0].metadata[RECORD_ID]);
+
+        let mut results = vec![ProcessingResult {
+            records: vec![record("e", "f"), records[1].clone()],
+            exchanges: vec![],
+            source_file: "doc.txt".to_string(),
+            processing_type: "standard".to_string(),
+            stats: ProcessingStats { total_records: 2, total_tokens: 0, processing_time_ms: 0 },
+            error: None,
+        }];
+        assert_eq!(id_of(&results[0].records[0]), content_id(&results[0].records[0]));
+        assert_eq!(ensure_ids(&mut results), 1);
+        assert_eq!(ensure_ids(&mut results), 0);
+        assert_eq!(results[0].records[1].metadata[RECORD_ID], "stary-uuid");
+
+        // Eksport tabeli domyślnie zaczyna się od id, także dla rekordów bez niego
+        let rows = tabular::rows(&[ProcessingResult { records: vec![record("g", "h")], ..results[0].clone() }], &tabular::default_columns());
+        assert_eq!(rows[0]["record_id"], content_id(&record("g", "h")));
+    }
+}
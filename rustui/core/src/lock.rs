@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use fs2::FileExt;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long to wait for another instance before giving up
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Advisory lock on `<file>.lock`, shared by every instance (TUI, CLI, daemons)
+/// touching the same file. Released on drop.
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+}
+
+impl FileLock {
+    pub fn acquire(target: &Path) -> Result<Self> {
+        Self::acquire_with_timeout(target, DEFAULT_TIMEOUT)
+    }
+
+    pub fn acquire_with_timeout(target: &Path, timeout: Duration) -> Result<Self> {
+        let path = lock_path(target);
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).context("Failed to create lock directory")?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("Failed to open lock file {}", path.display()))?;
+
+        let started = Instant::now();
+        while file.try_lock_exclusive().is_err() {
+            if started.elapsed() >= timeout {
+                anyhow::bail!("{}", describe_holder(&mut file, &path));
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+
+        // Record the holder so a blocked instance can say who it is waiting for
+        let command = std::env::args().collect::<Vec<_>>().join(" ");
+        let owner = format!("{} {} {}\n", std::process::id(), chrono::Utc::now().to_rfc3339(), command);
+        file.set_len(0).and_then(|_| file.seek(SeekFrom::Start(0))).and_then(|_| file.write_all(owner.as_bytes()))
+            .context("Failed to write lock file")?;
+        Ok(Self { file })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+pub fn lock_path(target: &Path) -> PathBuf {
+    let mut name = target.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    target.with_file_name(name)
+}
+
+/// Who holds the lock, from what the holder wrote into the file. The lock is released when
+/// its holder exits, so whoever this names is still running.
+fn describe_holder(file: &mut File, path: &Path) -> String {
+    let mut owner = String::new();
+    let _ = file.seek(SeekFrom::Start(0)).and_then(|_| file.read_to_string(&mut owner));
+    let mut parts = owner.trim_end().splitn(3, ' ');
+    let (pid, since, command) = (parts.next().and_then(|p| p.parse::<u32>().ok()), parts.next(), parts.next());
+
+    match (pid, since, command.filter(|c| !c.is_empty())) {
+        (Some(pid), Some(since), Some(command)) => format!(
+            "{} is locked by another instance (process {}: {}, since {})",
+            path.display(), pid, command, since
+        ),
+        (Some(pid), Some(since), None) => format!(
+            "{} is locked by another instance (process {}, since {})",
+            path.display(), pid, since
+        ),
+        _ => format!("{} is locked by another instance", path.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::{HistoryEntry, JobHistory};
+    use crate::lock::FileLock;
+    use std::time::Duration;
+
+    #[test]
+    fn test_lock_reports_holder() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let target = dir.path().join("config.toml");
+
+        let lock = FileLock::acquire(&target)?;
+        let err = FileLock::acquire_with_timeout(&target, Duration::from_millis(100)).unwrap_err();
+        assert!(err.to_string().contains(&format!("process {}", std::process::id())), "{}", err);
+        // Komenda posiadacza blokady, bez rady usuwania pliku
+        let program = std::env::args().next().unwrap_or_default();
+        assert!(err.to_string().contains(&program), "{}", err);
+        assert!(!err.to_string().contains("Remove"), "{}", err);
+
+        drop(lock);
+        assert!(FileLock::acquire_with_timeout(&target, Duration::from_millis(100)).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_histories_keep_all_entries() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("history.jsonl");
+        let entry = |job_id: &str| HistoryEntry {
+            job_id: job_id.to_string(),
+            file: "a.txt".to_string(),
+            processing_type: "standard".to_string(),
+            provider: String::new(),
+            model: String::new(),
+            status: "processing".to_string(),
+            submitted_at: chrono::Utc::now(),
+            finished_at: None,
+            records: None,
+            tokens: None,
+            cost_usd: None,
+            variables: Default::default(),
+        };
+
+        // Dwie instancje (np. TUI i CLI) wczytane przed zapisem drugiej
+        let mut first = JobHistory::load_from(path.clone())?;
+        let mut second = JobHistory::load_from(path.clone())?;
+        first.record(entry("job-1"))?;
+        second.record(entry("job-2"))?;
+
+        assert_eq!(JobHistory::load_from(path)?.entries().len(), 2);
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
 use std::path::PathBuf;
@@ -17,7 +18,9 @@ pub enum LogLevel {
 }
 
 impl LogLevel {
-    fn as_str(&self) -> &'static str {
+    pub const ALL: [LogLevel; 5] = [LogLevel::DEBUG, LogLevel::INFO, LogLevel::WARN, LogLevel::ERROR, LogLevel::FATAL];
+
+    pub fn as_str(&self) -> &'static str {
         match self {
             LogLevel::DEBUG => "DEBUG",
             LogLevel::INFO => "INFO",
@@ -39,6 +42,20 @@ impl LogLevel {
     }
 }
 
+/// Entries kept in memory for the Logs screen; older ones are only in the log file
+pub const RING_CAPACITY: usize = 2000;
+
+/// One logged message, as kept in the in-memory ring buffer
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    /// Number of the entry since start; tells a viewer which entries it has seen
+    pub seq: u64,
+    pub at: chrono::DateTime<chrono::Local>,
+    pub level: LogLevel,
+    pub module: String,
+    pub message: String,
+}
+
 // Global logger instance
 static LOGGER: Lazy<Mutex<Logger>> = Lazy::new(|| {
     Mutex::new(Logger::new().unwrap_or_else(|e| {
@@ -47,6 +64,8 @@ static LOGGER: Lazy<Mutex<Logger>> = Lazy::new(|| {
             log_file: None,
             console_level: LogLevel::INFO,
             file_level: LogLevel::DEBUG,
+            ring: VecDeque::new(),
+            logged: 0,
         }
     }))
 });
@@ -55,6 +74,9 @@ pub struct Logger {
     log_file: Option<File>,
     console_level: LogLevel,
     file_level: LogLevel,
+    /// Latest entries of every level, oldest first
+    ring: VecDeque<LogEntry>,
+    logged: u64,
 }
 
 impl Logger {
@@ -76,7 +98,6 @@ impl Logger {
         let log_file_path = log_dir.join(format!("anydataset-tui-{}.log", now));
         
         let file = OpenOptions::new()
-            .write(true)
             .create(true)
             .append(true)
             .open(log_file_path)
@@ -86,11 +107,20 @@ impl Logger {
             log_file: Some(file),
             console_level: LogLevel::INFO,
             file_level: LogLevel::DEBUG,
+            ring: VecDeque::new(),
+            logged: 0,
         })
     }
     
     fn log_impl(&mut self, level: LogLevel, message: &str, module: &str) -> Result<()> {
-        let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+        let at = chrono::Local::now();
+        self.logged += 1;
+        if self.ring.len() == RING_CAPACITY {
+            self.ring.pop_front();
+        }
+        self.ring.push_back(LogEntry { seq: self.logged, at, level, module: module.to_string(), message: message.to_string() });
+
+        let now = at.format("%Y-%m-%d %H:%M:%S%.3f").to_string();
         let log_entry = format!("[{}] [{}] [{}]: {}\n", now, level.as_str(), module, message);
         
         // Log do pliku
@@ -158,6 +188,15 @@ pub fn fatal(message: &str, module: &str) {
     log(LogLevel::FATAL, message, module);
 }
 
+/// Entries of the ring buffer logged after entry `seq` (0 for all of them), oldest first
+pub fn entries_since(seq: u64) -> Vec<LogEntry> {
+    let Ok(logger) = LOGGER.lock() else {
+        return Vec::new();
+    };
+    let skip = logger.ring.partition_point(|entry| entry.seq <= seq);
+    logger.ring.iter().skip(skip).cloned().collect()
+}
+
 // Ustawienia poziomu logowania
 pub fn set_console_level(level: LogLevel) {
     if let Ok(mut logger) = LOGGER.lock() {
@@ -0,0 +1,86 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::atomic;
+use crate::history::HistoryEntry;
+use crate::vault::Vault;
+
+/// Provenance record written next to every downloaded or exported dataset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetManifest {
+    pub file_name: String,
+    pub job_id: String,
+    pub source_file: String,
+    pub processing_type: String,
+    pub provider: String,
+    pub model: String,
+    pub size_bytes: u64,
+    pub created_at: DateTime<Utc>,
+    pub client_version: String,
+    /// Profile variables substituted into the job's prompts
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    /// Set when the dataset holds the partial results of a job that failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<JobFailure>,
+}
+
+/// Why a job stopped before it was done, and what it didn't get to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobFailure {
+    pub reason: String,
+    pub failed_at: DateTime<Utc>,
+    /// Source files without results, retried when the job is resumed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remaining_files: Vec<String>,
+}
+
+impl DatasetManifest {
+    pub fn for_dataset(dataset: &Path, entry: &HistoryEntry) -> Result<Self> {
+        let size_bytes = std::fs::metadata(dataset)
+            .with_context(|| format!("Cannot read {}", dataset.display()))?
+            .len();
+
+        Ok(Self {
+            file_name: dataset.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+            job_id: entry.job_id.clone(),
+            source_file: entry.file.clone(),
+            processing_type: entry.processing_type.clone(),
+            provider: entry.provider.clone(),
+            model: entry.model.clone(),
+            size_bytes,
+            created_at: Utc::now(),
+            client_version: env!("CARGO_PKG_VERSION").to_string(),
+            variables: entry.variables.clone(),
+            failure: None,
+        })
+    }
+
+    pub fn with_failure(mut self, failure: JobFailure) -> Self {
+        self.failure = Some(failure);
+        self
+    }
+
+    /// Reads a manifest, decrypting it when it was encrypted along with its dataset
+    pub fn load(path: &Path, vault: &Vault) -> Result<Self> {
+        let content = vault.read(path)?;
+        serde_json::from_slice(&content).with_context(|| format!("Invalid manifest {}", path.display()))
+    }
+
+    /// Path of the manifest belonging to a dataset file (`data.jsonl` -> `data.jsonl.manifest.json`)
+    pub fn path_for(dataset: &Path) -> PathBuf {
+        let mut name = dataset.file_name().unwrap_or_default().to_os_string();
+        name.push(".manifest.json");
+        dataset.with_file_name(name)
+    }
+
+    pub fn write_for(&self, dataset: &Path) -> Result<PathBuf> {
+        let path = Self::path_for(dataset);
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize manifest")?;
+        atomic::write(&path, content).context("Failed to write manifest")?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,781 @@
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, Semaphore};
+
+use crate::chaos::{self, Chaos, ChaosStats, Fault};
+
+/// Requests sent at once by one monitor; the rest wait in the queue
+pub const MAX_CONCURRENT_REQUESTS: usize = 4;
+/// Extra attempts for GET requests that failed to connect or timed out
+pub const MAX_RETRIES: u32 = 2;
+const RETRY_DELAY: Duration = Duration::from_millis(250);
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 120;
+const DEFAULT_TRANSFER_TIMEOUT_SECS: u64 = 3600;
+/// Stands in for "no limit" where a request has to override the client's timeout
+const NO_LIMIT: Duration = Duration::from_secs(365 * 24 * 3600);
+
+static SHARED_CLIENT: OnceCell<reqwest::Client> = OnceCell::new();
+static BACKEND_CLIENT: OnceCell<reqwest::Client> = OnceCell::new();
+static TRANSFER_TIMEOUT: OnceCell<Duration> = OnceCell::new();
+
+/// Connection reuse and timeouts for every HTTP request of the app; unset pool values keep
+/// reqwest's defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpSettings {
+    /// Idle connections kept open per host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Seconds an idle connection is kept before it's closed (reqwest: 90)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_idle_timeout_secs: Option<u64>,
+    /// For backend API requests: `true` speaks HTTP/2 right away, also without TLS; `false` sticks
+    /// to HTTP/1.1. Unset negotiates the version with TLS servers, as requests to other hosts always do.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http2: Option<bool>,
+    /// Interval of TCP keep-alive probes in seconds; unset sends none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_keepalive_secs: Option<u64>,
+    /// Seconds to wait for a connection to a host (default 10)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<u64>,
+    /// Seconds a request may take until its response has arrived (default 120); 0 waits forever
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_timeout_secs: Option<u64>,
+    /// The same for uploads and downloads of files (default 3600)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_timeout_secs: Option<u64>,
+    /// Proxy for every request; unset uses `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` if they are set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxySettings>,
+}
+
+/// `[http.proxy]` in config.toml, for networks that only reach the backend through a proxy
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxySettings {
+    /// e.g. `http://proxy.corp:3128`; HTTPS requests are tunneled through it as well
+    pub url: String,
+    /// Hosts reached directly: names (also matching their subdomains), IP addresses or CIDR ranges
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_proxy: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Password for `username`, or `env:NAME` to read it from the environment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+impl ProxySettings {
+    pub fn proxy(&self) -> Result<reqwest::Proxy> {
+        let mut proxy = reqwest::Proxy::all(&self.url)
+            .with_context(|| format!("Invalid proxy URL '{}'", self.url))?
+            .no_proxy(reqwest::NoProxy::from_string(&self.no_proxy.join(",")));
+        if let Some(username) = &self.username {
+            let password = match self.password.as_deref() {
+                Some(value) => match value.strip_prefix("env:") {
+                    Some(var) => std::env::var(var.trim())
+                        .with_context(|| format!("Proxy password variable {} is not set", var.trim()))?,
+                    None => value.to_string(),
+                },
+                None => String::new(),
+            };
+            proxy = proxy.basic_auth(username, &password);
+        }
+        Ok(proxy)
+    }
+}
+
+impl HttpSettings {
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs.unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS))
+    }
+
+    pub fn request_timeout(&self) -> Option<Duration> {
+        limit(self.request_timeout_secs.unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS))
+    }
+
+    pub fn transfer_timeout(&self) -> Option<Duration> {
+        limit(self.transfer_timeout_secs.unwrap_or(DEFAULT_TRANSFER_TIMEOUT_SECS))
+    }
+}
+
+fn limit(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Client for models, webhooks, shared presets and other hosts. `http2` isn't applied: many of
+/// them (a local Ollama, webhook receivers) only speak HTTP/1.1.
+pub fn build_client(settings: &HttpSettings) -> Result<reqwest::Client> {
+    builder(settings)?.build().context("Failed to build HTTP client")
+}
+
+/// Client for backend API requests: the settings of `build_client` plus the `http2` choice
+pub fn build_backend_client(settings: &HttpSettings) -> Result<reqwest::Client> {
+    let builder = match settings.http2 {
+        Some(true) => builder(settings)?.http2_prior_knowledge(),
+        Some(false) => builder(settings)?.http1_only(),
+        None => builder(settings)?,
+    };
+    builder.build().context("Failed to build HTTP client")
+}
+
+fn builder(settings: &HttpSettings) -> Result<reqwest::ClientBuilder> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(max) = settings.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max);
+    }
+    if let Some(secs) = settings.pool_idle_timeout_secs {
+        builder = builder.pool_idle_timeout(Duration::from_secs(secs));
+    }
+    if let Some(secs) = settings.tcp_keepalive_secs {
+        builder = builder.tcp_keepalive(Duration::from_secs(secs));
+    }
+    // reqwest falls back to the proxy environment variables by itself
+    if let Some(proxy) = &settings.proxy {
+        builder = builder.proxy(proxy.proxy()?);
+    }
+    builder = builder.connect_timeout(settings.connect_timeout());
+    if let Some(timeout) = settings.request_timeout() {
+        builder = builder.timeout(timeout);
+    }
+    Ok(builder)
+}
+
+/// Whether `name: value` can be sent as an HTTP header
+pub fn check_header(name: &str, value: &str) -> Result<()> {
+    reqwest::header::HeaderName::from_bytes(name.as_bytes())
+        .with_context(|| format!("Invalid header name '{}'", name))?;
+    reqwest::header::HeaderValue::from_str(value)
+        .with_context(|| format!("Invalid value of header '{}'", name))?;
+    Ok(())
+}
+
+/// Builds the clients shared by the whole app. Call once at startup, before the first
+/// request; until then (and in tests) clients with default settings are used.
+pub fn configure(settings: &HttpSettings) -> Result<()> {
+    let client = build_client(settings)?;
+    let backend = build_backend_client(settings)?;
+    SHARED_CLIENT.set(client).map_err(|_| anyhow::anyhow!("HTTP client is already in use"))?;
+    BACKEND_CLIENT.set(backend).map_err(|_| anyhow::anyhow!("HTTP client is already in use"))?;
+    let _ = TRANSFER_TIMEOUT.set(settings.transfer_timeout().unwrap_or(NO_LIMIT));
+    Ok(())
+}
+
+/// The shared client; clones share its connection pool
+pub fn client() -> reqwest::Client {
+    SHARED_CLIENT.get_or_init(|| build_client(&HttpSettings::default()).unwrap_or_default()).clone()
+}
+
+/// The shared client of backend API requests, behind `HttpClient`
+pub fn backend_client() -> reqwest::Client {
+    BACKEND_CLIENT.get_or_init(|| build_backend_client(&HttpSettings::default()).unwrap_or_default()).clone()
+}
+
+/// Time allowed for uploading or downloading a file, in place of the request timeout
+pub fn transfer_timeout() -> Duration {
+    *TRANSFER_TIMEOUT.get_or_init(|| HttpSettings::default().transfer_timeout().unwrap_or(NO_LIMIT))
+}
+
+/// Request shown on the Network screen
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+    pub id: u64,
+    pub method: String,
+    /// Path and query, without the backend address
+    pub endpoint: String,
+    pub queued_at: Instant,
+    /// When the request left the queue; `None` while it's waiting for a free slot
+    pub started_at: Option<Instant>,
+    pub retries: u32,
+}
+
+impl RequestInfo {
+    pub fn in_flight(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    /// Time since the request was sent, or queued when it's still waiting
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started_at.unwrap_or(self.queued_at))
+    }
+}
+
+struct Entry {
+    info: RequestInfo,
+    cancel: Option<oneshot::Sender<()>>,
+}
+
+/// Download shown as one progress gauge, however many requests it's split into
+#[derive(Debug, Clone)]
+pub struct TransferInfo {
+    pub id: u64,
+    pub received: u64,
+    /// Size of the file; `None` until the backend tells it
+    pub total: Option<u64>,
+}
+
+/// Error of a request cancelled on the Network screen
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Request cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Tracks the requests of every client it's attached to and lets the UI cancel them
+#[derive(Clone)]
+pub struct NetworkMonitor {
+    requests: Arc<Mutex<Vec<Entry>>>,
+    transfers: Arc<Mutex<Vec<TransferInfo>>>,
+    next_id: Arc<AtomicU64>,
+    slots: Arc<Semaphore>,
+    /// Faults injected into every request when chaos mode is on
+    chaos: Option<Arc<Chaos>>,
+}
+
+impl Default for NetworkMonitor {
+    fn default() -> Self {
+        Self {
+            requests: Arc::new(Mutex::new(Vec::new())),
+            transfers: Arc::new(Mutex::new(Vec::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            slots: Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS)),
+            chaos: None,
+        }
+    }
+}
+
+impl NetworkMonitor {
+    pub fn with_chaos(mut self, chaos: Option<Chaos>) -> Self {
+        self.chaos = chaos.map(Arc::new);
+        self
+    }
+
+    /// Faults injected so far; `None` when chaos mode is off
+    pub fn chaos_stats(&self) -> Option<ChaosStats> {
+        self.chaos.as_ref().map(|chaos| chaos.stats())
+    }
+
+    /// In-flight requests first, each group oldest first
+    pub fn snapshot(&self) -> Vec<RequestInfo> {
+        let mut requests: Vec<RequestInfo> = match self.requests.lock() {
+            Ok(requests) => requests.iter().map(|e| e.info.clone()).collect(),
+            Err(_) => Vec::new(),
+        };
+        requests.sort_by_key(|r| (!r.in_flight(), r.id));
+        requests
+    }
+
+    /// Aborts the request; its caller gets a "cancelled" error. False when it already finished.
+    pub fn cancel(&self, id: u64) -> bool {
+        let Ok(mut requests) = self.requests.lock() else {
+            return false;
+        };
+        requests.iter_mut()
+            .find(|e| e.info.id == id)
+            .and_then(|e| e.cancel.take())
+            .is_some_and(|cancel| cancel.send(()).is_ok())
+    }
+
+    pub fn transfers(&self) -> Vec<TransferInfo> {
+        match self.transfers.lock() {
+            Ok(transfers) => transfers.clone(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Bytes received and expected over all downloads whose size is known
+    pub fn transfer_progress(&self) -> Option<(u64, u64)> {
+        let transfers = self.transfers();
+        let sized: Vec<(u64, u64)> = transfers.iter()
+            .filter_map(|t| t.total.map(|total| (t.received, total)))
+            .collect();
+        if sized.is_empty() {
+            return None;
+        }
+        Some(sized.iter().fold((0, 0), |(received, total), (r, t)| (received + r, total + t)))
+    }
+
+    /// Starts tracking a download; it's listed until the returned guard is dropped
+    pub fn start_transfer(&self) -> Transfer {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut transfers) = self.transfers.lock() {
+            transfers.push(TransferInfo { id, received: 0, total: None });
+        }
+        Transfer { monitor: self.clone(), id }
+    }
+
+    fn update_transfer(&self, id: u64, change: impl FnOnce(&mut TransferInfo)) {
+        if let Ok(mut transfers) = self.transfers.lock() {
+            if let Some(transfer) = transfers.iter_mut().find(|t| t.id == id) {
+                change(transfer);
+            }
+        }
+    }
+
+    fn register(&self, request: &reqwest::Request) -> (u64, oneshot::Receiver<()>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let url = request.url();
+        let endpoint = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let (cancel, cancelled) = oneshot::channel();
+        if let Ok(mut requests) = self.requests.lock() {
+            requests.push(Entry {
+                info: RequestInfo {
+                    id,
+                    method: request.method().to_string(),
+                    endpoint,
+                    queued_at: Instant::now(),
+                    started_at: None,
+                    retries: 0,
+                },
+                cancel: Some(cancel),
+            });
+        }
+        (id, cancelled)
+    }
+
+    fn update(&self, id: u64, change: impl FnOnce(&mut RequestInfo)) {
+        if let Ok(mut requests) = self.requests.lock() {
+            if let Some(entry) = requests.iter_mut().find(|e| e.info.id == id) {
+                change(&mut entry.info);
+            }
+        }
+    }
+
+    fn finish(&self, id: u64) {
+        if let Ok(mut requests) = self.requests.lock() {
+            requests.retain(|e| e.info.id != id);
+        }
+    }
+
+    /// Waits for a free slot, then sends; GETs are retried on connection errors and timeouts
+    async fn execute(&self, client: &reqwest::Client, id: u64, mut request: reqwest::Request) -> Result<reqwest::Response> {
+        let _slot = self.slots.acquire().await.context("Request queue closed")?;
+        self.update(id, |info| info.started_at = Some(Instant::now()));
+        let mut attempt = 0;
+        loop {
+            let spare = (attempt < MAX_RETRIES && request.method() == reqwest::Method::GET)
+                .then(|| request.try_clone())
+                .flatten();
+            let (err, retryable) = match self.inject().await {
+                Fault::Pass => match client.execute(request).await {
+                    Ok(response) => return Ok(response),
+                    Err(err) => {
+                        let retryable = err.is_connect() || err.is_timeout();
+                        (anyhow::Error::from(err), retryable)
+                    },
+                },
+                Fault::Status(status) => return Ok(chaos::response(status)),
+                Fault::Drop => (anyhow::Error::from(chaos::Dropped), true),
+            };
+            match spare {
+                Some(next) if retryable => {
+                    attempt += 1;
+                    self.update(id, |info| info.retries = attempt);
+                    tokio::time::sleep(RETRY_DELAY * attempt).await;
+                    request = next;
+                },
+                _ => return Err(err),
+            }
+        }
+    }
+
+    /// Holds the request back and picks its fault when chaos mode is on
+    async fn inject(&self) -> Fault {
+        let Some(chaos) = &self.chaos else {
+            return Fault::Pass;
+        };
+        let latency = chaos.latency();
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        chaos.fault()
+    }
+}
+
+/// Removes the request from the monitor however `send` ends, including when it's dropped
+struct Tracked<'a> {
+    monitor: &'a NetworkMonitor,
+    id: u64,
+}
+
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        self.monitor.finish(self.id);
+    }
+}
+
+/// Progress of a download started with `NetworkMonitor::start_transfer`
+pub struct Transfer {
+    monitor: NetworkMonitor,
+    id: u64,
+}
+
+impl Transfer {
+    pub fn set_total(&self, total: Option<u64>) {
+        self.monitor.update_transfer(self.id, |t| t.total = total);
+    }
+
+    pub fn add(&self, bytes: u64) {
+        self.monitor.update_transfer(self.id, |t| t.received += bytes);
+    }
+
+    /// Takes back bytes of a part that failed and will be fetched again
+    pub fn rewind(&self, bytes: u64) {
+        self.monitor.update_transfer(self.id, |t| t.received = t.received.saturating_sub(bytes));
+    }
+}
+
+impl Drop for Transfer {
+    fn drop(&mut self) {
+        if let Ok(mut transfers) = self.monitor.transfers.lock() {
+            transfers.retain(|t| t.id != self.id);
+        }
+    }
+}
+
+/// The shared client with its requests going through a `NetworkMonitor`
+#[derive(Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    monitor: NetworkMonitor,
+    /// Sent with every request, e.g. `User-Agent` or gateway headers
+    headers: Vec<(String, String)>,
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(NetworkMonitor::default())
+    }
+}
+
+impl HttpClient {
+    pub fn new(monitor: NetworkMonitor) -> Self {
+        Self { client: backend_client(), monitor, headers: Vec::new() }
+    }
+
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    pub fn monitor(&self) -> &NetworkMonitor {
+        &self.monitor
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.request(self.client.get(url))
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.request(self.client.post(url))
+    }
+
+    fn request(&self, mut builder: reqwest::RequestBuilder) -> RequestBuilder {
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        RequestBuilder { builder, client: self.client.clone(), monitor: self.monitor.clone() }
+    }
+}
+
+/// The parts of `reqwest::RequestBuilder` the API client uses
+pub struct RequestBuilder {
+    builder: reqwest::RequestBuilder,
+    client: reqwest::Client,
+    monitor: NetworkMonitor,
+}
+
+impl RequestBuilder {
+    pub fn query<T: Serialize + ?Sized>(mut self, query: &T) -> Self {
+        self.builder = self.builder.query(query);
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.builder = self.builder.header(name, value);
+        self
+    }
+
+    pub fn json<T: Serialize + ?Sized>(mut self, json: &T) -> Self {
+        self.builder = self.builder.json(json);
+        self
+    }
+
+    pub fn multipart(mut self, form: reqwest::multipart::Form) -> Self {
+        self.builder = self.builder.multipart(form);
+        self
+    }
+
+    /// Gives a file upload or download `transfer_timeout` instead of the request timeout
+    pub fn transfer(mut self) -> Self {
+        self.builder = self.builder.timeout(transfer_timeout());
+        self
+    }
+
+    /// Sends the request once it gets a slot; ends early with an error when it's cancelled
+    pub async fn send(self) -> Result<reqwest::Response> {
+        let request = self.builder.build()?;
+        let (id, cancelled) = self.monitor.register(&request);
+        let _tracked = Tracked { monitor: &self.monitor, id };
+        tokio::select! {
+            result = self.monitor.execute(&self.client, id, request) => result,
+            _ = cancelled => Err(Cancelled.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::ApiClient;
+    use crate::network::{build_backend_client, build_client, HttpSettings, NetworkMonitor, ProxySettings, RequestInfo, MAX_CONCURRENT_REQUESTS};
+    use std::time::Duration;
+
+    #[test]
+    fn test_http_settings() -> anyhow::Result<()> {
+        let settings: HttpSettings = toml::from_str(r#"
+                pool_max_idle_per_host = 32
+                pool_idle_timeout_secs = 300
+                http2 = true
+                tcp_keepalive_secs = 60
+            "#)?;
+        assert_eq!(settings.pool_max_idle_per_host, Some(32));
+        assert_eq!(settings.http2, Some(true));
+        build_client(&settings)?;
+        build_backend_client(&settings)?;
+        build_backend_client(&HttpSettings { http2: Some(false), ..HttpSettings::default() })?;
+
+        // Nieustawione wartości nie trafiają do pliku konfiguracji
+        assert_eq!(toml::to_string(&HttpSettings::default())?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_http_timeouts() -> anyhow::Result<()> {
+        let defaults = HttpSettings::default();
+        assert_eq!(defaults.connect_timeout(), Duration::from_secs(10));
+        assert_eq!(defaults.request_timeout(), Some(Duration::from_secs(120)));
+        assert_eq!(defaults.transfer_timeout(), Some(Duration::from_secs(3600)));
+
+        let settings: HttpSettings = toml::from_str(r#"
+                connect_timeout_secs = 3
+                request_timeout_secs = 0
+                transfer_timeout_secs = 600
+            "#)?;
+        assert_eq!(settings.connect_timeout(), Duration::from_secs(3));
+        // 0 wyłącza limit
+        assert_eq!(settings.request_timeout(), None);
+        assert_eq!(settings.transfer_timeout(), Some(Duration::from_secs(600)));
+        build_client(&settings)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_ends_a_hanging_request() -> anyhow::Result<()> {
+        // Serwer przyjmuje połączenie, ale nigdy nie odpowiada
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                connections.push(socket);
+            }
+        });
+
+        let client = build_client(&HttpSettings { request_timeout_secs: Some(1), ..HttpSettings::default() })?;
+        let started = std::time::Instant::now();
+        let err = client.get(format!("http://{}/api/version", address)).send().await.unwrap_err();
+        assert!(err.is_timeout());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_http2_applies_only_to_backend_client() -> anyhow::Result<()> {
+        // Serwer mówiący tylko HTTP/1.1, jak lokalna Ollama
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buffer = [0; 1024];
+                let _ = socket.read(&mut buffer).await;
+                let _ = socket.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok").await;
+            }
+        });
+        let settings = HttpSettings { http2: Some(true), request_timeout_secs: Some(5), ..HttpSettings::default() };
+        let url = format!("http://{}/api/tags", address);
+
+        let response = build_client(&settings)?.get(&url).send().await?;
+        assert_eq!(response.text().await?, "ok");
+        // Klient backendu od razu mówi HTTP/2, więc taki serwer go nie zrozumie
+        assert!(build_backend_client(&settings)?.get(&url).send().await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_requests_go_through_proxy() -> anyhow::Result<()> {
+        // Serwer mockito udaje proxy: dostaje żądanie z pełnym adresem backendu
+        let mut proxy = mockito::Server::new_async().await;
+        let mock = proxy.mock("GET", "/api/version")
+            .match_header("proxy-authorization", "Basic YWxpY2U6czNjcjN0")
+            .with_status(200)
+            .with_body(r#"{"version":"1.0.0"}"#)
+            .create_async()
+            .await;
+        std::env::set_var("ANYDATASET_TEST_PROXY_PASSWORD", "s3cr3t");
+        let settings: HttpSettings = toml::from_str(&format!(r#"
+                [proxy]
+                url = "{}"
+                no_proxy = ["direct.example"]
+                username = "alice"
+                password = "env:ANYDATASET_TEST_PROXY_PASSWORD"
+            "#, proxy.url()))?;
+
+        let client = build_client(&settings)?;
+        let response = client.get("http://backend.example/api/version").send().await?;
+        assert_eq!(response.status(), 200);
+        mock.assert_async().await;
+
+        // Hosty z no_proxy są łączone bezpośrednio, z pominięciem proxy
+        let err = client.get("http://direct.example/api/version").send().await.unwrap_err();
+        assert!(err.is_connect() || err.is_request());
+        mock.expect(1).assert_async().await;
+
+        // Zmienna z hasłem musi istnieć
+        let missing = ProxySettings { password: Some("env:ANYDATASET_NO_SUCH_VAR".to_string()), ..settings.proxy.clone().unwrap() };
+        assert!(build_client(&HttpSettings { proxy: Some(missing), ..HttpSettings::default() }).is_err());
+        assert!(build_client(&HttpSettings {
+            proxy: Some(ProxySettings { url: "::not a url".to_string(), ..ProxySettings::default() }),
+            ..HttpSettings::default()
+        }).is_err());
+        Ok(())
+    }
+
+    // Czeka, aż monitor zobaczy `count` żądań
+    async fn wait_for(monitor: &NetworkMonitor, count: usize) -> Vec<RequestInfo> {
+        for _ in 0..100 {
+            let requests = monitor.snapshot();
+            if requests.len() >= count {
+                return requests;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        monitor.snapshot()
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stuck_request() -> anyhow::Result<()> {
+        // Serwer, który przyjmuje połączenia, ale nigdy nie odpowiada
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let monitor = NetworkMonitor::default();
+        let client = ApiClient::new(&format!("http://{}", listener.local_addr()?)).with_monitor(monitor.clone());
+        let request = tokio::spawn(async move { client.get_job_status("job-1").await });
+
+        let requests = wait_for(&monitor, 1).await;
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].endpoint, "/api/jobs/job-1");
+        assert!(requests[0].in_flight());
+
+        assert!(monitor.cancel(requests[0].id));
+        let err = request.await?.unwrap_err();
+        assert!(format!("{:#}", err).contains("cancelled"));
+        assert!(monitor.snapshot().is_empty());
+        assert!(!monitor.cancel(requests[0].id));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_requests_over_limit_wait_in_queue() -> anyhow::Result<()> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let monitor = NetworkMonitor::default();
+        let mut tasks = Vec::new();
+        for i in 0..=MAX_CONCURRENT_REQUESTS {
+            let client = ApiClient::new(&url).with_monitor(monitor.clone());
+            tasks.push(tokio::spawn(async move { client.get_job_status(&format!("job-{}", i)).await }));
+        }
+
+        let requests = wait_for(&monitor, MAX_CONCURRENT_REQUESTS + 1).await;
+        assert_eq!(requests.iter().filter(|r| r.in_flight()).count(), MAX_CONCURRENT_REQUESTS);
+        // Oczekujące żądania są na końcu listy
+        let queued = requests.last().unwrap();
+        assert!(!queued.in_flight());
+
+        assert!(monitor.cancel(queued.id));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(monitor.snapshot().len(), MAX_CONCURRENT_REQUESTS);
+        for task in tasks {
+            task.abort();
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_request_headers_per_workspace() {
+        use crate::workspace::WorkspaceSettings;
+        let mut config = crate::config::Config { user_agent: Some("acme-datasets/1.0".to_string()), ..Default::default() };
+        config.request_headers.insert("X-Org-Id".to_string(), "acme".to_string());
+        config.request_headers.insert("X-Trace-Token".to_string(), "env:ANYDATASET_TEST_TRACE_TOKEN".to_string());
+        config.request_headers.insert("Bad Header".to_string(), "x".to_string());
+        std::env::set_var("ANYDATASET_TEST_TRACE_TOKEN", "t-123");
+
+        let headers = config.request_headers();
+        // Nagłówek z niedozwoloną nazwą jest pomijany
+        assert_eq!(headers, vec![
+            ("User-Agent".to_string(), "acme-datasets/1.0".to_string()),
+            ("X-Org-Id".to_string(), "acme".to_string()),
+            ("X-Trace-Token".to_string(), "t-123".to_string()),
+        ]);
+
+        config.workspaces.insert("lab".to_string(), WorkspaceSettings {
+            user_agent: Some("lab-client".to_string()),
+            request_headers: [("X-Org-Id".to_string(), "lab".to_string())].into(),
+            ..Default::default()
+        });
+        config.workspace = Some("lab".to_string());
+        std::env::remove_var("ANYDATASET_TEST_TRACE_TOKEN");
+        assert_eq!(config.request_headers(), vec![
+            ("User-Agent".to_string(), "lab-client".to_string()),
+            ("X-Org-Id".to_string(), "lab".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_headers_sent_with_every_request() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("GET", "/api/version")
+            .match_header("user-agent", "acme-datasets/1.0")
+            .match_header("x-org-id", "acme")
+            .with_status(200)
+            .with_body(r#"{"version": "1.2.0"}"#)
+            .create();
+
+        let headers = vec![
+            ("User-Agent".to_string(), "acme-datasets/1.0".to_string()),
+            ("X-Org-Id".to_string(), "acme".to_string()),
+        ];
+        // Nagłówki przetrwają późniejsze podpięcie monitora
+        let client = ApiClient::new(&server.url()).with_headers(headers).with_monitor(NetworkMonitor::default());
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        runtime.block_on(client.get_version())?;
+        mock.assert();
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// `[notifications]` in config.toml: telling the user that a tracked job finished while
+/// the terminal is in the background
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Ring the terminal bell when no desktop notification can be shown
+    #[serde(default = "default_true")]
+    pub bell: bool,
+    /// Notify even while the terminal has focus
+    #[serde(default)]
+    pub when_focused: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self { enabled: true, bell: true, when_focused: false }
+    }
+}
+
+/// Summary and body of the notification about a job that ended with `status`
+pub fn job_finished(job_id: &str, status: &str, file: Option<&str>) -> (String, String) {
+    let summary = format!("AnyDataset job {}", status);
+    let body = match file {
+        Some(file) => format!("Job {} ({}) {}", job_id, file, status),
+        None => format!("Job {} {}", job_id, status),
+    };
+    (summary, body)
+}
+
+/// Shows a desktop notification through the platform's notification service
+#[cfg(feature = "notifications")]
+pub fn send(summary: &str, body: &str) -> Result<()> {
+    use anyhow::Context;
+
+    notify_rust::Notification::new()
+        .appname("AnyDataset TUI")
+        .summary(summary)
+        .body(body)
+        .show()
+        .context("Desktop notifications are not available")?;
+    Ok(())
+}
+
+#[cfg(not(feature = "notifications"))]
+pub fn send(_summary: &str, _body: &str) -> Result<()> {
+    anyhow::bail!("Desktop notifications are not available in this build; rebuild with `--features notifications`")
+}
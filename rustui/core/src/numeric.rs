@@ -0,0 +1,278 @@
+use std::fmt;
+
+/// Bare integers up to this are usually counts or list numbers ("3 reasons") and aren't checked
+const MAX_IGNORED_INTEGER: f64 = 10.0;
+
+/// Units recognized after a number, with the spelling they are compared by
+const UNITS: [(&str, &str); 34] = [
+    ("%", "%"), ("percent", "%"), ("per cent", "%"), ("procent", "%"), ("proc.", "%"),
+    ("mg", "mg"), ("g", "g"), ("kg", "kg"), ("µg", "µg"), ("mcg", "µg"),
+    ("ml", "ml"), ("l", "l"), ("mm", "mm"), ("cm", "cm"), ("m", "m"), ("km", "km"),
+    ("°c", "°c"), ("°f", "°f"), ("h", "h"), ("min", "min"), ("s", "s"), ("ms", "ms"),
+    ("$", "usd"), ("usd", "usd"), ("€", "eur"), ("eur", "eur"), ("zł", "pln"), ("pln", "pln"),
+    ("kb", "kb"), ("mb", "mb"), ("gb", "gb"), ("tb", "tb"), ("mln", "mln"), ("bn", "bn"),
+];
+
+const MONTHS: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june",
+    "july", "august", "september", "october", "november", "december",
+];
+
+/// A number (with its unit, if any) or a date found in text
+#[derive(Debug, Clone, PartialEq)]
+pub enum Figure {
+    Quantity { value: f64, unit: Option<String>, text: String },
+    /// ISO `YYYY-MM-DD`
+    Date { iso: String, text: String },
+}
+
+impl Figure {
+    pub fn text(&self) -> &str {
+        match self {
+            Figure::Quantity { text, .. } | Figure::Date { text, .. } => text,
+        }
+    }
+
+    /// Whether this figure from the source supports `other` from a completion.
+    /// Same value; a unit missing on either side doesn't count as a difference.
+    fn supports(&self, other: &Figure) -> bool {
+        match (self, other) {
+            (Figure::Quantity { value: a, unit: ua, .. }, Figure::Quantity { value: b, unit: ub, .. }) => {
+                (a - b).abs() <= f64::EPSILON * a.abs().max(1.0) && (ua.is_none() || ub.is_none() || ua == ub)
+            },
+            (Figure::Date { iso: a, .. }, Figure::Date { iso: b, .. }) => a == b,
+            // A full date supports its year mentioned alone, but not the other way round
+            (Figure::Date { iso, .. }, Figure::Quantity { value, unit: None, .. }) => {
+                iso.get(..4).and_then(|y| y.parse::<f64>().ok()) == Some(*value)
+            },
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Figure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text())
+    }
+}
+
+/// Figures in `completion` that don't appear in `source`
+pub fn unsupported(completion: &str, source: &str) -> Vec<Figure> {
+    let known = extract(source);
+    let mut missing: Vec<Figure> = Vec::new();
+    for figure in extract(completion) {
+        if !known.iter().any(|k| k.supports(&figure)) && !missing.iter().any(|m| m.supports(&figure)) {
+            missing.push(figure);
+        }
+    }
+    missing
+}
+
+/// Numbers with units, percentages, amounts of money and dates, in order of appearance
+pub fn extract(text: &str) -> Vec<Figure> {
+    let tokens = tokenize(text);
+    let mut figures = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        if let Some((figure, used)) = date_at(&tokens[i..]) {
+            figures.push(figure);
+            i += used;
+            continue;
+        }
+        if let Some(value) = parse_number(&tokens[i]) {
+            let currency_before = i > 0 && matches!(tokens[i - 1].as_str(), "$" | "€");
+            let (unit, used) = if currency_before {
+                (unit_name(&tokens[i - 1]).map(str::to_string), 1)
+            } else {
+                unit_after(&tokens[i + 1..])
+            };
+            let ignored = unit.is_none() && value.fract() == 0.0 && value.abs() <= MAX_IGNORED_INTEGER;
+            if !ignored {
+                let end = (i + used).min(tokens.len());
+                let start = if currency_before { i - 1 } else { i };
+                let text = tokens[start..end.max(i + 1)].join(" ");
+                figures.push(Figure::Quantity { value, unit, text });
+            }
+            i += used.max(1);
+            continue;
+        }
+        i += 1;
+    }
+    figures
+}
+
+/// Words, numbers and symbols; `%`, `$`, `€` and `°C` become tokens of their own
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for word in text.split_whitespace() {
+        let mut word = word.trim_matches(|c: char| matches!(c, '(' | ')' | '[' | ']' | '"' | '\'' | ';' | ':' | '!' | '?'));
+        // Sentence punctuation, but not a decimal point or `proc.`
+        while let Some(stripped) = word.strip_suffix(['.', ',']) {
+            if word == "proc." {
+                break;
+            }
+            word = stripped;
+        }
+        let mut rest = word;
+        for symbol in ["$", "€"] {
+            if let Some(after) = rest.strip_prefix(symbol) {
+                tokens.push(symbol.to_string());
+                rest = after;
+            }
+        }
+        let split = rest.find(|c: char| c == '%' || c == '°' || (c.is_alphabetic() && c != 'e'))
+            .filter(|&i| i > 0 && rest[..i].chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | '-' | '+')));
+        match split {
+            Some(i) => {
+                tokens.push(rest[..i].to_string());
+                tokens.push(rest[i..].to_lowercase());
+            },
+            None if !rest.is_empty() => tokens.push(rest.to_lowercase()),
+            None => {},
+        }
+    }
+    tokens
+}
+
+/// Value of a number written as `1234`, `1,234.5`, `1.234,5`, `12,5` or `-3`
+fn parse_number(token: &str) -> Option<f64> {
+    let token = token.strip_prefix('+').unwrap_or(token);
+    let digits = token.strip_prefix('-').unwrap_or(token);
+    if !digits.starts_with(|c: char| c.is_ascii_digit()) || !digits.chars().all(|c| c.is_ascii_digit() || c == '.' || c == ',') {
+        return None;
+    }
+    let normalized = match (token.rfind('.'), token.rfind(',')) {
+        // The later separator is the decimal one
+        (Some(dot), Some(comma)) if comma > dot => token.replace('.', "").replace(',', "."),
+        (Some(_), Some(_)) => token.replace(',', ""),
+        // A single comma before exactly three digits groups thousands
+        (None, Some(comma)) if token.matches(',').count() > 1 || token.len() - comma == 4 => token.replace(',', ""),
+        (None, Some(_)) => token.replace(',', "."),
+        (Some(_), None) if token.matches('.').count() > 1 => token.replace('.', ""),
+        _ => token.to_string(),
+    };
+    normalized.parse().ok()
+}
+
+fn unit_name(token: &str) -> Option<&'static str> {
+    UNITS.iter().find(|(spelling, _)| *spelling == token).map(|(_, unit)| *unit)
+}
+
+/// Unit following a number and the number of tokens used, including the number
+fn unit_after(tokens: &[String]) -> (Option<String>, usize) {
+    if let (Some(first), Some(second)) = (tokens.first(), tokens.get(1)) {
+        if let Some(unit) = unit_name(&format!("{} {}", first, second)) {
+            return (Some(unit.to_string()), 3);
+        }
+    }
+    match tokens.first().and_then(|t| unit_name(t)) {
+        Some(unit) => (Some(unit.to_string()), 2),
+        None => (None, 1),
+    }
+}
+
+/// Date at the start of `tokens`: `2024-03-01`, `01.03.2024`, `1 March 2024`, `March 1 2024`
+/// or `March 2024` (day `00`)
+fn date_at(tokens: &[String]) -> Option<(Figure, usize)> {
+    let first = tokens.first()?;
+    let iso = |y: &str, m: usize, d: &str| -> Option<String> {
+        let (year, day) = (y.parse::<u32>().ok()?, d.parse::<u32>().ok()?);
+        ((1000..=2999).contains(&year) && (1..=12).contains(&m) && day <= 31)
+            .then(|| format!("{:04}-{:02}-{:02}", year, m, day))
+    };
+    let figure = |iso: String, used: usize| {
+        Some((Figure::Date { iso, text: tokens[..used].join(" ") }, used))
+    };
+
+    let parts: Vec<&str> = first.split(['-', '.', '/']).collect();
+    if parts.len() == 3 && parts.iter().all(|p| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit())) {
+        let date = if parts[0].len() == 4 {
+            iso(parts[0], parts[1].parse().ok()?, parts[2])
+        } else if parts[2].len() == 4 {
+            iso(parts[2], parts[1].parse().ok()?, parts[0])
+        } else {
+            None
+        };
+        return date.and_then(|date| figure(date, 1));
+    }
+
+    let month = |token: &str| MONTHS.iter().position(|m| token.len() >= 3 && m.starts_with(token)).map(|i| i + 1);
+    if let (Some(m), Some(second)) = (month(first), tokens.get(1)) {
+        if let Some(date) = tokens.get(2).and_then(|year| iso(year, m, second)) {
+            return figure(date, 3);
+        }
+        if let Some(date) = iso(second, m, "0") {
+            return figure(date, 2);
+        }
+    }
+    if let (Some(m), Some(year)) = (tokens.get(1).and_then(|t| month(t)), tokens.get(2)) {
+        if let Some(date) = iso(year, m, first) {
+            return figure(date, 3);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checks::{summary, CheckSettings, Checks};
+    use crate::numeric::{extract, unsupported, Figure};
+    use crate::processors::{ProcessingResult, ProcessingStats, Record};
+
+    #[test]
+    fn test_extract_normalizes_figures() {
+        let figures = extract("Dose: 12,5 mg twice daily; costs $1,200.50 (up 15 percent) since 1 March 2024.");
+        assert_eq!(figures, vec![
+            Figure::Quantity { value: 12.5, unit: Some("mg".to_string()), text: "12,5 mg".to_string() },
+            Figure::Quantity { value: 1200.5, unit: Some("usd".to_string()), text: "$ 1,200.50".to_string() },
+            Figure::Quantity { value: 15.0, unit: Some("%".to_string()), text: "15 percent".to_string() },
+            Figure::Date { iso: "2024-03-01".to_string(), text: "1 march 2024".to_string() },
+        ]);
+
+        // Małe liczby całkowite bez jednostki to zwykle wyliczenia
+        assert!(extract("There are 3 reasons.").is_empty());
+    }
+
+    #[test]
+    fn test_unsupported_figures() {
+        let source = "Enrollment ended on 2024-03-01 with 1 234 patients; 15% responded to 12.5 mg.";
+
+        // Inny zapis tych samych wartości nie jest oznaczany
+        assert!(unsupported("By March 1, 2024, 15 percent responded to 12,5 mg.", source).is_empty());
+        assert!(unsupported("The trial ended in 2024.", source).is_empty());
+
+        let missing: Vec<String> = unsupported("About 18% responded to 12.5 g, 18% overall.", source).iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(missing, vec!["18 %".to_string(), "12.5 g".to_string()]);
+    }
+
+    #[test]
+    fn test_numbers_check_reads_source_document() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let document = dir.path().join("report.txt");
+        std::fs::write(&document, "Revenue grew 42% to 3.5 mln.")?;
+        let checks = Checks::load(&CheckSettings { numbers: true, ..Default::default() })?;
+
+        let record = |completion: &str| Record {
+            instruction: String::new(),
+            prompt: "How much did revenue grow?".to_string(),
+            completion: completion.to_string(),
+            metadata: Default::default(),
+        };
+        let mut results = vec![ProcessingResult {
+            records: vec![record("It grew 42%."), record("It grew 24% to 3.5 mln.")],
+            source_file: document.to_string_lossy().to_string(),
+            processing_type: "standard".to_string(),
+            stats: ProcessingStats { total_records: 2, total_tokens: 0, processing_time_ms: 0 },
+            exchanges: vec![],
+            error: None,
+        }];
+        assert_eq!(checks.apply(&mut results), 1);
+        assert_eq!(summary(&results), vec![
+            "#2:".to_string(),
+            "  [numbers] '24 %' does not appear in the source".to_string(),
+        ]);
+        Ok(())
+    }
+}
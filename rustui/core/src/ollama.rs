@@ -0,0 +1,86 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::network;
+
+/// Provider name of local models served by Ollama
+pub const PROVIDER: &str = "ollama";
+
+/// `[ollama]` in config.toml: where the local Ollama server listens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OllamaSettings {
+    /// Base URL of the Ollama API, as the backend reaches it
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+}
+
+fn default_base_url() -> String {
+    "http://localhost:11434".to_string()
+}
+
+impl Default for OllamaSettings {
+    fn default() -> Self {
+        Self { base_url: default_base_url() }
+    }
+}
+
+impl OllamaSettings {
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), path)
+    }
+}
+
+/// Model names from an `/api/tags` response, e.g. `llama3.1:8b`, sorted
+pub fn parse_tags(response: &Value) -> Result<Vec<String>> {
+    let models = response.get("models")
+        .and_then(Value::as_array)
+        .context("Ollama response has no model list")?;
+    let mut names: Vec<String> = models.iter()
+        .filter_map(|model| model.get("name").or_else(|| model.get("model")))
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// Models pulled on the Ollama server
+pub async fn list_models(settings: &OllamaSettings) -> Result<Vec<String>> {
+    let url = settings.url("api/tags");
+    let response = network::client()
+        .get(&url)
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .with_context(|| format!("Ollama is not reachable at {}", settings.base_url))?;
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("Ollama answered {}", status);
+    }
+    let body: Value = response.json().await.context("Failed to parse the Ollama model list")?;
+    parse_tags(&body)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ollama;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_tags() {
+        let response = json!({
+            "models": [
+                { "name": "qwen2.5:7b", "size": 4683087332u64 },
+                { "name": "llama3.1:8b", "digest": "abc" },
+                { "model": "mistral:latest" },
+                { "size": 1 }
+            ]
+        });
+        assert_eq!(ollama::parse_tags(&response).unwrap(), vec!["llama3.1:8b", "mistral:latest", "qwen2.5:7b"]);
+        assert_eq!(ollama::parse_tags(&json!({ "models": [] })).unwrap(), Vec::<String>::new());
+        assert!(ollama::parse_tags(&json!({ "error": "not found" })).is_err());
+    }
+}
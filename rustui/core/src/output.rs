@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+use crate::templating::{render_path, TemplateContext};
+
+/// What to do when the resolved output file already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CollisionPolicy {
+    Overwrite,
+    #[default]
+    Suffix,
+    Fail,
+}
+
+/// Naming of downloaded results and exports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputSettings {
+    #[serde(default = "default_name_template")]
+    pub name_template: String,
+    #[serde(default)]
+    pub collision: CollisionPolicy,
+}
+
+fn default_name_template() -> String {
+    "{preset}-{date}-{file_stem}.{ext}".to_string()
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        Self {
+            name_template: default_name_template(),
+            collision: CollisionPolicy::default(),
+        }
+    }
+}
+
+/// Renders the name template inside `dir` and applies the collision policy
+pub fn resolve_output_path(dir: &Path, settings: &OutputSettings, ctx: &TemplateContext) -> Result<PathBuf> {
+    let rendered = render_path(&settings.name_template, ctx);
+
+    // Templates may create subdirectories but must never escape the target directory
+    let relative: PathBuf = Path::new(&rendered)
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part),
+            _ => None,
+        })
+        .collect();
+    if relative.as_os_str().is_empty() {
+        anyhow::bail!("Output name template '{}' produced an empty file name", settings.name_template);
+    }
+
+    let path = dir.join(relative);
+    if !path.exists() {
+        return Ok(path);
+    }
+
+    match settings.collision {
+        CollisionPolicy::Overwrite => Ok(path),
+        CollisionPolicy::Fail => anyhow::bail!("Output file already exists: {}", path.display()),
+        CollisionPolicy::Suffix => {
+            let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+            let parent = path.parent().context("Output path has no parent directory")?;
+            (1..10_000)
+                .map(|n| parent.join(format!("{}-{}{}", stem, n, ext)))
+                .find(|candidate| !candidate.exists())
+                .context("Could not find a free output file name")
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::output::{resolve_output_path, CollisionPolicy, OutputSettings};
+    use crate::templating::TemplateContext;
+
+    fn context() -> TemplateContext {
+        let mut ctx = TemplateContext::default();
+        ctx.set("preset", "article");
+        ctx.set("date", "2025-01-31");
+        ctx.set("file_stem", "report");
+        ctx.set("ext", "jsonl");
+        ctx
+    }
+
+    #[test]
+    fn test_resolve_output_path_collision_policies() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut settings = OutputSettings::default();
+
+        let path = resolve_output_path(dir.path(), &settings, &context())?;
+        assert_eq!(path, dir.path().join("article-2025-01-31-report.jsonl"));
+        std::fs::write(&path, "{}")?;
+
+        // Suffix dokleja kolejny numer
+        let suffixed = resolve_output_path(dir.path(), &settings, &context())?;
+        assert_eq!(suffixed, dir.path().join("article-2025-01-31-report-1.jsonl"));
+
+        settings.collision = CollisionPolicy::Overwrite;
+        assert_eq!(resolve_output_path(dir.path(), &settings, &context())?, path);
+
+        settings.collision = CollisionPolicy::Fail;
+        assert!(resolve_output_path(dir.path(), &settings, &context()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_output_path_stays_in_directory() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let settings = OutputSettings {
+            name_template: "../{preset}/{file_stem}.{ext}".to_string(),
+            collision: CollisionPolicy::Fail,
+        };
+
+        let path = resolve_output_path(dir.path(), &settings, &context())?;
+        assert_eq!(path, dir.path().join("article").join("report.jsonl"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,439 @@
+use serde::{Deserialize, Serialize};
+
+use crate::audit::ModelExchange;
+use crate::chunking;
+use crate::lineage;
+use crate::processors::{self, ProcessingResult, ProcessingStats, Processor, ProcessorConfig, Record};
+
+/// Record metadata: normalized section name (`abstract`, `introduction`, `methods`, ...)
+pub const SECTION: &str = "section";
+/// Record metadata: section heading as written in the paper
+pub const SECTION_TITLE: &str = "section_title";
+
+/// Scientific paper processing from the `[paper]` config section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaperSettings {
+    /// Read `.tex` sources directly instead of as plain text
+    #[serde(default = "default_true")]
+    pub latex: bool,
+    /// Keep the reference list as records (dropped by default)
+    #[serde(default)]
+    pub keep_references: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for PaperSettings {
+    fn default() -> Self {
+        Self { latex: true, keep_references: false }
+    }
+}
+
+/// Part of a paper under one heading
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub kind: String,
+    pub title: String,
+    pub text: String,
+}
+
+/// Heading words and the section they stand for
+const SECTION_NAMES: [(&str, &str); 19] = [
+    ("abstract", "abstract"),
+    ("summary", "abstract"),
+    ("introduction", "introduction"),
+    ("background", "introduction"),
+    ("related work", "related_work"),
+    ("materials and methods", "methods"),
+    ("methods", "methods"),
+    ("methodology", "methods"),
+    ("method", "methods"),
+    ("experiments", "methods"),
+    ("results", "results"),
+    ("results and discussion", "results"),
+    ("discussion", "discussion"),
+    ("conclusions", "conclusion"),
+    ("conclusion", "conclusion"),
+    ("acknowledgments", "acknowledgments"),
+    ("acknowledgements", "acknowledgments"),
+    ("references", "references"),
+    ("bibliography", "references"),
+];
+
+/// Normalized section for a heading like `2. Materials and Methods` or `III. RESULTS`;
+/// other headings keep their own text
+fn section_kind(title: &str) -> String {
+    let words: Vec<&str> = title.split_whitespace().collect();
+    // Drop numbering: `2.`, `2.1`, `III.`, `A.`
+    let first_is_number = words.first().is_some_and(|w| {
+        let w = w.trim_end_matches('.');
+        w.chars().all(|c| c.is_ascii_digit() || c == '.')
+            || (!w.is_empty() && w.len() <= 4 && w.chars().all(|c| "IVXLC".contains(c)))
+            || (w.len() == 1 && words[0].ends_with('.') && w.chars().all(|c| c.is_ascii_uppercase()))
+    });
+    let name = if first_is_number && words.len() > 1 { words[1..].join(" ") } else { words.join(" ") };
+    let name = name.trim_end_matches(':').to_lowercase();
+    SECTION_NAMES.iter()
+        .find(|(heading, _)| *heading == name)
+        .map(|(_, kind)| kind.to_string())
+        .unwrap_or_else(|| "other".to_string())
+}
+
+/// Heading text of a line, if the line is one: a Markdown heading, or a short line
+/// naming a known section (optionally numbered, e.g. `1 Introduction`, `ABSTRACT:`)
+fn heading(line: &str) -> Option<String> {
+    let line = line.trim();
+    if let Some(title) = line.strip_prefix('#') {
+        return Some(title.trim_start_matches('#').trim().to_string());
+    }
+    if line.is_empty() || line.split_whitespace().count() > 6 || line.ends_with('.') {
+        return None;
+    }
+    (section_kind(line) != "other").then(|| line.trim_end_matches(':').to_string())
+}
+
+/// Splits a plain-text or Markdown paper at its section headings. Text before the first
+/// heading (title, authors) is a `front` section.
+pub fn split_sections(text: &str) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut current = Section { kind: "front".to_string(), title: String::new(), text: String::new() };
+    for line in text.lines() {
+        match heading(line) {
+            Some(title) => {
+                let next = Section { kind: section_kind(&title), title, text: String::new() };
+                push_section(&mut sections, std::mem::replace(&mut current, next));
+            },
+            None => {
+                current.text.push_str(line);
+                current.text.push('\n');
+            },
+        }
+    }
+    push_section(&mut sections, current);
+    sections
+}
+
+fn push_section(sections: &mut Vec<Section>, mut section: Section) {
+    section.text = section.text.trim().to_string();
+    if !section.text.is_empty() {
+        sections.push(section);
+    }
+}
+
+/// Sections of a LaTeX source: the `abstract` environment and `\section` headings of the
+/// document body. Commands are reduced to their text; citations, labels, figures and
+/// the bibliography are left out.
+pub fn split_latex(source: &str) -> Vec<Section> {
+    let body = strip_comments(source);
+    let body = match body.find("\\begin{document}") {
+        Some(start) => &body[start + "\\begin{document}".len()..],
+        None => body.as_str(),
+    };
+    let body = body.split("\\end{document}").next().unwrap_or_default();
+
+    let mut sections = Vec::new();
+    let mut rest = body;
+    let mut current = Section { kind: "front".to_string(), title: String::new(), text: String::new() };
+    loop {
+        let next_section = ["\\section{", "\\section*{"].iter()
+            .filter_map(|cmd| rest.find(cmd).map(|i| (i, cmd.len())))
+            .min();
+        let next_abstract = rest.find("\\begin{abstract}");
+        match (next_section, next_abstract) {
+            (_, Some(a)) if next_section.is_none_or(|(s, _)| a < s) => {
+                current.text.push_str(&rest[..a]);
+                let start = a + "\\begin{abstract}".len();
+                let end = rest[start..].find("\\end{abstract}").map(|e| start + e).unwrap_or(rest.len());
+                let after = Section { kind: "front".to_string(), title: String::new(), text: String::new() };
+                push_section(&mut sections, std::mem::replace(&mut current, after));
+                push_section(&mut sections, Section {
+                    kind: "abstract".to_string(),
+                    title: "Abstract".to_string(),
+                    text: rest[start..end].to_string(),
+                });
+                rest = rest[end..].strip_prefix("\\end{abstract}").unwrap_or(&rest[end..]);
+            },
+            (Some((s, len)), _) => {
+                current.text.push_str(&rest[..s]);
+                let title_start = s + len;
+                let title_len = closing_brace(&rest[title_start..]).unwrap_or(rest.len() - title_start);
+                let title = latex_text(&rest[title_start..title_start + title_len]);
+                let next = Section { kind: section_kind(&title), title, text: String::new() };
+                push_section(&mut sections, std::mem::replace(&mut current, next));
+                rest = rest.get(title_start + title_len + 1..).unwrap_or_default();
+            },
+            (None, _) => {
+                current.text.push_str(rest);
+                break;
+            },
+        }
+    }
+    push_section(&mut sections, current);
+
+    sections.into_iter()
+        .filter_map(|mut section| {
+            // The bibliography is an environment, not a section, in most sources
+            if let Some(start) = section.text.find("\\begin{thebibliography}") {
+                section.text.truncate(start);
+            }
+            section.text = latex_text(&section.text);
+            (!section.text.is_empty()).then_some(section)
+        })
+        .collect()
+}
+
+/// Drops `%` comments (but not `\%`)
+fn strip_comments(source: &str) -> String {
+    source.lines()
+        .map(|line| {
+            let mut prev = ' ';
+            let end = line.char_indices()
+                .find(|&(_, c)| {
+                    let comment = c == '%' && prev != '\\';
+                    prev = c;
+                    comment
+                })
+                .map(|(i, _)| i)
+                .unwrap_or(line.len());
+            &line[..end]
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Length of a brace group's content; `text` starts right after the opening brace
+fn closing_brace(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(i),
+            '}' => depth -= 1,
+            _ => {},
+        }
+    }
+    None
+}
+
+/// Commands dropped together with their argument
+const DROPPED_COMMANDS: [&str; 9] = ["cite", "citep", "citet", "ref", "eqref", "label", "bibliography", "bibliographystyle", "includegraphics"];
+/// Environments dropped entirely, except for figure and table captions
+const DROPPED_ENVIRONMENTS: [&str; 4] = ["figure", "table", "thebibliography", "equation"];
+
+/// Plain text of LaTeX markup
+fn latex_text(latex: &str) -> String {
+    let mut text = latex.to_string();
+    for env in DROPPED_ENVIRONMENTS {
+        let (begin, end) = (format!("\\begin{{{}}}", env), format!("\\end{{{}}}", env));
+        while let Some(start) = text.find(&begin) {
+            let stop = text[start..].find(&end).map(|i| start + i + end.len()).unwrap_or(text.len());
+            let captions: Vec<String> = text[start..stop].match_indices("\\caption{")
+                .filter_map(|(i, cmd)| {
+                    let from = start + i + cmd.len();
+                    closing_brace(&text[from..]).map(|len| text[from..from + len].to_string())
+                })
+                .collect();
+            text.replace_range(start..stop, &captions.join("\n"));
+        }
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                let name: String = text[i + 1..].chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+                if name.is_empty() {
+                    // Escaped character, e.g. `\%` or `\&`; `\\` is a line break
+                    match chars.next() {
+                        Some((_, '\\')) => out.push(' '),
+                        Some((_, escaped)) => out.push(escaped),
+                        None => {},
+                    }
+                    continue;
+                }
+                for _ in 0..name.chars().count() {
+                    chars.next();
+                }
+                // Starred variants and optional arguments
+                if chars.peek().is_some_and(|(_, c)| *c == '*') {
+                    chars.next();
+                }
+                if chars.peek().is_some_and(|(_, c)| *c == '[') {
+                    for (_, c) in chars.by_ref() {
+                        if c == ']' {
+                            break;
+                        }
+                    }
+                }
+                let has_argument = chars.peek().is_some_and(|(_, c)| *c == '{');
+                if has_argument && (DROPPED_COMMANDS.contains(&name.as_str()) || name == "begin" || name == "end") {
+                    let (start, _) = chars.next().unwrap_or((text.len(), '{'));
+                    let len = closing_brace(&text[start + 1..]).unwrap_or(text.len() - start - 1);
+                    while chars.peek().is_some_and(|(j, _)| *j <= start + 1 + len) {
+                        chars.next();
+                    }
+                }
+                // Other commands (`\emph{x}`, `\textbf{x}`) keep their argument's text
+            },
+            '{' | '}' => {},
+            '~' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+
+    out.split("\n\n")
+        .map(|paragraph| {
+            // Dropped citations leave a space before punctuation: `cats~\cite{x}.`
+            paragraph.split_whitespace().collect::<Vec<_>>().join(" ").replace(" .", ".").replace(" ,", ",")
+        })
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Records per section (per chunk when chunking is configured), tagged with the section
+pub struct PaperProcessor {
+    pub settings: PaperSettings,
+}
+
+impl Processor for PaperProcessor {
+    fn process_file(&self, file_path: &str, config: &ProcessorConfig) -> anyhow::Result<ProcessingResult> {
+        let mut records = Vec::new();
+        for source in processors::read_sources(file_path, config)? {
+            let first = records.len();
+            let sections = if self.settings.latex && file_path.to_lowercase().ends_with(".tex") {
+                split_latex(&source.text)
+            } else {
+                split_sections(&source.text)
+            };
+            for section in sections {
+                if section.kind == "references" && !self.settings.keep_references {
+                    continue;
+                }
+                for part in chunking::split_text(&section.text, config.chunking.as_ref()) {
+                    let mut metadata = source.metadata.clone();
+                    metadata.insert(SECTION.to_string(), section.kind.clone().into());
+                    if !section.title.is_empty() {
+                        metadata.insert(SECTION_TITLE.to_string(), section.title.clone().into());
+                    }
+                    records.push(Record {
+                        instruction: format!("Generate questions about the {} section of the paper", section.kind.replace('_', " ")),
+                        // Mock model call; the section text is the reference answer
+                        completion: part.clone(),
+                        prompt: part,
+                        metadata,
+                    });
+                }
+            }
+            lineage::link(&mut records[first..], &source.parents);
+        }
+
+        let total_tokens = records.iter()
+            .map(|r| r.prompt.split_whitespace().count() + r.completion.split_whitespace().count())
+            .sum();
+        let exchanges = records.iter_mut()
+            .map(|record| ModelExchange::for_record(record, file_path, config))
+            .collect();
+        Ok(ProcessingResult {
+            stats: ProcessingStats {
+                total_records: records.len(),
+                total_tokens,
+                processing_time_ms: 0,
+            },
+            records,
+            exchanges,
+            source_file: file_path.to_string(),
+            processing_type: "paper".to_string(),
+            error: None,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "paper"
+    }
+
+    fn description(&self) -> &'static str {
+        "Section-aware processing of scientific papers"
+    }
+}
+
+/// Sections of processed records with their record counts, in paper order
+pub fn outline(records: &[Record]) -> Vec<String> {
+    let mut lines: Vec<(String, usize)> = Vec::new();
+    for record in records {
+        let Some(kind) = record.metadata.get(SECTION).and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let title = record.metadata.get(SECTION_TITLE).and_then(|v| v.as_str()).unwrap_or("");
+        let label = if title.is_empty() { kind.to_string() } else { format!("{}: {}", kind, title) };
+        match lines.last_mut() {
+            Some((last, count)) if *last == label => *count += 1,
+            _ => lines.push((label, 1)),
+        }
+    }
+    lines.into_iter().map(|(label, count)| format!("{} ({} record(s))", label, count)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::paper::{outline, split_latex, split_sections, PaperProcessor, PaperSettings, SECTION, SECTION_TITLE};
+    use crate::processors::{test_config, Processor};
+
+    const PAPER: &str = "Deep Nets for Cats\nA. Author, B. Author\n\nABSTRACT:\nWe classify cats.\n\n1 Introduction\nCats are popular.\n\n2. Materials and Methods\nWe trained a network.\n\n## Error analysis\nSome errors remain.\n\nIII. RESULTS\nAccuracy was 99%.\n\nReferences\n[1] Smith, J. Cats. 2020.\n";
+
+    #[test]
+    fn test_split_text_sections() {
+        let sections = split_sections(PAPER);
+        let kinds: Vec<&str> = sections.iter().map(|s| s.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["front", "abstract", "introduction", "methods", "other", "results", "references"]);
+        assert_eq!(sections[3].title, "2. Materials and Methods");
+        assert_eq!(sections[4].title, "Error analysis");
+        assert_eq!(sections[5].text, "Accuracy was 99%.");
+    }
+
+    #[test]
+    fn test_split_latex_source() {
+        let source = r"\documentclass{article}
+\title{Ignored preamble}
+\begin{document}
+\maketitle
+\begin{abstract}
+We study \emph{cats}~\cite{smith}. % komentarz
+\end{abstract}
+\section{Introduction}\label{sec:intro}
+Cats are 50\% of the internet~\cite{jones}.
+\begin{figure}\includegraphics{cat.png}\caption{A \textbf{cat}.}\end{figure}
+\section*{Conclusions}
+Done.
+\begin{thebibliography}{9}\bibitem{smith} Smith.\end{thebibliography}
+\end{document}";
+        let sections = split_latex(source);
+        let kinds: Vec<&str> = sections.iter().map(|s| s.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["abstract", "introduction", "conclusion"]);
+        assert_eq!(sections[0].text, "We study cats.");
+        assert_eq!(sections[1].text, "Cats are 50% of the internet. A cat.");
+        assert_eq!(sections[2].text, "Done.");
+    }
+
+    #[test]
+    fn test_paper_processor_drops_references() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("paper.md");
+        std::fs::write(&path, PAPER)?;
+        let config = test_config();
+
+        let processor = PaperProcessor { settings: PaperSettings::default() };
+        let result = processor.process_file(&path.to_string_lossy(), &config)?;
+        assert_eq!(result.records.len(), 6);
+        assert!(result.records.iter().all(|r| r.metadata[SECTION] != "references"));
+        assert_eq!(result.records[2].metadata[SECTION_TITLE], "1 Introduction");
+        assert_eq!(outline(&result.records)[1], "abstract: ABSTRACT (1 record(s))");
+
+        let processor = PaperProcessor { settings: PaperSettings { keep_references: true, ..PaperSettings::default() } };
+        assert_eq!(processor.process_file(&path.to_string_lossy(), &config)?.records.len(), 7);
+        Ok(())
+    }
+}
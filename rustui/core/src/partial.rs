@@ -0,0 +1,185 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+
+use crate::history::HistoryEntry;
+use crate::manifest::{DatasetManifest, JobFailure};
+use crate::processors::ProcessingResult;
+use crate::vault::Vault;
+
+/// Where the results of a job that failed midway are kept: `results.json` -> `results.partial.json`
+pub fn path_for(output: &Path) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    match output.extension() {
+        Some(ext) => output.with_file_name(format!("{}.partial.{}", stem, ext.to_string_lossy())),
+        None => output.with_file_name(format!("{}.partial", stem)),
+    }
+}
+
+/// Results a failed local job got done, and the files it has left
+#[derive(Debug, Clone)]
+pub struct Partial {
+    pub path: PathBuf,
+    /// Results of the files that were processed; failed files are left out
+    pub results: Vec<ProcessingResult>,
+    /// Files that weren't processed or failed
+    pub remaining: Vec<String>,
+    pub failure: JobFailure,
+}
+
+impl Partial {
+    pub fn records(&self) -> usize {
+        self.results.iter().map(|r| r.records.len()).sum()
+    }
+}
+
+/// Writes the manifest of a partial output at `path`, with the failure reason
+pub fn record_failure(path: &Path, entry: &HistoryEntry, reason: &str, remaining_files: Vec<String>) -> Result<PathBuf> {
+    let failure = JobFailure { reason: reason.to_string(), failed_at: Utc::now(), remaining_files };
+    DatasetManifest::for_dataset(path, entry)?.with_failure(failure).write_for(path)
+}
+
+/// Writes the results of a failed local job next to its `output`. Files that failed and the
+/// `pending` ones are recorded as remaining, so that resuming retries them.
+pub fn save(output: &Path, entry: &HistoryEntry, results: &[ProcessingResult], pending: &[String], reason: &str, vault: &Vault) -> Result<PathBuf> {
+    let path = path_for(output);
+    vault.write(&path, serde_json::to_vec_pretty(results)?)?;
+    let remaining = results.iter()
+        .filter(|result| result.error.is_some())
+        .map(|result| result.source_file.clone())
+        .chain(pending.iter().cloned())
+        .collect();
+    record_failure(&path, entry, reason, remaining)?;
+    Ok(path)
+}
+
+/// The partial output of `output`, if the job failed and left one
+pub fn load(output: &Path, vault: &Vault) -> Result<Option<Partial>> {
+    let path = path_for(output);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let manifest = DatasetManifest::load(&DatasetManifest::path_for(&path), vault)?;
+    let failure = manifest.failure
+        .with_context(|| format!("{} is not the output of a failed job", path.display()))?;
+    let results: Vec<ProcessingResult> = serde_json::from_slice(&vault.read(&path)?)
+        .with_context(|| format!("{} holds no local results", path.display()))?;
+    Ok(Some(Partial {
+        results: results.into_iter().filter(|result| result.error.is_none()).collect(),
+        remaining: failure.remaining_files.clone(),
+        failure,
+        path,
+    }))
+}
+
+/// Removes the partial output of `output` and its manifest, once the job has finished
+pub fn discard(output: &Path) -> Result<()> {
+    let path = path_for(output);
+    for file in [DatasetManifest::path_for(&path), path] {
+        match std::fs::remove_file(&file) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                return Err(err).with_context(|| format!("Failed to remove {}", file.display()));
+            },
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::HistoryEntry;
+    use crate::manifest::DatasetManifest;
+    use crate::partial;
+    use crate::processors::{ProcessingResult, ProcessingStats, Record};
+    use crate::vault::Vault;
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    fn entry() -> HistoryEntry {
+        HistoryEntry {
+            job_id: "job-1".to_string(),
+            file: "docs".to_string(),
+            processing_type: "standard".to_string(),
+            provider: "p".to_string(),
+            model: "m".to_string(),
+            status: "failed".to_string(),
+            submitted_at: chrono::Utc::now(),
+            finished_at: None,
+            records: None,
+            tokens: None,
+            cost_usd: None,
+            variables: Default::default(),
+        }
+    }
+
+    fn result(file: &str, error: Option<&str>) -> ProcessingResult {
+        let records = if error.is_some() {
+            vec![]
+        } else {
+            vec![Record {
+                instruction: "Podsumuj".to_string(),
+                prompt: file.to_string(),
+                completion: "ok".to_string(),
+                metadata: HashMap::new(),
+            }]
+        };
+        ProcessingResult {
+            stats: ProcessingStats { total_records: records.len(), total_tokens: 0, processing_time_ms: 0 },
+            records,
+            exchanges: vec![],
+            source_file: file.to_string(),
+            processing_type: "standard".to_string(),
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_partial_path() {
+        assert_eq!(partial::path_for(Path::new("/out/results.json")), Path::new("/out/results.partial.json"));
+        assert_eq!(partial::path_for(Path::new("/out/results")), Path::new("/out/results.partial"));
+    }
+
+    #[test]
+    fn test_partial_roundtrip() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output = dir.path().join("results.json");
+        assert!(partial::load(&output, &Vault::default())?.is_none());
+
+        let results = vec![result("a.txt", None), result("b.txt", Some("not a UTF-8 text file")), result("c.txt", None)];
+        let path = partial::save(&output, &entry(), &results, &["d.txt".to_string()], "Provider answered 500", &Vault::default())?;
+        assert_eq!(path, dir.path().join("results.partial.json"));
+        // Pełny wynik zadania nie powstaje
+        assert!(!output.exists());
+
+        // Manifest podaje przyczynę błędu i pliki do ponowienia
+        let manifest = DatasetManifest::load(&DatasetManifest::path_for(&path), &Vault::default())?;
+        let failure = manifest.failure.expect("failure in the manifest");
+        assert_eq!(failure.reason, "Provider answered 500");
+        assert_eq!(failure.remaining_files, vec!["b.txt", "d.txt"]);
+
+        // Wznowienie pomija wyniki plików z błędem
+        let partial = partial::load(&output, &Vault::default())?.expect("partial output");
+        assert_eq!(partial.records(), 2);
+        assert_eq!(partial.results.iter().map(|r| r.source_file.as_str()).collect::<Vec<_>>(), vec!["a.txt", "c.txt"]);
+        assert_eq!(partial.remaining, vec!["b.txt", "d.txt"]);
+
+        partial::discard(&output)?;
+        assert!(!path.exists());
+        assert!(!DatasetManifest::path_for(&path).exists());
+        assert!(partial::load(&output, &Vault::default())?.is_none());
+        // Usuwanie nieistniejącego wyniku częściowego nie jest błędem
+        partial::discard(&output)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_completed_manifest_has_no_failure() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let dataset = dir.path().join("results.json");
+        std::fs::write(&dataset, "[]")?;
+        let json = serde_json::to_value(DatasetManifest::for_dataset(&dataset, &entry())?)?;
+        assert!(json.get("failure").is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,258 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::audit::ModelExchange;
+use crate::chunking;
+use crate::docmeta;
+use crate::error::ProcessingErrorKind;
+use crate::processing_error;
+use crate::processors::{ProcessingResult, ProcessingStats, Processor, ProcessorConfig, Record, Source};
+
+/// Text of a PDF, page by page
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfText {
+    /// Page number (from 1) and its text; pages without text are left out
+    pub pages: Vec<(u32, String)>,
+    pub page_count: usize,
+}
+
+pub fn is_pdf(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+}
+
+/// Extracts the text layer of every page. Password-protected PDFs and PDFs without any
+/// text (scans) are rejected as `UnsupportedFormat`.
+#[cfg(feature = "pdf")]
+pub fn extract(path: &Path) -> Result<PdfText> {
+    let file = path.display();
+    let bytes = std::fs::read(path)?;
+    let mut document = lopdf::Document::load_mem(&bytes).map_err(|err| {
+        processing_error!(ProcessingErrorKind::UnsupportedFormat, file, "not a readable PDF ({})", err)
+    })?;
+    // Many PDFs are encrypted only to restrict printing or copying, with an empty user password
+    if document.is_encrypted() && document.decrypt("").is_err() {
+        return Err(processing_error!(
+            ProcessingErrorKind::UnsupportedFormat, file,
+            "the PDF is password-protected; remove the password and try again"
+        ).into());
+    }
+
+    let numbers: Vec<u32> = document.get_pages().keys().copied().collect();
+    let mut pages = Vec::new();
+    for &number in &numbers {
+        // A page whose fonts can't be decoded counts as a page without text
+        let text = document.extract_text(&[number]).unwrap_or_default();
+        let text = text.lines().map(str::trim_end).collect::<Vec<_>>().join("\n").trim().to_string();
+        if !text.is_empty() {
+            pages.push((number, text));
+        }
+    }
+    if pages.is_empty() {
+        return Err(processing_error!(
+            ProcessingErrorKind::UnsupportedFormat, file,
+            "no text layer in {} page(s); the PDF looks scanned, run OCR on it first", numbers.len()
+        ).into());
+    }
+    Ok(PdfText { pages, page_count: numbers.len() })
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn extract(path: &Path) -> Result<PdfText> {
+    Err(processing_error!(
+        ProcessingErrorKind::UnsupportedFormat, path.display(),
+        "PDF support is not available in this build; rebuild with `--features pdf`"
+    ).into())
+}
+
+/// Pages with text, as the sources of the records made from them; the page number, page count
+/// and document properties go into their metadata
+pub fn sources(path: &Path) -> Result<Vec<Source>> {
+    let text = extract(path)?;
+    // Document properties are part of the record whether or not `[metadata]` harvesting is on
+    let info = docmeta::harvest(path).unwrap_or_default();
+    Ok(text.pages.into_iter()
+        .map(|(number, page)| {
+            let mut metadata: HashMap<String, serde_json::Value> = info.clone().into_iter().collect();
+            metadata.insert("file_type".to_string(), "pdf".into());
+            metadata.insert("page".to_string(), number.into());
+            metadata.insert("page_count".to_string(), text.page_count.into());
+            Source { text: page, metadata, parents: Vec::new() }
+        })
+        .collect())
+}
+
+/// One record per page with text, with the metadata of its source
+pub fn process(file_path: &str, config: &ProcessorConfig, processing_type: &str, instruction: &str) -> Result<ProcessingResult> {
+    let mut tokens = 0;
+    let mut records = Vec::new();
+    for page in sources(Path::new(file_path))? {
+        // With chunking on, a long page becomes several records
+        let parts = chunking::split_text(&page.text, config.chunking.as_ref());
+        let part_count = parts.len();
+        for (i, part) in parts.into_iter().enumerate() {
+            let mut metadata = page.metadata.clone();
+            if part_count > 1 {
+                metadata.insert("chunk".to_string(), (i + 1).into());
+                metadata.insert("chunk_count".to_string(), part_count.into());
+            }
+            // Mock model call; the page text comes back as the completion
+            tokens += 2 * part.split_whitespace().count();
+            records.push(Record {
+                instruction: instruction.to_string(),
+                prompt: part.clone(),
+                completion: part,
+                metadata,
+            });
+        }
+    }
+    let exchanges = records.iter_mut()
+        .map(|record| ModelExchange::for_record(record, file_path, config))
+        .collect();
+    Ok(ProcessingResult {
+        stats: ProcessingStats {
+            total_records: records.len(),
+            total_tokens: tokens,
+            processing_time_ms: 0,
+        },
+        records,
+        exchanges,
+        source_file: file_path.to_string(),
+        processing_type: processing_type.to_string(),
+        error: None,
+    })
+}
+
+/// Per-page text extraction from PDFs
+pub struct PdfProcessor;
+
+impl Processor for PdfProcessor {
+    fn process_file(&self, file_path: &str, config: &ProcessorConfig) -> Result<ProcessingResult> {
+        process(file_path, config, "pdf", "Extract the text of the page")
+    }
+
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn description(&self) -> &'static str {
+        "PDF text extraction, one record per page"
+    }
+}
+
+#[cfg(all(test, feature = "pdf"))]
+mod tests {
+    use crate::error::{AppError, ProcessingErrorKind};
+    use crate::pdf::{extract, PdfProcessor};
+    use crate::processors::{get_processor, test_config, Processor, ProcessorConfig};
+    use lopdf::content::{Content, Operation};
+    use lopdf::{dictionary, Document, Object, Stream};
+    use std::path::Path;
+
+    /// PDF z jedną stroną na tekst; pusty tekst daje stronę bez warstwy tekstowej
+    fn write_pdf(path: &Path, pages: &[&str]) -> anyhow::Result<()> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Courier",
+        });
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+        let mut kids = Vec::new();
+        for text in pages {
+            let operations = if text.is_empty() {
+                Vec::new()
+            } else {
+                vec![
+                    Operation::new("BT", vec![]),
+                    Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                    Operation::new("Td", vec![100.into(), 600.into()]),
+                    Operation::new("Tj", vec![Object::string_literal(*text)]),
+                    Operation::new("ET", vec![]),
+                ]
+            };
+            let content = Content { operations }.encode()?;
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content));
+            kids.push(doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            }).into());
+        }
+        doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        }));
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        doc.save(path)?;
+        Ok(())
+    }
+
+    fn config() -> ProcessorConfig {
+        ProcessorConfig { language: "pl".to_string(), ..test_config() }
+    }
+
+    fn unsupported(err: &anyhow::Error) -> bool {
+        matches!(
+            err.downcast_ref::<AppError>(),
+            Some(AppError::Processing { kind: ProcessingErrorKind::UnsupportedFormat, .. })
+        )
+    }
+
+    #[test]
+    fn test_extract_pages() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("raport.pdf");
+        write_pdf(&path, &["Pierwsza strona", "", "Trzecia strona"])?;
+
+        let text = extract(&path)?;
+        assert_eq!(text.page_count, 3);
+        let numbers: Vec<u32> = text.pages.iter().map(|(n, _)| *n).collect();
+        assert_eq!(numbers, vec![1, 3]);
+        assert!(text.pages[0].1.contains("Pierwsza strona"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_records_per_page() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("raport.pdf");
+        write_pdf(&path, &["Strona A", "Strona B"])?;
+
+        let result = PdfProcessor.process_file(&path.to_string_lossy(), &config())?;
+        assert_eq!(result.processing_type, "pdf");
+        assert_eq!(result.records.len(), 2);
+        let record = &result.records[1];
+        assert_eq!(record.metadata["page"], 2);
+        assert_eq!(record.metadata["page_count"], 2);
+        assert_eq!(record.metadata["file_type"], "pdf");
+        assert!(record.prompt.contains("Strona B"));
+
+        // Procesor standardowy też czyta PDF strona po stronie
+        let standard = get_processor("standard")?.process_file(&path.to_string_lossy(), &config())?;
+        assert_eq!(standard.records.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scanned_and_invalid_pdfs() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let scanned = dir.path().join("skan.pdf");
+        write_pdf(&scanned, &["", ""])?;
+        let err = extract(&scanned).unwrap_err();
+        assert!(unsupported(&err));
+        assert!(err.to_string().contains("OCR"));
+
+        let broken = dir.path().join("zepsuty.pdf");
+        std::fs::write(&broken, "to nie jest PDF")?;
+        assert!(unsupported(&extract(&broken).unwrap_err()));
+        Ok(())
+    }
+}
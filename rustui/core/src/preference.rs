@@ -0,0 +1,214 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::atomic;
+use crate::lineage;
+use crate::processors::{ProcessingResult, Record};
+use crate::vault::Vault;
+
+/// Record metadata: alternative completions for the same prompt, compared to the record's own
+pub const CANDIDATES: &str = "candidates";
+/// Record metadata: judgments as `{"candidate": i, "chosen": ..., "rejected": ...}` objects
+pub const PREFERENCES: &str = "preferences";
+
+/// One line of a preference dataset, as read by TRL's DPO and ORPO trainers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreferencePair {
+    pub prompt: String,
+    pub chosen: String,
+    pub rejected: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    /// The record's completion
+    A,
+    /// The candidate it is compared to
+    B,
+}
+
+/// Prompt of a record as the trainer sees it: instruction and prompt
+pub fn full_prompt(record: &Record) -> String {
+    if record.instruction.is_empty() {
+        record.prompt.clone()
+    } else {
+        format!("{}\n\n{}", record.instruction, record.prompt)
+    }
+}
+
+/// Alternative completions of a record
+pub fn candidates(record: &Record) -> Vec<String> {
+    record.metadata.get(CANDIDATES)
+        .and_then(|v| v.as_array())
+        .map(|list| list.iter().filter_map(|c| c.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+/// Judgment recorded for the record's completion against candidate `candidate`
+pub fn choice(record: &Record, candidate: usize) -> Option<Choice> {
+    let judgment = record.metadata.get(PREFERENCES)?.as_array()?.iter()
+        .find(|j| j["candidate"].as_u64() == Some(candidate as u64))?;
+    Some(if judgment["chosen"].as_str() == Some(record.completion.as_str()) { Choice::A } else { Choice::B })
+}
+
+/// Records `choice` between the record's completion and candidate `candidate`,
+/// replacing an earlier judgment of the same pair
+pub fn judge(record: &mut Record, candidate: usize, choice: Choice) {
+    let Some(alternative) = candidates(record).into_iter().nth(candidate) else {
+        return;
+    };
+    let (chosen, rejected) = match choice {
+        Choice::A => (record.completion.clone(), alternative),
+        Choice::B => (alternative, record.completion.clone()),
+    };
+    let mut judgments: Vec<serde_json::Value> = record.metadata.get(PREFERENCES)
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    judgments.retain(|j| j["candidate"].as_u64() != Some(candidate as u64));
+    judgments.push(serde_json::json!({ "candidate": candidate, "chosen": chosen, "rejected": rejected }));
+    record.metadata.insert(PREFERENCES.to_string(), judgments.into());
+}
+
+/// Preference pairs from the recorded judgments
+pub fn pairs(results: &[ProcessingResult]) -> Vec<PreferencePair> {
+    let mut pairs = Vec::new();
+    for record in results.iter().flat_map(|result| &result.records) {
+        let Some(judgments) = record.metadata.get(PREFERENCES).and_then(|v| v.as_array()) else {
+            continue;
+        };
+        for judgment in judgments {
+            if let (Some(chosen), Some(rejected)) = (judgment["chosen"].as_str(), judgment["rejected"].as_str()) {
+                pairs.push(PreferencePair {
+                    prompt: full_prompt(record),
+                    chosen: chosen.to_string(),
+                    rejected: rejected.to_string(),
+                });
+            }
+        }
+    }
+    pairs
+}
+
+/// Writes one JSON object per line
+pub fn write_jsonl(path: &Path, pairs: &[PreferencePair]) -> Result<()> {
+    let mut content = String::new();
+    for pair in pairs {
+        content.push_str(&serde_json::to_string(pair)?);
+        content.push('\n');
+    }
+    atomic::write(path, content)
+}
+
+/// Side-by-side judging of a dataset's records against their candidates, saved after every judgment
+#[derive(Debug, Clone)]
+pub struct Review {
+    pub dataset: PathBuf,
+    pub results: Vec<ProcessingResult>,
+    /// Result, record and candidate index of every comparison
+    pub items: Vec<(usize, usize, usize)>,
+    pub current: usize,
+    pub vault: Vault,
+}
+
+impl Review {
+    /// Loads a dataset that may be encrypted; judgments are saved through the same vault
+    pub fn load(dataset: &Path, vault: Vault) -> Result<Self> {
+        let content = vault.read(dataset)?;
+        let mut results: Vec<ProcessingResult> = serde_json::from_slice(&content)
+            .with_context(|| format!("{} is not a dataset produced by a local run", dataset.display()))?;
+        // Judgments are saved with the records, so they keep pointing at the same ids
+        lineage::ensure_ids(&mut results);
+        let mut items = Vec::new();
+        for (r, result) in results.iter().enumerate() {
+            for (i, record) in result.records.iter().enumerate() {
+                items.extend((0..candidates(record).len()).map(|c| (r, i, c)));
+            }
+        }
+        Ok(Self { dataset: dataset.to_path_buf(), results, items, current: 0, vault })
+    }
+
+    /// Record under review, the candidate it is compared to and the judgment so far
+    pub fn current(&self) -> Option<(&Record, String, Option<Choice>)> {
+        let &(r, i, c) = self.items.get(self.current)?;
+        let record = &self.results[r].records[i];
+        let candidate = candidates(record).into_iter().nth(c)?;
+        Some((record, candidate, choice(record, c)))
+    }
+
+    pub fn judged(&self) -> usize {
+        self.items.iter()
+            .filter(|&&(r, i, c)| choice(&self.results[r].records[i], c).is_some())
+            .count()
+    }
+
+    pub fn next(&mut self) {
+        if self.current + 1 < self.items.len() {
+            self.current += 1;
+        }
+    }
+
+    pub fn previous(&mut self) {
+        self.current = self.current.saturating_sub(1);
+    }
+
+    /// Records the judgment, saves the dataset and moves to the next comparison
+    pub fn judge(&mut self, choice: Choice) -> Result<()> {
+        let Some(&(r, i, c)) = self.items.get(self.current) else {
+            return Ok(());
+        };
+        judge(&mut self.results[r].records[i], c, choice);
+        self.vault.write(&self.dataset, serde_json::to_vec_pretty(&self.results)?)?;
+        self.next();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::preference::{self, Choice, PreferencePair, CANDIDATES};
+    use crate::processors::{ProcessingResult, ProcessingStats, Record};
+
+    fn dataset() -> Vec<ProcessingResult> {
+        let record = |prompt: &str, completion: &str, candidates: &[&str]| Record {
+            instruction: "Answer briefly".to_string(),
+            prompt: prompt.to_string(),
+            completion: completion.to_string(),
+            metadata: if candidates.is_empty() {
+                Default::default()
+            } else {
+                [(CANDIDATES.to_string(), serde_json::json!(candidates))].into_iter().collect()
+            },
+        };
+        vec![ProcessingResult {
+            records: vec![
+                record("What is 2+2?", "4", &["5", "four"]),
+                record("Capital of France?", "Paris", &[]),
+            ],
+            source_file: "notes.txt".to_string(),
+            processing_type: "qa".to_string(),
+            stats: ProcessingStats { total_records: 2, total_tokens: 0, processing_time_ms: 0 },
+            exchanges: vec![],
+            error: None,
+        }]
+    }
+
+    #[test]
+    fn test_judgments_become_pairs() {
+        let mut results = dataset();
+        let record = &mut results[0].records[0];
+        preference::judge(record, 0, Choice::B);
+        // Ponowna ocena tej samej pary zastępuje poprzednią
+        preference::judge(record, 0, Choice::A);
+        preference::judge(record, 1, Choice::B);
+        assert_eq!(preference::choice(record, 0), Some(Choice::A));
+        assert_eq!(preference::choice(record, 1), Some(Choice::B));
+
+        let prompt = "Answer briefly\n\nWhat is 2+2?".to_string();
+        assert_eq!(preference::pairs(&results), vec![
+            PreferencePair { prompt: prompt.clone(), chosen: "4".to_string(), rejected: "5".to_string() },
+            PreferencePair { prompt, chosen: "four".to_string(), rejected: "4".to_string() },
+        ]);
+    }
+}
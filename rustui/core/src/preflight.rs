@@ -0,0 +1,430 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+
+/// How much of each file the content checks read; enough to judge, fast on large corpora
+const SAMPLE_BYTES: u64 = 1024 * 1024;
+
+/// Formats that are binary by design and reach the processors through their own readers
+const DOCUMENT_EXTENSIONS: [&str; 11] = ["pdf", "docx", "odt", "jpg", "jpeg", "png", "tif", "tiff", "zip", "gz", "parquet"];
+
+/// Repeated lines are only judged in files with at least this many lines
+const MIN_LINES_FOR_REPETITION: usize = 10;
+
+/// Paragraphs and files with fewer words are too short to tell their language
+const MIN_WORDS_FOR_LANGUAGE: usize = 20;
+
+/// Frequent short words of the languages told apart
+const STOPWORDS: [(&str, &[&str]); 6] = [
+    ("en", &["the", "and", "of", "to", "is", "in", "that", "it", "for", "with", "was", "on", "are", "this", "be"]),
+    ("pl", &["i", "w", "na", "się", "nie", "z", "do", "to", "że", "jest", "jak", "o", "po", "ale", "od"]),
+    ("de", &["der", "die", "und", "das", "ist", "nicht", "zu", "den", "mit", "von", "sich", "ein", "auf", "es", "für"]),
+    ("fr", &["le", "la", "les", "et", "des", "est", "une", "que", "dans", "pour", "pas", "du", "sur", "qui", "au"]),
+    ("es", &["el", "la", "de", "que", "y", "los", "en", "las", "por", "con", "una", "para", "es", "del", "se"]),
+    ("it", &["il", "di", "che", "e", "la", "per", "non", "un", "sono", "gli", "della", "del", "una", "con", "le"]),
+];
+
+/// `[preflight]` in config.toml: the corpus check run before a job is submitted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreflightSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Share of repeated lines above which a file is flagged
+    #[serde(default = "default_max_repeated_lines")]
+    pub max_repeated_lines: f64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_repeated_lines() -> f64 {
+    0.5
+}
+
+impl Default for PreflightSettings {
+    fn default() -> Self {
+        Self { enabled: true, max_repeated_lines: default_max_repeated_lines() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    Unreadable,
+    Empty,
+    Binary,
+    Encoding,
+    Duplicate,
+    Language,
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            IssueKind::Unreadable => "unreadable",
+            IssueKind::Empty => "empty",
+            IssueKind::Binary => "binary",
+            IssueKind::Encoding => "encoding",
+            IssueKind::Duplicate => "duplicate",
+            IssueKind::Language => "language",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub kind: IssueKind,
+    pub detail: String,
+}
+
+impl Issue {
+    fn new(kind: IssueKind, detail: impl Into<String>) -> Self {
+        Self { kind, detail: detail.into() }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.kind, self.detail)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileReport {
+    pub file: String,
+    pub bytes: u64,
+    /// Language code, or script name for non-Latin text; `None` when it can't be told
+    pub language: Option<String>,
+    pub issues: Vec<Issue>,
+}
+
+impl FileReport {
+    pub fn is_flagged(&self) -> bool {
+        !self.issues.is_empty()
+    }
+}
+
+/// Findings for every file of a corpus, in the order the files were given
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CorpusReport {
+    pub files: Vec<FileReport>,
+}
+
+impl CorpusReport {
+    pub fn flagged(&self) -> Vec<&FileReport> {
+        self.files.iter().filter(|file| file.is_flagged()).collect()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.files.iter().all(|file| !file.is_flagged())
+    }
+
+    /// Number of files in each detected language
+    pub fn languages(&self) -> BTreeMap<String, usize> {
+        let mut languages = BTreeMap::new();
+        for language in self.files.iter().filter_map(|file| file.language.as_ref()) {
+            *languages.entry(language.clone()).or_insert(0) += 1;
+        }
+        languages
+    }
+
+    /// One line: file count, flagged count and the language mix
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{} file(s), {} flagged", self.files.len(), self.flagged().len());
+        let languages = self.languages();
+        if !languages.is_empty() {
+            let mix: Vec<String> = languages.iter().map(|(language, count)| format!("{} {}", language, count)).collect();
+            summary.push_str(&format!("; languages: {}", mix.join(", ")));
+        }
+        summary
+    }
+}
+
+/// Checks every file for emptiness, binary content, encoding problems, repeated lines and
+/// language, then compares the files with each other for duplicates and language outliers
+pub fn analyze(files: &[String], settings: &PreflightSettings) -> CorpusReport {
+    let mut reports = Vec::new();
+    let mut digests: HashMap<[u8; 32], usize> = HashMap::new();
+    for file in files {
+        let mut report = FileReport { file: file.clone(), bytes: 0, language: None, issues: Vec::new() };
+        match inspect(Path::new(file), settings, &mut report) {
+            Ok(Some(digest)) => {
+                if let Some(&first) = digests.get(&digest) {
+                    report.issues.push(Issue::new(IssueKind::Duplicate, format!("same content as {}", files[first])));
+                } else {
+                    digests.insert(digest, reports.len());
+                }
+            },
+            Ok(None) => {},
+            Err(err) => report.issues.push(Issue::new(IssueKind::Unreadable, err.to_string())),
+        }
+        reports.push(report);
+    }
+    flag_language_outliers(&mut reports);
+    CorpusReport { files: reports }
+}
+
+/// Runs the per-file checks and returns the file's digest, `None` for empty files
+fn inspect(path: &Path, settings: &PreflightSettings, report: &mut FileReport) -> std::io::Result<Option<[u8; 32]>> {
+    report.bytes = std::fs::metadata(path)?.len();
+    if report.bytes == 0 {
+        report.issues.push(Issue::new(IssueKind::Empty, "empty file"));
+        return Ok(None);
+    }
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    let digest = hasher.finalize().into();
+
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    if DOCUMENT_EXTENSIONS.contains(&extension.as_str()) {
+        return Ok(Some(digest));
+    }
+    let mut sample = Vec::new();
+    std::fs::File::open(path)?.take(SAMPLE_BYTES).read_to_end(&mut sample)?;
+    if let Some(issue) = check_bytes(&sample) {
+        report.issues.push(issue);
+        return Ok(Some(digest));
+    }
+    // Checked above: at most an incomplete character at the end of the sample
+    let text = String::from_utf8_lossy(&sample);
+    if text.trim().is_empty() {
+        report.issues.push(Issue::new(IssueKind::Empty, "only whitespace"));
+        return Ok(Some(digest));
+    }
+    report.issues.extend(check_text(&text));
+    if let Some(issue) = check_repetition(&text, settings.max_repeated_lines) {
+        report.issues.push(issue);
+    }
+    report.language = detect_language(&text).map(str::to_string);
+    if let Some(issue) = check_language_mix(&text) {
+        report.issues.push(issue);
+    }
+    Ok(Some(digest))
+}
+
+/// Binary content and encodings other than UTF-8
+pub fn check_bytes(sample: &[u8]) -> Option<Issue> {
+    if sample.starts_with(&[0xFF, 0xFE]) || sample.starts_with(&[0xFE, 0xFF]) {
+        return Some(Issue::new(IssueKind::Encoding, "UTF-16 text; convert it to UTF-8"));
+    }
+    let control = sample.iter()
+        .filter(|&&b| b == 0 || (b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C)))
+        .count();
+    if sample.contains(&0) || control * 10 > sample.len() {
+        return Some(Issue::new(IssueKind::Binary, "binary content in a file processed as text"));
+    }
+    match std::str::from_utf8(sample) {
+        // An error without a length is a character cut off at the end of the sample
+        Err(err) if err.error_len().is_some() => Some(Issue::new(
+            IssueKind::Encoding,
+            format!("not valid UTF-8 at byte {}; probably Windows-1250 or Latin-1", err.valid_up_to()),
+        )),
+        _ => None,
+    }
+}
+
+/// Damage left by earlier wrong conversions: replacement characters and mojibake
+pub fn check_text(text: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let replaced = text.chars().filter(|&c| c == '\u{FFFD}').count();
+    if replaced > 0 {
+        issues.push(Issue::new(
+            IssueKind::Encoding,
+            format!("{} replacement character(s) (\u{FFFD}); the text was already damaged by a conversion", replaced),
+        ));
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mojibake = chars.windows(2).filter(|pair| is_mojibake(pair[0], pair[1])).count();
+    if mojibake >= 3 {
+        issues.push(Issue::new(
+            IssueKind::Encoding,
+            format!("{} sequence(s) like 'Å‚' for 'ł'; UTF-8 text read as Windows-1252 or Latin-1", mojibake),
+        ));
+    }
+    issues
+}
+
+/// The first byte of a UTF-8 sequence decoded as Latin-1/Windows-1252, followed by a continuation byte
+fn is_mojibake(first: char, second: char) -> bool {
+    matches!(first, 'Â' | 'Ã' | 'Ä' | 'Å' | 'â')
+        && (('\u{80}'..='\u{BF}').contains(&second) || "€‚ƒ„…†‡ˆ‰Š‹ŒŽ‘’“”•–—˜™š›œžŸ".contains(second))
+}
+
+/// Files made mostly of lines that already occurred (copied boilerplate, duplicated records)
+pub fn check_repetition(text: &str, max_repeated: f64) -> Option<Issue> {
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    if lines.len() < MIN_LINES_FOR_REPETITION {
+        return None;
+    }
+    let mut seen = HashSet::new();
+    let repeated = lines.iter().filter(|line| !seen.insert(**line)).count();
+    let share = repeated as f64 / lines.len() as f64;
+    (share > max_repeated).then(|| Issue::new(
+        IssueKind::Duplicate,
+        format!("{:.0}% of {} lines are repeated", share * 100.0, lines.len()),
+    ))
+}
+
+/// Whether a letter belongs to a script
+type ScriptTest = fn(char) -> bool;
+
+/// Language by frequent words, or the script of non-Latin text
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.is_empty() {
+        return None;
+    }
+    let scripts: [(&str, ScriptTest); 5] = [
+        ("cyrillic", |c| ('\u{0400}'..='\u{04FF}').contains(&c)),
+        ("greek", |c| ('\u{0370}'..='\u{03FF}').contains(&c)),
+        ("arabic", |c| ('\u{0600}'..='\u{06FF}').contains(&c)),
+        ("hebrew", |c| ('\u{0590}'..='\u{05FF}').contains(&c)),
+        ("cjk", |c| ('\u{3040}'..='\u{30FF}').contains(&c) || ('\u{4E00}'..='\u{9FFF}').contains(&c) || ('\u{AC00}'..='\u{D7AF}').contains(&c)),
+    ];
+    for (script, in_script) in scripts {
+        if letters.iter().filter(|&&c| in_script(c)).count() * 2 > letters.len() {
+            return Some(script);
+        }
+    }
+
+    let words: Vec<String> = text.split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.len() < MIN_WORDS_FOR_LANGUAGE {
+        return None;
+    }
+    let mut scores: Vec<(&str, usize)> = STOPWORDS.iter()
+        .map(|(language, stopwords)| (*language, words.iter().filter(|word| stopwords.contains(&word.as_str())).count()))
+        .collect();
+    scores.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    let (language, best) = scores[0];
+    // Too few stopwords, or two languages scoring alike (shared words like "la", "de")
+    if best * 20 < words.len() || best * 4 < scores[1].1 * 5 {
+        return None;
+    }
+    Some(language)
+}
+
+/// A file whose paragraphs are written in different languages
+pub fn check_language_mix(text: &str) -> Option<Issue> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for paragraph in text.split("\n\n") {
+        if let Some(language) = detect_language(paragraph) {
+            *counts.entry(language).or_insert(0) += 1;
+        }
+    }
+    let total: usize = counts.values().sum();
+    // Every language must have a fifth of the paragraphs; a quote or two doesn't count
+    let mixed: Vec<String> = counts.iter()
+        .filter(|(_, count)| **count * 5 >= total)
+        .map(|(language, count)| format!("{} {:.0}%", language, *count as f64 * 100.0 / total as f64))
+        .collect();
+    (mixed.len() > 1).then(|| Issue::new(IssueKind::Language, format!("paragraphs in several languages: {}", mixed.join(", "))))
+}
+
+/// Flags files in another language than most of the corpus
+fn flag_language_outliers(reports: &mut [FileReport]) {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for language in reports.iter().filter_map(|report| report.language.clone()) {
+        *counts.entry(language).or_insert(0) += 1;
+    }
+    let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    // Without a clear majority there's no outlier to point at
+    let Some((majority, count)) = ranked.first().cloned() else {
+        return;
+    };
+    if ranked.get(1).is_some_and(|(_, second)| *second == count) {
+        return;
+    }
+    for report in reports {
+        if let Some(language) = report.language.as_ref().filter(|language| **language != majority) {
+            let detail = format!("written in {}, while {} of the files are in {}", language, count, majority);
+            report.issues.push(Issue::new(IssueKind::Language, detail));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::preflight::{analyze, check_bytes, check_language_mix, check_repetition, check_text, detect_language, IssueKind, PreflightSettings};
+
+    const POLISH: &str = "To jest krótki tekst po polsku, który nie jest ani pusty, ani binarny. \
+            Na początku dokumentu jest opis, a w dalszej części są wyniki i wnioski do omówienia.";
+
+    const ENGLISH: &str = "This is a short text in English that is neither empty nor binary. \
+            It is here to check that the language of the file is told apart from the rest of the corpus.";
+
+    #[test]
+    fn test_bytes_and_encoding() {
+        assert_eq!(check_bytes(b"abc\0def").unwrap().kind, IssueKind::Binary);
+        assert_eq!(check_bytes(&[0xFF, 0xFE, b'a', 0]).unwrap().kind, IssueKind::Encoding);
+        // "Zażółć" w Windows-1250
+        assert_eq!(check_bytes(b"Za\xBF\xF3\xB3\xE6 tekst").unwrap().kind, IssueKind::Encoding);
+        // Znak ucięty na końcu próbki to nie błąd kodowania
+        assert!(check_bytes("zażółć".as_bytes()[..9].as_ref()).is_none());
+        assert!(check_bytes("Zwykły tekst\n\tz tabulatorem".as_bytes()).is_none());
+
+        let issues = check_text("ZaÅ¼Ã³Å‚Ä‡ gÄ™Å›lÄ… jaÅºÅ„");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, IssueKind::Encoding);
+        assert!(check_text("Ärger über Ölpreise, à la carte").is_empty());
+        assert!(check_text("uszkodzony \u{FFFD} znak")[0].detail.starts_with("1 replacement"));
+    }
+
+    #[test]
+    fn test_repetition_and_language() {
+        let repeated = "Strona 1 z 10\n".repeat(8) + "pierwsza\ndruga\n";
+        let issue = check_repetition(&repeated, 0.5).unwrap();
+        assert_eq!(issue.kind, IssueKind::Duplicate);
+        assert!(issue.detail.starts_with("70% of 10 lines"));
+        assert!(check_repetition(&repeated, 0.8).is_none());
+        assert!(check_repetition("a\na\na", 0.1).is_none());
+
+        assert_eq!(detect_language(POLISH), Some("pl"));
+        assert_eq!(detect_language(ENGLISH), Some("en"));
+        assert_eq!(detect_language("Это короткий текст на русском языке"), Some("cyrillic"));
+        assert_eq!(detect_language("za krótki"), None);
+
+        let mixed = format!("{}\n\n{}\n\n{}", POLISH, ENGLISH, POLISH);
+        let issue = check_language_mix(&mixed).unwrap();
+        assert_eq!(issue.detail, "paragraphs in several languages: en 33%, pl 67%");
+        assert!(check_language_mix(&format!("{}\n\n{}", POLISH, POLISH)).is_none());
+    }
+
+    #[test]
+    fn test_corpus_report() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = |name: &str| dir.path().join(name).to_string_lossy().into_owned();
+        std::fs::write(path("a.txt"), POLISH)?;
+        std::fs::write(path("b.txt"), format!("{} Drugi plik.", POLISH))?;
+        std::fs::write(path("kopia.txt"), POLISH)?;
+        std::fs::write(path("en.txt"), ENGLISH)?;
+        std::fs::write(path("pusty.txt"), "")?;
+        std::fs::write(path("dane.txt"), b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR")?;
+        // PDF jest binarny z natury
+        std::fs::write(path("raport.pdf"), b"%PDF-1.4\n\0\0binary")?;
+
+        let files: Vec<String> = ["a.txt", "b.txt", "kopia.txt", "en.txt", "pusty.txt", "dane.txt", "raport.pdf", "brak.txt"]
+            .iter().map(|name| path(name)).collect();
+        let report = analyze(&files, &PreflightSettings::default());
+        let kinds = |i: usize| report.files[i].issues.iter().map(|issue| issue.kind).collect::<Vec<_>>();
+        assert_eq!(kinds(0), vec![]);
+        assert_eq!(kinds(1), vec![]);
+        assert_eq!(kinds(2), vec![IssueKind::Duplicate]);
+        assert!(report.files[2].issues[0].detail.ends_with("a.txt"));
+        assert_eq!(kinds(3), vec![IssueKind::Language]);
+        assert_eq!(kinds(4), vec![IssueKind::Empty]);
+        assert_eq!(kinds(5), vec![IssueKind::Binary]);
+        assert_eq!(kinds(6), vec![]);
+        assert_eq!(kinds(7), vec![IssueKind::Unreadable]);
+        assert_eq!(report.flagged().len(), 5);
+        assert_eq!(report.summary(), "8 file(s), 5 flagged; languages: en 1, pl 3");
+        Ok(())
+    }
+}
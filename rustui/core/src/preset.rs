@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::bestof::BestOfSettings;
+use crate::tabular::Column;
+use crate::voting::VoteSettings;
+
+/// Named processing configuration. Unset fields leave the current selection unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processing_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Name of a prompt template to use as the system prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<String>,
+    /// Best-of-N candidate generation for this preset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_of: Option<BestOfSettings>,
+    /// Self-consistency voting for this preset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voting: Option<VoteSettings>,
+    /// Record schema (`[schemas.<name>]`) for structured extraction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Column mapping for CSV/Parquet exports of this preset's datasets
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<Column>,
+}
+
+impl Preset {
+    /// Takes every field `other` sets; the rest stays as it is
+    pub fn overlay(&mut self, other: &Preset) {
+        fn set<T: Clone>(field: &mut Option<T>, value: &Option<T>) {
+            if value.is_some() {
+                field.clone_from(value);
+            }
+        }
+        set(&mut self.description, &other.description);
+        set(&mut self.processing_type, &other.processing_type);
+        set(&mut self.provider, &other.provider);
+        set(&mut self.model, &other.model);
+        set(&mut self.language, &other.language);
+        if other.template.is_some() || other.system_prompt.is_some() {
+            // A prompt given on top replaces the one underneath, template or not
+            self.template.clone_from(&other.template);
+            self.system_prompt.clone_from(&other.system_prompt);
+        }
+        if !other.keywords.is_empty() {
+            self.keywords.clone_from(&other.keywords);
+        }
+        set(&mut self.temperature, &other.temperature);
+        set(&mut self.max_tokens, &other.max_tokens);
+        set(&mut self.top_p, &other.top_p);
+        set(&mut self.output_format, &other.output_format);
+        set(&mut self.best_of, &other.best_of);
+        set(&mut self.voting, &other.voting);
+        set(&mut self.schema, &other.schema);
+        if !other.columns.is_empty() {
+            self.columns.clone_from(&other.columns);
+        }
+    }
+}
+
+/// Where an effective preset or template comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    Local,
+    Shared,
+    /// Local definition shadowing a shared one with the same name
+    Overridden,
+}
+
+impl Origin {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Origin::Local => "local",
+            Origin::Shared => "shared",
+            Origin::Overridden => "local override",
+        }
+    }
+}
+
+/// Presets and prompt templates as stored in a file (`presets.toml` in a shared repository)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PresetCollection {
+    #[serde(default)]
+    pub presets: BTreeMap<String, Preset>,
+    #[serde(default)]
+    pub templates: BTreeMap<String, String>,
+}
+
+/// Merges shared and local entries by name; local ones win
+pub fn merge<T: Clone>(local: &BTreeMap<String, T>, shared: &BTreeMap<String, T>) -> BTreeMap<String, (T, Origin)> {
+    let mut merged: BTreeMap<String, (T, Origin)> = shared.iter()
+        .map(|(name, item)| (name.clone(), (item.clone(), Origin::Shared)))
+        .collect();
+    for (name, item) in local {
+        let origin = if merged.contains_key(name) { Origin::Overridden } else { Origin::Local };
+        merged.insert(name.clone(), (item.clone(), origin));
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::preset::{merge, Origin, PresetCollection};
+    use crate::preset_sync::PresetSyncSettings;
+
+    #[test]
+    fn test_local_presets_override_shared() -> anyhow::Result<()> {
+        let shared: PresetCollection = toml::from_str(r#"
+                [presets.faq]
+                description = "shared"
+                language = "en"
+                
+                [presets.summary]
+                processing_type = "standard"
+                
+                [templates]
+                faq = "Shared prompt"
+            "#)?;
+        let local: PresetCollection = toml::from_str(r#"
+                [presets.faq]
+                description = "mine"
+                
+                [presets.draft]
+                model = "gpt-4o"
+            "#)?;
+
+        let merged = merge(&local.presets, &shared.presets);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged["faq"].1, Origin::Overridden);
+        assert_eq!(merged["faq"].0.description.as_deref(), Some("mine"));
+        // Lokalny preset zastępuje współdzielony w całości, bez łączenia pól
+        assert_eq!(merged["faq"].0.language, None);
+        assert_eq!(merged["summary"].1, Origin::Shared);
+        assert_eq!(merged["draft"].1, Origin::Local);
+
+        let templates = merge(&local.templates, &shared.templates);
+        assert_eq!(templates["faq"], ("Shared prompt".to_string(), Origin::Shared));
+        Ok(())
+    }
+
+    #[test]
+    fn test_preset_source_kind() {
+        let settings = |source: &str| PresetSyncSettings {
+            source: source.to_string(),
+            branch: None,
+            file: "presets.toml".to_string(),
+            refresh_minutes: 0,
+        };
+        assert!(settings("git@github.com:team/presets.git").is_git());
+        assert!(settings("https://github.com/team/presets.git").is_git());
+        assert!(settings("/srv/presets").is_git());
+        assert!(!settings("https://example.com/presets.toml").is_git());
+        assert_eq!(settings("/srv/presets").refresh_interval(), None);
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::atomic;
+use crate::git;
+use crate::network;
+use crate::preset::PresetCollection;
+
+/// Shared preset source from the `[preset_sync]` config section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PresetSyncSettings {
+    /// Git repository (URL ending in `.git`, `git@…`, `ssh://…` or a local path) or an HTTP(S) URL of the file
+    pub source: String,
+    /// Branch to track for git sources; the remote default when unset
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Path of the presets file inside a git repository
+    #[serde(default = "default_file")]
+    pub file: String,
+    /// How often to refresh while the TUI is running; 0 disables periodic refresh
+    #[serde(default = "default_refresh_minutes")]
+    pub refresh_minutes: u64,
+}
+
+fn default_file() -> String {
+    "presets.toml".to_string()
+}
+
+fn default_refresh_minutes() -> u64 {
+    60
+}
+
+impl PresetSyncSettings {
+    pub fn is_git(&self) -> bool {
+        let source = self.source.trim_end_matches('/');
+        source.ends_with(".git")
+            || source.starts_with("git@")
+            || source.starts_with("ssh://")
+            || !(source.starts_with("http://") || source.starts_with("https://"))
+    }
+
+    pub fn refresh_interval(&self) -> Option<Duration> {
+        (self.refresh_minutes > 0).then(|| Duration::from_secs(self.refresh_minutes * 60))
+    }
+
+    /// Cache location for this source, stable across runs
+    fn cache_dir(&self) -> Result<PathBuf> {
+        let digest = Sha256::digest(self.source.as_bytes());
+        let key: String = digest.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+        Ok(dirs::cache_dir()
+            .context("Could not determine cache directory")?
+            .join("anydataset-tui")
+            .join("shared-presets")
+            .join(key))
+    }
+}
+
+/// Result of a sync
+#[derive(Debug, Clone, Default)]
+pub struct SharedPresets {
+    pub collection: PresetCollection,
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// Set when fetching failed and the last cached copy is used instead
+    pub stale_reason: Option<String>,
+}
+
+/// Fetches the shared presets. When the source is unreachable the last successfully
+/// fetched copy is returned, marked stale.
+pub async fn sync(settings: &PresetSyncSettings) -> Result<SharedPresets> {
+    let cache_dir = settings.cache_dir()?;
+    let fetched = if settings.is_git() {
+        let settings = settings.clone();
+        let dir = cache_dir.clone();
+        tokio::task::spawn_blocking(move || fetch_git(&settings, &dir))
+            .await
+            .context("Sync task failed")?
+    } else {
+        fetch_http(&settings.source, &cache_dir).await
+    };
+
+    let (file, stale_reason) = match fetched {
+        Ok(file) => (file, None),
+        Err(err) => {
+            let cached = cached_file(settings, &cache_dir);
+            if !cached.exists() {
+                return Err(err);
+            }
+            (cached, Some(format!("{:#}", err)))
+        },
+    };
+
+    let content = std::fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let collection: PresetCollection = toml::from_str(&content)
+        .with_context(|| format!("Invalid presets file {}", file.display()))?;
+    let fetched_at = std::fs::metadata(&file).and_then(|m| m.modified()).ok().map(DateTime::<Utc>::from);
+
+    Ok(SharedPresets { collection, fetched_at, stale_reason })
+}
+
+fn cached_file(settings: &PresetSyncSettings, cache_dir: &Path) -> PathBuf {
+    if settings.is_git() {
+        cache_dir.join("repo").join(&settings.file)
+    } else {
+        cache_dir.join("presets.toml")
+    }
+}
+
+/// Clones on first use, afterwards fetches and hard-resets to the remote branch.
+/// The checkout is a cache, so local edits there are discarded.
+fn fetch_git(settings: &PresetSyncSettings, cache_dir: &Path) -> Result<PathBuf> {
+    let repo = cache_dir.join("repo");
+    if repo.join(".git").exists() {
+        let branch = settings.branch.as_deref().unwrap_or("HEAD");
+        git::run(&repo, &["fetch", "--depth", "1", "origin", branch])?;
+        git::run(&repo, &["reset", "--hard", "FETCH_HEAD"])?;
+    } else {
+        std::fs::create_dir_all(cache_dir).context("Failed to create preset cache directory")?;
+        let repo_arg = repo.to_string_lossy().into_owned();
+        let mut args = vec!["clone", "--depth", "1"];
+        if let Some(branch) = &settings.branch {
+            args.extend(["--branch", branch.as_str()]);
+        }
+        args.extend([settings.source.as_str(), repo_arg.as_str()]);
+        git::run(cache_dir, &args)?;
+    }
+
+    let file = repo.join(&settings.file);
+    if !file.exists() {
+        anyhow::bail!("{} not found in {}", settings.file, settings.source);
+    }
+    Ok(file)
+}
+
+async fn fetch_http(url: &str, cache_dir: &Path) -> Result<PathBuf> {
+    let response = network::client()
+        .get(url)
+        .timeout(Duration::from_secs(30))
+        .send()
+        .await
+        .context("Failed to download shared presets")?;
+    if !response.status().is_success() {
+        anyhow::bail!("Preset source returned {}", response.status());
+    }
+    let body = response.text().await.context("Failed to read shared presets")?;
+    // Validate before replacing the cached copy
+    toml::from_str::<PresetCollection>(&body).context("Shared presets file is invalid")?;
+
+    std::fs::create_dir_all(cache_dir).context("Failed to create preset cache directory")?;
+    let file = cache_dir.join("presets.toml");
+    atomic::write(&file, body)?;
+    Ok(file)
+}
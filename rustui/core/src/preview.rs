@@ -0,0 +1,208 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::chunking;
+use crate::crashsafe;
+use crate::office::{self, OfficeFormat};
+use crate::pdf;
+use crate::processors::{self, ProcessorConfig, Record};
+
+/// More samples than this would make the preview as slow as a small run
+pub const MAX_SAMPLES: usize = 5;
+
+/// `[preview]` in config.toml: the dry run on a few chunks before a full job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreviewSettings {
+    /// Chunks sampled per preview, at most `MAX_SAMPLES`
+    #[serde(default = "default_samples")]
+    pub samples: usize,
+}
+
+fn default_samples() -> usize {
+    3
+}
+
+impl Default for PreviewSettings {
+    fn default() -> Self {
+        Self { samples: default_samples() }
+    }
+}
+
+impl PreviewSettings {
+    pub fn sample_count(&self) -> usize {
+        self.samples.clamp(1, MAX_SAMPLES)
+    }
+}
+
+/// Records the pipeline produced for the sampled chunks of one file
+#[derive(Debug, Clone)]
+pub struct Preview {
+    pub file: String,
+    /// Chunk numbers (from 1) that were sampled, in document order
+    pub chunks: Vec<usize>,
+    pub total_chunks: usize,
+    pub records: Vec<Record>,
+    pub tokens: usize,
+}
+
+impl Preview {
+    pub fn summary(&self) -> String {
+        format!(
+            "Preview of {}: {} record(s) from chunk(s) {} of {}, {} token(s)",
+            self.file,
+            self.records.len(),
+            self.chunks.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", "),
+            self.total_chunks,
+            self.tokens,
+        )
+    }
+}
+
+/// Text of a document as the processors read it: PDF pages and DOCX/ODT sections
+/// are joined, other files go through the configured cleanup and figure captions
+pub fn document_text(path: &Path, config: &ProcessorConfig) -> Result<String> {
+    if pdf::is_pdf(path) {
+        let text = pdf::extract(path)?;
+        return Ok(text.pages.into_iter().map(|(_, page)| page).collect::<Vec<_>>().join("\n\n"));
+    }
+    if let Some(format) = OfficeFormat::of(path) {
+        let blocks = office::read_blocks(path, format)?;
+        return Ok(office::sections(&blocks).into_iter().map(|section| section.text).collect::<Vec<_>>().join("\n\n"));
+    }
+    let (text, _) = processors::read_document(&path.to_string_lossy(), config)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(text)
+}
+
+/// Picks `count` of `len` positions pseudo-randomly from `seed`, returned in ascending order
+pub fn sample_indices(len: usize, count: usize, seed: u64) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..len).collect();
+    // xorshift64*; zero is its only fixed point
+    let mut state = seed | 1;
+    let count = count.min(len);
+    for i in 0..count {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        let random = state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        let j = i + (random % (len - i) as u64) as usize;
+        indices.swap(i, j);
+    }
+    let mut picked = indices[..count].to_vec();
+    picked.sort_unstable();
+    picked
+}
+
+/// Positions `0..len` in a pseudo-random order (Fisher-Yates)
+pub fn shuffled(len: usize, seed: u64) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..len).collect();
+    // xorshift64*; zero is its only fixed point
+    let mut state = seed | 1;
+    for i in (1..len).rev() {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        let random = state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        order.swap(i, (random % (i as u64 + 1)) as usize);
+    }
+    order
+}
+
+/// Runs the `processing_type` pipeline on `samples` chunks of `file` picked with `seed`.
+/// Each sampled chunk is processed as a document of its own, so nothing else is sent.
+pub fn run(file: &str, processing_type: &str, config: &ProcessorConfig, samples: usize, seed: u64) -> Result<Preview> {
+    let text = crashsafe::parse(file, || document_text(Path::new(file), config))?;
+    let settings = config.chunking.clone().unwrap_or_default();
+    let chunks = chunking::split(&text, &settings);
+    if chunks.is_empty() {
+        anyhow::bail!("{} has no text to preview", file);
+    }
+    let picked = sample_indices(chunks.len(), samples, seed);
+
+    // The sample text is already read and cleaned, and fits one chunk
+    let mut sample_config = config.clone();
+    sample_config.chunking = Some(settings);
+    sample_config.cleanup = None;
+    sample_config.figures = None;
+
+    let processor = processors::get_processor(processing_type)?;
+    let dir = tempfile::tempdir().context("Failed to create a directory for the samples")?;
+    let mut records = Vec::new();
+    let mut tokens = 0;
+    for &index in &picked {
+        let sample_path = dir.path().join(format!("chunk-{}.txt", index + 1));
+        std::fs::write(&sample_path, &chunks[index].text)?;
+        let result = crashsafe::parse(file, || processor.process_file(&sample_path.to_string_lossy(), &sample_config))
+            .with_context(|| format!("Chunk {} of {}", index + 1, file))?;
+        tokens += result.stats.total_tokens;
+        for mut record in result.records {
+            record.metadata.insert("source_file".to_string(), serde_json::json!(file));
+            record.metadata.insert("preview_chunk".to_string(), serde_json::json!(index + 1));
+            record.metadata.insert("preview_chunks".to_string(), serde_json::json!(chunks.len()));
+            records.push(record);
+        }
+    }
+    Ok(Preview {
+        file: file.to_string(),
+        chunks: picked.iter().map(|index| index + 1).collect(),
+        total_chunks: chunks.len(),
+        records,
+        tokens,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunking::ChunkSettings;
+    use crate::preview::{run, sample_indices, PreviewSettings};
+    use crate::processors::{test_config, ProcessorConfig};
+
+    fn config() -> ProcessorConfig {
+        ProcessorConfig {
+            chunking: Some(ChunkSettings { size_words: 10, overlap_words: 2, ..Default::default() }),
+            ..test_config()
+        }
+    }
+
+    #[test]
+    fn test_sample_indices() {
+        let picked = sample_indices(20, 4, 42);
+        assert_eq!(picked.len(), 4);
+        assert!(picked.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(picked.iter().all(|&index| index < 20));
+        // To samo ziarno daje tę samą próbkę
+        assert_eq!(sample_indices(20, 4, 42), picked);
+        assert_eq!(sample_indices(3, 5, 7), vec![0, 1, 2]);
+        assert!(sample_indices(0, 3, 7).is_empty());
+
+        assert_eq!(PreviewSettings::default().sample_count(), 3);
+        assert_eq!(PreviewSettings { samples: 50 }.sample_count(), 5);
+        assert_eq!(PreviewSettings { samples: 0 }.sample_count(), 1);
+    }
+
+    #[test]
+    fn test_preview_runs_only_sampled_chunks() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("doc.txt");
+        let words: Vec<String> = (1..=50).map(|i| format!("word{}", i)).collect();
+        std::fs::write(&path, words.join(" "))?;
+        let file = path.to_string_lossy().into_owned();
+
+        let preview = run(&file, "standard", &config(), 3, 1234)?;
+        assert_eq!(preview.total_chunks, 5);
+        assert_eq!(preview.chunks.len(), 3);
+        assert_eq!(preview.records.len(), 3);
+        for (record, &chunk) in preview.records.iter().zip(&preview.chunks) {
+            // Model widzi tylko wylosowany fragment, bez kontekstu sąsiadów
+            assert_eq!(record.completion, words[(chunk - 1) * 10..chunk * 10].join(" "));
+            assert_eq!(record.metadata["preview_chunk"], serde_json::json!(chunk));
+            assert_eq!(record.metadata["preview_chunks"], serde_json::json!(5));
+            assert_eq!(record.metadata["source_file"], serde_json::json!(file));
+        }
+
+        std::fs::write(&path, "")?;
+        assert!(run(&file, "standard", &config(), 3, 1234).is_err());
+        Ok(())
+    }
+}
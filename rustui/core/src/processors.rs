@@ -0,0 +1,679 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::audit::{self, ModelExchange};
+use crate::bestof::{self, BestOfSettings, Selection};
+use crate::chunking::{self, ChunkSettings};
+use crate::cleanup::{self, CleanupSettings};
+use crate::crashsafe;
+use crate::confidence::{self, ConfidenceSettings, LowConfidence};
+use crate::docmeta::{self, MetadataSettings};
+use crate::error::{AppError, ProcessingErrorKind};
+use crate::figures::{self, FigureSettings};
+use crate::legal::ClauseProcessor;
+use crate::lineage;
+use crate::processing_error;
+use crate::office::{self, OfficeFormat, OfficeProcessor};
+use crate::paper::{PaperProcessor, PaperSettings};
+use crate::pdf::{self, PdfProcessor};
+use crate::qa::{QaProcessor, QaSettings};
+use crate::summarize::{MapReduceProcessor, MapReduceSettings};
+use crate::voting::{self, VoteSettings};
+
+/// Common record format used across all processing types
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub instruction: String,
+    pub prompt: String,
+    pub completion: String,
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+/// Result of any processing operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessingResult {
+    pub records: Vec<Record>,
+    pub source_file: String,
+    pub processing_type: String,
+    pub stats: ProcessingStats,
+    /// Why the file has no records; a failed file doesn't stop the rest of a local batch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Model calls behind the records; kept out of the dataset, see `audit`
+    #[serde(skip)]
+    pub exchanges: Vec<ModelExchange>,
+}
+
+impl ProcessingResult {
+    /// Entry of a file that failed, with the error in place of its records
+    pub fn failed(file_path: &str, processing_type: &str, err: &AppError) -> Self {
+        Self {
+            records: Vec::new(),
+            source_file: file_path.to_string(),
+            processing_type: processing_type.to_string(),
+            stats: ProcessingStats { total_records: 0, total_tokens: 0, processing_time_ms: 0 },
+            error: Some(err.to_string()),
+            exchanges: Vec::new(),
+        }
+    }
+}
+
+/// Statistics collected during processing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessingStats {
+    pub total_records: usize,
+    pub total_tokens: usize,
+    pub processing_time_ms: u64,
+}
+
+/// Interface for processors
+pub trait Processor {
+    fn process_file(&self, file_path: &str, config: &ProcessorConfig) -> anyhow::Result<ProcessingResult>;
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+}
+
+/// Configuration for processors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessorConfig {
+    pub model: String,
+    pub provider: String,
+    pub language: String,
+    pub system_prompt: Option<String>,
+    pub keywords: Vec<String>,
+    pub add_reasoning: bool,
+    pub output_format: String,
+    /// Chunk sizes: every chunk of a text file becomes a record, PDF pages and document sections
+    /// longer than a chunk become several records. Without it text is split with the defaults
+    /// and pages and sections stay whole.
+    #[serde(default)]
+    pub chunking: Option<ChunkSettings>,
+    /// Noise filters applied to text before chunking
+    #[serde(default)]
+    pub cleanup: Option<CleanupSettings>,
+    /// Routing of low-confidence chunks of OCR/transcription input
+    #[serde(default)]
+    pub confidence: Option<ConfidenceSettings>,
+    /// Caption images with a vision model and put `[Figure N: caption]` in their place
+    #[serde(default)]
+    pub figures: Option<FigureSettings>,
+    /// Document properties added to every record
+    #[serde(default)]
+    pub metadata: MetadataSettings,
+    /// Generate several candidates per chunk and keep the best one
+    #[serde(default)]
+    pub best_of: Option<BestOfSettings>,
+    /// Sample every chunk several times and keep the majority answer; takes precedence over `best_of`
+    #[serde(default)]
+    pub voting: Option<VoteSettings>,
+}
+
+impl Default for ProcessorConfig {
+    fn default() -> Self {
+        Self {
+            model: String::new(),
+            provider: String::new(),
+            language: "en".to_string(),
+            system_prompt: None,
+            keywords: Vec::new(),
+            add_reasoning: false,
+            output_format: "json".to_string(),
+            chunking: None,
+            cleanup: None,
+            confidence: None,
+            figures: None,
+            metadata: MetadataSettings::default(),
+            best_of: None,
+            voting: None,
+        }
+    }
+}
+
+/// Settings the processor tests share: defaults with a named model and provider
+#[cfg(test)]
+pub(crate) fn test_config() -> ProcessorConfig {
+    ProcessorConfig {
+        model: "test-model".to_string(),
+        provider: "test-provider".to_string(),
+        ..ProcessorConfig::default()
+    }
+}
+
+/// Reads a text document, cleaned up and with its figures captioned when that is configured.
+/// The metadata holds the figure count and any captioning errors. Binary files are
+/// `UnsupportedFormat`.
+pub fn read_document(file_path: &str, config: &ProcessorConfig) -> Result<(String, HashMap<String, serde_json::Value>), AppError> {
+    let mut text = std::fs::read_to_string(file_path).map_err(|err| match err.kind() {
+        std::io::ErrorKind::InvalidData => processing_error!(
+            ProcessingErrorKind::UnsupportedFormat, file_path, "not a UTF-8 text file"
+        ),
+        _ => processing_error!(ProcessingErrorKind::ProcessorError, file_path, "failed to read ({})", err),
+    })?;
+    // Front-matter is harvested into the record metadata, not sent as content
+    if config.metadata.harvest {
+        if let Ok(Some((_, body))) = docmeta::split_front_matter(&text) {
+            text = body.to_string();
+        }
+    }
+    if let Some(settings) = &config.cleanup {
+        text = cleanup::clean(&text, settings).text;
+    }
+    let Some(settings) = &config.figures else {
+        return Ok((text, HashMap::new()));
+    };
+
+    let base_dir = std::path::Path::new(file_path).parent().unwrap_or(std::path::Path::new("."));
+    let captioned = figures::caption_text(&text, base_dir, settings);
+    let mut metadata = HashMap::new();
+    if captioned.figures > 0 {
+        metadata.insert("figures".to_string(), serde_json::Value::from(captioned.figures));
+    }
+    if !captioned.errors.is_empty() {
+        metadata.insert("figure_errors".to_string(), serde_json::Value::from(captioned.errors));
+    }
+    Ok((captioned.text, metadata))
+}
+
+/// Text a processor works on: a whole document, or one record of an earlier stage's output
+#[derive(Debug, Clone, Default)]
+pub struct Source {
+    pub text: String,
+    /// Document properties (figure count, captioning errors) for the records made from it
+    pub metadata: HashMap<String, serde_json::Value>,
+    /// Record the text came from; empty for a raw document
+    pub parents: Vec<String>,
+}
+
+/// Pages of a PDF or sections of a DOCX/ODT document, picked by the file extension;
+/// `None` for other files
+fn document_sources(file_path: &str) -> anyhow::Result<Option<Vec<Source>>> {
+    let path = std::path::Path::new(file_path);
+    if pdf::is_pdf(path) {
+        return pdf::sources(path).map(Some);
+    }
+    if OfficeFormat::of(path).is_some() {
+        return office::sources(path).map(Some);
+    }
+    Ok(None)
+}
+
+/// Inputs of a file, read the same way for every processor: a PDF page by page, a DOCX/ODT
+/// document section by section, the dataset of an earlier stage record by record (so every
+/// output can be linked to the record it was made from), any other file as a whole
+pub fn read_sources(file_path: &str, config: &ProcessorConfig) -> anyhow::Result<Vec<Source>> {
+    if let Some(sources) = document_sources(file_path)? {
+        return Ok(sources);
+    }
+    let Some(records) = lineage::source_records(std::path::Path::new(file_path)) else {
+        let (text, metadata) = read_document(file_path, config)?;
+        return Ok(vec![Source { text, metadata, parents: Vec::new() }]);
+    };
+    Ok(records.into_iter()
+        .map(|record| Source {
+            text: match &config.cleanup {
+                Some(settings) => cleanup::clean(&record.text, settings).text,
+                None => record.text,
+            },
+            metadata: HashMap::new(),
+            parents: vec![record.id],
+        })
+        .collect())
+}
+
+/// Mock model response to a chunk; like real models, it repeats some of the context.
+/// Later samples of the same prompt cover less of the text, as weaker candidates would.
+fn mock_completion(chunk: &chunking::Chunk, sample: usize) -> String {
+    if sample == 0 {
+        return [chunk.before.as_str(), chunk.text.as_str(), chunk.after.as_str()]
+            .iter().filter(|s| !s.is_empty()).cloned().collect::<Vec<_>>().join(" ");
+    }
+    let words: Vec<&str> = chunk.text.split_whitespace().collect();
+    words[..words.len() / (sample + 1)].join(" ")
+}
+
+/// Sends a text file chunk by chunk, each with its neighbors as context, and makes one record
+/// per chunk, numbered and pointing back to the file. The dataset of an earlier stage is chunked
+/// record by record, and every chunk record is linked to the record it came from.
+/// OCR/transcription chunks below the confidence threshold are cleaned up first, or kept without
+/// a model call and flagged for review.
+/// With best-of-N, every chunk gets N candidates and the selected one goes into the output;
+/// with voting, the majority of the samples does.
+/// Without `[chunking]` the default chunk size applies.
+fn process_chunked(file_path: &str, config: &ProcessorConfig, processing_type: &str, instruction: &str) -> anyhow::Result<ProcessingResult> {
+    let settings = config.chunking.clone().unwrap_or_default();
+    // Transcripts are used as recognized; document filters would shift the word confidences
+    let (sources, word_confidences) = match confidence::load(std::path::Path::new(file_path)).ok().flatten() {
+        Some(transcript) => {
+            let source = Source { text: transcript.text(), ..Default::default() };
+            (vec![source], Some(transcript.word_confidences()))
+        },
+        None => (read_sources(file_path, config)?, None),
+    };
+
+    let mut records = Vec::new();
+    let mut exchanges = Vec::new();
+    let mut tokens = 0;
+    for source in sources {
+        let chunks = chunking::split(&source.text, &settings);
+        let first = records.len();
+        for chunk in &chunks {
+            let mut chunk = chunk.clone();
+            let first_exchange = exchanges.len();
+            let mut metadata = source.metadata.clone();
+            metadata.insert("source_file".to_string(), serde_json::Value::from(file_path));
+            metadata.insert("chunk".to_string(), serde_json::Value::from(chunk.index));
+            metadata.insert("chunk_count".to_string(), serde_json::Value::from(chunks.len()));
+            let chunk_confidence = word_confidences.as_deref().and_then(|confidences| {
+                confidence::mean(confidences, chunk.start, chunk.text.split_whitespace().count())
+            });
+            match (&config.confidence, chunk_confidence) {
+                (Some(routing), Some(score)) if score < routing.threshold => {
+                    metadata.insert("confidence".to_string(), serde_json::Value::from(score));
+                    match routing.low_confidence {
+                        LowConfidence::Flag => {
+                            metadata.insert("needs_review".to_string(), serde_json::Value::from(true));
+                            records.push(Record {
+                                instruction: instruction.to_string(),
+                                prompt: chunk.text,
+                                completion: String::new(),
+                                metadata,
+                            });
+                            continue;
+                        },
+                        LowConfidence::Cleanup => {
+                            let prompt = confidence::cleanup_prompt(&chunk.text);
+                            // Mock model call; the recognized text comes back unchanged
+                            let corrected = chunk.text.clone();
+                            tokens += prompt.split_whitespace().count() + corrected.split_whitespace().count();
+                            let mut call = Record {
+                                instruction: "Correct recognition errors".to_string(),
+                                prompt,
+                                completion: corrected.clone(),
+                                metadata: HashMap::new(),
+                            };
+                            exchanges.push(ModelExchange::for_record(&mut call, file_path, config));
+                            metadata.insert("cleaned".to_string(), serde_json::Value::from(true));
+                            chunk.text = corrected;
+                        },
+                    }
+                },
+                _ => {},
+            }
+
+            let prompt = chunk.prompt(instruction);
+            let samples = match (&config.voting, &config.best_of) {
+                (Some(voting), _) => voting.samples.max(1),
+                (None, Some(best_of)) => best_of.n.max(1),
+                (None, None) => 1,
+            };
+            let mut candidates = Vec::new();
+            for sample in 0..samples {
+                let completion = if config.voting.is_some() {
+                    // Mock sampling: every third sample drifts from the others
+                    mock_completion(&chunk, usize::from(sample % 3 == 2))
+                } else {
+                    mock_completion(&chunk, sample)
+                };
+                tokens += prompt.split_whitespace().count() + completion.split_whitespace().count();
+                let mut call = Record {
+                    instruction: instruction.to_string(),
+                    prompt: prompt.clone(),
+                    completion: completion.clone(),
+                    metadata: HashMap::new(),
+                };
+                exchanges.push(ModelExchange::for_record(&mut call, file_path, config));
+                candidates.push(completion);
+            }
+            let completion = if config.voting.is_some() {
+                let vote = voting::vote(&candidates);
+                metadata.insert(voting::AGREEMENT.to_string(), serde_json::Value::from(vote.agreement));
+                metadata.insert(voting::VOTES.to_string(), serde_json::json!([
+                    { "chunk": chunk.index, "agreement": vote.agreement, "distinct": vote.distinct },
+                ]));
+                candidates.swap_remove(vote.index)
+            } else if let Some(best_of) = config.best_of.as_ref().filter(|_| candidates.len() > 1) {
+                let (best, scores) = bestof::select(&candidates, &prompt, &chunk.text, best_of.selection);
+                if best_of.selection == Selection::Judge {
+                    let judge_prompt = bestof::judge_prompt(&prompt, &candidates);
+                    let verdict: String = scores.iter().enumerate()
+                        .map(|(i, score)| format!("Candidate {}: {:.1}\n", i + 1, score))
+                        .collect();
+                    tokens += judge_prompt.split_whitespace().count() + verdict.split_whitespace().count();
+                    let mut call = Record {
+                        instruction: "Score the candidates".to_string(),
+                        prompt: judge_prompt,
+                        completion: verdict,
+                        metadata: HashMap::new(),
+                    };
+                    exchanges.push(ModelExchange::for_record(&mut call, file_path, config));
+                }
+                if best_of.keep_rejected {
+                    let rejected: Vec<serde_json::Value> = candidates.iter().enumerate()
+                        .filter(|(i, _)| *i != best)
+                        .map(|(i, candidate)| serde_json::json!({
+                            "chunk": chunk.index,
+                            "prompt": prompt,
+                            "chosen": candidates[best],
+                            "rejected": candidate,
+                            "score": scores[i],
+                        }))
+                        .collect();
+                    metadata.insert(bestof::REJECTED.to_string(), serde_json::Value::from(rejected));
+                }
+                candidates.swap_remove(best)
+            } else {
+                candidates.swap_remove(0)
+            };
+
+            if let Some(best_of) = &config.best_of {
+                metadata.insert("best_of".to_string(), serde_json::Value::from(best_of.to_string()));
+            }
+            let audit_ids: Vec<String> = exchanges[first_exchange..].iter().map(|e| e.id.clone()).collect();
+            metadata.insert(audit::RECORD_KEY.to_string(), serde_json::Value::from(audit_ids));
+            records.push(Record {
+                instruction: instruction.to_string(),
+                prompt: chunk.text,
+                completion,
+                metadata,
+            });
+        }
+        lineage::link(&mut records[first..], &source.parents);
+    }
+
+    Ok(ProcessingResult {
+        stats: ProcessingStats {
+            total_records: records.len(),
+            total_tokens: tokens,
+            processing_time_ms: 0,
+        },
+        records,
+        exchanges,
+        source_file: file_path.to_string(),
+        processing_type: processing_type.to_string(),
+        error: None,
+    })
+}
+
+/// Standard processor implementation
+pub struct StandardProcessor;
+
+impl Processor for StandardProcessor {
+    fn process_file(&self, file_path: &str, config: &ProcessorConfig) -> anyhow::Result<ProcessingResult> {
+        process_chunked(file_path, config, "standard", "Analyze the document")
+    }
+    
+    fn name(&self) -> &'static str {
+        "standard"
+    }
+    
+    fn description(&self) -> &'static str {
+        "Standard document processing"
+    }
+}
+
+/// Article processor implementation
+pub struct ArticleProcessor;
+
+impl Processor for ArticleProcessor {
+    fn process_file(&self, file_path: &str, config: &ProcessorConfig) -> anyhow::Result<ProcessingResult> {
+        process_chunked(file_path, config, "article", "Extract article content")
+    }
+    
+    fn name(&self) -> &'static str {
+        "article"
+    }
+    
+    fn description(&self) -> &'static str {
+        "Article extraction and processing"
+    }
+}
+
+/// Translation processor implementation
+pub struct TranslateProcessor;
+
+impl Processor for TranslateProcessor {
+    fn process_file(&self, file_path: &str, config: &ProcessorConfig) -> anyhow::Result<ProcessingResult> {
+        process_chunked(file_path, config, "translate", &format!("Translate to {}", config.language))
+    }
+    
+    fn name(&self) -> &'static str {
+        "translate"
+    }
+    
+    fn description(&self) -> &'static str {
+        "Document translation"
+    }
+}
+
+/// Batch processor implementation
+pub struct BatchProcessor;
+
+impl Processor for BatchProcessor {
+    fn process_file(&self, file_path: &str, config: &ProcessorConfig) -> anyhow::Result<ProcessingResult> {
+        process_chunked(file_path, config, "batch", "Process the document")
+    }
+    
+    fn name(&self) -> &'static str {
+        "batch"
+    }
+    
+    fn description(&self) -> &'static str {
+        "Batch processing of multiple documents"
+    }
+}
+
+/// Cost limit for one job
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpendCap {
+    pub max_usd: f64,
+    pub spent_usd: f64,
+}
+
+impl SpendCap {
+    pub fn new(max_usd: f64) -> Self {
+        Self { max_usd, spent_usd: 0.0 }
+    }
+
+    pub fn is_reached(&self) -> bool {
+        self.spent_usd >= self.max_usd
+    }
+
+    /// Allows spending another `extra_usd` on top of what has been spent so far
+    pub fn raise(&mut self, extra_usd: f64) {
+        self.max_usd = self.spent_usd.max(self.max_usd) + extra_usd;
+    }
+}
+
+/// Output of a local run; `pending` lists the files left when the spend cap stopped it
+#[derive(Debug, Clone, Default)]
+pub struct LocalRun {
+    pub results: Vec<ProcessingResult>,
+    pub pending: Vec<String>,
+}
+
+impl LocalRun {
+    pub fn is_paused(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub fn records(&self) -> usize {
+        self.results.iter().map(|r| r.records.len()).sum()
+    }
+
+    pub fn tokens(&self) -> usize {
+        self.results.iter().map(|r| r.stats.total_tokens).sum()
+    }
+
+    /// Results of the files that failed
+    pub fn failed(&self) -> Vec<&ProcessingResult> {
+        self.results.iter().filter(|r| r.error.is_some()).collect()
+    }
+}
+
+/// `err` as a processing error of `file_path`; errors of no known kind are `ProcessorError`s
+pub fn file_error(file_path: &str, err: anyhow::Error) -> AppError {
+    match err.downcast::<AppError>() {
+        Ok(AppError::Processing { file_id, kind, message }) => AppError::Processing {
+            file_id: file_id.or_else(|| Some(file_path.to_string())),
+            kind,
+            message,
+        },
+        Ok(other) => processing_error!(ProcessingErrorKind::ProcessorError, file_path, other),
+        Err(err) => processing_error!(ProcessingErrorKind::ProcessorError, file_path, format!("{:#}", err)),
+    }
+}
+
+/// Processes `files` one at a time, adding each file's cost to `cap`.
+/// Once the cap is reached the run stops before the next file, keeping what was done.
+/// A file that fails gets a result with its error, and the run goes on with the next one.
+pub fn run_with_cap(
+    processor: &dyn Processor,
+    files: &[String],
+    config: &ProcessorConfig,
+    usd_per_1k_tokens: f64,
+    cap: &mut SpendCap,
+) -> LocalRun {
+    let mut run = LocalRun::default();
+    for (i, file) in files.iter().enumerate() {
+        if cap.is_reached() {
+            run.pending = files[i..].to_vec();
+            break;
+        }
+        let mut result = match crashsafe::parse(file, || processor.process_file(file, config)) {
+            Ok(result) => result,
+            Err(err) => {
+                run.results.push(ProcessingResult::failed(file, processor.name(), &file_error(file, err)));
+                continue;
+            },
+        };
+        lineage::tag(&mut result.records, &result.processing_type);
+        docmeta::merge(&mut result.records, &docmeta::for_file(std::path::Path::new(file), &config.metadata));
+        cap.spent_usd += result.stats.total_tokens as f64 / 1000.0 * usd_per_1k_tokens;
+        run.results.push(result);
+    }
+    run
+}
+
+/// Structure-aware segmentation strategies, one record per segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SegmentMode {
+    /// Numbered clauses and sections of contracts and regulations
+    Legal,
+    /// Sections of scientific papers (plain text, Markdown or LaTeX); references are dropped
+    Paper,
+}
+
+impl SegmentMode {
+    pub fn processing_type(&self) -> &'static str {
+        match self {
+            SegmentMode::Legal => "legal",
+            SegmentMode::Paper => "paper",
+        }
+    }
+}
+
+/// Factory function to get the appropriate processor based on the processing type
+pub fn get_processor(processing_type: &str) -> anyhow::Result<Box<dyn Processor>> {
+    match processing_type {
+        "standard" => Ok(Box::new(StandardProcessor)),
+        "article" => Ok(Box::new(ArticleProcessor)),
+        "translate" => Ok(Box::new(TranslateProcessor)),
+        "batch" => Ok(Box::new(BatchProcessor)),
+        "summarize" => Ok(Box::new(MapReduceProcessor { settings: MapReduceSettings::default() })),
+        "legal" => Ok(Box::new(ClauseProcessor)),
+        "paper" => Ok(Box::new(PaperProcessor { settings: PaperSettings::default() })),
+        "qa" => Ok(Box::new(QaProcessor { settings: QaSettings::default() })),
+        "pdf" => Ok(Box::new(PdfProcessor)),
+        // Every processor reads PDFs and DOCX/ODT documents by page or section, see `read_sources`
+        "office" | "docx" | "odt" => Ok(Box::new(OfficeProcessor)),
+        _ => anyhow::bail!("Unknown processing type: {}", processing_type),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunking::ChunkSettings;
+    use crate::error::{AppError, ProcessingErrorKind};
+    use crate::processors::{file_error, run_with_cap, test_config, Processor, ProcessorConfig, SpendCap, StandardProcessor};
+
+    #[test]
+    fn test_run_stops_at_spend_cap() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = test_config();
+        let files: Vec<String> = (1..=5).map(|i| {
+            let path = dir.path().join(format!("part{}.txt", i));
+            std::fs::write(&path, "Each part is short enough for a single chunk.")?;
+            Ok(path.display().to_string())
+        }).collect::<std::io::Result<_>>()?;
+
+        // Cena dobrana tak, by każdy plik kosztował $0.10
+        let per_file = StandardProcessor.process_file(&files[0], &config)?.stats.total_tokens;
+        let price = 100.0 / per_file as f64;
+        let mut cap = SpendCap::new(0.25);
+        let run = run_with_cap(&StandardProcessor, &files, &config, price, &mut cap);
+        assert!(run.is_paused());
+        assert_eq!(run.results.len(), 3);
+        assert_eq!(run.pending, files[3..].to_vec());
+        assert_eq!(run.tokens(), 3 * per_file);
+
+        // Kontynuacja z kolejnym limitem przetwarza resztę
+        cap.raise(0.25);
+        let rest = run_with_cap(&StandardProcessor, &run.pending, &config, price, &mut cap);
+        assert!(!rest.is_paused());
+        assert_eq!(rest.results.len(), 2);
+        assert!((cap.spent_usd - 0.5).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_file_does_not_stop_the_batch() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let good = dir.path().join("good.txt");
+        std::fs::write(&good, "Plain text that is processed in one chunk.")?;
+        // Plik binarny i PDF, który nie jest PDF-em
+        let binary = dir.path().join("binary.txt");
+        std::fs::write(&binary, [0xff, 0xfe, 0x00, 0x9f])?;
+        let broken = dir.path().join("broken.pdf");
+        std::fs::write(&broken, "not a pdf")?;
+        let config = ProcessorConfig {
+            chunking: Some(ChunkSettings::default()),
+            ..test_config()
+        };
+        let files: Vec<String> = [&binary, &good, &broken].iter().map(|path| path.display().to_string()).collect();
+
+        let run = run_with_cap(&StandardProcessor, &files, &config, 0.0, &mut SpendCap::new(1.0));
+        assert!(!run.is_paused());
+        assert_eq!(run.results.len(), 3);
+        assert_eq!(run.records(), 1);
+        let failed = run.failed();
+        assert_eq!(failed.len(), 2);
+        for (result, file) in failed.iter().zip([&files[0], &files[2]]) {
+            assert_eq!(&result.source_file, file);
+            assert!(result.records.is_empty());
+            let error = result.error.as_deref().unwrap();
+            assert!(error.contains("Unsupported Format"), "{}", error);
+            assert!(error.contains(file.as_str()), "{}", error);
+        }
+
+        // Błąd trafia do wyników jako osobne pole; udane pliki go nie mają
+        let json = serde_json::to_value(&run.results)?;
+        assert!(json[0]["error"].is_string());
+        assert!(json[1].get("error").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_error_keeps_the_kind() {
+        let err = anyhow::Error::from(crate::processing_error!(ProcessingErrorKind::UnsupportedFormat, "no text layer"));
+        assert!(matches!(
+            file_error("scan.pdf", err),
+            AppError::Processing { kind: ProcessingErrorKind::UnsupportedFormat, file_id: Some(file), .. } if file == "scan.pdf"
+        ));
+        let err = anyhow::anyhow!("model refused").context("Chunk 2");
+        let AppError::Processing { kind, message, .. } = file_error("a.txt", err) else {
+            panic!("not a processing error");
+        };
+        assert!(matches!(kind, ProcessingErrorKind::ProcessorError));
+        assert_eq!(message, "Chunk 2: model refused");
+    }
+}
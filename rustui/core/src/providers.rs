@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::credentials;
+use crate::network;
+use crate::ollama;
+
+/// Providers built into the client; custom providers can't take these names
+pub const BUILT_IN: [&str; 3] = ["openai", "anthropic", ollama::PROVIDER];
+
+/// `provider_api` of jobs for custom providers: the backend talks to them as it does to OpenAI
+pub const OPENAI_COMPATIBLE: &str = "openai-compatible";
+
+/// `[providers.<name>]` in config.toml: an OpenAI-compatible server such as LM Studio or vLLM
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomProvider {
+    /// Base URL of the API including the version, e.g. `http://localhost:1234/v1`
+    pub base_url: String,
+    /// Key sent to the server, or `env:NAME` to read it from the environment. When unset,
+    /// the keyring and `<NAME>_API_KEY` are tried as for other providers.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Models offered before the server is asked, or when it can't list them
+    #[serde(default)]
+    pub models: Vec<String>,
+}
+
+impl CustomProvider {
+    /// Key for requests to the server, if any; local servers often need none
+    pub fn api_key(&self, name: &str) -> Option<String> {
+        match self.api_key.as_deref() {
+            Some(value) => match value.strip_prefix("env:") {
+                Some(var) => std::env::var(var.trim()).ok(),
+                None => Some(value.to_string()),
+            },
+            None => credentials::get(name).map(|(key, _)| key),
+        }
+        .filter(|key| !key.trim().is_empty())
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), path)
+    }
+}
+
+/// Model ids from an OpenAI-style `/models` response, sorted
+pub fn parse_models(response: &Value) -> Result<Vec<String>> {
+    let models = response.get("data")
+        .and_then(Value::as_array)
+        .context("Model list has no `data` array")?;
+    let mut ids: Vec<String> = models.iter()
+        .filter_map(|model| model.get("id").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect();
+    ids.sort();
+    ids.dedup();
+    Ok(ids)
+}
+
+/// Models the server has loaded
+pub async fn list_models(name: &str, provider: &CustomProvider) -> Result<Vec<String>> {
+    let mut request = network::client().get(provider.url("models")).timeout(Duration::from_secs(5));
+    if let Some(key) = provider.api_key(name) {
+        request = request.bearer_auth(key);
+    }
+    let response = request.send().await
+        .with_context(|| format!("{} is not reachable at {}", name, provider.base_url))?;
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("{} answered {}", name, status);
+    }
+    let body: Value = response.json().await.with_context(|| format!("Failed to parse the model list of {}", name))?;
+    parse_models(&body)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::providers::{self, CustomProvider};
+    use serde_json::json;
+
+    fn lmstudio() -> CustomProvider {
+        CustomProvider {
+            base_url: "http://localhost:1234/v1/".to_string(),
+            api_key: Some("lm-studio".to_string()),
+            models: vec!["qwen2.5-7b-instruct".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_parse_models_and_keys() {
+        let response = json!({ "object": "list", "data": [{ "id": "mistral-7b" }, { "id": "llama-3-8b" }, { "object": "model" }] });
+        assert_eq!(providers::parse_models(&response).unwrap(), vec!["llama-3-8b", "mistral-7b"]);
+        assert!(providers::parse_models(&json!({ "models": [] })).is_err());
+
+        let mut provider = lmstudio();
+        assert_eq!(provider.url("models"), "http://localhost:1234/v1/models");
+        assert_eq!(provider.api_key("lmstudio").as_deref(), Some("lm-studio"));
+        std::env::set_var("ANYDATASET_TEST_VLLM_KEY", "vllm-secret");
+        provider.api_key = Some("env:ANYDATASET_TEST_VLLM_KEY".to_string());
+        assert_eq!(provider.api_key("vllm").as_deref(), Some("vllm-secret"));
+        // Pusty klucz oznacza serwer bez uwierzytelniania
+        provider.api_key = Some(" ".to_string());
+        assert_eq!(provider.api_key("vllm"), None);
+    }
+}
@@ -0,0 +1,254 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use crate::background;
+use crate::log_debug;
+use crate::templating::{render_path, TemplateContext};
+use crate::webhooks::WebhookEvent;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Message broker receiving the same events as webhooks, from the `[event_broker]` config section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum BrokerSettings {
+    Mqtt {
+        host: String,
+        #[serde(default = "default_mqtt_port")]
+        port: u16,
+        /// Topic template; `{event}` and `{host}` are substituted
+        #[serde(default = "default_mqtt_topic")]
+        topic: String,
+        #[serde(default)]
+        client_id: Option<String>,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    Redis {
+        /// `redis://[:password@]host[:port]`
+        url: String,
+        /// Channel template; `{event}` and `{host}` are substituted
+        #[serde(default = "default_redis_channel")]
+        channel: String,
+    },
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_mqtt_topic() -> String {
+    "anydataset/{host}/{event}".to_string()
+}
+
+fn default_redis_channel() -> String {
+    "anydataset:events".to_string()
+}
+
+impl BrokerSettings {
+    pub fn label(&self) -> String {
+        match self {
+            BrokerSettings::Mqtt { host, port, .. } => format!("mqtt://{}:{}", host, port),
+            BrokerSettings::Redis { url, .. } => url.clone(),
+        }
+    }
+}
+
+/// Publishes the event as JSON, opening a short-lived connection
+pub async fn publish(settings: &BrokerSettings, event: &WebhookEvent) -> Result<()> {
+    let payload = serde_json::to_vec(event).context("Failed to serialize event")?;
+    let mut ctx = TemplateContext::default();
+    ctx.set("event", event.event.as_str());
+    ctx.set("host", hostname());
+
+    let publish = async {
+        match settings {
+            BrokerSettings::Mqtt { host, port, topic, client_id, username, password } => {
+                let client_id = client_id.clone()
+                    .unwrap_or_else(|| format!("anydataset-tui-{}", std::process::id()));
+                let credentials = username.as_deref().map(|u| (u, password.as_deref()));
+                mqtt_publish(host, *port, &client_id, credentials, &render_path(topic, &ctx), &payload).await
+            },
+            BrokerSettings::Redis { url, channel } => redis_publish(url, &render_path(channel, &ctx), &payload).await,
+        }
+    };
+    tokio::time::timeout(TIMEOUT, publish).await
+        .context("Broker did not respond in time")?
+}
+
+/// Fire-and-forget publishing off the UI thread
+pub fn dispatch(settings: Option<&BrokerSettings>, event: &WebhookEvent) {
+    let Some(settings) = settings.cloned() else {
+        return;
+    };
+    let event = event.clone();
+    background::spawn("Event publishing", move |runtime| {
+        if let Err(err) = runtime.block_on(publish(&settings, &event)) {
+            log_debug!("Failed to publish {} to {}: {:#}", event.event.as_str(), settings.label(), err);
+        }
+    });
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+async fn redis_publish(url: &str, channel: &str, payload: &[u8]) -> Result<()> {
+    let url = reqwest::Url::parse(url).context("Invalid Redis URL")?;
+    let host = url.host_str().context("Redis URL has no host")?;
+    let stream = TcpStream::connect((host, url.port().unwrap_or(6379))).await
+        .with_context(|| format!("Cannot connect to Redis at {}", host))?;
+    let mut stream = BufReader::new(stream);
+
+    if let Some(password) = url.password() {
+        let mut auth: Vec<&[u8]> = vec![b"AUTH"];
+        if !url.username().is_empty() {
+            auth.push(url.username().as_bytes());
+        }
+        auth.push(password.as_bytes());
+        redis_command(&mut stream, &auth).await.context("Redis authentication failed")?;
+    }
+    redis_command(&mut stream, &[b"PUBLISH", channel.as_bytes(), payload]).await
+}
+
+/// Sends a RESP command and checks the reply isn't an error
+async fn redis_command(stream: &mut BufReader<TcpStream>, args: &[&[u8]]) -> Result<()> {
+    stream.get_mut().write_all(&encode_resp(args)).await?;
+    let mut reply = String::new();
+    stream.read_line(&mut reply).await?;
+    match reply.chars().next() {
+        Some('-') => anyhow::bail!("{}", reply[1..].trim()),
+        Some(_) => Ok(()),
+        None => anyhow::bail!("Connection closed by Redis"),
+    }
+}
+
+pub fn encode_resp(args: &[&[u8]]) -> Vec<u8> {
+    let mut out = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        out.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        out.extend_from_slice(arg);
+        out.extend_from_slice(b"\r\n");
+    }
+    out
+}
+
+/// MQTT 3.1.1: CONNECT, PUBLISH with QoS 0, DISCONNECT
+async fn mqtt_publish(
+    host: &str,
+    port: u16,
+    client_id: &str,
+    credentials: Option<(&str, Option<&str>)>,
+    topic: &str,
+    payload: &[u8],
+) -> Result<()> {
+    let mut stream = TcpStream::connect((host, port)).await
+        .with_context(|| format!("Cannot connect to MQTT broker at {}:{}", host, port))?;
+
+    stream.write_all(&mqtt_connect_packet(client_id, credentials)).await?;
+    let mut connack = [0u8; 4];
+    stream.read_exact(&mut connack).await.context("No CONNACK from broker")?;
+    if connack[0] != 0x20 {
+        anyhow::bail!("Unexpected packet from broker: {:#04x}", connack[0]);
+    }
+    if connack[3] != 0 {
+        anyhow::bail!("Broker refused connection (return code {})", connack[3]);
+    }
+
+    stream.write_all(&mqtt_publish_packet(topic, payload)).await?;
+    stream.write_all(&[0xE0, 0x00]).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+pub fn mqtt_connect_packet(client_id: &str, credentials: Option<(&str, Option<&str>)>) -> Vec<u8> {
+    let mut body = Vec::new();
+    put_mqtt_string(&mut body, b"MQTT");
+    body.push(4); // protocol level 3.1.1
+
+    let mut flags = 0x02; // clean session
+    if let Some((_, password)) = credentials {
+        flags |= 0x80;
+        if password.is_some() {
+            flags |= 0x40;
+        }
+    }
+    body.push(flags);
+    body.extend_from_slice(&30u16.to_be_bytes()); // keep-alive seconds
+
+    put_mqtt_string(&mut body, client_id.as_bytes());
+    if let Some((username, password)) = credentials {
+        put_mqtt_string(&mut body, username.as_bytes());
+        if let Some(password) = password {
+            put_mqtt_string(&mut body, password.as_bytes());
+        }
+    }
+    mqtt_packet(0x10, body)
+}
+
+pub fn mqtt_publish_packet(topic: &str, payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    put_mqtt_string(&mut body, topic.as_bytes());
+    body.extend_from_slice(payload);
+    mqtt_packet(0x30, body)
+}
+
+fn mqtt_packet(header: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut packet = vec![header];
+    packet.extend(mqtt_remaining_length(body.len()));
+    packet.extend(body);
+    packet
+}
+
+/// Variable-length integer: 7 bits per byte, high bit set while more bytes follow
+pub fn mqtt_remaining_length(mut len: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if len == 0 {
+            return out;
+        }
+    }
+}
+
+fn put_mqtt_string(buf: &mut Vec<u8>, value: &[u8]) {
+    buf.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    buf.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pubsub::{encode_resp, mqtt_publish_packet, mqtt_remaining_length, BrokerSettings};
+
+    #[test]
+    fn test_mqtt_encoding() {
+        assert_eq!(mqtt_remaining_length(0), vec![0x00]);
+        assert_eq!(mqtt_remaining_length(127), vec![0x7F]);
+        assert_eq!(mqtt_remaining_length(321), vec![0xC1, 0x02]);
+
+        // Nagłówek, długość, temat z prefiksem długości, payload
+        assert_eq!(mqtt_publish_packet("a/b", b"{}"), vec![0x30, 7, 0, 3, b'a', b'/', b'b', b'{', b'}']);
+    }
+
+    #[test]
+    fn test_redis_encoding_and_config() -> anyhow::Result<()> {
+        assert_eq!(encode_resp(&[b"PUBLISH", b"ch", b"x"]), b"*3\r\n$7\r\nPUBLISH\r\n$2\r\nch\r\n$1\r\nx\r\n".to_vec());
+
+        let broker: BrokerSettings = toml::from_str("kind = \"mqtt\"\nhost = \"broker.local\"")?;
+        assert_eq!(broker.label(), "mqtt://broker.local:1883");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+use serde::{Deserialize, Serialize};
+
+use crate::audit::ModelExchange;
+use crate::chunking;
+use crate::labeling::{self, LabelSettings};
+use crate::lineage;
+use crate::processors::{self, ProcessingResult, ProcessingStats, Processor, ProcessorConfig, Record};
+
+/// Record metadata: source span the model quoted in support of its answer
+pub const QUOTE: &str = "support_quote";
+/// Record metadata: whether the quote was found in the chunk; `false` only on flagged records
+pub const GROUNDED: &str = "grounded";
+/// Record metadata: index of the chunk the question was generated from
+pub const CHUNK: &str = "chunk";
+
+/// Quotes shorter than this (in words) are too generic to show the answer comes from the text
+const MIN_QUOTE_WORDS: usize = 3;
+
+/// Question-answer generation from the `[qa]` config section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QaSettings {
+    #[serde(default = "default_questions_per_chunk")]
+    pub questions_per_chunk: usize,
+    /// What happens to questions whose quoted support isn't in the chunk
+    #[serde(default)]
+    pub unsupported: Unsupported,
+    /// Second pass labeling every question with difficulty and category
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<LabelSettings>,
+}
+
+fn default_questions_per_chunk() -> usize {
+    3
+}
+
+impl Default for QaSettings {
+    fn default() -> Self {
+        Self { questions_per_chunk: default_questions_per_chunk(), unsupported: Unsupported::default(), labels: None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Unsupported {
+    /// Leave the question out of the dataset
+    #[default]
+    Drop,
+    /// Keep the question with `grounded: false` in its metadata
+    Flag,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QaPair {
+    pub question: String,
+    pub answer: String,
+    pub quote: String,
+}
+
+/// Asks for `count` questions answerable from the text, each with a verbatim supporting quote
+pub fn instruction(count: usize) -> String {
+    format!(
+        "Write {} questions that can be answered from the text below. For each, give the answer and \
+         quote the sentence(s) of the text that support it, copied word for word. Skip questions the \
+         text doesn't answer. Use this format:\n\
+         Question: ...\nAnswer: ...\nQuote: \"...\"",
+        count
+    )
+}
+
+/// Pairs in the `Question:`/`Answer:`/`Quote:` format; lines without a label continue the previous field.
+/// Pairs without a question or an answer are skipped.
+pub fn parse_pairs(output: &str) -> Vec<QaPair> {
+    let mut pairs = Vec::new();
+    let mut current: Option<QaPair> = None;
+    let mut field: Option<usize> = None;
+    for line in output.lines() {
+        let line = line.trim();
+        let labeled = [("Question:", 0), ("Q:", 0), ("Answer:", 1), ("A:", 1), ("Quote:", 2), ("Support:", 2)]
+            .into_iter()
+            .find_map(|(label, i)| line.strip_prefix(label).map(|rest| (i, rest.trim())));
+        let (i, text) = match labeled {
+            Some((0, text)) => {
+                pairs.extend(current.take());
+                current = Some(QaPair { question: String::new(), answer: String::new(), quote: String::new() });
+                (0, text)
+            },
+            Some((i, text)) => (i, text),
+            None => match field {
+                Some(i) if !line.is_empty() => (i, line),
+                _ => continue,
+            },
+        };
+        let Some(pair) = current.as_mut() else {
+            continue;
+        };
+        field = Some(i);
+        let target = match i {
+            0 => &mut pair.question,
+            1 => &mut pair.answer,
+            _ => &mut pair.quote,
+        };
+        if !target.is_empty() {
+            target.push(' ');
+        }
+        target.push_str(text);
+    }
+    pairs.extend(current);
+    pairs.into_iter()
+        .filter(|pair| !pair.question.is_empty() && !pair.answer.is_empty())
+        .map(|mut pair| {
+            pair.quote = pair.quote.trim_matches(|c: char| matches!(c, '"' | '“' | '”' | '„' | '\'')).trim().to_string();
+            pair
+        })
+        .collect()
+}
+
+/// Whether `quote` appears in `text`, ignoring case, spacing and typographic quotes and dashes.
+/// Parts separated by an ellipsis must appear in order.
+pub fn quote_in(quote: &str, text: &str) -> bool {
+    let quote = normalize(quote);
+    let text = normalize(text);
+    if quote.split_whitespace().count() < MIN_QUOTE_WORDS {
+        return false;
+    }
+    let mut rest = text.as_str();
+    for part in quote.split("...").map(str::trim).filter(|p| !p.is_empty()) {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn normalize(text: &str) -> String {
+    let text: String = text.chars()
+        .map(|c| match c {
+            '“' | '”' | '„' => '"',
+            '‘' | '’' => '\'',
+            '–' | '—' => '-',
+            _ => c,
+        })
+        .collect();
+    text.replace('…', "...").to_lowercase().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Pairs to keep with whether their quote is in `text`, and the number dropped
+pub fn verify(pairs: Vec<QaPair>, text: &str, unsupported: Unsupported) -> (Vec<(QaPair, bool)>, usize) {
+    let mut kept = Vec::new();
+    let mut dropped = 0;
+    for pair in pairs {
+        let grounded = quote_in(&pair.quote, text);
+        if !grounded && unsupported == Unsupported::Drop {
+            dropped += 1;
+        } else {
+            kept.push((pair, grounded));
+        }
+    }
+    (kept, dropped)
+}
+
+/// Questions and answers per chunk, each checked against the span it quotes
+pub struct QaProcessor {
+    pub settings: QaSettings,
+}
+
+impl QaProcessor {
+    /// Like `process_file`, also returning the number of dropped questions
+    pub fn generate(&self, file_path: &str, config: &ProcessorConfig) -> anyhow::Result<(ProcessingResult, usize)> {
+        let sources = processors::read_sources(file_path, config)?;
+        let chunk_settings = config.chunking.clone().unwrap_or_default();
+        let instruction = instruction(self.settings.questions_per_chunk);
+
+        let mut records = Vec::new();
+        let mut dropped = 0;
+        for source in sources {
+            let first = records.len();
+            for chunk in chunking::split(&source.text, &chunk_settings) {
+                let output = mock_output(&chunk.prompt(&instruction), self.settings.questions_per_chunk);
+                // Only the chunk counts as support, not the context around it
+                let (pairs, chunk_dropped) = verify(parse_pairs(&output), &chunk.text, self.settings.unsupported);
+                dropped += chunk_dropped;
+                for (pair, grounded) in pairs {
+                    let mut metadata = source.metadata.clone();
+                    metadata.insert(QUOTE.to_string(), pair.quote.into());
+                    metadata.insert(CHUNK.to_string(), chunk.index.into());
+                    if !grounded {
+                        metadata.insert(GROUNDED.to_string(), false.into());
+                    }
+                    records.push(Record {
+                        instruction: "Answer the question using the text".to_string(),
+                        prompt: format!("{}\n\nText:\n{}", pair.question, chunk.text),
+                        completion: pair.answer,
+                        metadata,
+                    });
+                }
+            }
+            lineage::link(&mut records[first..], &source.parents);
+        }
+        if let Some(settings) = &self.settings.labels {
+            labeling::label(&mut records, settings);
+        }
+
+        let total_tokens = records.iter()
+            .map(|r| r.prompt.split_whitespace().count() + r.completion.split_whitespace().count())
+            .sum();
+        let exchanges = records.iter_mut()
+            .map(|record| ModelExchange::for_record(record, file_path, config))
+            .collect();
+        let result = ProcessingResult {
+            stats: ProcessingStats {
+                total_records: records.len(),
+                total_tokens,
+                processing_time_ms: 0,
+            },
+            records,
+            exchanges,
+            source_file: file_path.to_string(),
+            processing_type: "qa".to_string(),
+            error: None,
+        };
+        Ok((result, dropped))
+    }
+}
+
+impl Processor for QaProcessor {
+    fn process_file(&self, file_path: &str, config: &ProcessorConfig) -> anyhow::Result<ProcessingResult> {
+        self.generate(file_path, config).map(|(result, _)| result)
+    }
+
+    fn name(&self) -> &'static str {
+        "qa"
+    }
+
+    fn description(&self) -> &'static str {
+        "Question-answer generation with quoted, verified support"
+    }
+}
+
+/// Mock model call: one question per sentence of the text part of the prompt, quoting the sentence
+fn mock_output(prompt: &str, count: usize) -> String {
+    let text = prompt.split("Text:\n").nth(1).unwrap_or_default();
+    let text = text.split("\n\nFollowing context").next().unwrap_or_default();
+    text.split_inclusive(['.', '?', '!'])
+        .map(str::trim)
+        .filter(|sentence| sentence.split_whitespace().count() >= MIN_QUOTE_WORDS)
+        .take(count)
+        .map(|sentence| {
+            let topic: Vec<&str> = sentence.split_whitespace().take(4).collect();
+            format!("Question: What does the text say about \"{}\"?\nAnswer: {}\nQuote: \"{}\"\n", topic.join(" "), sentence, sentence)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processors::test_config;
+    use crate::qa::{parse_pairs, quote_in, verify, QaProcessor, QaSettings, Unsupported, GROUNDED, QUOTE};
+
+    #[test]
+    fn test_parse_pairs() {
+        let output = "Question: When was the clinic opened?\nAnswer: In 1998.\nQuote: \"The clinic opened in 1998\"\n\n\
+                          Q: Who runs it?\nA: Dr. Nowak,\n   a surgeon.\nQuote: “Dr. Nowak runs the clinic”\n\
+                          Question: Without an answer?\n";
+        let pairs = parse_pairs(output);
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].quote, "The clinic opened in 1998");
+        // Wiersze bez etykiety kontynuują poprzednie pole
+        assert_eq!(pairs[1].answer, "Dr. Nowak, a surgeon.");
+        assert_eq!(pairs[1].quote, "Dr. Nowak runs the clinic");
+    }
+
+    #[test]
+    fn test_quote_in_text() {
+        let text = "The clinic opened in 1998 — two years after the merger.\nDr. Nowak runs   the clinic.";
+        assert!(quote_in("the clinic opened in 1998 - two years", text));
+        assert!(quote_in("The clinic opened ... Dr. Nowak runs the clinic", text));
+        assert!(!quote_in("Dr. Nowak runs ... the clinic opened in 1998", text));
+        assert!(!quote_in("The clinic opened in 2001", text));
+        // Zbyt krótki cytat niczego nie potwierdza
+        assert!(!quote_in("the clinic", text));
+    }
+
+    #[test]
+    fn test_verify_drops_or_flags_unsupported() {
+        let text = "The clinic opened in 1998. It treats horses and cattle.";
+        let pairs = parse_pairs("Question: When?\nAnswer: 1998.\nQuote: The clinic opened in 1998\n\
+                                     Question: What animals?\nAnswer: Dogs.\nQuote: It treats dogs and cats\n\
+                                     Question: Who?\nAnswer: Nobody.\n");
+
+        let (kept, dropped) = verify(pairs.clone(), text, Unsupported::Drop);
+        assert_eq!(dropped, 2);
+        assert_eq!(kept, vec![(pairs[0].clone(), true)]);
+
+        // Pytanie bez cytatu też nie ma potwierdzenia
+        let (kept, dropped) = verify(pairs, text, Unsupported::Flag);
+        assert_eq!(dropped, 0);
+        assert_eq!(kept.iter().map(|(_, grounded)| *grounded).collect::<Vec<_>>(), vec![true, false, false]);
+    }
+
+    #[test]
+    fn test_qa_processor_records() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "The clinic opened in 1998. It treats horses and cattle. Ok.")?;
+        let config = test_config();
+
+        let processor = QaProcessor { settings: QaSettings::default() };
+        let (result, dropped) = processor.generate(&file.to_string_lossy(), &config)?;
+        assert_eq!((result.records.len(), dropped), (2, 0));
+        assert_eq!(result.processing_type, "qa");
+        let record = &result.records[0];
+        assert_eq!(record.metadata[QUOTE], "The clinic opened in 1998.");
+        assert!(record.prompt.starts_with("What does the text say about"));
+        assert!(record.prompt.ends_with("Text:\nThe clinic opened in 1998. It treats horses and cattle. Ok."));
+        assert!(!record.metadata.contains_key(GROUNDED));
+        Ok(())
+    }
+}
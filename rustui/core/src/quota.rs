@@ -0,0 +1,202 @@
+use chrono::{DateTime, Datelike, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::history::HistoryEntry;
+use crate::report::{JobReportRow, UsageTotals};
+
+/// Hard limits for one provider from the `[quotas.<provider>]` config section.
+/// Days and months follow local time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderQuota {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_cost_usd: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly_cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    Month,
+}
+
+impl Period {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Period::Day => "daily",
+            Period::Month => "monthly",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Tokens,
+    Cost,
+}
+
+/// A limit that has been reached; returned as an error by the API client
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuotaExceeded {
+    pub provider: String,
+    pub period: Period,
+    pub metric: Metric,
+    pub used: f64,
+    pub limit: f64,
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (used, limit) = match self.metric {
+            Metric::Tokens => (format!("{:.0} tokens", self.used), format!("{:.0}", self.limit)),
+            Metric::Cost => (format!("${:.2}", self.used), format!("${:.2}", self.limit)),
+        };
+        write!(f, "{} {} quota reached: {} used of {}", self.provider, self.period.label(), used, limit)
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+/// Quotas together with the usage they are checked against, taken from the job history
+#[derive(Debug, Clone, Default)]
+pub struct QuotaGuard {
+    quotas: BTreeMap<String, ProviderQuota>,
+    /// Per provider: usage today and this month
+    usage: BTreeMap<String, (UsageTotals, UsageTotals)>,
+}
+
+impl QuotaGuard {
+    pub fn new(quotas: &BTreeMap<String, ProviderQuota>, entries: &[HistoryEntry], now: DateTime<Local>) -> Self {
+        let today = now.date_naive();
+        let mut usage: BTreeMap<String, (UsageTotals, UsageTotals)> = BTreeMap::new();
+        for entry in entries.iter().filter(|e| quotas.contains_key(&e.provider)) {
+            let submitted = entry.submitted_at.with_timezone(&Local).date_naive();
+            if submitted.year() != today.year() || submitted.month() != today.month() {
+                continue;
+            }
+            let row = JobReportRow::from(entry);
+            let (daily, monthly) = usage.entry(entry.provider.clone()).or_default();
+            monthly.add(&row);
+            if submitted == today {
+                daily.add(&row);
+            }
+        }
+        Self { quotas: quotas.clone(), usage }
+    }
+
+    /// Fails with the first limit the provider has reached
+    pub fn check(&self, provider: &str) -> Result<(), QuotaExceeded> {
+        let Some(quota) = self.quotas.get(provider) else {
+            return Ok(());
+        };
+        let (daily, monthly) = self.usage.get(provider).cloned().unwrap_or_default();
+        let limits = [
+            (Period::Day, Metric::Tokens, quota.daily_tokens.map(|t| t as f64), daily.tokens as f64),
+            (Period::Day, Metric::Cost, quota.daily_cost_usd, daily.cost_usd),
+            (Period::Month, Metric::Tokens, quota.monthly_tokens.map(|t| t as f64), monthly.tokens as f64),
+            (Period::Month, Metric::Cost, quota.monthly_cost_usd, monthly.cost_usd),
+        ];
+        for (period, metric, limit, used) in limits {
+            if let Some(limit) = limit {
+                if used >= limit {
+                    return Err(QuotaExceeded { provider: provider.to_string(), period, metric, used, limit });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{ApiClient, ProcessingConfig};
+    use crate::history::HistoryEntry;
+    use crate::quota::{Metric, Period, ProviderQuota, QuotaExceeded, QuotaGuard};
+    use chrono::{Local, TimeZone};
+    use std::collections::BTreeMap;
+
+    fn entry(provider: &str, day: u32, tokens: u64, cost: f64) -> HistoryEntry {
+        HistoryEntry {
+            job_id: format!("job-{}", day),
+            file: "a.txt".to_string(),
+            processing_type: "standard".to_string(),
+            provider: provider.to_string(),
+            model: "model".to_string(),
+            status: "completed".to_string(),
+            submitted_at: Local.with_ymd_and_hms(2025, 3, day, 12, 0, 0).unwrap().with_timezone(&chrono::Utc),
+            finished_at: None,
+            records: None,
+            tokens: Some(tokens),
+            cost_usd: Some(cost),
+            variables: Default::default(),
+        }
+    }
+
+    fn quotas() -> BTreeMap<String, ProviderQuota> {
+        let mut quotas = BTreeMap::new();
+        quotas.insert("openai".to_string(), ProviderQuota {
+            daily_cost_usd: Some(5.0),
+            monthly_tokens: Some(10_000),
+            ..Default::default()
+        });
+        quotas
+    }
+
+    #[test]
+    fn test_quota_periods() {
+        let now = Local.with_ymd_and_hms(2025, 3, 15, 18, 0, 0).unwrap();
+        // Wczorajsze koszty nie liczą się do limitu dziennego
+        let entries = vec![entry("openai", 14, 1000, 9.0), entry("openai", 15, 1000, 2.0), entry("anthropic", 15, 50_000, 99.0)];
+        let guard = QuotaGuard::new(&quotas(), &entries, now);
+        assert!(guard.check("openai").is_ok());
+        assert!(guard.check("anthropic").is_ok());
+
+        let entries = vec![entry("openai", 1, 9_000, 0.0), entry("openai", 15, 1_000, 0.0)];
+        let exceeded = QuotaGuard::new(&quotas(), &entries, now).check("openai").unwrap_err();
+        assert_eq!((exceeded.period, exceeded.metric), (Period::Month, Metric::Tokens));
+        assert_eq!(exceeded.to_string(), "openai monthly quota reached: 10000 tokens used of 10000");
+
+        let entries = vec![entry("openai", 15, 10, 5.5)];
+        let exceeded = QuotaGuard::new(&quotas(), &entries, now).check("openai").unwrap_err();
+        assert_eq!((exceeded.period, exceeded.metric), (Period::Day, Metric::Cost));
+    }
+
+    #[test]
+    fn test_api_client_refuses_over_quota() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/api/process").expect(0).create();
+
+        let now = Local::now();
+        let mut spent = entry("openai", 1, 0, 6.0);
+        spent.submitted_at = now.with_timezone(&chrono::Utc);
+        let client = ApiClient::new(&server.url())
+            .with_quota_guard(QuotaGuard::new(&quotas(), &[spent], now));
+        let config = ProcessingConfig {
+            provider: "openai".to_string(),
+            model: "gpt-4o".to_string(),
+            system_prompt: None,
+            keywords: None,
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            language: None,
+            processing_type: "standard".to_string(),
+            add_reasoning: None,
+            output_format: None,
+            provider_base_url: None,
+            provider_api: None,
+        };
+
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        let err = runtime.block_on(client.process_file("file-1", config)).unwrap_err();
+        assert!(err.downcast_ref::<QuotaExceeded>().is_some());
+        mock.assert();
+        Ok(())
+    }
+}
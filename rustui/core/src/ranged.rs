@@ -0,0 +1,471 @@
+use anyhow::{Context, Result};
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::io::SeekFrom;
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+use crate::network::{Cancelled, HttpClient, Transfer};
+use crate::resume::PartFile;
+
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// How result files are split into ranged requests fetched side by side
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadSettings {
+    /// Parts fetched at once; 1 downloads the file in a single request
+    #[serde(default = "default_parallel_chunks")]
+    pub parallel_chunks: usize,
+    #[serde(default = "default_chunk_size_mb")]
+    pub chunk_size_mb: u64,
+    /// Extra attempts for a part that failed, before the whole download fails
+    #[serde(default = "default_chunk_retries")]
+    pub chunk_retries: u32,
+}
+
+fn default_parallel_chunks() -> usize {
+    4
+}
+
+fn default_chunk_size_mb() -> u64 {
+    16
+}
+
+fn default_chunk_retries() -> u32 {
+    3
+}
+
+impl Default for DownloadSettings {
+    fn default() -> Self {
+        Self {
+            parallel_chunks: default_parallel_chunks(),
+            chunk_size_mb: default_chunk_size_mb(),
+            chunk_retries: default_chunk_retries(),
+        }
+    }
+}
+
+impl DownloadSettings {
+    pub fn ranged(&self) -> bool {
+        self.parallel_chunks > 1
+    }
+
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size_mb.max(1) * 1024 * 1024
+    }
+}
+
+/// Inclusive byte ranges covering `start..total`, `chunk_size` bytes each but the last
+pub fn ranges(start: u64, total: u64, chunk_size: u64) -> Vec<(u64, u64)> {
+    let chunk_size = chunk_size.max(1);
+    (start..total)
+        .step_by(chunk_size as usize)
+        .map(|from| (from, (from + chunk_size).min(total) - 1))
+        .collect()
+}
+
+/// First byte, last byte and file size from a header like `bytes 0-1023/4096`
+pub fn parse_content_range(value: &str) -> Option<(u64, u64, u64)> {
+    let (range, total) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let (first, last) = range.split_once('-')?;
+    let (first, last, total) = (first.parse().ok()?, last.parse().ok()?, total.parse().ok()?);
+    (first <= last && last < total).then_some((first, last, total))
+}
+
+/// Header value asking for the first part of a file
+pub fn first_range(settings: &DownloadSettings) -> String {
+    format!("bytes=0-{}", settings.chunk_size() - 1)
+}
+
+/// Strong ETag or Last-Modified of a response, sent back as `If-Range` when resuming
+pub fn validator(response: &reqwest::Response) -> Option<String> {
+    let header = |name| response.headers().get(name).and_then(|value| value.to_str().ok()).map(str::to_string);
+    // If-Range only takes strong ETags
+    header(reqwest::header::ETAG).filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(reqwest::header::LAST_MODIFIED))
+}
+
+/// Finishes a download whose first part came back as `206 Partial Content`: the rest is
+/// fetched in parallel ranged requests, written in place. Parts an interrupted attempt
+/// already got are skipped.
+pub async fn download_rest(
+    client: &HttpClient,
+    url: &str,
+    first: reqwest::Response,
+    settings: &DownloadSettings,
+    part: &mut PartFile,
+    transfer: &Transfer,
+) -> Result<()> {
+    let (start, last, total) = content_range(&first)?;
+    if start != 0 {
+        anyhow::bail!("Backend answered the first part with bytes {}-{}", start, last);
+    }
+    let path = part.path().to_path_buf();
+    let (rest, skipped): (Vec<_>, Vec<_>) = ranges(last + 1, total, settings.chunk_size())
+        .into_iter()
+        .partition(|&range| !part.state.contains(range));
+    transfer.add(skipped.iter().map(|(first, last)| last - first + 1).sum());
+    let part = Mutex::new(part);
+    write_part(first, &path, (0, last), &part, transfer).await?;
+
+    futures::stream::iter(rest)
+        .map(|range| fetch_part(client, url, range, settings.chunk_retries, &path, &part, transfer))
+        .buffer_unordered(settings.parallel_chunks)
+        .try_collect::<Vec<()>>()
+        .await?;
+    Ok(())
+}
+
+/// Streams a body sent from `offset` to the end of the file into the part file
+pub async fn download_stream(response: reqwest::Response, part: &mut PartFile, offset: u64, transfer: &Transfer) -> Result<()> {
+    let path = part.path().to_path_buf();
+    let written = stream_to(response, &path, (offset, None), transfer, |range| part.record(range, false)).await?;
+    match part.state.total {
+        Some(total) if offset + written != total => {
+            anyhow::bail!("Download broke off after {} of {} bytes", offset + written, total)
+        },
+        _ => Ok(()),
+    }
+}
+
+pub fn content_range(response: &reqwest::Response) -> Result<(u64, u64, u64)> {
+    response.headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_content_range)
+        .context("Backend sent a partial response without a valid Content-Range")
+}
+
+/// Fetches one part, retrying failed attempts; a cancelled request isn't retried
+async fn fetch_part(
+    client: &HttpClient,
+    url: &str,
+    (first, last): (u64, u64),
+    retries: u32,
+    path: &Path,
+    part: &Mutex<&mut PartFile>,
+    transfer: &Transfer,
+) -> Result<()> {
+    let mut attempt = 0;
+    loop {
+        let result = async {
+            let response = client.get(url)
+                .header("Range", &format!("bytes={}-{}", first, last))
+                .transfer()
+                .send()
+                .await?;
+            if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+                anyhow::bail!("Backend answered bytes {}-{} with {}", first, last, response.status());
+            }
+            if content_range(&response)?.0 != first {
+                anyhow::bail!("Backend sent another range than bytes {}-{}", first, last);
+            }
+            write_part(response, path, (first, last), part, transfer).await
+        }.await;
+        match result {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt < retries && !err.is::<Cancelled>() => {
+                attempt += 1;
+                tokio::time::sleep(RETRY_DELAY * attempt).await;
+            },
+            Err(err) => return Err(err).with_context(|| format!("Failed to download bytes {}-{}", first, last)),
+        }
+    }
+}
+
+/// Writes the body of one part and records it once complete
+async fn write_part(
+    response: reqwest::Response,
+    path: &Path,
+    (first, last): (u64, u64),
+    part: &Mutex<&mut PartFile>,
+    transfer: &Transfer,
+) -> Result<()> {
+    let lock = || part.lock().unwrap_or_else(PoisonError::into_inner);
+    let written = stream_to(response, path, (first, Some(last)), transfer, |range| lock().record(range, false)).await?;
+    if written != last - first + 1 {
+        transfer.rewind(written);
+        anyhow::bail!("Bytes {}-{} arrived incomplete", first, last);
+    }
+    lock().save()
+}
+
+/// Writes a response body to `path` from the first byte of `range` as it arrives, counting
+/// it towards the download's progress; `written` gets each range in the file. Returns the
+/// number of bytes written. When the body breaks off, what was counted is taken back.
+pub async fn stream_to(
+    mut response: reqwest::Response,
+    path: &Path,
+    (first, last): (u64, Option<u64>),
+    transfer: &Transfer,
+    mut written: impl FnMut((u64, u64)) -> Result<()>,
+) -> Result<u64> {
+    let mut file = tokio::fs::OpenOptions::new().write(true).open(path).await
+        .context("Failed to open the download file")?;
+    file.seek(SeekFrom::Start(first)).await.context("Failed to write file")?;
+    let mut position = first;
+    loop {
+        let bytes = match response.chunk().await {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => return Ok(position - first),
+            Err(err) => {
+                transfer.rewind(position - first);
+                return Err(err).context("Failed to read response body");
+            },
+        };
+        let end = position + bytes.len() as u64;
+        if last.is_some_and(|last| end > last + 1) {
+            transfer.rewind(position - first);
+            anyhow::bail!("Backend sent more than bytes {}-{}", first, last.unwrap_or_default());
+        }
+        file.write_all(&bytes).await.context("Failed to write file")?;
+        // The bytes are recorded as downloaded only once they are in the file
+        file.flush().await.context("Failed to write file")?;
+        transfer.add(bytes.len() as u64);
+        if end > position {
+            written((position, end - 1))?;
+        }
+        position = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::ApiClient;
+    use crate::network::NetworkMonitor;
+    use crate::ranged::{parse_content_range, ranges, DownloadSettings};
+    use crate::resume::{part_paths, PartState};
+
+    const MB: usize = 1024 * 1024;
+
+    #[test]
+    fn test_ranges_and_content_range() {
+        assert_eq!(ranges(0, 10, 4), vec![(0, 3), (4, 7), (8, 9)]);
+        assert_eq!(ranges(4, 8, 4), vec![(4, 7)]);
+        assert!(ranges(10, 10, 4).is_empty());
+
+        assert_eq!(parse_content_range("bytes 0-1023/4096"), Some((0, 1023, 4096)));
+        assert_eq!(parse_content_range("bytes */4096"), None);
+        // Koniec zakresu poza plikiem
+        assert_eq!(parse_content_range("bytes 0-4096/4096"), None);
+    }
+
+    #[test]
+    fn test_download_settings_defaults() -> anyhow::Result<()> {
+        let settings: DownloadSettings = toml::from_str("parallel_chunks = 8")?;
+        assert_eq!(settings.parallel_chunks, 8);
+        assert_eq!(settings.chunk_size(), 16 * MB as u64);
+        assert!(!DownloadSettings { parallel_chunks: 1, ..settings }.ranged());
+        Ok(())
+    }
+
+    #[test]
+    fn test_download_in_parallel_parts() -> anyhow::Result<()> {
+        let body: Vec<u8> = (0..2 * MB + MB / 2).map(|i| (i % 251) as u8).collect();
+        let mut server = mockito::Server::new();
+        let mut mocks = Vec::new();
+        for (first, last) in ranges(0, body.len() as u64, MB as u64) {
+            mocks.push(server.mock("GET", "/api/results/job-1")
+                .match_header("range", format!("bytes={}-{}", first, last).as_str())
+                .with_status(206)
+                .with_header("content-range", &format!("bytes {}-{}/{}", first, last, body.len()))
+                .with_body(&body[first as usize..=last as usize])
+                .create());
+        }
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.json");
+        let network = NetworkMonitor::default();
+        let client = ApiClient::new(&server.url())
+            .with_monitor(network.clone())
+            .with_download_settings(DownloadSettings { parallel_chunks: 2, chunk_size_mb: 1, chunk_retries: 0 });
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        runtime.block_on(client.download_results_checked("job-1", &path, |size| {
+            assert_eq!(size, Some(body.len() as u64));
+            Ok(())
+        }))?;
+
+        assert_eq!(std::fs::read(&path)?, body);
+        for mock in mocks {
+            mock.assert();
+        }
+        // Zakończone pobieranie znika z paska postępu
+        assert!(network.transfer_progress().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_download_without_range_support() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new();
+        let _mock = server.mock("GET", "/api/results/job-1")
+            .with_status(200)
+            .with_body("[]")
+            .create();
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.json");
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        runtime.block_on(ApiClient::new(&server.url()).download_results("job-1", &path))?;
+        assert_eq!(std::fs::read_to_string(&path)?, "[]");
+        // Po pobraniu nie zostaje plik częściowy
+        let (part, state) = part_paths(&path)?;
+        assert!(!part.exists() && !state.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_state_ranges() {
+        let mut state = PartState::default();
+        state.add((10, 19));
+        assert_eq!(state.prefix(), 0);
+        state.add((0, 9));
+        state.add((30, 39));
+        assert_eq!(state.done, vec![(0, 19), (30, 39)]);
+        assert_eq!(state.prefix(), 20);
+        assert!(state.contains((5, 15)));
+        assert!(!state.contains((15, 35)));
+        state.add((20, 29));
+        assert_eq!(state.done, vec![(0, 39)]);
+    }
+
+    /// Zostawia plik częściowy z początkiem `body`, jak po przerwanym pobieraniu
+    fn leave_part(path: &std::path::Path, url: &str, body: &[u8], done: Vec<(u64, u64)>) -> anyhow::Result<()> {
+        let (part, state) = part_paths(path)?;
+        let mut content = vec![0; body.len()];
+        for &(first, last) in &done {
+            content[first as usize..=last as usize].copy_from_slice(&body[first as usize..=last as usize]);
+        }
+        std::fs::write(part, content)?;
+        let state_json = serde_json::to_vec(&PartState {
+            url: url.to_string(),
+            total: Some(body.len() as u64),
+            validator: Some("\"v1\"".to_string()),
+            done,
+        })?;
+        std::fs::write(state, state_json)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume_single_stream() -> anyhow::Result<()> {
+        let body: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+        let mut server = mockito::Server::new();
+        let mock = server.mock("GET", "/api/results/job-1")
+            .match_header("range", "bytes=400-")
+            .match_header("if-range", "\"v1\"")
+            .with_status(206)
+            .with_header("etag", "\"v1\"")
+            .with_header("content-range", "bytes 400-999/1000")
+            .with_body(&body[400..])
+            .create();
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.json");
+        std::fs::write(&path, "stare wyniki")?;
+        leave_part(&path, &format!("{}/api/results/job-1", server.url()), &body, vec![(0, 399)])?;
+        let client = ApiClient::new(&server.url())
+            .with_download_settings(DownloadSettings { parallel_chunks: 1, ..Default::default() });
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        runtime.block_on(client.download_results("job-1", &path))?;
+
+        mock.assert();
+        assert_eq!(std::fs::read(&path)?, body);
+        let (part, state) = part_paths(&path)?;
+        assert!(!part.exists() && !state.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume_skips_downloaded_parts() -> anyhow::Result<()> {
+        let body: Vec<u8> = (0..3 * MB).map(|i| (i % 251) as u8).collect();
+        let mut server = mockito::Server::new();
+        let mut mocks = Vec::new();
+        for (first, last) in ranges(0, body.len() as u64, MB as u64) {
+            mocks.push(server.mock("GET", "/api/results/job-1")
+                .match_header("range", format!("bytes={}-{}", first, last).as_str())
+                .with_status(206)
+                .with_header("etag", "\"v1\"")
+                .with_header("content-range", &format!("bytes {}-{}/{}", first, last, body.len()))
+                .with_body(&body[first as usize..=last as usize])
+                // Drugą część ma już plik częściowy
+                .expect(if first == MB as u64 { 0 } else { 1 })
+                .create());
+        }
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.json");
+        let done = vec![(0, MB as u64 - 1), (MB as u64, 2 * MB as u64 - 1)];
+        leave_part(&path, &format!("{}/api/results/job-1", server.url()), &body, done)?;
+        let client = ApiClient::new(&server.url())
+            .with_download_settings(DownloadSettings { parallel_chunks: 2, chunk_size_mb: 1, chunk_retries: 0 });
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        runtime.block_on(client.download_results("job-1", &path))?;
+
+        assert_eq!(std::fs::read(&path)?, body);
+        for mock in mocks {
+            mock.assert();
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_download_keeps_part() -> anyhow::Result<()> {
+        let body: Vec<u8> = (0..2 * MB).map(|i| (i % 251) as u8).collect();
+        let mut server = mockito::Server::new();
+        let _first = server.mock("GET", "/api/results/job-1")
+            .match_header("range", format!("bytes=0-{}", MB - 1).as_str())
+            .with_status(206)
+            .with_header("etag", "\"v1\"")
+            .with_header("content-range", &format!("bytes 0-{}/{}", MB - 1, body.len()))
+            .with_body(&body[..MB])
+            .create();
+        let _second = server.mock("GET", "/api/results/job-1")
+            .match_header("range", format!("bytes={}-{}", MB, 2 * MB - 1).as_str())
+            .with_status(500)
+            .create();
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.json");
+        std::fs::write(&path, "stare wyniki")?;
+        let client = ApiClient::new(&server.url())
+            .with_download_settings(DownloadSettings { parallel_chunks: 2, chunk_size_mb: 1, chunk_retries: 0 });
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        assert!(runtime.block_on(client.download_results("job-1", &path)).is_err());
+
+        // Istniejące wyniki zostają nietknięte, a pobrana część czeka na wznowienie
+        assert_eq!(std::fs::read_to_string(&path)?, "stare wyniki");
+        let (part, state) = part_paths(&path)?;
+        let state: PartState = serde_json::from_slice(&std::fs::read(state)?)?;
+        assert_eq!(state.done, vec![(0, MB as u64 - 1)]);
+        assert_eq!(state.validator.as_deref(), Some("\"v1\""));
+        assert_eq!(&std::fs::read(part)?[..MB], &body[..MB]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_changed_file_starts_over() -> anyhow::Result<()> {
+        let body = b"nowa wersja pliku".to_vec();
+        let mut server = mockito::Server::new();
+        // Plik zmienił się, więc backend ignoruje zakres z If-Range i wysyła całość
+        let mock = server.mock("GET", "/api/results/job-1")
+            .with_status(200)
+            .with_header("etag", "\"v2\"")
+            .with_body(&body)
+            .create();
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.json");
+        let old: Vec<u8> = b"stara wersja pliku, dluzsza od nowej".to_vec();
+        leave_part(&path, &format!("{}/api/results/job-1", server.url()), &old, vec![(0, 9)])?;
+        let client = ApiClient::new(&server.url())
+            .with_download_settings(DownloadSettings { parallel_chunks: 1, ..Default::default() });
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        runtime.block_on(client.download_results("job-1", &path))?;
+
+        mock.assert();
+        assert_eq!(std::fs::read(&path)?, body);
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::atomic;
+use crate::history::HistoryEntry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "csv",
+            ReportFormat::Json => "json",
+        }
+    }
+}
+
+/// Calendar month a report covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Month {
+    pub year: i32,
+    pub month: u32,
+}
+
+impl Month {
+    pub fn current() -> Self {
+        let now = Utc::now();
+        Self { year: now.year(), month: now.month() }
+    }
+
+    /// Parses `YYYY-MM`
+    pub fn parse(value: &str) -> Result<Self> {
+        let date = NaiveDate::parse_from_str(&format!("{}-01", value.trim()), "%Y-%m-%d")
+            .with_context(|| format!("Invalid month '{}', expected YYYY-MM", value))?;
+        Ok(Self { year: date.year(), month: date.month() })
+    }
+
+    pub fn contains(&self, time: &DateTime<Utc>) -> bool {
+        time.year() == self.year && time.month() == self.month
+    }
+}
+
+impl std::fmt::Display for Month {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+/// One exported row per job
+#[derive(Debug, Clone, Serialize)]
+pub struct JobReportRow {
+    pub job_id: String,
+    pub file: String,
+    pub processing_type: String,
+    pub provider: String,
+    pub model: String,
+    pub status: String,
+    pub submitted_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration_secs: Option<i64>,
+    pub records: Option<u64>,
+    pub tokens: Option<u64>,
+    pub cost_usd: Option<f64>,
+}
+
+impl From<&HistoryEntry> for JobReportRow {
+    fn from(entry: &HistoryEntry) -> Self {
+        Self {
+            job_id: entry.job_id.clone(),
+            file: entry.file.clone(),
+            processing_type: entry.processing_type.clone(),
+            provider: entry.provider.clone(),
+            model: entry.model.clone(),
+            status: entry.status.clone(),
+            submitted_at: entry.submitted_at,
+            finished_at: entry.finished_at,
+            duration_secs: entry.finished_at.map(|f| (f - entry.submitted_at).num_seconds()),
+            records: entry.records,
+            tokens: entry.tokens,
+            cost_usd: entry.cost_usd,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageTotals {
+    pub jobs: usize,
+    pub records: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    pub fn add(&mut self, row: &JobReportRow) {
+        self.jobs += 1;
+        self.records += row.records.unwrap_or(0);
+        self.tokens += row.tokens.unwrap_or(0);
+        self.cost_usd += row.cost_usd.unwrap_or(0.0);
+    }
+}
+
+/// Usage report for a set of jobs, with totals overall and per provider
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    pub period: Option<String>,
+    pub generated_at: DateTime<Utc>,
+    pub totals: UsageTotals,
+    pub by_provider: BTreeMap<String, UsageTotals>,
+    pub jobs: Vec<JobReportRow>,
+}
+
+impl UsageReport {
+    /// Builds the report from history entries; `month` limits it to jobs submitted in that month
+    pub fn build(entries: &[HistoryEntry], month: Option<Month>) -> Self {
+        let jobs: Vec<JobReportRow> = entries.iter()
+            .filter(|e| month.is_none_or(|m| m.contains(&e.submitted_at)))
+            .map(JobReportRow::from)
+            .collect();
+
+        let mut totals = UsageTotals::default();
+        let mut by_provider: BTreeMap<String, UsageTotals> = BTreeMap::new();
+        for row in &jobs {
+            totals.add(row);
+            by_provider.entry(row.provider.clone()).or_default().add(row);
+        }
+
+        Self {
+            period: month.map(|m| m.to_string()),
+            generated_at: Utc::now(),
+            totals,
+            by_provider,
+            jobs,
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Json => serde_json::to_string_pretty(self).context("Failed to serialize report"),
+            ReportFormat::Csv => Ok(self.to_csv()),
+        }
+    }
+
+    pub fn write_to(&self, path: &Path, format: ReportFormat) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).context("Failed to create report directory")?;
+        }
+        atomic::write(path, self.render(format)?)
+            .with_context(|| format!("Failed to write report to {}", path.display()))
+    }
+
+    /// Per-job rows only; totals are easy to recompute in a spreadsheet
+    fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "job_id,file,processing_type,provider,model,status,submitted_at,finished_at,duration_secs,records,tokens,cost_usd\n",
+        );
+        let opt = |v: Option<String>| v.unwrap_or_default();
+        for row in &self.jobs {
+            let fields = [
+                csv_field(&row.job_id),
+                csv_field(&row.file),
+                csv_field(&row.processing_type),
+                csv_field(&row.provider),
+                csv_field(&row.model),
+                csv_field(&row.status),
+                row.submitted_at.to_rfc3339(),
+                opt(row.finished_at.map(|t| t.to_rfc3339())),
+                opt(row.duration_secs.map(|d| d.to_string())),
+                opt(row.records.map(|r| r.to_string())),
+                opt(row.tokens.map(|t| t.to_string())),
+                opt(row.cost_usd.map(|c| format!("{:.4}", c))),
+            ];
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::HistoryEntry;
+    use crate::report::{Month, ReportFormat, UsageReport};
+    use chrono::{TimeZone, Utc};
+
+    fn entry(job_id: &str, provider: &str, month: u32, cost: f64) -> HistoryEntry {
+        let submitted_at = Utc.with_ymd_and_hms(2025, month, 10, 12, 0, 0).unwrap();
+        HistoryEntry {
+            job_id: job_id.to_string(),
+            file: "notes, draft.txt".to_string(),
+            processing_type: "standard".to_string(),
+            provider: provider.to_string(),
+            model: "model".to_string(),
+            status: "completed".to_string(),
+            submitted_at,
+            finished_at: Some(submitted_at + chrono::Duration::seconds(90)),
+            records: Some(10),
+            tokens: Some(1000),
+            cost_usd: Some(cost),
+            variables: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_monthly_report() -> anyhow::Result<()> {
+        let entries = vec![
+            entry("a", "openai", 3, 1.5),
+            entry("b", "anthropic", 3, 0.5),
+            entry("c", "openai", 4, 2.0),
+        ];
+        let report = UsageReport::build(&entries, Some(Month::parse("2025-03")?));
+
+        assert_eq!(report.period.as_deref(), Some("2025-03"));
+        assert_eq!(report.totals.jobs, 2);
+        assert_eq!(report.totals.tokens, 2000);
+        assert!((report.totals.cost_usd - 2.0).abs() < f64::EPSILON);
+        assert_eq!(report.by_provider["openai"].jobs, 1);
+        assert_eq!(report.jobs[0].duration_secs, Some(90));
+
+        // Pola z przecinkiem są cytowane
+        let csv = report.render(ReportFormat::Csv)?;
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.lines().nth(1).unwrap().starts_with("a,\"notes, draft.txt\",standard,openai"));
+
+        assert!(Month::parse("2025-13").is_err());
+        Ok(())
+    }
+}
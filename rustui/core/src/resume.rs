@@ -0,0 +1,167 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::atomic;
+
+/// How often the progress of a streamed download is written down
+const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What an interrupted download got: `.<name>.part.json` next to the target describes the
+/// bytes in `.<name>.part`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartState {
+    pub url: String,
+    /// File size announced by the backend
+    #[serde(default)]
+    pub total: Option<u64>,
+    /// ETag or Last-Modified of the file, sent as `If-Range` so a changed file starts over
+    #[serde(default)]
+    pub validator: Option<String>,
+    /// Inclusive byte ranges already in the part file, sorted and merged
+    #[serde(default)]
+    pub done: Vec<(u64, u64)>,
+}
+
+impl PartState {
+    /// Length of the part of the file downloaded from its start
+    pub fn prefix(&self) -> u64 {
+        match self.done.first() {
+            Some(&(0, last)) => last + 1,
+            _ => 0,
+        }
+    }
+
+    pub fn contains(&self, (first, last): (u64, u64)) -> bool {
+        self.done.iter().any(|&(from, to)| from <= first && last <= to)
+    }
+
+    pub fn add(&mut self, range: (u64, u64)) {
+        self.done.push(range);
+        self.done.sort_unstable();
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(self.done.len());
+        for &(first, last) in &self.done {
+            match merged.last_mut() {
+                Some(previous) if first <= previous.1 + 1 => previous.1 = previous.1.max(last),
+                _ => merged.push((first, last)),
+            }
+        }
+        self.done = merged;
+    }
+}
+
+/// Download in progress, written to a part file that survives a failed or cancelled attempt.
+/// The next download of the same URL to the same target continues from what it holds, and
+/// the target is replaced only once the file is complete.
+#[derive(Debug)]
+pub struct PartFile {
+    path: PathBuf,
+    state_path: PathBuf,
+    pub state: PartState,
+    saved: Instant,
+}
+
+/// Part file and its state file for a download to `output`
+pub fn part_paths(output: &Path) -> Result<(PathBuf, PathBuf)> {
+    let name = output.file_name().context("Target path has no file name")?.to_string_lossy();
+    Ok((
+        output.with_file_name(format!(".{}.part", name)),
+        output.with_file_name(format!(".{}.part.json", name)),
+    ))
+}
+
+impl PartFile {
+    /// Picks up the part left by an earlier download of `url` to `output`. A part without a
+    /// validator can't be checked against the backend and is dropped, like one of another URL.
+    pub fn open(output: &Path, url: &str) -> Result<Self> {
+        let (path, state_path) = part_paths(output)?;
+        let state = std::fs::read(&state_path).ok()
+            .and_then(|bytes| serde_json::from_slice::<PartState>(&bytes).ok())
+            .filter(|state| state.url == url && state.validator.is_some() && path.exists())
+            .unwrap_or_else(|| PartState { url: url.to_string(), ..Default::default() });
+        let mut part = Self { path, state_path, state, saved: Instant::now() };
+        if part.state.done.is_empty() {
+            part.truncate()?;
+        }
+        Ok(part)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Validator to send as `If-Range` when resuming; None when there is nothing to resume
+    pub fn if_range(&self) -> Option<&str> {
+        self.state.validator.as_deref().filter(|_| !self.state.done.is_empty())
+    }
+
+    /// Describes the file the backend is sending. Bytes kept for another version of it are
+    /// dropped; returns whether they were.
+    pub fn start(&mut self, total: Option<u64>, validator: Option<String>) -> Result<bool> {
+        let changed = !self.state.done.is_empty() && (self.state.total != total || self.state.validator != validator);
+        if changed {
+            self.state.done.clear();
+            self.truncate()?;
+        }
+        self.state.total = total;
+        self.state.validator = validator;
+        Ok(changed)
+    }
+
+    /// The backend sends the whole file again
+    pub fn restart(&mut self, total: Option<u64>, validator: Option<String>) -> Result<()> {
+        self.state.done.clear();
+        self.truncate()?;
+        self.start(total, validator).map(|_| ())
+    }
+
+    /// Records bytes written to the part file; the state file is updated now and then,
+    /// or right away with `save_now`
+    pub fn record(&mut self, range: (u64, u64), save_now: bool) -> Result<()> {
+        self.state.add(range);
+        if save_now || self.saved.elapsed() >= SAVE_INTERVAL {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        self.saved = Instant::now();
+        atomic::write(&self.state_path, serde_json::to_vec(&self.state)?)
+            .context("Failed to save the download state")
+    }
+
+    /// Puts the complete file in place of `output` and forgets the part
+    pub fn finish(self, output: &Path) -> Result<()> {
+        let file = std::fs::OpenOptions::new().write(true).open(&self.path)
+            .context("Failed to open the download file")?;
+        if let Some(total) = self.state.total {
+            file.set_len(total).context("Failed to write file")?;
+        }
+        let path = tempfile::TempPath::try_from_path(&self.path).context("Failed to open the download file")?;
+        let tmp = tempfile::NamedTempFile::from_parts(file, path);
+        atomic::persist(tmp, output)?;
+        let _ = std::fs::remove_file(&self.state_path);
+        Ok(())
+    }
+
+    /// Keeps what a failed attempt got for the next one; a part that can't be resumed is deleted
+    pub fn suspend(mut self) {
+        if self.if_range().is_none() || self.save().is_err() {
+            self.discard();
+        }
+    }
+
+    /// Deletes the part and its state
+    pub fn discard(self) {
+        let _ = std::fs::remove_file(&self.path);
+        let _ = std::fs::remove_file(&self.state_path);
+    }
+
+    fn truncate(&mut self) -> Result<()> {
+        std::fs::File::create(&self.path)
+            .with_context(|| format!("Failed to create {}", self.path.display()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::api::ProcessingConfig;
+
+/// `[retry]` in config.toml: failed backend jobs are submitted again, so a batch left running
+/// overnight gets over a backend restart on its own
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Submissions of a job including the first one; 1 turns resubmitting off
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Wait before the first resubmission
+    #[serde(default = "default_backoff_secs")]
+    pub backoff_secs: u64,
+    /// Each further wait is this many times longer
+    #[serde(default = "default_backoff_factor")]
+    pub backoff_factor: f64,
+    /// Upper bound of the wait
+    #[serde(default = "default_max_backoff_secs")]
+    pub max_backoff_secs: u64,
+    /// Resubmit only after errors matching `retryable_errors`; a job failing on its input
+    /// fails the same way every time
+    #[serde(default = "default_only_retryable")]
+    pub only_retryable: bool,
+    /// Parts of error messages (case-insensitive) that mark a failure as transient
+    #[serde(default = "default_retryable_errors")]
+    pub retryable_errors: Vec<String>,
+}
+
+fn default_max_attempts() -> u32 {
+    1
+}
+
+fn default_backoff_secs() -> u64 {
+    60
+}
+
+fn default_backoff_factor() -> f64 {
+    2.0
+}
+
+fn default_max_backoff_secs() -> u64 {
+    1800
+}
+
+fn default_only_retryable() -> bool {
+    true
+}
+
+fn default_retryable_errors() -> Vec<String> {
+    [
+        "timed out", "timeout", "connection", "unavailable", "502", "503", "504", "429",
+        "rate limit", "overloaded", "restart", "shutdown", "worker lost",
+    ]
+    .map(str::to_string)
+    .to_vec()
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            backoff_secs: default_backoff_secs(),
+            backoff_factor: default_backoff_factor(),
+            max_backoff_secs: default_max_backoff_secs(),
+            only_retryable: default_only_retryable(),
+            retryable_errors: default_retryable_errors(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn is_retryable(&self, error: &str) -> bool {
+        if !self.only_retryable {
+            return true;
+        }
+        let error = error.to_lowercase();
+        self.retryable_errors.iter()
+            .filter(|pattern| !pattern.trim().is_empty())
+            .any(|pattern| error.contains(&pattern.to_lowercase()))
+    }
+
+    /// Wait before submitting again after attempt `attempt` (counted from 1) failed
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self.backoff_factor.max(1.0).powi(attempt.saturating_sub(1).min(32) as i32);
+        let secs = (self.backoff_secs as f64 * factor).min(self.max_backoff_secs as f64);
+        Duration::from_secs_f64(secs.max(0.0))
+    }
+
+    /// When to submit again after attempt `attempt` failed with `error`; `None` once the
+    /// attempts are used up or the error isn't transient
+    pub fn next_attempt(&self, attempt: u32, error: &str) -> Option<Duration> {
+        (attempt < self.max_attempts && self.is_retryable(error)).then(|| self.backoff(attempt))
+    }
+}
+
+/// What a backend job was submitted with, kept to submit it again
+#[derive(Debug, Clone)]
+pub struct Submission {
+    pub file: String,
+    pub file_id: String,
+    pub config: ProcessingConfig,
+    pub api_key: Option<String>,
+    /// Counted from 1
+    pub attempt: u32,
+    /// Job that failed and is submitted again with this
+    pub retry_of: Option<String>,
+}
+
+/// A resubmission waiting for its backoff to pass
+#[derive(Debug, Clone)]
+pub struct ScheduledRetry {
+    pub due: Instant,
+    pub submission: Submission,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::retry::RetryPolicy;
+    use std::time::Duration;
+
+    #[test]
+    fn test_retry_policy() {
+        // Domyślnie zadania nie są wysyłane ponownie
+        assert_eq!(RetryPolicy::default().next_attempt(1, "503 Service Unavailable"), None);
+
+        let policy = RetryPolicy { max_attempts: 4, backoff_secs: 10, max_backoff_secs: 30, ..Default::default() };
+        assert_eq!(policy.next_attempt(1, "Backend Service Unavailable"), Some(Duration::from_secs(10)));
+        assert_eq!(policy.next_attempt(2, "connection reset by peer"), Some(Duration::from_secs(20)));
+        assert_eq!(policy.next_attempt(3, "Request timed out"), Some(Duration::from_secs(30)));
+        assert_eq!(policy.next_attempt(4, "Request timed out"), None);
+        // Błąd danych wejściowych powtórzyłby się przy każdej próbie
+        assert_eq!(policy.next_attempt(1, "Unsupported file format"), None);
+
+        let any = RetryPolicy { only_retryable: false, ..policy };
+        assert_eq!(any.next_attempt(1, "Unsupported file format"), Some(Duration::from_secs(10)));
+    }
+}
@@ -0,0 +1,207 @@
+#[cfg(feature = "keyring")]
+use aes_gcm::aead::OsRng;
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::atomic;
+#[cfg(feature = "keyring")]
+use crate::credentials::KEYRING_SERVICE;
+
+/// Keyring entry of the signing key, next to the provider API keys
+#[cfg(feature = "keyring")]
+const KEYRING_USER: &str = "export-signing-key";
+
+/// Prefix of the signed message, so these signatures can't be passed off as anything else
+const CONTEXT: &[u8] = b"anydataset-export-v1\0";
+
+/// `[signing]` in config.toml
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SigningSettings {
+    /// Sign downloaded and exported datasets and their manifests
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Contents of `<file>.sig`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileSignature {
+    pub algorithm: String,
+    /// SHA-256 of the file as hex
+    pub sha256: String,
+    pub public_key: String,
+    pub signature: String,
+    pub signed_at: DateTime<Utc>,
+}
+
+/// Where a file's signature is kept (`data.jsonl` -> `data.jsonl.sig`)
+pub fn signature_path(file: &Path) -> PathBuf {
+    let mut name = file.file_name().unwrap_or_default().to_os_string();
+    name.push(".sig");
+    file.with_file_name(name)
+}
+
+/// The signing key from the OS keyring, generated and stored there on first use
+#[cfg(feature = "keyring")]
+pub fn load_or_create_key() -> Result<SigningKey> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).context("OS keyring unavailable")?;
+    match entry.get_password() {
+        Ok(encoded) => {
+            let bytes = STANDARD.decode(encoded.trim()).context("Signing key in the keyring is not base64")?;
+            let seed: [u8; 32] = bytes.try_into()
+                .map_err(|_| anyhow::anyhow!("Signing key in the keyring must be 32 bytes"))?;
+            Ok(SigningKey::from_bytes(&seed))
+        },
+        Err(keyring::Error::NoEntry) => {
+            let key = SigningKey::generate(&mut OsRng);
+            entry.set_password(&STANDARD.encode(key.to_bytes()))
+                .context("Failed to save the signing key in the OS keyring")?;
+            Ok(key)
+        },
+        Err(err) => Err(err).context("Failed to read the signing key from the OS keyring"),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn load_or_create_key() -> Result<SigningKey> {
+    anyhow::bail!("Signing needs the OS keyring, which is not available in this build; rebuild with `--features keyring`")
+}
+
+pub fn encode_public_key(key: &VerifyingKey) -> String {
+    STANDARD.encode(key.as_bytes())
+}
+
+pub fn parse_public_key(encoded: &str) -> Result<VerifyingKey> {
+    let bytes = STANDARD.decode(encoded.trim()).context("Public key is not base64")?;
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| anyhow::anyhow!("Public key must be 32 bytes"))?;
+    VerifyingKey::from_bytes(&bytes).context("Not an ed25519 public key")
+}
+
+fn digest(file: &Path) -> Result<[u8; 32]> {
+    let content = std::fs::read(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    Ok(Sha256::digest(content).into())
+}
+
+fn message(digest: &[u8; 32]) -> Vec<u8> {
+    [CONTEXT, digest.as_slice()].concat()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Signs a file as it is on disk and writes the signature next to it
+pub fn sign_file(key: &SigningKey, file: &Path) -> Result<PathBuf> {
+    let digest = digest(file)?;
+    let signature = FileSignature {
+        algorithm: "ed25519".to_string(),
+        sha256: hex(&digest),
+        public_key: encode_public_key(&key.verifying_key()),
+        signature: STANDARD.encode(key.sign(&message(&digest)).to_bytes()),
+        signed_at: Utc::now(),
+    };
+    let path = signature_path(file);
+    atomic::write(&path, serde_json::to_string_pretty(&signature)?)?;
+    Ok(path)
+}
+
+/// Signs every file with the keyring key. Returns how many were signed.
+pub fn sign_all<'a>(files: impl IntoIterator<Item = &'a Path>) -> Result<usize> {
+    let key = load_or_create_key()?;
+    let mut signed = 0;
+    for file in files {
+        sign_file(&key, file)?;
+        signed += 1;
+    }
+    Ok(signed)
+}
+
+/// Checks a file against its `.sig` and returns the key that signed it. With `trusted`
+/// the signature must also come from that key; without it anyone could have re-signed
+/// a modified file.
+pub fn verify_file(file: &Path, trusted: Option<&VerifyingKey>) -> Result<VerifyingKey> {
+    let path = signature_path(file);
+    let content = std::fs::read(&path).with_context(|| format!("No signature {}", path.display()))?;
+    let signature: FileSignature = serde_json::from_slice(&content)
+        .with_context(|| format!("{} is not a signature file", path.display()))?;
+    if signature.algorithm != "ed25519" {
+        anyhow::bail!("Unsupported signature algorithm {}", signature.algorithm);
+    }
+    let public_key = parse_public_key(&signature.public_key)?;
+    if trusted.is_some_and(|trusted| trusted != &public_key) {
+        anyhow::bail!("Signed by another key ({})", signature.public_key);
+    }
+    let digest = digest(file)?;
+    if hex(&digest) != signature.sha256 {
+        anyhow::bail!("Contents changed since signing");
+    }
+    let bytes: [u8; 64] = STANDARD.decode(&signature.signature).ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .context("Malformed signature")?;
+    public_key.verify(&message(&digest), &Signature::from_bytes(&bytes))
+        .map_err(|_| anyhow::anyhow!("Signature doesn't match the contents"))?;
+    Ok(public_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::signing::{encode_public_key, parse_public_key, sign_file, signature_path, verify_file};
+    use aes_gcm::aead::OsRng;
+    use ed25519_dalek::SigningKey;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_sign_and_verify() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("wyniki.jsonl");
+        std::fs::write(&path, "{\"prompt\": \"a\"}\n")?;
+        let key = SigningKey::generate(&mut OsRng);
+
+        assert_eq!(sign_file(&key, &path)?, dir.path().join("wyniki.jsonl.sig"));
+        assert_eq!(verify_file(&path, None)?, key.verifying_key());
+        let trusted = parse_public_key(&encode_public_key(&key.verifying_key()))?;
+        assert!(verify_file(&path, Some(&trusted)).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_modified_file_fails() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("wyniki.json");
+        std::fs::write(&path, "[1]")?;
+        sign_file(&SigningKey::generate(&mut OsRng), &path)?;
+
+        std::fs::write(&path, "[2]")?;
+        let err = verify_file(&path, None).unwrap_err();
+        assert!(err.to_string().contains("changed"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_resigned_by_another_key_fails() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("wyniki.json");
+        std::fs::write(&path, "[1]")?;
+        let ours = SigningKey::generate(&mut OsRng);
+        // Ktoś zmienia plik i podpisuje go własnym kluczem
+        std::fs::write(&path, "[2]")?;
+        sign_file(&SigningKey::generate(&mut OsRng), &path)?;
+
+        assert!(verify_file(&path, None).is_ok());
+        assert!(verify_file(&path, Some(&ours.verifying_key())).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_signature_and_bad_key() {
+        assert_eq!(signature_path(Path::new("/a/b.csv")), PathBuf::from("/a/b.csv.sig"));
+        assert!(verify_file(Path::new("/nonexistent/b.csv"), None).is_err());
+        assert!(parse_public_key("not base64!").is_err());
+        assert!(parse_public_key("AAAA").is_err());
+    }
+}
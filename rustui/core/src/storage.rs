@@ -0,0 +1,362 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::manifest::DatasetManifest;
+use crate::vault::Vault;
+
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// How deep below the downloads directory manifests are looked for
+const MAX_SCAN_DEPTH: usize = 8;
+
+/// Subdirectory of the cache kept out of cleanups: the shared presets used when offline
+const KEPT_CACHE_DIR: &str = "shared-presets";
+
+/// `[retention]` in config.toml: how long downloaded results and caches are kept.
+/// Both limits are off at 0.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct RetentionSettings {
+    /// Job artifacts and caches not modified for this many days are removed
+    #[serde(default)]
+    pub keep_days: u64,
+    /// The oldest artifacts are removed while everything together takes more than this
+    #[serde(default)]
+    pub max_size_gb: f64,
+}
+
+impl RetentionSettings {
+    pub fn enabled(&self) -> bool {
+        self.keep_days > 0 || self.max_size_gb > 0.0
+    }
+
+    fn max_age(&self) -> Option<Duration> {
+        (self.keep_days > 0).then(|| Duration::from_secs(self.keep_days * 24 * 60 * 60))
+    }
+
+    fn max_bytes(&self) -> Option<u64> {
+        (self.max_size_gb > 0.0).then_some((self.max_size_gb * GB) as u64)
+    }
+}
+
+/// The app's own cache directory, shared with crash reports
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("anydataset-tui"))
+}
+
+/// Files taking up disk space for one job, or the app's cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageGroup {
+    /// `None` for the cache
+    pub job_id: Option<String>,
+    /// Source file the job processed
+    pub source: String,
+    /// Directory the files are under; directories they leave empty are removed with them
+    pub root: PathBuf,
+    pub files: Vec<PathBuf>,
+    pub bytes: u64,
+    /// Latest modification of any of the files
+    pub modified: SystemTime,
+}
+
+impl StorageGroup {
+    pub fn label(&self) -> &str {
+        self.job_id.as_deref().unwrap_or("cache")
+    }
+
+    fn add(&mut self, path: PathBuf, metadata: &std::fs::Metadata) {
+        self.bytes += metadata.len();
+        if let Ok(modified) = metadata.modified() {
+            self.modified = self.modified.max(modified);
+        }
+        self.files.push(path);
+    }
+}
+
+/// Downloaded results grouped by job, newest first, followed by the cache. Jobs are found
+/// through the manifests written next to every downloaded dataset, which the vault opens
+/// when they're encrypted.
+pub fn scan(downloads_dir: &Path, cache_dir: Option<&Path>, vault: &Vault) -> Result<Vec<StorageGroup>> {
+    let mut manifests = Vec::new();
+    walk(downloads_dir, 0, &mut |path| {
+        if path.to_string_lossy().ends_with(".manifest.json") {
+            manifests.push(path.to_path_buf());
+        }
+    }).with_context(|| format!("Cannot read {}", downloads_dir.display()))?;
+
+    let mut jobs: BTreeMap<String, StorageGroup> = BTreeMap::new();
+    for manifest_path in manifests {
+        let Ok(manifest) = DatasetManifest::load(&manifest_path, vault) else {
+            continue;
+        };
+        let group = jobs.entry(manifest.job_id.clone()).or_insert_with(|| StorageGroup {
+            job_id: Some(manifest.job_id.clone()),
+            source: manifest.source_file.clone(),
+            root: downloads_dir.to_path_buf(),
+            files: Vec::new(),
+            bytes: 0,
+            modified: SystemTime::UNIX_EPOCH,
+        });
+        let dataset = manifest_path.with_file_name(&manifest.file_name);
+        for path in [dataset, manifest_path] {
+            if let Ok(metadata) = std::fs::metadata(&path) {
+                group.add(path, &metadata);
+            }
+        }
+    }
+    let mut groups: Vec<StorageGroup> = jobs.into_values().collect();
+    groups.sort_by_key(|group| std::cmp::Reverse(group.modified));
+
+    if let Some(cache_dir) = cache_dir.filter(|dir| dir.is_dir()) {
+        let mut cache = StorageGroup {
+            job_id: None,
+            source: String::new(),
+            root: cache_dir.to_path_buf(),
+            files: Vec::new(),
+            bytes: 0,
+            modified: SystemTime::UNIX_EPOCH,
+        };
+        walk(cache_dir, 0, &mut |path| {
+            if let Ok(metadata) = std::fs::metadata(path) {
+                cache.add(path.to_path_buf(), &metadata);
+            }
+        }).with_context(|| format!("Cannot read {}", cache_dir.display()))?;
+        if !cache.files.is_empty() {
+            groups.push(cache);
+        }
+    }
+    Ok(groups)
+}
+
+/// Calls `visit` for every file below `dir`, skipping hidden directories and the kept cache
+fn walk(dir: &Path, depth: usize, visit: &mut dyn FnMut(&Path)) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if depth < MAX_SCAN_DEPTH && !name.starts_with('.') && name != KEPT_CACHE_DIR {
+                // Unreadable subdirectories don't stop the scan
+                let _ = walk(&path, depth + 1, visit);
+            }
+        } else if file_type.is_file() {
+            visit(&path);
+        }
+    }
+    Ok(())
+}
+
+/// Removes a group's files and the directories below its root they leave empty.
+/// Returns the bytes freed.
+pub fn purge(group: &StorageGroup) -> Result<u64> {
+    let mut freed = 0;
+    for path in &group.files {
+        let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        match std::fs::remove_file(path) {
+            Ok(()) => freed += size,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {},
+            Err(err) => return Err(err).with_context(|| format!("Failed to remove {}", path.display())),
+        }
+        let mut dir = path.parent();
+        while let Some(parent) = dir.filter(|d| d.starts_with(&group.root) && *d != group.root) {
+            // Fails on the first directory that still has something in it
+            if std::fs::remove_dir(parent).is_err() {
+                break;
+            }
+            dir = parent.parent();
+        }
+    }
+    Ok(freed)
+}
+
+/// Groups the retention policy removes: those older than `keep_days`, then the oldest of
+/// the rest until what's left fits in `max_size_gb`
+pub fn expired<'a>(groups: &'a [StorageGroup], settings: &RetentionSettings, now: SystemTime) -> Vec<&'a StorageGroup> {
+    let mut by_age: Vec<&StorageGroup> = groups.iter().collect();
+    by_age.sort_by_key(|group| group.modified);
+
+    let too_old = |group: &StorageGroup| settings.max_age()
+        .is_some_and(|max_age| now.duration_since(group.modified).unwrap_or_default() > max_age);
+    let (mut expired, kept): (Vec<&StorageGroup>, Vec<&StorageGroup>) = by_age.into_iter().partition(|g| too_old(g));
+
+    if let Some(max_bytes) = settings.max_bytes() {
+        let mut total: u64 = kept.iter().map(|group| group.bytes).sum();
+        for group in kept {
+            if total <= max_bytes {
+                break;
+            }
+            total -= group.bytes;
+            expired.push(group);
+        }
+    }
+    expired
+}
+
+/// What a cleanup removed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CleanupReport {
+    /// Jobs (and the cache) whose files were removed
+    pub removed: Vec<String>,
+    pub freed: u64,
+    pub errors: Vec<String>,
+}
+
+/// Applies the retention policy to the downloads directory and the cache
+pub fn cleanup(downloads_dir: &Path, cache_dir: Option<&Path>, settings: &RetentionSettings, vault: &Vault) -> Result<CleanupReport> {
+    let groups = scan(downloads_dir, cache_dir, vault)?;
+    let mut report = CleanupReport::default();
+    for group in expired(&groups, settings, SystemTime::now()) {
+        match purge(group) {
+            Ok(freed) => {
+                report.freed += freed;
+                report.removed.push(group.label().to_string());
+            },
+            Err(err) => report.errors.push(format!("{}: {:#}", group.label(), err)),
+        }
+    }
+    Ok(report)
+}
+
+/// Disk usage on the Storage screen
+#[derive(Debug, Clone, Default)]
+pub struct StorageView {
+    pub groups: Vec<StorageGroup>,
+    pub selected: usize,
+    pub loading: bool,
+    /// The usage was scanned at least once
+    pub loaded: bool,
+    pub error: Option<String>,
+    pub cleaning: bool,
+}
+
+impl StorageView {
+    pub fn current(&self) -> Option<&StorageGroup> {
+        self.groups.get(self.selected)
+    }
+
+    pub fn next(&mut self) {
+        if !self.groups.is_empty() {
+            self.selected = (self.selected + 1) % self.groups.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.groups.is_empty() {
+            self.selected = (self.selected + self.groups.len() - 1) % self.groups.len();
+        }
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.groups.iter().map(|group| group.bytes).sum()
+    }
+
+    /// New scan; the selection stays on the same group when it's still there
+    pub fn scanned(&mut self, groups: Vec<StorageGroup>) {
+        let selected = self.current().map(|group| group.job_id.clone());
+        self.selected = selected
+            .and_then(|id| groups.iter().position(|group| group.job_id == id))
+            .unwrap_or(0);
+        self.groups = groups;
+        self.loading = false;
+        self.loaded = true;
+        self.error = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::HistoryEntry;
+    use crate::manifest::DatasetManifest;
+    use crate::storage::{expired, purge, scan, RetentionSettings, StorageGroup};
+    use crate::vault::Vault;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn download(dir: &Path, relative: &str, job_id: &str, content: &str) -> anyhow::Result<PathBuf> {
+        let path = dir.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(&path, content)?;
+        let entry = HistoryEntry {
+            job_id: job_id.to_string(),
+            file: "a.txt".to_string(),
+            processing_type: "standard".to_string(),
+            provider: "openai".to_string(),
+            model: "model".to_string(),
+            status: "completed".to_string(),
+            submitted_at: chrono::Utc::now(),
+            finished_at: None,
+            records: None,
+            tokens: None,
+            cost_usd: None,
+            variables: Default::default(),
+        };
+        DatasetManifest::for_dataset(&path, &entry)?.write_for(&path)?;
+        Ok(path)
+    }
+
+    fn group(job_id: &str, bytes: u64, age_days: u32) -> StorageGroup {
+        StorageGroup {
+            job_id: Some(job_id.to_string()),
+            source: "a.txt".to_string(),
+            root: PathBuf::from("/downloads"),
+            files: Vec::new(),
+            bytes,
+            modified: SystemTime::now() - DAY * age_days,
+        }
+    }
+
+    #[test]
+    fn test_scan_groups_files_by_job() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let first = download(dir.path(), "job-1/out.jsonl", "job-1", "{}")?;
+        download(dir.path(), "job-1/more.csv", "job-1", "a,b")?;
+        download(dir.path(), "job-2.json", "job-2", "[]")?;
+        // Pliki bez manifestu nie należą do żadnego zadania
+        std::fs::write(dir.path().join("notes.txt"), "x")?;
+
+        let groups = scan(dir.path(), None, &Vault::default())?;
+        assert_eq!(groups.len(), 2);
+        let job = groups.iter().find(|g| g.job_id.as_deref() == Some("job-1")).unwrap();
+        assert_eq!(job.files.len(), 4);
+        assert!(job.files.contains(&first));
+        assert!(job.files.contains(&DatasetManifest::path_for(&first)));
+        assert_eq!(job.source, "a.txt");
+        Ok(())
+    }
+
+    #[test]
+    fn test_purge_removes_files_and_empty_directories() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        download(dir.path(), "job-1/data/out.jsonl", "job-1", "{}")?;
+        download(dir.path(), "job-2.json", "job-2", "[]")?;
+
+        let groups = scan(dir.path(), None, &Vault::default())?;
+        let job = groups.iter().find(|g| g.job_id.as_deref() == Some("job-1")).unwrap();
+        assert_eq!(purge(job)?, job.bytes);
+        assert!(!dir.path().join("job-1").exists());
+        assert!(dir.path().exists());
+        assert!(dir.path().join("job-2.json").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_expired_by_age_and_size() {
+        let groups = vec![group("new", 600, 1), group("old", 100, 40), group("middle", 500, 10)];
+        let now = SystemTime::now();
+
+        let ids = |settings: &RetentionSettings| -> Vec<String> {
+            expired(&groups, settings, now).iter().map(|g| g.label().to_string()).collect()
+        };
+        assert!(ids(&RetentionSettings::default()).is_empty());
+        assert_eq!(ids(&RetentionSettings { keep_days: 30, max_size_gb: 0.0 }), vec!["old"]);
+        // Limit ok. 1 KB: po usunięciu najstarszych zostaje tylko najnowsze
+        let settings = RetentionSettings { keep_days: 0, max_size_gb: 1000.0 / (1024.0 * 1024.0 * 1024.0) };
+        assert_eq!(ids(&settings), vec!["old", "middle"]);
+    }
+}
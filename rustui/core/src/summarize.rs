@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::chunking;
+use crate::lineage::{self, PARENT_IDS, RECORD_ID, STAGE};
+use crate::processors::{self, ProcessingResult, ProcessingStats, Processor, ProcessorConfig, Record};
+
+/// Record metadata: 0 for chunk summaries, growing towards the root
+pub const LEVEL: &str = "level";
+
+/// Map-reduce summarization from the `[summarize]` config section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapReduceSettings {
+    /// Summaries combined into one at the next level
+    #[serde(default = "default_fan_in")]
+    pub fan_in: usize,
+    /// Intermediate levels at most; the final abstract then covers whatever is left
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+}
+
+fn default_fan_in() -> usize {
+    4
+}
+
+fn default_max_depth() -> usize {
+    3
+}
+
+impl Default for MapReduceSettings {
+    fn default() -> Self {
+        Self { fan_in: default_fan_in(), max_depth: default_max_depth() }
+    }
+}
+
+/// Summarizes chunks, then summaries of summaries, then writes a final abstract.
+/// Every summary becomes a record linked to the summaries it was made from.
+pub struct MapReduceProcessor {
+    pub settings: MapReduceSettings,
+}
+
+impl Processor for MapReduceProcessor {
+    fn process_file(&self, file_path: &str, config: &ProcessorConfig) -> anyhow::Result<ProcessingResult> {
+        let sources = processors::read_sources(file_path, config)?;
+        let chunk_settings = config.chunking.clone().unwrap_or_default();
+        let fan_in = self.settings.fan_in.max(2);
+
+        let mut records = Vec::new();
+        let mut figure_metadata = HashMap::new();
+        // Map: one summary per chunk, with the neighbors as context; chunks of an earlier
+        // stage's record are linked to it
+        let mut current = Vec::new();
+        for source in sources {
+            let first = records.len();
+            for chunk in chunking::split(&source.text, &chunk_settings) {
+                let prompt = chunk.prompt("Summarize this part of the document");
+                current.push(push_summary(&mut records, 0, prompt, &chunk.text, &[]));
+            }
+            lineage::link(&mut records[first..], &source.parents);
+            figure_metadata.extend(source.metadata);
+        }
+
+        // Reduce until few enough summaries remain or the depth limit is hit
+        let mut level = 0;
+        while current.len() > fan_in && level < self.settings.max_depth {
+            level += 1;
+            current = current.chunks(fan_in)
+                .map(|group| {
+                    let (prompt, input) = combined_prompt("Summarize these summaries", &records, group);
+                    push_summary(&mut records, level, prompt, &input, group)
+                })
+                .collect();
+        }
+
+        let (prompt, input) = combined_prompt("Write a short abstract of the whole document from these summaries", &records, &current);
+        let root = push_summary(&mut records, level + 1, prompt, &input, &current);
+        records[root].metadata.extend(figure_metadata);
+
+        let total_tokens = records.iter()
+            .map(|r| r.prompt.split_whitespace().count() + r.completion.split_whitespace().count())
+            .sum();
+        let exchanges = records.iter_mut()
+            .map(|record| crate::audit::ModelExchange::for_record(record, file_path, config))
+            .collect();
+        Ok(ProcessingResult {
+            stats: ProcessingStats {
+                total_records: records.len(),
+                total_tokens,
+                processing_time_ms: 0,
+            },
+            records,
+            exchanges,
+            source_file: file_path.to_string(),
+            processing_type: "summarize".to_string(),
+            error: None,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "summarize"
+    }
+
+    fn description(&self) -> &'static str {
+        "Hierarchical map-reduce summarization"
+    }
+}
+
+/// Prompt over the summaries in `group`, and the summaries alone
+fn combined_prompt(instruction: &str, records: &[Record], group: &[usize]) -> (String, String) {
+    let parts: Vec<&str> = group.iter().map(|&i| records[i].completion.as_str()).collect();
+    let input = parts.join("\n\n");
+    (format!("{}\n\n{}", instruction, input), input)
+}
+
+/// Adds a summary of `input` sent as `prompt` and returns its index
+fn push_summary(records: &mut Vec<Record>, level: usize, prompt: String, input: &str, children: &[usize]) -> usize {
+    let parent_ids: Vec<String> = children.iter()
+        .filter_map(|&i| records[i].metadata.get(RECORD_ID)?.as_str().map(str::to_string))
+        .collect();
+    let mut metadata = HashMap::from([
+        (STAGE.to_string(), serde_json::Value::from("summarize")),
+        (LEVEL.to_string(), serde_json::Value::from(level)),
+    ]);
+    // Leaves are linked to the source record they summarize, if any, once made
+    if !parent_ids.is_empty() {
+        metadata.insert(PARENT_IDS.to_string(), parent_ids.into());
+    }
+
+    let instruction = prompt.lines().next().unwrap_or_default().to_string();
+    let mut record = Record {
+        instruction,
+        completion: mock_summary(input),
+        prompt,
+        metadata,
+    };
+    let id = lineage::content_id(&record);
+    record.metadata.insert(RECORD_ID.to_string(), id.into());
+    records.push(record);
+    records.len() - 1
+}
+
+/// Stand-in for the model call: the opening words of the input
+fn mock_summary(input: &str) -> String {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    if words.len() <= 30 {
+        words.join(" ")
+    } else {
+        format!("{}…", words[..30].join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunking::ChunkSettings;
+    use crate::lineage::{PARENT_IDS, RECORD_ID};
+    use crate::processors::{test_config, Processor, ProcessorConfig};
+    use crate::summarize::{MapReduceProcessor, MapReduceSettings, LEVEL};
+
+    #[test]
+    fn test_map_reduce_levels() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("long.txt");
+        let words: Vec<String> = (1..=100).map(|i| format!("w{}", i)).collect();
+        std::fs::write(&path, words.join(" "))?;
+
+        let config = ProcessorConfig {
+            chunking: Some(ChunkSettings { size_words: 10, overlap_words: 2, ..Default::default() }),
+            ..test_config()
+        };
+        let processor = MapReduceProcessor { settings: MapReduceSettings { fan_in: 3, max_depth: 1 } };
+        let result = processor.process_file(path.to_str().unwrap(), &config)?;
+
+        // 10 streszczeń fragmentów, 4 pośrednie (grupy po 3), 1 końcowe
+        let level = |l: u64| result.records.iter().filter(|r| r.metadata[LEVEL].as_u64() == Some(l)).count();
+        assert_eq!((level(0), level(1), level(2)), (10, 4, 1));
+        assert_eq!(result.exchanges.len(), 15);
+
+        let root = result.records.last().unwrap();
+        assert_eq!(root.metadata[PARENT_IDS].as_array().unwrap().len(), 4);
+        let first_group = &result.records[10];
+        let leaf_ids: Vec<_> = result.records[..3].iter().map(|r| r.metadata[RECORD_ID].clone()).collect();
+        assert_eq!(first_group.metadata[PARENT_IDS].as_array().unwrap(), &leaf_ids);
+        Ok(())
+    }
+}
@@ -0,0 +1,386 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+use crate::audit::ModelExchange;
+use crate::preview;
+use crate::processors::{ProcessingResult, ProcessingStats, ProcessorConfig, Record};
+use crate::qa;
+use crate::templating::{self, TemplateContext};
+
+/// Record metadata: topic the record was generated for
+pub const TOPIC: &str = "topic";
+/// Record metadata: angle the model was asked to take on the topic
+pub const ANGLE: &str = "angle";
+
+/// Generation without input files, from the `[synthetic]` config section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyntheticSettings {
+    #[serde(default = "default_records_per_topic")]
+    pub records_per_topic: usize,
+    /// Prompt of each request: `{{topic}}`, `{{angle}}`, `{{lang}}` and the variables of a seed
+    #[serde(default = "default_template")]
+    pub template: String,
+    /// Angles a topic is approached from, drawn in a shuffled round-robin so records of one
+    /// topic don't all ask the same thing
+    #[serde(default = "default_angles")]
+    pub angles: Vec<String>,
+    /// Records sharing this part of their question's word pairs (0-1) with an earlier one are dropped
+    #[serde(default = "default_max_similarity")]
+    pub max_similarity: f64,
+    /// Requests per wanted record before a topic gives up on duplicates
+    #[serde(default = "default_attempts_per_record")]
+    pub attempts_per_record: usize,
+    /// Changes the order angles are drawn in; the same seed gives the same dataset
+    #[serde(default)]
+    pub seed: u64,
+}
+
+fn default_records_per_topic() -> usize {
+    10
+}
+
+fn default_template() -> String {
+    "Write one question about {{topic}} from the angle of {{angle}}, and answer it, in {{lang}}. \
+     Don't repeat questions a reader would ask first. Use this format:\n\
+     Question: ...\nAnswer: ..."
+        .to_string()
+}
+
+fn default_angles() -> Vec<String> {
+    ["a beginner", "an expert", "a common mistake", "a practical example", "a comparison", "an edge case", "its history", "a how-to"]
+        .map(str::to_string)
+        .to_vec()
+}
+
+fn default_max_similarity() -> f64 {
+    0.8
+}
+
+fn default_attempts_per_record() -> usize {
+    3
+}
+
+impl Default for SyntheticSettings {
+    fn default() -> Self {
+        Self {
+            records_per_topic: default_records_per_topic(),
+            template: default_template(),
+            angles: default_angles(),
+            max_similarity: default_max_similarity(),
+            attempts_per_record: default_attempts_per_record(),
+            seed: 0,
+        }
+    }
+}
+
+/// Topic to generate records for
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Seed {
+    pub topic: String,
+    /// Records wanted for this topic (default: `records_per_topic`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+    /// Further template variables, e.g. `audience` for `{{audience}}`
+    #[serde(flatten)]
+    pub variables: BTreeMap<String, String>,
+}
+
+impl Seed {
+    pub fn new(topic: impl Into<String>) -> Self {
+        Self { topic: topic.into(), ..Default::default() }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SeedEntry {
+    Topic(String),
+    Seed(Seed),
+}
+
+/// Seeds of a file: a YAML or JSON list of topics or `{topic, count, <variable>...}` maps,
+/// or plain text with a topic per line (`#` starts a comment)
+pub fn load_seeds(path: &Path) -> Result<Vec<Seed>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let structured = matches!(
+        path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref(),
+        Some("yaml" | "yml" | "json")
+    );
+    parse_seeds(&content, structured).with_context(|| format!("Invalid seed file {}", path.display()))
+}
+
+pub fn parse_seeds(content: &str, structured: bool) -> Result<Vec<Seed>> {
+    let seeds: Vec<Seed> = if structured {
+        serde_yaml::from_str::<Vec<SeedEntry>>(content)?
+            .into_iter()
+            .map(|entry| match entry {
+                SeedEntry::Topic(topic) => Seed::new(topic),
+                SeedEntry::Seed(seed) => seed,
+            })
+            .collect()
+    } else {
+        content.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(Seed::new)
+            .collect()
+    };
+    if let Some(seed) = seeds.iter().find(|seed| seed.topic.trim().is_empty()) {
+        anyhow::bail!("Seed without a topic: {:?}", seed);
+    }
+    Ok(seeds)
+}
+
+/// Records of a generation and what kept it from giving more
+#[derive(Debug, Clone)]
+pub struct Generation {
+    pub result: ProcessingResult,
+    /// Records dropped as duplicates of earlier ones
+    pub duplicates: usize,
+    /// Topics that ran out of attempts, with the records they got
+    pub short: Vec<(String, usize)>,
+}
+
+/// Generates records for every seed. `source` names the seeds in the result and audit log.
+pub fn generate(seeds: &[Seed], settings: &SyntheticSettings, config: &ProcessorConfig, source: &str) -> Result<Generation> {
+    if seeds.is_empty() {
+        anyhow::bail!("No topics to generate records for");
+    }
+    let angles = if settings.angles.is_empty() { vec![String::new()] } else { settings.angles.clone() };
+    let mut dedup = Dedup::default();
+    let mut records = Vec::new();
+    let mut prompts = Vec::new();
+    let mut duplicates = 0;
+    let mut short = Vec::new();
+
+    for seed in seeds {
+        let wanted = seed.count.unwrap_or(settings.records_per_topic);
+        let mut kept = 0;
+        let mut order = Vec::new();
+        for attempt in 0..wanted * settings.attempts_per_record.max(1) {
+            if kept == wanted {
+                break;
+            }
+            // Every pass over the angles draws them in a new order
+            let pass = attempt / angles.len();
+            if attempt % angles.len() == 0 {
+                order = preview::shuffled(angles.len(), mix(settings.seed, &seed.topic, pass));
+            }
+            let angle = &angles[order[attempt % angles.len()]];
+
+            let mut ctx = TemplateContext::default().with_variables(&seed.variables);
+            ctx.set("topic", seed.topic.as_str());
+            ctx.set("angle", angle.as_str());
+            ctx.set("lang", config.language.as_str());
+            let prompt = templating::render(&settings.template, &ctx);
+            let Some(pair) = qa::parse_pairs(&mock_output(&seed.topic, angle, pass)).into_iter().next() else {
+                continue;
+            };
+            if !dedup.admit(&pair.question, settings.max_similarity) {
+                duplicates += 1;
+                continue;
+            }
+
+            let mut metadata: HashMap<String, serde_json::Value> = seed.variables.iter()
+                .map(|(name, value)| (name.clone(), value.clone().into()))
+                .collect();
+            metadata.insert(TOPIC.to_string(), seed.topic.clone().into());
+            if !angle.is_empty() {
+                metadata.insert(ANGLE.to_string(), angle.clone().into());
+            }
+            records.push(Record {
+                instruction: "Answer the question".to_string(),
+                prompt: pair.question,
+                completion: pair.answer,
+                metadata,
+            });
+            prompts.push(prompt);
+            kept += 1;
+        }
+        if kept < wanted {
+            short.push((seed.topic.clone(), kept));
+        }
+    }
+
+    let total_tokens = records.iter()
+        .map(|r| r.prompt.split_whitespace().count() + r.completion.split_whitespace().count())
+        .sum();
+    // The log keeps the generation prompt, not the question the record ends up with
+    let exchanges = records.iter_mut().zip(prompts)
+        .map(|(record, prompt)| ModelExchange { prompt, ..ModelExchange::for_record(record, source, config) })
+        .collect();
+    let result = ProcessingResult {
+        stats: ProcessingStats {
+            total_records: records.len(),
+            total_tokens,
+            processing_time_ms: 0,
+        },
+        records,
+        exchanges,
+        source_file: source.to_string(),
+        processing_type: "synthetic".to_string(),
+        error: None,
+    };
+    Ok(Generation { result, duplicates, short })
+}
+
+/// Questions accepted so far, by their normalized word pairs
+#[derive(Debug, Default)]
+struct Dedup {
+    exact: HashSet<String>,
+    seen: Vec<BTreeSet<String>>,
+}
+
+impl Dedup {
+    /// Whether `text` is new enough to keep; it is remembered if so
+    fn admit(&mut self, text: &str, max_similarity: f64) -> bool {
+        let words = words(text);
+        let key = words.iter().cloned().collect::<Vec<_>>().join("|");
+        if self.exact.contains(&key) || self.seen.iter().any(|seen| jaccard(seen, &words) >= max_similarity) {
+            return false;
+        }
+        self.exact.insert(key);
+        self.seen.push(words);
+        true
+    }
+}
+
+/// Word pairs of a text (a lone word stands for itself), lowercased and without punctuation;
+/// pairs tell apart questions that share most of their words in another order
+fn words(text: &str) -> BTreeSet<String> {
+    let words: Vec<String> = text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.len() < 2 {
+        return words.into_iter().collect();
+    }
+    words.windows(2).map(|pair| pair.join(" ")).collect()
+}
+
+/// Shared part of the word pairs of two texts: 1 for the same text, 0 for nothing in common
+pub fn similarity(a: &str, b: &str) -> f64 {
+    jaccard(&words(a), &words(b))
+}
+
+fn jaccard(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Seed of a topic's pass over the angles; FNV-1a of the topic mixed with the configured seed
+fn mix(seed: u64, topic: &str, pass: usize) -> u64 {
+    let hash = topic.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3));
+    hash ^ seed.rotate_left(17) ^ (pass as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
+
+/// Mock model call: a question in one of a few phrasings, which repeat after the third pass
+/// the way a model runs out of new ideas
+fn mock_output(topic: &str, angle: &str, pass: usize) -> String {
+    let question = match pass % 3 {
+        0 => format!("What is worth knowing about {} from the angle of {}?", topic, angle),
+        1 => format!("How would you explain {} with {} in mind?", topic, angle),
+        _ => format!("Which question about {} does {} raise first?", topic, angle),
+    };
+    format!("Question: {}\nAnswer: {} seen through {}: a synthetic answer.\n", question, topic, angle)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::processors::{test_config, ProcessorConfig};
+    use crate::synthetic::{generate, load_seeds, parse_seeds, similarity, Seed, SyntheticSettings, ANGLE, TOPIC};
+    use std::collections::HashSet;
+
+    fn config() -> ProcessorConfig {
+        ProcessorConfig { language: "pl".to_string(), ..test_config() }
+    }
+
+    #[test]
+    fn test_parse_seeds() -> anyhow::Result<()> {
+        let seeds = parse_seeds("# tematy\nprawo pracy\n\n  podatki  \n", false)?;
+        assert_eq!(seeds, vec![Seed::new("prawo pracy"), Seed::new("podatki")]);
+
+        let seeds = parse_seeds("- prawo pracy\n- topic: podatki\n  count: 2\n  audience: studenci\n", true)?;
+        assert_eq!(seeds[0], Seed::new("prawo pracy"));
+        assert_eq!(seeds[1].count, Some(2));
+        assert_eq!(seeds[1].variables.get("audience").map(String::as_str), Some("studenci"));
+        assert!(parse_seeds("- topic: ''\n", true).is_err());
+
+        // JSON też jest poprawnym YAML-em
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("seeds.json");
+        std::fs::write(&path, r#"[{"topic": "RODO"}, "umowy"]"#)?;
+        assert_eq!(load_seeds(&path)?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_with_diverse_angles() -> anyhow::Result<()> {
+        let settings = SyntheticSettings {
+            records_per_topic: 4,
+            template: "{{topic}} / {{angle}} / {{audience}} / {{lang}}".to_string(),
+            ..Default::default()
+        };
+        let mut seed = Seed::new("prawo pracy");
+        seed.variables.insert("audience".to_string(), "studenci".to_string());
+        let seeds = vec![seed, Seed { count: Some(2), ..Seed::new("podatki") }];
+        let generation = generate(&seeds, &settings, &config(), "seeds.txt")?;
+        let records = &generation.result.records;
+
+        assert_eq!(records.len(), 6);
+        assert_eq!(generation.result.processing_type, "synthetic");
+        assert!(generation.short.is_empty());
+        assert_eq!(records.iter().filter(|r| r.metadata[TOPIC] == "podatki").count(), 2);
+        assert_eq!(records[0].metadata["audience"], "studenci");
+        // Każdy rekord tematu dostaje inne ujęcie
+        let angles: HashSet<&str> = records[..4].iter().filter_map(|r| r.metadata[ANGLE].as_str()).collect();
+        assert_eq!(angles.len(), 4);
+        // Dziennik wywołań zawiera wypełniony szablon
+        let prompt = &generation.result.exchanges[0].prompt;
+        assert!(prompt.starts_with("prawo pracy / ") && prompt.ends_with(" / studenci / pl"), "{}", prompt);
+
+        // Ten sam seed daje ten sam zbiór
+        let again = generate(&seeds, &settings, &config(), "seeds.txt")?;
+        let questions = |records: &[crate::processors::Record]| records.iter().map(|r| r.prompt.clone()).collect::<Vec<_>>();
+        assert_eq!(questions(records), questions(&again.result.records));
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_drops_duplicates() -> anyhow::Result<()> {
+        // Jedno ujęcie: model powtarza się po trzech sformułowaniach
+        let settings = SyntheticSettings {
+            records_per_topic: 5,
+            angles: vec!["a beginner".to_string()],
+            attempts_per_record: 2,
+            ..Default::default()
+        };
+        let generation = generate(&[Seed::new("RODO")], &settings, &config(), "topics")?;
+        assert_eq!(generation.result.records.len(), 3);
+        assert_eq!(generation.duplicates, 7);
+        assert_eq!(generation.short, vec![("RODO".to_string(), 3)]);
+
+        // Próg zero odrzuca wszystko po pierwszym pytaniu
+        let strict = SyntheticSettings { max_similarity: 0.0, ..settings };
+        let generation = generate(&[Seed::new("RODO")], &strict, &config(), "topics")?;
+        assert_eq!(generation.result.records.len(), 1);
+
+        assert!(generate(&[], &SyntheticSettings::default(), &config(), "topics").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("Co to jest RODO?", "co to jest rodo"), 1.0);
+        assert_eq!(similarity("prawo pracy", "podatki dochodowe"), 0.0);
+        assert!((similarity("a b c", "a b d") - 1.0 / 3.0).abs() < 1e-9);
+        // Te same słowa w innej kolejności to inne pytanie
+        assert!(similarity("kto płaci podatek", "podatek płaci kto") < 0.5);
+    }
+}
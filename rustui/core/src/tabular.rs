@@ -0,0 +1,348 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::atomic;
+use crate::extract::{self, FieldType, RecordSchema, SchemaField};
+use crate::lineage;
+use crate::processors::{ProcessingResult, Record};
+use crate::report::csv_field;
+
+/// Record fields every dataset has; metadata keys are `metadata.<key>`, fields of
+/// JSON object completions (structured extraction) `completion.<key>`
+pub const RECORD_FIELDS: [&str; 3] = ["instruction", "prompt", "completion"];
+
+/// Column source of the record id
+const ID_SOURCE: &str = "metadata.record_id";
+
+/// Dataset field exported as a table column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Column {
+    /// `instruction`, `prompt`, `completion`, `metadata.<key>` or `completion.<key>`
+    pub source: String,
+    /// Column header (default: the last part of `source`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "type", default = "default_type")]
+    pub field_type: FieldType,
+}
+
+fn default_type() -> FieldType {
+    FieldType::String
+}
+
+impl Column {
+    pub fn new(source: &str) -> Self {
+        Self { source: source.to_string(), name: None, field_type: FieldType::String }
+    }
+
+    pub fn header(&self) -> &str {
+        self.name.as_deref()
+            .unwrap_or_else(|| self.source.rsplit('.').next().unwrap_or(&self.source))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TableFormat {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+impl TableFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TableFormat::Csv => "csv",
+            TableFormat::Parquet => "parquet",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            TableFormat::Csv => TableFormat::Parquet,
+            TableFormat::Parquet => TableFormat::Csv,
+        }
+    }
+}
+
+impl fmt::Display for TableFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+/// Every field found in the dataset's records, record fields first
+pub fn sources(results: &[ProcessingResult]) -> Vec<String> {
+    let records = || results.iter().flat_map(|r| &r.records);
+    let metadata: BTreeSet<String> = records()
+        .flat_map(|record| record.metadata.keys())
+        .map(|key| format!("metadata.{}", key))
+        .collect();
+    let completion: BTreeSet<String> = records()
+        .filter_map(|record| match serde_json::from_str(&record.completion) {
+            Ok(Value::Object(object)) => Some(object),
+            _ => None,
+        })
+        .flat_map(|object| object.into_iter().map(|(key, _)| format!("completion.{}", key)))
+        .collect();
+    RECORD_FIELDS.iter().map(|f| f.to_string())
+        .chain(metadata)
+        .chain(completion)
+        .collect()
+}
+
+/// Layout used when a preset has no saved mapping: the record id and the record fields
+pub fn default_columns() -> Vec<Column> {
+    std::iter::once(ID_SOURCE).chain(RECORD_FIELDS).map(Column::new).collect()
+}
+
+fn lookup(record: &Record, source: &str) -> Value {
+    match source.split_once('.') {
+        // Records of older datasets get the id they'd have now
+        Some(("metadata", lineage::RECORD_ID)) => Value::from(lineage::id_of(record)),
+        Some(("metadata", key)) => record.metadata.get(key).cloned().unwrap_or(Value::Null),
+        Some(("completion", key)) => match serde_json::from_str::<Value>(&record.completion) {
+            Ok(Value::Object(mut object)) => object.remove(key).unwrap_or(Value::Null),
+            _ => Value::Null,
+        },
+        _ => match source {
+            "instruction" => Value::from(record.instruction.clone()),
+            "prompt" => Value::from(record.prompt.clone()),
+            "completion" => Value::from(record.completion.clone()),
+            _ => Value::Null,
+        },
+    }
+}
+
+/// One row per record keyed by column header. Values that don't convert to the column's
+/// type are left empty; lists and objects in string columns are kept as JSON text.
+pub fn rows(results: &[ProcessingResult], columns: &[Column]) -> Vec<Map<String, Value>> {
+    results.iter()
+        .flat_map(|result| &result.records)
+        .map(|record| {
+            columns.iter()
+                .map(|column| {
+                    let value = lookup(record, &column.source);
+                    let typed = match (&value, column.field_type) {
+                        (Value::Array(_) | Value::Object(_), FieldType::String) => Some(Value::from(value.to_string())),
+                        (Value::Null, _) => None,
+                        _ => extract::convert(&value, column.field_type),
+                    };
+                    (column.header().to_string(), typed.unwrap_or(Value::Null))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Writes the dataset as a table with the given columns; returns the number of rows
+pub fn export(path: &Path, results: &[ProcessingResult], columns: &[Column], format: TableFormat) -> Result<usize> {
+    if columns.is_empty() {
+        anyhow::bail!("No columns selected");
+    }
+    let mut headers = BTreeSet::new();
+    if let Some(column) = columns.iter().find(|c| !headers.insert(c.header())) {
+        anyhow::bail!("Duplicate column {}; give one of them a different name", column.header());
+    }
+    let rows = rows(results, columns);
+    let content = match format {
+        TableFormat::Csv => to_csv(columns, &rows).into_bytes(),
+        TableFormat::Parquet => {
+            let schema = RecordSchema {
+                description: None,
+                fields: columns.iter()
+                    .map(|column| SchemaField {
+                        name: column.header().to_string(),
+                        field_type: column.field_type,
+                        description: String::new(),
+                        required: false,
+                    })
+                    .collect(),
+            };
+            extract::to_parquet(&schema, &rows)?
+        },
+    };
+    atomic::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(rows.len())
+}
+
+fn to_csv(columns: &[Column], rows: &[Map<String, Value>]) -> String {
+    let header: Vec<String> = columns.iter().map(|c| csv_field(c.header())).collect();
+    let mut out = header.join(",");
+    out.push('\n');
+    for row in rows {
+        let fields: Vec<String> = columns.iter()
+            .map(|column| match row.get(column.header()) {
+                Some(Value::String(s)) => csv_field(s),
+                Some(Value::Null) | None => String::new(),
+                Some(other) => other.to_string(),
+            })
+            .collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Column-mapping popup: every field of the dataset, the included ones in export order
+#[derive(Debug, Clone)]
+pub struct ColumnEditor {
+    /// Preset the mapping is saved to
+    pub preset: String,
+    pub dataset: PathBuf,
+    pub results: Vec<ProcessingResult>,
+    pub entries: Vec<(Column, bool)>,
+    pub selected: usize,
+    pub format: TableFormat,
+}
+
+impl ColumnEditor {
+    /// Saved columns come first and included; the dataset's other fields follow, left out.
+    /// Without a saved mapping the record fields are included.
+    pub fn open(preset: &str, dataset: &Path, results: Vec<ProcessingResult>, saved: &[Column]) -> Self {
+        let included = if saved.is_empty() { default_columns() } else { saved.to_vec() };
+        let mut entries: Vec<(Column, bool)> = included.into_iter().map(|c| (c, true)).collect();
+        for source in sources(&results) {
+            if !entries.iter().any(|(c, _)| c.source == source) {
+                entries.push((Column::new(&source), false));
+            }
+        }
+        Self {
+            preset: preset.to_string(),
+            dataset: dataset.to_path_buf(),
+            results,
+            entries,
+            selected: 0,
+            format: TableFormat::default(),
+        }
+    }
+
+    pub fn next(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected = (self.selected + 1) % self.entries.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected = (self.selected + self.entries.len() - 1) % self.entries.len();
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        if let Some((_, included)) = self.entries.get_mut(self.selected) {
+            *included = !*included;
+        }
+    }
+
+    pub fn cycle_type(&mut self) {
+        if let Some((column, _)) = self.entries.get_mut(self.selected) {
+            column.field_type = column.field_type.next();
+        }
+    }
+
+    /// Moves the selected field one place up (-1) or down (1), keeping it selected
+    pub fn move_selected(&mut self, offset: isize) {
+        let Some(target) = self.selected.checked_add_signed(offset).filter(|t| *t < self.entries.len()) else {
+            return;
+        };
+        self.entries.swap(self.selected, target);
+        self.selected = target;
+    }
+
+    /// Included columns in export order
+    pub fn columns(&self) -> Vec<Column> {
+        self.entries.iter().filter(|(_, included)| *included).map(|(c, _)| c.clone()).collect()
+    }
+
+    pub fn output(&self) -> PathBuf {
+        self.dataset.with_extension(self.format.extension())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::extract::FieldType;
+    use crate::processors::{ProcessingResult, ProcessingStats, Record};
+    use crate::tabular::{self, Column, ColumnEditor, TableFormat};
+    #[cfg(feature = "parquet")]
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    fn results() -> Vec<ProcessingResult> {
+        let record = |completion: &str, chunk: serde_json::Value| Record {
+            instruction: "Extract visits".to_string(),
+            prompt: "Rex, 31.5 kg".to_string(),
+            completion: completion.to_string(),
+            metadata: [("chunk".to_string(), chunk)].into_iter().collect(),
+        };
+        vec![ProcessingResult {
+            records: vec![
+                record(r#"{"summary": "Checkup, all fine", "weight_kg": 31.5}"#, serde_json::json!(0)),
+                record(r#"{"summary": "Vaccination", "weight_kg": "heavy"}"#, serde_json::json!("1")),
+            ],
+            source_file: "visit.txt".to_string(),
+            processing_type: "extract".to_string(),
+            stats: ProcessingStats { total_records: 2, total_tokens: 0, processing_time_ms: 0 },
+            exchanges: vec![],
+            error: None,
+        }]
+    }
+
+    #[test]
+    fn test_editor_lists_saved_columns_first() {
+        let saved = vec![Column { field_type: FieldType::Number, ..Column::new("completion.weight_kg") }];
+        let mut editor = ColumnEditor::open("extract", std::path::Path::new("visits.json"), results(), &saved);
+        let sources: Vec<&str> = editor.entries.iter().map(|(c, _)| c.source.as_str()).collect();
+        assert_eq!(sources, vec![
+            "completion.weight_kg", "instruction", "prompt", "completion", "metadata.chunk", "completion.summary",
+        ]);
+        assert_eq!(editor.columns(), saved);
+
+        editor.next();
+        editor.next();
+        editor.toggle();
+        editor.move_selected(-1);
+        editor.move_selected(-1);
+        // Pierwszego pola nie da się przesunąć wyżej
+        editor.move_selected(-1);
+        editor.cycle_type();
+        let headers: Vec<String> = editor.columns().iter().map(|c| format!("{}:{}", c.header(), c.field_type)).collect();
+        assert_eq!(headers, vec!["prompt:integer", "weight_kg:number"]);
+        assert_eq!(editor.output(), std::path::PathBuf::from("visits.csv"));
+    }
+
+    #[test]
+    fn test_export_csv_and_parquet() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let columns = vec![
+            Column::new("completion.summary"),
+            Column { field_type: FieldType::Number, ..Column::new("completion.weight_kg") },
+            Column { name: Some("part".to_string()), field_type: FieldType::Integer, ..Column::new("metadata.chunk") },
+        ];
+
+        let csv = dir.path().join("visits.csv");
+        assert_eq!(tabular::export(&csv, &results(), &columns, TableFormat::Csv)?, 2);
+        // Wartości niezgodne z typem kolumny zostają puste
+        assert_eq!(
+            std::fs::read_to_string(&csv)?,
+            "summary,weight_kg,part\n\"Checkup, all fine\",31.5,0\nVaccination,,1\n",
+        );
+
+        let parquet = dir.path().join("visits.parquet");
+        #[cfg(feature = "parquet")]
+        {
+            tabular::export(&parquet, &results(), &columns, TableFormat::Parquet)?;
+            let reader = SerializedFileReader::new(std::fs::File::open(&parquet)?)?;
+            assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        }
+        #[cfg(not(feature = "parquet"))]
+        assert!(tabular::export(&parquet, &results(), &columns, TableFormat::Parquet).is_err());
+
+        let duplicate = vec![Column::new("completion.summary"), Column::new("metadata.summary")];
+        assert!(tabular::export(&csv, &results(), &duplicate, TableFormat::Csv).is_err());
+        Ok(())
+    }
+}
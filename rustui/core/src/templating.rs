@@ -0,0 +1,127 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Values available to `{{variable}}` placeholders in prompts and output paths
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    vars: HashMap<String, String>,
+}
+
+impl TemplateContext {
+    /// Builds the context for a source file: `file_stem`, `file_name`, `ext`,
+    /// `dir_name`, `date` and `lang`.
+    pub fn from_source(path: &Path, language: &str) -> Self {
+        let mut ctx = Self::default();
+        let name = |s: Option<&std::ffi::OsStr>| s.map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+
+        ctx.set("file_stem", name(path.file_stem()));
+        ctx.set("file_name", name(path.file_name()));
+        ctx.set("ext", name(path.extension()));
+        ctx.set("dir_name", name(path.parent().and_then(|p| p.file_name())));
+        ctx.set("date", chrono::Local::now().format("%Y-%m-%d").to_string());
+        ctx.set("lang", language);
+        ctx
+    }
+
+    /// Adds profile variables; the built-in ones keep their values
+    pub fn with_variables(mut self, variables: &BTreeMap<String, String>) -> Self {
+        for (name, value) in variables {
+            self.vars.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+        self
+    }
+
+    pub fn set(&mut self, key: &str, value: impl Into<String>) {
+        self.vars.insert(key.to_string(), value.into());
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.vars.get(key).map(|v| v.as_str())
+    }
+}
+
+/// Replaces `{{name}}` placeholders with values from the context.
+/// Unknown placeholders are left untouched so typos stay visible in the output.
+pub fn render(template: &str, ctx: &TemplateContext) -> String {
+    render_delimited(template, ctx, "{{", "}}")
+}
+
+/// Same as [`render`] but for the shorter `{name}` syntax used in output file names
+pub fn render_path(template: &str, ctx: &TemplateContext) -> String {
+    render_delimited(template, ctx, "{", "}")
+}
+
+fn render_delimited(template: &str, ctx: &TemplateContext, open: &str, close: &str) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find(open) {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + open.len()..];
+        match after.find(close) {
+            Some(end) => {
+                let key = after[..end].trim();
+                match ctx.get(key) {
+                    Some(value) => output.push_str(value),
+                    None => output.push_str(&rest[start..start + open.len() + end + close.len()]),
+                }
+                rest = &after[end + close.len()..];
+            },
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            },
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::HistoryEntry;
+    use crate::manifest::DatasetManifest;
+    use crate::templating::{render, TemplateContext};
+    use std::path::Path;
+
+    #[test]
+    fn test_render_source_variables() {
+        let ctx = TemplateContext::from_source(Path::new("/data/clinic/report.pdf"), "pl");
+
+        assert_eq!(render("{{file_stem}}.{{ext}} in {{dir_name}} ({{lang}})", &ctx), "report.pdf in clinic (pl)");
+        assert_eq!(render("{{ date }}", &ctx), chrono::Local::now().format("%Y-%m-%d").to_string());
+
+        // Nieznane i niedomknięte zmienne zostają bez zmian
+        assert_eq!(render("{{unknown}} {{file_stem", &ctx), "{{unknown}} {{file_stem");
+    }
+
+    #[test]
+    fn test_manifest_records_variables() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let dataset = dir.path().join("wyniki.json");
+        std::fs::write(&dataset, "[]")?;
+        let mut entry = HistoryEntry {
+            job_id: "job-1".to_string(),
+            file: "ulotka.md".to_string(),
+            processing_type: "standard".to_string(),
+            provider: "p".to_string(),
+            model: "m".to_string(),
+            status: "completed".to_string(),
+            submitted_at: chrono::Utc::now(),
+            finished_at: None,
+            records: None,
+            tokens: None,
+            cost_usd: None,
+            variables: Default::default(),
+        };
+        let json = serde_json::to_value(DatasetManifest::for_dataset(&dataset, &entry)?)?;
+        assert!(json.get("variables").is_none());
+
+        entry.variables.insert("clinic_name".to_string(), "Klinika Główna".to_string());
+        // Wpis historii zachowuje zmienne do czasu pobrania wyników
+        let entry: HistoryEntry = serde_json::from_str(&serde_json::to_string(&entry)?)?;
+        let json = serde_json::to_value(DatasetManifest::for_dataset(&dataset, &entry)?)?;
+        assert_eq!(json["variables"], serde_json::json!({ "clinic_name": "Klinika Główna" }));
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+/// Shortest word checked for misspellings; shorter words are too often a letter apart by chance
+const MIN_WORD_LEN: usize = 5;
+/// Shortest term ending that counts as typical of the domain (e.g. `-mycin`, `-azole`)
+const SUFFIX_LEN: usize = 5;
+/// Terms that must share an ending for it to count as typical
+const MIN_SUFFIX_TERMS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TermIssue {
+    /// Close to a known term but not spelled like it
+    Mangled { found: String, expected: String },
+    /// Looks like a domain term (typical ending) but isn't in the list
+    Unknown { found: String },
+}
+
+impl std::fmt::Display for TermIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TermIssue::Mangled { found, expected } => write!(f, "'{}' looks like a misspelling of '{}'", found, expected),
+            TermIssue::Unknown { found } => write!(f, "'{}' is not in the terminology list", found),
+        }
+    }
+}
+
+/// User-supplied domain terms, e.g. drug names or anatomical terms
+#[derive(Debug, Clone, Default)]
+pub struct Terminology {
+    /// Lowercase term to the term as listed, grouped by number of words
+    terms: BTreeMap<usize, HashMap<String, String>>,
+    suffixes: BTreeSet<String>,
+}
+
+impl Terminology {
+    /// One term per line; empty lines and lines starting with `#` are skipped
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read terminology list {}", path.display()))?;
+        Ok(Self::from_terms(content.lines()))
+    }
+
+    pub fn from_terms<'a>(terms: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut terminology = Self::default();
+        let mut endings: HashMap<String, usize> = HashMap::new();
+        for term in terms.into_iter().map(str::trim).filter(|t| !t.is_empty() && !t.starts_with('#')) {
+            let lower = term.to_lowercase();
+            let words = lower.split_whitespace().count();
+            if words == 1 && lower.chars().count() > SUFFIX_LEN + 1 {
+                let ending: String = lower.chars().rev().take(SUFFIX_LEN).collect::<Vec<_>>().into_iter().rev().collect();
+                *endings.entry(ending).or_default() += 1;
+            }
+            terminology.terms.entry(words).or_default().insert(lower, term.to_string());
+        }
+        terminology.suffixes = endings.into_iter()
+            .filter(|(_, count)| *count >= MIN_SUFFIX_TERMS)
+            .map(|(ending, _)| ending)
+            .collect();
+        terminology
+    }
+
+    /// Problems with the terms used in `completion`. Words that also appear in `source`
+    /// are taken as correct: the model copied them.
+    pub fn check(&self, completion: &str, source: &str) -> Vec<TermIssue> {
+        let source = words(source).join(" ");
+        let tokens = words(completion);
+        let mut issues = Vec::new();
+        let mut reported = BTreeSet::new();
+
+        for (&size, terms) in &self.terms {
+            for window in tokens.windows(size) {
+                let candidate = window.join(" ");
+                if terms.contains_key(&candidate)
+                    || candidate.chars().count() < MIN_WORD_LEN
+                    || contains_phrase(&source, &candidate)
+                    || reported.contains(&candidate)
+                {
+                    continue;
+                }
+                if let Some(expected) = closest(terms, &candidate) {
+                    reported.insert(candidate.clone());
+                    issues.push(TermIssue::Mangled { found: candidate, expected });
+                }
+            }
+        }
+
+        let single_terms = self.terms.get(&1);
+        for token in &tokens {
+            let known = single_terms.is_some_and(|terms| terms.contains_key(token));
+            let typical = self.suffixes.iter().any(|suffix| token.ends_with(suffix.as_str()) && token.len() > suffix.len());
+            if typical && !known && !reported.contains(token) && !contains_phrase(&source, token) {
+                reported.insert(token.clone());
+                issues.push(TermIssue::Unknown { found: token.clone() });
+            }
+        }
+        issues
+    }
+}
+
+/// Lowercase words, keeping inner hyphens (`beta-lactam`)
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '-'))
+        .map(|w| w.trim_matches('-').to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+fn contains_phrase(text: &str, phrase: &str) -> bool {
+    text.split(' ').collect::<Vec<_>>()
+        .windows(phrase.split(' ').count())
+        .any(|window| window.join(" ") == phrase)
+}
+
+/// The term `candidate` is most likely a misspelling of, if any. Plurals don't count.
+fn closest(terms: &HashMap<String, String>, candidate: &str) -> Option<String> {
+    let len = candidate.chars().count();
+    let allowed = if len < 8 { 1 } else { 2 };
+    terms.iter()
+        .filter(|(term, _)| {
+            let plural = [candidate.strip_suffix('s'), candidate.strip_suffix("es")].into_iter()
+                .flatten()
+                .any(|singular| singular == term.as_str());
+            !plural && term.chars().count().abs_diff(len) <= allowed
+        })
+        .map(|(term, original)| (levenshtein(term, candidate), original))
+        .filter(|(distance, _)| *distance > 0 && *distance <= allowed)
+        .min_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(b.1)))
+        .map(|(_, original)| original.clone())
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checks::{summary, CheckSettings, Checks, ISSUES};
+    use crate::processors::{ProcessingResult, ProcessingStats, Record};
+    use crate::terminology::{TermIssue, Terminology};
+
+    const TERMS: &str = "# leki\namoxicillin\nampicillin\ncloxacillin\nenrofloxacin\nmeloxicam\ncranial cruciate ligament\n";
+
+    #[test]
+    fn test_mangled_and_unknown_terms() {
+        let terminology = Terminology::from_terms(TERMS.lines());
+        let issues = terminology.check(
+            "Give amoxicilin and meloxicam; consider tylocillin. Check the cranial cruciate ligamant. Tablets of Enrofloxacin.",
+            "Dog with lameness.",
+        );
+        assert_eq!(issues, vec![
+            TermIssue::Mangled { found: "amoxicilin".to_string(), expected: "amoxicillin".to_string() },
+            TermIssue::Mangled { found: "cranial cruciate ligamant".to_string(), expected: "cranial cruciate ligament".to_string() },
+            TermIssue::Unknown { found: "tylocillin".to_string() },
+        ]);
+
+        // Słowa skopiowane ze źródła nie są oznaczane
+        assert!(terminology.check("Give tylocillin.", "The owner gave tylocillin yesterday.").is_empty());
+    }
+
+    #[test]
+    fn test_checks_flag_records() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let terms = dir.path().join("terms.txt");
+        std::fs::write(&terms, TERMS)?;
+        let checks = Checks::load(&CheckSettings { terminology: Some(terms), ..Default::default() })?;
+
+        let record = |completion: &str| Record {
+            instruction: String::new(),
+            prompt: "Source".to_string(),
+            completion: completion.to_string(),
+            metadata: Default::default(),
+        };
+        let mut results = vec![ProcessingResult {
+            records: vec![record("Meloxicam is an NSAID."), record("Use enrofloxacine.")],
+            source_file: "notes.txt".to_string(),
+            processing_type: "standard".to_string(),
+            stats: ProcessingStats { total_records: 2, total_tokens: 0, processing_time_ms: 0 },
+            exchanges: vec![],
+            error: None,
+        }];
+        assert_eq!(checks.apply(&mut results), 1);
+        assert!(!results[0].records[0].metadata.contains_key(ISSUES));
+        assert_eq!(summary(&results), vec![
+            "#2:".to_string(),
+            "  [terminology] 'enrofloxacine' looks like a misspelling of 'enrofloxacin'".to_string(),
+        ]);
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+/// `[theme]` in config.toml: a palette and colors that replace its own
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThemeSettings {
+    /// A built-in palette or the name of a file in the themes directory
+    #[serde(default = "default_palette")]
+    pub palette: String,
+    /// Colors as names ("red", "lightblue"), "#rrggbb" or a 0-255 palette index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub muted: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chart: Option<Vec<String>>,
+    /// Box-drawing borders, block gauges and symbols; "auto" checks the terminal's locale
+    #[serde(default, skip_serializing_if = "Support::is_auto")]
+    pub unicode: Support,
+    /// Colors at all; "auto" honours `NO_COLOR` and dumb terminals
+    #[serde(default, skip_serializing_if = "Support::is_auto")]
+    pub color: Support,
+}
+
+/// Whether the UI uses a terminal feature: detected, or forced on or off
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Support {
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+impl Support {
+    pub fn is_auto(&self) -> bool {
+        *self == Support::Auto
+    }
+
+    /// The forced setting, else `detected`
+    pub fn resolve(self, detected: bool) -> bool {
+        match self {
+            Support::Auto => detected,
+            Support::On => true,
+            Support::Off => false,
+        }
+    }
+}
+
+fn default_palette() -> String {
+    "default".to_string()
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self {
+            palette: default_palette(),
+            text: None,
+            muted: None,
+            highlight: None,
+            accent: None,
+            success: None,
+            warning: None,
+            error: None,
+            chart: None,
+            unicode: Support::Auto,
+            color: Support::Auto,
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use crate::atomic;
+use crate::audit::ModelExchange;
+use crate::config::Config;
+use crate::credentials;
+use crate::ollama;
+
+/// Prefixes of provider API keys, hidden wherever they show up in a transcript
+const KEY_PREFIXES: [&str; 5] = ["sk-", "xai-", "gsk_", "pplx-", "hf_"];
+
+/// Shorter tokens with these prefixes are ordinary words, e.g. "sk-learn"
+const MIN_KEY_LEN: usize = 20;
+
+pub const REDACTED: &str = "[redacted]";
+
+/// `[transcript]` in config.toml: raw provider requests kept for debugging
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSettings {
+    /// Capture from startup; toggled with 't' on the Debug screen
+    #[serde(default)]
+    pub enabled: bool,
+    /// Exchanges kept per job, newest last
+    #[serde(default = "default_per_job")]
+    pub per_job: usize,
+}
+
+fn default_per_job() -> usize {
+    20
+}
+
+impl Default for TranscriptSettings {
+    fn default() -> Self {
+        Self { enabled: false, per_job: default_per_job() }
+    }
+}
+
+/// One model call as the provider would see it on the wire, with secrets hidden
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub job_id: String,
+    pub source_file: String,
+    pub provider: String,
+    pub model: String,
+    pub at: DateTime<Utc>,
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+    pub request: Value,
+    pub response: String,
+}
+
+impl TranscriptEntry {
+    /// `secrets` are hidden wherever they appear, e.g. a key pasted into a prompt
+    pub fn from_exchange(job_id: &str, exchange: &ModelExchange, config: &Config, secrets: &[String]) -> Self {
+        let (url, request) = request_for(exchange, config);
+        let mut headers = BTreeMap::from([("Content-Type".to_string(), "application/json".to_string())]);
+        if exchange.provider != ollama::PROVIDER {
+            let (name, _) = credentials::auth_header(&exchange.provider, "");
+            headers.insert(name.to_string(), REDACTED.to_string());
+        }
+        if exchange.provider == "anthropic" {
+            headers.insert("anthropic-version".to_string(), "2023-06-01".to_string());
+        }
+        Self {
+            job_id: job_id.to_string(),
+            source_file: exchange.source_file.clone(),
+            provider: exchange.provider.clone(),
+            model: exchange.model.clone(),
+            at: exchange.at,
+            url,
+            headers,
+            request: redact_value(request, secrets),
+            response: redact(&exchange.raw_response, secrets),
+        }
+    }
+
+    /// Request and response as shown on the Debug screen
+    pub fn render(&self) -> String {
+        let mut text = format!("POST {}\n", self.url);
+        for (name, value) in &self.headers {
+            text.push_str(&format!("{}: {}\n", name, value));
+        }
+        text.push('\n');
+        text.push_str(&serde_json::to_string_pretty(&self.request).unwrap_or_default());
+        text.push_str("\n\n--- response ---\n");
+        text.push_str(&self.response);
+        text
+    }
+}
+
+/// Endpoint and body of the chat call for the exchange's provider; custom providers speak
+/// the OpenAI API at their own base URL
+pub fn request_for(exchange: &ModelExchange, config: &Config) -> (String, Value) {
+    let system = exchange.system_prompt.as_deref().filter(|s| !s.trim().is_empty());
+    let user = json!({ "role": "user", "content": exchange.prompt });
+    let with_system = |mut messages: Vec<Value>| {
+        if let Some(system) = system {
+            messages.insert(0, json!({ "role": "system", "content": system }));
+        }
+        messages
+    };
+    match exchange.provider.as_str() {
+        "anthropic" => {
+            let mut body = json!({ "model": exchange.model, "max_tokens": 4096, "messages": [user] });
+            if let Some(system) = system {
+                body["system"] = Value::from(system);
+            }
+            ("https://api.anthropic.com/v1/messages".to_string(), body)
+        },
+        ollama::PROVIDER => (
+            format!("{}/api/chat", config.ollama.base_url.trim_end_matches('/')),
+            json!({ "model": exchange.model, "messages": with_system(vec![user]), "stream": false }),
+        ),
+        provider => (
+            config.custom_provider(provider)
+                .map(|custom| custom.url("chat/completions"))
+                .unwrap_or_else(|| "https://api.openai.com/v1/chat/completions".to_string()),
+            json!({ "model": exchange.model, "messages": with_system(vec![user]) }),
+        ),
+    }
+}
+
+/// `text` with the given secrets and anything shaped like a provider API key replaced
+pub fn redact(text: &str, secrets: &[String]) -> String {
+    let mut text = text.to_string();
+    for secret in secrets.iter().filter(|s| !s.trim().is_empty()) {
+        text = text.replace(secret.as_str(), REDACTED);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some((start, _)) = KEY_PREFIXES.iter().filter_map(|prefix| rest.find(prefix).map(|i| (i, prefix))).min() {
+        let len = rest[start..].find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_')).unwrap_or(rest.len() - start);
+        // Only whole tokens: "task-" or "risk-" inside a word isn't a key
+        let boundary = rest[..start].chars().last().is_none_or(|c| !c.is_ascii_alphanumeric());
+        out.push_str(&rest[..start]);
+        if boundary && len >= MIN_KEY_LEN {
+            out.push_str(REDACTED);
+        } else {
+            out.push_str(&rest[start..start + len]);
+        }
+        rest = &rest[start + len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn redact_value(value: Value, secrets: &[String]) -> Value {
+    match value {
+        Value::String(s) => Value::String(redact(&s, secrets)),
+        Value::Array(items) => Value::Array(items.into_iter().map(|v| redact_value(v, secrets)).collect()),
+        Value::Object(map) => Value::Object(map.into_iter().map(|(k, v)| (k, redact_value(v, secrets))).collect()),
+        other => other,
+    }
+}
+
+/// Last exchanges of each job, kept while the TUI runs
+#[derive(Debug, Clone, Default)]
+pub struct Transcripts {
+    pub enabled: bool,
+    per_job: usize,
+    jobs: BTreeMap<String, VecDeque<TranscriptEntry>>,
+}
+
+impl Transcripts {
+    pub fn new(settings: &TranscriptSettings) -> Self {
+        Self { enabled: settings.enabled, per_job: settings.per_job.max(1), jobs: BTreeMap::new() }
+    }
+
+    /// Keeps the last `per_job` of a job's exchanges; earlier captures of the job are replaced,
+    /// as a resumed run reports its previous exchanges again
+    pub fn capture(&mut self, job_id: &str, exchanges: &[ModelExchange], config: &Config) {
+        if !self.enabled || exchanges.is_empty() {
+            return;
+        }
+        let kept = &exchanges[exchanges.len().saturating_sub(self.per_job)..];
+        let providers: BTreeSet<&str> = kept.iter().map(|exchange| exchange.provider.as_str()).collect();
+        let secrets: Vec<String> = providers.into_iter()
+            .filter_map(|provider| match config.custom_provider(provider) {
+                Some(custom) => custom.api_key(provider),
+                None => credentials::get(provider).map(|(key, _)| key),
+            })
+            .collect();
+        let entries = kept.iter()
+            .map(|exchange| TranscriptEntry::from_exchange(job_id, exchange, config, &secrets))
+            .collect();
+        self.jobs.insert(job_id.to_string(), entries);
+    }
+
+    /// Every captured entry, newest first
+    pub fn entries(&self) -> Vec<&TranscriptEntry> {
+        let mut entries: Vec<&TranscriptEntry> = self.jobs.values().flatten().collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.at));
+        entries
+    }
+
+    pub fn for_job(&self, job_id: &str) -> Vec<&TranscriptEntry> {
+        self.jobs.get(job_id).map(|entries| entries.iter().collect()).unwrap_or_default()
+    }
+
+    pub fn clear(&mut self) {
+        self.jobs.clear();
+    }
+
+    /// Writes a job's entries as JSON to `<dir>/transcripts/<job_id>.json`
+    pub fn dump(&self, job_id: &str, dir: &Path) -> Result<PathBuf> {
+        let entries = self.for_job(job_id);
+        if entries.is_empty() {
+            anyhow::bail!("Nothing captured for {}", job_id);
+        }
+        let dir = dir.join("transcripts");
+        std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.json", job_id));
+        atomic::write(&path, serde_json::to_vec_pretty(&entries)?)?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audit::ModelExchange;
+    use crate::config::Config;
+    use crate::providers::CustomProvider;
+    use crate::transcript::{self, TranscriptSettings, Transcripts, REDACTED};
+
+    fn exchange(provider: &str, prompt: &str) -> ModelExchange {
+        ModelExchange {
+            id: uuid::Uuid::new_v4().to_string(),
+            source_file: "doc.txt".to_string(),
+            provider: provider.to_string(),
+            model: "model-x".to_string(),
+            system_prompt: Some("Be brief".to_string()),
+            prompt: prompt.to_string(),
+            raw_response: "answer".to_string(),
+            at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_redact() {
+        let key = "sk-proj-abcdefghijklmnopqrstuvwxyz123456";
+        assert_eq!(transcript::redact(&format!("key={} end", key), &[]), format!("key={} end", REDACTED));
+        // Krótkie tokeny i fragmenty słów nie są kluczami
+        assert_eq!(transcript::redact("uses sk-learn and task-sk-abcdefghijklmnopqrstuvwxyz", &[]), "uses sk-learn and task-sk-abcdefghijklmnopqrstuvwxyz");
+        assert_eq!(transcript::redact("token 0123secret0123 here", &["0123secret0123".to_string()]), format!("token {} here", REDACTED));
+        assert_eq!(transcript::redact("nothing to hide", &[String::new()]), "nothing to hide");
+    }
+
+    #[test]
+    fn test_request_shape_per_provider() {
+        let mut config = Config::default();
+        config.ollama.base_url = "http://gpu-box:11434/".to_string();
+        config.providers.insert("lmstudio".to_string(), CustomProvider {
+            base_url: "http://localhost:1234/v1".to_string(),
+            api_key: None,
+            models: Vec::new(),
+        });
+        let (url, body) = transcript::request_for(&exchange("anthropic", "Hi"), &config);
+        assert_eq!(url, "https://api.anthropic.com/v1/messages");
+        assert_eq!(body["system"], "Be brief");
+        assert_eq!(body["messages"][0]["role"], "user");
+
+        let (url, body) = transcript::request_for(&exchange("ollama", "Hi"), &config);
+        assert_eq!(url, "http://gpu-box:11434/api/chat");
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["content"], "Hi");
+        assert_eq!(body["stream"], false);
+
+        let (url, _) = transcript::request_for(&exchange("openai", "Hi"), &config);
+        assert_eq!(url, "https://api.openai.com/v1/chat/completions");
+
+        // Własny dostawca mówi API OpenAI pod swoim adresem
+        let (url, body) = transcript::request_for(&exchange("lmstudio", "Hi"), &config);
+        assert_eq!(url, "http://localhost:1234/v1/chat/completions");
+        assert_eq!(body["messages"][1]["content"], "Hi");
+    }
+
+    #[test]
+    fn test_ring_buffer_per_job() -> anyhow::Result<()> {
+        let config = Config::default();
+        let exchanges: Vec<ModelExchange> = (0..5).map(|i| exchange("ollama", &format!("prompt {}", i))).collect();
+
+        let mut transcripts = Transcripts::new(&TranscriptSettings { enabled: false, per_job: 3 });
+        transcripts.capture("job_1", &exchanges, &config);
+        assert!(transcripts.entries().is_empty());
+
+        transcripts.enabled = true;
+        transcripts.capture("job_1", &exchanges, &config);
+        // Wznowione zadanie zgłasza wszystkie wymiany ponownie; bufor ich nie dubluje
+        transcripts.capture("job_1", &exchanges, &config);
+        let kept = transcripts.for_job("job_1");
+        assert_eq!(kept.len(), 3);
+        assert_eq!(kept[0].request["messages"][1]["content"], "prompt 2");
+        assert!(!kept[0].headers.contains_key("Authorization"));
+        assert!(kept[0].render().starts_with("POST http://localhost:11434/api/chat\n"));
+
+        let dir = tempfile::tempdir()?;
+        let path = transcripts.dump("job_1", dir.path())?;
+        assert_eq!(path, dir.path().join("transcripts").join("job_1.json"));
+        let dumped: Vec<serde_json::Value> = serde_json::from_slice(&std::fs::read(&path)?)?;
+        assert_eq!(dumped.len(), 3);
+        assert!(transcripts.dump("job_2", dir.path()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::atomic;
+use crate::audit;
+use crate::workspace;
+
+/// Header of an encrypted file; bump the digit when the layout changes
+const MAGIC: &[u8; 8] = b"ADVAULT1";
+const NONCE_LEN: usize = 12;
+
+/// `[encryption]` in config.toml: downloaded results and local run outputs at rest
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EncryptionSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Key for every workspace; when unset each workspace gets its own `results.key`
+    /// in its data directory, created on first use
+    #[serde(default)]
+    pub key_file: Option<PathBuf>,
+}
+
+impl EncryptionSettings {
+    pub fn key_path(&self, workspace: &str) -> Result<PathBuf> {
+        match &self.key_file {
+            Some(path) => Ok(path.clone()),
+            None => Ok(workspace::data_dir(workspace)?.join("results.key")),
+        }
+    }
+}
+
+/// Reads and writes result files, encrypting what it writes when it holds a key.
+/// Files are recognized by their header, so plaintext ones written before encryption
+/// was turned on still open.
+#[derive(Clone, Default)]
+pub struct Vault {
+    key: Option<Key<Aes256Gcm>>,
+}
+
+impl fmt::Debug for Vault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Vault").field("enabled", &self.enabled()).finish()
+    }
+}
+
+impl Vault {
+    /// The workspace's vault; without `enabled` files are written as they are
+    pub fn open(settings: &EncryptionSettings, workspace: &str) -> Result<Self> {
+        if !settings.enabled {
+            return Ok(Self::default());
+        }
+        let key = audit::load_or_create_key(&settings.key_path(workspace)?)?;
+        Ok(Self::with_key(key))
+    }
+
+    pub fn with_key(key: Key<Aes256Gcm>) -> Self {
+        Self { key: Some(key) }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Content of a file, decrypted if it's encrypted
+    pub fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        let content = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let Some(body) = content.strip_prefix(MAGIC.as_slice()) else {
+            return Ok(content);
+        };
+        let key = self.key.as_ref()
+            .with_context(|| format!("{} is encrypted; turn on [encryption] to open it", path.display()))?;
+        decrypt(key, body).with_context(|| format!("Cannot decrypt {}: wrong key or corrupted file", path.display()))
+    }
+
+    pub fn read_to_string(&self, path: &Path) -> Result<String> {
+        String::from_utf8(self.read(path)?).with_context(|| format!("{} is not UTF-8 text", path.display()))
+    }
+
+    /// Writes a file atomically, encrypted when the vault holds a key
+    pub fn write(&self, path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+        match &self.key {
+            Some(key) => atomic::write(path, encrypt(key, content.as_ref())?),
+            None => atomic::write(path, content),
+        }
+    }
+
+    /// Replaces a plaintext file with its encrypted copy. Returns whether anything changed:
+    /// nothing does without a key or when the file is already encrypted.
+    pub fn encrypt_in_place(&self, path: &Path) -> Result<bool> {
+        if !self.enabled() || is_encrypted(path)? {
+            return Ok(false);
+        }
+        let content = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        self.write(path, content)?;
+        Ok(true)
+    }
+}
+
+pub fn is_encrypted(path: &Path) -> Result<bool> {
+    let mut header = [0u8; MAGIC.len()];
+    let read = std::fs::File::open(path)
+        .and_then(|mut file| file.read(&mut header))
+        .with_context(|| format!("Cannot read {}", path.display()))?;
+    Ok(&header[..read] == MAGIC.as_slice())
+}
+
+fn encrypt(key: &Key<Aes256Gcm>, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = Aes256Gcm::new(key)
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt"))?;
+    let mut content = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    content.extend_from_slice(MAGIC);
+    content.extend_from_slice(&nonce);
+    content.extend_from_slice(&ciphertext);
+    Ok(content)
+}
+
+fn decrypt(key: &Key<Aes256Gcm>, body: &[u8]) -> Result<Vec<u8>> {
+    if body.len() <= NONCE_LEN {
+        anyhow::bail!("Encrypted file is truncated");
+    }
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    Aes256Gcm::new(key)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Authentication failed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vault::{EncryptionSettings, Vault};
+    use aes_gcm::aead::{KeyInit, OsRng};
+    use aes_gcm::Aes256Gcm;
+
+    fn vault() -> Vault {
+        Vault::with_key(Aes256Gcm::generate_key(OsRng))
+    }
+
+    #[test]
+    fn test_wrong_or_missing_key() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.json");
+        vault().write(&path, "[]")?;
+
+        assert!(vault().read(&path).is_err());
+        let err = Vault::default().read(&path).unwrap_err();
+        assert!(format!("{:#}", err).contains("[encryption]"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_in_place() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("results.jsonl");
+        std::fs::write(&path, "{}\n")?;
+
+        // Bez klucza pliki zostają jawne, a jawne pliki nadal się otwierają
+        assert!(!Vault::default().encrypt_in_place(&path)?);
+        let vault = vault();
+        assert_eq!(vault.read(&path)?, b"{}\n");
+        assert!(vault.encrypt_in_place(&path)?);
+        assert!(!vault.encrypt_in_place(&path)?);
+        assert_eq!(vault.read(&path)?, b"{}\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_per_workspace() -> anyhow::Result<()> {
+        let settings = EncryptionSettings { enabled: true, key_file: None };
+        assert_ne!(settings.key_path("klient-a")?, settings.key_path("klient-b")?);
+        let shared = EncryptionSettings { key_file: Some("/keys/results.key".into()), ..settings };
+        assert_eq!(shared.key_path("klient-a")?, shared.key_path("klient-b")?);
+        Ok(())
+    }
+}
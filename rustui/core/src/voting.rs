@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Record metadata: share of samples that agreed with the kept answer of the record's chunk
+pub const AGREEMENT: &str = "agreement";
+/// Record metadata: `{"chunk": i, "agreement": ..., "distinct": ...}` of the record's chunk
+pub const VOTES: &str = "votes";
+
+/// Self-consistency voting from the `[voting]` config section or a preset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoteSettings {
+    /// Samples taken per chunk
+    #[serde(default = "default_samples")]
+    pub samples: usize,
+}
+
+fn default_samples() -> usize {
+    5
+}
+
+impl Default for VoteSettings {
+    fn default() -> Self {
+        Self { samples: default_samples() }
+    }
+}
+
+impl fmt::Display for VoteSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "majority of {}", self.samples)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vote {
+    /// Sample to keep
+    pub index: usize,
+    /// Share of samples with the same answer as the kept one
+    pub agreement: f64,
+    /// Number of different answers
+    pub distinct: usize,
+}
+
+/// Answer compared between samples: case, whitespace and trailing punctuation don't matter
+pub fn normalize(answer: &str) -> String {
+    answer.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(|c: char| c.is_ascii_punctuation())
+        .to_lowercase()
+}
+
+/// Keeps the majority answer. Without one (every sample different) the sample sharing
+/// the most words with the others wins. Ties go to the earlier sample.
+pub fn vote(samples: &[String]) -> Vote {
+    let answers: Vec<String> = samples.iter().map(|s| normalize(s)).collect();
+    let support: Vec<usize> = answers.iter()
+        .map(|answer| answers.iter().filter(|other| *other == answer).count())
+        .collect();
+    let distinct = answers.iter().collect::<HashSet<_>>().len();
+    let best = support.iter().enumerate()
+        .fold(0, |best, (i, count)| if *count > support[best] { i } else { best });
+    let index = if support.get(best).copied().unwrap_or(0) > 1 { best } else { most_consistent(&answers) };
+    Vote {
+        index,
+        agreement: support.get(index).copied().unwrap_or(0) as f64 / samples.len().max(1) as f64,
+        distinct,
+    }
+}
+
+/// Sample with the highest mean word overlap (Jaccard) with the other samples
+fn most_consistent(answers: &[String]) -> usize {
+    let words: Vec<HashSet<&str>> = answers.iter().map(|a| a.split_whitespace().collect()).collect();
+    let similarity = |a: &HashSet<&str>, b: &HashSet<&str>| {
+        let union = a.union(b).count();
+        if union == 0 { 1.0 } else { a.intersection(b).count() as f64 / union as f64 }
+    };
+    let scores: Vec<f64> = words.iter().enumerate()
+        .map(|(i, own)| words.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, other)| similarity(own, other)).sum())
+        .collect();
+    scores.iter().enumerate()
+        .fold(0, |best, (i, score)| if *score > scores[best] { i } else { best })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunking::ChunkSettings;
+    use crate::processors::{test_config, Processor, ProcessorConfig, StandardProcessor};
+    use crate::voting::{self, VoteSettings, AGREEMENT};
+
+    #[test]
+    fn test_majority_ignores_case_and_punctuation() {
+        let samples: Vec<String> = ["Positive.", "negative", "positive", "  POSITIVE "].iter().map(|s| s.to_string()).collect();
+        let vote = voting::vote(&samples);
+        assert_eq!(vote.index, 0);
+        assert_eq!(vote.agreement, 0.75);
+        assert_eq!(vote.distinct, 2);
+    }
+
+    #[test]
+    fn test_most_consistent_without_majority() {
+        let samples: Vec<String> = [
+            "the dog barked loudly",
+            "the dog barked",
+            "dog barked at night",
+        ].iter().map(|s| s.to_string()).collect();
+        // Każda odpowiedź jest inna, więc wygrywa ta najbardziej podobna do pozostałych
+        let vote = voting::vote(&samples);
+        assert_eq!(vote.index, 1);
+        assert!((vote.agreement - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_chunked_run_records_agreement() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("doc.txt");
+        let text: Vec<String> = (1..=24).map(|i| format!("word{}", i)).collect();
+        std::fs::write(&path, text.join(" "))?;
+
+        let config = ProcessorConfig {
+            chunking: Some(ChunkSettings { size_words: 12, overlap_words: 0, ..Default::default() }),
+            voting: Some(VoteSettings { samples: 3 }),
+            ..test_config()
+        };
+        let result = StandardProcessor.process_file(path.to_str().unwrap(), &config)?;
+
+        assert_eq!(result.exchanges.len(), 6);
+        assert_eq!(result.records[0].completion, text[..12].join(" "));
+        assert_eq!(result.records[1].completion, text[12..].join(" "));
+        for record in &result.records {
+            let agreement = record.metadata[AGREEMENT].as_f64().unwrap();
+            assert!((agreement - 2.0 / 3.0).abs() < 1e-9);
+        }
+        Ok(())
+    }
+}
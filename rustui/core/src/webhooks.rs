@@ -0,0 +1,259 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::Path;
+
+use crate::background;
+use crate::history::HistoryEntry;
+use crate::log_debug;
+use crate::network;
+
+/// Bumped whenever a field is removed or changes meaning; see docs/webhook-events.schema.json
+pub const SCHEMA_VERSION: u32 = 1;
+pub const EVENT_HEADER: &str = "X-AnyDataset-Event";
+pub const SIGNATURE_HEADER: &str = "X-AnyDataset-Signature";
+
+/// Event types sent to webhooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventKind {
+    #[serde(rename = "job.created")]
+    JobCreated,
+    #[serde(rename = "job.progress")]
+    JobProgress,
+    #[serde(rename = "job.completed")]
+    JobCompleted,
+    #[serde(rename = "dataset.exported")]
+    DatasetExported,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::JobCreated => "job.created",
+            EventKind::JobProgress => "job.progress",
+            EventKind::JobCompleted => "job.completed",
+            EventKind::DatasetExported => "dataset.exported",
+        }
+    }
+}
+
+/// Webhook endpoint from the `[[webhooks]]` config section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookSettings {
+    pub url: String,
+    /// Shared secret for the HMAC-SHA256 signature header
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Events to deliver; empty means all of them
+    #[serde(default)]
+    pub events: Vec<EventKind>,
+}
+
+impl WebhookSettings {
+    pub fn accepts(&self, kind: EventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+/// Job fields shared by the `job.*` events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobData {
+    pub job_id: String,
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processing_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<Progress>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    pub current: u64,
+    pub total: u64,
+}
+
+/// Payload of `dataset.exported`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportData {
+    pub job_id: String,
+    pub path: String,
+    pub size_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EventData {
+    Job(JobData),
+    Export(ExportData),
+}
+
+/// Envelope sent as the JSON body of every webhook request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub schema_version: u32,
+    pub id: String,
+    pub event: EventKind,
+    pub occurred_at: DateTime<Utc>,
+    pub source: String,
+    pub data: EventData,
+}
+
+impl WebhookEvent {
+    fn new(event: EventKind, data: EventData) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            id: uuid::Uuid::new_v4().to_string(),
+            event,
+            occurred_at: Utc::now(),
+            source: format!("anydataset-tui/{}", env!("CARGO_PKG_VERSION")),
+            data,
+        }
+    }
+
+    pub fn job_created(entry: &HistoryEntry) -> Self {
+        Self::new(EventKind::JobCreated, EventData::Job(job_data(entry)))
+    }
+
+    pub fn job_completed(entry: &HistoryEntry) -> Self {
+        Self::new(EventKind::JobCompleted, EventData::Job(job_data(entry)))
+    }
+
+    pub fn job_progress(job_id: &str, status: &str, progress: Option<(u64, u64)>) -> Self {
+        Self::new(EventKind::JobProgress, EventData::Job(JobData {
+            job_id: job_id.to_string(),
+            status: status.to_string(),
+            file: None,
+            processing_type: None,
+            provider: None,
+            model: None,
+            progress: progress.map(|(current, total)| Progress { current, total }),
+        }))
+    }
+
+    pub fn dataset_exported(job_id: &str, path: &Path, size_bytes: u64, manifest: Option<&Path>) -> Self {
+        Self::new(EventKind::DatasetExported, EventData::Export(ExportData {
+            job_id: job_id.to_string(),
+            path: path.display().to_string(),
+            size_bytes,
+            manifest_path: manifest.map(|m| m.display().to_string()),
+        }))
+    }
+}
+
+fn job_data(entry: &HistoryEntry) -> JobData {
+    let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+    JobData {
+        job_id: entry.job_id.clone(),
+        status: entry.status.clone(),
+        file: non_empty(&entry.file),
+        processing_type: non_empty(&entry.processing_type),
+        provider: non_empty(&entry.provider),
+        model: non_empty(&entry.model),
+        progress: None,
+    }
+}
+
+/// `sha256=<hex>` HMAC of the raw request body, same format as GitHub webhooks
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
+
+/// Posts the event to every webhook subscribed to it. Returns one note per delivery.
+pub async fn deliver_all(hooks: &[WebhookSettings], event: &WebhookEvent) -> Vec<String> {
+    let targets: Vec<&WebhookSettings> = hooks.iter().filter(|h| h.accepts(event.event)).collect();
+    if targets.is_empty() {
+        return Vec::new();
+    }
+
+    let body = match serde_json::to_vec(event) {
+        Ok(body) => body,
+        Err(err) => return vec![format!("Failed to serialize {} event: {}", event.event.as_str(), err)],
+    };
+    let client = network::client();
+    let mut notes = Vec::new();
+    for hook in targets {
+        match deliver(&client, hook, event.event, &body).await {
+            Ok(()) => notes.push(format!("Webhook {}: {} delivered", hook.url, event.event.as_str())),
+            Err(err) => notes.push(format!("Failed webhook {}: {:#}", hook.url, err)),
+        }
+    }
+    notes
+}
+
+async fn deliver(client: &reqwest::Client, hook: &WebhookSettings, kind: EventKind, body: &[u8]) -> Result<()> {
+    let mut request = client.post(&hook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, kind.as_str())
+        .timeout(std::time::Duration::from_secs(10))
+        .body(body.to_vec());
+    if let Some(secret) = &hook.secret {
+        request = request.header(SIGNATURE_HEADER, sign(secret, body));
+    }
+
+    let response = request.send().await.context("Request failed")?;
+    if !response.status().is_success() {
+        anyhow::bail!("Endpoint returned {}", response.status());
+    }
+    Ok(())
+}
+
+/// Fire-and-forget delivery off the UI thread
+pub fn dispatch(hooks: &[WebhookSettings], event: WebhookEvent) {
+    if !hooks.iter().any(|h| h.accepts(event.event)) {
+        return;
+    }
+    let hooks = hooks.to_vec();
+    background::spawn("Webhook delivery", move |runtime| {
+        for note in runtime.block_on(deliver_all(&hooks, &event)) {
+            log_debug!("{}", note);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::webhooks::{sign, EventKind, WebhookEvent, WebhookSettings};
+
+    #[test]
+    fn test_sign_matches_known_hmac() {
+        // Wektor referencyjny HMAC-SHA256
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn test_event_payload_shape() -> anyhow::Result<()> {
+        let event = WebhookEvent::job_progress("job-1", "processing", Some((5, 10)));
+        let json: serde_json::Value = serde_json::to_value(&event)?;
+
+        assert_eq!(json["event"], "job.progress");
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["data"]["job_id"], "job-1");
+        assert_eq!(json["data"]["progress"]["current"], 5);
+        // Puste pola opcjonalne nie trafiają do payloadu
+        assert!(json["data"].get("file").is_none());
+
+        let hook: WebhookSettings = toml::from_str("url = \"http://localhost\"\nevents = [\"job.completed\"]")?;
+        assert!(hook.accepts(EventKind::JobCompleted));
+        assert!(!hook.accepts(EventKind::JobProgress));
+
+        Ok(())
+    }
+}
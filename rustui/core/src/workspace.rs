@@ -0,0 +1,130 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::config::{get_config_dir, Config};
+use crate::preset::Preset;
+
+/// Workspace used when none is selected; its data lives directly in the config directory
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// Per-workspace overrides from the `[workspaces.<name>]` config section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceSettings {
+    #[serde(default)]
+    pub downloads_directory: Option<PathBuf>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Template variables of this workspace, over the global `[variables]`
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    /// User-Agent of this workspace's backend requests, over the global `user_agent`
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Headers of this workspace's backend requests, over the global `[request_headers]`
+    #[serde(default)]
+    pub request_headers: BTreeMap<String, String>,
+    /// Local presets of this workspace; the default workspace keeps its own in the global `[presets]`
+    #[serde(default)]
+    pub presets: BTreeMap<String, Preset>,
+    /// Labels of this workspace, e.g. the client's industry, shown next to its name
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Workspace names double as directory names
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        anyhow::bail!("Invalid workspace name '{}': use letters, digits, '-' and '_'", name);
+    }
+    Ok(())
+}
+
+/// Directory holding a workspace's history and other per-workspace state
+pub fn data_dir(name: &str) -> Result<PathBuf> {
+    let config_dir = get_config_dir()?;
+    if name == DEFAULT_WORKSPACE {
+        return Ok(config_dir);
+    }
+    validate_name(name)?;
+    Ok(config_dir.join("workspaces").join(name))
+}
+
+/// Known workspaces: the default one, those configured and those with data on disk
+pub fn list(config: &Config) -> Vec<String> {
+    let mut names = vec![DEFAULT_WORKSPACE.to_string()];
+    names.extend(config.workspaces.keys().cloned());
+    if let Ok(entries) = get_config_dir().and_then(|dir| Ok(std::fs::read_dir(dir.join("workspaces"))?)) {
+        names.extend(entries.flatten()
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| e.file_name().into_string().ok()));
+    }
+    names.push(config.active_workspace().to_string());
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::preset::Preset;
+    use crate::workspace::{validate_name, WorkspaceSettings};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_workspace_downloads_dir() {
+        let mut config = Config {
+            downloads_directory: Some(PathBuf::from("/data")),
+            ..Config::default()
+        };
+        assert_eq!(config.active_workspace(), "default");
+        assert_eq!(config.downloads_dir(), PathBuf::from("/data"));
+
+        // Workspace z własnym katalogiem nadpisuje globalny
+        config.workspaces.insert("acme".to_string(), WorkspaceSettings {
+            downloads_directory: Some(PathBuf::from("/clients/acme")),
+            description: None,
+            variables: Default::default(),
+            user_agent: None,
+            request_headers: Default::default(),
+            presets: Default::default(),
+            tags: Vec::new(),
+        });
+        config.workspace = Some("acme".to_string());
+        assert_eq!(config.downloads_dir(), PathBuf::from("/clients/acme"));
+
+        config.workspace = Some("other".to_string());
+        assert_eq!(config.downloads_dir(), PathBuf::from("/data"));
+    }
+
+    #[test]
+    fn test_workspaces_keep_separate_presets() {
+        let mut config = Config::default();
+        config.active_presets_mut().insert("faq".to_string(), Preset { model: Some("global".to_string()), ..Preset::default() });
+        config.workspace = Some("acme".to_string());
+        assert!(config.active_presets().is_empty());
+        config.active_presets_mut().insert("faq".to_string(), Preset { model: Some("acme".to_string()), ..Preset::default() });
+        config.workspace = Some("globex".to_string());
+        config.active_presets_mut().insert("draft".to_string(), Preset::default());
+
+        // Każdy workspace widzi tylko własne presety, także po zapisie i odczycie konfiguracji
+        let config: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(config.workspace_presets("default")["faq"].model.as_deref(), Some("global"));
+        assert_eq!(config.workspace_presets("acme")["faq"].model.as_deref(), Some("acme"));
+        assert!(!config.workspace_presets("acme").contains_key("draft"));
+        assert_eq!(config.active_presets().keys().collect::<Vec<_>>(), vec!["draft"]);
+        assert!(config.workspace_presets("other").is_empty());
+    }
+
+    #[test]
+    fn test_validate_workspace_name() {
+        assert!(validate_name("client-a_2025").is_ok());
+        assert!(validate_name("../etc").is_err());
+        assert!(validate_name("").is_err());
+    }
+}
@@ -0,0 +1,14 @@
+#![no_main]
+
+use anydataset_core::office::{self, OfficeFormat};
+use libfuzzer_sys::fuzz_target;
+
+// DOCX and ODT are both zip archives, so each input is read as either format
+fuzz_target!(|data: &[u8]| {
+    let dir = tempfile::tempdir().unwrap();
+    for format in [OfficeFormat::Docx, OfficeFormat::Odt] {
+        let path = dir.path().join(format!("input.{}", format.name()));
+        std::fs::write(&path, data).unwrap();
+        let _ = office::read_blocks(&path, format);
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use anydataset_core::pdf;
+use libfuzzer_sys::fuzz_target;
+
+// `pdf::extract` reads from a path, so every input goes through a temporary file
+fuzz_target!(|data: &[u8]| {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("input.pdf");
+    std::fs::write(&path, data).unwrap();
+    let _ = pdf::extract(&path);
+});
@@ -0,0 +1,35 @@
+#![no_main]
+
+use anydataset_core::formats::{self, DatasetFormat};
+use anydataset_core::processors::{ProcessingResult, ProcessingStats, Record};
+use anydataset_core::tabular;
+use libfuzzer_sys::fuzz_target;
+
+// Whatever parses as JSON or JSONL results is turned into records and run through every converter
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    let Ok(values) = formats::parse_records(&text) else {
+        return;
+    };
+    let records: Vec<Record> = values.iter().map(|value| Record {
+        instruction: value.get("instruction").map(|v| v.to_string()).unwrap_or_default(),
+        prompt: value.to_string(),
+        completion: value.get("completion").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+        metadata: value.as_object()
+            .map(|object| object.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default(),
+    }).collect();
+    let count = records.len();
+    let results = vec![ProcessingResult {
+        records,
+        source_file: "input.jsonl".to_string(),
+        processing_type: "standard".to_string(),
+        stats: ProcessingStats { total_records: count, total_tokens: 0, processing_time_ms: 0 },
+        exchanges: Vec::new(),
+        error: None,
+    }];
+    for format in DatasetFormat::ALL {
+        let _ = formats::convert(&results, format, Some("system"));
+    }
+    assert_eq!(tabular::rows(&results, &tabular::default_columns()).len(), count);
+});
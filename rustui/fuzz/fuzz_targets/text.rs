@@ -0,0 +1,11 @@
+#![no_main]
+
+use anydataset_core::{docmeta, paper};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    paper::split_latex(&text);
+    paper::split_sections(&text);
+    let _ = docmeta::split_front_matter(&text);
+});
@@ -1,6 +1,77 @@
-use crossterm::event::{KeyCode, KeyEvent};
-use std::collections::HashMap;
-use tui_input::Input;
+use anyhow::Context;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::api::{BackendVersion, JobStatus, ProcessingConfig, UploadProgress};
+use crate::api::ApiClient;
+use crate::archive;
+use crate::assembly::{self, AssemblyWizard, Step};
+use crate::audit;
+use crate::background::{self, BackgroundEvent};
+use crate::catalog::{ModelCatalog, ModelWarning};
+use crate::chaos::Chaos;
+use crate::checks::{self, CheckSettings, Checks};
+use crate::config::Config;
+use crate::control::SharedSnapshot;
+use crate::credentials::{self, KeyManager};
+use crate::datasets::{self, DatasetBrowser};
+use crate::discovery::{self, BackendPicker, DiscoveredBackend};
+use crate::diskspace;
+use crate::filebrowser::FileBrowser;
+use crate::formats::{self, DatasetFormat};
+use crate::git;
+use crate::latency::{self, BackendProbe};
+use crate::{log_info, log_warn};
+use crate::history::{HistoryEntry, JobHistory};
+use crate::hooks::{self, PostDownloadAction};
+use crate::jobfile::{JobFile, ResolvedJob};
+use crate::jobs::{self, JobSort};
+use crate::manifest::DatasetManifest;
+use crate::network::NetworkMonitor;
+use crate::notifications;
+use crate::ollama;
+use crate::output::resolve_output_path;
+use crate::partial;
+use crate::bestof;
+use crate::capabilities::Capabilities;
+use crate::preference::{self, Choice, Review};
+use crate::preflight::{self, CorpusReport};
+use crate::preset::{self, Origin, Preset};
+use crate::preset_sync::{self, SharedPresets};
+use crate::preview::{self, Preview};
+use crate::providers;
+use crate::processors::{self, LocalRun, ProcessorConfig, SpendCap};
+use crate::pubsub;
+use crate::quota::{QuotaExceeded, QuotaGuard};
+use crate::recording::{self, Recorder};
+use crate::report::{Month, ReportFormat, UsageReport};
+use crate::results::ResultsViewer;
+use crate::stats::StatsView;
+use crate::retry::{ScheduledRetry, Submission};
+use crate::signing;
+use crate::storage::{self, StorageView};
+use crate::tabular::{self, ColumnEditor};
+use crate::templating::{self, TemplateContext};
+use crate::theme::{self, Theme};
+use crate::transcript::Transcripts;
+use crate::tuning::ChunkTuner;
+use crate::keywords::KeywordEditor;
+use crate::logview::LogView;
+use crate::sampling::{SamplingEditor, SamplingParams};
+use crate::tutorial::Tutorial;
+use crate::usage::{self, UsageMetric};
+use crate::vault::Vault;
+use crate::webhooks::{self, WebhookEvent};
+use crate::workspace;
+use crate::worker::{ApiWorker, WorkerCommand};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::mpsc::{Receiver, Sender};
+use tui_input::{Input, InputRequest};
+
+/// How often the status of the current backend job is polled
+const STATUS_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+/// How often the retention policy is applied while the app runs
+const CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+/// Lines PgUp/PgDn scroll the Logs screen by
+const LOG_PAGE: usize = 20;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AppState {
@@ -9,6 +80,16 @@ pub enum AppState {
     Process,
     Settings,
     JobStatus,
+    Usage,
+    Review,
+    Jobs,
+    Datasets,
+    Results,
+    Network,
+    Storage,
+    Debug,
+    Logs,
+    Stats,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -29,6 +110,10 @@ impl ProcessingType {
         }
     }
     
+    pub fn parse(value: &str) -> Option<ProcessingType> {
+        Self::all().into_iter().find(|pt| pt.to_str() == value)
+    }
+    
     pub fn all() -> Vec<ProcessingType> {
         vec![
             ProcessingType::Standard,
@@ -39,136 +124,2069 @@ impl ProcessingType {
     }
 }
 
+/// Download confirmation popup with toggleable post-download actions
+#[derive(Debug, Clone)]
+pub struct DownloadDialog {
+    pub job_id: String,
+    pub preset: String,
+    pub actions: Vec<(PostDownloadAction, bool)>,
+    pub selected: usize,
+    /// Download even when the file won't fit on disk
+    pub ignore_disk_space: bool,
+}
+
+/// Preset popup of the Process screen: effective presets by name, and the name being typed
+/// while the current configuration is saved as a preset
+#[derive(Debug, Clone, Default)]
+pub struct PresetPicker {
+    pub names: Vec<String>,
+    pub selected: usize,
+    pub naming: Option<Input>,
+}
+
+/// Shown instead of submitting when the provider's quota is used up
+#[derive(Debug, Clone)]
+pub struct QuotaDialog {
+    pub exceeded: QuotaExceeded,
+    pub file: String,
+}
+
+/// Pre-flight report of a job's files, shown before submitting when some were flagged
+#[derive(Debug, Clone)]
+pub struct PreflightDialog {
+    pub report: CorpusReport,
+    /// File the job was started for
+    pub file: String,
+    /// Flagged files left out of the job
+    pub excluded: BTreeSet<String>,
+    /// Index into the flagged files
+    pub selected: usize,
+}
+
+impl PreflightDialog {
+    pub fn current(&self) -> Option<&str> {
+        self.report.flagged().get(self.selected).map(|report| report.file.as_str())
+    }
+}
+
+/// Asks before cancelling a job on the backend
+#[derive(Debug, Clone)]
+pub struct CancelDialog {
+    pub job_id: String,
+}
+
+/// A job processed on this machine under a spend cap
+#[derive(Debug, Clone)]
+pub struct LocalJob {
+    pub job_id: String,
+    pub processing_type: String,
+    pub config: ProcessorConfig,
+    pub usd_per_1k_tokens: f64,
+    /// Cap requested at submission; continuing past it allows the same amount again
+    pub cap_step_usd: f64,
+    pub cap: SpendCap,
+    pub run: LocalRun,
+    pub total_files: usize,
+    pub output: std::path::PathBuf,
+    /// Audit archive and key file when prompt/response capture is enabled
+    pub audit: Option<(std::path::PathBuf, std::path::PathBuf)>,
+    /// Verification passes run over each batch of new records
+    pub checks: CheckSettings,
+    /// Encrypts the output when `[encryption]` is on
+    pub vault: Vault,
+}
+
 pub struct App {
+    pub config: Config,
     pub state: AppState,
     pub backend_url: String,
     pub uploaded_files: Vec<String>,
+    /// Backend file IDs of the uploaded files, by local path
+    pub uploaded_file_ids: HashMap<String, String>,
+    /// Uploads still being sent, by local path
+    pub uploads: BTreeMap<String, UploadProgress>,
     pub selected_file_index: Option<usize>,
+    /// Picker for files to upload on the Upload screen
+    pub file_browser: FileBrowser,
     pub processing_type: ProcessingType,
     pub language: String,
     pub job_id_input: Input,
     pub current_job_id: Option<String>,
     pub job_progress: Option<(u64, u64)>, // (current, total)
     pub job_status: Option<String>,
+    pub job_owner: Option<String>,
+    pub jobs: Vec<JobStatus>,
+    pub jobs_mine_only: bool,
+    pub current_user: Option<String>,
+    /// Row selected on the Jobs screen, an index into `visible_jobs`
+    pub jobs_selected: usize,
+    pub jobs_sort: JobSort,
+    /// Page of the backend's job list shown on the Jobs screen, starting at 1
+    pub jobs_page: u32,
+    pub jobs_total_pages: u32,
+    /// Jobs matching the filter on all pages, when the backend reports it
+    pub jobs_total: Option<u64>,
+    /// Only jobs in this state are fetched
+    pub jobs_status_filter: Option<String>,
+    pub jobs_loading: bool,
+    /// The job list was fetched at least once
+    pub jobs_loaded: bool,
+    /// Why the job list couldn't be fetched
+    pub jobs_error: Option<String>,
     pub providers: Vec<String>,
     pub selected_provider_index: Option<usize>,
     pub models: HashMap<String, Vec<String>>,
     pub selected_model_index: Option<usize>,
+    /// Provider model lists from the backend, once fetched
+    pub model_catalog: Option<ModelCatalog>,
+    pub model_refresh_started: Option<std::time::Instant>,
+    pub model_refresh_running: bool,
+    /// Why the last refresh failed; the built-in or cached lists stay in use
+    pub model_refresh_error: Option<String>,
+    /// Where fetched catalogs are cached between runs, once `load_cached_models` set it
+    pub model_cache_path: Option<std::path::PathBuf>,
+    pub ollama_discovery_running: bool,
+    /// Why the Ollama server couldn't be asked for its models
+    pub ollama_error: Option<String>,
     pub keywords: Vec<String>,
     pub system_prompt: String,
     pub message: Option<String>,
     pub debug_info: Vec<String>,
+    pub update_available: Option<String>,
+    pub backend_version: Option<BackendVersion>,
+    pub backend_error: Option<String>,
+    pub background_tx: Sender<BackgroundEvent>,
+    pub background_rx: Receiver<BackgroundEvent>,
+    /// Runs uploads, submissions and status polls off the event loop
+    pub worker: ApiWorker,
+    /// Backend requests in flight or queued, for the Network screen
+    pub network: NetworkMonitor,
+    pub network_selected: usize,
+    /// Provider requests and responses of recent jobs, for the Debug screen
+    pub transcripts: Transcripts,
+    pub debug_selected: usize,
+    pub debug_scroll: u16,
+    /// Latest log entries on the Logs screen
+    pub log_view: LogView,
+    pub status_poll_started: Option<std::time::Instant>,
+    pub status_poll_running: bool,
+    /// Jobs whose status isn't polled on the backend: local runs and jobs the backend didn't know
+    pub unpolled_jobs: HashSet<String>,
+    /// Backend submissions waiting for the job ID, by file
+    pub submissions: HashMap<String, Submission>,
+    /// Backend jobs submitted in this session, kept to submit them again under `[retry]`
+    pub submitted_jobs: HashMap<String, Submission>,
+    pub scheduled_retries: Vec<ScheduledRetry>,
+    pub tutorial: Tutorial,
+    pub tutorial_seen: bool,
+    pub history: JobHistory,
+    pub last_processing_config: Option<ProcessingConfig>,
+    pub download_dialog: Option<DownloadDialog>,
+    pub quota_dialog: Option<QuotaDialog>,
+    pub preflight_dialog: Option<PreflightDialog>,
+    pub cancel_dialog: Option<CancelDialog>,
+    /// Spend cap for the next submitted job
+    pub job_spend_cap: Option<f64>,
+    /// Layout of the datasets of the next jobs
+    pub output_format: DatasetFormat,
+    /// Local job paused at its spend cap, waiting for "continue?"
+    pub budget_prompt: Option<LocalJob>,
+    /// Jobs of a job file (`--jobs`) waiting to be processed locally, one after another
+    pub job_queue: std::collections::VecDeque<ResolvedJob>,
+    /// Local job of the queue being processed
+    pub queue_job_id: Option<String>,
+    pub pending_editor: Option<std::path::PathBuf>,
+    /// Reduced-redraw rendering for slow remote terminals, toggled with Ctrl+L
+    pub remote_shell: bool,
+    /// Whether the terminal has focus; finished jobs are notified while it hasn't
+    pub focused: bool,
+    /// Ring the terminal bell on the next pass of the event loop
+    pub ring_bell: bool,
+    /// Session recording, shared with the terminal writer
+    pub recorder: Recorder,
+    /// Clear the terminal and draw the whole screen on the next pass of the event loop
+    pub redraw_all: bool,
+    pub usage_days: usize,
+    pub usage_metric: UsageMetric,
+    pub usage_hidden_providers: HashSet<String>,
+    /// Set on the primary instance; read by attached clients through the control server
+    pub shared_state: Option<SharedSnapshot>,
+    pub shared_presets: SharedPresets,
+    pub preset_sync_started: Option<std::time::Instant>,
+    pub preset_sync_running: bool,
+    /// Dataset being judged on the Review screen
+    pub review: Option<Review>,
+    pub review_input: Input,
+    /// Dataset written by the last local run, offered for review
+    pub last_local_output: Option<std::path::PathBuf>,
+    /// Downloaded results open on the Results screen
+    pub results_viewer: Option<ResultsViewer>,
+    /// Summary of a dataset on the Stats screen
+    pub stats_view: Option<StatsView>,
+    pub results_input: Input,
+    /// File written by the last download, offered on the Results screen
+    pub last_download: Option<std::path::PathBuf>,
+    /// CSV/Parquet export of the last local run's dataset
+    pub column_editor: Option<ColumnEditor>,
+    /// Training corpus put together from the datasets of finished jobs
+    pub assembly: Option<AssemblyWizard>,
+    /// Chunk boundaries of the selected file under adjustable settings
+    pub chunk_tuner: Option<ChunkTuner>,
+    /// Temperature, max tokens and top p sent with jobs
+    pub sampling: SamplingParams,
+    /// Advanced-parameters panel of the Process screen
+    pub sampling_editor: Option<SamplingEditor>,
+    /// Tag input editing `keywords`, focused while Some
+    pub keyword_editor: Option<KeywordEditor>,
+    pub preset_picker: Option<PresetPicker>,
+    /// Datasets stored on the backend
+    pub datasets: DatasetBrowser,
+    /// Backends found on the local network
+    pub backend_picker: Option<BackendPicker>,
+    /// Provider API keys, opened from Settings
+    pub key_manager: Option<KeyManager>,
+    /// Colors of the UI, from `[theme]`
+    pub theme: Theme,
+    /// Unicode and color support of the terminal; a limited one gets ASCII and no colors
+    pub capabilities: Capabilities,
+    /// First run: offer the backends found on the network once the tutorial is closed
+    pub offer_discovered_backends: bool,
+    pub discovered_backends: Vec<DiscoveredBackend>,
+    /// Latencies measured by the last ping of the backend and its mirrors
+    pub backend_probes: Vec<BackendProbe>,
+    pub probing_backends: bool,
+    /// Disk usage of downloaded results, on the Storage screen
+    pub storage: StorageView,
+    pub cleanup_started: Option<std::time::Instant>,
+    /// Encrypts results of the active workspace, from `[encryption]`
+    pub vault: Vault,
 }
 
 impl App {
+    #[cfg(test)]
     pub fn new(backend_url: &str) -> Self {
+        let config = Config {
+            backend_url: backend_url.to_string(),
+            ..Config::default()
+        };
+        Self::with_config(config)
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        let backend_url = config.backend_url.clone();
+        let job_spend_cap = config.job_spend_cap_usd;
+        let remote_shell = config.starts_remote_shell();
+        let capabilities = Capabilities::detect(&config.theme);
+        let transcripts = Transcripts::new(&config.transcript);
+        let mut providers = vec!["openai".to_string(), "anthropic".to_string(), ollama::PROVIDER.to_string()];
+        let mut models = HashMap::from([
+            ("openai".to_string(), vec!["gpt-4-turbo".to_string(), "gpt-3.5-turbo".to_string()]),
+            ("anthropic".to_string(), vec!["claude-3-opus".to_string(), "claude-3-sonnet".to_string()]),
+            // Filled from the Ollama server by `start_ollama_discovery`
+            (ollama::PROVIDER.to_string(), Vec::new()),
+        ]);
+        for (name, custom) in config.custom_providers() {
+            providers.push(name.clone());
+            models.insert(name.clone(), custom.models.clone());
+        }
+        let (background_tx, background_rx) = std::sync::mpsc::channel();
+        let network = NetworkMonitor::default().with_chaos(Chaos::from_settings(&config.chaos_settings()));
+        let (theme, theme_error) = match Theme::for_config(&config) {
+            Ok(theme) => (theme, None),
+            Err(err) => (Theme::default(), Some(format!("Using the default theme: {:#}", err))),
+        };
+        let (vault, vault_error) = match Vault::open(&config.encryption, config.active_workspace()) {
+            Ok(vault) => (vault, None),
+            Err(err) => (Vault::default(), Some(format!("Results will NOT be encrypted: {:#}", err))),
+        };
+        let worker = ApiWorker::spawn(
+            &backend_url, background_tx.clone(), network.clone(), config.download.clone(), config.request_headers(),
+        );
         Self {
+            config,
             state: AppState::Main,
-            backend_url: backend_url.to_string(),
+            backend_url,
             uploaded_files: Vec::new(),
+            uploaded_file_ids: HashMap::new(),
+            uploads: BTreeMap::new(),
             selected_file_index: None,
+            file_browser: FileBrowser::open(&std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."))),
             processing_type: ProcessingType::Standard,
             language: "en".to_string(),
             job_id_input: Input::default(),
             current_job_id: None,
             job_progress: None,
             job_status: None,
-            providers: vec!["openai".to_string(), "anthropic".to_string()],
+            job_owner: None,
+            jobs: Vec::new(),
+            jobs_mine_only: false,
+            current_user: None,
+            jobs_selected: 0,
+            jobs_sort: JobSort::default(),
+            jobs_page: 1,
+            jobs_total_pages: 1,
+            jobs_total: None,
+            jobs_status_filter: None,
+            jobs_loading: false,
+            jobs_loaded: false,
+            jobs_error: None,
+            providers,
             selected_provider_index: Some(0),
-            models: HashMap::from([
-                ("openai".to_string(), vec!["gpt-4-turbo".to_string(), "gpt-3.5-turbo".to_string()]),
-                ("anthropic".to_string(), vec!["claude-3-opus".to_string(), "claude-3-sonnet".to_string()])
-            ]),
+            models,
             selected_model_index: Some(0),
+            model_catalog: None,
+            model_refresh_started: None,
+            model_refresh_running: false,
+            model_refresh_error: None,
+            model_cache_path: None,
+            ollama_discovery_running: false,
+            ollama_error: None,
             keywords: Vec::new(),
             system_prompt: String::new(),
-            message: None,
+            message: vault_error.or(theme_error),
             debug_info: Vec::new(),
+            update_available: None,
+            backend_version: None,
+            backend_error: None,
+            background_tx,
+            background_rx,
+            worker,
+            network,
+            network_selected: 0,
+            transcripts,
+            debug_selected: 0,
+            debug_scroll: 0,
+            log_view: LogView::default(),
+            status_poll_started: None,
+            status_poll_running: false,
+            unpolled_jobs: HashSet::new(),
+            submissions: HashMap::new(),
+            submitted_jobs: HashMap::new(),
+            scheduled_retries: Vec::new(),
+            tutorial: Tutorial::default(),
+            tutorial_seen: false,
+            history: JobHistory::default(),
+            last_processing_config: None,
+            download_dialog: None,
+            quota_dialog: None,
+            preflight_dialog: None,
+            cancel_dialog: None,
+            job_spend_cap,
+            output_format: DatasetFormat::default(),
+            budget_prompt: None,
+            job_queue: std::collections::VecDeque::new(),
+            queue_job_id: None,
+            pending_editor: None,
+            remote_shell,
+            focused: true,
+            ring_bell: false,
+            recorder: Recorder::default(),
+            redraw_all: false,
+            usage_days: 30,
+            usage_metric: UsageMetric::Records,
+            usage_hidden_providers: HashSet::new(),
+            shared_state: None,
+            shared_presets: SharedPresets::default(),
+            preset_sync_started: None,
+            preset_sync_running: false,
+            review: None,
+            review_input: Input::default(),
+            last_local_output: None,
+            results_viewer: None,
+            stats_view: None,
+            results_input: Input::default(),
+            last_download: None,
+            column_editor: None,
+            assembly: None,
+            chunk_tuner: None,
+            sampling: SamplingParams::default(),
+            sampling_editor: None,
+            keyword_editor: None,
+            preset_picker: None,
+            datasets: DatasetBrowser::default(),
+            backend_picker: None,
+            key_manager: None,
+            theme,
+            capabilities,
+            offer_discovered_backends: false,
+            discovered_backends: Vec::new(),
+            backend_probes: Vec::new(),
+            probing_backends: false,
+            storage: StorageView::default(),
+            cleanup_started: None,
+            vault,
+        }
+    }
+
+    /// Runs the periodic work; true when background events changed the state
+    pub fn on_tick(&mut self) -> bool {
+        let events: Vec<BackgroundEvent> = self.background_rx.try_iter().collect();
+        let mut changed = !events.is_empty();
+        for event in events {
+            self.handle_background_event(event);
+        }
+
+        if self.state == AppState::Logs && self.log_view.poll() {
+            changed = true;
+        }
+
+        if self.status_poll_due() {
+            self.poll_job_status();
+        }
+
+        self.submit_due_retries();
+
+        if self.preset_sync_due() {
+            self.start_preset_sync();
+        }
+
+        if self.model_refresh_due() {
+            self.start_model_refresh();
+        }
+
+        if self.cleanup_due() {
+            self.start_cleanup();
+        }
+
+        self.publish_snapshot();
+        changed
+    }
+
+    pub fn toggle_remote_shell(&mut self) {
+        self.remote_shell = !self.remote_shell;
+        self.message = Some(if self.remote_shell {
+            "Remote shell mode on: fewer redraws, slower refresh while idle (Ctrl+L to turn off)".to_string()
+        } else {
+            "Remote shell mode off".to_string()
+        });
+    }
+
+    /// Routes a key press to the open dialog or overlay, else to the current screen, as the
+    /// event loop does. False when the user quits.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.code == KeyCode::Char('l') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.toggle_remote_shell();
+            return true;
+        }
+        // Aktywny samouczek przechwytuje klawisze
+        if self.tutorial.is_active() {
+            self.handle_tutorial_input(key);
+            return true;
+        }
+        if self.download_dialog.is_some() {
+            self.handle_download_dialog_input(key);
+            return true;
+        }
+        if self.quota_dialog.is_some() {
+            self.handle_quota_dialog_input(key);
+            return true;
+        }
+        if self.preflight_dialog.is_some() {
+            self.handle_preflight_dialog_input(key);
+            return true;
+        }
+        if self.cancel_dialog.is_some() {
+            self.handle_cancel_dialog_input(key);
+            return true;
+        }
+        if self.budget_prompt.is_some() {
+            self.handle_budget_prompt_input(key);
+            return true;
+        }
+        if self.backend_picker.is_some() {
+            self.handle_backend_picker_input(key);
+            return true;
+        }
+        if self.key_manager.is_some() {
+            self.handle_key_manager_input(key);
+            return true;
+        }
+        if self.column_editor.is_some() {
+            self.handle_column_editor_input(key);
+            return true;
+        }
+        if self.assembly.is_some() {
+            self.handle_assembly_input(key);
+            return true;
+        }
+        if self.chunk_tuner.is_some() {
+            self.handle_chunk_tuner_input(key);
+            return true;
+        }
+        if self.sampling_editor.is_some() {
+            self.handle_sampling_editor_input(key);
+            return true;
+        }
+        if self.keyword_editor.is_some() {
+            self.handle_keyword_editor_input(key);
+            return true;
+        }
+        if self.preset_picker.is_some() {
+            self.handle_preset_picker_input(key);
+            return true;
+        }
+        match self.state {
+            AppState::Main => match key.code {
+                KeyCode::Char('q') => return false,
+                KeyCode::Char('u') => self.state = AppState::Upload,
+                KeyCode::Char('p') => self.state = AppState::Process,
+                KeyCode::Char('s') => self.open_settings(),
+                KeyCode::Char('j') => self.state = AppState::JobStatus,
+                KeyCode::Char('l') => self.open_jobs(),
+                KeyCode::Char('g') => self.state = AppState::Usage,
+                KeyCode::Char('r') => self.open_review(),
+                KeyCode::Char('t') => self.start_tutorial(),
+                KeyCode::Char('1') => self.rerun_last_job(),
+                KeyCode::Char('2') => self.resume_last_job(),
+                KeyCode::Char('x') => self.open_column_editor(),
+                KeyCode::Char('a') => self.open_assembly(),
+                KeyCode::Char('d') => self.open_datasets(),
+                KeyCode::Char('v') => self.open_results(),
+                KeyCode::Char('n') => self.state = AppState::Network,
+                KeyCode::Char('o') => self.open_storage(),
+                KeyCode::Char('b') => self.state = AppState::Debug,
+                KeyCode::Char('L') => self.open_logs(),
+                KeyCode::Char('S') => self.open_stats(self.last_download.iter().cloned().collect()),
+                _ => {}
+            },
+            AppState::Upload => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_upload_input(key),
+            },
+            AppState::Process => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_process_input(key),
+            },
+            AppState::Settings => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_settings_input(key),
+            },
+            AppState::JobStatus => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_job_status_input(key),
+            },
+            AppState::Usage => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_usage_input(key),
+            },
+            AppState::Review => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_review_input(key),
+            },
+            AppState::Jobs => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_jobs_input(key),
+            },
+            AppState::Datasets => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_datasets_input(key),
+            },
+            AppState::Results => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_results_input(key),
+            },
+            AppState::Network => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_network_input(key),
+            },
+            AppState::Storage => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_storage_input(key),
+            },
+            AppState::Debug => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_debug_input(key),
+            },
+            AppState::Logs => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_logs_input(key),
+            },
+            AppState::Stats => match key.code {
+                KeyCode::Esc => self.state = AppState::Main,
+                _ => self.handle_stats_input(key),
+            },
+        }
+        true
+    }
+
+    fn status_poll_due(&self) -> bool {
+        let Some(job_id) = &self.current_job_id else {
+            return false;
+        };
+        if self.status_poll_running || self.unpolled_jobs.contains(job_id) {
+            return false;
+        }
+        if matches!(self.job_status.as_deref(), Some("completed" | "failed" | "cancelled")) {
+            return false;
+        }
+        self.status_poll_started.is_none_or(|started| started.elapsed() >= STATUS_POLL_INTERVAL)
+    }
+
+    /// Asks the backend for the status of the current job
+    pub fn poll_job_status(&mut self) {
+        let Some(job_id) = self.current_job_id.clone() else {
+            return;
+        };
+        self.status_poll_started = Some(std::time::Instant::now());
+        self.status_poll_running = self.worker.send(WorkerCommand::PollStatus { job_id });
+    }
+
+    fn preset_sync_due(&self) -> bool {
+        let Some(settings) = &self.config.preset_sync else {
+            return false;
+        };
+        if self.preset_sync_running {
+            return false;
+        }
+        match (self.preset_sync_started, settings.refresh_interval()) {
+            (None, _) => true,
+            (Some(started), Some(interval)) => started.elapsed() >= interval,
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Refreshes shared presets in the background
+    pub fn start_preset_sync(&mut self) {
+        let Some(settings) = self.config.preset_sync.clone() else {
+            self.message = Some("No shared preset source configured ([preset_sync] in config)".to_string());
+            return;
+        };
+        if self.preset_sync_running {
+            return;
+        }
+        self.preset_sync_running = true;
+        self.preset_sync_started = Some(std::time::Instant::now());
+        let tx = self.background_tx.clone();
+        background::spawn("Preset sync", move |runtime| {
+            let event = match runtime.block_on(preset_sync::sync(&settings)) {
+                Ok(shared) => BackgroundEvent::PresetsSynced(shared),
+                Err(err) => BackgroundEvent::PresetSyncFailed(format!("{:#}", err)),
+            };
+            let _ = tx.send(event);
+        });
+    }
+
+    fn model_refresh_due(&self) -> bool {
+        // Waits for the version handshake, so an unreachable backend isn't asked every tick
+        if self.model_refresh_running || self.backend_version.is_none() {
+            return false;
+        }
+        match (self.model_refresh_started, self.config.model_catalog.refresh_interval()) {
+            (None, _) => !self.model_catalog_fresh(),
+            (Some(started), Some(interval)) => started.elapsed() >= interval,
+            (Some(_), None) => false,
+        }
+    }
+
+    fn model_catalog_fresh(&self) -> bool {
+        let ttl = self.config.model_catalog.cache_ttl();
+        self.model_catalog.as_ref().is_some_and(|catalog| catalog.is_fresh(ttl))
+    }
+
+    /// Uses the catalog cached at `path` by an earlier run while it is fresh, and caches
+    /// fetched ones there
+    pub fn load_cached_models(&mut self, path: std::path::PathBuf) {
+        match ModelCatalog::load_cached(&path, self.config.model_catalog.cache_ttl()) {
+            Ok(Some(catalog)) => {
+                self.debug_info.push(format!("Model lists from the cache of {}", catalog.fetched_at.format("%Y-%m-%d %H:%M")));
+                self.models_refreshed(catalog);
+            },
+            Ok(None) => {},
+            Err(err) => self.debug_info.push(format!("Ignoring the model list cache: {:#}", err)),
+        }
+        self.model_cache_path = Some(path);
+    }
+
+    /// Opens Settings, fetching the model lists first unless the cached ones are fresh
+    pub fn open_settings(&mut self) {
+        self.state = AppState::Settings;
+        if !self.model_catalog_fresh() {
+            self.start_model_refresh();
+        }
+    }
+
+    /// Fetches the provider model lists from the backend in the background
+    pub fn start_model_refresh(&mut self) {
+        if self.model_refresh_running {
+            return;
+        }
+        self.model_refresh_running = true;
+        self.model_refresh_started = Some(std::time::Instant::now());
+        let client = ApiClient::new(&self.backend_url)
+            .with_monitor(self.network.clone())
+            .with_headers(self.config.request_headers());
+        let cache_path = self.model_cache_path.clone();
+        let tx = self.background_tx.clone();
+        background::spawn("Model list refresh", move |runtime| {
+            let event = match runtime.block_on(client.get_available_models()).and_then(|models| ModelCatalog::parse(&models)) {
+                Ok(catalog) => {
+                    if let Some(path) = cache_path {
+                        if let Err(err) = catalog.save(&path) {
+                            log_warn!("Failed to cache the model lists: {:#}", err);
+                        }
+                    }
+                    BackgroundEvent::ModelsRefreshed(catalog)
+                },
+                Err(err) => BackgroundEvent::ModelRefreshFailed(format!("{:#}", err)),
+            };
+            let _ = tx.send(event);
+        });
+    }
+
+    /// Replaces the model lists with the catalog's, keeping the selected provider and model
+    pub fn models_refreshed(&mut self, mut catalog: ModelCatalog) {
+        self.model_refresh_running = false;
+        self.model_refresh_error = None;
+        // Local and custom providers list their models themselves
+        catalog.providers.remove(ollama::PROVIDER);
+        for (name, _) in self.config.custom_providers() {
+            catalog.providers.remove(name);
+        }
+        let provider = self.get_current_provider().map(str::to_string);
+        let model = self.get_current_model().map(str::to_string);
+        for (name, models) in &catalog.providers {
+            if models.is_empty() {
+                continue;
+            }
+            let mut ids = catalog.ids(name);
+            // A withdrawn model stays selectable so the warning can point at it
+            if provider.as_deref() == Some(name.as_str()) {
+                if let Some(model) = model.as_ref().filter(|m| !ids.contains(m)) {
+                    ids.push(model.clone());
+                }
+            }
+            if !self.providers.contains(name) {
+                self.providers.push(name.clone());
+            }
+            self.models.insert(name.clone(), ids);
+        }
+        self.model_catalog = Some(catalog);
+        if let (Some(provider), Some(model)) = (provider, model) {
+            self.select_model(&provider, &model);
+        }
+        for warning in self.model_warnings() {
+            self.debug_info.push(format!("Model check: {}", warning));
+        }
+    }
+
+    /// Asks the Ollama server which models it has pulled
+    pub fn start_ollama_discovery(&mut self) {
+        if self.ollama_discovery_running {
+            return;
+        }
+        self.ollama_discovery_running = true;
+        let settings = self.config.ollama.clone();
+        let tx = self.background_tx.clone();
+        background::spawn("Ollama model discovery", move |runtime| {
+            let event = match runtime.block_on(ollama::list_models(&settings)) {
+                Ok(models) => BackgroundEvent::OllamaModelsListed(models),
+                Err(err) => BackgroundEvent::OllamaUnreachable(format!("{:#}", err)),
+            };
+            let _ = tx.send(event);
+        });
+    }
+
+    pub fn ollama_models_listed(&mut self, models: Vec<String>) {
+        self.ollama_discovery_running = false;
+        self.ollama_error = None;
+        self.debug_info.push(format!("Ollama at {}: {} model(s)", self.config.ollama.base_url, models.len()));
+        self.set_provider_models(ollama::PROVIDER, models);
+    }
+
+    /// Asks each custom provider's server for its models
+    pub fn start_provider_discovery(&mut self) {
+        for (name, custom) in self.config.custom_providers() {
+            let (name, custom) = (name.clone(), custom.clone());
+            let tx = self.background_tx.clone();
+            background::spawn("Provider model discovery", move |runtime| {
+                let event = match runtime.block_on(providers::list_models(&name, &custom)) {
+                    Ok(models) => BackgroundEvent::ProviderModelsListed { provider: name, models },
+                    Err(err) => BackgroundEvent::ProviderDiscoveryFailed { provider: name, error: format!("{:#}", err) },
+                };
+                let _ = tx.send(event);
+            });
+        }
+    }
+
+    /// A custom provider's server listed its models; the configured ones stay when it lists none
+    pub fn provider_models_listed(&mut self, provider: &str, models: Vec<String>) {
+        let Some(custom) = self.config.custom_provider(provider) else {
+            return;
+        };
+        self.debug_info.push(format!("{} at {}: {} model(s)", provider, custom.base_url, models.len()));
+        let models = if models.is_empty() { custom.models.clone() } else { models };
+        self.set_provider_models(provider, models);
+    }
+
+    /// Replaces a provider's model list, keeping the selected provider and model
+    fn set_provider_models(&mut self, provider: &str, models: Vec<String>) {
+        let selected_provider = self.get_current_provider().map(str::to_string);
+        let selected_model = self.get_current_model().map(str::to_string);
+        self.models.insert(provider.to_string(), models);
+        if !self.providers.iter().any(|p| p == provider) {
+            self.providers.push(provider.to_string());
+        }
+        if let Some(selected_provider) = selected_provider {
+            self.select_model(&selected_provider, selected_model.as_deref().unwrap_or_default());
+        }
+    }
+
+    fn select_model(&mut self, provider: &str, model: &str) {
+        if let Some(i) = self.providers.iter().position(|p| p == provider) {
+            self.selected_provider_index = Some(i);
+            self.selected_model_index = self.models.get(provider)
+                .and_then(|models| models.iter().position(|m| m == model))
+                .or(Some(0));
         }
     }
 
-    pub fn on_tick(&mut self) {
-        // Update job status if there's a current job
-        if let Some(job_id) = &self.current_job_id {
-            // In a real app, this would make an API call to get job status
-            self.debug_info.push(format!("Checking job status for {}", job_id));
+    /// Deprecated or withdrawn models among the configured default and the selected one
+    pub fn model_warnings(&self) -> Vec<ModelWarning> {
+        let Some(catalog) = &self.model_catalog else {
+            return Vec::new();
+        };
+        let settings = &self.config.model_catalog;
+        let mut warnings: Vec<ModelWarning> = catalog
+            .check(&self.config.default_provider, &self.config.default_model, settings)
+            .into_iter()
+            .collect();
+        if let (Some(provider), Some(model)) = (self.get_current_provider(), self.get_current_model()) {
+            if let Some(warning) = catalog.check(provider, model, settings) {
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+        }
+        warnings
+    }
+
+    /// Switches the default and the selected model to the suggested replacements
+    pub fn apply_model_replacements(&mut self) {
+        let warnings = self.model_warnings();
+        let mut switched = Vec::new();
+        for warning in &warnings {
+            let Some(replacement) = &warning.replacement else {
+                continue;
+            };
+            if warning.provider == self.config.default_provider && warning.model == self.config.default_model {
+                self.config.default_model = replacement.clone();
+            }
+            if self.get_current_provider() == Some(warning.provider.as_str()) && self.get_current_model() == Some(warning.model.as_str()) {
+                self.select_model(&warning.provider, replacement);
+            }
+            switched.push(format!("{} -> {}", warning.model, replacement));
+        }
+        self.message = Some(if switched.is_empty() {
+            if warnings.is_empty() {
+                "The configured models are all current".to_string()
+            } else {
+                "No replacement known; set one in [model_catalog.replacements]".to_string()
+            }
+        } else {
+            match self.config.save() {
+                Ok(()) => format!("Switched models: {}", switched.join(", ")),
+                Err(err) => format!("Switched models: {} (saving the config failed: {})", switched.join(", "), err),
+            }
+        });
+    }
+
+    /// Local presets of the active workspace and shared presets by name; local definitions win
+    pub fn effective_presets(&self) -> std::collections::BTreeMap<String, (Preset, Origin)> {
+        preset::merge(self.config.active_presets(), &self.shared_presets.collection.presets)
+    }
+
+    /// Local and shared prompt templates by name; local definitions win
+    pub fn effective_templates(&self) -> std::collections::BTreeMap<String, (String, Origin)> {
+        preset::merge(&self.config.templates, &self.shared_presets.collection.templates)
+    }
+
+    fn publish_snapshot(&self) {
+        let Some(shared) = &self.shared_state else {
+            return;
+        };
+        let Ok(mut snapshot) = shared.lock() else {
+            return;
+        };
+        snapshot.screen = format!("{:?}", self.state);
+        snapshot.current_job_id = self.current_job_id.clone();
+        snapshot.job_status = self.job_status.clone();
+        snapshot.job_progress = self.job_progress;
+        snapshot.recent_jobs = self.history.recent(10).into_iter().cloned().collect();
+        let log: Vec<&String> = self.debug_info.iter().chain(self.message.iter()).collect();
+        snapshot.log = log[log.len().saturating_sub(50)..].iter().map(|line| line.to_string()).collect();
+    }
+
+    pub fn handle_background_event(&mut self, event: BackgroundEvent) {
+        match event {
+            BackgroundEvent::UpdateAvailable(version) => self.update_available = Some(version),
+            BackgroundEvent::BackendVersion(version) => {
+                self.backend_version = Some(version);
+                self.backend_error = None;
+            },
+            BackgroundEvent::BackendUnreachable(err) => self.backend_error = Some(err),
+            BackgroundEvent::ModelsRefreshed(catalog) => self.models_refreshed(catalog),
+            BackgroundEvent::OllamaModelsListed(models) => self.ollama_models_listed(models),
+            BackgroundEvent::ProviderModelsListed { provider, models } => self.provider_models_listed(&provider, models),
+            BackgroundEvent::ProviderDiscoveryFailed { provider, error } => {
+                self.debug_info.push(format!("Model discovery of {} failed: {}", provider, error));
+            },
+            BackgroundEvent::OllamaUnreachable(err) => {
+                self.ollama_discovery_running = false;
+                self.debug_info.push(format!("Ollama model discovery failed: {}", err));
+                self.ollama_error = Some(err);
+            },
+            BackgroundEvent::ModelRefreshFailed(err) => {
+                self.model_refresh_running = false;
+                self.debug_info.push(format!("Model list refresh failed: {}", err));
+                self.model_refresh_error = Some(err);
+            },
+            BackgroundEvent::DownloadFinished { job_id, path, datasets, notes, open_in_editor } => {
+                self.debug_info.extend(notes.iter().cloned());
+                let failed_hooks = notes.iter().filter(|n| n.starts_with("Failed")).count();
+                self.message = Some(if failed_hooks > 0 {
+                    format!("Results of {} saved to {} ({} post-download action(s) failed)", job_id, path.display(), failed_hooks)
+                } else {
+                    format!("Results of {} saved to {} - 'v' on the Main screen to view", job_id, path.display())
+                });
+                self.last_download = Some(path.clone());
+                // An unpacked archive opens on the Stats screen with its first dataset
+                if let Some(dataset) = datasets.first().cloned() {
+                    self.last_download = Some(dataset.clone());
+                    let count = datasets.len();
+                    self.open_stats(datasets);
+                    if failed_hooks == 0 {
+                        self.message = Some(format!(
+                            "Results of {} unpacked: {} dataset file(s), showing stats of {}",
+                            job_id, count, dataset.display()
+                        ));
+                    }
+                }
+                if open_in_editor {
+                    self.pending_editor = Some(path);
+                }
+            },
+            BackgroundEvent::DownloadFailed { job_id, error } => {
+                self.message = Some(format!("Download of {} failed: {}", job_id, error));
+            },
+            BackgroundEvent::LocalRunStopped(job) => {
+                let job_id = job.job_id.clone();
+                self.local_run_stopped(job);
+                self.queued_job_ended(&job_id);
+            },
+            BackgroundEvent::LocalRunFailed { job_id, error, partial } => {
+                if self.current_job_id.as_deref() == Some(job_id.as_str()) {
+                    self.job_status = Some("failed".to_string());
+                }
+                self.report_job_status(&job_id, "failed");
+                self.message = Some(match partial.filter(|job| !job.run.results.is_empty()) {
+                    Some(job) => match self.save_partial(&job, &error) {
+                        Ok(path) => format!(
+                            "Job {} failed: {} - {} record(s) kept in {}; Ctrl+R on Job Status resumes",
+                            job_id, error, job.run.records(), path.display()
+                        ),
+                        Err(err) => format!("Job {} failed: {} (partial results not saved: {:#})", job_id, error, err),
+                    },
+                    None => format!("Job {} failed: {}", job_id, error),
+                });
+                self.queued_job_ended(&job_id);
+            },
+            BackgroundEvent::PartialSaved { job_id, result } => match result {
+                Ok(path) => {
+                    self.message = Some(format!("Job {} failed; the results it got done are in {}", job_id, path.display()));
+                },
+                // Most backends keep nothing of a failed job, so this is not worth a message
+                Err(err) => self.debug_info.push(format!("No partial results of {}: {}", job_id, err)),
+            },
+            BackgroundEvent::UploadProgress { file, sent, total } => {
+                if let Some(upload) = self.uploads.get_mut(&file) {
+                    upload.sent = sent;
+                    upload.total = total;
+                }
+            },
+            BackgroundEvent::UploadFinished { file, file_id } => {
+                self.uploads.remove(&file);
+                self.message = Some(format!("Uploaded {}", file));
+                self.uploaded_file_ids.insert(file.clone(), file_id);
+                if !self.uploaded_files.contains(&file) {
+                    self.uploaded_files.push(file);
+                }
+                if self.selected_file_index.is_none() {
+                    self.selected_file_index = Some(self.uploaded_files.len() - 1);
+                }
+            },
+            BackgroundEvent::UploadFailed { file, error } => {
+                self.uploads.remove(&file);
+                self.message = Some(format!("Upload of {} failed: {}", file, error));
+            },
+            BackgroundEvent::JobSubmitted { file, job_id } => match self.submissions.remove(&file) {
+                Some(submission) if submission.retry_of.is_some() => self.job_resubmitted(job_id, submission),
+                submission => {
+                    if let Some(submission) = submission {
+                        self.submitted_jobs.insert(job_id.clone(), submission);
+                    }
+                    self.job_started(job_id, file);
+                },
+            },
+            BackgroundEvent::SubmitFailed { file, error } => {
+                self.message = Some(format!("Submitting {} failed: {}", file, error));
+                if let Some(submission) = self.submissions.remove(&file) {
+                    self.schedule_retry(submission, &error);
+                }
+            },
+            BackgroundEvent::JobStatusUpdated(status) => self.job_status_updated(status),
+            BackgroundEvent::StatusPollFailed { job_id, error } => {
+                self.status_poll_running = false;
+                // With resubmitting on, polling outlasts a backend restart; otherwise it resumes
+                // when the job is looked up again on the Job Status screen
+                let retry = &self.config.retry;
+                if !(self.submitted_jobs.contains_key(&job_id) && retry.max_attempts > 1 && retry.is_retryable(&error)) {
+                    self.unpolled_jobs.insert(job_id.clone());
+                }
+                self.message = Some(format!("Status of {} unavailable: {}", job_id, error));
+            },
+            BackgroundEvent::PresetsSynced(shared) => {
+                self.preset_sync_running = false;
+                if let Some(reason) = &shared.stale_reason {
+                    self.debug_info.push(format!("Preset sync failed, using cached copy: {}", reason));
+                }
+                self.shared_presets = shared;
+            },
+            BackgroundEvent::NotificationFailed(err) => {
+                self.debug_info.push(format!("Desktop notification failed: {}", err));
+                self.ring_bell |= self.config.notifications.bell;
+            },
+            BackgroundEvent::PresetSyncFailed(err) => {
+                self.preset_sync_running = false;
+                self.debug_info.push(format!("Preset sync failed: {}", err));
+                self.message = Some(format!("Shared preset sync failed: {}", err));
+            },
+            BackgroundEvent::JobCancelled { job_id } => {
+                self.message = Some(format!("Job {} cancelled", job_id));
+                if let Some(job) = self.jobs.iter_mut().find(|job| job.job_id == job_id) {
+                    job.status = "cancelled".to_string();
+                }
+                if self.current_job_id.as_deref() == Some(job_id.as_str()) {
+                    self.job_status = Some("cancelled".to_string());
+                }
+                self.report_job_status(&job_id, "cancelled");
+            },
+            BackgroundEvent::CancelFailed { job_id, error } => {
+                self.message = Some(format!("Cancelling {} failed: {}", job_id, error));
+            },
+            BackgroundEvent::JobsListed(page) => {
+                self.jobs = page.jobs;
+                self.jobs_page = page.page;
+                self.jobs_total_pages = page.total_pages;
+                self.jobs_total = page.total;
+                self.jobs_loading = false;
+                self.jobs_loaded = true;
+                self.jobs_error = None;
+                self.clamp_jobs_selection();
+            },
+            BackgroundEvent::JobsFailed(err) => {
+                self.jobs_loading = false;
+                self.jobs_loaded = true;
+                self.jobs_error = Some(err);
+            },
+            BackgroundEvent::BackendsDiscovered(backends) => {
+                self.discovered_backends = backends;
+                if let Some(picker) = &mut self.backend_picker {
+                    picker.scanning = false;
+                    picker.error = None;
+                    picker.backends = self.discovered_backends.clone();
+                    picker.selected = picker.selected.min(picker.backends.len().saturating_sub(1));
+                } else if !self.tutorial.is_active() {
+                    self.offer_backends();
+                }
+            },
+            BackgroundEvent::DiscoveryFailed(err) => {
+                self.debug_info.push(format!("Backend discovery failed: {}", err));
+                if let Some(picker) = &mut self.backend_picker {
+                    picker.scanning = false;
+                    picker.error = Some(err);
+                }
+            },
+            BackgroundEvent::KeyChecked { provider, result } => {
+                self.message = Some(match &result {
+                    Ok(()) => format!("{} key accepted", provider),
+                    Err(err) => format!("{} key: {}", provider, err),
+                });
+                if let Some(manager) = &mut self.key_manager {
+                    manager.checked(&provider, result);
+                }
+            },
+            BackgroundEvent::StorageScanned(groups) => self.storage.scanned(groups),
+            BackgroundEvent::PreflightFinished { file, report } => self.preflight_finished(file, report),
+            BackgroundEvent::PreviewFinished(preview) => self.preview_finished(preview),
+            BackgroundEvent::PreviewFailed { file, error } => {
+                self.message = Some(format!("Preview of {} failed: {}", file, error));
+            },
+            BackgroundEvent::StorageScanFailed(err) => {
+                self.storage.loading = false;
+                self.storage.error = Some(err);
+            },
+            BackgroundEvent::CleanupFinished(report) => {
+                self.storage.cleaning = false;
+                for err in &report.errors {
+                    self.debug_info.push(format!("Cleanup failed for {}", err));
+                }
+                if !report.removed.is_empty() || !report.errors.is_empty() {
+                    let mut message = format!(
+                        "Cleanup removed {} job(s), freed {}",
+                        report.removed.len(), diskspace::format_bytes(report.freed)
+                    );
+                    if !report.errors.is_empty() {
+                        message.push_str(&format!(", {} failed", report.errors.len()));
+                    }
+                    self.message = Some(message);
+                }
+                if self.state == AppState::Storage {
+                    self.refresh_storage();
+                }
+            },
+            BackgroundEvent::CleanupFailed(err) => {
+                self.storage.cleaning = false;
+                self.debug_info.push(format!("Cleanup failed: {}", err));
+                self.message = Some(format!("Cleanup failed: {}", err));
+            },
+            BackgroundEvent::BackendsProbed(probes) => {
+                self.probing_backends = false;
+                let fastest = latency::fastest(&probes).cloned();
+                self.backend_probes = probes;
+                match fastest {
+                    Some(probe) if probe.url != self.backend_url => {
+                        self.connect_backend(&probe.url);
+                        self.message = Some(format!("Using fastest backend {} ({})", probe.url, probe.describe()));
+                    },
+                    Some(probe) => {
+                        self.message = Some(format!("Backend {} is the fastest ({})", probe.url, probe.describe()));
+                    },
+                    None => {
+                        self.message = Some(format!("No backend answered; staying on {}", self.backend_url));
+                    },
+                }
+            },
+            BackgroundEvent::DatasetsListed(items) => {
+                self.message = Some(format!("{} dataset(s) on the backend", items.len()));
+                self.datasets.listed(items);
+            },
+            BackgroundEvent::DatasetsFailed(err) => self.datasets.failed(err),
+            BackgroundEvent::DatasetLoaded(dataset) => self.datasets.preview_loaded(dataset),
+            BackgroundEvent::DatasetDownloaded { id, path } => {
+                self.message = Some(format!("Dataset {} saved to {}", id, path.display()));
+                self.last_download = Some(path);
+            },
+            BackgroundEvent::DatasetFailed { id, error } => {
+                self.message = Some(format!("Dataset {}: {}", id, error));
+            },
+        }
+    }
+
+    pub fn start_tutorial(&mut self) {
+        self.tutorial.start();
+        self.sync_tutorial_screen();
+    }
+
+    pub fn handle_tutorial_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char(' ') => self.tutorial.next(),
+            KeyCode::Left | KeyCode::Backspace => self.tutorial.back(),
+            KeyCode::Esc => self.tutorial.dismiss(),
+            _ => {},
+        }
+        self.sync_tutorial_screen();
+    }
+
+    // Przełącza ekran pod podpowiedzią; po zakończeniu wraca na Main
+    fn sync_tutorial_screen(&mut self) {
+        match self.tutorial.current() {
+            Some(step) => self.state = step.screen(),
+            None => {
+                self.tutorial_seen = true;
+                self.state = AppState::Main;
+                self.message = Some("Tutorial closed - press 't' on the Main screen to replay it".to_string());
+                self.offer_backends();
+            },
         }
     }
 
     pub fn handle_upload_input(&mut self, key: KeyEvent) {
         match key.code {
-            KeyCode::Char('f') => {
-                // Simulate file upload
-                self.uploaded_files.push(format!("file_{}.pdf", self.uploaded_files.len() + 1));
-                self.message = Some("File uploaded successfully".to_string());
+            KeyCode::Down => self.file_browser.next(),
+            KeyCode::Up => self.file_browser.previous(),
+            KeyCode::Enter => {
+                if let Some(path) = self.file_browser.enter() {
+                    self.upload_file(path);
+                }
             },
+            KeyCode::Backspace | KeyCode::Left => self.file_browser.parent(),
+            KeyCode::Char('.') => self.file_browser.toggle_hidden(),
+            KeyCode::Char('s') => self.file_browser.cycle_sort(),
+            KeyCode::Char('r') => self.file_browser.refresh(),
             _ => {},
         }
     }
 
+    /// Uploads a file picked in the browser to the backend through the API worker
+    pub fn upload_file(&mut self, path: std::path::PathBuf) {
+        let file = path.to_string_lossy().into_owned();
+        if self.uploaded_files.contains(&file) {
+            self.message = Some(format!("{} is already uploaded", file));
+            return;
+        }
+        let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        if size > self.config.max_upload_size_mb * 1024 * 1024 {
+            self.message = Some(format!(
+                "{} is larger than the {} MB upload limit (max_upload_size_mb in config)", file, self.config.max_upload_size_mb
+            ));
+            return;
+        }
+
+        if self.uploads.contains_key(&file) {
+            self.message = Some(format!("{} is already being uploaded", file));
+            return;
+        }
+
+        self.message = Some(if self.worker.send(WorkerCommand::Upload { file: file.clone() }) {
+            self.uploads.insert(file.clone(), UploadProgress::new(size));
+            format!("Uploading {}...", file)
+        } else {
+            "Cannot upload: the API worker has stopped".to_string()
+        });
+    }
+
     pub fn handle_process_input(&mut self, key: KeyEvent) {
         match key.code {
             KeyCode::Char('1') => self.processing_type = ProcessingType::Standard,
             KeyCode::Char('2') => self.processing_type = ProcessingType::Article,
             KeyCode::Char('3') => self.processing_type = ProcessingType::Translate,
             KeyCode::Char('4') => self.processing_type = ProcessingType::Batch,
+            KeyCode::Char('+') => {
+                self.job_spend_cap = Some(self.job_spend_cap.map_or(1.0, |cap| cap + 1.0));
+            },
+            KeyCode::Char('-') => {
+                self.job_spend_cap = self.job_spend_cap.map(|cap| cap - 1.0).filter(|cap| *cap > 0.0);
+            },
+            KeyCode::Char('0') => self.job_spend_cap = None,
+            KeyCode::Char('f') => self.output_format = self.output_format.next(),
+            KeyCode::Char('a') => self.sampling_editor = Some(SamplingEditor::open(&self.sampling)),
+            KeyCode::Char('w') => self.keyword_editor = Some(KeywordEditor::default()),
+            KeyCode::Char('P') => self.open_preset_picker(),
             KeyCode::Char('p') if !self.uploaded_files.is_empty() => {
                 if let Some(index) = self.selected_file_index {
-                    let file = &self.uploaded_files[index];
-                    // Simulate job submission
-                    self.current_job_id = Some(format!("job_{}", uuid::Uuid::new_v4()));
-                    self.job_progress = Some((0, 100));
-                    self.job_status = Some("processing".to_string());
-                    self.message = Some(format!("Processing {} with {} type", file, self.processing_type.to_str()));
+                    let file = self.uploaded_files[index].clone();
+                    self.start_preflight(file);
                 } else {
                     self.message = Some("No file selected".to_string());
                 }
             },
-            KeyCode::Down => {
-                if !self.uploaded_files.is_empty() {
-                    let new_index = match self.selected_file_index {
-                        Some(i) if i < self.uploaded_files.len() - 1 => Some(i + 1),
-                        Some(_) => Some(0),
-                        None => Some(0),
-                    };
-                    self.selected_file_index = new_index;
+            KeyCode::Char('v') if !self.uploaded_files.is_empty() => {
+                if let Some(index) = self.selected_file_index {
+                    let file = self.uploaded_files[index].clone();
+                    self.start_preview(file);
+                } else {
+                    self.message = Some("No file selected".to_string());
                 }
             },
-            KeyCode::Up => {
-                if !self.uploaded_files.is_empty() {
-                    let new_index = match self.selected_file_index {
-                        Some(i) if i > 0 => Some(i - 1),
-                        Some(_) => Some(self.uploaded_files.len() - 1),
-                        None => Some(0),
-                    };
-                    self.selected_file_index = new_index;
+            KeyCode::Char('k') if !self.uploaded_files.is_empty() => {
+                if let Some(index) = self.selected_file_index {
+                    let file = self.uploaded_files[index].clone();
+                    self.open_chunk_tuner(&file);
+                } else {
+                    self.message = Some("No file selected".to_string());
                 }
             },
+            KeyCode::Down if !self.uploaded_files.is_empty() => {
+                let new_index = match self.selected_file_index {
+                    Some(i) if i < self.uploaded_files.len() - 1 => Some(i + 1),
+                    Some(_) => Some(0),
+                    None => Some(0),
+                };
+                self.selected_file_index = new_index;
+            },
+            KeyCode::Up if !self.uploaded_files.is_empty() => {
+                let new_index = match self.selected_file_index {
+                    Some(i) if i > 0 => Some(i - 1),
+                    Some(_) => Some(self.uploaded_files.len() - 1),
+                    None => Some(0),
+                };
+                self.selected_file_index = new_index;
+            },
             _ => {},
         }
     }
 
-    pub fn handle_settings_input(&mut self, key: KeyEvent) {
+    /// Runs the pipeline on a few sampled chunks of `file`; the records open in the Results screen
+    fn start_preview(&mut self, file: String) {
+        let processing_type = self.processing_type.to_str().to_string();
+        let config = self.processor_config(&file);
+        let samples = self.config.preview.sample_count();
+        // A new sample on every preview
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        self.message = Some(format!("Previewing {} on {} sampled chunk(s)...", file, samples));
+        let tx = self.background_tx.clone();
+        background::spawn("Preview", move |_| {
+            let event = match preview::run(&file, &processing_type, &config, samples, seed) {
+                Ok(preview) => BackgroundEvent::PreviewFinished(preview),
+                Err(err) => BackgroundEvent::PreviewFailed { file, error: format!("{:#}", err) },
+            };
+            let _ = tx.send(event);
+        });
+    }
+
+    fn preview_finished(&mut self, preview: Preview) {
+        let records = preview.records.iter().filter_map(|record| serde_json::to_value(record).ok()).collect();
+        let path = std::path::PathBuf::from(format!("preview of {}", preview.file));
+        self.results_viewer = Some(ResultsViewer::new(&path, records));
+        self.message = Some(format!("{}; press 'p' on Process to run the whole file", preview.summary()));
+        self.state = AppState::Results;
+    }
+
+    /// Files a job started for `file` processes: all uploaded files in a batch
+    fn job_files(&self, file: &str) -> Vec<String> {
+        if self.processing_type == ProcessingType::Batch {
+            self.uploaded_files.clone()
+        } else {
+            vec![file.to_string()]
+        }
+    }
+
+    /// Checks the job's files in the background; the job is submitted when nothing is
+    /// flagged, otherwise the report is shown first
+    fn start_preflight(&mut self, file: String) {
+        if !self.config.preflight.enabled {
+            self.check_quota_and_submit(file);
+            return;
+        }
+        let files = self.job_files(&file);
+        let settings = self.config.preflight.clone();
+        self.message = Some(format!("Checking {} file(s) before processing...", files.len()));
+        let tx = self.background_tx.clone();
+        background::spawn("Pre-flight check", move |_| {
+            let report = preflight::analyze(&files, &settings);
+            let _ = tx.send(BackgroundEvent::PreflightFinished { file, report });
+        });
+    }
+
+    fn preflight_finished(&mut self, file: String, report: CorpusReport) {
+        if report.is_clean() {
+            self.debug_info.push(format!("Pre-flight: {}", report.summary()));
+            self.check_quota_and_submit(file);
+        } else {
+            self.preflight_dialog = Some(PreflightDialog { report, file, excluded: BTreeSet::new(), selected: 0 });
+        }
+    }
+
+    /// Pre-flight popup: ↑/↓ select a flagged file, Space excludes or includes it, 'a' excludes
+    /// every flagged file, Enter submits without the excluded files, Esc cancels
+    pub fn handle_preflight_dialog_input(&mut self, key: KeyEvent) {
+        let Some(dialog) = self.preflight_dialog.as_mut() else {
+            return;
+        };
+        let flagged = dialog.report.flagged().len();
         match key.code {
-            KeyCode::Char('l') => {
-                self.language = if self.language == "en" { "pl".to_string() } else { "en".to_string() };
-                self.message = Some(format!("Language changed to {}", self.language));
-            },
-            KeyCode::Char('p') => {
-                if !self.providers.is_empty() {
-                    let new_index = match self.selected_provider_index {
-                        Some(i) if i < self.providers.len() - 1 => Some(i + 1),
-                        Some(_) => Some(0),
-                        None => Some(0),
-                    };
-                    self.selected_provider_index = new_index;
-                    self.selected_model_index = Some(0);
+            KeyCode::Down if flagged > 0 => dialog.selected = (dialog.selected + 1) % flagged,
+            KeyCode::Up if flagged > 0 => dialog.selected = (dialog.selected + flagged - 1) % flagged,
+            KeyCode::Char(' ') => {
+                if let Some(file) = dialog.current().map(str::to_string) {
+                    if !dialog.excluded.remove(&file) {
+                        dialog.excluded.insert(file);
+                    }
                 }
             },
-            KeyCode::Char('m') => {
-                if let Some(provider_idx) = self.selected_provider_index {
-                    let provider = &self.providers[provider_idx];
-                    if let Some(models) = self.models.get(provider) {
+            KeyCode::Char('a') => {
+                let files: Vec<String> = dialog.report.flagged().iter().map(|report| report.file.clone()).collect();
+                dialog.excluded.extend(files);
+            },
+            KeyCode::Esc => {
+                self.preflight_dialog = None;
+                self.message = Some("Job not submitted".to_string());
+            },
+            KeyCode::Enter => {
+                let Some(dialog) = self.preflight_dialog.take() else {
+                    return;
+                };
+                self.exclude_files(&dialog.excluded);
+                let file = if dialog.excluded.contains(&dialog.file) {
+                    // A batch goes on with the files that are left
+                    match self.uploaded_files.first() {
+                        Some(first) if self.processing_type == ProcessingType::Batch => first.clone(),
+                        _ => {
+                            self.message = Some(format!("{} excluded; nothing submitted", dialog.file));
+                            return;
+                        },
+                    }
+                } else {
+                    dialog.file
+                };
+                self.check_quota_and_submit(file);
+            },
+            _ => {},
+        }
+    }
+
+    /// Removes files from the uploaded ones, keeping the selection on the same file if it stays
+    fn exclude_files(&mut self, excluded: &BTreeSet<String>) {
+        if excluded.is_empty() {
+            return;
+        }
+        let selected = self.selected_file_index.and_then(|i| self.uploaded_files.get(i).cloned());
+        self.uploaded_files.retain(|file| !excluded.contains(file));
+        self.uploaded_file_ids.retain(|file, _| !excluded.contains(file));
+        self.selected_file_index = selected
+            .and_then(|file| self.uploaded_files.iter().position(|f| *f == file))
+            .or_else(|| (!self.uploaded_files.is_empty()).then_some(0));
+        self.debug_info.push(format!("Excluded after pre-flight: {}", excluded.iter().cloned().collect::<Vec<_>>().join(", ")));
+    }
+
+    fn check_quota_and_submit(&mut self, file: String) {
+        let provider = self.get_current_provider().unwrap_or_default().to_string();
+        match self.quota_guard().check(&provider) {
+            Ok(()) => self.submit_job(file),
+            Err(exceeded) => self.quota_dialog = Some(QuotaDialog { exceeded, file }),
+        }
+    }
+
+    fn submit_job(&mut self, file: String) {
+        let provider = self.get_current_provider().unwrap_or_default().to_string();
+        if provider == ollama::PROVIDER && self.get_current_model().is_none() {
+            self.message = Some(format!(
+                "No Ollama model found at {} - pull one with `ollama pull` and press 'M' in Settings",
+                self.config.ollama.base_url
+            ));
+            return;
+        }
+        if let Some(warning) = self.model_warnings().into_iter().find(|w| w.provider == provider && Some(w.model.as_str()) == self.get_current_model()) {
+            if warning.is_withdrawn() {
+                self.message = Some(format!("Not submitted: {} - press 'u' in Settings to switch", warning));
+                return;
+            }
+            self.debug_info.push(format!("Submitting with {}", warning));
+        }
+        let capped = match self.job_spend_cap {
+            Some(max_usd) => match self.config.token_prices.get(&provider) {
+                Some(price) => Some((max_usd, *price)),
+                None => {
+                    self.message = Some(format!(
+                        "A spend cap needs the token price of {} ([token_prices] in config)", provider
+                    ));
+                    return;
+                },
+            },
+            None => None,
+        };
+
+        if let Some((max_usd, usd_per_1k_tokens)) = capped {
+            let job_id = format!("job_{}", uuid::Uuid::new_v4());
+            self.unpolled_jobs.insert(job_id.clone());
+            self.job_started(job_id.clone(), file.clone());
+            self.start_capped_job(job_id, file, max_usd, usd_per_1k_tokens);
+            return;
+        }
+
+        let Some(file_id) = self.uploaded_file_ids.get(&file).cloned() else {
+            self.message = Some(format!("{} is not on the backend yet - upload it on the Upload screen", file));
+            return;
+        };
+        let config = self.build_processing_config(&file);
+        let api_key = self.config.custom_provider(&provider).and_then(|custom| custom.api_key(&provider));
+        let submission = Submission { file: file.clone(), file_id, config, api_key, attempt: 1, retry_of: None };
+        self.message = Some(if self.send_submission(submission) {
+            format!("Submitting {}...", file)
+        } else {
+            "Cannot submit: the API worker has stopped".to_string()
+        });
+    }
+
+    fn send_submission(&mut self, submission: Submission) -> bool {
+        let command = WorkerCommand::Submit {
+            file: submission.file.clone(),
+            file_id: submission.file_id.clone(),
+            config: Box::new(submission.config.clone()),
+            api_key: submission.api_key.clone(),
+        };
+        let sent = self.worker.send(command);
+        if sent {
+            self.submissions.insert(submission.file.clone(), submission);
+        }
+        sent
+    }
+
+    /// Queues another attempt of a failed submission or job if `[retry]` allows one
+    fn schedule_retry(&mut self, submission: Submission, error: &str) {
+        let Some(delay) = self.config.retry.next_attempt(submission.attempt, error) else {
+            return;
+        };
+        let note = format!(
+            "Resubmitting {} in {}s (attempt {}/{})",
+            submission.file, delay.as_secs(), submission.attempt + 1, self.config.retry.max_attempts
+        );
+        log_info!("{} after: {}", note, error);
+        self.debug_info.push(format!("{} after: {}", note, error));
+        self.message = Some(match &self.message {
+            Some(message) => format!("{} - {}", message, note),
+            None => note,
+        });
+        self.scheduled_retries.push(ScheduledRetry { due: std::time::Instant::now() + delay, submission });
+    }
+
+    /// Submits the retries whose backoff has passed. Retries run unattended, so one that would
+    /// go over the provider's quota is dropped instead of asking to override it.
+    fn submit_due_retries(&mut self) {
+        let now = std::time::Instant::now();
+        let (due, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.scheduled_retries)
+            .into_iter()
+            .partition(|retry| retry.due <= now);
+        self.scheduled_retries = waiting;
+        for ScheduledRetry { mut submission, .. } in due {
+            submission.attempt += 1;
+            let file = submission.file.clone();
+            let attempt = submission.attempt;
+            if let Err(exceeded) = self.quota_guard().check(&submission.config.provider) {
+                let note = format!("Not resubmitting {}: {}", file, exceeded);
+                log_info!("{}", note);
+                self.debug_info.push(note.clone());
+                self.message = Some(note);
+                continue;
+            }
+            if !self.send_submission(submission) {
+                self.message = Some(format!("Cannot resubmit {}: the API worker has stopped", file));
+                continue;
+            }
+            self.message = Some(format!(
+                "Resubmitting {} (attempt {}/{})...", file, attempt, self.config.retry.max_attempts
+            ));
+        }
+    }
+
+    /// Records a job submitted again under `[retry]` with the settings of the failed one,
+    /// and follows it in place of that one
+    fn job_resubmitted(&mut self, job_id: String, submission: Submission) {
+        let failed = submission.retry_of.clone().unwrap_or_default();
+        let entry = self.history.entries().iter().rev().find(|e| e.job_id == failed).cloned();
+        let Some(mut entry) = entry else {
+            self.submitted_jobs.insert(job_id.clone(), submission.clone());
+            self.job_started(job_id, submission.file);
+            return;
+        };
+        if self.current_job_id.as_deref() == Some(failed.as_str()) {
+            self.current_job_id = Some(job_id.clone());
+            self.job_id_input = Input::new(job_id.clone());
+            self.job_progress = None;
+            self.job_status = Some("processing".to_string());
+            self.status_poll_started = None;
+        }
+        self.message = Some(format!(
+            "Resubmitted {} as {} (attempt {}/{})",
+            submission.file, job_id, submission.attempt, self.config.retry.max_attempts
+        ));
+        entry.job_id = job_id.clone();
+        entry.status = "processing".to_string();
+        entry.submitted_at = chrono::Utc::now();
+        entry.finished_at = None;
+        entry.records = None;
+        entry.tokens = None;
+        entry.cost_usd = None;
+        self.emit_event(WebhookEvent::job_created(&entry));
+        if let Err(err) = self.history.record(entry) {
+            self.debug_info.push(format!("Failed to save job history: {}", err));
+        }
+        self.submitted_jobs.insert(job_id, submission);
+    }
+
+    /// Makes a just submitted job the current one and records it in the history
+    fn job_started(&mut self, job_id: String, file: String) {
+        self.current_job_id = Some(job_id.clone());
+        self.job_progress = None;
+        self.job_status = Some("processing".to_string());
+        self.status_poll_started = None;
+        self.message = Some(format!("Processing {} with {} type", file, self.processing_type.to_str()));
+        self.last_processing_config = Some(self.build_processing_config(&file));
+        self.record_history(job_id, file);
+    }
+
+    /// Applies a polled job status; history and webhooks only hear about changes
+    fn job_status_updated(&mut self, status: JobStatus) {
+        self.status_poll_running = false;
+        if let Some(job) = self.jobs.iter_mut().find(|job| job.job_id == status.job_id) {
+            *job = status.clone();
+        }
+        self.show_job_status(&status);
+        if status.status == "failed" {
+            if let Some(mut submission) = self.submitted_jobs.remove(&status.job_id) {
+                submission.retry_of = Some(status.job_id.clone());
+                self.schedule_retry(submission, status.error.as_deref().unwrap_or_default());
+            }
+        }
+    }
+
+    fn show_job_status(&mut self, status: &JobStatus) {
+        if self.current_job_id.as_deref() != Some(status.job_id.as_str()) {
+            return;
+        }
+        if let (Some(current), Some(total)) = (status.current, status.total) {
+            self.job_progress = Some((current, total));
+        }
+        self.job_owner = status.owner.clone();
+        if let Some(error) = &status.error {
+            self.message = Some(format!("Job {} failed: {}", status.job_id, error));
+        }
+        if self.job_status.as_deref() != Some(status.status.as_str()) {
+            self.job_status = Some(status.status.clone());
+            self.report_job_status(&status.job_id, &status.status);
+            if status.status == "failed" {
+                self.fetch_backend_partial(status);
+            }
+        }
+    }
+
+    /// Downloads whatever a failed backend job got done to its partial output, with the
+    /// failure reason in the manifest
+    fn fetch_backend_partial(&mut self, status: &JobStatus) {
+        let Some(entry) = self.history.entries().iter().rev().find(|e| e.job_id == status.job_id).cloned() else {
+            return;
+        };
+        let path = match self.output_path_for(&entry) {
+            Ok(output) => partial::path_for(&output),
+            Err(err) => {
+                self.debug_info.push(format!("No partial results of {}: {:#}", entry.job_id, err));
+                return;
+            },
+        };
+        let client = ApiClient::new(&self.backend_url)
+            .with_monitor(self.network.clone())
+            .with_download_settings(self.config.download.clone())
+            .with_headers(self.config.request_headers());
+        let reason = status.error.clone().unwrap_or_else(|| "the backend reported the job as failed".to_string());
+        let tx = self.background_tx.clone();
+        background::spawn("Partial results", move |runtime| {
+            let result = runtime.block_on(async {
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                client.download_results(&entry.job_id, &path).await
+            });
+            let result = result
+                .and_then(|()| partial::record_failure(&path, &entry, &reason, Vec::new()))
+                .map(|_| path)
+                .map_err(|err| format!("{:#}", err));
+            let _ = tx.send(BackgroundEvent::PartialSaved { job_id: entry.job_id, result });
+        });
+    }
+
+    /// Configuration the local processors run `file` with
+    fn processor_config(&self, file: &str) -> ProcessorConfig {
+        let request = self.build_processing_config(file);
+        ProcessorConfig {
+            model: request.model,
+            provider: request.provider,
+            language: self.language.clone(),
+            system_prompt: request.system_prompt,
+            keywords: self.keywords.clone(),
+            output_format: self.output_format.name().to_string(),
+            chunking: self.config.chunking.clone(),
+            cleanup: self.config.cleanup.clone(),
+            confidence: self.config.confidence.clone(),
+            figures: self.config.figures.clone(),
+            metadata: self.config.metadata.clone(),
+            best_of: self.config.best_of.clone(),
+            voting: self.config.voting.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Processes the job locally, tracking its cost against the spend cap
+    fn start_capped_job(&mut self, job_id: String, file: String, max_usd: f64, usd_per_1k_tokens: f64) {
+        let files = self.job_files(&file);
+        let processing_type = self.processing_type.to_str().to_string();
+        let config = self.processor_config(&file);
+        self.start_local_job(job_id, processing_type, config, files, LocalRun::default(), (max_usd, usd_per_1k_tokens));
+    }
+
+    /// Runs `files` on this machine, adding their results to those `run` already has
+    fn start_local_job(
+        &mut self,
+        job_id: String,
+        processing_type: String,
+        config: ProcessorConfig,
+        files: Vec<String>,
+        run: LocalRun,
+        (max_usd, usd_per_1k_tokens): (f64, f64),
+    ) {
+        let output = match self.history.entries().iter().rev().find(|e| e.job_id == job_id) {
+            Some(entry) => self.output_path_for(entry),
+            None => Err(anyhow::anyhow!("Job {} is missing from the history", job_id)),
+        };
+        let output = match output {
+            Ok(path) => path,
+            Err(err) => {
+                self.handle_background_event(BackgroundEvent::LocalRunFailed { job_id, error: format!("{:#}", err), partial: None });
+                return;
+            },
+        };
+
+        let audit = match self.audit_paths(&job_id) {
+            Ok(audit) => audit,
+            Err(err) => {
+                self.handle_background_event(BackgroundEvent::LocalRunFailed { job_id, error: format!("{:#}", err), partial: None });
+                return;
+            },
+        };
+
+        let done = run.results.len();
+        let job = LocalJob {
+            job_id,
+            processing_type,
+            config,
+            usd_per_1k_tokens,
+            cap_step_usd: max_usd,
+            cap: SpendCap::new(max_usd),
+            run,
+            total_files: done + files.len(),
+            output,
+            audit,
+            checks: self.config.checks.clone(),
+            vault: self.vault.clone(),
+        };
+        self.job_progress = Some((done as u64, job.total_files as u64));
+        self.run_local_job(job, files);
+    }
+
+    /// Audit archive and key file of a local job, when prompt/response capture is enabled
+    fn audit_paths(&self, job_id: &str) -> anyhow::Result<Option<(std::path::PathBuf, std::path::PathBuf)>> {
+        if !self.config.audit.enabled {
+            return Ok(None);
+        }
+        let dir = workspace::data_dir(self.config.active_workspace())?;
+        Ok(Some((audit::archive_path(&dir, job_id), self.config.audit.key_path()?)))
+    }
+
+    /// Queues the jobs of a job file; they run locally one after another, with the presets
+    /// and outputs the file gives them rather than the Process screen's selection
+    pub fn load_job_file(&mut self, path: &std::path::Path) {
+        let base = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(std::path::Path::new("."));
+        let jobs = JobFile::load(path)
+            .and_then(|file| file.resolve(base, &self.effective_presets(), &self.effective_templates()));
+        match jobs {
+            Ok(jobs) => {
+                self.message = Some(format!("Queued {} job(s) from {}", jobs.len(), path.display()));
+                self.job_queue.extend(jobs);
+                self.start_next_queued();
+            },
+            Err(err) => self.message = Some(format!("Failed to load {}: {:#}", path.display(), err)),
+        }
+    }
+
+    /// Starts the next job of the queue unless one of it is running
+    fn start_next_queued(&mut self) {
+        if self.queue_job_id.is_some() {
+            return;
+        }
+        let Some(job) = self.job_queue.pop_front() else {
+            return;
+        };
+        let job_id = format!("job_{}", uuid::Uuid::new_v4());
+        let provider = job.settings.provider.clone().unwrap_or_default();
+        self.record_entry(HistoryEntry {
+            job_id: job_id.clone(),
+            file: job.files[0].clone(),
+            processing_type: job.processing_type.clone(),
+            provider: provider.clone(),
+            model: job.settings.model.clone().unwrap_or_default(),
+            status: "processing".to_string(),
+            submitted_at: chrono::Utc::now(),
+            finished_at: None,
+            records: None,
+            tokens: None,
+            cost_usd: None,
+            variables: self.config.profile_variables(),
+        });
+        self.queue_job_id = Some(job_id.clone());
+        self.current_job_id = Some(job_id.clone());
+        self.job_status = Some("processing".to_string());
+        self.job_progress = Some((0, job.files.len() as u64));
+        self.message = Some(format!("Processing queued job {} ({} file(s), {} more queued)", job.name, job.files.len(), self.job_queue.len()));
+
+        let audit = self.audit_paths(&job_id).and_then(|audit| {
+            if let Some(dir) = job.output.parent() {
+                std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+            }
+            Ok(audit)
+        });
+        let audit = match audit {
+            Ok(audit) => audit,
+            Err(err) => {
+                self.handle_background_event(BackgroundEvent::LocalRunFailed { job_id, error: format!("{:#}", err), partial: None });
+                return;
+            },
+        };
+        let local = LocalJob {
+            job_id,
+            processing_type: job.processing_type.clone(),
+            config: job.processor_config(&self.config),
+            usd_per_1k_tokens: self.config.token_prices.get(&provider).copied().unwrap_or_default(),
+            cap_step_usd: f64::INFINITY,
+            cap: SpendCap::new(f64::INFINITY),
+            run: LocalRun::default(),
+            total_files: job.files.len(),
+            output: job.output,
+            audit,
+            checks: self.config.checks.clone(),
+            vault: self.vault.clone(),
+        };
+        self.run_local_job(local, job.files);
+    }
+
+    /// Goes on with the queue after its job `job_id` finished or failed
+    fn queued_job_ended(&mut self, job_id: &str) {
+        if self.queue_job_id.as_deref() != Some(job_id) {
+            return;
+        }
+        self.queue_job_id = None;
+        let ended = self.message.take();
+        self.start_next_queued();
+        self.message = match (ended, self.message.take()) {
+            (Some(ended), Some(next)) => Some(format!("{} - {}", ended, next)),
+            (ended, next) => next.or(ended),
+        };
+    }
+
+    fn run_local_job(&self, mut job: LocalJob, files: Vec<String>) {
+        let tx = self.background_tx.clone();
+        // Files stay pending until they are processed, so a failed run still knows what's left
+        job.run.pending = files;
+        background::spawn("Local processing", move |_| {
+            let result = processors::get_processor(&job.processing_type).and_then(|processor| {
+                let files = std::mem::take(&mut job.run.pending);
+                let run = processors::run_with_cap(
+                    processor.as_ref(), &files, &job.config, job.usd_per_1k_tokens, &mut job.cap,
+                );
+                let start = job.run.results.len();
+                job.run.results.extend(run.results);
+                job.run.pending = run.pending;
+                let new = &mut job.run.results[start..];
+                if !job.checks.is_empty() {
+                    Checks::load(&job.checks)?.apply(new);
+                }
+                if let Some((archive, key_file)) = &job.audit {
+                    let exchanges: Vec<_> = new.iter().flat_map(|r| r.exchanges.iter().cloned()).collect();
+                    audit::append(archive, &audit::load_or_create_key(key_file)?, &exchanges)?;
+                }
+                // Everything processed so far goes to disk, also when the run pauses
+                let content = serde_json::to_vec_pretty(&job.run.results)?;
+                job.vault.write(&job.output, content)?;
+                // The dataset itself stays in the native layout other tools read
+                let format = DatasetFormat::from_name(&job.config.output_format);
+                if format != DatasetFormat::Json {
+                    let converted = formats::convert(&job.run.results, format, job.config.system_prompt.as_deref())?;
+                    job.vault.write(&format.path_for(&job.output), serde_json::to_vec_pretty(&converted)?)?;
+                }
+                Ok(())
+            });
+            let event = match result {
+                Ok(()) => BackgroundEvent::LocalRunStopped(job),
+                Err(err) => BackgroundEvent::LocalRunFailed {
+                    job_id: job.job_id.clone(),
+                    error: format!("{:#}", err),
+                    partial: Some(job),
+                },
+            };
+            let _ = tx.send(event);
+        });
+    }
+
+    fn local_run_stopped(&mut self, job: LocalJob) {
+        self.last_local_output = Some(job.output.clone());
+        let exchanges: Vec<_> = job.run.results.iter().flat_map(|result| result.exchanges.iter().cloned()).collect();
+        self.transcripts.capture(&job.job_id, &exchanges, &self.config);
+        let done = job.total_files - job.run.pending.len();
+        let is_current = self.current_job_id.as_deref() == Some(job.job_id.as_str());
+        if is_current {
+            self.job_progress = Some((done as u64, job.total_files as u64));
+        }
+        let failed = job.run.failed();
+        for result in &failed {
+            self.debug_info.push(format!("{}: {}", job.job_id, result.error.as_deref().unwrap_or_default()));
+        }
+        let failed = if failed.is_empty() { String::new() } else { format!(", {} file(s) failed", failed.len()) };
+        if let Err(err) = self.history.update_usage(
+            &job.job_id, job.run.records() as u64, job.run.tokens() as u64, job.cap.spent_usd,
+        ) {
+            self.debug_info.push(format!("Failed to save job history: {}", err));
+        }
+
+        let status = if job.run.is_paused() { "paused" } else { "completed" };
+        if !job.run.is_paused() {
+            // A resumed job has its full output now
+            if let Err(err) = partial::discard(&job.output) {
+                self.debug_info.push(format!("{:#}", err));
+            }
+        }
+        if is_current {
+            self.job_status = Some(status.to_string());
+        }
+        self.report_job_status(&job.job_id, status);
+        if job.run.is_paused() {
+            self.message = Some(format!(
+                "Budget reached for {} after {}/{} file(s){} - partial results saved to {}",
+                job.job_id, done, job.total_files, failed, job.output.display()
+            ));
+            self.budget_prompt = Some(job);
+        } else {
+            let flagged = job.run.results.iter()
+                .flat_map(|result| &result.records)
+                .filter(|record| record.metadata.contains_key(checks::ISSUES))
+                .count();
+            let flagged = if flagged > 0 { format!(", {} flagged by checks", flagged) } else { String::new() };
+            let format = DatasetFormat::from_name(&job.config.output_format);
+            let converted = if format != DatasetFormat::Json {
+                format!(" ({} copy: {})", format, format.path_for(&job.output).display())
+            } else {
+                String::new()
+            };
+            self.message = Some(format!(
+                "Job {} finished: {} record(s){}{}, ${:.2}, saved to {}{}",
+                job.job_id, job.run.records(), flagged, failed, job.cap.spent_usd, job.output.display(), converted
+            ));
+        }
+    }
+
+    /// Writes the results a failed local job got done to its partial output
+    fn save_partial(&self, job: &LocalJob, reason: &str) -> anyhow::Result<std::path::PathBuf> {
+        let entry = self.history.entries().iter().rev()
+            .find(|e| e.job_id == job.job_id)
+            .with_context(|| format!("Job {} is missing from the history", job.job_id))?;
+        partial::save(&job.output, entry, &job.run.results, &job.run.pending, reason, &job.vault)
+    }
+
+    /// Continues the current job from its partial output: the files it didn't get done are
+    /// processed locally and added to the results it kept
+    pub fn resume_partial(&mut self) {
+        let Some(job_id) = self.current_job_id.clone() else {
+            self.message = Some("No job selected".to_string());
+            return;
+        };
+        let Some(entry) = self.history.entries().iter().rev().find(|e| e.job_id == job_id).cloned() else {
+            self.message = Some(format!("Job {} is missing from the history", job_id));
+            return;
+        };
+        let loaded = self.output_path_for(&entry).and_then(|output| partial::load(&output, &self.vault));
+        let partial = match loaded {
+            Ok(Some(partial)) => partial,
+            Ok(None) => {
+                self.message = Some(format!("Job {} has no partial results to resume from", job_id));
+                return;
+            },
+            Err(err) => {
+                self.message = Some(format!("Cannot resume {}: {:#}", job_id, err));
+                return;
+            },
+        };
+        if partial.remaining.is_empty() {
+            self.message = Some(format!(
+                "Nothing left to resume: {} holds everything {} got done before it failed",
+                partial.path.display(), job_id
+            ));
+            return;
+        }
+
+        let usd_per_1k_tokens = self.config.token_prices.get(&entry.provider).copied().unwrap_or(0.0);
+        let max_usd = self.job_spend_cap.unwrap_or(f64::INFINITY);
+        let mut config = self.processor_config(&entry.file);
+        config.provider = entry.provider.clone();
+        config.model = entry.model.clone();
+        self.message = Some(format!(
+            "Resuming {} after \"{}\": {} record(s) kept in {}, {} file(s) left",
+            job_id, partial.failure.reason, partial.records(), partial.path.display(), partial.remaining.len()
+        ));
+        self.job_status = Some("processing".to_string());
+        self.report_job_status(&job_id, "processing");
+        let run = LocalRun { results: partial.results, pending: Vec::new() };
+        self.start_local_job(job_id, entry.processing_type, config, partial.remaining, run, (max_usd, usd_per_1k_tokens));
+    }
+
+    /// "Budget reached - continue?" prompt: 'c' allows another cap's worth of spend,
+    /// Esc stops the job and keeps the partial results
+    pub fn handle_budget_prompt_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('c') => {
+                let Some(mut job) = self.budget_prompt.take() else {
+                    return;
+                };
+                job.cap.raise(job.cap_step_usd);
+                let files = std::mem::take(&mut job.run.pending);
+                if self.current_job_id.as_deref() == Some(job.job_id.as_str()) {
+                    self.job_status = Some("processing".to_string());
+                }
+                self.report_job_status(&job.job_id, "processing");
+                self.message = Some(format!("Continuing {} with a cap of ${:.2}", job.job_id, job.cap.max_usd));
+                self.run_local_job(job, files);
+            },
+            KeyCode::Esc => {
+                let Some(job) = self.budget_prompt.take() else {
+                    return;
+                };
+                if self.current_job_id.as_deref() == Some(job.job_id.as_str()) {
+                    self.job_status = Some("cancelled".to_string());
+                }
+                self.report_job_status(&job.job_id, "cancelled");
+                self.message = Some(format!(
+                    "Job {} stopped at its budget - partial results kept in {}", job.job_id, job.output.display()
+                ));
+            },
+            _ => {},
+        }
+    }
+
+    /// Configured quotas checked against this workspace's history
+    pub fn quota_guard(&self) -> QuotaGuard {
+        QuotaGuard::new(&self.config.quotas, self.history.entries(), chrono::Local::now())
+    }
+
+    pub fn handle_quota_dialog_input(&mut self, key: KeyEvent) {
+        let Some(dialog) = &self.quota_dialog else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.quota_dialog = None;
+                self.message = Some("Job not submitted: quota reached".to_string());
+            },
+            // Overriding must be deliberate, so only a capital 'O' does it
+            KeyCode::Char('O') => {
+                let dialog = dialog.clone();
+                self.quota_dialog = None;
+                self.debug_info.push(format!("Quota overridden for one job: {}", dialog.exceeded));
+                self.submit_job(dialog.file);
+            },
+            _ => {},
+        }
+    }
+
+    pub fn handle_settings_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('l') => {
+                self.language = if self.language == "en" { "pl".to_string() } else { "en".to_string() };
+                self.message = Some(format!("Language changed to {}", self.language));
+            },
+            KeyCode::Char('p') if !self.providers.is_empty() => {
+                let new_index = match self.selected_provider_index {
+                    Some(i) if i < self.providers.len() - 1 => Some(i + 1),
+                    Some(_) => Some(0),
+                    None => Some(0),
+                };
+                self.selected_provider_index = new_index;
+                self.selected_model_index = Some(0);
+                if self.get_current_provider() == Some(ollama::PROVIDER) && self.get_current_model().is_none() {
+                    self.start_ollama_discovery();
+                }
+            },
+            KeyCode::Char('m') => {
+                if let Some(provider_idx) = self.selected_provider_index {
+                    let provider = &self.providers[provider_idx];
+                    if let Some(models) = self.models.get(provider) {
                         if !models.is_empty() {
                             let new_index = match self.selected_model_index {
                                 Some(i) if i < models.len() - 1 => Some(i + 1),
@@ -180,25 +2198,362 @@ impl App {
                     }
                 }
             },
+            KeyCode::Char('w') => {
+                let names = workspace::list(&self.config);
+                let current = names.iter().position(|n| n == self.config.active_workspace()).unwrap_or(0);
+                let next = names[(current + 1) % names.len()].clone();
+                self.switch_workspace(&next);
+            },
+            KeyCode::Char('y') => self.start_preset_sync(),
+            KeyCode::Char('M') => {
+                self.start_model_refresh();
+                self.start_ollama_discovery();
+                self.start_provider_discovery();
+                self.message = Some("Refreshing the model lists...".to_string());
+            },
+            KeyCode::Char('u') => self.apply_model_replacements(),
+            KeyCode::Char('b') => self.open_backend_picker(),
+            KeyCode::Char('f') => self.probe_backends(),
+            KeyCode::Char('k') => {
+                // Ollama runs without a key
+                let providers: Vec<String> = self.providers.iter().filter(|p| *p != ollama::PROVIDER).cloned().collect();
+                self.key_manager = Some(KeyManager::load(&providers));
+            },
+            KeyCode::Char('t') => self.cycle_theme(),
+            KeyCode::Char('r') => self.export_usage_report(ReportFormat::Csv),
+            KeyCode::Char('R') => self.export_usage_report(ReportFormat::Json),
+            _ => {},
+        }
+    }
+
+    /// Usage screen: 'm' cycles the metric, 'd' switches 30/90 days,
+    /// digits show/hide providers in the order of the legend
+    pub fn handle_usage_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('m') => self.usage_metric = self.usage_metric.next(),
+            KeyCode::Char('d') => self.usage_days = if self.usage_days == 30 { 90 } else { 30 },
+            KeyCode::Char(c @ '1'..='9') => {
+                let index = c as usize - '1' as usize;
+                if let Some(provider) = usage::providers(self.history.entries()).get(index) {
+                    if !self.usage_hidden_providers.remove(provider) {
+                        self.usage_hidden_providers.insert(provider.clone());
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Switches to the next built-in palette and remembers it; color overrides of `[theme]` stay
+    fn cycle_theme(&mut self) {
+        let current = theme::PALETTES.iter().position(|p| *p == self.config.theme.palette);
+        let palette = theme::PALETTES[current.map_or(0, |i| (i + 1) % theme::PALETTES.len())];
+        let previous = std::mem::replace(&mut self.config.theme.palette, palette.to_string());
+        match Theme::for_config(&self.config) {
+            Ok(theme) => self.theme = theme,
+            Err(err) => {
+                self.config.theme.palette = previous;
+                self.message = Some(format!("Cannot switch to theme {}: {:#}", palette, err));
+                return;
+            },
+        }
+        self.message = Some(match self.config.save() {
+            Ok(()) => format!("Switched to theme {}", palette),
+            Err(err) => format!("Switched to theme {} (not saved: {})", palette, err),
+        });
+    }
+
+    /// Makes `name` the active workspace: reloads its history and remembers the choice
+    pub fn switch_workspace(&mut self, name: &str) {
+        let history = match JobHistory::load_for(name) {
+            Ok(history) => history,
+            Err(err) => {
+                self.message = Some(format!("Cannot switch to workspace {}: {:#}", name, err));
+                return;
+            },
+        };
+        let vault = match Vault::open(&self.config.encryption, name) {
+            Ok(vault) => vault,
+            Err(err) => {
+                self.message = Some(format!("Cannot switch to workspace {}: {:#}", name, err));
+                return;
+            },
+        };
+        self.history = history;
+        self.vault = vault;
+        self.last_processing_config = None;
+        self.usage_hidden_providers.clear();
+        self.config.select_workspace(name);
+        self.message = Some(match self.config.save() {
+            Ok(()) => format!("Switched to workspace {}", name),
+            Err(err) => format!("Switched to workspace {} (not saved: {})", name, err),
+        });
+    }
+
+    /// Looks for backends on the local network in the background
+    pub fn discover_backends(&mut self) {
+        if let Some(picker) = &mut self.backend_picker {
+            picker.scanning = true;
+            picker.error = None;
+        }
+        let tx = self.background_tx.clone();
+        background::spawn("Backend discovery", move |_| {
+            let event = match discovery::scan(discovery::SCAN_TIMEOUT) {
+                Ok(backends) => BackgroundEvent::BackendsDiscovered(backends),
+                Err(err) => BackgroundEvent::DiscoveryFailed(format!("{:#}", err)),
+            };
+            let _ = tx.send(event);
+        });
+    }
+
+    /// Settings action: lists the backends on the network, scanning again
+    pub fn open_backend_picker(&mut self) {
+        self.backend_picker = Some(BackendPicker {
+            backends: self.discovered_backends.clone(),
+            ..BackendPicker::default()
+        });
+        self.discover_backends();
+    }
+
+    // Pierwsze uruchomienie: znalezione backendy są proponowane raz, gdy nic ich nie zasłania
+    fn offer_backends(&mut self) {
+        if !self.offer_discovered_backends || self.discovered_backends.is_empty() {
+            return;
+        }
+        self.offer_discovered_backends = false;
+        if self.discovered_backends.iter().any(|b| b.url == self.backend_url) {
+            return;
+        }
+        self.backend_picker = Some(BackendPicker {
+            backends: self.discovered_backends.clone(),
+            ..BackendPicker::default()
+        });
+    }
+
+    pub fn handle_backend_picker_input(&mut self, key: KeyEvent) {
+        let Some(picker) = &mut self.backend_picker else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.backend_picker = None,
+            KeyCode::Down => picker.next(),
+            KeyCode::Up => picker.previous(),
+            KeyCode::Char('r') => self.discover_backends(),
+            KeyCode::Enter => {
+                if let Some(url) = picker.current().map(|b| b.url.clone()) {
+                    self.backend_picker = None;
+                    self.switch_backend(&url);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Key popup: ↑/↓ select a provider, Enter types a new key (saved to the OS keyring and
+    /// checked), 'v' checks the current key, 'd' removes the stored one
+    pub fn handle_key_manager_input(&mut self, key: KeyEvent) {
+        let Some(manager) = &mut self.key_manager else {
+            return;
+        };
+        if let Some(typed) = &mut manager.editing {
+            match key.code {
+                KeyCode::Char(c) => typed.push(c),
+                KeyCode::Backspace => {
+                    typed.pop();
+                },
+                KeyCode::Esc => manager.editing = None,
+                KeyCode::Enter => {
+                    let typed = manager.editing.take().unwrap_or_default();
+                    let Some(provider) = manager.current().map(|e| e.provider.clone()) else {
+                        return;
+                    };
+                    if typed.trim().is_empty() {
+                        return;
+                    }
+                    match credentials::set(&provider, &typed) {
+                        Ok(()) => {
+                            manager.refresh(manager.selected);
+                            self.check_key(&provider);
+                        },
+                        Err(err) => self.message = Some(format!("{:#}", err)),
+                    }
+                },
+                _ => {},
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Esc => self.key_manager = None,
+            KeyCode::Down => manager.next(),
+            KeyCode::Up => manager.previous(),
+            KeyCode::Enter => manager.editing = Some(String::new()),
+            KeyCode::Char('v') => {
+                if let Some(provider) = manager.current().map(|e| e.provider.clone()) {
+                    self.check_key(&provider);
+                }
+            },
+            KeyCode::Char('d') => {
+                let Some(provider) = manager.current().map(|e| e.provider.clone()) else {
+                    return;
+                };
+                match credentials::delete(&provider) {
+                    Ok(()) => {
+                        manager.refresh(manager.selected);
+                        self.message = Some(format!("Removed the stored {} key", provider));
+                    },
+                    Err(err) => self.message = Some(format!("{:#}", err)),
+                }
+            },
             _ => {},
         }
     }
 
+    /// Asks the provider whether it accepts the key found for it
+    fn check_key(&mut self, provider: &str) {
+        let Some((key, _)) = credentials::get(provider) else {
+            self.message = Some(format!("No {} key to check", provider));
+            return;
+        };
+        if let Some(entry) = self.key_manager.as_mut().and_then(|m| m.entries.iter_mut().find(|e| e.provider == provider)) {
+            entry.checking = true;
+        }
+        let provider = provider.to_string();
+        let tx = self.background_tx.clone();
+        background::spawn("Key check", move |runtime| {
+            let result = runtime.block_on(credentials::validate(&provider, &key)).map_err(|err| format!("{:#}", err));
+            let _ = tx.send(BackgroundEvent::KeyChecked { provider, result });
+        });
+    }
+
+    /// Points the app at another backend and saves it as the default
+    pub fn switch_backend(&mut self, url: &str) {
+        self.config.backend_url = url.to_string();
+        self.connect_backend(url);
+        self.message = Some(match self.config.save() {
+            Ok(()) => format!("Switched to backend {}", url),
+            Err(err) => format!("Switched to backend {} (not saved: {})", url, err),
+        });
+    }
+
+    /// Points the app at another backend for this session only.
+    /// File IDs and fetched lists belong to the old backend and are dropped.
+    fn connect_backend(&mut self, url: &str) {
+        self.backend_url = url.to_string();
+        self.worker = ApiWorker::spawn(
+            url, self.background_tx.clone(), self.network.clone(), self.config.download.clone(), self.config.request_headers(),
+        );
+        self.worker.send(WorkerCommand::CheckVersion);
+        self.backend_version = None;
+        self.backend_error = None;
+        self.uploaded_file_ids.clear();
+        self.jobs.clear();
+        self.jobs_loaded = false;
+        self.datasets = DatasetBrowser::default();
+    }
+
+    /// Pings the configured backend and its mirrors; the fastest healthy one is used for the session
+    pub fn probe_backends(&mut self) {
+        let urls = latency::candidates(&self.config.backend_url, &self.config.backend_mirrors);
+        if urls.len() < 2 {
+            self.message = Some("No backend mirrors configured (backend_mirrors in config.toml)".to_string());
+            return;
+        }
+        self.probing_backends = true;
+        let headers = self.config.request_headers();
+        self.message = Some(format!("Pinging {} backends...", urls.len()));
+        let tx = self.background_tx.clone();
+        background::spawn("Backend latency probe", move |runtime| {
+            let probes = runtime.block_on(latency::probe_all(&urls, &headers));
+            for probe in &probes {
+                log_info!("Backend {}: {}", probe.url, probe.describe());
+            }
+            match latency::fastest(&probes) {
+                Some(probe) => log_info!("Selected backend {} for this session", probe.url),
+                None => log_warn!("No backend answered the latency probe"),
+            }
+            let _ = tx.send(BackgroundEvent::BackendsProbed(probes));
+        });
+    }
+
+    /// Settings action: writes this month's usage report to the downloads directory
+    pub fn export_usage_report(&mut self, format: ReportFormat) {
+        let month = Month::current();
+        let dir = self.config.downloads_dir();
+        let path = dir.join(format!("usage-report-{}.{}", month, format.extension()));
+        let report = UsageReport::build(self.history.entries(), Some(month));
+        let result = report.render(format)
+            .and_then(|content| diskspace::ensure_fits(&path, Some(content.len() as u64), self.config.min_free_space_mb, false))
+            .and_then(|_| report.write_to(&path, format));
+        self.message = Some(match result {
+            Ok(()) => format!("Usage report ({} jobs) saved to {}", report.totals.jobs, path.display()),
+            Err(err) => format!("Failed to export usage report: {:#}", err),
+        });
+    }
+
+    /// Opens the Datasets screen, listing the backend's datasets on the first visit
+    pub fn open_datasets(&mut self) {
+        self.state = AppState::Datasets;
+        if !self.datasets.loaded && !self.datasets.loading {
+            self.refresh_datasets();
+        }
+    }
+
+    pub fn refresh_datasets(&mut self) {
+        self.datasets.loading = self.worker.send(WorkerCommand::ListDatasets);
+    }
+
+    /// Arrows select, Enter previews, 'd' downloads to the downloads directory, 'r' refreshes
+    pub fn handle_datasets_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Down => self.datasets.next(),
+            KeyCode::Up => self.datasets.previous(),
+            KeyCode::Char('r') => self.refresh_datasets(),
+            KeyCode::Enter => {
+                if let Some(id) = self.datasets.current().map(|d| d.id.clone()) {
+                    self.worker.send(WorkerCommand::GetDataset { id });
+                }
+            },
+            KeyCode::Char('d') => {
+                let Some(info) = self.datasets.current() else {
+                    return;
+                };
+                let path = self.config.downloads_dir().join(datasets::file_name(info));
+                self.message = Some(format!("Downloading dataset {} to {}", info.label(), path.display()));
+                self.worker.send(WorkerCommand::DownloadDataset {
+                    id: info.id.clone(),
+                    path,
+                    min_free_space_mb: self.config.min_free_space_mb,
+                    vault: self.vault.clone(),
+                });
+            },
+            _ => {},
+        }
+    }
+
+    /// Keys type the job ID; Ctrl+D downloads, Ctrl+R resumes a failed job from its partial
+    /// results and 'c' cancels the job whose ID is shown
     pub fn handle_job_status_input(&mut self, key: KeyEvent) {
         match key.code {
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.open_download_dialog();
+            },
+            KeyCode::Char('c') if self.current_job_id.as_deref() == Some(self.job_id_input.value()) => {
+                self.open_cancel_dialog();
+            },
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.resume_partial();
+            },
             KeyCode::Char(c) => {
-                self.job_id_input.insert(c);
+                self.job_id_input.handle(InputRequest::InsertChar(c));
             },
             KeyCode::Backspace => {
-                self.job_id_input.delete();
+                self.job_id_input.handle(InputRequest::DeletePrevChar);
             },
             KeyCode::Enter => {
                 let job_id = self.job_id_input.value().to_string();
                 if !job_id.is_empty() {
-                    self.current_job_id = Some(job_id);
-                    self.job_progress = Some((50, 100)); // Simulate progress
-                    self.job_status = Some("processing".to_string());
-                    self.message = Some("Job status retrieved".to_string());
+                    self.show_job(job_id);
                 } else {
                     self.message = Some("Please enter a job ID".to_string());
                 }
@@ -207,6 +2562,1220 @@ impl App {
         }
     }
 
+    /// Makes `job_id` the current job and fetches its status right away
+    pub fn show_job(&mut self, job_id: String) {
+        self.unpolled_jobs.remove(&job_id);
+        self.current_job_id = Some(job_id.clone());
+        self.job_progress = None;
+        self.job_status = None;
+        self.job_owner = None;
+        self.message = Some(format!("Fetching status of {}...", job_id));
+        self.poll_job_status();
+    }
+
+    /// Opens the Jobs screen, listing the backend's jobs on the first visit
+    pub fn open_jobs(&mut self) {
+        self.state = AppState::Jobs;
+        if !self.jobs_loaded && !self.jobs_loading {
+            self.refresh_jobs();
+        }
+    }
+
+    pub fn refresh_jobs(&mut self) {
+        self.jobs_loading = self.worker.send(WorkerCommand::ListJobs {
+            page: self.jobs_page,
+            status: self.jobs_status_filter.clone(),
+        });
+    }
+
+    /// Fetches another page of the job list; out-of-range pages are ignored
+    fn show_jobs_page(&mut self, page: u32) {
+        if page == 0 || page > self.jobs_total_pages || page == self.jobs_page {
+            return;
+        }
+        self.jobs_page = page;
+        self.jobs_selected = 0;
+        self.refresh_jobs();
+    }
+
+    fn clamp_jobs_selection(&mut self) {
+        self.jobs_selected = self.jobs_selected.min(self.visible_jobs().len().saturating_sub(1));
+    }
+
+    /// Arrows select, PageUp/PageDown change the page, 'f' the status filter, 's' sorts the page,
+    /// 'm' toggles "mine only", 'r' refreshes and Enter opens the job on the Job Status screen
+    pub fn handle_jobs_input(&mut self, key: KeyEvent) {
+        let count = self.visible_jobs().len();
+        match key.code {
+            KeyCode::Down if count > 0 => self.jobs_selected = (self.jobs_selected + 1) % count,
+            KeyCode::Up if count > 0 => self.jobs_selected = (self.jobs_selected + count - 1) % count,
+            KeyCode::PageDown => self.show_jobs_page(self.jobs_page + 1),
+            KeyCode::PageUp => self.show_jobs_page(self.jobs_page.saturating_sub(1)),
+            KeyCode::Char('f') => {
+                self.jobs_status_filter = jobs::next_status_filter(self.jobs_status_filter.as_deref());
+                self.jobs_page = 1;
+                self.jobs_selected = 0;
+                self.refresh_jobs();
+            },
+            KeyCode::Char('s') => {
+                self.jobs_sort = self.jobs_sort.next();
+                self.jobs_selected = 0;
+            },
+            KeyCode::Char('m') => {
+                self.toggle_jobs_mine_only();
+                self.clamp_jobs_selection();
+            },
+            KeyCode::Char('r') => self.refresh_jobs(),
+            KeyCode::Enter => {
+                if let Some(job_id) = self.visible_jobs().get(self.jobs_selected).map(|job| job.job_id.clone()) {
+                    self.job_id_input = Input::new(job_id.clone());
+                    self.state = AppState::JobStatus;
+                    self.show_job(job_id);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    pub fn open_cancel_dialog(&mut self) {
+        let Some(job_id) = self.current_job_id.clone() else {
+            self.message = Some("No job selected - enter a job ID first".to_string());
+            return;
+        };
+        if let Some(status @ ("completed" | "failed" | "cancelled")) = self.job_status.as_deref() {
+            self.message = Some(format!("Job {} is already {}", job_id, status));
+            return;
+        }
+        self.cancel_dialog = Some(CancelDialog { job_id });
+    }
+
+    pub fn handle_cancel_dialog_input(&mut self, key: KeyEvent) {
+        let Some(dialog) = &self.cancel_dialog else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                let job_id = dialog.job_id.clone();
+                self.cancel_dialog = None;
+                self.message = Some(format!("Cancelling {}...", job_id));
+                self.worker.send(WorkerCommand::CancelJob { job_id });
+            },
+            KeyCode::Char('n') | KeyCode::Esc => self.cancel_dialog = None,
+            _ => {},
+        }
+    }
+
+    /// Opens the Review screen, offering the last local run's dataset
+    pub fn open_review(&mut self) {
+        self.state = AppState::Review;
+        if self.review.is_none() && self.review_input.value().is_empty() {
+            if let Some(path) = &self.last_local_output {
+                self.review_input = Input::new(path.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    /// Without a dataset loaded keys edit its path; then '1'/'2' choose, arrows move,
+    /// 'e' exports the preference pairs and 'o' opens another dataset
+    pub fn handle_review_input(&mut self, key: KeyEvent) {
+        let Some(review) = self.review.as_mut() else {
+            match key.code {
+                KeyCode::Char(c) => {
+                    self.review_input.handle(InputRequest::InsertChar(c));
+                },
+                KeyCode::Backspace => {
+                    self.review_input.handle(InputRequest::DeletePrevChar);
+                },
+                KeyCode::Enter => {
+                    let path = std::path::PathBuf::from(self.review_input.value());
+                    match Review::load(&path, self.vault.clone()) {
+                        Ok(review) if review.items.is_empty() => {
+                            self.message = Some(format!("No records with candidates to compare in {}", path.display()));
+                        },
+                        Ok(review) => {
+                            self.message = Some(format!("{} comparison(s), {} judged", review.items.len(), review.judged()));
+                            self.review = Some(review);
+                        },
+                        Err(err) => self.message = Some(format!("{:#}", err)),
+                    }
+                },
+                _ => {},
+            }
+            return;
+        };
+
+        let choice = match key.code {
+            KeyCode::Char('1') | KeyCode::Char('a') => Some(Choice::A),
+            KeyCode::Char('2') | KeyCode::Char('b') => Some(Choice::B),
+            KeyCode::Left => {
+                review.previous();
+                None
+            },
+            KeyCode::Right => {
+                review.next();
+                None
+            },
+            KeyCode::Char('e') => {
+                let output = review.dataset.with_extension("dpo.jsonl");
+                let mut pairs = preference::pairs(&review.results);
+                pairs.extend(bestof::pairs(&review.results));
+                self.message = Some(match preference::write_jsonl(&output, &pairs) {
+                    Ok(()) => format!("{} preference pair(s) exported to {}", pairs.len(), output.display()),
+                    Err(err) => format!("Failed to export preference pairs: {:#}", err),
+                });
+                None
+            },
+            KeyCode::Char('o') => {
+                self.review = None;
+                return;
+            },
+            _ => None,
+        };
+        if let Some(choice) = choice {
+            if let Err(err) = review.judge(choice) {
+                self.message = Some(format!("Failed to save judgment: {:#}", err));
+            }
+        }
+    }
+
+    /// Opens the Results screen, loading the last download unless it's already shown
+    pub fn open_results(&mut self) {
+        self.state = AppState::Results;
+        let Some(path) = self.last_download.clone() else {
+            return;
+        };
+        if self.results_viewer.as_ref().is_some_and(|viewer| viewer.path == path) {
+            return;
+        }
+        self.results_input = Input::new(path.to_string_lossy().to_string());
+        self.load_results(&path);
+    }
+
+    fn load_results(&mut self, path: &std::path::Path) {
+        match ResultsViewer::load(path, &self.vault) {
+            Ok(viewer) => {
+                self.message = Some(format!("{} record(s) in {}", viewer.records.len(), path.display()));
+                self.results_viewer = Some(viewer);
+            },
+            Err(err) => self.message = Some(format!("{:#}", err)),
+        }
+    }
+
+    /// Without a file loaded keys edit its path; then ←/→ page through records, ↑/↓ and
+    /// PgUp/PgDn scroll, Tab moves between fields, Space folds one and 'a' all of them
+    pub fn handle_results_input(&mut self, key: KeyEvent) {
+        let Some(viewer) = self.results_viewer.as_mut() else {
+            match key.code {
+                KeyCode::Char(c) => {
+                    self.results_input.handle(InputRequest::InsertChar(c));
+                },
+                KeyCode::Backspace => {
+                    self.results_input.handle(InputRequest::DeletePrevChar);
+                },
+                KeyCode::Enter => {
+                    let path = std::path::PathBuf::from(self.results_input.value());
+                    self.load_results(&path);
+                },
+                _ => {},
+            }
+            return;
+        };
+
+        match key.code {
+            KeyCode::Right => viewer.next_record(),
+            KeyCode::Left => viewer.previous_record(),
+            KeyCode::Home => viewer.show_record(0),
+            KeyCode::End => viewer.show_record(viewer.records.len()),
+            KeyCode::Down => viewer.scroll_down(1),
+            KeyCode::Up => viewer.scroll_up(1),
+            KeyCode::PageDown => viewer.scroll_down(10),
+            KeyCode::PageUp => viewer.scroll_up(10),
+            KeyCode::Tab => viewer.next_field(),
+            KeyCode::Char(' ') | KeyCode::Enter => viewer.toggle_fold(),
+            KeyCode::Char('a') => viewer.toggle_all(),
+            KeyCode::Char('g') => viewer.toggle_lineage(),
+            KeyCode::Char('o') => self.results_viewer = None,
+            _ => {},
+        }
+    }
+
+    /// Network screen: ↑/↓ select a request, 'c' cancels it
+    pub fn handle_network_input(&mut self, key: KeyEvent) {
+        let requests = self.network.snapshot();
+        self.network_selected = self.network_selected.min(requests.len().saturating_sub(1));
+        match key.code {
+            KeyCode::Down if self.network_selected + 1 < requests.len() => self.network_selected += 1,
+            KeyCode::Up => self.network_selected = self.network_selected.saturating_sub(1),
+            KeyCode::Char('c') => {
+                let Some(request) = requests.get(self.network_selected) else {
+                    return;
+                };
+                self.message = Some(if self.network.cancel(request.id) {
+                    format!("Cancelled {} {}", request.method, request.endpoint)
+                } else {
+                    format!("{} {} already finished", request.method, request.endpoint)
+                });
+            },
+            _ => {},
+        }
+    }
+
+    /// Stats screen on the first of `datasets`; the others are a ←/→ away
+    pub fn open_stats(&mut self, datasets: Vec<std::path::PathBuf>) {
+        self.state = AppState::Stats;
+        if datasets.is_empty() {
+            self.message = Some("No downloaded dataset to show stats of".to_string());
+            return;
+        }
+        self.stats_view = Some(StatsView::open(datasets, &self.vault));
+    }
+
+    /// ←/→ switch between the datasets, ↑/↓ and PgUp/PgDn scroll, 'v' opens the dataset
+    /// on the Results screen
+    pub fn handle_stats_input(&mut self, key: KeyEvent) {
+        let Some(view) = self.stats_view.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Left => view.select(view.selected.saturating_sub(1), &self.vault),
+            KeyCode::Right => view.select(view.selected + 1, &self.vault),
+            KeyCode::Up => view.scroll = view.scroll.saturating_sub(1),
+            KeyCode::Down => view.scroll = view.scroll.saturating_add(1),
+            KeyCode::PageUp => view.scroll = view.scroll.saturating_sub(10),
+            KeyCode::PageDown => view.scroll = view.scroll.saturating_add(10),
+            KeyCode::Char('v') => {
+                if let Some(path) = view.path().map(std::path::Path::to_path_buf) {
+                    self.last_download = Some(path);
+                    self.open_results();
+                }
+            },
+            _ => {},
+        }
+    }
+
+    pub fn open_logs(&mut self) {
+        self.state = AppState::Logs;
+        self.log_view.poll();
+    }
+
+    /// ↑/↓ and PgUp/PgDn scroll, Home goes to the oldest entry, End/'f' follows new ones;
+    /// Space pauses, 'l' raises the minimum level and 'c' clears the view
+    pub fn handle_logs_input(&mut self, key: KeyEvent) {
+        let view = &mut self.log_view;
+        match key.code {
+            KeyCode::Up => view.scroll_up(1),
+            KeyCode::Down => view.scroll_down(1),
+            KeyCode::PageUp => view.scroll_up(LOG_PAGE),
+            KeyCode::PageDown => view.scroll_down(LOG_PAGE),
+            KeyCode::Home => view.scroll_to_top(),
+            KeyCode::End | KeyCode::Char('f') => view.follow_tail(),
+            KeyCode::Char(' ') | KeyCode::Char('p') => view.toggle_pause(),
+            KeyCode::Char('l') => view.cycle_level(),
+            KeyCode::Char('c') => view.clear(),
+            _ => {},
+        }
+    }
+
+    /// Debug screen: ↑/↓ select a captured exchange, PgUp/PgDn scroll it, 't' toggles capture,
+    /// 'w' writes the selected job's transcript to a file, 'c' clears the captures
+    pub fn handle_debug_input(&mut self, key: KeyEvent) {
+        let entries = self.transcripts.entries().len();
+        self.debug_selected = self.debug_selected.min(entries.saturating_sub(1));
+        match key.code {
+            KeyCode::Down if self.debug_selected + 1 < entries => {
+                self.debug_selected += 1;
+                self.debug_scroll = 0;
+            },
+            KeyCode::Up => {
+                self.debug_selected = self.debug_selected.saturating_sub(1);
+                self.debug_scroll = 0;
+            },
+            KeyCode::PageDown => self.debug_scroll = self.debug_scroll.saturating_add(10),
+            KeyCode::PageUp => self.debug_scroll = self.debug_scroll.saturating_sub(10),
+            KeyCode::Char('t') => {
+                self.transcripts.enabled = !self.transcripts.enabled;
+                self.message = Some(if self.transcripts.enabled {
+                    format!("Capturing provider transcripts (last {} per job)", self.config.transcript.per_job.max(1))
+                } else {
+                    "Transcript capture off".to_string()
+                });
+            },
+            KeyCode::Char('w') => {
+                let Some(job_id) = self.transcripts.entries().get(self.debug_selected).map(|entry| entry.job_id.clone()) else {
+                    self.message = Some("No transcript captured yet".to_string());
+                    return;
+                };
+                let dumped = workspace::data_dir(self.config.active_workspace())
+                    .and_then(|dir| self.transcripts.dump(&job_id, &dir));
+                self.message = Some(match dumped {
+                    Ok(path) => format!("Transcript of {} written to {}", job_id, path.display()),
+                    Err(err) => format!("Failed to write the transcript: {:#}", err),
+                });
+            },
+            KeyCode::Char('r') => self.toggle_recording(),
+            KeyCode::Char('c') => {
+                self.transcripts.clear();
+                self.debug_selected = 0;
+                self.debug_scroll = 0;
+                self.message = Some("Transcripts cleared".to_string());
+            },
+            _ => {},
+        }
+    }
+
+    /// Starts recording the session to `recordings/` in the config directory, or saves the
+    /// recording in progress
+    pub fn toggle_recording(&mut self) {
+        if let Some(saved) = self.recorder.stop() {
+            self.message = Some(match saved {
+                Ok(path) => format!("Session recorded to {} (replay: asciinema play)", path.display()),
+                Err(err) => format!("Failed to save the session recording: {:#}", err),
+            });
+            return;
+        }
+        let started = recording::default_path().and_then(|path| {
+            self.recorder.start(&path)?;
+            Ok(path)
+        });
+        self.message = Some(match started {
+            Ok(path) => {
+                self.redraw_all = true;
+                format!("Recording the session to {} ('r' on Debug stops)", path.display())
+            },
+            Err(err) => format!("Cannot record the session: {:#}", err),
+        });
+    }
+
+    fn cleanup_due(&self) -> bool {
+        self.config.retention.enabled()
+            && !self.storage.cleaning
+            && self.cleanup_started.is_none_or(|started| started.elapsed() >= CLEANUP_INTERVAL)
+    }
+
+    /// Applies the retention policy in the background
+    pub fn start_cleanup(&mut self) {
+        let settings = self.config.retention.clone();
+        if !settings.enabled() {
+            self.message = Some("No retention policy configured ([retention] in config)".to_string());
+            return;
+        }
+        self.storage.cleaning = true;
+        self.cleanup_started = Some(std::time::Instant::now());
+        let downloads_dir = self.config.downloads_dir();
+        let vault = self.vault.clone();
+        let tx = self.background_tx.clone();
+        background::spawn("Cleanup", move |_| {
+            let event = match storage::cleanup(&downloads_dir, storage::cache_dir().as_deref(), &settings, &vault) {
+                Ok(report) => BackgroundEvent::CleanupFinished(report),
+                Err(err) => BackgroundEvent::CleanupFailed(format!("{:#}", err)),
+            };
+            let _ = tx.send(event);
+        });
+    }
+
+    pub fn open_storage(&mut self) {
+        self.state = AppState::Storage;
+        self.refresh_storage();
+    }
+
+    /// Measures the downloads directory and the cache in the background
+    pub fn refresh_storage(&mut self) {
+        if self.storage.loading {
+            return;
+        }
+        self.storage.loading = true;
+        let downloads_dir = self.config.downloads_dir();
+        let vault = self.vault.clone();
+        let tx = self.background_tx.clone();
+        background::spawn("Storage scan", move |_| {
+            let event = match storage::scan(&downloads_dir, storage::cache_dir().as_deref(), &vault) {
+                Ok(groups) => BackgroundEvent::StorageScanned(groups),
+                Err(err) => BackgroundEvent::StorageScanFailed(format!("{:#}", err)),
+            };
+            let _ = tx.send(event);
+        });
+    }
+
+    /// Arrows select, 'p' purges the selected job's files, 'c' applies the retention policy now,
+    /// 'r' measures again
+    pub fn handle_storage_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Down => self.storage.next(),
+            KeyCode::Up => self.storage.previous(),
+            KeyCode::Char('r') => self.refresh_storage(),
+            KeyCode::Char('c') => self.start_cleanup(),
+            KeyCode::Char('p') => {
+                let Some(group) = self.storage.current().cloned() else {
+                    return;
+                };
+                self.message = Some(match storage::purge(&group) {
+                    Ok(freed) => format!("Purged {}, freed {}", group.label(), diskspace::format_bytes(freed)),
+                    Err(err) => format!("Failed to purge {}: {:#}", group.label(), err),
+                });
+                if self.last_download.as_ref().is_some_and(|path| group.files.contains(path)) {
+                    self.last_download = None;
+                    self.results_viewer = None;
+                }
+                self.refresh_storage();
+            },
+            _ => {},
+        }
+    }
+
+    /// Opens the column mapping of the last local run's dataset. The dataset's processing
+    /// type names the preset the mapping is saved to.
+    pub fn open_column_editor(&mut self) {
+        let Some(dataset) = self.last_local_output.clone() else {
+            self.message = Some("No local run yet - nothing to export".to_string());
+            return;
+        };
+        let results: Vec<processors::ProcessingResult> = match self.vault.read(&dataset)
+            .and_then(|content| serde_json::from_slice(&content).context("Not a dataset produced by a local run"))
+        {
+            Ok(results) => results,
+            Err(err) => {
+                self.message = Some(format!("{:#}", err));
+                return;
+            },
+        };
+        let preset = results.first()
+            .map(|r| r.processing_type.clone())
+            .unwrap_or_else(|| self.processing_type.to_str().to_string());
+        let saved = self.effective_presets().remove(&preset)
+            .map(|(preset, _)| preset.columns)
+            .unwrap_or_default();
+        self.column_editor = Some(ColumnEditor::open(&preset, &dataset, results, &saved));
+    }
+
+    /// Space includes a field, 'u'/'d' move it, 't' changes its type, 'f' the format;
+    /// 's' saves the mapping to the preset and Enter exports
+    pub fn handle_column_editor_input(&mut self, key: KeyEvent) {
+        let Some(editor) = &mut self.column_editor else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.column_editor = None,
+            KeyCode::Down => editor.next(),
+            KeyCode::Up => editor.previous(),
+            KeyCode::Char(' ') => editor.toggle(),
+            KeyCode::Char('u') => editor.move_selected(-1),
+            KeyCode::Char('d') => editor.move_selected(1),
+            KeyCode::Char('t') => editor.cycle_type(),
+            KeyCode::Char('f') => editor.format = editor.format.next(),
+            KeyCode::Char('s') => {
+                let preset = editor.preset.clone();
+                self.config.active_presets_mut().entry(preset.clone()).or_default().columns = editor.columns();
+                self.message = Some(match self.config.save() {
+                    Ok(()) => format!("Column mapping saved to preset {}", preset),
+                    Err(err) => format!("Failed to save column mapping: {:#}", err),
+                });
+            },
+            KeyCode::Enter => {
+                let output = editor.output();
+                let exported = tabular::export(&output, &editor.results, &editor.columns(), editor.format)
+                    .and_then(|rows| {
+                        if self.config.signing.enabled {
+                            signing::sign_all([output.as_path()])?;
+                        }
+                        Ok(rows)
+                    });
+                match exported {
+                    Ok(rows) => {
+                        self.message = Some(format!("{} row(s) exported to {}", rows, output.display()));
+                        self.column_editor = None;
+                    },
+                    Err(err) => self.message = Some(format!("Export failed: {:#}", err)),
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Opens the corpus wizard on the downloaded datasets and the last local run
+    pub fn open_assembly(&mut self) {
+        let downloads_dir = self.config.downloads_dir();
+        let sources = assembly::find_sources(&downloads_dir, self.last_local_output.as_deref(), &self.vault);
+        if sources.is_empty() {
+            self.message = Some("No finished job datasets found - download results or run a job first".to_string());
+            return;
+        }
+        let output = downloads_dir.join(format!("corpus-{}", chrono::Local::now().format("%Y%m%d-%H%M")));
+        self.assembly = Some(AssemblyWizard::new(sources, &output));
+    }
+
+    /// Tab/Shift+Tab change the step, ↑/↓ the row; Space toggles, ←/→ adjust numbers and
+    /// weights, typing edits text fields; Enter moves on and, on the last step, exports
+    pub fn handle_assembly_input(&mut self, key: KeyEvent) {
+        let Some(wizard) = &mut self.assembly else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.assembly = None,
+            KeyCode::Tab => wizard.go(1),
+            KeyCode::BackTab => wizard.go(-1),
+            KeyCode::Down => wizard.next_row(),
+            KeyCode::Up => wizard.previous_row(),
+            KeyCode::Left => wizard.adjust(-1),
+            KeyCode::Right => wizard.adjust(1),
+            KeyCode::Enter if wizard.step == Step::Export => {
+                let exported = wizard.export().map(|files| (files, wizard.preview.records()));
+                self.finish_assembly(exported);
+            },
+            KeyCode::Enter => wizard.go(1),
+            KeyCode::Char(' ') if wizard.text_input().is_none() => wizard.toggle(),
+            KeyCode::Char(c) => {
+                if let Some(input) = wizard.text_input() {
+                    input.handle(InputRequest::InsertChar(c));
+                }
+            },
+            KeyCode::Backspace => {
+                if let Some(input) = wizard.text_input() {
+                    input.handle(InputRequest::DeletePrevChar);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn finish_assembly(&mut self, exported: anyhow::Result<(Vec<std::path::PathBuf>, usize)>) {
+        let (files, records) = match exported {
+            Ok(exported) => exported,
+            Err(err) => {
+                if let Some(wizard) = &mut self.assembly {
+                    wizard.error = Some(format!("{:#}", err));
+                }
+                return;
+            },
+        };
+        let dir = files[0].parent().map(|dir| dir.display().to_string()).unwrap_or_default();
+        let signed = if self.config.signing.enabled {
+            signing::sign_all(files.iter().map(|file| file.as_path())).map(|_| ())
+        } else {
+            Ok(())
+        };
+        self.message = Some(match signed {
+            Ok(()) => format!("{} record(s) exported to {}", records, dir),
+            Err(err) => format!("Corpus exported to {}, but signing failed: {:#}", dir, err),
+        });
+        self.assembly = None;
+    }
+
+    /// Shows how `file` splits under the `[chunking]` settings, or the defaults when chunking is off
+    pub fn open_chunk_tuner(&mut self, file: &str) {
+        let config = self.processor_config(file);
+        match preview::document_text(std::path::Path::new(file), &config) {
+            Ok(text) => {
+                let settings = self.config.chunking.clone().unwrap_or_default();
+                self.chunk_tuner = Some(ChunkTuner::new(file, text, settings));
+            },
+            Err(err) => self.message = Some(format!("{:#}", err)),
+        }
+    }
+
+    /// ↑/↓ select a chunk, Tab changes the strategy, '+'/'-' the chunk size and ']'/'[' the
+    /// context; 's' saves the settings as `[chunking]`
+    pub fn handle_chunk_tuner_input(&mut self, key: KeyEvent) {
+        let Some(tuner) = &mut self.chunk_tuner else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.chunk_tuner = None,
+            KeyCode::Down => tuner.next(),
+            KeyCode::Up => tuner.previous(),
+            KeyCode::Tab => tuner.cycle_strategy(),
+            KeyCode::Char('+') | KeyCode::Char('=') => tuner.adjust_size(1),
+            KeyCode::Char('-') => tuner.adjust_size(-1),
+            KeyCode::Char(']') => tuner.adjust_overlap(1),
+            KeyCode::Char('[') => tuner.adjust_overlap(-1),
+            KeyCode::Char('s') => {
+                self.config.chunking = Some(tuner.settings.clone());
+                self.message = Some(match self.config.save() {
+                    Ok(()) => format!("Chunking saved: {}", tuner.summary()),
+                    Err(err) => format!("Failed to save chunking settings: {:#}", err),
+                });
+            },
+            _ => {},
+        }
+    }
+
+    /// ↑/↓/Tab move between the parameters, Enter applies them if they are all in range
+    pub fn handle_sampling_editor_input(&mut self, key: KeyEvent) {
+        let Some(editor) = &mut self.sampling_editor else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.sampling_editor = None,
+            KeyCode::Down | KeyCode::Tab => editor.next(),
+            KeyCode::Up | KeyCode::BackTab => editor.previous(),
+            KeyCode::Char(c) => editor.insert(c),
+            KeyCode::Backspace => editor.delete(),
+            KeyCode::Enter => match editor.parse() {
+                Ok(params) => {
+                    self.sampling = params;
+                    self.sampling_editor = None;
+                    self.message = Some(format!("Sampling: {}", params.describe()));
+                },
+                Err(err) => editor.error = Some(format!("{:#}", err)),
+            },
+            _ => {},
+        }
+    }
+
+    /// Enter or a comma adds the typed keyword, Backspace/Delete remove chips picked with ←/→,
+    /// Esc leaves the field keeping what was typed
+    pub fn handle_keyword_editor_input(&mut self, key: KeyEvent) {
+        let Some(editor) = &mut self.keyword_editor else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                editor.commit(&mut self.keywords);
+                self.keyword_editor = None;
+            },
+            KeyCode::Enter => editor.commit(&mut self.keywords),
+            KeyCode::Char(c) => editor.insert(&mut self.keywords, c),
+            KeyCode::Backspace => editor.backspace(&mut self.keywords),
+            KeyCode::Delete => editor.delete(&mut self.keywords),
+            KeyCode::Left => editor.select_previous(&self.keywords),
+            KeyCode::Right => editor.select_next(&self.keywords),
+            _ => {},
+        }
+    }
+
+    /// Pasted text goes to the keyword field when it's focused; elsewhere it is ignored rather
+    /// than replayed as shortcuts
+    pub fn handle_paste(&mut self, text: &str) {
+        if let Some(editor) = &mut self.keyword_editor {
+            editor.paste(&mut self.keywords, text);
+        }
+    }
+
+    pub fn open_preset_picker(&mut self) {
+        let names = self.effective_presets().into_keys().collect();
+        self.preset_picker = Some(PresetPicker { names, ..Default::default() });
+    }
+
+    /// Digits 1-9 or Enter apply a preset, 's' saves the current configuration under a typed
+    /// name, 'd' deletes a local preset
+    pub fn handle_preset_picker_input(&mut self, key: KeyEvent) {
+        let Some(picker) = &mut self.preset_picker else {
+            return;
+        };
+        if let Some(name) = &mut picker.naming {
+            match key.code {
+                KeyCode::Esc => picker.naming = None,
+                KeyCode::Char(c) => { name.handle(InputRequest::InsertChar(c)); },
+                KeyCode::Backspace => { name.handle(InputRequest::DeletePrevChar); },
+                KeyCode::Enter => {
+                    let name = name.value().trim().to_string();
+                    if !name.is_empty() {
+                        self.save_preset(&name);
+                        self.open_preset_picker();
+                    }
+                },
+                _ => {},
+            }
+            return;
+        }
+        match key.code {
+            KeyCode::Esc => self.preset_picker = None,
+            KeyCode::Down if !picker.names.is_empty() => picker.selected = (picker.selected + 1) % picker.names.len(),
+            KeyCode::Up if !picker.names.is_empty() => {
+                picker.selected = (picker.selected + picker.names.len() - 1) % picker.names.len();
+            },
+            KeyCode::Char('s') => {
+                let name = picker.names.get(picker.selected).cloned().unwrap_or_default();
+                picker.naming = Some(Input::new(name));
+            },
+            KeyCode::Char('d') => {
+                let Some(name) = picker.names.get(picker.selected).cloned() else {
+                    return;
+                };
+                if self.config.active_presets_mut().remove(&name).is_none() {
+                    self.message = Some(format!("Preset {} is shared and can't be deleted here", name));
+                    return;
+                }
+                self.message = Some(match self.config.save() {
+                    Ok(()) => format!("Preset {} deleted", name),
+                    Err(err) => format!("Failed to delete preset: {:#}", err),
+                });
+                self.open_preset_picker();
+            },
+            KeyCode::Enter => {
+                if let Some(name) = picker.names.get(picker.selected).cloned() {
+                    self.apply_preset(&name);
+                }
+            },
+            KeyCode::Char(c @ '1'..='9') => {
+                if let Some(name) = picker.names.get(c as usize - '1' as usize).cloned() {
+                    self.apply_preset(&name);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Saves the current processing configuration as a local preset, keeping the preset's
+    /// other settings (description, columns, ...) when it already exists
+    pub fn save_preset(&mut self, name: &str) {
+        let mut preset = self.config.active_presets().get(name).cloned().unwrap_or_default();
+        self.fill_preset(&mut preset);
+        self.config.active_presets_mut().insert(name.to_string(), preset);
+        self.message = Some(match self.config.save() {
+            Ok(()) => format!("Preset {} saved", name),
+            Err(err) => format!("Failed to save preset: {:#}", err),
+        });
+    }
+
+    /// Writes the current processing configuration into a preset
+    pub fn fill_preset(&self, preset: &mut Preset) {
+        preset.processing_type = Some(self.processing_type.to_str().to_string());
+        preset.provider = self.get_current_provider().map(str::to_string);
+        preset.model = self.get_current_model().map(str::to_string);
+        preset.language = Some(self.language.clone());
+        preset.system_prompt = (!self.system_prompt.is_empty()).then(|| self.system_prompt.clone());
+        preset.template = None;
+        preset.keywords = self.keywords.clone();
+        preset.temperature = self.sampling.temperature;
+        preset.max_tokens = self.sampling.max_tokens;
+        preset.top_p = self.sampling.top_p;
+        preset.output_format = Some(self.output_format.name().to_string());
+    }
+
+    /// Applies a local or shared preset; its unset fields leave the current selection unchanged
+    pub fn apply_preset(&mut self, name: &str) {
+        let Some((preset, _)) = self.effective_presets().remove(name) else {
+            self.message = Some(format!("Unknown preset {}", name));
+            return;
+        };
+        if let Some(processing_type) = preset.processing_type.as_deref().and_then(ProcessingType::parse) {
+            self.processing_type = processing_type;
+        }
+        if let Some(provider) = &preset.provider {
+            let model = preset.model.as_deref().or(self.get_current_model()).unwrap_or_default().to_string();
+            self.select_model(provider, &model);
+        } else if let (Some(provider), Some(model)) = (self.get_current_provider().map(str::to_string), &preset.model) {
+            self.select_model(&provider, model);
+        }
+        if let Some(language) = &preset.language {
+            self.language = language.clone();
+        }
+        if let Some(template) = &preset.template {
+            match self.effective_templates().remove(template) {
+                Some((prompt, _)) => self.system_prompt = prompt,
+                None => self.debug_info.push(format!("Preset {} names unknown template {}", name, template)),
+            }
+        } else if let Some(system_prompt) = &preset.system_prompt {
+            self.system_prompt = system_prompt.clone();
+        }
+        if !preset.keywords.is_empty() {
+            self.keywords = preset.keywords.clone();
+        }
+        self.sampling = SamplingParams {
+            temperature: preset.temperature.or(self.sampling.temperature),
+            max_tokens: preset.max_tokens.or(self.sampling.max_tokens),
+            top_p: preset.top_p.or(self.sampling.top_p),
+        };
+        if let Some(format) = &preset.output_format {
+            self.output_format = DatasetFormat::from_name(format);
+        }
+        self.preset_picker = None;
+        self.message = Some(format!("Preset {} applied", name));
+    }
+
+    /// Records a polled status in the history and notifies webhooks
+    fn report_job_status(&mut self, job_id: &str, status: &str) {
+        if let Some((summary, body)) = self.job_notification(job_id, status) {
+            self.notify(summary, body);
+        }
+        if let Err(err) = self.history.update_status(job_id, status) {
+            self.debug_info.push(format!("Failed to save job history: {}", err));
+        }
+        self.emit_event(WebhookEvent::job_progress(job_id, status, self.job_progress));
+        if status == "completed" {
+            if let Some(entry) = self.history.entries().iter().rev().find(|e| e.job_id == job_id) {
+                self.emit_event(WebhookEvent::job_completed(entry));
+            }
+        }
+    }
+
+    /// Summary and body of the notification due when a job in the history reaches `status`:
+    /// it moved to completed or failed while the terminal isn't focused
+    pub fn job_notification(&self, job_id: &str, status: &str) -> Option<(String, String)> {
+        let settings = &self.config.notifications;
+        if !settings.enabled || (self.focused && !settings.when_focused) || !matches!(status, "completed" | "failed") {
+            return None;
+        }
+        let entry = self.history.entries().iter().rev().find(|e| e.job_id == job_id)?;
+        (entry.status != status).then(|| notifications::job_finished(job_id, status, Some(&entry.file)))
+    }
+
+    /// Shows a desktop notification off the UI thread; the bell rings if it can't be shown
+    fn notify(&self, summary: String, body: String) {
+        let tx = self.background_tx.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = notifications::send(&summary, &body) {
+                let _ = tx.send(BackgroundEvent::NotificationFailed(format!("{:#}", err)));
+            }
+        });
+    }
+
+    /// Sends an event to the configured webhooks and message broker
+    fn emit_event(&self, event: WebhookEvent) {
+        pubsub::dispatch(self.config.event_broker.as_ref(), &event);
+        webhooks::dispatch(&self.config.webhooks, event);
+    }
+
+    pub fn toggle_jobs_mine_only(&mut self) {
+        self.jobs_mine_only = !self.jobs_mine_only;
+        self.message = Some(if self.jobs_mine_only {
+            "Showing only my jobs".to_string()
+        } else {
+            "Showing jobs of all users".to_string()
+        });
+    }
+
+    /// Jobs visible in the jobs list, honoring the "mine only" toggle, in `jobs_sort` order.
+    /// Without a known current user the filter is a no-op.
+    pub fn visible_jobs(&self) -> Vec<&JobStatus> {
+        let mut jobs: Vec<&JobStatus> = match (&self.current_user, self.jobs_mine_only) {
+            (Some(user), true) => self.jobs.iter().filter(|job| job.is_owned_by(user)).collect(),
+            _ => self.jobs.iter().collect(),
+        };
+        jobs.sort_by(|a, b| self.jobs_sort.compare(a, b));
+        jobs
+    }
+
+    /// Built-in variables of `file` and the profile variables of the active workspace
+    fn template_context(&self, file: &str) -> TemplateContext {
+        TemplateContext::from_source(std::path::Path::new(file), &self.language)
+            .with_variables(&self.config.profile_variables())
+    }
+
+    /// Builds the request config for a file, resolving `{{variables}}` in the
+    /// system prompt against that file so batch jobs get per-file prompts.
+    pub fn build_processing_config(&self, file: &str) -> ProcessingConfig {
+        let ctx = self.template_context(file);
+        let system_prompt = if self.system_prompt.is_empty() {
+            None
+        } else {
+            Some(templating::render(&self.system_prompt, &ctx))
+        };
+        let provider = self.get_current_provider().unwrap_or_default().to_string();
+
+        ProcessingConfig {
+            model: self.get_current_model().unwrap_or_default().to_string(),
+            system_prompt,
+            keywords: if self.keywords.is_empty() { None } else { Some(self.keywords.clone()) },
+            temperature: self.sampling.temperature,
+            max_tokens: self.sampling.max_tokens,
+            top_p: self.sampling.top_p,
+            language: Some(self.language.clone()),
+            processing_type: self.processing_type.to_str().to_string(),
+            add_reasoning: None,
+            output_format: (self.output_format != DatasetFormat::Json).then(|| self.output_format.name().to_string()),
+            provider_base_url: self.config.provider_base_url(&provider),
+            provider_api: self.config.custom_provider(&provider).map(|_| providers::OPENAI_COMPATIBLE.to_string()),
+            provider,
+        }
+    }
+
+    pub fn open_download_dialog(&mut self) {
+        let Some(job_id) = self.current_job_id.clone() else {
+            self.message = Some("No job selected - enter a job ID first".to_string());
+            return;
+        };
+        let preset = self.history.entries().iter().rev()
+            .find(|e| e.job_id == job_id)
+            .map(|e| e.processing_type.clone())
+            .unwrap_or_else(|| self.processing_type.to_str().to_string());
+        let actions = self.config.post_download_actions(&preset)
+            .into_iter()
+            .map(|action| (action, true))
+            .collect();
+
+        self.download_dialog = Some(DownloadDialog { job_id, preset, actions, selected: 0, ignore_disk_space: false });
+    }
+
+    pub fn handle_download_dialog_input(&mut self, key: KeyEvent) {
+        let Some(dialog) = &mut self.download_dialog else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.download_dialog = None,
+            KeyCode::Down if !dialog.actions.is_empty() => {
+                dialog.selected = (dialog.selected + 1) % dialog.actions.len();
+            },
+            KeyCode::Up if !dialog.actions.is_empty() => {
+                dialog.selected = (dialog.selected + dialog.actions.len() - 1) % dialog.actions.len();
+            },
+            KeyCode::Char(' ') => {
+                if let Some((_, enabled)) = dialog.actions.get_mut(dialog.selected) {
+                    *enabled = !*enabled;
+                }
+            },
+            KeyCode::Char('f') => dialog.ignore_disk_space = !dialog.ignore_disk_space,
+            KeyCode::Enter => self.confirm_download(),
+            _ => {},
+        }
+    }
+
+    /// Starts the download in the background; selected actions run once the file is on disk
+    pub fn confirm_download(&mut self) {
+        let Some(dialog) = self.download_dialog.take() else {
+            return;
+        };
+
+        let entry = self.history.entries().iter().rev()
+            .find(|e| e.job_id == dialog.job_id)
+            .cloned()
+            .unwrap_or_else(|| HistoryEntry {
+                job_id: dialog.job_id.clone(),
+                file: dialog.job_id.clone(),
+                processing_type: dialog.preset.clone(),
+                provider: String::new(),
+                model: String::new(),
+                status: self.job_status.clone().unwrap_or_default(),
+                submitted_at: chrono::Utc::now(),
+                finished_at: None,
+                records: None,
+                tokens: None,
+                cost_usd: None,
+                variables: BTreeMap::new(),
+            });
+        let path = match self.output_path_for(&entry) {
+            Ok(path) => path,
+            Err(err) => {
+                self.message = Some(format!("Cannot download results: {}", err));
+                return;
+            },
+        };
+
+        let actions: Vec<PostDownloadAction> = dialog.actions.into_iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(action, _)| action)
+            .collect();
+        let client = ApiClient::new(&self.backend_url)
+            .with_monitor(self.network.clone())
+            .with_download_settings(self.config.download.clone())
+            .with_headers(self.config.request_headers());
+        let tx = self.background_tx.clone();
+        let git_settings = self.config.git.clone().filter(|git| git.enabled);
+        let webhook_settings = self.config.webhooks.clone();
+        let broker_settings = self.config.event_broker.clone();
+        let mut git_ctx = self.template_context(&entry.file);
+        git_ctx.set("job_id", entry.job_id.as_str());
+        git_ctx.set("preset", entry.processing_type.as_str());
+        git_ctx.set("file_name", path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default());
+        let job_id = dialog.job_id;
+        let min_free_space_mb = self.config.min_free_space_mb;
+        let ignore_disk_space = dialog.ignore_disk_space;
+        let extract_archives = self.config.extract_archives;
+        let extract_limits = self.config.extract_limits.clone();
+        let vault = self.vault.clone();
+        let sign = self.config.signing.enabled;
+        // Size is unknown until the response headers arrive; this only catches an almost full disk
+        let warning = diskspace::ensure_fits(&path, None, min_free_space_mb, ignore_disk_space)
+            .ok()
+            .flatten();
+        self.message = Some(match warning {
+            Some(warning) => format!("Downloading results of {} to {} - {}", job_id, path.display(), warning),
+            None => format!("Downloading results of {} to {}", job_id, path.display()),
+        });
+
+        background::spawn("Download", move |runtime| {
+            let result = runtime.block_on(async {
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                client.download_results_checked(&job_id, &path, |size| {
+                    diskspace::ensure_fits(&path, size, min_free_space_mb, ignore_disk_space)
+                        .map(|_| ())
+                        .context("Press 'f' in the download dialog to download anyway")
+                }).await
+            });
+            let event = match result {
+                Ok(()) => {
+                    let mut notes = Vec::new();
+                    let mut unpacked = None;
+                    if extract_archives {
+                        match archive::unpack_results(&path, &entry, &extract_limits) {
+                            Ok(Some(archive)) => {
+                                notes.push(format!("Unpacked {} file(s) to {}", archive.files.len(), archive.dir.display()));
+                                unpacked = Some(archive);
+                            },
+                            Ok(None) => {},
+                            Err(err) => notes.push(format!("Failed to unpack {}: {:#}", path.display(), err)),
+                        }
+                    }
+                    let datasets = unpacked.as_ref().map(|archive| archive.datasets.clone()).unwrap_or_default();
+                    // The datasets of an unpacked archive got their manifests from `unpack_results`;
+                    // the archive itself isn't a dataset and gets none
+                    let manifest = if unpacked.is_none() {
+                        match DatasetManifest::for_dataset(&path, &entry).and_then(|m| m.write_for(&path)) {
+                            Ok(manifest) => Some(manifest),
+                            Err(err) => {
+                                notes.push(format!("Failed to write manifest: {:#}", err));
+                                None
+                            },
+                        }
+                    } else {
+                        None
+                    };
+
+                    // Encrypted before the post-download actions, git and webhooks get to them,
+                    // so nothing but ciphertext leaves this machine or stays on disk
+                    if vault.enabled() {
+                        let mut written: Vec<std::path::PathBuf> = std::iter::once(path.clone()).chain(manifest.clone()).collect();
+                        if let Some(archive) = &unpacked {
+                            written.extend(archive.files.iter().cloned());
+                            written.extend(datasets.iter().map(|dataset| DatasetManifest::path_for(dataset)));
+                        }
+                        for file in &written {
+                            if let Err(err) = vault.encrypt_in_place(file) {
+                                notes.push(format!("Failed to encrypt {}: {:#}", file.display(), err));
+                            }
+                        }
+                    }
+
+                    let outcome = hooks::run_actions(&actions, &path);
+                    notes.extend(outcome.notes);
+                    let mut open_in_editor = outcome.open_in_editor;
+                    if vault.enabled() && open_in_editor {
+                        open_in_editor = false;
+                        notes.push(format!("Not opening {} in the editor: it is encrypted", outcome.path.display()));
+                    }
+                    // `MoveTo` takes the manifest along with the file
+                    let manifest = manifest
+                        .map(|_| DatasetManifest::path_for(&outcome.path))
+                        .filter(|manifest| manifest.exists());
+
+                    if let Some(git) = &git_settings {
+                        let committed: Vec<std::path::PathBuf> = match &unpacked {
+                            Some(archive) => archive.files.iter().cloned()
+                                .chain(datasets.iter().map(|dataset| DatasetManifest::path_for(dataset)))
+                                .collect(),
+                            None => std::iter::once(outcome.path.clone()).chain(manifest.clone()).collect(),
+                        };
+                        let files: Vec<&std::path::Path> = committed.iter().map(|file| file.as_path()).collect();
+                        match git::commit_dataset(git, &files, &git_ctx) {
+                            Ok(()) => notes.push(format!("Committed {} file(s) to {}", files.len(), git.repository.display())),
+                            Err(err) => notes.push(format!("Failed git commit: {:#}", err)),
+                        }
+                    }
+
+                    let size = std::fs::metadata(&outcome.path).map(|m| m.len()).unwrap_or_default();
+                    let event = WebhookEvent::dataset_exported(&job_id, &outcome.path, size, manifest.as_deref());
+                    notes.extend(runtime.block_on(webhooks::deliver_all(&webhook_settings, &event)));
+                    if let Some(broker) = &broker_settings {
+                        if let Err(err) = runtime.block_on(pubsub::publish(broker, &event)) {
+                            notes.push(format!("Failed to publish to {}: {:#}", broker.label(), err));
+                        }
+                    }
+
+                    // Signed last, so the signatures cover the files as they stay on disk
+                    if sign {
+                        let files: Vec<std::path::PathBuf> = std::iter::once(outcome.path.clone())
+                            .chain(datasets.iter().cloned())
+                            .flat_map(|file| [DatasetManifest::path_for(&file), file])
+                            .filter(|file| file.exists())
+                            .collect();
+                        match signing::sign_all(files.iter().map(|file| file.as_path())) {
+                            Ok(signed) => notes.push(format!("Signed {} file(s)", signed)),
+                            Err(err) => notes.push(format!("Failed to sign: {:#}", err)),
+                        }
+                    }
+                    BackgroundEvent::DownloadFinished {
+                        job_id,
+                        path: outcome.path,
+                        datasets,
+                        notes,
+                        open_in_editor,
+                    }
+                },
+                Err(err) => BackgroundEvent::DownloadFailed { job_id, error: format!("{:#}", err) },
+            };
+            let _ = tx.send(event);
+        });
+    }
+
+    /// Download target for a job's results, named after the configured
+    /// output template and collision policy
+    pub fn output_path_for(&self, entry: &HistoryEntry) -> anyhow::Result<std::path::PathBuf> {
+        let dir = self.config.downloads_dir();
+        let mut ctx = self.template_context(&entry.file);
+        ctx.set("preset", entry.processing_type.as_str());
+        ctx.set("job_id", entry.job_id.as_str());
+        ctx.set("ext", "json");
+        resolve_output_path(&dir, &self.config.output, &ctx)
+    }
+
+    fn record_history(&mut self, job_id: String, file: String) {
+        self.record_entry(HistoryEntry {
+            job_id,
+            file,
+            processing_type: self.processing_type.to_str().to_string(),
+            provider: self.get_current_provider().unwrap_or_default().to_string(),
+            model: self.get_current_model().unwrap_or_default().to_string(),
+            status: "processing".to_string(),
+            submitted_at: chrono::Utc::now(),
+            finished_at: None,
+            records: None,
+            tokens: None,
+            cost_usd: None,
+            variables: self.config.profile_variables(),
+        });
+    }
+
+    fn record_entry(&mut self, entry: HistoryEntry) {
+        self.emit_event(WebhookEvent::job_created(&entry));
+        if let Err(err) = self.history.record(entry) {
+            self.debug_info.push(format!("Failed to save job history: {}", err));
+        }
+    }
+
+    /// Quick action: load the settings of the last submitted job into the Process screen
+    pub fn rerun_last_job(&mut self) {
+        let Some(last) = self.history.last().cloned() else {
+            self.message = Some("No previous jobs to re-run".to_string());
+            return;
+        };
+
+        if let Some(pt) = ProcessingType::parse(&last.processing_type) {
+            self.processing_type = pt;
+        }
+        if let Some(i) = self.providers.iter().position(|p| p == &last.provider) {
+            self.selected_provider_index = Some(i);
+            self.selected_model_index = self.models.get(&last.provider)
+                .and_then(|models| models.iter().position(|m| m == &last.model))
+                .or(Some(0));
+        }
+        let file_index = match self.uploaded_files.iter().position(|f| f == &last.file) {
+            Some(i) => i,
+            None => {
+                self.uploaded_files.push(last.file.clone());
+                self.uploaded_files.len() - 1
+            },
+        };
+        self.selected_file_index = Some(file_index);
+        self.state = AppState::Process;
+        self.message = Some(format!("Loaded settings of job {} - press 'p' to submit", last.job_id));
+    }
+
+    /// Quick action: reattach the Job Status screen to the last submitted job
+    pub fn resume_last_job(&mut self) {
+        let Some(last) = self.history.last().cloned() else {
+            self.message = Some("No previous jobs to resume".to_string());
+            return;
+        };
+
+        self.job_id_input = Input::new(last.job_id.clone());
+        self.current_job_id = Some(last.job_id.clone());
+        self.job_status = Some(last.status.clone());
+        self.state = AppState::JobStatus;
+        self.message = Some(format!("Resumed job {}", last.job_id));
+    }
+
     pub fn get_current_provider(&self) -> Option<&str> {
         self.selected_provider_index.and_then(|i| self.providers.get(i)).map(|s| s.as_str())
     }
@@ -0,0 +1,500 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tui_input::Input;
+
+use crate::atomic;
+use crate::checks;
+use crate::extract;
+use crate::formats::parse_records;
+use crate::lineage;
+use crate::manifest::DatasetManifest;
+use crate::preview;
+use crate::processors::Record;
+use crate::qa;
+use crate::storage;
+use crate::vault::Vault;
+
+/// Record metadata: job the record was taken from
+pub const SOURCE_JOB: &str = "source_job";
+
+/// Dataset of a finished job the corpus can draw from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source {
+    /// Job id, or the file name of a local run
+    pub label: String,
+    pub path: PathBuf,
+    pub processing_type: String,
+    pub model: String,
+    pub records: Vec<Value>,
+    pub included: bool,
+    /// Part of the source's records (after filtering and dedup) taken into the corpus, 0-1
+    pub weight: f64,
+}
+
+impl Source {
+    pub fn new(label: &str, path: &Path, records: Vec<Value>) -> Self {
+        Self {
+            label: label.to_string(),
+            path: path.to_path_buf(),
+            processing_type: String::new(),
+            model: String::new(),
+            records,
+            included: false,
+            weight: 1.0,
+        }
+    }
+}
+
+/// Downloaded datasets found through their manifests, newest first, then the last local run.
+/// Files that can't be read or parsed are left out.
+pub fn find_sources(downloads_dir: &Path, local: Option<&Path>, vault: &Vault) -> Vec<Source> {
+    let mut sources = Vec::new();
+    for group in storage::scan(downloads_dir, None, vault).unwrap_or_default() {
+        let Some(job_id) = &group.job_id else {
+            continue;
+        };
+        for path in group.files.iter().filter(|path| !path.to_string_lossy().ends_with(".manifest.json")) {
+            let Ok(manifest) = DatasetManifest::load(&DatasetManifest::path_for(path), vault) else {
+                continue;
+            };
+            let Some(records) = vault.read_to_string(path).ok().and_then(|content| parse_records(&content).ok()) else {
+                continue;
+            };
+            let mut source = Source::new(job_id, path, records);
+            source.processing_type = manifest.processing_type;
+            source.model = manifest.model;
+            sources.push(source);
+        }
+    }
+    if let Some(path) = local {
+        if let Some(records) = vault.read_to_string(path).ok().and_then(|content| parse_records(&content).ok()) {
+            let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            let mut source = Source::new(&name, path, records);
+            source.processing_type = "local".to_string();
+            sources.push(source);
+        }
+    }
+    sources
+}
+
+/// Which records may enter the corpus
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filters {
+    /// Fewest words of a completion
+    pub min_words: usize,
+    /// Most words of a completion; 0 for no limit
+    pub max_words: usize,
+    /// Leave out records flagged by checks, quote verification or schema validation
+    pub drop_flagged: bool,
+    /// Metadata field and the value it must have, as `field=value`
+    pub require: Option<(String, String)>,
+}
+
+impl Filters {
+    pub fn keep(&self, record: &Value) -> bool {
+        let words = completion(record).split_whitespace().count();
+        if words < self.min_words || (self.max_words > 0 && words > self.max_words) {
+            return false;
+        }
+        let metadata = record.get("metadata");
+        let field = |name: &str| metadata.and_then(|m| m.get(name));
+        if self.drop_flagged {
+            let listed = |name: &str| field(name).and_then(Value::as_array).is_some_and(|items| !items.is_empty());
+            if listed(checks::ISSUES) || listed(extract::SCHEMA_ERRORS) || field(qa::GROUNDED) == Some(&Value::Bool(false)) {
+                return false;
+            }
+        }
+        match &self.require {
+            Some((name, value)) => field(name).is_some_and(|found| match found {
+                Value::String(found) => found == value,
+                other => serde_json::from_str::<Value>(value).is_ok_and(|value| value == *other),
+            }),
+            None => true,
+        }
+    }
+
+    /// Parses `field=value`; an empty text removes the requirement
+    pub fn set_require(&mut self, text: &str) -> Result<()> {
+        let text = text.trim();
+        if text.is_empty() {
+            self.require = None;
+            return Ok(());
+        }
+        let (name, value) = text.split_once('=').context("Expected field=value")?;
+        if name.trim().is_empty() {
+            anyhow::bail!("Expected field=value");
+        }
+        self.require = Some((name.trim().to_string(), value.trim().to_string()));
+        Ok(())
+    }
+
+    /// One-line description for the dataset card
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.min_words > 0 {
+            parts.push(format!("completions of at least {} words", self.min_words));
+        }
+        if self.max_words > 0 {
+            parts.push(format!("completions of at most {} words", self.max_words));
+        }
+        if self.drop_flagged {
+            parts.push("flagged records left out".to_string());
+        }
+        if let Some((name, value)) = &self.require {
+            parts.push(format!("`{}` = `{}`", name, value));
+        }
+        if parts.is_empty() {
+            return "none".to_string();
+        }
+        parts.join(", ")
+    }
+}
+
+/// Completion of a record; records of other shapes use their `output` or `response`
+fn completion(record: &Value) -> &str {
+    ["completion", "output", "response"].iter()
+        .find_map(|field| record.get(field).and_then(Value::as_str))
+        .unwrap_or_default()
+}
+
+/// Identity of a record's content: the lineage content id for records of local runs,
+/// the record without its metadata otherwise
+fn content_key(record: &Value) -> String {
+    if let Ok(record) = serde_json::from_value::<Record>(record.clone()) {
+        return lineage::content_id(&record);
+    }
+    let mut record = record.clone();
+    if let Some(object) = record.as_object_mut() {
+        object.remove("metadata");
+    }
+    record.to_string()
+}
+
+/// Everything besides the sources that shapes the corpus
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub filters: Filters,
+    /// Drop records whose content came up before, in the same job or an earlier one
+    pub dedup: bool,
+    /// Part of the corpus held out for validation, 0-1
+    pub val_fraction: f64,
+    /// Sampling and split seed; the same seed gives the same corpus
+    pub seed: u64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self { filters: Filters::default(), dedup: true, val_fraction: 0.1, seed: 0 }
+    }
+}
+
+/// What became of one source's records
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceCount {
+    pub available: usize,
+    pub filtered: usize,
+    pub duplicates: usize,
+    pub taken: usize,
+}
+
+/// Corpus put together from the included sources
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Assembly {
+    pub train: Vec<Value>,
+    pub val: Vec<Value>,
+    /// By position in the sources, excluded ones included
+    pub counts: Vec<SourceCount>,
+}
+
+impl Assembly {
+    pub fn records(&self) -> usize {
+        self.train.len() + self.val.len()
+    }
+}
+
+/// Filters every included source, drops records seen in an earlier source, samples each by its
+/// weight, then shuffles the lot and holds out the validation split
+pub fn assemble(sources: &[Source], options: &Options) -> Assembly {
+    let mut seen = HashSet::new();
+    let mut records = Vec::new();
+    let mut counts = Vec::with_capacity(sources.len());
+    for (i, source) in sources.iter().enumerate() {
+        let mut count = SourceCount { available: source.records.len(), ..Default::default() };
+        if !source.included {
+            counts.push(count);
+            continue;
+        }
+        let mut kept = Vec::new();
+        for record in &source.records {
+            if !options.filters.keep(record) {
+                count.filtered += 1;
+            } else if options.dedup && !seen.insert(content_key(record)) {
+                count.duplicates += 1;
+            } else {
+                kept.push(record);
+            }
+        }
+        let take = (kept.len() as f64 * source.weight.clamp(0.0, 1.0)).round() as usize;
+        for index in preview::sample_indices(kept.len(), take, options.seed ^ (i as u64 + 1)) {
+            let mut record = kept[index].clone();
+            if let Some(object) = record.as_object_mut() {
+                let metadata = object.entry("metadata").or_insert_with(|| Value::Object(Default::default()));
+                if let Some(metadata) = metadata.as_object_mut() {
+                    metadata.insert(SOURCE_JOB.to_string(), source.label.clone().into());
+                }
+            }
+            records.push(record);
+        }
+        count.taken = take;
+        counts.push(count);
+    }
+
+    let order = preview::shuffled(records.len(), options.seed);
+    let val_len = (records.len() as f64 * options.val_fraction.clamp(0.0, 1.0)).round() as usize;
+    let mut val = Vec::with_capacity(val_len);
+    let mut train = Vec::with_capacity(records.len() - val_len);
+    for (position, index) in order.into_iter().enumerate() {
+        let record = std::mem::take(&mut records[index]);
+        if position < val_len {
+            val.push(record);
+        } else {
+            train.push(record);
+        }
+    }
+    Assembly { train, val, counts }
+}
+
+/// Dataset card (README.md) describing where the corpus comes from and how it was made
+pub fn card(name: &str, sources: &[Source], options: &Options, assembly: &Assembly) -> String {
+    let mut card = format!(
+        "---\npretty_name: {}\nconfigs:\n- config_name: default\n  data_files:\n  - split: train\n    path: train.jsonl\n  - split: validation\n    path: val.jsonl\n---\n\n# {}\n\n\
+         Assembled with anydataset-tui {} on {} from {} job(s).\n\n\
+         | Split | Records |\n|---|---|\n| train | {} |\n| validation | {} |\n\n\
+         ## Sources\n\n| Job | Type | Model | Records | Filtered | Duplicates | Weight | Taken |\n|---|---|---|---|---|---|---|---|\n",
+        name,
+        name,
+        env!("CARGO_PKG_VERSION"),
+        chrono::Utc::now().format("%Y-%m-%d"),
+        sources.iter().filter(|source| source.included).count(),
+        assembly.train.len(),
+        assembly.val.len(),
+    );
+    for (source, count) in sources.iter().zip(&assembly.counts).filter(|(source, _)| source.included) {
+        card.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {:.2} | {} |\n",
+            source.label, source.processing_type, source.model, count.available, count.filtered, count.duplicates, source.weight, count.taken
+        ));
+    }
+    card.push_str(&format!(
+        "\n## Processing\n\n- Filters: {}\n- Deduplication across sources: {}\n- Validation split: {:.0}%, seed {}\n- Each record names its job in `metadata.{}`\n",
+        options.filters.describe(),
+        if options.dedup { "on" } else { "off" },
+        options.val_fraction * 100.0,
+        options.seed,
+        SOURCE_JOB,
+    ));
+    card
+}
+
+/// Writes `train.jsonl`, `val.jsonl` and the dataset card to `dir`; returns the files
+pub fn export(dir: &Path, sources: &[Source], options: &Options, assembly: &Assembly) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let name = dir.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| "corpus".to_string());
+    let jsonl = |records: &[Value]| -> Result<String> {
+        let mut out = String::new();
+        for record in records {
+            out.push_str(&serde_json::to_string(record)?);
+            out.push('\n');
+        }
+        Ok(out)
+    };
+    let files = [
+        (dir.join("train.jsonl"), jsonl(&assembly.train)?),
+        (dir.join("val.jsonl"), jsonl(&assembly.val)?),
+        (dir.join("README.md"), card(&name, sources, options, assembly)),
+    ];
+    for (path, content) in &files {
+        atomic::write(path, content.as_bytes())?;
+    }
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+/// Steps of the wizard, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Sources,
+    Filters,
+    Weights,
+    Split,
+    Export,
+}
+
+impl Step {
+    pub const ALL: [Step; 5] = [Step::Sources, Step::Filters, Step::Weights, Step::Split, Step::Export];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Step::Sources => "Pick jobs",
+            Step::Filters => "Filters",
+            Step::Weights => "Sampling weights",
+            Step::Split => "Dedup and split",
+            Step::Export => "Export",
+        }
+    }
+
+    fn offset(self, by: isize) -> Self {
+        let index = Self::ALL.iter().position(|step| *step == self).unwrap_or(0);
+        Self::ALL[index.saturating_add_signed(by).min(Self::ALL.len() - 1)]
+    }
+}
+
+/// Fields of the filters step, in display order
+pub const FILTER_FIELDS: [&str; 4] = ["Min words", "Max words", "Drop flagged", "Require field=value"];
+/// Fields of the dedup-and-split step, in display order
+pub const SPLIT_FIELDS: [&str; 3] = ["Dedup across jobs", "Validation split", "Seed"];
+
+/// Popup assembling one training corpus from the datasets of many finished jobs
+#[derive(Debug, Clone)]
+pub struct AssemblyWizard {
+    pub step: Step,
+    pub sources: Vec<Source>,
+    pub options: Options,
+    /// Row with the cursor in the current step
+    pub selected: usize,
+    pub require: Input,
+    pub output: Input,
+    pub error: Option<String>,
+    /// Corpus with the current choices
+    pub preview: Assembly,
+}
+
+impl AssemblyWizard {
+    pub fn new(sources: Vec<Source>, output: &Path) -> Self {
+        let mut wizard = Self {
+            step: Step::Sources,
+            sources,
+            options: Options::default(),
+            selected: 0,
+            require: Input::default(),
+            output: Input::new(output.to_string_lossy().into_owned()),
+            error: None,
+            preview: Assembly::default(),
+        };
+        wizard.refresh();
+        wizard
+    }
+
+    /// Rows of the current step
+    pub fn rows(&self) -> usize {
+        match self.step {
+            Step::Sources => self.sources.len(),
+            Step::Weights => self.included().count(),
+            Step::Filters => FILTER_FIELDS.len(),
+            Step::Split => SPLIT_FIELDS.len(),
+            Step::Export => 0,
+        }
+    }
+
+    pub fn included(&self) -> impl Iterator<Item = &Source> {
+        self.sources.iter().filter(|source| source.included)
+    }
+
+    pub fn next_row(&mut self) {
+        if self.rows() > 0 {
+            self.selected = (self.selected + 1) % self.rows();
+        }
+    }
+
+    pub fn previous_row(&mut self) {
+        if self.rows() > 0 {
+            self.selected = (self.selected + self.rows() - 1) % self.rows();
+        }
+    }
+
+    /// Moves to the next (1) or previous (-1) step. Leaving the first step needs a job picked,
+    /// leaving the filters a valid requirement.
+    pub fn go(&mut self, by: isize) {
+        self.error = None;
+        if by > 0 {
+            match self.step {
+                Step::Sources if self.included().count() == 0 => {
+                    self.error = Some("Pick at least one job".to_string());
+                    return;
+                },
+                Step::Filters => {
+                    if let Err(err) = self.options.filters.set_require(self.require.value()) {
+                        self.error = Some(format!("{:#}", err));
+                        return;
+                    }
+                },
+                _ => {},
+            }
+        }
+        self.step = self.step.offset(by);
+        self.selected = 0;
+        self.refresh();
+    }
+
+    /// Space: includes a job, toggles a switch
+    pub fn toggle(&mut self) {
+        match (self.step, self.selected) {
+            (Step::Sources, i) => {
+                if let Some(source) = self.sources.get_mut(i) {
+                    source.included = !source.included;
+                }
+            },
+            (Step::Filters, 2) => self.options.filters.drop_flagged = !self.options.filters.drop_flagged,
+            (Step::Split, 0) => self.options.dedup = !self.options.dedup,
+            _ => return,
+        }
+        self.refresh();
+    }
+
+    /// ←/→: changes the number or weight under the cursor by one step down (-1) or up (1)
+    pub fn adjust(&mut self, by: i64) {
+        let filters = &mut self.options.filters;
+        match (self.step, self.selected) {
+            (Step::Filters, 0) => filters.min_words = filters.min_words.saturating_add_signed(by as isize * 5),
+            (Step::Filters, 1) => filters.max_words = filters.max_words.saturating_add_signed(by as isize * 50),
+            (Step::Weights, i) => {
+                let Some(source) = self.sources.iter_mut().filter(|source| source.included).nth(i) else {
+                    return;
+                };
+                source.weight = ((source.weight + by as f64 * 0.1).clamp(0.0, 1.0) * 10.0).round() / 10.0;
+            },
+            (Step::Split, 1) => {
+                self.options.val_fraction = ((self.options.val_fraction + by as f64 * 0.05).clamp(0.0, 0.5) * 20.0).round() / 20.0;
+            },
+            (Step::Split, 2) => self.options.seed = self.options.seed.saturating_add_signed(by),
+            _ => return,
+        }
+        self.refresh();
+    }
+
+    /// Text input of the current row, if it takes text
+    pub fn text_input(&mut self) -> Option<&mut Input> {
+        match (self.step, self.selected) {
+            (Step::Filters, 3) => Some(&mut self.require),
+            (Step::Export, _) => Some(&mut self.output),
+            _ => None,
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        self.preview = assemble(&self.sources, &self.options);
+    }
+
+    /// Writes the corpus to the output directory
+    pub fn export(&self) -> Result<Vec<PathBuf>> {
+        let dir = PathBuf::from(self.output.value().trim());
+        if dir.as_os_str().is_empty() {
+            anyhow::bail!("Enter an output directory");
+        }
+        if self.preview.records() == 0 {
+            anyhow::bail!("The corpus is empty; loosen the filters or weights");
+        }
+        export(&dir, &self.sources, &self.options, &self.preview)
+    }
+}
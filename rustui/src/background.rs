@@ -0,0 +1,132 @@
+use std::path::PathBuf;
+
+pub use anydataset_core::background::spawn;
+
+use crate::api::{BackendVersion, Dataset, DatasetInfo, JobPage, JobStatus};
+use crate::app::LocalJob;
+use crate::catalog::ModelCatalog;
+use crate::discovery::DiscoveredBackend;
+use crate::latency::BackendProbe;
+use crate::preflight::CorpusReport;
+use crate::preset_sync::SharedPresets;
+use crate::preview::Preview;
+use crate::storage::{CleanupReport, StorageGroup};
+
+/// Results of work running off the UI thread, delivered to `App::on_tick`
+#[derive(Debug, Clone)]
+pub enum BackgroundEvent {
+    UpdateAvailable(String),
+    BackendVersion(BackendVersion),
+    BackendUnreachable(String),
+    ModelsRefreshed(ModelCatalog),
+    ModelRefreshFailed(String),
+    OllamaModelsListed(Vec<String>),
+    OllamaUnreachable(String),
+    ProviderModelsListed {
+        provider: String,
+        models: Vec<String>,
+    },
+    ProviderDiscoveryFailed {
+        provider: String,
+        error: String,
+    },
+    DownloadFinished {
+        job_id: String,
+        path: PathBuf,
+        /// Dataset files unpacked from the results archive
+        datasets: Vec<PathBuf>,
+        notes: Vec<String>,
+        open_in_editor: bool,
+    },
+    DownloadFailed {
+        job_id: String,
+        error: String,
+    },
+    UploadProgress {
+        file: String,
+        sent: u64,
+        total: u64,
+    },
+    UploadFinished {
+        file: String,
+        file_id: String,
+    },
+    UploadFailed {
+        file: String,
+        error: String,
+    },
+    JobSubmitted {
+        file: String,
+        job_id: String,
+    },
+    SubmitFailed {
+        file: String,
+        error: String,
+    },
+    JobStatusUpdated(JobStatus),
+    StatusPollFailed {
+        job_id: String,
+        error: String,
+    },
+    JobCancelled {
+        job_id: String,
+    },
+    CancelFailed {
+        job_id: String,
+        error: String,
+    },
+    JobsListed(JobPage),
+    JobsFailed(String),
+    DatasetsListed(Vec<DatasetInfo>),
+    DatasetsFailed(String),
+    DatasetLoaded(Dataset),
+    DatasetDownloaded {
+        id: String,
+        path: PathBuf,
+    },
+    /// Fetching the details or downloading a stored dataset failed
+    DatasetFailed {
+        id: String,
+        error: String,
+    },
+    /// A local run finished or stopped at its spend cap; results so far are on disk
+    LocalRunStopped(LocalJob),
+    LocalRunFailed {
+        job_id: String,
+        error: String,
+        /// The job with the results it got done, when it failed after it started processing
+        partial: Option<LocalJob>,
+    },
+    /// Fetching the results a failed backend job got done finished
+    PartialSaved {
+        job_id: String,
+        result: Result<PathBuf, String>,
+    },
+    BackendsDiscovered(Vec<DiscoveredBackend>),
+    DiscoveryFailed(String),
+    KeyChecked {
+        provider: String,
+        result: Result<(), String>,
+    },
+    /// Latencies of the configured backend and its mirrors
+    BackendsProbed(Vec<BackendProbe>),
+    PresetsSynced(SharedPresets),
+    PresetSyncFailed(String),
+    StorageScanned(Vec<StorageGroup>),
+    StorageScanFailed(String),
+    CleanupFinished(CleanupReport),
+    CleanupFailed(String),
+    /// Pre-flight check of the files of a job about to be submitted
+    PreflightFinished {
+        file: String,
+        report: CorpusReport,
+    },
+    /// The pipeline ran on a few sampled chunks of a file
+    PreviewFinished(Preview),
+    PreviewFailed {
+        file: String,
+        error: String,
+    },
+    /// No desktop notification could be shown
+    NotificationFailed(String),
+}
@@ -0,0 +1,97 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+
+use crate::theme::ThemeSettings;
+
+/// What the terminal can show: Unicode glyphs (box-drawing borders, block gauges, Braille
+/// charts, arrows) and colors. Detected from the environment unless `[theme]` forces them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub unicode: bool,
+    pub color: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self { unicode: true, color: true }
+    }
+}
+
+impl Capabilities {
+    pub fn detect(settings: &ThemeSettings) -> Self {
+        Self::from_env(settings, |name| std::env::var(name).ok())
+    }
+
+    /// `var` reads an environment variable. A terminal gets Unicode when its locale is UTF-8
+    /// and colors unless `NO_COLOR` is set; dumb terminals and CI logs (no `TERM`) get neither.
+    pub fn from_env(settings: &ThemeSettings, var: impl Fn(&str) -> Option<String>) -> Self {
+        let term = var("TERM").unwrap_or_default();
+        // Windows consoles don't set TERM
+        let dumb = term == "dumb" || (term.is_empty() && !cfg!(windows));
+        let utf8 = ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+            .find_map(|name| var(name).filter(|value| !value.is_empty()))
+            .map_or(cfg!(windows), |locale| {
+                let locale = locale.to_ascii_lowercase();
+                locale.contains("utf-8") || locale.contains("utf8")
+            });
+        // The Linux virtual console font has no Braille and few block glyphs
+        let unicode = utf8 && !dumb && term != "linux";
+        let color = !dumb && var("NO_COLOR").is_none_or(|value| value.is_empty());
+        Self {
+            unicode: settings.unicode.resolve(unicode),
+            color: settings.color.resolve(color),
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.unicode && self.color
+    }
+
+    /// Rewrites a drawn frame for a limited terminal: ASCII in place of borders, gauges,
+    /// chart dots and symbols, and the terminal's default colors. Bold and reversed text
+    /// stay, so the selection is still visible.
+    pub fn simplify(&self, buf: &mut Buffer) {
+        if self.is_full() {
+            return;
+        }
+        for cell in &mut buf.content {
+            if !self.unicode {
+                if let Some(ascii) = ascii_symbol(cell.symbol()) {
+                    cell.set_symbol(ascii);
+                }
+            }
+            if !self.color {
+                cell.set_fg(Color::Reset).set_bg(Color::Reset);
+            }
+        }
+    }
+}
+
+/// ASCII stand-in for a one-cell Unicode symbol the UI draws; None for ASCII and for text
+/// such as accented letters, which is left alone
+pub fn ascii_symbol(symbol: &str) -> Option<&'static str> {
+    let mut chars = symbol.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        return None;
+    };
+    let ascii = match c {
+        '─' | '━' | '═' | '┄' | '┈' | '—' | '–' => "-",
+        '│' | '┃' | '║' | '┆' | '┊' => "|",
+        '┌' | '┐' | '└' | '┘' | '├' | '┤' | '┬' | '┴' | '┼' | '╭' | '╮' | '╯' | '╰' | '╔' | '╗' | '╚' | '╝'
+        | '╠' | '╣' | '╦' | '╩' | '╬' | '┏' | '┓' | '┗' | '┛' | '┣' | '┫' | '┳' | '┻' | '╋' => "+",
+        '█' | '▉' | '▊' | '▋' | '▌' | '▍' | '▎' | '▏' | '▐' | '▀' | '▇' | '▆' | '▅' | '▄' | '▃' | '▂' | '▁'
+        | '■' => "#",
+        '•' | '●' => "*",
+        '·' | '…' => ".",
+        '↑' | '▲' => "^",
+        '↓' | '▼' | '▾' => "v",
+        '←' | '◀' => "<",
+        '→' | '▶' | '▸' => ">",
+        '✓' | '✔' => "+",
+        '✗' | '✘' => "x",
+        '\u{2800}' => " ",
+        '\u{2801}'..='\u{28ff}' => "*",
+        _ => return None,
+    };
+    Some(ascii)
+}
@@ -0,0 +1,254 @@
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use crate::extract::ExportFormat;
+use crate::formats::DatasetFormat;
+use crate::processors::SegmentMode;
+use crate::report::ReportFormat;
+use crate::tabular::TableFormat;
+
+/// Command line interface. Without a subcommand the TUI is started.
+#[derive(Debug, Parser)]
+#[command(name = "anydataset-tui", version, about = "Terminal UI client for AnyDataNext")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Workspace to use (history, output directories); created on first use
+    #[arg(long, short = 'w', global = true)]
+    pub workspace: Option<String>,
+    /// Inject latency, 429/500 answers and dropped connections into backend requests
+    /// (tuned in `[chaos]` of config.toml)
+    #[arg(long)]
+    pub chaos: bool,
+    /// Redraw less and refresh slower while idle, for SSH/mosh links with high latency
+    /// (Ctrl+L toggles it in the TUI)
+    #[arg(long)]
+    pub remote_shell: bool,
+    /// Record the session to this file in the asciicast format (`asciinema play` replays it)
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<std::path::PathBuf>,
+    /// Queue the jobs of a job file (jobs.yaml) and process them locally one after another
+    #[arg(long, value_name = "FILE")]
+    pub jobs: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Download the latest release and replace the current binary
+    SelfUpdate,
+    /// Show the state (active job, recent jobs, log) of the running instance
+    Attach,
+    /// Share settings between machines
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Show or refresh local and team-shared presets
+    Presets {
+        #[command(subcommand)]
+        action: PresetsAction,
+    },
+    /// Show captured prompts and raw responses of a job
+    Audit {
+        job_id: String,
+        /// Print the full exchange behind a dataset record (its `audit_id`)
+        #[arg(long)]
+        record: Option<String>,
+    },
+    /// Show how a record of a multi-stage pipeline was derived
+    Lineage {
+        /// `record_id` from the record's metadata
+        record: String,
+        /// Datasets of all pipeline stages to search
+        #[arg(required = true)]
+        datasets: Vec<PathBuf>,
+    },
+    /// Turn a recorded run into anonymized test fixtures: its model calls (from the `[audit]`
+    /// archive), input files and dataset
+    ExportFixture {
+        job_id: String,
+        /// Directory name of the fixture (default: <processing type>-<job id>)
+        #[arg(long)]
+        name: Option<String>,
+        /// Dataset the run produced (default: where the run wrote it)
+        #[arg(long)]
+        dataset: Option<PathBuf>,
+        /// Text to hide, e.g. a person's or client's name; can be given several times
+        #[arg(long = "redact")]
+        redact: Vec<String>,
+        #[arg(long, default_value = crate::fixture::DEFAULT_DIR)]
+        dir: PathBuf,
+    },
+    /// Process the jobs of a job file (jobs.yaml) locally, with a progress table
+    Run {
+        file: PathBuf,
+        /// Jobs processed at once (default: `parallel` of the job file)
+        #[arg(long)]
+        parallel: Option<usize>,
+    },
+    /// Summarize a document locally: chunk summaries, summaries of those and a final abstract
+    Summarize {
+        file: PathBuf,
+        /// Where to write all summaries as records (default: <file>.summary.json)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Split a document into structural segments, one record each
+    Segment {
+        file: PathBuf,
+        #[arg(long, value_enum)]
+        mode: SegmentMode,
+        /// Where to write the records (default: <file>.<mode>.json)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Generate question-answer pairs from a document, each backed by a quote found in the text
+    Qa {
+        file: PathBuf,
+        /// Where to write the records (default: <file>.qa.json)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Generate synthetic question-answer records for topics, without input files (`[synthetic]`)
+    Generate {
+        /// Topics to generate records for
+        topics: Vec<String>,
+        /// File of topics: one per line, or a YAML/JSON list of topics with template variables
+        #[arg(long)]
+        seeds: Option<PathBuf>,
+        /// Records per topic (default: `records_per_topic` in `[synthetic]`)
+        #[arg(long)]
+        count: Option<usize>,
+        /// Prompt template from `[templates]` used instead of `template` in `[synthetic]`
+        #[arg(long)]
+        template: Option<String>,
+        /// Where to write the records (default: <seeds>.synthetic.json, or synthetic.json)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Extract records of a user-defined schema (`[schemas.<name>]`) from a document
+    Extract {
+        file: PathBuf,
+        /// Schema name (default: `schema` in `[extract]`)
+        #[arg(long)]
+        schema: Option<String>,
+        /// Also write the valid records as typed rows: <output>.jsonl or <output>.parquet
+        #[arg(long, value_enum)]
+        export: Option<ExportFormat>,
+        /// Where to write the records (default: <file>.extract.json)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Label the records of a QA dataset with difficulty and topic category (`[qa.labels]`)
+    Label {
+        dataset: PathBuf,
+        /// Where to write the labeled dataset (default: update the dataset in place)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Export judged records as preference pairs (prompt, chosen, rejected) for DPO/ORPO training
+    ExportPreferences {
+        dataset: PathBuf,
+        /// Where to write the JSONL file (default: <dataset>.dpo.jsonl)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Export a dataset as a CSV or Parquet table with a preset's column mapping
+    ExportTable {
+        dataset: PathBuf,
+        /// Preset whose `columns` to use (default: the dataset's processing type)
+        #[arg(long)]
+        preset: Option<String>,
+        #[arg(long, value_enum, default_value_t = TableFormat::Csv)]
+        format: TableFormat,
+        /// Where to write the table (default: <dataset>.csv or <dataset>.parquet)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Convert a dataset to the Alpaca or ShareGPT layout of fine-tuning tools
+    Convert {
+        dataset: PathBuf,
+        #[arg(long, value_enum, default_value_t = DatasetFormat::Alpaca)]
+        format: DatasetFormat,
+        /// System prompt added to every entry
+        #[arg(long)]
+        system_prompt: Option<String>,
+        /// Where to write the converted dataset (default: <dataset>.<format>.json)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Run the `[checks]` verification passes over a dataset and flag problem records
+    Check {
+        dataset: PathBuf,
+        /// Where to write the flagged dataset (default: update the dataset in place)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Preview what the `[cleanup]` filters remove from a document
+    Clean {
+        file: PathBuf,
+        /// Write the cleaned text here
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Export job history and usage statistics
+    Report {
+        #[arg(long, value_enum, default_value = "csv")]
+        format: ReportFormat,
+        /// Only jobs submitted in this month (YYYY-MM)
+        #[arg(long)]
+        month: Option<String>,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Check that datasets and their manifests weren't modified since they were signed
+    Verify {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Public key the files must be signed with (base64, from `signing-key`)
+        #[arg(long)]
+        key: Option<String>,
+    },
+    /// Print the public key exported files are signed with, for the people checking them
+    SigningKey,
+    /// Check files before processing: empty, binary or badly encoded files, repeated
+    /// content and the language mix
+    Preflight {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Print an encrypted result file as plaintext, with the workspace's key
+    Decrypt {
+        file: PathBuf,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigAction {
+    /// Write a portable bundle of the configuration, without secrets
+    Export {
+        file: PathBuf,
+    },
+    /// Merge a bundle into the local configuration, asking about conflicting settings
+    Import {
+        file: PathBuf,
+        /// Take the bundled value for every conflict
+        #[arg(long, conflicts_with = "keep_local")]
+        yes: bool,
+        /// Keep the local value for every conflict
+        #[arg(long)]
+        keep_local: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PresetsAction {
+    /// List presets and prompt templates with where they come from
+    List,
+    /// Fetch the shared presets now
+    Sync,
+}
@@ -0,0 +1,80 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::history::HistoryEntry;
+use crate::log_debug;
+
+/// State of a running instance as seen by an attached client
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub pid: u32,
+    pub started_at: Option<DateTime<Utc>>,
+    pub screen: String,
+    pub current_job_id: Option<String>,
+    pub job_status: Option<String>,
+    pub job_progress: Option<(u64, u64)>,
+    pub recent_jobs: Vec<HistoryEntry>,
+    pub log: Vec<String>,
+}
+
+/// Snapshot the UI thread refreshes on every tick and the server hands out
+pub type SharedSnapshot = Arc<Mutex<StateSnapshot>>;
+
+/// Local control server: accepts one-line requests on 127.0.0.1 and answers with
+/// one JSON line. Every request must start with the instance token.
+/// Returns the port it listens on.
+pub fn serve(snapshot: SharedSnapshot, token: String) -> Result<u16> {
+    let listener = TcpListener::bind("127.0.0.1:0").context("Failed to start control server")?;
+    let port = listener.local_addr()?.port();
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(err) = handle_client(stream, &snapshot, &token) {
+                log_debug!("Control client error: {}", err);
+            }
+        }
+    });
+    Ok(port)
+}
+
+fn handle_client(stream: TcpStream, snapshot: &SharedSnapshot, token: &str) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let mut parts = line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some(t), _) if t != token => serde_json::json!({ "error": "invalid token" }),
+        (_, Some("status")) => {
+            let snapshot = snapshot.lock().map_err(|_| anyhow::anyhow!("Snapshot lock poisoned"))?.clone();
+            serde_json::to_value(snapshot)?
+        },
+        (_, command) => serde_json::json!({ "error": format!("unknown command {:?}", command.unwrap_or("")) }),
+    };
+
+    let mut stream = stream;
+    writeln!(stream, "{}", response)?;
+    Ok(())
+}
+
+/// Asks the instance listening on `port` for its state
+pub fn fetch_status(port: u16, token: &str) -> Result<StateSnapshot> {
+    let mut stream = TcpStream::connect(("127.0.0.1", port))
+        .context("Cannot connect to the running instance")?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    writeln!(stream, "{} status", token)?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    let value: serde_json::Value = serde_json::from_str(&line).context("Invalid response from instance")?;
+    if let Some(error) = value.get("error").and_then(|e| e.as_str()) {
+        anyhow::bail!("Instance refused request: {}", error);
+    }
+    serde_json::from_value(value).context("Invalid state snapshot")
+}
@@ -0,0 +1,78 @@
+use crate::api::{Dataset, DatasetInfo};
+
+/// Server-side datasets on the Datasets screen
+#[derive(Debug, Clone, Default)]
+pub struct DatasetBrowser {
+    pub items: Vec<DatasetInfo>,
+    pub selected: usize,
+    /// Details of the selected dataset, once fetched
+    pub preview: Option<Dataset>,
+    pub loading: bool,
+    /// The list was fetched at least once
+    pub loaded: bool,
+    /// Why the list couldn't be fetched
+    pub error: Option<String>,
+}
+
+impl DatasetBrowser {
+    pub fn current(&self) -> Option<&DatasetInfo> {
+        self.items.get(self.selected)
+    }
+
+    pub fn next(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + 1) % self.items.len();
+            self.preview = None;
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+            self.preview = None;
+        }
+    }
+
+    /// New listing, newest first; the selection stays on the same dataset when it's still there
+    pub fn listed(&mut self, mut items: Vec<DatasetInfo>) {
+        let selected = self.current().map(|d| d.id.clone());
+        items.sort_by_key(|d| std::cmp::Reverse(d.created_at));
+        self.selected = selected.as_ref()
+            .and_then(|id| items.iter().position(|d| &d.id == id))
+            .unwrap_or(0);
+        if items.get(self.selected).map(|d| &d.id) != selected.as_ref() {
+            self.preview = None;
+        }
+        self.items = items;
+        self.loading = false;
+        self.loaded = true;
+        self.error = None;
+    }
+
+    pub fn failed(&mut self, error: String) {
+        self.loading = false;
+        self.loaded = true;
+        self.error = Some(error);
+    }
+
+    /// Shows the details unless the selection moved on while they were fetched
+    pub fn preview_loaded(&mut self, dataset: Dataset) {
+        if self.current().is_some_and(|d| d.id == dataset.info.id) {
+            self.preview = Some(dataset);
+        }
+    }
+}
+
+/// Local file name for a downloaded dataset: its name made safe for the filesystem,
+/// `.json` when the name has no extension
+pub fn file_name(info: &DatasetInfo) -> String {
+    let name: String = info.label().chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    let name = name.trim_start_matches('.');
+    match name {
+        "" => format!("{}.json", info.id),
+        name if name.contains('.') => name.to_string(),
+        name => format!("{}.json", name),
+    }
+}
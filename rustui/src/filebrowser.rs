@@ -0,0 +1,174 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Directory or file listed in the browser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl Entry {
+    pub fn is_hidden(&self) -> bool {
+        self.name.starts_with('.')
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Name,
+    /// Largest first
+    Size,
+    /// Newest first
+    Modified,
+}
+
+impl SortOrder {
+    pub fn next(self) -> Self {
+        match self {
+            SortOrder::Name => SortOrder::Size,
+            SortOrder::Size => SortOrder::Modified,
+            SortOrder::Modified => SortOrder::Name,
+        }
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortOrder::Name => write!(f, "name"),
+            SortOrder::Size => write!(f, "size"),
+            SortOrder::Modified => write!(f, "modified"),
+        }
+    }
+}
+
+/// File picker over the local filesystem. Directories are listed before files in every sort order.
+#[derive(Debug, Clone)]
+pub struct FileBrowser {
+    pub dir: PathBuf,
+    pub entries: Vec<Entry>,
+    pub selected: usize,
+    pub show_hidden: bool,
+    pub sort: SortOrder,
+    /// Why the directory couldn't be listed
+    pub error: Option<String>,
+}
+
+impl FileBrowser {
+    pub fn open(dir: &Path) -> Self {
+        let mut browser = Self {
+            dir: dir.to_path_buf(),
+            entries: Vec::new(),
+            selected: 0,
+            show_hidden: false,
+            sort: SortOrder::default(),
+            error: None,
+        };
+        browser.refresh();
+        browser
+    }
+
+    /// Re-reads the directory, keeping the selection on the same entry when it's still there
+    pub fn refresh(&mut self) {
+        let selected = self.current().map(|e| e.path.clone());
+        match list(&self.dir) {
+            Ok(entries) => {
+                self.entries = entries;
+                self.error = None;
+            },
+            Err(err) => {
+                self.entries.clear();
+                self.error = Some(err.to_string());
+            },
+        }
+        if !self.show_hidden {
+            self.entries.retain(|e| !e.is_hidden());
+        }
+        let sort = self.sort;
+        self.entries.sort_by(|a, b| {
+            b.is_dir.cmp(&a.is_dir).then_with(|| match sort {
+                SortOrder::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                SortOrder::Size => b.size.cmp(&a.size),
+                SortOrder::Modified => b.modified.cmp(&a.modified),
+            })
+        });
+        self.selected = selected
+            .and_then(|path| self.entries.iter().position(|e| e.path == path))
+            .unwrap_or(0);
+    }
+
+    pub fn current(&self) -> Option<&Entry> {
+        self.entries.get(self.selected)
+    }
+
+    pub fn next(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected = (self.selected + 1) % self.entries.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected = (self.selected + self.entries.len() - 1) % self.entries.len();
+        }
+    }
+
+    /// Enters the selected directory, or returns the selected file
+    pub fn enter(&mut self) -> Option<PathBuf> {
+        let entry = self.current()?.clone();
+        if !entry.is_dir {
+            return Some(entry.path);
+        }
+        self.dir = entry.path;
+        self.selected = 0;
+        self.refresh();
+        None
+    }
+
+    /// Goes to the parent directory with the directory just left selected
+    pub fn parent(&mut self) {
+        let Some(parent) = self.dir.parent().map(Path::to_path_buf) else {
+            return;
+        };
+        let left = std::mem::replace(&mut self.dir, parent);
+        self.refresh();
+        if let Some(index) = self.entries.iter().position(|e| e.path == left) {
+            self.selected = index;
+        }
+    }
+
+    pub fn toggle_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        self.refresh();
+    }
+
+    pub fn cycle_sort(&mut self) {
+        self.sort = self.sort.next();
+        self.refresh();
+    }
+}
+
+fn list(dir: &Path) -> std::io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    for item in std::fs::read_dir(dir)? {
+        let item = item?;
+        // Broken symlinks and entries that vanished since the listing are skipped
+        let Ok(metadata) = std::fs::metadata(item.path()) else {
+            continue;
+        };
+        entries.push(Entry {
+            name: item.file_name().to_string_lossy().into_owned(),
+            path: item.path(),
+            is_dir: metadata.is_dir(),
+            size: if metadata.is_dir() { 0 } else { metadata.len() },
+            modified: metadata.modified().ok(),
+        });
+    }
+    Ok(entries)
+}
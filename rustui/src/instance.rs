@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::atomic;
+use crate::config::get_config_dir;
+use crate::control::{self, SharedSnapshot};
+use crate::lock::FileLock;
+
+/// Written by the primary instance so others can find its control server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceInfo {
+    pub pid: u32,
+    pub port: u16,
+    pub token: String,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Held by the primary instance for its whole lifetime
+pub struct InstanceGuard {
+    _lock: FileLock,
+    info_path: PathBuf,
+}
+
+impl Drop for InstanceGuard {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.info_path);
+    }
+}
+
+pub enum Claim {
+    /// No other instance: this one owns the session files and runs the control server
+    Primary(InstanceGuard),
+    /// Another instance holds the lock; `info` is `None` if it has no control server
+    Running { info: Option<InstanceInfo>, reason: String },
+}
+
+/// Tries to become the primary instance for the default config directory
+pub fn claim(snapshot: SharedSnapshot) -> Result<Claim> {
+    claim_in(&get_config_dir()?, snapshot)
+}
+
+pub fn claim_in(dir: &Path, snapshot: SharedSnapshot) -> Result<Claim> {
+    let lock = match FileLock::acquire_with_timeout(&dir.join("instance"), Duration::ZERO) {
+        Ok(lock) => lock,
+        Err(err) => {
+            return Ok(Claim::Running { info: read_info(dir), reason: err.to_string() });
+        },
+    };
+
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let port = control::serve(snapshot, token.clone())?;
+    let info = InstanceInfo { pid: std::process::id(), port, token, started_at: Utc::now() };
+    let info_path = dir.join("instance.json");
+    // The token lets anyone read the session state, keep it private
+    atomic::write_private(&info_path, serde_json::to_vec_pretty(&info)?)
+        .context("Failed to write instance file")?;
+
+    Ok(Claim::Primary(InstanceGuard { _lock: lock, info_path }))
+}
+
+/// Info of the running instance, if any
+pub fn running() -> Result<Option<InstanceInfo>> {
+    let dir = get_config_dir()?;
+    match FileLock::acquire_with_timeout(&dir.join("instance"), Duration::ZERO) {
+        // Nobody holds the lock, so any instance file is left over from a crash
+        Ok(_) => Ok(None),
+        Err(_) => Ok(read_info(&dir)),
+    }
+}
+
+fn read_info(dir: &Path) -> Option<InstanceInfo> {
+    let content = std::fs::read_to_string(dir.join("instance.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
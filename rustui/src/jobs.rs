@@ -0,0 +1,70 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::api::JobStatus;
+
+/// Statuses the Jobs screen can filter by, in the order 'f' cycles through them
+pub const STATUS_FILTERS: [&str; 5] = ["queued", "processing", "completed", "failed", "cancelled"];
+
+/// Filter after `current`; all jobs again after the last status
+pub fn next_status_filter(current: Option<&str>) -> Option<String> {
+    let next = match current {
+        None => 0,
+        Some(status) => STATUS_FILTERS.iter().position(|s| *s == status).map_or(0, |i| i + 1),
+    };
+    STATUS_FILTERS.get(next).map(|s| s.to_string())
+}
+
+/// Column the current page of the Jobs screen is sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JobSort {
+    /// Newest first; jobs without a creation time last
+    #[default]
+    Created,
+    Status,
+    /// Least done first
+    Progress,
+    Id,
+}
+
+impl JobSort {
+    pub fn next(self) -> Self {
+        match self {
+            JobSort::Created => JobSort::Status,
+            JobSort::Status => JobSort::Progress,
+            JobSort::Progress => JobSort::Id,
+            JobSort::Id => JobSort::Created,
+        }
+    }
+
+    pub fn compare(&self, a: &JobStatus, b: &JobStatus) -> Ordering {
+        match self {
+            JobSort::Created => match (a.created_at, b.created_at) {
+                (Some(a), Some(b)) => b.cmp(&a),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            },
+            JobSort::Status => a.status.cmp(&b.status),
+            JobSort::Progress => progress(a).partial_cmp(&progress(b)).unwrap_or(Ordering::Equal),
+            JobSort::Id => a.job_id.cmp(&b.job_id),
+        }
+    }
+}
+
+impl fmt::Display for JobSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobSort::Created => write!(f, "created"),
+            JobSort::Status => write!(f, "status"),
+            JobSort::Progress => write!(f, "progress"),
+            JobSort::Id => write!(f, "id"),
+        }
+    }
+}
+
+/// Share of the job done, 0.0-1.0; jobs that don't report progress count as not started
+pub fn progress(job: &JobStatus) -> f64 {
+    match (job.current, job.total) {
+        (Some(current), Some(total)) if total > 0 => current as f64 / total as f64,
+        _ => 0.0,
+    }
+}
@@ -0,0 +1,89 @@
+use crossterm::{cursor::MoveUp, queue, terminal::{Clear, ClearType}};
+use std::io::{self, Write};
+
+use crate::jobfile::{JobState, ResolvedJob};
+
+/// Progress of `run` as a table with a row per job. On a terminal the table is redrawn in
+/// place; elsewhere (pipes, CI logs) every change is printed as a line of its own.
+#[derive(Debug)]
+pub struct ProgressTable {
+    rows: Vec<(String, String, usize, String)>,
+    states: Vec<JobState>,
+    /// Terminal width rows are cut to, when redrawing in place
+    width: Option<usize>,
+    drawn: usize,
+}
+
+impl ProgressTable {
+    pub fn new(jobs: &[ResolvedJob], width: Option<usize>) -> Self {
+        Self {
+            rows: jobs.iter()
+                .map(|job| (job.name.clone(), job.processing_type.clone(), job.files.len(), job.output.display().to_string()))
+                .collect(),
+            states: vec![JobState::Queued; jobs.len()],
+            width,
+            drawn: 0,
+        }
+    }
+
+    pub fn update(&mut self, index: usize, state: &JobState, out: &mut impl Write) -> io::Result<()> {
+        let Some(current) = self.states.get_mut(index) else {
+            return Ok(());
+        };
+        *current = state.clone();
+        if self.width.is_some() {
+            return self.redraw(out);
+        }
+        let (name, ..) = &self.rows[index];
+        writeln!(out, "{}: {}", name, status(state))
+    }
+
+    /// Draws the table over the one drawn before
+    pub fn redraw(&mut self, out: &mut impl Write) -> io::Result<()> {
+        if self.drawn > 0 {
+            queue!(out, MoveUp(self.drawn as u16), Clear(ClearType::FromCursorDown))?;
+        }
+        let lines = self.lines();
+        for line in &lines {
+            match self.width {
+                Some(width) => writeln!(out, "{}", line.chars().take(width.saturating_sub(1)).collect::<String>())?,
+                None => writeln!(out, "{}", line)?,
+            }
+        }
+        self.drawn = lines.len();
+        out.flush()
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let header = ["JOB", "TYPE", "FILES", "OUTPUT", "STATUS"].map(str::to_string);
+        let rows: Vec<[String; 5]> = self.rows.iter().zip(&self.states)
+            .map(|((name, processing_type, files, output), state)| {
+                [name.clone(), processing_type.clone(), files.to_string(), output.clone(), status(state)]
+            })
+            .collect();
+        let mut widths = header.clone().map(|cell| cell.chars().count());
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        std::iter::once(&header).chain(&rows)
+            .map(|row| {
+                let cells: Vec<String> = row.iter().zip(widths)
+                    .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                    .collect();
+                cells.join("  ").trim_end().to_string()
+            })
+            .collect()
+    }
+}
+
+pub fn status(state: &JobState) -> String {
+    match state {
+        JobState::Queued => "queued".to_string(),
+        JobState::Running { done, total } => format!("running {}/{}", done, total),
+        JobState::Finished { records, failed_files: 0 } => format!("done, {} record(s)", records),
+        JobState::Finished { records, failed_files } => format!("done, {} record(s), {} file(s) failed", records, failed_files),
+        JobState::Failed(error) => format!("failed: {}", error),
+    }
+}
@@ -0,0 +1,92 @@
+use tui_input::{Input, InputRequest};
+
+/// Adds every comma- or newline-separated keyword of `text`, trimmed; duplicates are skipped.
+/// Returns how many were added.
+pub fn add(keywords: &mut Vec<String>, text: &str) -> usize {
+    let mut added = 0;
+    for keyword in text.split([',', '\n']).map(str::trim).filter(|k| !k.is_empty()) {
+        if !keywords.iter().any(|k| k == keyword) {
+            keywords.push(keyword.to_string());
+            added += 1;
+        }
+    }
+    added
+}
+
+/// Tag-style keyword input of the Process screen. Keywords are shown as chips; the text being
+/// typed becomes a chip on Enter or a comma.
+#[derive(Debug, Clone, Default)]
+pub struct KeywordEditor {
+    pub input: Input,
+    /// Chip picked with Left/Right for Delete; None while typing
+    pub selected: Option<usize>,
+}
+
+impl KeywordEditor {
+    pub fn insert(&mut self, keywords: &mut Vec<String>, c: char) {
+        self.selected = None;
+        if c == ',' {
+            self.commit(keywords);
+        } else {
+            self.input.handle(InputRequest::InsertChar(c));
+        }
+    }
+
+    /// Turns the typed text into chips
+    pub fn commit(&mut self, keywords: &mut Vec<String>) {
+        add(keywords, self.input.value());
+        self.input.reset();
+    }
+
+    /// Pasted text is split on commas and newlines; a piece without a separator after it
+    /// stays in the input for further typing
+    pub fn paste(&mut self, keywords: &mut Vec<String>, text: &str) {
+        self.selected = None;
+        let text = format!("{}{}", self.input.value(), text);
+        let (complete, rest) = text.rsplit_once([',', '\n']).unwrap_or(("", &text));
+        add(keywords, complete);
+        self.input = Input::new(rest.trim_start().to_string());
+    }
+
+    /// Deletes a typed character; with nothing typed, removes the picked or the last chip
+    pub fn backspace(&mut self, keywords: &mut Vec<String>) {
+        if !self.input.value().is_empty() {
+            self.input.handle(InputRequest::DeletePrevChar);
+        } else if self.selected.is_some() {
+            self.delete(keywords);
+        } else {
+            keywords.pop();
+        }
+    }
+
+    /// Removes the picked chip
+    pub fn delete(&mut self, keywords: &mut Vec<String>) {
+        let Some(index) = self.selected.filter(|&i| i < keywords.len()) else {
+            return;
+        };
+        keywords.remove(index);
+        self.selected = match keywords.len() {
+            0 => None,
+            len => Some(index.min(len - 1)),
+        };
+    }
+
+    pub fn select_previous(&mut self, keywords: &[String]) {
+        if keywords.is_empty() || !self.input.value().is_empty() {
+            return;
+        }
+        self.selected = Some(match self.selected {
+            Some(i) if i > 0 => i - 1,
+            Some(i) => i,
+            None => keywords.len() - 1,
+        });
+    }
+
+    pub fn select_next(&mut self, keywords: &[String]) {
+        self.selected = match self.selected {
+            Some(i) if i + 1 < keywords.len() => Some(i + 1),
+            // Past the last chip is the input again
+            _ => None,
+        };
+    }
+}
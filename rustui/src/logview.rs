@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+
+use crate::logger::{self, LogEntry, LogLevel, RING_CAPACITY};
+
+/// Logs screen: the latest entries of the logger's ring buffer, following new ones as they
+/// come unless paused or scrolled up
+#[derive(Debug, Clone)]
+pub struct LogView {
+    entries: VecDeque<LogEntry>,
+    /// Last entry fetched from the logger; cleared entries stay gone
+    seen: u64,
+    /// No new entries are fetched; they wait in the logger until resumed
+    pub paused: bool,
+    /// Keep the newest entry in view
+    pub follow: bool,
+    /// Lines scrolled up from the newest shown entry
+    pub offset: usize,
+    /// Entries below this level are hidden
+    pub min_level: LogLevel,
+}
+
+impl Default for LogView {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            seen: 0,
+            paused: false,
+            follow: true,
+            offset: 0,
+            min_level: LogLevel::DEBUG,
+        }
+    }
+}
+
+impl LogView {
+    /// Fetches the entries logged since the last call; true when there were any
+    pub fn poll(&mut self) -> bool {
+        if self.paused {
+            return false;
+        }
+        self.add(logger::entries_since(self.seen))
+    }
+
+    /// Appends entries fetched from the logger
+    pub fn add(&mut self, new: Vec<LogEntry>) -> bool {
+        let Some(last) = new.last() else {
+            return false;
+        };
+        self.seen = last.seq;
+        // Scrolled up, the same lines stay in view as new ones arrive below
+        if !self.follow {
+            self.offset += new.iter().filter(|entry| entry.level >= self.min_level).count();
+        }
+        self.entries.extend(new);
+        while self.entries.len() > RING_CAPACITY {
+            self.entries.pop_front();
+        }
+        true
+    }
+
+    /// Entries at or above the minimum level, oldest first
+    pub fn shown(&self) -> Vec<&LogEntry> {
+        self.entries.iter().filter(|entry| entry.level >= self.min_level).collect()
+    }
+
+    /// Entries filling `height` lines at the current scroll position
+    pub fn window(&self, height: usize) -> Vec<&LogEntry> {
+        let shown = self.shown();
+        let end = shown.len().saturating_sub(self.offset);
+        shown[end.saturating_sub(height)..end].to_vec()
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        let max = self.shown().len().saturating_sub(1);
+        self.offset = (self.offset + lines).min(max);
+        self.follow = self.offset == 0;
+    }
+
+    /// Scrolling back to the newest entry follows again
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.offset = self.offset.saturating_sub(lines);
+        self.follow = self.offset == 0;
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.scroll_up(usize::MAX / 2);
+    }
+
+    pub fn follow_tail(&mut self) {
+        self.offset = 0;
+        self.follow = true;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.poll();
+    }
+
+    /// Shows one level more severe, wrapping back to everything after FATAL
+    pub fn cycle_level(&mut self) {
+        let index = LogLevel::ALL.iter().position(|level| *level == self.min_level).unwrap_or(0);
+        self.min_level = LogLevel::ALL[(index + 1) % LogLevel::ALL.len()];
+        self.follow_tail();
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.follow_tail();
+    }
+
+    /// Screen title describing the mode
+    pub fn status(&self) -> String {
+        let mode = if self.paused {
+            "paused".to_string()
+        } else if self.follow {
+            "following".to_string()
+        } else {
+            format!("{} line(s) up", self.offset)
+        };
+        let count = self.shown().len();
+        format!("{} entr{}, {}, level {}+", count, if count == 1 { "y" } else { "ies" }, mode, self.min_level.as_str())
+    }
+}
@@ -1,10 +1,14 @@
 use anyhow::{Context, Result};
+use clap::Parser;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{
+        self, DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste, EnableFocusChange,
+        EnableMouseCapture, Event, KeyEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ratatui::{prelude::*, widgets::*};
+use ratatui::prelude::*;
 use std::{
     io,
     time::{Duration, Instant}, 
@@ -12,37 +16,62 @@ use std::{
 };
 
 mod app;
+mod assembly;
 mod ui;
-mod api;
-mod config;
-mod processors;
-mod logger;
-mod error;
+mod cli;
+mod update;
+mod version;
+mod tutorial;
+mod tuning;
+mod keywords;
+mod pacing;
+mod recording;
+mod sampling;
+mod jobs;
+mod jobtable;
+mod logview;
+mod background;
+mod capabilities;
+mod results;
+mod stats;
+mod usage;
+mod datasets;
+mod filebrowser;
+mod control;
+mod instance;
+mod worker;
+mod theme;
+#[cfg(test)]
 mod tests;
 
-#[macro_use]
-extern crate lazy_static;
+// Rdzeń (klient API, procesory, formaty, konfiguracja) jest w bibliotece anydataset-core
+use anydataset_core::{
+    api, config, processors, checks, chunking, cleanup, docmeta, transcript, history, templating, output,
+    hooks, manifest, network, git, webhooks, pubsub, report, summarize, discovery, diskspace, latency,
+    formats, atomic, archive, audit, lock, credentials, jobfile, labeling, legal, logger, notifications,
+    ollama, lineage, paper, qa, bestof, catalog, extract, fixture, tabular, workspace, bundle,
+    preference, preflight, preview, preset, preset_sync, providers, chaos, crashsafe, partial, quota, retry,
+    ranged, storage, synthetic, vault, signing,
+};
 
 // Reeksportujemy makra logowania, aby były dostępne w całym projekcie
-pub use crate::logger::{debug, info, warn, error, fatal, LogLevel, set_console_level, set_file_level};
-#[macro_use] 
-pub use crate::log_debug;
-#[macro_use]
-pub use crate::log_info;
-#[macro_use]
-pub use crate::log_warn;
-#[macro_use]
-pub use crate::log_error;
-#[macro_use]
-pub use crate::log_fatal;
-
-use app::{App, AppState};
+pub use anydataset_core::logger::{debug, info, warn, error, fatal, LogLevel, set_console_level, set_file_level};
+pub use anydataset_core::{log_debug, log_info, log_warn, log_error, log_fatal, processing_error};
+
+use app::App;
+use background::BackgroundEvent;
 use ui::ui;
 
 // Wersja aplikacji z pliku Cargo.toml
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 fn main() -> Result<()> {
+    // Komendy CLI wykonujemy bez uruchamiania interfejsu terminalowego
+    let cli = cli::Cli::parse();
+    if let Some(command) = cli.command {
+        return run_command(command, cli.workspace);
+    }
+
     // Inicjalizacja niestandardowego handlera paniki
     setup_panic_handler();
     
@@ -55,7 +84,7 @@ fn main() -> Result<()> {
     log_info!("Working directory: {:?}", std::env::current_dir().unwrap_or_default());
     
     // Wczytaj konfigurację
-    let config = match config::Config::load() {
+    let mut config = match config::Config::load() {
         Ok(cfg) => {
             log_info!("Configuration loaded successfully");
             cfg
@@ -65,20 +94,92 @@ fn main() -> Result<()> {
             config::Config::default()
         }
     };
+    if let Some(name) = cli.workspace {
+        workspace::validate_name(&name)?;
+        config.overrides.workspace = Some(name);
+    }
+    log_info!("Using workspace {}", config.active_workspace());
+    config.overrides.remote_shell = cli.remote_shell;
+    config.overrides.chaos = cli.chaos;
+    if config.chaos_settings().enabled {
+        log_warn!("Chaos mode: backend requests are delayed, fail and drop on purpose");
+    }
+    if let Err(err) = network::configure(&config.http) {
+        log_warn!("HTTP settings not applied: {:#}", err);
+    }
+    
+    // Tylko jedna instancja zarządza plikami sesji; kolejna może się do niej podłączyć
+    let snapshot = control::SharedSnapshot::default();
+    let instance = match instance::claim(snapshot.clone()) {
+        Ok(instance::Claim::Primary(guard)) => Some(guard),
+        Ok(instance::Claim::Running { info, reason }) => {
+            log_warn!("{}", reason);
+            if !prompt_running_instance(info.as_ref())? {
+                return Ok(());
+            }
+            None
+        },
+        Err(err) => {
+            log_warn!("Single-instance check failed: {}", err);
+            None
+        }
+    };
     
     // Setup terminala
     log_debug!("Setting up terminal");
     enable_raw_mode().context("Failed to enable raw mode")?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste, EnableFocusChange)?;
+    // Sesja jest nagrywana z tego, co trafia do terminala
+    let recorder = recording::Recorder::default();
+    let backend = CrosstermBackend::new(recording::Tee::new(stdout, recorder.clone()));
     let mut terminal = Terminal::new(backend)?;
 
     // Utwórz aplikację i uruchom ją
-    let tick_rate = Duration::from_millis(250);
-    let app = App::new(&config.backend_url);
+    let mut app = App::with_config(config.clone());
+    app.recorder = recorder.clone();
+    if let Some(path) = &cli.record {
+        match recorder.start(path) {
+            Ok(()) => log_info!("Recording the session to {}", path.display()),
+            Err(err) => log_warn!("Session not recorded: {:#}", err),
+        }
+    }
+    if instance.is_some() {
+        if let Ok(mut state) = snapshot.lock() {
+            state.pid = std::process::id();
+            state.started_at = Some(chrono::Utc::now());
+        }
+        app.shared_state = Some(snapshot);
+    }
+    app.current_user = config.effective_username();
+    match history::JobHistory::load_for(config.active_workspace()) {
+        Ok(history) => app.history = history,
+        Err(err) => log_warn!("Failed to load job history: {}", err),
+    }
+    match catalog::cache_path() {
+        Ok(path) => app.load_cached_models(path),
+        Err(err) => log_warn!("Model lists won't be cached: {}", err),
+    }
+    spawn_version_handshake(&config.backend_url, config.request_headers(), app.background_tx.clone());
+    app.start_ollama_discovery();
+    app.start_provider_discovery();
+    if config.pick_fastest_backend {
+        app.probe_backends();
+    }
+    if config.check_for_updates {
+        spawn_update_check(app.background_tx.clone());
+    }
+    if !config.tutorial_completed {
+        app.start_tutorial();
+        app.offer_discovered_backends = true;
+        app.discover_backends();
+    }
+    // Zadania z pliku startują dopiero po wczytaniu historii, do której trafiają
+    if let Some(path) = &cli.jobs {
+        app.load_job_file(path);
+    }
     log_info!("Starting application main loop");
-    let res = run_app(&mut terminal, app, tick_rate);
+    let res = run_app(&mut terminal, &mut app);
 
     // Przywracanie terminala
     log_debug!("Restoring terminal");
@@ -86,29 +187,695 @@ fn main() -> Result<()> {
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        DisableFocusChange
     )?;
     terminal.show_cursor()?;
 
+    match recorder.stop() {
+        Some(Ok(path)) => println!("Session recorded to {} (replay: asciinema play {})", path.display(), path.display()),
+        Some(Err(err)) => println!("Failed to save the session recording: {:#}", err),
+        None => {},
+    }
     if let Err(err) = res {
         log_error!("Application error: {:?}", err);
         println!("Error: {}\nSee log file for details.", err);
     }
 
+    // Zapis dopisuje tylko ukończenie samouczka do tego, co jest teraz w config.toml
+    if app.tutorial_seen && !app.config.tutorial_completed {
+        app.config.tutorial_completed = true;
+        if let Err(err) = app.config.save() {
+            log_warn!("Failed to save configuration: {}", err);
+        }
+    }
+
     log_info!("Application terminated");
     Ok(())
 }
 
+fn run_command(command: cli::Command, workspace: Option<String>) -> Result<()> {
+    let runtime = tokio::runtime::Runtime::new().context("Failed to start async runtime")?;
+    // Komendy korzystają z tego samego, dostrojonego klienta HTTP co interfejs
+    if let Ok(config) = config::Config::load() {
+        network::configure(&config.http)?;
+    }
+    match command {
+        cli::Command::SelfUpdate => {
+            println!("Current version: {}", VERSION);
+            match runtime.block_on(update::self_update(VERSION))? {
+                Some(version) => println!("Updated to version {}", version),
+                None => println!("Already up to date"),
+            }
+        }
+        cli::Command::Attach => match instance::running()? {
+            Some(info) => print_snapshot(&control::fetch_status(info.port, &info.token)?),
+            None => println!("No running instance found"),
+        },
+        cli::Command::Config { action } => run_config_command(action)?,
+        cli::Command::Audit { job_id, record } => {
+            let config = config::Config::load()?;
+            let workspace = workspace.unwrap_or_else(|| config.active_workspace().to_string());
+            let path = audit::archive_path(&workspace::data_dir(&workspace)?, &job_id);
+            if !path.exists() {
+                anyhow::bail!("No audit archive for job {} in workspace {}", job_id, workspace);
+            }
+            let key = audit::load_key(&config.audit.key_path()?)?;
+            let exchanges = audit::read(&path, &key)?;
+            match record {
+                Some(id) => {
+                    let exchange = exchanges.iter().find(|e| e.id == id)
+                        .with_context(|| format!("No exchange {} in job {}", id, job_id))?;
+                    println!("Exchange {} ({} / {}, {})", exchange.id, exchange.provider, exchange.model, exchange.at.to_rfc3339());
+                    println!("Source file: {}", exchange.source_file);
+                    if let Some(system_prompt) = &exchange.system_prompt {
+                        println!("\n--- System prompt ---\n{}", system_prompt);
+                    }
+                    println!("\n--- Prompt ---\n{}", exchange.prompt);
+                    println!("\n--- Raw response ---\n{}", exchange.raw_response);
+                }
+                None => {
+                    for exchange in &exchanges {
+                        let preview: String = exchange.prompt.lines().next().unwrap_or_default().chars().take(60).collect();
+                        println!("{}  {}  {}  {}", exchange.id, exchange.at.format("%Y-%m-%d %H:%M:%S"), exchange.source_file, preview);
+                    }
+                    println!("{} exchange(s); use --record <audit_id> for details", exchanges.len());
+                }
+            }
+        }
+        cli::Command::Lineage { record, datasets } => {
+            let graph = lineage::LineageGraph::load(&datasets)?;
+            let lines = graph.render(&record)
+                .with_context(|| format!("Record {} not found in the given datasets", record))?;
+            for line in lines {
+                println!("{}", line);
+            }
+        }
+        cli::Command::ExportFixture { job_id, name, dataset, redact, dir } => {
+            let config = config::Config::load()?;
+            let workspace = workspace.unwrap_or_else(|| config.active_workspace().to_string());
+            let history = history::JobHistory::load_for(&workspace)?;
+            let entry = history.entries().iter().rev().find(|e| e.job_id == job_id)
+                .with_context(|| format!("No job {} in the history of workspace {}", job_id, workspace))?;
+            let archive = audit::archive_path(&workspace::data_dir(&workspace)?, &job_id);
+            let exchanges = if archive.exists() {
+                audit::read(&archive, &audit::load_key(&config.audit.key_path()?)?)?
+            } else {
+                Vec::new()
+            };
+            let dataset = match dataset {
+                Some(path) => Some(path),
+                None => {
+                    let mut ctx = templating::TemplateContext::from_source(std::path::Path::new(&entry.file), &config.default_language)
+                        .with_variables(&entry.variables);
+                    ctx.set("preset", entry.processing_type.as_str());
+                    ctx.set("job_id", entry.job_id.as_str());
+                    ctx.set("ext", "json");
+                    Some(output::resolve_output_path(&config.downloads_dir(), &config.output, &ctx)?).filter(|path| path.exists())
+                },
+            };
+            let vault = vault::Vault::open(&config.encryption, &workspace)?;
+            let dataset = dataset.map(|path| vault.read(&path)).transpose()?;
+            let name = name.unwrap_or_else(|| format!("{}-{}", entry.processing_type, entry.job_id));
+            let recording = fixture::Recording { entry, exchanges: &exchanges, dataset };
+            let mut anonymizer = fixture::Anonymizer::for_run(&config, entry, &redact);
+            let report = fixture::export(&recording, &config, &dir, &name, &mut anonymizer)?;
+            for file in &report.files {
+                println!("{}", file.display());
+            }
+            for note in &report.notes {
+                println!("Note: {}", note);
+            }
+            println!("\nFixture written to {}; review it for personal data before committing", report.dir.display());
+        }
+        cli::Command::Run { file, parallel } => {
+            let config = config::Config::load()?;
+            let workspace = workspace.unwrap_or_else(|| config.active_workspace().to_string());
+            let job_file = jobfile::JobFile::load(&file)?;
+            let shared = match &config.preset_sync {
+                Some(settings) => runtime.block_on(preset_sync::sync(settings))?,
+                None => preset_sync::SharedPresets::default(),
+            };
+            let base = file.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(std::path::Path::new("."));
+            let jobs = job_file.resolve(
+                base,
+                &preset::merge(config.workspace_presets(&workspace), &shared.collection.presets),
+                &preset::merge(&config.templates, &shared.collection.templates),
+            )?;
+            let vault = vault::Vault::open(&config.encryption, &workspace)?;
+            // Na terminalu tabela jest odświeżana w miejscu
+            let width = io::IsTerminal::is_terminal(&io::stdout())
+                .then(|| crossterm::terminal::size().map_or(80, |(width, _)| width as usize));
+            let table = std::sync::Mutex::new(jobtable::ProgressTable::new(&jobs, width));
+            if width.is_some() {
+                table.lock().unwrap_or_else(std::sync::PoisonError::into_inner).redraw(&mut io::stdout())?;
+            }
+            let states = jobfile::run_all(&jobs, &config, &vault, parallel.unwrap_or(job_file.parallel), &|index, state| {
+                let mut table = table.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+                let _ = table.update(index, state, &mut io::stdout());
+            });
+            if width.is_none() {
+                println!();
+                for line in table.lock().unwrap_or_else(std::sync::PoisonError::into_inner).lines() {
+                    println!("{}", line);
+                }
+            }
+            let failed = states.iter().filter(|state| state.is_failed()).count();
+            if failed > 0 {
+                anyhow::bail!("{} of {} job(s) failed", failed, jobs.len());
+            }
+            println!("{} job(s) finished", jobs.len());
+        }
+        cli::Command::Summarize { file, output } => {
+            use processors::Processor;
+            let config = config::Config::load()?;
+            let processor = summarize::MapReduceProcessor { settings: config.summarize.clone() };
+            let processor_config = local_processor_config(&config);
+            let path = file.to_string_lossy();
+            let mut result = crashsafe::parse(&path, || processor.process_file(&path, &processor_config))?;
+            docmeta::merge(&mut result.records, &docmeta::for_file(&file, &config.metadata));
+            let levels = result.records.iter()
+                .filter_map(|r| r.metadata.get(summarize::LEVEL)?.as_u64())
+                .max()
+                .unwrap_or(0);
+            let output = output.unwrap_or_else(|| file.with_extension("summary.json"));
+            let root = result.records.last().map(|r| r.completion.clone()).unwrap_or_default();
+            atomic::write(&output, serde_json::to_vec_pretty(&[result])?)?;
+            println!("{}\n\nSummaries over {} level(s) written to {}", root, levels + 1, output.display());
+        }
+        cli::Command::Segment { file, mode, output } => {
+            let config = config::Config::load()?;
+            let processor: Box<dyn processors::Processor> = match mode {
+                processors::SegmentMode::Legal => Box::new(legal::ClauseProcessor),
+                processors::SegmentMode::Paper => Box::new(paper::PaperProcessor { settings: config.paper.clone() }),
+            };
+            let path = file.to_string_lossy();
+            let mut result = crashsafe::parse(&path, || processor.process_file(&path, &local_processor_config(&config)))?;
+            lineage::tag(&mut result.records, &result.processing_type);
+            docmeta::merge(&mut result.records, &docmeta::for_file(&file, &config.metadata));
+            let outline = match mode {
+                processors::SegmentMode::Legal => legal::outline(&result.records),
+                processors::SegmentMode::Paper => paper::outline(&result.records),
+            };
+            for line in outline {
+                println!("{}", line);
+            }
+            let output = output.unwrap_or_else(|| file.with_extension(format!("{}.json", mode.processing_type())));
+            let count = result.records.len();
+            atomic::write(&output, serde_json::to_vec_pretty(&[result])?)?;
+            println!("\n{} segment(s) written to {}", count, output.display());
+        }
+        cli::Command::Qa { file, output } => {
+            let config = config::Config::load()?;
+            let processor = qa::QaProcessor { settings: config.qa.clone() };
+            let (mut result, dropped) = processor.generate(&file.to_string_lossy(), &local_processor_config(&config))?;
+            lineage::tag(&mut result.records, &result.processing_type);
+            docmeta::merge(&mut result.records, &docmeta::for_file(&file, &config.metadata));
+            let flagged = result.records.iter()
+                .filter(|r| r.metadata.get(qa::GROUNDED).and_then(|v| v.as_bool()) == Some(false))
+                .count();
+            let difficulty = labeling::counts(&result.records);
+            let output = output.unwrap_or_else(|| file.with_extension("qa.json"));
+            let count = result.records.len();
+            atomic::write(&output, serde_json::to_vec_pretty(&[result])?)?;
+            println!("{} question(s) written to {}", count, output.display());
+            // Pytania bez cytatu znalezionego we fragmencie
+            if dropped > 0 {
+                println!("{} dropped: quoted support not found in the text", dropped);
+            }
+            if flagged > 0 {
+                println!("{} flagged with \"grounded\": false", flagged);
+            }
+            if config.qa.labels.is_some() {
+                let counts: Vec<String> = difficulty.iter()
+                    .map(|(difficulty, count)| format!("{} {}", count, difficulty))
+                    .collect();
+                println!("Difficulty: {}", counts.join(", "));
+            }
+        }
+        cli::Command::Generate { topics, seeds, count, template, output } => {
+            let config = config::Config::load()?;
+            let mut settings = config.synthetic.clone();
+            if let Some(name) = template {
+                settings.template = config.templates.get(&name)
+                    .with_context(|| format!("Unknown template {}", name))?
+                    .clone();
+            }
+            let mut all: Vec<synthetic::Seed> = topics.into_iter().map(synthetic::Seed::new).collect();
+            if let Some(path) = &seeds {
+                all.extend(synthetic::load_seeds(path)?);
+            }
+            if let Some(count) = count {
+                for seed in &mut all {
+                    seed.count = Some(count);
+                }
+            }
+            let source = seeds.as_ref().map_or_else(|| "topics".to_string(), |path| path.to_string_lossy().into_owned());
+            let mut generation = synthetic::generate(&all, &settings, &local_processor_config(&config), &source)?;
+            let result = &mut generation.result;
+            lineage::tag(&mut result.records, &result.processing_type);
+            let output = output.unwrap_or_else(|| match &seeds {
+                Some(path) => path.with_extension("synthetic.json"),
+                None => PathBuf::from("synthetic.json"),
+            });
+            let count = result.records.len();
+            atomic::write(&output, serde_json::to_vec_pretty(&[&generation.result])?)?;
+            println!("{} record(s) for {} topic(s) written to {}", count, all.len(), output.display());
+            if generation.duplicates > 0 {
+                println!("{} dropped as duplicates", generation.duplicates);
+            }
+            // Tematy, dla których zabrakło prób na nowe rekordy
+            for (topic, records) in &generation.short {
+                println!("Only {} record(s) for \"{}\"", records, topic);
+            }
+        }
+        cli::Command::Label { dataset, output } => {
+            let config = config::Config::load()?;
+            // Bez sekcji [qa.labels] kategorie wybiera model
+            let settings = config.qa.labels.clone().unwrap_or_default();
+            let content = std::fs::read(&dataset)
+                .with_context(|| format!("Failed to read {}", dataset.display()))?;
+            let mut results: Vec<processors::ProcessingResult> = serde_json::from_slice(&content)
+                .with_context(|| format!("{} is not a dataset produced by a local run", dataset.display()))?;
+            lineage::ensure_ids(&mut results);
+            let mut labeled = 0;
+            for result in &mut results {
+                labeled += labeling::label(&mut result.records, &settings);
+            }
+            let records: Vec<processors::Record> = results.iter().flat_map(|r| r.records.clone()).collect();
+            for (difficulty, count) in labeling::counts(&records) {
+                println!("{:<8} {}", difficulty, count);
+            }
+            let output = output.unwrap_or(dataset);
+            atomic::write(&output, serde_json::to_vec_pretty(&results)?)?;
+            println!("\n{} of {} record(s) labeled, written to {}", labeled, records.len(), output.display());
+        }
+        cli::Command::ExportPreferences { dataset, output } => {
+            let content = std::fs::read(&dataset)
+                .with_context(|| format!("Failed to read {}", dataset.display()))?;
+            let results: Vec<processors::ProcessingResult> = serde_json::from_slice(&content)
+                .with_context(|| format!("{} is not a dataset produced by a local run", dataset.display()))?;
+            let mut pairs = preference::pairs(&results);
+            pairs.extend(bestof::pairs(&results));
+            if pairs.is_empty() {
+                anyhow::bail!(
+                    "No judged records or best-of-N rejects in {}; judge candidates on the Review screen first",
+                    dataset.display()
+                );
+            }
+            let output = output.unwrap_or_else(|| dataset.with_extension("dpo.jsonl"));
+            preference::write_jsonl(&output, &pairs)?;
+            println!("{} preference pair(s) written to {}", pairs.len(), output.display());
+        }
+        cli::Command::ExportTable { dataset, preset, format, output } => {
+            let config = config::Config::load()?;
+            let workspace = workspace.unwrap_or_else(|| config.active_workspace().to_string());
+            let content = std::fs::read(&dataset)
+                .with_context(|| format!("Failed to read {}", dataset.display()))?;
+            let results: Vec<processors::ProcessingResult> = serde_json::from_slice(&content)
+                .with_context(|| format!("{} is not a dataset produced by a local run", dataset.display()))?;
+            let preset = preset.or_else(|| results.first().map(|r| r.processing_type.clone())).unwrap_or_default();
+            // Bez zapisanego mapowania eksportowane są pola rekordu
+            let columns = config.workspace_presets(&workspace).get(&preset)
+                .map(|p| p.columns.clone())
+                .filter(|columns| !columns.is_empty())
+                .unwrap_or_else(tabular::default_columns);
+            let output = output.unwrap_or_else(|| dataset.with_extension(format.extension()));
+            let rows = tabular::export(&output, &results, &columns, format)?;
+            println!("{} row(s) with {} column(s) written to {}", rows, columns.len(), output.display());
+            if config.signing.enabled {
+                signing::sign_all([output.as_path()])?;
+                println!("Signed: {}", signing::signature_path(&output).display());
+            }
+        }
+        cli::Command::Convert { dataset, format, system_prompt, output } => {
+            let config = config::Config::load()?;
+            let content = std::fs::read(&dataset)
+                .with_context(|| format!("Failed to read {}", dataset.display()))?;
+            let results: Vec<processors::ProcessingResult> = serde_json::from_slice(&content)
+                .with_context(|| format!("{} is not a dataset produced by a local run", dataset.display()))?;
+            let output = output.unwrap_or_else(|| format.path_for(&dataset));
+            let converted = formats::convert(&results, format, system_prompt.as_deref())?;
+            atomic::write(&output, serde_json::to_vec_pretty(&converted)?)?;
+            let count: usize = results.iter().map(|r| r.records.len()).sum();
+            println!("{} record(s) written to {} as {}", count, output.display(), format);
+            if config.signing.enabled {
+                signing::sign_all([output.as_path()])?;
+                println!("Signed: {}", signing::signature_path(&output).display());
+            }
+        }
+        cli::Command::Extract { file, schema, export, output } => {
+            let config = config::Config::load()?;
+            let name = schema.or_else(|| config.extract.schema.clone())
+                .context("No schema given; pass --schema or set `schema` in [extract]")?;
+            let schema = config.schemas.get(&name)
+                .with_context(|| format!("Unknown schema {}; define it in [schemas.{}]", name, name))?
+                .clone();
+            let processor = extract::ExtractProcessor { name, schema, invalid: config.extract.invalid };
+            let (mut result, dropped) = processor.extract(&file.to_string_lossy(), &local_processor_config(&config))?;
+            lineage::tag(&mut result.records, &result.processing_type);
+            let flagged = result.records.iter().filter(|r| r.metadata.contains_key(extract::SCHEMA_ERRORS)).count();
+            let output = output.unwrap_or_else(|| file.with_extension("extract.json"));
+            let count = result.records.len();
+            let results = [result];
+            atomic::write(&output, serde_json::to_vec_pretty(&results)?)?;
+            println!("{} record(s) written to {}", count, output.display());
+            // Obiekty niezgodne ze schematem
+            if dropped > 0 {
+                println!("{} dropped: not matching schema {}", dropped, processor.name);
+            }
+            if flagged > 0 {
+                println!("{} flagged with \"schema_errors\"", flagged);
+            }
+            if let Some(format) = export {
+                let rows = extract::rows(&results);
+                let path = output.with_extension(format.extension());
+                extract::export(&path, &processor.schema, &rows, format)?;
+                println!("{} typed row(s) written to {}", rows.len(), path.display());
+                if config.signing.enabled {
+                    signing::sign_all([path.as_path()])?;
+                    println!("Signed: {}", signing::signature_path(&path).display());
+                }
+            }
+        }
+        cli::Command::Check { dataset, output } => {
+            let config = config::Config::load()?;
+            if config.checks.is_empty() {
+                anyhow::bail!("No checks configured; add a [checks] section to the config file");
+            }
+            let content = std::fs::read(&dataset)
+                .with_context(|| format!("Failed to read {}", dataset.display()))?;
+            let mut results: Vec<processors::ProcessingResult> = serde_json::from_slice(&content)
+                .with_context(|| format!("{} is not a dataset produced by a local run", dataset.display()))?;
+            let flagged = checks::Checks::load(&config.checks)?.apply(&mut results);
+            for line in checks::summary(&results) {
+                println!("{}", line);
+            }
+            // Domyślnie oznaczenia trafiają do sprawdzanego pliku
+            let output = output.unwrap_or(dataset);
+            atomic::write(&output, serde_json::to_vec_pretty(&results)?)?;
+            let total: usize = results.iter().map(|r| r.records.len()).sum();
+            println!("\n{} of {} record(s) flagged, written to {}", flagged, total, output.display());
+        }
+        cli::Command::Clean { file, output } => {
+            let config = config::Config::load()?;
+            // Bez sekcji [cleanup] podgląd pokazuje działanie filtrów domyślnych
+            let settings = config.cleanup.clone().unwrap_or_default();
+            let text = std::fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let cleaned = cleanup::clean(&text, &settings);
+            let preview = cleanup::preview(&cleaned);
+            if preview.is_empty() {
+                println!("Nothing to remove");
+            }
+            for line in preview {
+                println!("{}", line);
+            }
+            println!("\n{} → {} characters", text.chars().count(), cleaned.text.chars().count());
+            if let Some(output) = output {
+                atomic::write(&output, &cleaned.text)?;
+                println!("Cleaned text written to {}", output.display());
+            }
+        }
+        cli::Command::Presets { action } => {
+            let config = config::Config::load()?;
+            let workspace = workspace.unwrap_or_else(|| config.active_workspace().to_string());
+            // Bez skonfigurowanego źródła pokazujemy tylko presety lokalne
+            let shared = match &config.preset_sync {
+                Some(settings) => {
+                    let shared = runtime.block_on(preset_sync::sync(settings))?;
+                    if let Some(reason) = &shared.stale_reason {
+                        println!("Warning: {} is unreachable, using cached copy ({})", settings.source, reason);
+                    }
+                    shared
+                }
+                None if matches!(action, cli::PresetsAction::Sync) => {
+                    anyhow::bail!("No shared preset source configured ([preset_sync] in config.toml)")
+                }
+                None => preset_sync::SharedPresets::default(),
+            };
+            match action {
+                cli::PresetsAction::Sync => println!(
+                    "Fetched {} preset(s) and {} template(s)",
+                    shared.collection.presets.len(), shared.collection.templates.len()
+                ),
+                cli::PresetsAction::List => {
+                    println!("Presets:");
+                    for (name, (preset, origin)) in preset::merge(config.workspace_presets(&workspace), &shared.collection.presets) {
+                        let description = preset.description.map(|d| format!(" - {}", d)).unwrap_or_default();
+                        println!("  {} [{}]{}", name, origin.label(), description);
+                    }
+                    println!("Templates:");
+                    for (name, (_, origin)) in preset::merge(&config.templates, &shared.collection.templates) {
+                        println!("  {} [{}]", name, origin.label());
+                    }
+                }
+            }
+        }
+        cli::Command::Report { format, month, output } => {
+            let month = month.as_deref().map(report::Month::parse).transpose()?;
+            let workspace = match workspace {
+                Some(name) => name,
+                None => config::Config::load().map(|c| c.active_workspace().to_string())
+                    .unwrap_or_else(|_| workspace::DEFAULT_WORKSPACE.to_string()),
+            };
+            let history = history::JobHistory::load_for(&workspace)?;
+            let report = report::UsageReport::build(history.entries(), month);
+            match output {
+                Some(path) => {
+                    report.write_to(&path, format)?;
+                    println!("Report with {} job(s) written to {}", report.totals.jobs, path.display());
+                }
+                None => print!("{}", report.render(format)?),
+            }
+        }
+        cli::Command::Verify { files, key } => {
+            let trusted = key.as_deref().map(signing::parse_public_key).transpose()?;
+            let mut failed = 0;
+            for file in &files {
+                // Manifest obok zbioru jest sprawdzany razem z nim
+                let manifest = manifest::DatasetManifest::path_for(file);
+                let checked = std::iter::once(file.clone()).chain(manifest.exists().then_some(manifest));
+                for path in checked {
+                    match signing::verify_file(&path, trusted.as_ref()) {
+                        Ok(signer) => println!("OK      {} (signed by {})", path.display(), signing::encode_public_key(&signer)),
+                        Err(err) => {
+                            failed += 1;
+                            println!("FAILED  {}: {:#}", path.display(), err);
+                        }
+                    }
+                }
+            }
+            if trusted.is_none() {
+                println!("\nNo --key given: the signatures are valid, but not checked against a known signer");
+            }
+            if failed > 0 {
+                anyhow::bail!("{} file(s) failed verification", failed);
+            }
+        }
+        cli::Command::Preflight { files } => {
+            let config = config::Config::load()?;
+            let files: Vec<String> = files.iter().map(|f| f.to_string_lossy().into_owned()).collect();
+            let report = preflight::analyze(&files, &config.preflight);
+            for file in report.flagged() {
+                println!("{}", file.file);
+                for issue in &file.issues {
+                    println!("  {}", issue);
+                }
+            }
+            println!("{}", report.summary());
+            if !report.is_clean() {
+                anyhow::bail!("{} file(s) flagged", report.flagged().len());
+            }
+        }
+        cli::Command::SigningKey => {
+            let key = signing::load_or_create_key()?;
+            println!("{}", signing::encode_public_key(&key.verifying_key()));
+        }
+        cli::Command::Decrypt { file, output } => {
+            let config = config::Config::load()?;
+            let workspace = workspace.unwrap_or_else(|| config.active_workspace().to_string());
+            let settings = vault::EncryptionSettings { enabled: true, ..config.encryption.clone() };
+            let key_path = settings.key_path(&workspace)?;
+            if !key_path.exists() {
+                anyhow::bail!("No encryption key for workspace {} ({})", workspace, key_path.display());
+            }
+            let content = vault::Vault::open(&settings, &workspace)?.read(&file)?;
+            match output {
+                Some(path) => {
+                    atomic::write(&path, content)?;
+                    println!("Decrypted {} to {}", file.display(), path.display());
+                }
+                None => io::Write::write_all(&mut io::stdout(), &content)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Ustawienia przetwarzania lokalnego dla komend CLI (bez modelu i dostawcy)
+fn local_processor_config(config: &config::Config) -> processors::ProcessorConfig {
+    processors::ProcessorConfig {
+        language: config.default_language.clone(),
+        chunking: config.chunking.clone(),
+        cleanup: config.cleanup.clone(),
+        confidence: config.confidence.clone(),
+        figures: config.figures.clone(),
+        metadata: config.metadata.clone(),
+        best_of: config.best_of.clone(),
+        voting: config.voting.clone(),
+        ..Default::default()
+    }
+}
+
+fn run_config_command(action: cli::ConfigAction) -> Result<()> {
+    match action {
+        cli::ConfigAction::Export { file } => {
+            let config = config::Config::load()?;
+            atomic::write(&file, bundle::export(&config)?)?;
+            println!("Configuration bundle written to {} (secrets and machine-specific settings excluded)", file.display());
+        }
+        cli::ConfigAction::Import { file, yes, keep_local } => {
+            let content = fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let bundled = bundle::read(&content)?;
+            let local = toml::Table::try_from(config::Config::load()?).context("Failed to serialize config")?;
+
+            // Konflikty rozstrzyga użytkownik, chyba że wybrano --yes lub --keep-local
+            let mut keep = std::collections::HashSet::new();
+            let mut default_choice = if yes { Some(false) } else if keep_local { Some(true) } else { None };
+            for conflict in bundle::conflicts(&local, &bundled) {
+                let keep_this = match default_choice {
+                    Some(choice) => choice,
+                    None => {
+                        println!("\n{}\n  local:  {}\n  bundle: {}", conflict.key, conflict.local, conflict.bundled);
+                        print!("[b] use bundle, [l] keep local, [B] bundle for all, [L] local for all: ");
+                        io::Write::flush(&mut io::stdout())?;
+                        let mut answer = String::new();
+                        io::stdin().read_line(&mut answer)?;
+                        match answer.trim() {
+                            "B" => { default_choice = Some(false); false }
+                            "L" => { default_choice = Some(true); true }
+                            "b" => false,
+                            _ => true,
+                        }
+                    }
+                };
+                if keep_this {
+                    keep.insert(conflict.key);
+                }
+            }
+
+            bundle::merge(&local, &bundled, &keep)?.save()?;
+            println!("Configuration imported from {}", file.display());
+        }
+    }
+    Ok(())
+}
+
+// Zwraca true, jeśli użytkownik chce mimo wszystko uruchomić drugą instancję
+fn prompt_running_instance(info: Option<&instance::InstanceInfo>) -> Result<bool> {
+    match info {
+        Some(info) => println!(
+            "Another AnyDataset TUI instance is running (pid {}, started {}).",
+            info.pid, info.started_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+        ),
+        None => println!("Another AnyDataset TUI instance is running."),
+    }
+    println!("Two instances share the same history and config files.");
+    if info.is_some() {
+        print!("[a] attach and show its state, [c] continue anyway, [q] quit: ");
+    } else {
+        print!("[c] continue anyway, [q] quit: ");
+    }
+    io::Write::flush(&mut io::stdout())?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    match (answer.trim(), info) {
+        ("a", Some(info)) => {
+            print_snapshot(&control::fetch_status(info.port, &info.token)?);
+            Ok(false)
+        }
+        ("c", _) => Ok(true),
+        _ => Ok(false),
+    }
+}
+
+fn print_snapshot(state: &control::StateSnapshot) {
+    println!("Instance pid {} - screen: {}", state.pid, state.screen);
+    if let Some(job_id) = &state.current_job_id {
+        let progress = state.job_progress
+            .map(|(current, total)| format!(" ({}/{})", current, total))
+            .unwrap_or_default();
+        println!("Active job: {} - {}{}", job_id, state.job_status.as_deref().unwrap_or("unknown"), progress);
+    }
+    if !state.recent_jobs.is_empty() {
+        println!("\nRecent jobs:");
+        for job in &state.recent_jobs {
+            println!("  {}  {:<10} {:<10} {}", job.submitted_at.with_timezone(&chrono::Local).format("%m-%d %H:%M"), job.status, job.processing_type, job.job_id);
+        }
+    }
+    if !state.log.is_empty() {
+        println!("\nLog:");
+        for line in &state.log {
+            println!("  {}", line);
+        }
+    }
+}
+
+fn spawn_update_check(tx: std::sync::mpsc::Sender<BackgroundEvent>) {
+    background::spawn("Update check", move |runtime| {
+        match runtime.block_on(update::check_for_update(VERSION)) {
+            Ok(Some(release)) => {
+                let version = release.version().map(|v| v.to_string()).unwrap_or(release.tag_name);
+                log_debug!("New version available: {}", version);
+                let _ = tx.send(BackgroundEvent::UpdateAvailable(version));
+            }
+            Ok(None) => log_debug!("No updates available"),
+            Err(err) => log_debug!("Update check failed: {}", err),
+        }
+    });
+}
+
+fn spawn_version_handshake(backend_url: &str, headers: Vec<(String, String)>, tx: std::sync::mpsc::Sender<BackgroundEvent>) {
+    let client = api::ApiClient::new(backend_url).with_headers(headers);
+    background::spawn("Version handshake", move |runtime| {
+        match runtime.block_on(client.get_version()) {
+            Ok(backend) => {
+                log_debug!("Backend version: {} (build {})", backend.version, backend.build.as_deref().unwrap_or("unknown"));
+                if version::check_compatibility(VERSION, &backend.version) != version::Compatibility::Tested {
+                    log_debug!("Client {} with backend {} is outside the tested compatibility matrix", VERSION, backend.version);
+                }
+                let _ = tx.send(BackgroundEvent::BackendVersion(backend));
+            }
+            Err(err) => {
+                log_debug!("Version handshake failed: {}", err);
+                let _ = tx.send(BackgroundEvent::BackendUnreachable(err.to_string()));
+            }
+        }
+    });
+}
+
 // Niestandardowy handler paniki
 fn setup_panic_handler() {
     panic::set_hook(Box::new(|panic_info| {
+        // Panika parsera pliku użytkownika kończy tylko jego zadanie - terminal zostaje
+        if crashsafe::in_guard() {
+            log::warn!("Parser panicked: {}", panic_info);
+            return;
+        }
+
         // Przywracanie terminala w przypadku paniki
         let _ = disable_raw_mode();
         let mut stdout = io::stdout();
         let _ = execute!(
             stdout,
             LeaveAlternateScreen,
-            DisableMouseCapture
+            DisableMouseCapture,
+            DisableBracketedPaste,
+            DisableFocusChange
         );
         
         // Zbieranie informacji o panice
@@ -147,56 +914,91 @@ fn setup_panic_handler() {
     }));
 }
 
-fn run_app<B: Backend>(
+fn run_app<B: Backend + io::Write>(
     terminal: &mut Terminal<B>,
-    mut app: App,
-    tick_rate: Duration,
+    app: &mut App,
 ) -> Result<()> {
+    let mut pacer = pacing::Pacer::new(app.remote_shell);
     let mut last_tick = Instant::now();
     
     loop {
-        terminal.draw(|f| ui(f, &mut app))?;
+        if pacer.take_redraw() {
+            terminal.draw(|f| ui(f, app))?;
+        }
 
-        let timeout = tick_rate
+        let timeout = pacer.tick_rate()
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| Duration::from_secs(0));
 
         if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match app.state {
-                        AppState::Main => match key.code {
-                            KeyCode::Char('q') => return Ok(()),
-                            KeyCode::Char('u') => app.state = AppState::Upload,
-                            KeyCode::Char('p') => app.state = AppState::Process,
-                            KeyCode::Char('s') => app.state = AppState::Settings,
-                            KeyCode::Char('j') => app.state = AppState::JobStatus,
-                            _ => {}
-                        },
-                        AppState::Upload => match key.code {
-                            KeyCode::Esc => app.state = AppState::Main,
-                            _ => app.handle_upload_input(key),
-                        },
-                        AppState::Process => match key.code {
-                            KeyCode::Esc => app.state = AppState::Main,
-                            _ => app.handle_process_input(key),
-                        },
-                        AppState::Settings => match key.code {
-                            KeyCode::Esc => app.state = AppState::Main,
-                            _ => app.handle_settings_input(key),
-                        },
-                        AppState::JobStatus => match key.code {
-                            KeyCode::Esc => app.state = AppState::Main,
-                            _ => app.handle_job_status_input(key),
-                        },
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    if !app.handle_key(key) {
+                        return Ok(());
                     }
-                }
+                    pacer.activity();
+                },
+                Event::Paste(text) => {
+                    app.handle_paste(&text);
+                    pacer.activity();
+                },
+                // Po zmianie rozmiaru cały ekran musi zostać narysowany od nowa
+                Event::Resize(width, height) => {
+                    app.recorder.resize(width, height);
+                    pacer.activity();
+                },
+                Event::FocusGained => app.focused = true,
+                Event::FocusLost => app.focused = false,
+                _ => {},
             }
         }
 
-        if last_tick.elapsed() >= tick_rate {
-            app.on_tick();
+        if last_tick.elapsed() >= pacer.tick_rate() {
+            if app.on_tick() {
+                pacer.activity();
+            } else {
+                pacer.idle_tick();
+            }
             last_tick = Instant::now();
         }
+
+        // Nagranie rozpoczęte w trakcie sesji zaczyna się od pełnej klatki
+        if std::mem::take(&mut app.redraw_all) {
+            terminal.clear()?;
+            pacer.activity();
+        }
+
+        if std::mem::take(&mut app.ring_bell) {
+            let backend = terminal.backend_mut();
+            io::Write::write_all(backend, b"\x07")?;
+            io::Write::flush(backend)?;
+        }
+
+        if pacer.is_remote() != app.remote_shell {
+            pacer.set_remote(app.remote_shell);
+        }
+
+        // Edytor przejmuje terminal, więc na czas edycji wychodzimy z trybu TUI
+        if let Some(path) = app.pending_editor.take() {
+            open_in_editor(terminal, &path)?;
+            pacer.activity();
+        }
+    }
+}
+
+fn open_in_editor<B: Backend + io::Write>(terminal: &mut Terminal<B>, path: &std::path::Path) -> Result<()> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste, DisableFocusChange)?;
+
+    let editor = hooks::editor_command();
+    let status = std::process::Command::new(&editor).arg(path).status();
+
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste, EnableFocusChange)?;
+    terminal.clear()?;
+
+    if let Err(err) = status {
+        log_debug!("Failed to start editor {}: {}", editor, err);
     }
-}
\ No newline at end of file
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+/// Tick of the normal mode, and of the remote-shell mode right after activity
+pub const TICK: Duration = Duration::from_millis(250);
+/// Slowest tick of the remote-shell mode, reached after a few idle ticks
+pub const MAX_REMOTE_TICK: Duration = Duration::from_secs(2);
+
+/// Decides when the event loop ticks and redraws. The normal mode ticks every 250 ms and draws
+/// on every pass; the remote-shell mode draws only after input, a change or a tick, and doubles
+/// the tick while nothing happens, so a slow SSH/mosh link carries little output.
+#[derive(Debug, Clone)]
+pub struct Pacer {
+    remote: bool,
+    tick: Duration,
+    redraw: bool,
+}
+
+impl Pacer {
+    pub fn new(remote: bool) -> Self {
+        Self { remote, tick: TICK, redraw: true }
+    }
+
+    pub fn is_remote(&self) -> bool {
+        self.remote
+    }
+
+    pub fn set_remote(&mut self, remote: bool) {
+        self.remote = remote;
+        self.tick = TICK;
+        self.redraw = true;
+    }
+
+    pub fn tick_rate(&self) -> Duration {
+        self.tick
+    }
+
+    /// Input or a change found on a tick: redraw and tick quickly again
+    pub fn activity(&mut self) {
+        self.redraw = true;
+        self.tick = TICK;
+    }
+
+    /// A tick that changed nothing. It still redraws so progress and timers move, only less
+    /// often as the tick slows down.
+    pub fn idle_tick(&mut self) {
+        self.redraw = true;
+        if self.remote {
+            self.tick = (self.tick * 2).min(MAX_REMOTE_TICK);
+        }
+    }
+
+    /// Whether to draw on this pass of the loop
+    pub fn take_redraw(&mut self) -> bool {
+        let redraw = self.redraw || !self.remote;
+        self.redraw = false;
+        redraw
+    }
+}
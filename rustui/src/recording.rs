@@ -0,0 +1,160 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::config::get_config_dir;
+
+/// Session recording in the asciicast v2 format (as written by `asciinema rec`): a JSON
+/// header line, then one `[seconds, "o", text]` event per drawn frame and `[seconds, "r",
+/// "WxH"]` per terminal resize. `asciinema play` replays it.
+pub struct Cast {
+    out: BufWriter<File>,
+    path: PathBuf,
+    started: Instant,
+    /// Output since the last frame, including the start of a UTF-8 character split by a write
+    pending: Vec<u8>,
+}
+
+impl Cast {
+    pub fn create(path: &Path, width: u16, height: u16) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let file = File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut cast = Self { out: BufWriter::new(file), path: path.to_path_buf(), started: Instant::now(), pending: Vec::new() };
+        let header = serde_json::json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": chrono::Utc::now().timestamp(),
+            "title": "anydataset-tui",
+            "env": { "TERM": std::env::var("TERM").unwrap_or_default() },
+        });
+        writeln!(cast.out, "{}", header)?;
+        Ok(cast)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Terminal output; it becomes an event when the frame is flushed
+    pub fn output(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+    }
+
+    /// Writes the output since the last frame as one event
+    pub fn frame(&mut self) -> io::Result<()> {
+        let complete = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            // A character cut at the end is completed by the next write
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(_) => self.pending.len(),
+        };
+        if complete == 0 {
+            return Ok(());
+        }
+        let text = String::from_utf8_lossy(&self.pending[..complete]).into_owned();
+        self.pending.drain(..complete);
+        self.event("o", &text)
+    }
+
+    pub fn resize(&mut self, width: u16, height: u16) -> io::Result<()> {
+        self.frame()?;
+        self.event("r", &format!("{}x{}", width, height))
+    }
+
+    /// Flushes the last frame and the file
+    pub fn finish(mut self) -> Result<PathBuf> {
+        self.frame()?;
+        self.out.flush().with_context(|| format!("Failed to write {}", self.path.display()))?;
+        Ok(self.path)
+    }
+
+    fn event(&mut self, code: &str, data: &str) -> io::Result<()> {
+        let time = self.started.elapsed().as_secs_f64();
+        writeln!(self.out, "{}", serde_json::json!([(time * 1e6).round() / 1e6, code, data]))
+    }
+}
+
+/// Shared handle on the recording in progress: the terminal writer feeds it, the app starts
+/// and stops it
+#[derive(Clone, Default)]
+pub struct Recorder(Arc<Mutex<Option<Cast>>>);
+
+impl std::fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Recorder").field(&self.path()).finish()
+    }
+}
+
+impl Recorder {
+    /// Starts recording to `path`, at the current size of the terminal
+    pub fn start(&self, path: &Path) -> Result<()> {
+        let (width, height) = crossterm::terminal::size().unwrap_or((80, 24));
+        let cast = Cast::create(path, width, height)?;
+        *self.lock() = Some(cast);
+        Ok(())
+    }
+
+    /// Ends the recording; the path of the saved cast, None when nothing was being recorded
+    pub fn stop(&self) -> Option<Result<PathBuf>> {
+        self.lock().take().map(Cast::finish)
+    }
+
+    /// File being recorded to
+    pub fn path(&self) -> Option<PathBuf> {
+        self.lock().as_ref().map(|cast| cast.path().to_path_buf())
+    }
+
+    pub fn resize(&self, width: u16, height: u16) {
+        if let Some(cast) = self.lock().as_mut() {
+            let _ = cast.resize(width, height);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Cast>> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Terminal writer that also passes what it writes to the recorder, one frame per flush
+pub struct Tee<W: Write> {
+    inner: W,
+    recorder: Recorder,
+}
+
+impl<W: Write> Tee<W> {
+    pub fn new(inner: W, recorder: Recorder) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl<W: Write> Write for Tee<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if let Some(cast) = self.recorder.lock().as_mut() {
+            cast.output(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        if let Some(cast) = self.recorder.lock().as_mut() {
+            // A failing recording must not break the UI
+            let _ = cast.frame();
+        }
+        Ok(())
+    }
+}
+
+/// Default file of a recording started from the TUI: `recordings/session-<time>.cast` in
+/// the config directory
+pub fn default_path() -> Result<PathBuf> {
+    let name = format!("session-{}.cast", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    Ok(get_config_dir()?.join("recordings").join(name))
+}
@@ -0,0 +1,191 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crate::formats::parse_records;
+use crate::lineage::{LineageGraph, RECORD_ID};
+use crate::vault::Vault;
+
+/// Fields of a `Record`, shown first and in this order
+const RECORD_FIELDS: [&str; 4] = ["instruction", "prompt", "completion", "metadata"];
+
+/// Field of the current record as shown on the Results screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub name: String,
+    pub lines: Vec<String>,
+    pub folded: bool,
+}
+
+/// Text of a field: JSON values and strings holding JSON are pretty-printed
+fn pretty(value: &Value) -> Vec<String> {
+    let parsed = match value {
+        Value::String(s) => match serde_json::from_str::<Value>(s) {
+            Ok(json @ (Value::Object(_) | Value::Array(_))) => json,
+            _ => return s.lines().map(str::to_string).collect(),
+        },
+        other => other.clone(),
+    };
+    serde_json::to_string_pretty(&parsed)
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Results screen: one record at a time, fields folded independently
+#[derive(Debug, Clone)]
+pub struct ResultsViewer {
+    pub path: PathBuf,
+    pub records: Vec<Value>,
+    pub current: usize,
+    /// Field with the cursor, by position in `sections()`
+    pub selected: usize,
+    pub scroll: u16,
+    /// Folded field names; they stay folded when paging to other records
+    pub folded: BTreeSet<String>,
+    /// How the current record was derived, shown in place of its fields
+    pub lineage: Option<Vec<String>>,
+}
+
+impl ResultsViewer {
+    /// Loads a results file, decrypting it if needed
+    pub fn load(path: &Path, vault: &Vault) -> Result<Self> {
+        let content = vault.read_to_string(path)?;
+        let records = parse_records(&content)
+            .with_context(|| format!("{} is not a JSON or JSONL results file", path.display()))?;
+        Ok(Self::new(path, records))
+    }
+
+    /// Metadata starts folded; it's often longer than the record itself
+    pub fn new(path: &Path, records: Vec<Value>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            records,
+            current: 0,
+            selected: 0,
+            scroll: 0,
+            folded: BTreeSet::from(["metadata".to_string()]),
+            lineage: None,
+        }
+    }
+
+    /// Fields of the current record, the `Record` ones first
+    pub fn sections(&self) -> Vec<Section> {
+        let Some(record) = self.records.get(self.current) else {
+            return Vec::new();
+        };
+        let fields: Vec<(String, &Value)> = match record {
+            Value::Object(object) => {
+                let known = RECORD_FIELDS.iter()
+                    .filter_map(|name| object.get(*name).map(|value| (name.to_string(), value)));
+                let other = object.iter()
+                    .filter(|(name, _)| !RECORD_FIELDS.contains(&name.as_str()))
+                    .map(|(name, value)| (name.clone(), value));
+                known.chain(other).collect()
+            },
+            other => vec![("value".to_string(), other)],
+        };
+        fields.into_iter()
+            .map(|(name, value)| Section {
+                folded: self.folded.contains(&name),
+                lines: pretty(value),
+                name,
+            })
+            .collect()
+    }
+
+    /// Screen lines of the current record, each section's header included
+    pub fn line_count(&self) -> usize {
+        if let Some(lines) = &self.lineage {
+            return lines.len();
+        }
+        self.sections().iter().map(|s| 1 + if s.folded { 0 } else { s.lines.len() }).sum()
+    }
+
+    pub fn next_record(&mut self) {
+        if self.current + 1 < self.records.len() {
+            self.show_record(self.current + 1);
+        }
+    }
+
+    pub fn previous_record(&mut self) {
+        if self.current > 0 {
+            self.show_record(self.current - 1);
+        }
+    }
+
+    pub fn show_record(&mut self, index: usize) {
+        self.current = index.min(self.records.len().saturating_sub(1));
+        self.selected = 0;
+        self.scroll = 0;
+        if self.lineage.take().is_some() {
+            self.toggle_lineage();
+        }
+    }
+
+    /// Shows how the current record was derived, or its fields again. Parents are looked up
+    /// in the datasets next to the file, where the stages of a pipeline are written.
+    pub fn toggle_lineage(&mut self) {
+        if self.lineage.take().is_some() {
+            return;
+        }
+        let dir = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let id = self.records.get(self.current)
+            .and_then(|record| record.get("metadata")?.get(RECORD_ID)?.as_str());
+        self.lineage = Some(match id {
+            Some(id) => LineageGraph::load_dir(dir).render(id)
+                .unwrap_or_else(|| vec![format!("{} is not in a dataset of {}", id, dir.display())]),
+            None => vec!["No record_id; only records of local runs are linked to their sources".to_string()],
+        });
+        self.scroll = 0;
+    }
+
+    pub fn scroll_down(&mut self, lines: u16) {
+        let last = self.line_count().saturating_sub(1) as u16;
+        self.scroll = self.scroll.saturating_add(lines).min(last);
+    }
+
+    pub fn scroll_up(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    /// Moves the cursor to the next field and scrolls to its header
+    pub fn next_field(&mut self) {
+        let count = self.sections().len();
+        if count > 0 {
+            self.selected = (self.selected + 1) % count;
+            self.scroll_to_selected();
+        }
+    }
+
+    pub fn toggle_fold(&mut self) {
+        let Some(section) = self.sections().into_iter().nth(self.selected) else {
+            return;
+        };
+        if !self.folded.remove(&section.name) {
+            self.folded.insert(section.name);
+        }
+        self.scroll_to_selected();
+    }
+
+    /// Folds every field of the record, or unfolds them all when they already are
+    pub fn toggle_all(&mut self) {
+        let names: Vec<String> = self.sections().into_iter().map(|s| s.name).collect();
+        if names.iter().all(|name| self.folded.contains(name)) {
+            self.folded.clear();
+        } else {
+            self.folded.extend(names);
+        }
+        self.scroll_to_selected();
+    }
+
+    fn scroll_to_selected(&mut self) {
+        let header: usize = self.sections().iter()
+            .take(self.selected)
+            .map(|s| 1 + if s.folded { 0 } else { s.lines.len() })
+            .sum();
+        self.scroll = header as u16;
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::{bail, Context, Result};
+use tui_input::{Input, InputRequest};
+
+/// Highest `max_tokens` accepted; larger limits are beyond what any provider generates in one answer
+pub const MAX_TOKENS_LIMIT: u32 = 128_000;
+
+/// Sampling parameters sent with a job; `None` leaves the provider's default
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SamplingParams {
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub top_p: Option<f32>,
+}
+
+impl SamplingParams {
+    /// One-line summary for the Process screen
+    pub fn describe(&self) -> String {
+        let show = |value: Option<String>| value.unwrap_or_else(|| "default".to_string());
+        format!(
+            "temperature {}, max tokens {}, top_p {}",
+            show(self.temperature.map(|t| t.to_string())),
+            show(self.max_tokens.map(|t| t.to_string())),
+            show(self.top_p.map(|p| p.to_string())),
+        )
+    }
+}
+
+/// Fields of the advanced-parameters panel, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SamplingField {
+    Temperature,
+    MaxTokens,
+    TopP,
+}
+
+impl SamplingField {
+    pub const ALL: [SamplingField; 3] = [SamplingField::Temperature, SamplingField::MaxTokens, SamplingField::TopP];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SamplingField::Temperature => "Temperature (0.0-2.0)",
+            SamplingField::MaxTokens => "Max tokens (1-128000)",
+            SamplingField::TopP => "Top p (0.0-1.0)",
+        }
+    }
+}
+
+/// Advanced-parameters popup of the Process screen: one text input per parameter, empty for
+/// the provider's default
+#[derive(Debug, Clone)]
+pub struct SamplingEditor {
+    pub inputs: [Input; 3],
+    pub selected: usize,
+    /// Why the last Enter didn't apply the values
+    pub error: Option<String>,
+}
+
+impl SamplingEditor {
+    pub fn open(params: &SamplingParams) -> Self {
+        let text = |value: Option<String>| Input::new(value.unwrap_or_default());
+        Self {
+            inputs: [
+                text(params.temperature.map(|t| t.to_string())),
+                text(params.max_tokens.map(|t| t.to_string())),
+                text(params.top_p.map(|p| p.to_string())),
+            ],
+            selected: 0,
+            error: None,
+        }
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.inputs.len();
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = (self.selected + self.inputs.len() - 1) % self.inputs.len();
+    }
+
+    /// Types into the selected field; only characters of a number are accepted
+    pub fn insert(&mut self, c: char) {
+        if c.is_ascii_digit() || (c == '.' && SamplingField::ALL[self.selected] != SamplingField::MaxTokens) {
+            self.inputs[self.selected].handle(InputRequest::InsertChar(c));
+        }
+    }
+
+    pub fn delete(&mut self) {
+        self.inputs[self.selected].handle(InputRequest::DeletePrevChar);
+    }
+
+    /// The entered values, or which one is out of range
+    pub fn parse(&self) -> Result<SamplingParams> {
+        let [temperature, max_tokens, top_p] = &self.inputs;
+        Ok(SamplingParams {
+            temperature: parse_in_range(temperature.value(), "Temperature", 0.0, 2.0)?,
+            max_tokens: match max_tokens.value().trim() {
+                "" => None,
+                value => {
+                    let tokens: u32 = value.parse().with_context(|| format!("Max tokens '{}' is not a whole number", value))?;
+                    if !(1..=MAX_TOKENS_LIMIT).contains(&tokens) {
+                        bail!("Max tokens must be between 1 and {}", MAX_TOKENS_LIMIT);
+                    }
+                    Some(tokens)
+                },
+            },
+            top_p: parse_in_range(top_p.value(), "Top p", 0.0, 1.0)?,
+        })
+    }
+}
+
+fn parse_in_range(value: &str, name: &str, min: f32, max: f32) -> Result<Option<f32>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let number: f32 = value.parse().with_context(|| format!("{} '{}' is not a number", name, value))?;
+    if !(min..=max).contains(&number) {
+        bail!("{} must be between {:.1} and {:.1}", name, min, max);
+    }
+    Ok(Some(number))
+}
@@ -0,0 +1,139 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::results::ResultsViewer;
+use crate::vault::Vault;
+
+/// Word counts of a text field over the records that have it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WordCounts {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+}
+
+impl WordCounts {
+    fn of(counts: &[usize]) -> Option<Self> {
+        Some(Self {
+            min: *counts.iter().min()?,
+            max: *counts.iter().max()?,
+            mean: counts.iter().sum::<usize>() as f64 / counts.len() as f64,
+        })
+    }
+}
+
+/// Summary of a dataset's records, as shown on the Stats screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatasetStats {
+    pub records: usize,
+    /// Records having each top-level field
+    pub fields: BTreeMap<String, usize>,
+    /// Records having each `metadata` key
+    pub metadata: BTreeMap<String, usize>,
+    pub prompt_words: Option<WordCounts>,
+    pub completion_words: Option<WordCounts>,
+    pub empty_completions: usize,
+    /// Records whose completion repeats one of an earlier record
+    pub duplicate_completions: usize,
+}
+
+impl DatasetStats {
+    pub fn compute(records: &[Value]) -> Self {
+        let mut fields = BTreeMap::new();
+        let mut metadata = BTreeMap::new();
+        let mut prompt_words = Vec::new();
+        let mut completion_words = Vec::new();
+        let mut empty_completions = 0;
+        let mut duplicate_completions = 0;
+        let mut seen = HashSet::new();
+        for record in records {
+            let Some(object) = record.as_object() else {
+                continue;
+            };
+            for name in object.keys() {
+                *fields.entry(name.clone()).or_insert(0) += 1;
+            }
+            for key in object.get("metadata").and_then(Value::as_object).into_iter().flat_map(|m| m.keys()) {
+                *metadata.entry(key.clone()).or_insert(0) += 1;
+            }
+            if let Some(prompt) = object.get("prompt").and_then(Value::as_str) {
+                prompt_words.push(prompt.split_whitespace().count());
+            }
+            let Some(completion) = object.get("completion").and_then(Value::as_str) else {
+                continue;
+            };
+            completion_words.push(completion.split_whitespace().count());
+            if completion.trim().is_empty() {
+                empty_completions += 1;
+            } else if !seen.insert(completion.trim()) {
+                duplicate_completions += 1;
+            }
+        }
+        Self {
+            records: records.len(),
+            fields,
+            metadata,
+            prompt_words: WordCounts::of(&prompt_words),
+            completion_words: WordCounts::of(&completion_words),
+            empty_completions,
+            duplicate_completions,
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let words = |name: &str, counts: Option<WordCounts>| match counts {
+            Some(c) => format!("{} words: {} min, {:.1} mean, {} max", name, c.min, c.mean, c.max),
+            None => format!("{} words: -", name),
+        };
+        let mut lines = vec![
+            format!("Records: {}", self.records),
+            words("Prompt", self.prompt_words),
+            words("Completion", self.completion_words),
+            format!("Empty completions: {}", self.empty_completions),
+            format!("Duplicate completions: {}", self.duplicate_completions),
+            String::new(),
+            "Fields (records having them):".to_string(),
+        ];
+        lines.extend(self.fields.iter().map(|(name, count)| format!("  {:<24} {}", name, count)));
+        if !self.metadata.is_empty() {
+            lines.push(String::new());
+            lines.push("Metadata keys:".to_string());
+            lines.extend(self.metadata.iter().map(|(key, count)| format!("  {:<24} {}", key, count)));
+        }
+        lines
+    }
+}
+
+/// Stats screen: the summary of one of the given datasets, e.g. those of an unpacked archive
+#[derive(Debug, Clone)]
+pub struct StatsView {
+    pub datasets: Vec<PathBuf>,
+    pub selected: usize,
+    /// Stats of the selected dataset, or why it couldn't be read
+    pub stats: Result<DatasetStats, String>,
+    pub scroll: u16,
+}
+
+impl StatsView {
+    pub fn open(datasets: Vec<PathBuf>, vault: &Vault) -> Self {
+        let mut view = Self { datasets, selected: 0, stats: Err("No dataset".to_string()), scroll: 0 };
+        view.select(0, vault);
+        view
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.datasets.get(self.selected).map(PathBuf::as_path)
+    }
+
+    pub fn select(&mut self, index: usize, vault: &Vault) {
+        self.selected = index.min(self.datasets.len().saturating_sub(1));
+        self.scroll = 0;
+        self.stats = match self.path() {
+            Some(path) => ResultsViewer::load(path, vault)
+                .map(|viewer| DatasetStats::compute(&viewer.records))
+                .map_err(|err| format!("{:#}", err)),
+            None => Err("No dataset".to_string()),
+        };
+    }
+}
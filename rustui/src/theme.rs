@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use ratatui::style::Color;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::config::{get_config_dir, Config};
+
+pub use anydataset_core::theme::ThemeSettings;
+
+/// Built-in palettes, in the order 't' in Settings cycles through them
+pub const PALETTES: [&str; 4] = ["default", "dark", "light", "solarized"];
+
+/// Colors of the UI by role; every style in `ui.rs` takes its color from here
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub text: Color,
+    /// Hints, placeholders and inactive entries
+    pub muted: Color,
+    /// Selected row, active tab, popups asking for attention
+    pub highlight: Color,
+    /// Headings, gauges and directories
+    pub accent: Color,
+    pub success: Color,
+    /// Work in progress and non-fatal problems
+    pub warning: Color,
+    pub error: Color,
+    /// Series colors of the Usage chart
+    pub chart: Vec<Color>,
+}
+
+impl Default for Theme {
+    /// Named terminal colors, so the terminal's own color scheme applies
+    fn default() -> Self {
+        Self {
+            text: Color::White,
+            muted: Color::Gray,
+            highlight: Color::Yellow,
+            accent: Color::Cyan,
+            success: Color::Green,
+            warning: Color::Yellow,
+            error: Color::Red,
+            chart: vec![Color::Cyan, Color::Yellow, Color::Magenta, Color::Green, Color::Red, Color::Blue],
+        }
+    }
+}
+
+impl Theme {
+    pub fn palette(name: &str) -> Option<Self> {
+        let theme = match name {
+            "default" => Self::default(),
+            "dark" => Self {
+                text: Color::Rgb(0xd0, 0xd0, 0xd0),
+                muted: Color::Rgb(0x80, 0x80, 0x80),
+                highlight: Color::Rgb(0xff, 0xd7, 0x5f),
+                accent: Color::Rgb(0x5f, 0xd7, 0xff),
+                success: Color::Rgb(0x87, 0xd7, 0x87),
+                warning: Color::Rgb(0xff, 0xaf, 0x5f),
+                error: Color::Rgb(0xff, 0x5f, 0x5f),
+                chart: vec![
+                    Color::Rgb(0x5f, 0xd7, 0xff),
+                    Color::Rgb(0xff, 0xd7, 0x5f),
+                    Color::Rgb(0xd7, 0x87, 0xff),
+                    Color::Rgb(0x87, 0xd7, 0x87),
+                    Color::Rgb(0xff, 0x87, 0x87),
+                    Color::Rgb(0x87, 0xaf, 0xff),
+                ],
+            },
+            // Yellow and white don't read on a light background
+            "light" => Self {
+                text: Color::Black,
+                muted: Color::Rgb(0x6c, 0x6c, 0x6c),
+                highlight: Color::Rgb(0x00, 0x5f, 0xaf),
+                accent: Color::Rgb(0x00, 0x87, 0x87),
+                success: Color::Rgb(0x00, 0x87, 0x00),
+                warning: Color::Rgb(0xaf, 0x5f, 0x00),
+                error: Color::Rgb(0xd7, 0x00, 0x00),
+                chart: vec![
+                    Color::Rgb(0x00, 0x5f, 0xaf),
+                    Color::Rgb(0xaf, 0x5f, 0x00),
+                    Color::Rgb(0x87, 0x00, 0xaf),
+                    Color::Rgb(0x00, 0x87, 0x00),
+                    Color::Rgb(0xd7, 0x00, 0x00),
+                    Color::Rgb(0x00, 0x87, 0x87),
+                ],
+            },
+            "solarized" => Self {
+                text: Color::Rgb(0x83, 0x94, 0x96),
+                muted: Color::Rgb(0x58, 0x6e, 0x75),
+                highlight: Color::Rgb(0xb5, 0x89, 0x00),
+                accent: Color::Rgb(0x2a, 0xa1, 0x98),
+                success: Color::Rgb(0x85, 0x99, 0x00),
+                warning: Color::Rgb(0xcb, 0x4b, 0x16),
+                error: Color::Rgb(0xdc, 0x32, 0x2f),
+                chart: vec![
+                    Color::Rgb(0x26, 0x8b, 0xd2),
+                    Color::Rgb(0xb5, 0x89, 0x00),
+                    Color::Rgb(0xd3, 0x36, 0x82),
+                    Color::Rgb(0x85, 0x99, 0x00),
+                    Color::Rgb(0xdc, 0x32, 0x2f),
+                    Color::Rgb(0x6c, 0x71, 0xc4),
+                ],
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+
+    /// The palette with the `[theme]` overrides applied. A palette that isn't built in is
+    /// read from `<name>.toml` in `themes_dir`: the same keys as `[theme]`, based on a
+    /// built-in palette.
+    pub fn load(settings: &ThemeSettings, themes_dir: &Path) -> Result<Self> {
+        let mut theme = match Self::palette(&settings.palette) {
+            Some(theme) => theme,
+            None => {
+                let path = themes_dir.join(format!("{}.toml", settings.palette));
+                let content = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Unknown theme '{}' and no {}", settings.palette, path.display()))?;
+                let file: ThemeSettings = toml::from_str(&content)
+                    .with_context(|| format!("Failed to parse {}", path.display()))?;
+                let mut theme = Self::palette(&file.palette)
+                    .with_context(|| format!("{} is based on '{}', which is not a built-in palette", path.display(), file.palette))?;
+                apply(&file, &mut theme)?;
+                theme
+            },
+        };
+        apply(settings, &mut theme)?;
+        Ok(theme)
+    }
+
+    /// The theme `[theme]` of `config` configures; custom palettes are read from `themes/`
+    /// in the config directory
+    pub fn for_config(config: &Config) -> Result<Self> {
+        Self::load(&config.theme, &get_config_dir()?.join("themes"))
+    }
+}
+
+fn parse_color(role: &str, value: &str) -> Result<Color> {
+    Color::from_str(value.trim()).map_err(|_| anyhow::anyhow!("Invalid {} color '{}'", role, value))
+}
+
+fn apply(settings: &ThemeSettings, theme: &mut Theme) -> Result<()> {
+    let roles = [
+        ("text", &settings.text, &mut theme.text),
+        ("muted", &settings.muted, &mut theme.muted),
+        ("highlight", &settings.highlight, &mut theme.highlight),
+        ("accent", &settings.accent, &mut theme.accent),
+        ("success", &settings.success, &mut theme.success),
+        ("warning", &settings.warning, &mut theme.warning),
+        ("error", &settings.error, &mut theme.error),
+    ];
+    for (role, value, color) in roles {
+        if let Some(value) = value {
+            *color = parse_color(role, value)?;
+        }
+    }
+    if let Some(chart) = &settings.chart {
+        let colors = chart.iter()
+            .map(|value| parse_color("chart", value))
+            .collect::<Result<Vec<Color>>>()?;
+        if !colors.is_empty() {
+            theme.chart = colors;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use crate::chunking::{self, Chunk, ChunkSettings, ChunkStrategy};
+
+/// Steps of the size and overlap adjustments, in words or estimated tokens
+const SIZE_STEP_WORDS: usize = 50;
+const SIZE_STEP_TOKENS: usize = 100;
+const OVERLAP_STEP_WORDS: usize = 10;
+const OVERLAP_STEP_TOKENS: usize = 20;
+
+/// Chunks of one document under settings being adjusted; every change splits the text again
+#[derive(Debug, Clone)]
+pub struct ChunkTuner {
+    pub file: String,
+    text: String,
+    pub settings: ChunkSettings,
+    pub chunks: Vec<Chunk>,
+    pub selected: usize,
+}
+
+impl ChunkTuner {
+    pub fn new(file: &str, text: String, settings: ChunkSettings) -> Self {
+        let mut tuner = Self { file: file.to_string(), text, settings, chunks: Vec::new(), selected: 0 };
+        tuner.rechunk();
+        tuner
+    }
+
+    fn rechunk(&mut self) {
+        self.chunks = chunking::split(&self.text, &self.settings);
+        self.selected = self.selected.min(self.chunks.len().saturating_sub(1));
+    }
+
+    fn counts_words(&self) -> bool {
+        self.settings.strategy == ChunkStrategy::Words
+    }
+
+    pub fn next(&mut self) {
+        if !self.chunks.is_empty() {
+            self.selected = (self.selected + 1) % self.chunks.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.chunks.is_empty() {
+            self.selected = (self.selected + self.chunks.len() - 1) % self.chunks.len();
+        }
+    }
+
+    pub fn cycle_strategy(&mut self) {
+        self.settings.strategy = self.settings.strategy.next();
+        self.rechunk();
+    }
+
+    /// Grows (1) or shrinks (-1) the chunks by one step; they never get empty
+    pub fn adjust_size(&mut self, direction: isize) {
+        let (size, step) = if self.counts_words() {
+            (&mut self.settings.size_words, SIZE_STEP_WORDS)
+        } else {
+            (&mut self.settings.size_tokens, SIZE_STEP_TOKENS)
+        };
+        *size = size.saturating_add_signed(direction * step as isize).max(step);
+        self.rechunk();
+    }
+
+    /// Widens (1) or narrows (-1) the context on each side by one step
+    pub fn adjust_overlap(&mut self, direction: isize) {
+        let (overlap, step) = if self.counts_words() {
+            (&mut self.settings.overlap_words, OVERLAP_STEP_WORDS)
+        } else {
+            (&mut self.settings.overlap_tokens, OVERLAP_STEP_TOKENS)
+        };
+        *overlap = overlap.saturating_add_signed(direction * step as isize);
+        self.rechunk();
+    }
+
+    /// Settings in one line, e.g. "recursive, 1000 tokens per chunk, 120 of context"
+    pub fn summary(&self) -> String {
+        let (size, overlap, unit) = if self.counts_words() {
+            (self.settings.size_words, self.settings.overlap_words, "words")
+        } else {
+            (self.settings.size_tokens, self.settings.overlap_tokens, "tokens")
+        };
+        format!(
+            "{}, {} {} per chunk, {} of context: {} chunk(s)",
+            self.settings.strategy.name(), size, unit, overlap, self.chunks.len()
+        )
+    }
+
+    /// Words and estimated tokens of a chunk, without its context
+    pub fn size_of(chunk: &Chunk) -> (usize, usize) {
+        (chunk.text.split_whitespace().count(), chunking::estimate_tokens(&chunk.text))
+    }
+}
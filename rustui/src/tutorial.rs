@@ -0,0 +1,95 @@
+use crate::app::AppState;
+
+/// Steps of the onboarding tour, in the order a first job is usually run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TutorialStep {
+    Welcome,
+    Upload,
+    Process,
+    JobStatus,
+    Download,
+}
+
+impl TutorialStep {
+    pub fn all() -> Vec<TutorialStep> {
+        vec![
+            TutorialStep::Welcome,
+            TutorialStep::Upload,
+            TutorialStep::Process,
+            TutorialStep::JobStatus,
+            TutorialStep::Download,
+        ]
+    }
+
+    /// Screen shown underneath the hint for this step
+    pub fn screen(&self) -> AppState {
+        match self {
+            TutorialStep::Welcome => AppState::Main,
+            TutorialStep::Upload => AppState::Upload,
+            TutorialStep::Process => AppState::Process,
+            TutorialStep::JobStatus | TutorialStep::Download => AppState::JobStatus,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            TutorialStep::Welcome => "Welcome",
+            TutorialStep::Upload => "1. Upload",
+            TutorialStep::Process => "2. Process",
+            TutorialStep::JobStatus => "3. Job Status",
+            TutorialStep::Download => "4. Download",
+        }
+    }
+
+    pub fn hint(&self) -> &'static str {
+        match self {
+            TutorialStep::Welcome => "This short tour walks you through building your first dataset. Use Enter/Right to continue, Left to go back and Esc to skip.",
+            TutorialStep::Upload => "Start on the Upload screen ('u' from Main): pick the source documents you want to turn into a dataset.",
+            TutorialStep::Process => "On the Process screen ('p') select a file with Up/Down, choose a processing type with 1-4 and press 'p' to submit the job.",
+            TutorialStep::JobStatus => "The Job Status screen ('j') tracks progress. Enter a job id to follow any job submitted to the backend.",
+            TutorialStep::Download => "When the job completes, its results can be downloaded from here. You can replay this tour with 't' on the Main screen.",
+        }
+    }
+}
+
+/// Small state machine driving the onboarding overlay
+#[derive(Debug, Clone, Default)]
+pub struct Tutorial {
+    step: Option<usize>,
+}
+
+impl Tutorial {
+    pub fn start(&mut self) {
+        self.step = Some(0);
+    }
+
+    pub fn dismiss(&mut self) {
+        self.step = None;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.step.is_some()
+    }
+
+    pub fn current(&self) -> Option<TutorialStep> {
+        self.step.and_then(|i| TutorialStep::all().get(i).copied())
+    }
+
+    /// Position of the current step as (index, total), 1-based
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        self.step.map(|i| (i + 1, TutorialStep::all().len()))
+    }
+
+    /// Advances the tour; finishing the last step ends it
+    pub fn next(&mut self) {
+        if let Some(i) = self.step {
+            self.step = if i + 1 < TutorialStep::all().len() { Some(i + 1) } else { None };
+        }
+    }
+
+    pub fn back(&mut self) {
+        if let Some(i) = self.step {
+            self.step = Some(i.saturating_sub(1));
+        }
+    }
+}
@@ -2,13 +2,31 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Tabs, Gauge},
+    symbols,
+    widgets::{Axis, Bar, BarChart, BarGroup, Block, Borders, Cell, Chart, Clear, Dataset, GraphType, List, ListItem, Paragraph, Row, Table, Tabs, Gauge, Wrap},
     Frame,
 };
 
-use crate::app::{App, AppState, ProcessingType};
+use crate::credentials::{self, KeyManager};
+use crate::discovery::BackendPicker;
+use crate::diskspace;
+use crate::logger::LogLevel;
+use crate::jobs;
+use crate::network;
+use crate::ollama;
+use crate::assembly::{AssemblyWizard, Step, FILTER_FIELDS, SPLIT_FIELDS};
+use crate::app::{App, AppState, CancelDialog, DownloadDialog, LocalJob, PreflightDialog, PresetPicker, ProcessingType, QuotaDialog};
+use crate::preference::{self, Choice};
+use crate::preset::Origin;
+use crate::tabular::ColumnEditor;
+use crate::theme::Theme;
+use crate::tuning::ChunkTuner;
+use crate::sampling::{SamplingEditor, SamplingField};
+use crate::usage;
+use crate::version::{check_compatibility, Compatibility};
 
 pub fn ui(f: &mut Frame, app: &mut App) {
+    let theme = &app.theme;
     let size = f.size();
 
     // Create the layout
@@ -22,18 +40,32 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         .split(size);
 
     // Draw the tabs
-    let titles = vec!["Main", "Upload", "Process", "Settings", "Job Status"];
-    let tabs = Tabs::new(titles.into_iter().map(Line::from).collect())
-        .block(Block::default().borders(Borders::ALL).title("AnyDataset TUI"))
+    let titles = vec!["Main", "Upload", "Process", "Settings", "Job Status", "Usage", "Review", "Jobs", "Datasets", "Results", "Network", "Storage", "Debug", "Logs", "Stats"];
+    let tabs = Tabs::new(titles.into_iter().map(Line::from).collect::<Vec<_>>())
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "AnyDataset TUI [{}]{}",
+            app.config.active_workspace(),
+            if app.recorder.path().is_some() { " ● REC" } else { "" },
+        )))
         .select(match app.state {
             AppState::Main => 0,
             AppState::Upload => 1,
             AppState::Process => 2,
             AppState::Settings => 3,
             AppState::JobStatus => 4,
+            AppState::Usage => 5,
+            AppState::Review => 6,
+            AppState::Jobs => 7,
+            AppState::Datasets => 8,
+            AppState::Results => 9,
+            AppState::Network => 10,
+            AppState::Storage => 11,
+            AppState::Debug => 12,
+            AppState::Logs => 13,
+            AppState::Stats => 14,
         })
-        .style(Style::default().fg(Color::White))
-        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+        .style(Style::default().fg(theme.text))
+        .highlight_style(Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD));
     f.render_widget(tabs, chunks[0]);
 
     // Draw the content based on the current app state
@@ -43,57 +75,795 @@ pub fn ui(f: &mut Frame, app: &mut App) {
         AppState::Process => draw_process(f, app, chunks[1]),
         AppState::Settings => draw_settings(f, app, chunks[1]),
         AppState::JobStatus => draw_job_status(f, app, chunks[1]),
+        AppState::Usage => draw_usage(f, app, chunks[1]),
+        AppState::Review => draw_review(f, app, chunks[1]),
+        AppState::Jobs => draw_jobs(f, app, chunks[1]),
+        AppState::Datasets => draw_datasets(f, app, chunks[1]),
+        AppState::Results => draw_results(f, app, chunks[1]),
+        AppState::Network => draw_network(f, app, chunks[1]),
+        AppState::Storage => draw_storage(f, app, chunks[1]),
+        AppState::Debug => draw_debug(f, app, chunks[1]),
+        AppState::Logs => draw_logs(f, app, chunks[1]),
+        AppState::Stats => draw_stats(f, app, chunks[1]),
     }
 
     // Draw the status bar
     draw_status_bar(f, app, chunks[2]);
+
+    if let Some(dialog) = &app.download_dialog {
+        draw_download_dialog(f, theme, dialog, chunks[1]);
+    }
+    if let Some(dialog) = &app.quota_dialog {
+        draw_quota_dialog(f, theme, dialog, chunks[1]);
+    }
+    if let Some(dialog) = &app.preflight_dialog {
+        draw_preflight_dialog(f, theme, dialog, chunks[1]);
+    }
+    if let Some(dialog) = &app.cancel_dialog {
+        draw_cancel_dialog(f, app, dialog, chunks[1]);
+    }
+    if let Some(job) = &app.budget_prompt {
+        draw_budget_prompt(f, theme, job, chunks[1]);
+    }
+    if let Some(editor) = &app.column_editor {
+        draw_column_editor(f, theme, editor, chunks[1]);
+    }
+    if let Some(wizard) = &app.assembly {
+        draw_assembly_wizard(f, theme, wizard, chunks[1]);
+    }
+    if let Some(tuner) = &app.chunk_tuner {
+        draw_chunk_tuner(f, theme, tuner, chunks[1]);
+    }
+    if let Some(editor) = &app.sampling_editor {
+        draw_sampling_editor(f, theme, editor, chunks[1]);
+    }
+    if let Some(picker) = &app.preset_picker {
+        draw_preset_picker(f, app, picker, chunks[1]);
+    }
+    if let Some(picker) = &app.backend_picker {
+        draw_backend_picker(f, app, picker, chunks[1]);
+    }
+    if let Some(manager) = &app.key_manager {
+        draw_key_manager(f, theme, manager, chunks[1]);
+    }
+
+    // Onboarding hints are drawn on top of everything else
+    if app.tutorial.is_active() {
+        draw_tutorial(f, app, chunks[1]);
+    }
+
+    app.capabilities.simplify(f.buffer_mut());
+}
+
+fn draw_download_dialog(f: &mut Frame, theme: &Theme, dialog: &DownloadDialog, area: Rect) {
+    let height = (dialog.actions.len() as u16 + 7).min(area.height);
+    let popup = centered_rect(60, height, area);
+    f.render_widget(Clear, popup);
+
+    let mut lines = vec![
+        Line::from(format!("Download results of {}", dialog.job_id)),
+        Line::from(""),
+    ];
+    if dialog.actions.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("No post-download actions configured for '{}'", dialog.preset),
+            Style::default().fg(theme.muted),
+        )));
+    }
+    for (i, (action, enabled)) in dialog.actions.iter().enumerate() {
+        let marker = if *enabled { "[x]" } else { "[ ]" };
+        let style = if i == dialog.selected {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        lines.push(Line::from(Span::styled(format!("{} {}", marker, action.label()), style)));
+    }
+    lines.push(Line::from(""));
+    if dialog.ignore_disk_space {
+        lines.push(Line::from(Span::styled(
+            "Disk-space check disabled for this download",
+            Style::default().fg(theme.error),
+        )));
+    }
+    lines.push(Line::from(Span::styled(
+        "Space: toggle | f: ignore disk space | Enter: download | Esc: cancel",
+        Style::default().fg(theme.muted),
+    )));
+
+    let paragraph = Paragraph::new(Text::from(lines))
+        .block(Block::default().borders(Borders::ALL).title("Download"));
+    f.render_widget(paragraph, popup);
+}
+
+fn draw_column_editor(f: &mut Frame, theme: &Theme, editor: &ColumnEditor, area: Rect) {
+    let height = (editor.entries.len() as u16 + 7).min(area.height);
+    let popup = centered_rect(70, height, area);
+    f.render_widget(Clear, popup);
+
+    let mut lines = vec![
+        Line::from(format!("Export {} as {}", editor.dataset.display(), editor.format)),
+        Line::from(""),
+    ];
+    for (i, (column, included)) in editor.entries.iter().enumerate() {
+        let marker = if *included { "[x]" } else { "[ ]" };
+        let style = if i == editor.selected {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else if *included {
+            Style::default().fg(theme.text)
+        } else {
+            Style::default().fg(theme.muted)
+        };
+        lines.push(Line::from(Span::styled(
+            format!("{} {:<32} {:<16} {}", marker, column.source, column.header(), column.field_type),
+            style,
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!(
+            "Space: include | u/d: move | t: type | f: format | s: save to preset '{}' | Enter: export | Esc: cancel",
+            editor.preset
+        ),
+        Style::default().fg(theme.muted),
+    )));
+
+    let paragraph = Paragraph::new(Text::from(lines))
+        .wrap(Wrap { trim: true })
+        .block(Block::default().borders(Borders::ALL).title("Columns"));
+    f.render_widget(paragraph, popup);
+}
+
+fn draw_assembly_wizard(f: &mut Frame, theme: &Theme, wizard: &AssemblyWizard, area: Rect) {
+    let popup = centered_rect(80, area.height.saturating_sub(2).max(3), area);
+    f.render_widget(Clear, popup);
+
+    let steps: Vec<Span> = Step::ALL.iter().enumerate()
+        .flat_map(|(i, step)| {
+            let style = if *step == wizard.step {
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.muted)
+            };
+            [Span::styled(format!("{}. {}", i + 1, step.title()), style), Span::raw("  ")]
+        })
+        .collect();
+    let mut lines = vec![Line::from(steps), Line::from("")];
+    let row_style = |i: usize| if i == wizard.selected {
+        Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.text)
+    };
+    let counts = &wizard.preview.counts;
+
+    match wizard.step {
+        Step::Sources => {
+            for (i, source) in wizard.sources.iter().enumerate() {
+                let marker = if source.included { "[x]" } else { "[ ]" };
+                lines.push(Line::from(Span::styled(
+                    format!("{} {:<24} {:<12} {:<20} {:>7} record(s)", marker, source.label, source.processing_type, source.model, source.records.len()),
+                    row_style(i),
+                )));
+            }
+        },
+        Step::Filters => {
+            let filters = &wizard.options.filters;
+            let values = [
+                filters.min_words.to_string(),
+                if filters.max_words == 0 { "no limit".to_string() } else { filters.max_words.to_string() },
+                if filters.drop_flagged { "yes".to_string() } else { "no".to_string() },
+                format!("{}{}", wizard.require.value(), if wizard.selected == 3 { "_" } else { "" }),
+            ];
+            for (i, (field, value)) in FILTER_FIELDS.iter().zip(values).enumerate() {
+                lines.push(Line::from(Span::styled(format!("{:<24} {}", field, value), row_style(i))));
+            }
+        },
+        Step::Weights => {
+            let included = wizard.sources.iter().zip(counts).filter(|(source, _)| source.included);
+            for (i, (source, count)) in included.enumerate() {
+                let kept = count.available - count.filtered - count.duplicates;
+                lines.push(Line::from(Span::styled(
+                    format!("{:<24} weight {:.1}  {:>7} of {} kept record(s)", source.label, source.weight, count.taken, kept),
+                    row_style(i),
+                )));
+            }
+        },
+        Step::Split => {
+            let options = &wizard.options;
+            let values = [
+                if options.dedup { "on".to_string() } else { "off".to_string() },
+                format!("{:.0}%", options.val_fraction * 100.0),
+                options.seed.to_string(),
+            ];
+            for (i, (field, value)) in SPLIT_FIELDS.iter().zip(values).enumerate() {
+                lines.push(Line::from(Span::styled(format!("{:<24} {}", field, value), row_style(i))));
+            }
+            let duplicates: usize = counts.iter().map(|count| count.duplicates).sum();
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(format!("{} duplicate(s) dropped", duplicates), Style::default().fg(theme.muted))));
+        },
+        Step::Export => {
+            lines.push(Line::from(format!("Output directory: {}_", wizard.output.value())));
+            lines.push(Line::from(Span::styled(
+                "train.jsonl, val.jsonl and a README.md dataset card are written there",
+                Style::default().fg(theme.muted),
+            )));
+        },
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!("Corpus: {} train / {} validation record(s)", wizard.preview.train.len(), wizard.preview.val.len()),
+        Style::default().fg(theme.accent),
+    )));
+    if let Some(error) = &wizard.error {
+        lines.push(Line::from(Span::styled(error.clone(), Style::default().fg(theme.error))));
+    }
+    let help = match wizard.step {
+        Step::Sources => "Space: pick job",
+        Step::Filters => "←/→: change | Space: toggle | type field=value",
+        Step::Weights => "←/→: weight",
+        Step::Split => "Space: toggle | ←/→: change",
+        Step::Export => "type a directory | Enter: export",
+    };
+    lines.push(Line::from(Span::styled(
+        format!("{} | ↑/↓: row | Tab/Enter: next step | Shift+Tab: back | Esc: close", help),
+        Style::default().fg(theme.muted),
+    )));
+
+    let paragraph = Paragraph::new(Text::from(lines))
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title("Assemble dataset"));
+    f.render_widget(paragraph, popup);
+}
+
+fn draw_sampling_editor(f: &mut Frame, theme: &Theme, editor: &SamplingEditor, area: Rect) {
+    let popup = centered_rect(50, 10.min(area.height), area);
+    f.render_widget(Clear, popup);
+
+    let mut lines = Vec::new();
+    for (i, (field, input)) in SamplingField::ALL.iter().zip(&editor.inputs).enumerate() {
+        let (style, cursor) = if i == editor.selected {
+            (Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD), "_")
+        } else {
+            (Style::default().fg(theme.text), "")
+        };
+        let value = if input.value().is_empty() && i != editor.selected { "default" } else { input.value() };
+        lines.push(Line::from(Span::styled(format!("{:<24} {}{}", field.label(), value, cursor), style)));
+    }
+    lines.push(Line::from(""));
+    match &editor.error {
+        Some(error) => lines.push(Line::from(Span::styled(error.clone(), Style::default().fg(theme.error)))),
+        None => lines.push(Line::from(Span::styled("Empty fields keep the provider's default", Style::default().fg(theme.muted)))),
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "↑/↓: field | Backspace: delete | Enter: apply | Esc: cancel",
+        Style::default().fg(theme.muted),
+    )));
+
+    let paragraph = Paragraph::new(Text::from(lines))
+        .wrap(Wrap { trim: true })
+        .block(Block::default().borders(Borders::ALL).title("Advanced parameters"));
+    f.render_widget(paragraph, popup);
+}
+
+fn draw_preset_picker(f: &mut Frame, app: &App, picker: &PresetPicker, area: Rect) {
+    let theme = &app.theme;
+    let height = (picker.names.len() as u16 + 7).min(area.height);
+    let popup = centered_rect(60, height, area);
+    f.render_widget(Clear, popup);
+
+    let presets = app.effective_presets();
+    let mut lines = Vec::new();
+    if picker.names.is_empty() {
+        lines.push(Line::from(Span::styled("No presets yet - press 's' to save the current configuration", Style::default().fg(theme.muted))));
+    }
+    for (i, name) in picker.names.iter().enumerate() {
+        let style = if i == picker.selected {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        let key = if i < 9 { format!("{}.", i + 1) } else { "  ".to_string() };
+        let (origin, description) = presets.get(name)
+            .map(|(preset, origin)| (origin.label(), preset.description.clone().map(|d| format!(" - {}", d)).unwrap_or_default()))
+            .unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} {}", key, name), style),
+            Span::styled(format!(" [{}]{}", origin, description), Style::default().fg(theme.muted)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    match &picker.naming {
+        Some(name) => {
+            lines.push(Line::from(Span::styled(format!("Save as: {}_", name.value()), Style::default().fg(theme.highlight))));
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Enter: save | Esc: back", Style::default().fg(theme.muted))));
+        },
+        None => lines.push(Line::from(Span::styled(
+            "1-9/Enter: apply | s: save current | d: delete local | Esc: close",
+            Style::default().fg(theme.muted),
+        ))),
+    }
+
+    let paragraph = Paragraph::new(Text::from(lines))
+        .block(Block::default().borders(Borders::ALL).title("Presets"));
+    f.render_widget(paragraph, popup);
+}
+
+fn draw_chunk_tuner(f: &mut Frame, theme: &Theme, tuner: &ChunkTuner, area: Rect) {
+    let popup = centered_rect(90, area.height, area);
+    f.render_widget(Clear, popup);
+    let block = Block::default().borders(Borders::ALL).title(format!("Chunks of {}", tuner.file));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    f.render_widget(Paragraph::new(tuner.summary()).style(Style::default().fg(theme.text)), rows[0]);
+    f.render_widget(Paragraph::new(Span::styled(
+        "↑/↓: chunk | Tab: strategy | +/-: chunk size | ]/[: context | s: save to config | Esc: close",
+        Style::default().fg(theme.muted),
+    )), rows[2]);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(30), Constraint::Min(20)])
+        .split(rows[1]);
+    // Keep the selected chunk in view in a long list
+    let visible = columns[0].height.saturating_sub(2) as usize;
+    let first = tuner.selected.saturating_sub(visible.saturating_sub(1));
+    let items: Vec<ListItem> = tuner.chunks.iter().enumerate().skip(first).map(|(i, chunk)| {
+        let (words, tokens) = ChunkTuner::size_of(chunk);
+        let style = if i == tuner.selected {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        ListItem::new(Span::styled(format!("#{:<4} {:>6} w {:>6} tok", i + 1, words, tokens), style))
+    }).collect();
+    f.render_widget(List::new(items).block(Block::default().borders(Borders::ALL).title("Sizes")), columns[0]);
+
+    // The selected chunk and the ones after it, each boundary marked, context dimmed
+    let mut lines = Vec::new();
+    if let Some(chunk) = tuner.chunks.get(tuner.selected) {
+        if !chunk.before.is_empty() {
+            lines.push(Line::from(Span::styled(format!("… {}", chunk.before), Style::default().fg(theme.muted))));
+        }
+    }
+    for (i, chunk) in tuner.chunks.iter().enumerate().skip(tuner.selected) {
+        let (words, tokens) = ChunkTuner::size_of(chunk);
+        lines.push(Line::from(Span::styled(
+            format!("── #{} · {} words · ~{} tokens ──", i + 1, words, tokens),
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        )));
+        let style = if i == tuner.selected {
+            Style::default().fg(theme.highlight)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        lines.push(Line::from(Span::styled(chunk.text.clone(), style)));
+        if i == tuner.selected && !chunk.after.is_empty() {
+            lines.push(Line::from(Span::styled(format!("{} …", chunk.after), Style::default().fg(theme.muted))));
+        }
+        if lines.len() > rows[1].height as usize {
+            break;
+        }
+    }
+    if tuner.chunks.is_empty() {
+        lines.push(Line::from(Span::styled("No text in this file", Style::default().fg(theme.muted))));
+    }
+    let text = Paragraph::new(Text::from(lines))
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title("Boundaries"));
+    f.render_widget(text, columns[1]);
+}
+
+fn draw_backend_picker(f: &mut Frame, app: &App, picker: &BackendPicker, area: Rect) {
+    let theme = &app.theme;
+    let height = (picker.backends.len() as u16 + 7).min(area.height);
+    let popup = centered_rect(70, height, area);
+    f.render_widget(Clear, popup);
+
+    let mut lines = vec![
+        Line::from(format!("Current backend: {}", app.backend_url)),
+        Line::from(""),
+    ];
+    if let Some(err) = &picker.error {
+        lines.push(Line::from(Span::styled(err.clone(), Style::default().fg(theme.error))));
+    } else if picker.scanning && picker.backends.is_empty() {
+        lines.push(Line::from(Span::styled("Searching the local network…", Style::default().fg(theme.warning))));
+    } else if picker.backends.is_empty() {
+        lines.push(Line::from(Span::styled("No backends found on the local network", Style::default().fg(theme.muted))));
+    }
+    for (i, backend) in picker.backends.iter().enumerate() {
+        let style = if i == picker.selected {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        let version = backend.version.as_deref().map(|v| format!(" v{}", v)).unwrap_or_default();
+        lines.push(Line::from(Span::styled(format!("{:<24} {}{}", backend.name, backend.url, version), style)));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Enter: use this backend | r: search again | Esc: close",
+        Style::default().fg(theme.muted),
+    )));
+
+    let title = if picker.scanning { "Backends on the network (searching…)" } else { "Backends on the network" };
+    let paragraph = Paragraph::new(Text::from(lines))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, popup);
+}
+
+fn draw_key_manager(f: &mut Frame, theme: &Theme, manager: &KeyManager, area: Rect) {
+    let height = (manager.entries.len() as u16 + 7).min(area.height);
+    let popup = centered_rect(70, height, area);
+    f.render_widget(Clear, popup);
+
+    let mut lines = Vec::new();
+    for (i, entry) in manager.entries.iter().enumerate() {
+        let style = if i == manager.selected {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        let key = match (&manager.editing, &entry.key) {
+            (Some(typed), _) if i == manager.selected => format!("{}▏", "•".repeat(typed.chars().count())),
+            (_, Some((masked, source))) => format!("{} ({})", masked, source),
+            (_, None) => format!("not set (keyring or ${})", credentials::env_var(&entry.provider)),
+        };
+        let (check, color) = match &entry.check {
+            _ if entry.checking => ("checking…".to_string(), theme.warning),
+            Some(Ok(())) => ("✓ accepted".to_string(), theme.success),
+            Some(Err(err)) => (format!("✗ {}", err), theme.error),
+            None => (String::new(), theme.muted),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<12} {}  ", entry.provider, key), style),
+            Span::styled(check, Style::default().fg(color)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        if manager.editing.is_some() {
+            "Enter: save to the OS keyring and check | Esc: cancel"
+        } else {
+            "Enter: new key | v: check key | d: remove stored key | Esc: close"
+        },
+        Style::default().fg(theme.muted),
+    )));
+
+    let paragraph = Paragraph::new(Text::from(lines))
+        .block(Block::default().borders(Borders::ALL).title("Provider API keys"));
+    f.render_widget(paragraph, popup);
+}
+
+fn draw_quota_dialog(f: &mut Frame, theme: &Theme, dialog: &QuotaDialog, area: Rect) {
+    let popup = centered_rect(60, 9.min(area.height), area);
+    f.render_widget(Clear, popup);
+
+    let text = Text::from(vec![
+        Line::from(Span::styled(dialog.exceeded.to_string(), Style::default().fg(theme.error).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(format!("{} was not submitted.", dialog.file)),
+        Line::from("Raise the limit in [quotas] of config.toml, switch provider, or override for this job only."),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Shift+O: submit anyway | Esc: cancel",
+            Style::default().fg(theme.muted),
+        )),
+    ]);
+    let paragraph = Paragraph::new(text)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.error))
+                .title("Quota Reached"),
+        );
+    f.render_widget(paragraph, popup);
+}
+
+fn draw_preflight_dialog(f: &mut Frame, theme: &Theme, dialog: &PreflightDialog, area: Rect) {
+    let mut lines = vec![
+        Line::from(dialog.report.summary()),
+        Line::from(""),
+    ];
+    for (i, report) in dialog.report.flagged().iter().enumerate() {
+        let mark = if dialog.excluded.contains(&report.file) { "[x]" } else { "[ ]" };
+        let style = if i == dialog.selected {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(Span::styled(format!("{} {}", mark, report.file), style)));
+        for issue in &report.issues {
+            lines.push(Line::from(Span::styled(format!("      {}", issue), Style::default().fg(theme.warning))));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "↑/↓: select | Space: exclude/include | a: exclude all flagged | Enter: process | Esc: cancel",
+        Style::default().fg(theme.muted),
+    )));
+
+    let popup = centered_rect(80, (lines.len() as u16 + 2).min(area.height), area);
+    f.render_widget(Clear, popup);
+    let paragraph = Paragraph::new(Text::from(lines))
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.warning))
+                .title("Pre-flight Check"),
+        );
+    f.render_widget(paragraph, popup);
+}
+
+fn draw_cancel_dialog(f: &mut Frame, app: &App, dialog: &CancelDialog, area: Rect) {
+    let theme = &app.theme;
+    let popup = centered_rect(60, 8.min(area.height), area);
+    f.render_widget(Clear, popup);
+
+    let progress = app.job_progress
+        .map(|(current, total)| format!(" at {}/{}", current, total))
+        .unwrap_or_default();
+    let text = Text::from(vec![
+        Line::from(format!("Cancel job {}{}?", dialog.job_id, progress)),
+        Line::from(""),
+        Line::from("The backend stops the job; results produced so far may be lost."),
+        Line::from(""),
+        Line::from(Span::styled(
+            "y/Enter: cancel the job | n/Esc: keep it running",
+            Style::default().fg(theme.muted),
+        )),
+    ]);
+    let paragraph = Paragraph::new(text)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.error))
+                .title("Cancel Job"),
+        );
+    f.render_widget(paragraph, popup);
+}
+
+fn draw_budget_prompt(f: &mut Frame, theme: &Theme, job: &LocalJob, area: Rect) {
+    let popup = centered_rect(60, 9.min(area.height), area);
+    f.render_widget(Clear, popup);
+
+    let done = job.total_files - job.run.pending.len();
+    let text = Text::from(vec![
+        Line::from(Span::styled(
+            format!("Budget reached: ${:.2} of ${:.2} spent", job.cap.spent_usd, job.cap.max_usd),
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(format!("{}/{} file(s) of {} processed.", done, job.total_files, job.job_id)),
+        Line::from(format!("Partial results saved to {}", job.output.display())),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("c: continue with another ${:.2} | Esc: stop here", job.cap_step_usd),
+            Style::default().fg(theme.muted),
+        )),
+    ]);
+    let paragraph = Paragraph::new(text)
+        .wrap(Wrap { trim: true })
+        .block(Block::default().borders(Borders::ALL).title("Continue?"));
+    f.render_widget(paragraph, popup);
+}
+
+fn draw_tutorial(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let (Some(step), Some((index, total))) = (app.tutorial.current(), app.tutorial.progress()) else {
+        return;
+    };
+
+    let popup = centered_rect(70, 7.min(area.height), area);
+    f.render_widget(Clear, popup);
+
+    let text = Text::from(vec![
+        Line::from(step.hint()),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter: next | Left: back | Esc: skip tutorial",
+            Style::default().fg(theme.muted),
+        )),
+    ]);
+    let hint = Paragraph::new(text)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(theme.text))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD))
+                .title(format!("Tutorial {}/{}: {}", index, total, step.title())),
+        );
+    f.render_widget(hint, popup);
 }
 
 fn draw_main(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let block = Block::default()
-        .title("Welcome to AnyDataset TUI")
+        .title("Dashboard")
         .borders(Borders::ALL);
     f.render_widget(block, area);
 
     let inner_area = inner_area(area);
-    let text = Text::from(vec![
-        Line::from("AnyDataset Terminal UI Client"),
-        Line::from(""),
-        Line::from("Press keys to navigate:"),
-        Line::from("- 'u': Upload files"),
-        Line::from("- 'p': Process files"),
-        Line::from("- 's': Settings"),
-        Line::from("- 'j': Job Status"),
-        Line::from("- 'q': Quit"),
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(60),  // Recent jobs
+            Constraint::Percentage(40),  // Health, spend, quick actions
+        ])
+        .split(inner_area);
+
+    // Recent jobs from the local history
+    let recent = app.history.recent(10);
+    let items: Vec<ListItem> = if recent.is_empty() {
+        vec![ListItem::new("No jobs yet - upload a file and process it to get started")
+            .style(Style::default().fg(theme.muted))]
+    } else {
+        recent.iter().map(|entry| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<11}", entry.status), Style::default().fg(job_status_color(theme, &entry.status))),
+                Span::raw(format!(
+                    "{}  {}  {}",
+                    entry.submitted_at.with_timezone(&chrono::Local).format("%m-%d %H:%M"),
+                    entry.processing_type,
+                    entry.file,
+                )),
+            ]))
+        }).collect()
+    };
+    let list = List::new(items)
+        .block(Block::default().title("Recent Jobs").borders(Borders::ALL));
+    f.render_widget(list, columns[0]);
+
+    let side = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(6),  // Backend health
+            Constraint::Length(3),  // Spend
+            Constraint::Min(0),     // Quick actions
+        ])
+        .split(columns[1]);
+
+    let mut health_lines = vec![Line::from(format!("URL: {}", app.backend_url))];
+    health_lines.extend(version_lines(app));
+    let health = Paragraph::new(Text::from(health_lines))
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(theme.text))
+        .block(Block::default().title("Backend").borders(Borders::ALL));
+    f.render_widget(health, side[0]);
+
+    let spend = Paragraph::new(format!("${:.2}", app.history.spend_this_month()))
+        .style(Style::default().fg(theme.accent))
+        .block(Block::default().title("Spend This Month").borders(Borders::ALL));
+    f.render_widget(spend, side[1]);
+
+    let actions = Text::from(vec![
+        Line::from("1: Re-run last job settings"),
+        Line::from("2: Resume last job"),
+        Line::from("x: Export last run as CSV/Parquet"),
+        Line::from("a: Assemble dataset from finished jobs"),
         Line::from(""),
-        Line::from(format!("Backend URL: {}", app.backend_url)),
+        Line::from("u: Upload  p: Process  s: Settings"),
+        Line::from("j: Job Status  g: Usage  r: Review"),
+        Line::from("l: Jobs on the backend  d: Datasets"),
+        Line::from("v: View downloaded results  n: Network"),
+        Line::from("o: Storage  b: Debug  L: Logs"),
+        Line::from("t: Tutorial"),
+        Line::from("q: Quit"),
     ]);
+    let actions = Paragraph::new(actions)
+        .style(Style::default().fg(theme.text))
+        .block(Block::default().title("Quick Actions").borders(Borders::ALL));
+    f.render_widget(actions, side[2]);
+}
 
-    let paragraph = Paragraph::new(text)
-        .style(Style::default().fg(Color::White))
-        .block(Block::default());
-    f.render_widget(paragraph, inner_area);
+fn job_status_color(theme: &Theme, status: &str) -> Color {
+    match status {
+        "completed" => theme.success,
+        "failed" | "cancelled" => theme.error,
+        _ => theme.warning,
+    }
+}
+
+fn version_lines(app: &App) -> Vec<Line<'static>> {
+    let theme = &app.theme;
+    let client_version = env!("CARGO_PKG_VERSION");
+    match (&app.backend_version, &app.backend_error) {
+        (Some(backend), _) => {
+            let build = backend.build.as_deref().map(|b| format!(" (build {})", b)).unwrap_or_default();
+            let mut lines = vec![Line::from(format!(
+                "Client v{} | Backend v{}{}", client_version, backend.version, build
+            ))];
+            match check_compatibility(client_version, &backend.version) {
+                Compatibility::Tested => {},
+                Compatibility::Untested => lines.push(Line::from(Span::styled(
+                    "Warning: this client/backend pair is outside the tested compatibility matrix",
+                    Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
+                ))),
+                Compatibility::Unknown => lines.push(Line::from(Span::styled(
+                    "Warning: backend reported an unrecognized version",
+                    Style::default().fg(theme.warning),
+                ))),
+            }
+            lines
+        },
+        (None, Some(err)) => vec![
+            Line::from(format!("Client v{}", client_version)),
+            Line::from(Span::styled(format!("Backend unreachable: {}", err), Style::default().fg(theme.error))),
+        ],
+        (None, None) => vec![Line::from(format!("Client v{} | Backend: checking...", client_version))],
+    }
 }
 
 fn draw_upload(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let block = Block::default()
         .title("Upload Files")
         .borders(Borders::ALL);
     f.render_widget(block, area);
 
     let inner_area = inner_area(area);
+    let uploads_height = if app.uploads.is_empty() { 0 } else { app.uploads.len() as u16 + 2 };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3),  // Instructions
-            Constraint::Min(0),     // File list
+            Constraint::Min(0),     // Browser and uploaded files
+            Constraint::Length(uploads_height),  // Uploads in progress
         ])
         .split(inner_area);
 
-    let instructions = Paragraph::new("Press 'f' to simulate file upload, Esc to return")
-        .style(Style::default().fg(Color::White));
+    let browser = &app.file_browser;
+    let instructions = Paragraph::new(format!(
+        "Enter: open directory / upload file, Backspace: parent directory, '.': hidden files ({}), 's': sort by {}, 'r': refresh, Esc to return",
+        if browser.show_hidden { "shown" } else { "hidden" },
+        browser.sort
+    ))
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(theme.text));
     f.render_widget(instructions, chunks[0]);
 
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(chunks[1]);
+
+    let items: Vec<ListItem> = match &browser.error {
+        Some(err) => vec![ListItem::new(format!("Cannot read directory: {}", err)).style(Style::default().fg(theme.error))],
+        None => browser.entries.iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let label = if entry.is_dir {
+                    format!("{}/", entry.name)
+                } else {
+                    format!("{:<40} {:>10}", entry.name, diskspace::format_bytes(entry.size))
+                };
+                let style = if i == browser.selected {
+                    Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+                } else if entry.is_dir {
+                    Style::default().fg(theme.accent)
+                } else {
+                    Style::default().fg(theme.text)
+                };
+                ListItem::new(label).style(style)
+            })
+            .collect(),
+    };
+    let list = List::new(items)
+        .block(Block::default().title(browser.dir.display().to_string()).borders(Borders::ALL));
+    f.render_widget(list, columns[0]);
+
     // Draw uploaded files
     let items: Vec<ListItem> = app
         .uploaded_files
@@ -101,9 +871,9 @@ fn draw_upload(f: &mut Frame, app: &App, area: Rect) {
         .enumerate()
         .map(|(i, file)| {
             let style = if Some(i) == app.selected_file_index {
-                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
             } else {
-                Style::default().fg(Color::White)
+                Style::default().fg(theme.text)
             };
             ListItem::new(file.clone()).style(style)
         })
@@ -111,10 +881,55 @@ fn draw_upload(f: &mut Frame, app: &App, area: Rect) {
 
     let list = List::new(items)
         .block(Block::default().title("Uploaded Files").borders(Borders::ALL));
-    f.render_widget(list, chunks[1]);
+    f.render_widget(list, columns[1]);
+
+    if !app.uploads.is_empty() {
+        draw_uploads(f, app, chunks[2]);
+    }
+}
+
+/// One gauge per upload in progress, with its rate and time left
+fn draw_uploads(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let block = Block::default().title("Uploading").borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let now = std::time::Instant::now();
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); app.uploads.len()])
+        .split(inner);
+    for ((file, upload), row) in app.uploads.iter().zip(rows.iter()) {
+        let name = std::path::Path::new(file).file_name().map_or_else(|| file.clone(), |n| n.to_string_lossy().into_owned());
+        let eta = upload.eta(now).map_or_else(|| "-".to_string(), format_eta);
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(theme.accent))
+            .ratio(upload.ratio())
+            .label(format!(
+                "↑ {}  {} / {}  {}/s  ETA {}",
+                name,
+                diskspace::format_bytes(upload.sent),
+                diskspace::format_bytes(upload.total),
+                diskspace::format_bytes(upload.rate(now) as u64),
+                eta
+            ));
+        f.render_widget(gauge, *row);
+    }
+}
+
+/// Time left of a transfer, e.g. `45s`, `3m 05s` or `1h 20m`
+fn format_eta(eta: std::time::Duration) -> String {
+    let secs = eta.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
 }
 
 fn draw_process(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let block = Block::default()
         .title("Process Files")
         .borders(Borders::ALL);
@@ -124,8 +939,9 @@ fn draw_process(f: &mut Frame, app: &App, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(4),  // Processing type
-            Constraint::Length(2),  // Instructions
+            Constraint::Length(5),  // Processing type and output format
+            Constraint::Length(3),  // Instructions
+            Constraint::Length(3),  // Keywords
             Constraint::Min(0),     // File list
             Constraint::Length(3),  // Progress bar (if active)
         ])
@@ -137,9 +953,9 @@ fn draw_process(f: &mut Frame, app: &App, area: Rect) {
         .enumerate()
         .map(|(i, pt)| {
             let style = if pt == &app.processing_type {
-                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
             } else {
-                Style::default().fg(Color::White)
+                Style::default().fg(theme.text)
             };
             Span::styled(format!("{}. {}", i + 1, pt.to_str()), style)
         })
@@ -149,15 +965,26 @@ fn draw_process(f: &mut Frame, app: &App, area: Rect) {
     let processing_type_para = Paragraph::new(Text::from(vec![
         Line::from("Processing Type:"),
         processing_text,
+        Line::from(format!("Output format: {} ('f' to change)", app.output_format)),
     ]))
     .block(Block::default().borders(Borders::ALL));
     f.render_widget(processing_type_para, chunks[0]);
 
     // Instructions
-    let instructions = Paragraph::new("Select file with Up/Down, change processing type with 1-4, press 'p' to process")
-        .style(Style::default().fg(Color::White));
+    let spend_cap = match app.job_spend_cap {
+        Some(cap) => format!("Spend cap: ${:.2} ('+'/'-' to change, '0' to remove)", cap),
+        None => "Spend cap: none (press '+' to set one)".to_string(),
+    };
+    let instructions = Paragraph::new(Text::from(vec![
+        Line::from("Select file with Up/Down, change processing type with 1-4, press 'p' to process, 'v' to preview a sample or 'k' to tune chunks"),
+        Line::from(spend_cap),
+        Line::from(format!("Sampling: {} ('a' to edit), 'P' for presets", app.sampling.describe())),
+    ]))
+        .style(Style::default().fg(theme.text));
     f.render_widget(instructions, chunks[1]);
 
+    draw_keywords(f, app, chunks[2]);
+
     // File list
     let items: Vec<ListItem> = app
         .uploaded_files
@@ -165,9 +992,9 @@ fn draw_process(f: &mut Frame, app: &App, area: Rect) {
         .enumerate()
         .map(|(i, file)| {
             let style = if Some(i) == app.selected_file_index {
-                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
             } else {
-                Style::default().fg(Color::White)
+                Style::default().fg(theme.text)
             };
             ListItem::new(file.clone()).style(style)
         })
@@ -175,20 +1002,61 @@ fn draw_process(f: &mut Frame, app: &App, area: Rect) {
 
     let list = List::new(items)
         .block(Block::default().title("Select File").borders(Borders::ALL));
-    f.render_widget(list, chunks[2]);
+    f.render_widget(list, chunks[3]);
 
     // Progress bar if job is active
     if let (Some(job_id), Some((current, total))) = (&app.current_job_id, app.job_progress) {
         let progress_percent = if total > 0 { (current as f64 / total as f64) * 100.0 } else { 0.0 };
         let gauge = Gauge::default()
-            .block(Block::default().title(format!("Job: {} - Progress", job_id)).borders(Borders::ALL))
-            .gauge_style(Style::default().fg(Color::Cyan))
+            .block(Block::default().title(job_progress_title(app, job_id)).borders(Borders::ALL))
+            .gauge_style(Style::default().fg(theme.accent))
             .percent(progress_percent as u16);
-        f.render_widget(gauge, chunks[3]);
+        f.render_widget(gauge, chunks[4]);
+    }
+}
+
+fn job_progress_title(app: &App, job_id: &str) -> String {
+    match app.job_queue.len() {
+        0 => format!("Job: {} - Progress", job_id),
+        queued => format!("Job: {} - Progress ({} queued)", job_id, queued),
     }
 }
 
+/// Keywords sent with the job as chips, followed by the input while the field is focused
+fn draw_keywords(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let editor = app.keyword_editor.as_ref();
+    let mut spans = Vec::new();
+    for (i, keyword) in app.keywords.iter().enumerate() {
+        let style = if editor.is_some_and(|e| e.selected == Some(i)) {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::REVERSED | Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.accent).add_modifier(Modifier::REVERSED)
+        };
+        spans.push(Span::styled(format!(" {} ", keyword), style));
+        spans.push(Span::raw(" "));
+    }
+    let title = match editor {
+        Some(editor) => {
+            let cursor = if editor.selected.is_none() { "_" } else { "" };
+            spans.push(Span::styled(format!("{}{}", editor.input.value(), cursor), Style::default().fg(theme.highlight)));
+            "Keywords (Enter/',': add | ←/→: pick | Backspace/Delete: remove | Esc: done)"
+        },
+        None => {
+            if app.keywords.is_empty() {
+                spans.push(Span::styled("none", Style::default().fg(theme.muted)));
+            }
+            "Keywords ('w' to edit)"
+        },
+    };
+    let border = if editor.is_some() { theme.highlight } else { theme.text };
+    let keywords = Paragraph::new(Line::from(spans))
+        .block(Block::default().title(title).borders(Borders::ALL).border_style(Style::default().fg(border)));
+    f.render_widget(keywords, area);
+}
+
 fn draw_settings(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let block = Block::default()
         .title("Settings")
         .borders(Borders::ALL);
@@ -205,10 +1073,16 @@ fn draw_settings(f: &mut Frame, app: &App, area: Rect) {
         .split(inner_area);
 
     // Language settings
-    let lang_text = format!("Language: {} (press 'l' to change)", app.language);
+    let tags = app.config.workspace_tags();
+    let lang_text = format!(
+        "Language: {} (press 'l' to change)    Workspace: {}{} (press 'w' to switch)",
+        app.language,
+        app.config.active_workspace(),
+        if tags.is_empty() { String::new() } else { format!(" [{}]", tags.join(", ")) },
+    );
     let language = Paragraph::new(lang_text)
         .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(theme.text));
     f.render_widget(language, chunks[0]);
 
     // Provider and Model selection
@@ -217,32 +1091,226 @@ fn draw_settings(f: &mut Frame, app: &App, area: Rect) {
     
     let provider_model_text = Text::from(vec![
         Line::from(format!("Provider: {} (press 'p' to change)", current_provider)),
-        Line::from(""),
+        model_catalog_line(app),
         Line::from(format!("Model: {} (press 'm' to change)", current_model)),
     ]);
     
     let provider_model = Paragraph::new(provider_model_text)
         .block(Block::default().title("Provider & Model").borders(Borders::ALL))
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(theme.text));
     f.render_widget(provider_model, chunks[1]);
 
     // Other settings
-    let other_settings_text = Text::from(vec![
+    let mut other_settings_lines = vec![
         Line::from("Other settings:"),
         Line::from(""),
-        Line::from("Backend URL: ").add_span(Span::styled(
-            app.backend_url.clone(),
-            Style::default().fg(Color::Cyan),
+        Line::from("Usage report for this month: 'r' CSV, 'R' JSON"),
+        Line::from(""),
+        preset_sync_line(app),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("Backend URL: "),
+            Span::styled(app.backend_url.clone(), Style::default().fg(theme.accent)),
+        ]),
+        Line::from("'b' to find backends on the local network"),
+        Line::from("'k' to manage provider API keys"),
+        Line::from(format!("'t' to switch the color theme (now: {})", app.config.theme.palette)),
+        Line::from(format!(
+            "Terminal: {}, {} (unicode/color in [theme] override the detection)",
+            if app.capabilities.unicode { "Unicode" } else { "ASCII only" },
+            if app.capabilities.color { "colors" } else { "no colors" },
         )),
-    ]);
+    ];
+    other_settings_lines.extend(backend_latency_lines(app));
     
-    let other_settings = Paragraph::new(other_settings_text)
+    let other_settings = Paragraph::new(Text::from(other_settings_lines))
         .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(theme.text));
     f.render_widget(other_settings, chunks[2]);
 }
 
+fn backend_latency_lines(app: &App) -> Vec<Line<'static>> {
+    let theme = &app.theme;
+    if app.config.backend_mirrors.is_empty() {
+        return Vec::new();
+    }
+    let mut lines = vec![Line::from(format!(
+        "Mirrors: {} (fastest picked at startup: {}) — 'f' to ping now{}",
+        app.config.backend_mirrors.len(),
+        if app.config.pick_fastest_backend { "on" } else { "off" },
+        if app.probing_backends { ", pinging…" } else { "" },
+    ))];
+    for probe in &app.backend_probes {
+        let style = match (probe.healthy(), probe.url == app.backend_url) {
+            (true, true) => Style::default().fg(theme.success),
+            (true, false) => Style::default().fg(theme.text),
+            (false, _) => Style::default().fg(theme.error),
+        };
+        let marker = if probe.url == app.backend_url { "▶" } else { " " };
+        lines.push(Line::from(Span::styled(
+            format!("  {} {} — {}", marker, probe.url, probe.describe()),
+            style,
+        )));
+    }
+    lines
+}
+
+fn model_catalog_line(app: &App) -> Line<'static> {
+    let theme = &app.theme;
+    let warnings = app.model_warnings();
+    if let Some(warning) = warnings.first() {
+        let hint = if warning.replacement.is_some() { " - 'u' to switch" } else { "" };
+        return Line::from(Span::styled(format!("{}{}", warning, hint), Style::default().fg(theme.warning)));
+    }
+    if app.get_current_provider() == Some(ollama::PROVIDER) {
+        let base_url = &app.config.ollama.base_url;
+        let (status, color) = match (&app.ollama_error, app.models.get(ollama::PROVIDER)) {
+            _ if app.ollama_discovery_running => (format!("Ollama at {}: looking for models…", base_url), theme.warning),
+            (Some(err), _) => (format!("Ollama: {}", err), theme.error),
+            (None, Some(models)) if !models.is_empty() => (format!("Ollama at {}: {} model(s) ('M' to refresh)", base_url, models.len()), theme.muted),
+            _ => (format!("Ollama at {}: no models pulled ('M' to refresh)", base_url), theme.warning),
+        };
+        return Line::from(Span::styled(status, Style::default().fg(color)));
+    }
+    let status = match (&app.model_catalog, &app.model_refresh_error) {
+        _ if app.model_refresh_running => "refreshing…".to_string(),
+        (Some(catalog), error) => format!(
+            "updated {}{}",
+            catalog.fetched_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+            if error.is_some() { ", backend unreachable" } else { "" },
+        ),
+        (None, Some(_)) => "built-in, backend unreachable".to_string(),
+        (None, None) => "not fetched yet".to_string(),
+    };
+    Line::from(Span::styled(format!("Model lists: {} ('M' to refresh)", status), Style::default().fg(theme.muted)))
+}
+
+fn preset_sync_line(app: &App) -> Line<'static> {
+    let theme = &app.theme;
+    let presets = app.effective_presets();
+    let templates = app.effective_templates().len();
+    let shared = presets.values().filter(|(_, origin)| *origin != Origin::Local).count();
+    let Some(settings) = &app.config.preset_sync else {
+        return Line::from(format!(
+            "Presets: {} local, templates: {} (no shared source configured)", presets.len(), templates
+        ));
+    };
+
+    let status = if app.preset_sync_running {
+        Span::styled("syncing…".to_string(), Style::default().fg(theme.warning))
+    } else if let Some(reason) = &app.shared_presets.stale_reason {
+        Span::styled(format!("offline, using cached copy ({})", reason), Style::default().fg(theme.error))
+    } else if let Some(fetched_at) = app.shared_presets.fetched_at {
+        Span::styled(
+            format!("updated {}", fetched_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")),
+            Style::default().fg(theme.success),
+        )
+    } else {
+        Span::raw("not synced yet".to_string())
+    };
+
+    Line::from(vec![
+        Span::raw(format!(
+            "Presets: {} ({} shared from {}), templates: {} ", presets.len(), shared, settings.source, templates
+        )),
+        status,
+        Span::raw(" — 'y' to sync now".to_string()),
+    ])
+}
+
+/// Chart line of one provider: legend index, provider, daily points and color
+type UsageSeries<'a> = (usize, &'a String, Vec<(f64, f64)>, Color);
+
+fn draw_usage(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let block = Block::default()
+        .title(format!("Usage - {} over the last {} days", app.usage_metric.label(), app.usage_days))
+        .borders(Borders::ALL);
+    f.render_widget(block, area);
+
+    let inner_area = inner_area(area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(8),     // Daily chart
+            Constraint::Length(7),  // Totals per provider
+        ])
+        .split(inner_area);
+
+    let entries = app.history.entries();
+    let today = chrono::Local::now().date_naive();
+    let metric = app.usage_metric;
+    let providers = usage::providers(entries);
+    if providers.is_empty() {
+        let empty = Paragraph::new("No jobs in the local history yet")
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(empty, chunks[0]);
+        return;
+    }
+
+    // Hidden providers keep their number and color
+    let series: Vec<UsageSeries> = providers.iter().enumerate()
+        .filter(|(_, p)| !app.usage_hidden_providers.contains(*p))
+        .map(|(i, provider)| {
+            let points = usage::daily_totals(entries, Some(provider), app.usage_days, today)
+                .iter()
+                .enumerate()
+                .map(|(day, totals)| (day as f64, metric.value(totals)))
+                .collect();
+            (i, provider, points, theme.chart[i % theme.chart.len()])
+        })
+        .collect();
+
+    let max = series.iter()
+        .flat_map(|(_, _, points, _)| points.iter().map(|(_, y)| *y))
+        .fold(0.0, f64::max)
+        .max(1.0);
+    let datasets = series.iter()
+        .map(|(i, provider, points, color)| {
+            let name = if provider.is_empty() { "unknown".to_string() } else { provider.to_string() };
+            Dataset::default()
+                .name(format!("{}: {}", i + 1, name))
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(*color))
+                .data(points)
+        })
+        .collect();
+
+    let first_day = today - chrono::Duration::days(app.usage_days as i64 - 1);
+    let chart = Chart::new(datasets)
+        .x_axis(Axis::default()
+            .bounds([0.0, (app.usage_days - 1) as f64])
+            .labels(vec![
+                Span::raw(first_day.format("%m-%d").to_string()),
+                Span::raw(today.format("%m-%d").to_string()),
+            ]))
+        .y_axis(Axis::default()
+            .bounds([0.0, max])
+            .labels(vec![Span::raw(metric.format(0.0)), Span::raw(metric.format(max))]));
+    f.render_widget(chart, chunks[0]);
+
+    let bars: Vec<Bar> = series.iter()
+        .map(|(_, provider, points, color)| {
+            let total: f64 = points.iter().map(|(_, y)| y).sum();
+            Bar::default()
+                .label(Line::from(provider.to_string()))
+                .value(total.round() as u64)
+                .text_value(metric.format(total))
+                .style(Style::default().fg(*color))
+        })
+        .collect();
+    let totals = BarChart::default()
+        .block(Block::default().title("Totals per provider").borders(Borders::ALL))
+        .direction(Direction::Horizontal)
+        .bar_width(1)
+        .bar_gap(0)
+        .data(BarGroup::default().bars(&bars));
+    f.render_widget(totals, chunks[1]);
+}
+
 fn draw_job_status(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let block = Block::default()
         .title("Job Status")
         .borders(Borders::ALL);
@@ -260,8 +1328,8 @@ fn draw_job_status(f: &mut Frame, app: &App, area: Rect) {
     // Job ID input
     let input_text = format!("Job ID: {}", app.job_id_input.value());
     let input = Paragraph::new(input_text)
-        .style(Style::default().fg(Color::White))
-        .block(Block::default().borders(Borders::ALL).title("Enter Job ID and press Enter"));
+        .style(Style::default().fg(theme.text))
+        .block(Block::default().borders(Borders::ALL).title("Enter Job ID and press Enter (Ctrl+D: download results, Ctrl+R: resume failed job, c: cancel job)"));
     f.render_widget(input, chunks[0]);
 
     // Job status display
@@ -272,11 +1340,12 @@ fn draw_job_status(f: &mut Frame, app: &App, area: Rect) {
         let status_text = Text::from(vec![
             Line::from(format!("Job ID: {}", job_id)),
             Line::from(format!("Status: {}", status)),
+            Line::from(format!("Owner: {}", app.job_owner.as_deref().unwrap_or("-"))),
             Line::from(format!("Progress: {}/{} ({:.1}%)", current, total, progress_percent)),
         ]);
         
         let status_display = Paragraph::new(status_text)
-            .style(Style::default().fg(Color::White))
+            .style(Style::default().fg(theme.text))
             .block(Block::default().borders(Borders::ALL).title("Job Status"));
         f.render_widget(status_display, chunks[1]);
         
@@ -284,21 +1353,536 @@ fn draw_job_status(f: &mut Frame, app: &App, area: Rect) {
         let gauge_area = centered_rect(60, 3, chunks[1]);
         let gauge = Gauge::default()
             .block(Block::default().borders(Borders::ALL))
-            .gauge_style(Style::default().fg(Color::Cyan))
+            .gauge_style(Style::default().fg(theme.accent))
             .percent(progress_percent as u16);
         f.render_widget(gauge, gauge_area);
     } else {
         let instructions = Paragraph::new("Enter a job ID to check status")
-            .style(Style::default().fg(Color::Gray))
+            .style(Style::default().fg(theme.muted))
             .block(Block::default().borders(Borders::ALL));
         f.render_widget(instructions, chunks[1]);
     }
 }
 
+fn draw_review(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let block = Block::default()
+        .title("Review")
+        .borders(Borders::ALL);
+    f.render_widget(block, area);
+
+    let inner_area = inner_area(area);
+    let Some(review) = &app.review else {
+        let input = Paragraph::new(format!("Dataset: {}", app.review_input.value()))
+            .style(Style::default().fg(theme.text))
+            .block(Block::default().borders(Borders::ALL).title("Dataset with candidate completions"));
+        f.render_widget(input, Rect { height: 3.min(inner_area.height), ..inner_area });
+        return;
+    };
+    let Some((record, candidate, choice)) = review.current() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(30),  // Prompt
+            Constraint::Min(0),          // Candidates
+        ])
+        .split(inner_area);
+    let prompt = Paragraph::new(preference::full_prompt(record))
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "Prompt {}/{} ({} judged) - {}",
+            review.current + 1, review.items.len(), review.judged(), review.dataset.display()
+        )));
+    f.render_widget(prompt, chunks[0]);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+    for (i, (text, side)) in [(record.completion.as_str(), Choice::A), (candidate.as_str(), Choice::B)].into_iter().enumerate() {
+        let (title, style) = match choice {
+            Some(chosen) if chosen == side => (format!("{}: chosen", i + 1), Style::default().fg(theme.success)),
+            Some(_) => (format!("{}: rejected", i + 1), Style::default().fg(theme.error)),
+            None => (format!("{}", i + 1), Style::default().fg(theme.text)),
+        };
+        let paragraph = Paragraph::new(text.to_string())
+            .wrap(Wrap { trim: false })
+            .block(Block::default().borders(Borders::ALL).title(title).border_style(style));
+        f.render_widget(paragraph, columns[i]);
+    }
+}
+
+fn draw_results(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let Some(viewer) = &app.results_viewer else {
+        let block = Block::default()
+            .title("Results")
+            .borders(Borders::ALL);
+        f.render_widget(block, area);
+        let inner_area = inner_area(area);
+        let input = Paragraph::new(format!("File: {}", app.results_input.value()))
+            .style(Style::default().fg(theme.text))
+            .block(Block::default().borders(Borders::ALL).title("Downloaded results (JSON or JSONL)"));
+        f.render_widget(input, Rect { height: 3.min(inner_area.height), ..inner_area });
+        return;
+    };
+
+    if let Some(lineage) = &viewer.lineage {
+        let title = format!("Results - lineage of record {}/{} - {}", viewer.current + 1, viewer.records.len(), viewer.path.display());
+        let paragraph = Paragraph::new(Text::from(lineage.iter().map(|line| Line::from(line.as_str())).collect::<Vec<_>>()))
+            .style(Style::default().fg(theme.text))
+            .scroll((viewer.scroll, 0))
+            .block(Block::default().title(title).borders(Borders::ALL));
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let title = if viewer.records.is_empty() {
+        format!("Results - no records in {}", viewer.path.display())
+    } else {
+        format!("Results - record {}/{} - {}", viewer.current + 1, viewer.records.len(), viewer.path.display())
+    };
+    let mut lines = Vec::new();
+    for (i, section) in viewer.sections().into_iter().enumerate() {
+        let marker = if section.folded { "▸" } else { "▾" };
+        let mut header = format!("{} {}", marker, section.name);
+        if section.folded {
+            header.push_str(&format!(" ({} line(s))", section.lines.len()));
+        }
+        let style = if i == viewer.selected {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.accent).add_modifier(Modifier::BOLD)
+        };
+        lines.push(Line::from(Span::styled(header, style)));
+        if !section.folded {
+            lines.extend(section.lines.into_iter().map(|line| Line::from(format!("  {}", line))));
+        }
+    }
+    let paragraph = Paragraph::new(Text::from(lines))
+        .wrap(Wrap { trim: false })
+        .scroll((viewer.scroll, 0))
+        .block(Block::default().title(title).borders(Borders::ALL));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_network(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let requests = app.network.snapshot();
+    let in_flight = requests.iter().filter(|r| r.in_flight()).count();
+    let mut title = format!(
+        "Network - {} in flight, {} queued (max {} at once)",
+        in_flight, requests.len() - in_flight, network::MAX_CONCURRENT_REQUESTS
+    );
+    if let Some(stats) = app.network.chaos_stats() {
+        title.push_str(&format!(
+            " - CHAOS: {} delayed, {} errors, {} dropped",
+            stats.delayed, stats.errors, stats.dropped
+        ));
+    }
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL);
+    f.render_widget(block, area);
+
+    let inner_area = inner_area(area);
+    if requests.is_empty() {
+        let idle = Paragraph::new("No requests to the backend right now")
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(idle, inner_area);
+        return;
+    }
+
+    let now = std::time::Instant::now();
+    let selected = app.network_selected.min(requests.len() - 1);
+    let rows: Vec<Row> = requests.iter().enumerate().map(|(i, request)| {
+        let style = if i == selected {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        let (state, color) = if request.in_flight() {
+            ("in flight", theme.success)
+        } else {
+            ("queued", theme.muted)
+        };
+        let elapsed = request.elapsed(now);
+        let elapsed_style = if elapsed.as_secs() >= 30 {
+            Style::default().fg(theme.error)
+        } else {
+            Style::default()
+        };
+        Row::new(vec![
+            Cell::from(request.method.clone()),
+            Cell::from(request.endpoint.clone()),
+            Cell::from(Span::styled(state, Style::default().fg(color))),
+            Cell::from(Span::styled(format_elapsed(elapsed, app.remote_shell), elapsed_style)),
+            Cell::from(request.retries.to_string()),
+        ]).style(style)
+    }).collect();
+    let header = Row::new(vec!["Method", "Endpoint", "State", "Elapsed", "Retries"])
+        .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Length(7),
+        Constraint::Min(20),
+        Constraint::Length(10),
+        Constraint::Length(9),
+        Constraint::Length(7),
+    ];
+    f.render_widget(Table::new(rows, widths).header(header), inner_area);
+}
+
+fn draw_debug(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let entries = app.transcripts.entries();
+    let capture = if app.transcripts.enabled { "capturing" } else { "capture off - 't' to start" };
+    let block = Block::default()
+        .title(format!("Debug - {} provider exchange(s), {}", entries.len(), capture))
+        .borders(Borders::ALL);
+    f.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(8)])
+        .split(inner_area(area));
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(rows[0]);
+
+    let selected = app.debug_selected.min(entries.len().saturating_sub(1));
+    let items: Vec<ListItem> = entries.iter().enumerate().map(|(i, entry)| {
+        let style = if i == selected {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        ListItem::new(format!(
+            "{} {} {}/{}",
+            entry.at.with_timezone(&chrono::Local).format("%H:%M:%S"), entry.job_id, entry.provider, entry.model
+        )).style(style)
+    }).collect();
+    let list = List::new(items).block(Block::default().title("Exchanges").borders(Borders::ALL));
+    f.render_widget(list, columns[0]);
+
+    let detail = match entries.get(selected) {
+        Some(entry) => Paragraph::new(entry.render()).style(Style::default().fg(theme.text)),
+        None => Paragraph::new("Exchanges of local runs show up here while capture is on")
+            .style(Style::default().fg(theme.muted)),
+    };
+    let detail = detail
+        .wrap(Wrap { trim: false })
+        .scroll((app.debug_scroll, 0))
+        .block(Block::default().title("Request / response (redacted)").borders(Borders::ALL));
+    f.render_widget(detail, columns[1]);
+
+    let log: Vec<Line> = app.debug_info[app.debug_info.len().saturating_sub(6)..].iter()
+        .map(|line| Line::from(line.clone()))
+        .collect();
+    let log = Paragraph::new(Text::from(log))
+        .style(Style::default().fg(theme.muted))
+        .block(Block::default().title("Log").borders(Borders::ALL));
+    f.render_widget(log, rows[1]);
+}
+
+fn draw_logs(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let view = &app.log_view;
+    let height = inner_area(area).height as usize;
+    let lines: Vec<Line> = view.window(height).into_iter()
+        .map(|entry| {
+            let color = match entry.level {
+                LogLevel::DEBUG => theme.muted,
+                LogLevel::INFO => theme.text,
+                LogLevel::WARN => theme.warning,
+                LogLevel::ERROR | LogLevel::FATAL => theme.error,
+            };
+            Line::from(vec![
+                Span::styled(entry.at.format("%H:%M:%S%.3f ").to_string(), Style::default().fg(theme.muted)),
+                Span::styled(format!("{:<5} ", entry.level.as_str()), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+                Span::styled(format!("{}: ", entry.module), Style::default().fg(theme.accent)),
+                Span::styled(entry.message.clone(), Style::default().fg(color)),
+            ])
+        })
+        .collect();
+    let paragraph = if lines.is_empty() {
+        Paragraph::new("Nothing logged at this level yet").style(Style::default().fg(theme.muted))
+    } else {
+        Paragraph::new(Text::from(lines))
+    };
+    let title_style = if view.paused { Style::default().fg(theme.warning) } else { Style::default().fg(theme.text) };
+    let paragraph = paragraph.block(Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled(format!("Logs - {}", view.status()), title_style)));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_stats(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let Some(view) = &app.stats_view else {
+        let paragraph = Paragraph::new("No dataset; 'S' on the Main screen shows the stats of the last download")
+            .style(Style::default().fg(theme.muted))
+            .block(Block::default().title("Stats").borders(Borders::ALL));
+        f.render_widget(paragraph, area);
+        return;
+    };
+    let title = format!(
+        "Stats - {} ({}/{})",
+        view.path().map(|path| path.display().to_string()).unwrap_or_default(),
+        view.selected + 1,
+        view.datasets.len(),
+    );
+    let paragraph = match &view.stats {
+        Ok(stats) => Paragraph::new(Text::from(stats.lines().into_iter().map(Line::from).collect::<Vec<_>>()))
+            .style(Style::default().fg(theme.text)),
+        Err(err) => Paragraph::new(err.as_str()).style(Style::default().fg(theme.error)),
+    };
+    let paragraph = paragraph
+        .scroll((view.scroll, 0))
+        .block(Block::default().title(title).borders(Borders::ALL));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_storage(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let storage = &app.storage;
+    let retention = &app.config.retention;
+    let mut title = format!(
+        "Storage - {} in {}",
+        diskspace::format_bytes(storage.total_bytes()), app.config.downloads_dir().display()
+    );
+    if retention.keep_days > 0 {
+        title.push_str(&format!(", keep {} days", retention.keep_days));
+    }
+    if retention.max_size_gb > 0.0 {
+        title.push_str(&format!(", max {} GB", retention.max_size_gb));
+    }
+    if storage.loading || storage.cleaning {
+        title.push_str(if storage.cleaning { " (cleaning up...)" } else { " (scanning...)" });
+    }
+    let block = Block::default().title(title).borders(Borders::ALL);
+    f.render_widget(block, area);
+
+    let inner_area = inner_area(area);
+    if let Some(err) = &storage.error {
+        let error = Paragraph::new(format!("Cannot measure disk usage: {}", err))
+            .style(Style::default().fg(theme.error))
+            .wrap(Wrap { trim: true });
+        f.render_widget(error, inner_area);
+        return;
+    }
+    if storage.groups.is_empty() {
+        let text = if storage.loaded { "No downloaded results" } else { "Scanning..." };
+        f.render_widget(Paragraph::new(text).style(Style::default().fg(theme.muted)), inner_area);
+        return;
+    }
+
+    let rows: Vec<Row> = storage.groups.iter().enumerate().map(|(i, group)| {
+        let style = if i == storage.selected {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else if group.job_id.is_none() {
+            Style::default().fg(theme.muted)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        let modified = chrono::DateTime::<chrono::Local>::from(group.modified).format("%Y-%m-%d %H:%M").to_string();
+        Row::new(vec![
+            Cell::from(group.label().to_string()),
+            Cell::from(group.source.clone()),
+            Cell::from(group.files.len().to_string()),
+            Cell::from(diskspace::format_bytes(group.bytes)),
+            Cell::from(modified),
+        ]).style(style)
+    }).collect();
+    let header = Row::new(vec!["Job", "Source", "Files", "Size", "Modified"])
+        .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Length(24),
+        Constraint::Min(20),
+        Constraint::Length(6),
+        Constraint::Length(10),
+        Constraint::Length(17),
+    ];
+    f.render_widget(Table::new(rows, widths).header(header), inner_area);
+}
+
+fn draw_jobs(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let mut title = format!("Jobs - page {}/{}", app.jobs_page, app.jobs_total_pages);
+    if let Some(total) = app.jobs_total {
+        title.push_str(&format!(", {} total", total));
+    }
+    if let Some(status) = &app.jobs_status_filter {
+        title.push_str(&format!(", {} only", status));
+    }
+    if app.jobs_mine_only {
+        title.push_str(", mine only");
+    }
+    title.push_str(&format!(" (page sorted by {})", app.jobs_sort));
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL);
+    f.render_widget(block, area);
+
+    let inner_area = inner_area(area);
+    let jobs = app.visible_jobs();
+    let placeholder = if let Some(err) = &app.jobs_error {
+        Some((err.clone(), theme.error))
+    } else if app.jobs_loading && jobs.is_empty() {
+        Some(("Loading…".to_string(), theme.warning))
+    } else if jobs.is_empty() {
+        Some(("No jobs on the backend".to_string(), theme.muted))
+    } else {
+        None
+    };
+    if let Some((text, color)) = placeholder {
+        f.render_widget(Paragraph::new(text).style(Style::default().fg(color)), inner_area);
+        return;
+    }
+
+    let rows: Vec<Row> = jobs.iter().enumerate().map(|(i, job)| {
+        let style = if i == app.jobs_selected {
+            Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        let progress = match (job.current, job.total) {
+            (Some(current), Some(total)) => format!("{}/{} ({:.0}%)", current, total, jobs::progress(job) * 100.0),
+            _ => "-".to_string(),
+        };
+        let created = job.created_at
+            .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "-".to_string());
+        Row::new(vec![
+            Cell::from(job.job_id.clone()),
+            Cell::from(Span::styled(job.status.clone(), Style::default().fg(job_status_color(theme, &job.status)))),
+            Cell::from(progress),
+            Cell::from(created),
+            Cell::from(job.owner.clone().unwrap_or_else(|| "-".to_string())),
+        ]).style(style)
+    }).collect();
+    let header = Row::new(vec!["ID", "Status", "Progress", "Created", "Owner"])
+        .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Percentage(34),
+        Constraint::Length(11),
+        Constraint::Length(16),
+        Constraint::Length(17),
+        Constraint::Min(8),
+    ];
+    f.render_widget(Table::new(rows, widths).header(header), inner_area);
+}
+
+fn draw_datasets(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let block = Block::default()
+        .title("Datasets")
+        .borders(Borders::ALL);
+    f.render_widget(block, area);
+
+    let inner_area = inner_area(area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(inner_area);
+
+    let browser = &app.datasets;
+    let items: Vec<ListItem> = if let Some(err) = &browser.error {
+        vec![ListItem::new(err.clone()).style(Style::default().fg(theme.error))]
+    } else if browser.loading && browser.items.is_empty() {
+        vec![ListItem::new("Loading…").style(Style::default().fg(theme.warning))]
+    } else if browser.items.is_empty() {
+        vec![ListItem::new("No datasets stored on the backend").style(Style::default().fg(theme.muted))]
+    } else {
+        browser.items.iter().enumerate().map(|(i, dataset)| {
+            let style = if i == browser.selected {
+                Style::default().fg(theme.highlight).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.text)
+            };
+            let created = dataset.created_at
+                .map(|t| t.with_timezone(&chrono::Local).format("%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            ListItem::new(format!(
+                "{:<11} {:<12} {}",
+                created,
+                dataset.owner.as_deref().unwrap_or("-"),
+                dataset.label(),
+            )).style(style)
+        }).collect()
+    };
+    let list = List::new(items)
+        .block(Block::default().title(format!("On {}", app.backend_url)).borders(Borders::ALL));
+    f.render_widget(list, columns[0]);
+
+    let Some(current) = browser.current() else {
+        return;
+    };
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    let mut lines = vec![
+        Line::from(format!("ID: {}", current.id)),
+        Line::from(format!("Owner: {}", optional(current.owner.clone()))),
+        Line::from(format!("Processing type: {}", optional(current.processing_type.clone()))),
+        Line::from(format!("Records: {}", optional(current.records.map(|r| r.to_string())))),
+        Line::from(format!("Size: {}", optional(current.size.map(diskspace::format_bytes)))),
+        Line::from(""),
+    ];
+    match &browser.preview {
+        Some(dataset) if dataset.preview.is_empty() => lines.push(Line::from(Span::styled(
+            "The backend sent no preview records",
+            Style::default().fg(theme.muted),
+        ))),
+        Some(dataset) => {
+            for record in &dataset.preview {
+                lines.push(Line::from(record.to_string()));
+                lines.push(Line::from(""));
+            }
+        },
+        None => lines.push(Line::from(Span::styled(
+            "Enter: preview records",
+            Style::default().fg(theme.muted),
+        ))),
+    }
+    let details = Paragraph::new(Text::from(lines))
+        .wrap(Wrap { trim: false })
+        .block(Block::default().title(current.label().to_string()).borders(Borders::ALL));
+    f.render_widget(details, columns[1]);
+}
+
+/// Tenths of a second change on nearly every frame; the remote-shell mode shows whole seconds
+fn format_elapsed(elapsed: std::time::Duration, remote_shell: bool) -> String {
+    if remote_shell {
+        format!("{}s", elapsed.as_secs())
+    } else {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    }
+}
+
 fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let block = Block::default().borders(Borders::ALL);
     f.render_widget(block, area);
 
+    // Downloads in progress share one gauge on the right
+    let area = match app.network.transfer_progress() {
+        Some((received, total)) => {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(0), Constraint::Length(36)])
+                .split(area);
+            let ratio = if total > 0 { (received as f64 / total as f64).min(1.0) } else { 0.0 };
+            let gauge = Gauge::default()
+                .gauge_style(Style::default().fg(theme.accent))
+                .ratio(ratio)
+                .label(format!("↓ {} / {}", diskspace::format_bytes(received), diskspace::format_bytes(total)));
+            f.render_widget(gauge, Block::default().borders(Borders::ALL).inner(chunks[1]));
+            chunks[0]
+        },
+        None => area,
+    };
     let inner_area = inner_area(area);
     let message = match &app.message {
         Some(msg) => msg.clone(),
@@ -308,18 +1892,38 @@ fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
             AppState::Process => "Process screen - Esc to return".to_string(),
             AppState::Settings => "Settings screen - Esc to return".to_string(),
             AppState::JobStatus => "Job Status screen - Esc to return".to_string(),
+            AppState::Usage => "Usage screen - m: metric, d: 30/90 days, 1-9: toggle provider, Esc to return".to_string(),
+            AppState::Review if app.review.is_some() => "Review screen - 1/2: choose, ←/→: move, e: export pairs, o: open another, Esc to return".to_string(),
+            AppState::Review => "Review screen - enter a dataset path and press Enter, Esc to return".to_string(),
+            AppState::Jobs => "Jobs screen - ↑/↓: select, Enter: details, PgUp/PgDn: page, f: status filter, s: sort, m: mine only, r: refresh, Esc to return".to_string(),
+            AppState::Datasets => "Datasets screen - ↑/↓: select, Enter: preview, d: download, r: refresh, Esc to return".to_string(),
+            AppState::Results if app.results_viewer.is_some() => "Results screen - ←/→: record, ↑/↓/PgUp/PgDn: scroll, Tab: field, Space: fold, a: fold all, g: lineage, o: open another, Esc to return".to_string(),
+            AppState::Results => "Results screen - enter a results file path and press Enter, Esc to return".to_string(),
+            AppState::Network => "Network screen - ↑/↓: select, c: cancel request, Esc to return".to_string(),
+            AppState::Storage => "Storage screen - ↑/↓: select, p: purge job files, c: clean up now, r: refresh, Esc to return".to_string(),
+            AppState::Debug => "Debug screen - ↑/↓: select, PgUp/PgDn: scroll, t: capture on/off, w: write job transcript, c: clear, r: record session, Esc to return".to_string(),
+            AppState::Stats => "Stats screen - ←/→: dataset, ↑/↓/PgUp/PgDn: scroll, v: view records, Esc to return".to_string(),
+            AppState::Logs => "Logs screen - ↑/↓/PgUp/PgDn: scroll, Home: oldest, End/f: follow, Space: pause, l: minimum level, c: clear, Esc to return".to_string(),
         },
     };
+    let message = match &app.update_available {
+        Some(version) => format!("{} | New version {} available - run `anydataset-tui self-update`", message, version),
+        None => message,
+    };
+    let message = match app.model_warnings().first() {
+        Some(warning) => format!("{} | {} ('u' in Settings)", message, warning),
+        None => message,
+    };
 
     let paragraph = Paragraph::new(message)
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(theme.text))
         .alignment(ratatui::layout::Alignment::Center);
     f.render_widget(paragraph, inner_area);
 }
 
 // Helper function to get a smaller area within a block
 fn inner_area(area: Rect) -> Rect {
-    let inner = area.inner(&Constraint::new(1, 1, 1, 1));
+    let inner = area.inner(&ratatui::layout::Margin::new(1, 1));
     Rect {
         x: inner.x,
         y: inner.y,
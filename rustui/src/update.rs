@@ -0,0 +1,161 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+use crate::atomic;
+use crate::network;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/Szowesgad/AnyDataNext/releases/latest";
+
+/// Minisign public key used to verify release binaries. Injected by the release
+/// pipeline; builds without it can check for updates but refuse to self-update.
+const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("ANYDATASET_UPDATE_PUBKEY");
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    /// Release version with any tag prefix (`v`, `rustui-v`) stripped
+    pub fn version(&self) -> Option<semver::Version> {
+        parse_version(&self.tag_name)
+    }
+
+    fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|a| a.name == name)
+    }
+}
+
+pub fn parse_version(tag: &str) -> Option<semver::Version> {
+    let start = tag.find(|c: char| c.is_ascii_digit())?;
+    semver::Version::parse(&tag[start..]).ok()
+}
+
+/// Name of the release asset built for the current platform
+pub fn platform_asset_name() -> String {
+    let suffix = if cfg!(windows) { ".exe" } else { "" };
+    format!("anydataset-tui-{}-{}{}", std::env::consts::OS, std::env::consts::ARCH, suffix)
+}
+
+/// GitHub rejects API requests without a User-Agent
+const USER_AGENT: &str = concat!("anydataset-tui/", env!("CARGO_PKG_VERSION"));
+
+// The shared client, so the proxy and timeouts of `[http]` apply to updates as well
+fn get(url: &str) -> reqwest::RequestBuilder {
+    network::client().get(url).header(reqwest::header::USER_AGENT, USER_AGENT)
+}
+
+pub async fn fetch_latest_release() -> Result<Release> {
+    let response = get(RELEASES_URL)
+        .send()
+        .await
+        .context("Failed to query GitHub releases")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("GitHub API error ({})", status);
+    }
+
+    response.json().await.context("Failed to parse release info")
+}
+
+/// Returns the latest release if it is newer than `current_version`
+pub async fn check_for_update(current_version: &str) -> Result<Option<Release>> {
+    let current = semver::Version::parse(current_version)
+        .context("Invalid current version")?;
+    let release = fetch_latest_release().await?;
+
+    match release.version() {
+        Some(latest) if latest > current => Ok(Some(release)),
+        _ => Ok(None),
+    }
+}
+
+/// Downloads the latest release for this platform, verifies its minisign
+/// signature and swaps it in place of the running executable.
+pub async fn self_update(current_version: &str) -> Result<Option<semver::Version>> {
+    let release = match check_for_update(current_version).await? {
+        Some(release) => release,
+        None => return Ok(None),
+    };
+    let public_key = UPDATE_PUBLIC_KEY
+        .context("This build has no update signing key; please download the release manually")?;
+
+    let asset_name = platform_asset_name();
+    let binary_asset = release.asset(&asset_name)
+        .with_context(|| format!("Release {} has no binary for this platform ({})", release.tag_name, asset_name))?;
+    let signature_asset = release.asset(&format!("{}.minisig", asset_name))
+        .with_context(|| format!("Release {} has no signature for {}", release.tag_name, asset_name))?;
+
+    let binary = download(&binary_asset.browser_download_url).await?;
+    let signature = download(&signature_asset.browser_download_url).await?;
+
+    verify_signature(public_key, &binary, &signature)?;
+
+    let current_exe = std::env::current_exe().context("Cannot locate current executable")?;
+    replace_executable(&current_exe, &binary)?;
+
+    Ok(release.version())
+}
+
+async fn download(url: &str) -> Result<Vec<u8>> {
+    let response = get(url)
+        .timeout(network::transfer_timeout())
+        .send()
+        .await
+        .with_context(|| format!("Failed to download {}", url))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("Download error ({}): {}", status, url);
+    }
+
+    let bytes = response.bytes().await
+        .context("Failed to read response body")?;
+    Ok(bytes.to_vec())
+}
+
+pub fn verify_signature(public_key: &str, data: &[u8], signature: &[u8]) -> Result<()> {
+    let public_key = minisign_verify::PublicKey::from_base64(public_key)
+        .map_err(|e| anyhow::anyhow!("Invalid update public key: {}", e))?;
+    let signature = std::str::from_utf8(signature)
+        .context("Signature file is not valid UTF-8")?;
+    let signature = minisign_verify::Signature::decode(signature)
+        .map_err(|e| anyhow::anyhow!("Invalid signature file: {}", e))?;
+
+    public_key.verify(data, &signature, false)
+        .map_err(|e| anyhow::anyhow!("Signature verification failed: {}", e))
+}
+
+pub fn replace_executable(current_exe: &Path, binary: &[u8]) -> Result<()> {
+    use std::io::Write;
+    // Staged under a unique name next to the binary; dropped, and so removed, on any error
+    let mut staged = atomic::temp_file(current_exe)?;
+    staged.write_all(binary).context("Failed to write new binary")?;
+
+    // Windows can't overwrite a running executable, but it can rename it
+    #[cfg(windows)]
+    let old = {
+        let old = current_exe.with_extension("old.exe");
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(current_exe, &old).context("Failed to move current binary aside")?;
+        old
+    };
+
+    // The new binary takes over the permissions of the current one, executable bit included
+    let replaced = atomic::persist(staged, current_exe).context("Failed to replace binary");
+    #[cfg(windows)]
+    if replaced.is_err() {
+        let _ = std::fs::rename(&old, current_exe);
+    }
+    replaced
+}
@@ -0,0 +1,71 @@
+use chrono::{Local, NaiveDate};
+
+use crate::history::HistoryEntry;
+use crate::report::{JobReportRow, UsageTotals};
+
+/// Value plotted on the Usage screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageMetric {
+    Records,
+    Tokens,
+    Spend,
+}
+
+impl UsageMetric {
+    pub fn label(&self) -> &'static str {
+        match self {
+            UsageMetric::Records => "Records",
+            UsageMetric::Tokens => "Tokens",
+            UsageMetric::Spend => "Spend (USD)",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            UsageMetric::Records => UsageMetric::Tokens,
+            UsageMetric::Tokens => UsageMetric::Spend,
+            UsageMetric::Spend => UsageMetric::Records,
+        }
+    }
+
+    pub fn value(&self, totals: &UsageTotals) -> f64 {
+        match self {
+            UsageMetric::Records => totals.records as f64,
+            UsageMetric::Tokens => totals.tokens as f64,
+            UsageMetric::Spend => totals.cost_usd,
+        }
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        match self {
+            UsageMetric::Spend => format!("${:.2}", value),
+            _ => format!("{:.0}", value),
+        }
+    }
+}
+
+/// Providers that appear in the history, sorted
+pub fn providers(entries: &[HistoryEntry]) -> Vec<String> {
+    let mut providers: Vec<String> = entries.iter().map(|e| e.provider.clone()).collect();
+    providers.sort();
+    providers.dedup();
+    providers
+}
+
+/// Totals per local calendar day for the last `days` days ending at `today`,
+/// oldest first. `provider` limits the series to one provider.
+pub fn daily_totals(entries: &[HistoryEntry], provider: Option<&str>, days: usize, today: NaiveDate) -> Vec<UsageTotals> {
+    let mut series = vec![UsageTotals::default(); days];
+    for entry in entries {
+        if provider.is_some_and(|p| p != entry.provider) {
+            continue;
+        }
+        let day = entry.submitted_at.with_timezone(&Local).date_naive();
+        let age = (today - day).num_days();
+        if age < 0 || age >= days as i64 {
+            continue;
+        }
+        series[days - 1 - age as usize].add(&JobReportRow::from(entry));
+    }
+    series
+}
@@ -0,0 +1,38 @@
+use semver::{Version, VersionReq};
+
+use crate::update::parse_version;
+
+/// Client version ranges mapped to the backend versions they were tested against
+const COMPATIBILITY_MATRIX: &[(&str, &str)] = &[
+    ("~0.1", ">=0.1.0, <0.3.0"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Pair is covered by the compatibility matrix
+    Tested,
+    /// Pair is outside the tested matrix and may misbehave
+    Untested,
+    /// Backend did not report a parseable version
+    Unknown,
+}
+
+pub fn check_compatibility(client_version: &str, backend_version: &str) -> Compatibility {
+    let (Some(client), Some(backend)) = (parse_version(client_version), parse_version(backend_version)) else {
+        return Compatibility::Unknown;
+    };
+
+    let tested = COMPATIBILITY_MATRIX.iter().any(|(client_req, backend_req)| {
+        matches(client_req, &client) && matches(backend_req, &backend)
+    });
+
+    if tested {
+        Compatibility::Tested
+    } else {
+        Compatibility::Untested
+    }
+}
+
+fn matches(req: &str, version: &Version) -> bool {
+    VersionReq::parse(req).map(|r| r.matches(version)).unwrap_or(false)
+}
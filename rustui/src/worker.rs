@@ -0,0 +1,168 @@
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+use crate::api::{ApiClient, ProcessingConfig};
+use crate::background::BackgroundEvent;
+use crate::diskspace;
+use crate::network::NetworkMonitor;
+use crate::ranged::DownloadSettings;
+use crate::vault::Vault;
+use crate::log_debug;
+
+/// Least time between two progress reports of one upload
+const UPLOAD_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Backend requests made on behalf of the UI
+#[derive(Debug, Clone)]
+pub enum WorkerCommand {
+    CheckVersion,
+    Upload {
+        file: String,
+    },
+    Submit {
+        file: String,
+        file_id: String,
+        config: Box<ProcessingConfig>,
+        /// Overrides the stored key of the provider
+        api_key: Option<String>,
+    },
+    PollStatus {
+        job_id: String,
+    },
+    CancelJob {
+        job_id: String,
+    },
+    ListJobs {
+        page: u32,
+        status: Option<String>,
+    },
+    ListDatasets,
+    GetDataset {
+        id: String,
+    },
+    DownloadDataset {
+        id: String,
+        path: PathBuf,
+        min_free_space_mb: u64,
+        /// Encrypts the file once it's complete
+        vault: Vault,
+    },
+}
+
+/// Handle to the thread that runs `ApiClient` calls for the event loop.
+/// Commands run concurrently; each answer arrives as a `BackgroundEvent` in `App::on_tick`.
+/// The thread exits when the handle is dropped.
+pub struct ApiWorker {
+    commands: UnboundedSender<WorkerCommand>,
+}
+
+impl ApiWorker {
+    pub fn spawn(
+        backend_url: &str,
+        events: Sender<BackgroundEvent>,
+        network: NetworkMonitor,
+        download: DownloadSettings,
+        headers: Vec<(String, String)>,
+    ) -> Self {
+        let (commands, mut rx) = mpsc::unbounded_channel();
+        let client = Arc::new(
+            ApiClient::new(backend_url).with_monitor(network).with_download_settings(download).with_headers(headers)
+        );
+        std::thread::spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(runtime) => runtime,
+                Err(err) => {
+                    log_debug!("API worker not started: {}", err);
+                    return;
+                },
+            };
+            runtime.block_on(async move {
+                while let Some(command) = rx.recv().await {
+                    tokio::spawn(run(client.clone(), command, events.clone()));
+                }
+            });
+        });
+        Self { commands }
+    }
+
+    /// Queues a command; false when the worker thread is gone
+    pub fn send(&self, command: WorkerCommand) -> bool {
+        self.commands.send(command).is_ok()
+    }
+}
+
+async fn run(client: Arc<ApiClient>, command: WorkerCommand, events: Sender<BackgroundEvent>) {
+    let event = match command {
+        WorkerCommand::CheckVersion => match client.get_version().await {
+            Ok(version) => BackgroundEvent::BackendVersion(version),
+            Err(err) => BackgroundEvent::BackendUnreachable(err.to_string()),
+        },
+        WorkerCommand::Upload { file } => {
+            let progress = upload_progress(file.clone(), events.clone());
+            match client.upload_file_with_progress(&PathBuf::from(&file), progress).await {
+                Ok(file_id) => BackgroundEvent::UploadFinished { file, file_id },
+                Err(err) => BackgroundEvent::UploadFailed { file, error: format!("{:#}", err) },
+            }
+        },
+        WorkerCommand::Submit { file, file_id, config, api_key } => match client.process_file_with_key(&file_id, *config, api_key).await {
+            Ok(job_id) => BackgroundEvent::JobSubmitted { file, job_id },
+            Err(err) => BackgroundEvent::SubmitFailed { file, error: format!("{:#}", err) },
+        },
+        WorkerCommand::PollStatus { job_id } => match client.get_job_status(&job_id).await {
+            Ok(status) => BackgroundEvent::JobStatusUpdated(status),
+            Err(err) => BackgroundEvent::StatusPollFailed { job_id, error: format!("{:#}", err) },
+        },
+        WorkerCommand::CancelJob { job_id } => match client.cancel_job(&job_id).await {
+            Ok(()) => BackgroundEvent::JobCancelled { job_id },
+            Err(err) => BackgroundEvent::CancelFailed { job_id, error: format!("{:#}", err) },
+        },
+        WorkerCommand::ListJobs { page, status } => match client.list_jobs(page, status.as_deref()).await {
+            Ok(jobs) => BackgroundEvent::JobsListed(jobs),
+            Err(err) => BackgroundEvent::JobsFailed(format!("{:#}", err)),
+        },
+        WorkerCommand::ListDatasets => match client.list_datasets().await {
+            Ok(datasets) => BackgroundEvent::DatasetsListed(datasets),
+            Err(err) => BackgroundEvent::DatasetsFailed(format!("{:#}", err)),
+        },
+        WorkerCommand::GetDataset { id } => match client.get_dataset(&id).await {
+            Ok(dataset) => BackgroundEvent::DatasetLoaded(dataset),
+            Err(err) => BackgroundEvent::DatasetFailed { id, error: format!("{:#}", err) },
+        },
+        WorkerCommand::DownloadDataset { id, path, min_free_space_mb, vault } => {
+            let result = async {
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                client.download_dataset_checked(&id, &path, |size| {
+                    diskspace::ensure_fits(&path, size, min_free_space_mb, false).map(|_| ())
+                }).await?;
+                let file = path.clone();
+                tokio::task::spawn_blocking(move || vault.encrypt_in_place(&file)).await??;
+                anyhow::Ok(())
+            };
+            match result.await {
+                Ok(()) => BackgroundEvent::DatasetDownloaded { id, path },
+                Err(err) => BackgroundEvent::DatasetFailed { id, error: format!("{:#}", err) },
+            }
+        },
+    };
+    let _ = events.send(event);
+}
+
+/// Reports the bytes sent of an upload at most every `UPLOAD_PROGRESS_INTERVAL`, and once done
+fn upload_progress(file: String, events: Sender<BackgroundEvent>) -> impl Fn(u64, u64) + Send + Sync + 'static {
+    let last = Mutex::new(None::<Instant>);
+    move |sent, total| {
+        let Ok(mut last) = last.lock() else {
+            return;
+        };
+        if sent < total && last.is_some_and(|at| at.elapsed() < UPLOAD_PROGRESS_INTERVAL) {
+            return;
+        }
+        *last = Some(Instant::now());
+        let _ = events.send(BackgroundEvent::UploadProgress { file: file.clone(), sent, total });
+    }
+}